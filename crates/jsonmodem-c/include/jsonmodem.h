@@ -0,0 +1,184 @@
+#ifndef JSONMODEM_H
+#define JSONMODEM_H
+
+/* This file is generated by cbindgen from crates/jsonmodem-c. Do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The kind of a [`JsonModemEvent`].
+ */
+typedef enum {
+  JSON_MODEM_EVENT_KIND_NULL,
+  JSON_MODEM_EVENT_KIND_BOOLEAN,
+  JSON_MODEM_EVENT_KIND_NUMBER,
+  JSON_MODEM_EVENT_KIND_STRING,
+  JSON_MODEM_EVENT_KIND_ARRAY_START,
+  JSON_MODEM_EVENT_KIND_ARRAY_END,
+  JSON_MODEM_EVENT_KIND_OBJECT_BEGIN,
+  JSON_MODEM_EVENT_KIND_OBJECT_END,
+  JSON_MODEM_EVENT_KIND_SCALAR_BEGIN,
+  JSON_MODEM_EVENT_KIND_SCALAR_END,
+  JSON_MODEM_EVENT_KIND_OBJECT_KEY,
+} JsonModemEventKind;
+
+/**
+ * The kind of a [`JsonModemPathComponent`].
+ */
+typedef enum {
+  JSON_MODEM_PATH_KIND_KEY,
+  JSON_MODEM_PATH_KIND_INDEX,
+} JsonModemPathKind;
+
+/**
+ * Heap storage backing the pointers of a [`JsonModemEvent`].
+ */
+typedef struct EventStorage EventStorage;
+
+/**
+ * An opaque streaming parser handle.
+ */
+typedef struct JsonModemParser JsonModemParser;
+
+/**
+ * One step in the path of an event.
+ *
+ * For `Key` components, `key` points at `key_len` bytes of UTF-8 that are not
+ * NUL-terminated. For `Index` components, `index` holds the array index and
+ * `key` is null.
+ */
+typedef struct {
+  JsonModemPathKind kind;
+  const char *key;
+  uintptr_t key_len;
+  uintptr_t index;
+} JsonModemPathComponent;
+
+/**
+ * An event produced by [`jsonmodem_next_event`].
+ *
+ * Only the fields relevant to `kind` are meaningful:
+ *
+ * - `boolean` for `Boolean` events,
+ * - `number` for `Number` events,
+ * - `string`, `string_len` and `is_final` for `String` events,
+ * - `string` and `string_len` for `ObjectKey` events, holding the key.
+ *
+ * `string` is a fragment of the string value, NUL-terminated for
+ * convenience; `string_len` excludes the terminator and may be smaller than
+ * `strlen(string)` if the fragment itself contains NUL characters.
+ *
+ * All pointers remain valid until the event is passed to
+ * [`jsonmodem_event_free`].
+ */
+typedef struct {
+  JsonModemEventKind kind;
+  const JsonModemPathComponent *path;
+  uintptr_t path_len;
+  bool boolean;
+  double number;
+  const char *string;
+  uintptr_t string_len;
+  bool is_final;
+  EventStorage *storage;
+} JsonModemEvent;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Creates a parser with default options.
+ *
+ * The returned handle must be released with [`jsonmodem_parser_free`].
+ */
+JsonModemParser *jsonmodem_parser_create(void);
+
+/**
+ * Releases a parser created by [`jsonmodem_parser_create`].
+ *
+ * Passing null is a no-op.
+ *
+ * # Safety
+ *
+ * `parser` must be null or a handle returned by [`jsonmodem_parser_create`]
+ * that has not already been freed.
+ */
+void jsonmodem_parser_free(JsonModemParser *parser);
+
+/**
+ * Appends `len` bytes of UTF-8 input to the parser.
+ *
+ * A multi-byte character may be split across calls. Returns 0 on success and
+ * -1 on error, in which case [`jsonmodem_last_error`] describes the failure.
+ *
+ * # Safety
+ *
+ * `parser` must be a live handle and `buf` must point to `len` readable bytes
+ * (it may be null when `len` is 0).
+ */
+int jsonmodem_feed(JsonModemParser *parser, const uint8_t *buf, uintptr_t len);
+
+/**
+ * Marks the end of input.
+ *
+ * Events for any values still open are queued for
+ * [`jsonmodem_next_event`]. Returns 0 on success and -1 on error.
+ *
+ * # Safety
+ *
+ * `parser` must be a live handle.
+ */
+int jsonmodem_finish(JsonModemParser *parser);
+
+/**
+ * Retrieves the next event.
+ *
+ * Returns 1 when an event was written to `out_event`, 0 when more input (or
+ * a call to [`jsonmodem_finish`]) is needed, and -1 on a syntax error. Once
+ * an error has been reported, every later call returns -1.
+ *
+ * # Safety
+ *
+ * `parser` must be a live handle and `out_event` must point to writable
+ * memory for one [`JsonModemEvent`]. An event written by this function must
+ * be released with [`jsonmodem_event_free`].
+ */
+int jsonmodem_next_event(JsonModemParser *parser, JsonModemEvent *out_event);
+
+/**
+ * Releases the storage owned by an event written by
+ * [`jsonmodem_next_event`].
+ *
+ * The event's pointers are reset, so freeing the same event twice is
+ * harmless. Passing null is a no-op.
+ *
+ * # Safety
+ *
+ * `event` must be null or point to an event written by
+ * [`jsonmodem_next_event`].
+ */
+void jsonmodem_event_free(JsonModemEvent *event);
+
+/**
+ * Returns the message of the last error raised on this thread, or null if
+ * there was none.
+ *
+ * The pointer is valid until the next failing call on the same thread or a
+ * call to [`jsonmodem_clear_error`].
+ */
+const char *jsonmodem_last_error(void);
+
+/**
+ * Clears the error returned by [`jsonmodem_last_error`].
+ */
+void jsonmodem_clear_error(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* JSONMODEM_H */
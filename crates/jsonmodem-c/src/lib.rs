@@ -0,0 +1,459 @@
+//! C bindings for the `jsonmodem` streaming parser.
+//!
+//! The parser is exposed as an opaque [`JsonModemParser`] handle. Input is
+//! appended with [`jsonmodem_feed`] and events are pulled one at a time with
+//! [`jsonmodem_next_event`], which fills in a caller-provided
+//! [`JsonModemEvent`]. Every event written this way owns heap storage for its
+//! path and string fragment and must be released with
+//! [`jsonmodem_event_free`].
+//!
+//! Functions that can fail record a message in a thread-local buffer, which
+//! can be read with [`jsonmodem_last_error`].
+//!
+//! The header, `include/jsonmodem.h`, is generated by cbindgen and checked in;
+//! run `make header` to regenerate it after changing these definitions.
+
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    ffi::{CString, c_char, c_int},
+    ptr, slice,
+};
+
+use jsonmodem::{ParseEvent, ParserOptions, PathComponent, StreamingParser};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(msg: impl Into<Vec<u8>>) {
+    let mut msg = msg.into();
+    msg.retain(|&b| b != 0);
+    let msg = CString::new(msg).expect("interior NUL bytes were removed");
+    LAST_ERROR.with(|slot| *slot.borrow_mut() = Some(msg));
+}
+
+fn clear_last_error() {
+    LAST_ERROR.with(|slot| *slot.borrow_mut() = None);
+}
+
+/// The kind of a [`JsonModemEvent`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonModemEventKind {
+    Null,
+    Boolean,
+    Number,
+    String,
+    ArrayStart,
+    ArrayEnd,
+    ObjectBegin,
+    ObjectEnd,
+    ScalarBegin,
+    ScalarEnd,
+    ObjectKey,
+}
+
+/// The kind of a [`JsonModemPathComponent`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonModemPathKind {
+    Key,
+    Index,
+}
+
+/// One step in the path of an event.
+///
+/// For `Key` components, `key` points at `key_len` bytes of UTF-8 that are not
+/// NUL-terminated. For `Index` components, `index` holds the array index and
+/// `key` is null.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct JsonModemPathComponent {
+    pub kind: JsonModemPathKind,
+    pub key: *const c_char,
+    pub key_len: usize,
+    pub index: usize,
+}
+
+/// An event produced by [`jsonmodem_next_event`].
+///
+/// Only the fields relevant to `kind` are meaningful:
+///
+/// - `boolean` for `Boolean` events,
+/// - `number` for `Number` events,
+/// - `string`, `string_len` and `is_final` for `String` events,
+/// - `string` and `string_len` for `ObjectKey` events, holding the key.
+///
+/// `string` is a fragment of the string value, NUL-terminated for
+/// convenience; `string_len` excludes the terminator and may be smaller than
+/// `strlen(string)` if the fragment itself contains NUL characters.
+///
+/// All pointers remain valid until the event is passed to
+/// [`jsonmodem_event_free`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct JsonModemEvent {
+    pub kind: JsonModemEventKind,
+    pub path: *const JsonModemPathComponent,
+    pub path_len: usize,
+    pub boolean: bool,
+    pub number: f64,
+    pub string: *const c_char,
+    pub string_len: usize,
+    pub is_final: bool,
+    storage: *mut EventStorage,
+}
+
+/// Heap storage backing the pointers of a [`JsonModemEvent`].
+struct EventStorage {
+    // Holds the `Arc<str>` keys that `components` points into.
+    _path: Vec<PathComponent>,
+    components: Vec<JsonModemPathComponent>,
+    string: Vec<u8>,
+}
+
+impl JsonModemEvent {
+    fn new(event: ParseEvent) -> Self {
+        let (kind, path, boolean, number, fragment, is_final) = match event {
+            ParseEvent::Null { path } => (JsonModemEventKind::Null, path, false, 0.0, None, false),
+            ParseEvent::Boolean { path, value } => {
+                (JsonModemEventKind::Boolean, path, value, 0.0, None, false)
+            }
+            ParseEvent::Number { path, value } => {
+                (JsonModemEventKind::Number, path, false, value, None, false)
+            }
+            ParseEvent::String {
+                path,
+                fragment,
+                is_final,
+                ..
+            } => (
+                JsonModemEventKind::String,
+                path,
+                false,
+                0.0,
+                Some(fragment),
+                is_final,
+            ),
+            // Not produced by the parser; reported as a single final fragment.
+            ParseEvent::StringComplete { path, value } => (
+                JsonModemEventKind::String,
+                path,
+                false,
+                0.0,
+                Some(value),
+                true,
+            ),
+            ParseEvent::ArrayStart { path, .. } => (
+                JsonModemEventKind::ArrayStart,
+                path,
+                false,
+                0.0,
+                None,
+                false,
+            ),
+            ParseEvent::ArrayEnd { path, .. } => {
+                (JsonModemEventKind::ArrayEnd, path, false, 0.0, None, false)
+            }
+            ParseEvent::ObjectBegin { path, .. } => (
+                JsonModemEventKind::ObjectBegin,
+                path,
+                false,
+                0.0,
+                None,
+                false,
+            ),
+            ParseEvent::ObjectEnd { path, .. } => {
+                (JsonModemEventKind::ObjectEnd, path, false, 0.0, None, false)
+            }
+            ParseEvent::ScalarBegin { path } => (
+                JsonModemEventKind::ScalarBegin,
+                path,
+                false,
+                0.0,
+                None,
+                false,
+            ),
+            ParseEvent::ScalarEnd { path } => {
+                (JsonModemEventKind::ScalarEnd, path, false, 0.0, None, false)
+            }
+            ParseEvent::ObjectKey { path, key } => (
+                JsonModemEventKind::ObjectKey,
+                path,
+                false,
+                0.0,
+                Some(key.to_string()),
+                false,
+            ),
+        };
+
+        let components = path
+            .iter()
+            .map(|component| match component {
+                PathComponent::Key(key) => JsonModemPathComponent {
+                    kind: JsonModemPathKind::Key,
+                    key: key.as_ptr().cast(),
+                    key_len: key.len(),
+                    index: 0,
+                },
+                PathComponent::Index(index) => JsonModemPathComponent {
+                    kind: JsonModemPathKind::Index,
+                    key: ptr::null(),
+                    key_len: 0,
+                    index: *index,
+                },
+            })
+            .collect();
+
+        let string_len = fragment.as_ref().map_or(0, String::len);
+        let mut string = fragment.map(String::into_bytes).unwrap_or_default();
+        string.push(0);
+
+        let storage = Box::new(EventStorage {
+            _path: path,
+            components,
+            string,
+        });
+
+        Self {
+            kind,
+            path: storage.components.as_ptr(),
+            path_len: storage.components.len(),
+            boolean,
+            number,
+            string: storage.string.as_ptr().cast(),
+            string_len,
+            is_final,
+            storage: Box::into_raw(storage),
+        }
+    }
+}
+
+/// An opaque streaming parser handle.
+pub struct JsonModemParser {
+    /// `None` once [`jsonmodem_finish`] has been called.
+    parser: Option<StreamingParser>,
+    /// Trailing bytes of an incomplete UTF-8 sequence from the last feed.
+    partial: Vec<u8>,
+    events: VecDeque<Result<ParseEvent, String>>,
+    failed: bool,
+}
+
+impl JsonModemParser {
+    fn feed(&mut self, bytes: &[u8]) -> Result<(), String> {
+        let Some(parser) = &mut self.parser else {
+            return Err("cannot feed a parser after jsonmodem_finish".into());
+        };
+
+        self.partial.extend_from_slice(bytes);
+        let valid = match std::str::from_utf8(&self.partial) {
+            Ok(text) => text.len(),
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            Err(err) => {
+                return Err(format!(
+                    "invalid UTF-8 at byte {} of the buffered input",
+                    err.valid_up_to()
+                ));
+            }
+        };
+        let text = std::str::from_utf8(&self.partial[..valid]).expect("validated above");
+        self.events.extend(
+            parser
+                .feed(text)
+                .map(|event| event.map_err(|err| err.to_string())),
+        );
+        self.partial.drain(..valid);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        let Some(parser) = self.parser.take() else {
+            return Err("jsonmodem_finish called twice".into());
+        };
+        if !self.partial.is_empty() {
+            return Err("input ends with an incomplete UTF-8 sequence".into());
+        }
+        self.events.extend(
+            parser
+                .finish()
+                .map(|event| event.map_err(|err| err.to_string())),
+        );
+        Ok(())
+    }
+}
+
+/// Creates a parser with default options.
+///
+/// The returned handle must be released with [`jsonmodem_parser_free`].
+#[unsafe(no_mangle)]
+pub extern "C" fn jsonmodem_parser_create() -> *mut JsonModemParser {
+    Box::into_raw(Box::new(JsonModemParser {
+        parser: Some(StreamingParser::new(ParserOptions::default())),
+        partial: Vec::new(),
+        events: VecDeque::new(),
+        failed: false,
+    }))
+}
+
+/// Releases a parser created by [`jsonmodem_parser_create`].
+///
+/// Passing null is a no-op.
+///
+/// # Safety
+///
+/// `parser` must be null or a handle returned by [`jsonmodem_parser_create`]
+/// that has not already been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jsonmodem_parser_free(parser: *mut JsonModemParser) {
+    if !parser.is_null() {
+        drop(unsafe { Box::from_raw(parser) });
+    }
+}
+
+/// Appends `len` bytes of UTF-8 input to the parser.
+///
+/// A multi-byte character may be split across calls. Returns 0 on success and
+/// -1 on error, in which case [`jsonmodem_last_error`] describes the failure.
+///
+/// # Safety
+///
+/// `parser` must be a live handle and `buf` must point to `len` readable bytes
+/// (it may be null when `len` is 0).
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jsonmodem_feed(
+    parser: *mut JsonModemParser,
+    buf: *const u8,
+    len: usize,
+) -> c_int {
+    let Some(parser) = (unsafe { parser.as_mut() }) else {
+        set_last_error("parser is null");
+        return -1;
+    };
+    let bytes = if len == 0 {
+        &[][..]
+    } else if buf.is_null() {
+        set_last_error("buffer is null");
+        return -1;
+    } else {
+        unsafe { slice::from_raw_parts(buf, len) }
+    };
+    match parser.feed(bytes) {
+        Ok(()) => 0,
+        Err(msg) => {
+            set_last_error(msg);
+            -1
+        }
+    }
+}
+
+/// Marks the end of input.
+///
+/// Events for any values still open are queued for
+/// [`jsonmodem_next_event`]. Returns 0 on success and -1 on error.
+///
+/// # Safety
+///
+/// `parser` must be a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jsonmodem_finish(parser: *mut JsonModemParser) -> c_int {
+    let Some(parser) = (unsafe { parser.as_mut() }) else {
+        set_last_error("parser is null");
+        return -1;
+    };
+    match parser.finish() {
+        Ok(()) => 0,
+        Err(msg) => {
+            set_last_error(msg);
+            -1
+        }
+    }
+}
+
+/// Retrieves the next event.
+///
+/// Returns 1 when an event was written to `out_event`, 0 when more input (or
+/// a call to [`jsonmodem_finish`]) is needed, and -1 on a syntax error. Once
+/// an error has been reported, every later call returns -1.
+///
+/// # Safety
+///
+/// `parser` must be a live handle and `out_event` must point to writable
+/// memory for one [`JsonModemEvent`]. An event written by this function must
+/// be released with [`jsonmodem_event_free`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jsonmodem_next_event(
+    parser: *mut JsonModemParser,
+    out_event: *mut JsonModemEvent,
+) -> c_int {
+    let Some(parser) = (unsafe { parser.as_mut() }) else {
+        set_last_error("parser is null");
+        return -1;
+    };
+    if out_event.is_null() {
+        set_last_error("out_event is null");
+        return -1;
+    }
+    if parser.failed {
+        set_last_error("parser has already failed");
+        return -1;
+    }
+    match parser.events.pop_front() {
+        None => 0,
+        Some(Ok(event)) => {
+            unsafe { out_event.write(JsonModemEvent::new(event)) };
+            1
+        }
+        Some(Err(msg)) => {
+            parser.failed = true;
+            parser.events.clear();
+            set_last_error(msg);
+            -1
+        }
+    }
+}
+
+/// Releases the storage owned by an event written by
+/// [`jsonmodem_next_event`].
+///
+/// The event's pointers are reset, so freeing the same event twice is
+/// harmless. Passing null is a no-op.
+///
+/// # Safety
+///
+/// `event` must be null or point to an event written by
+/// [`jsonmodem_next_event`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jsonmodem_event_free(event: *mut JsonModemEvent) {
+    let Some(event) = (unsafe { event.as_mut() }) else {
+        return;
+    };
+    if !event.storage.is_null() {
+        drop(unsafe { Box::from_raw(event.storage) });
+    }
+    event.storage = ptr::null_mut();
+    event.path = ptr::null();
+    event.path_len = 0;
+    event.string = ptr::null();
+    event.string_len = 0;
+}
+
+/// Returns the message of the last error raised on this thread, or null if
+/// there was none.
+///
+/// The pointer is valid until the next failing call on the same thread or a
+/// call to [`jsonmodem_clear_error`].
+#[unsafe(no_mangle)]
+pub extern "C" fn jsonmodem_last_error() -> *const c_char {
+    LAST_ERROR.with(|slot| {
+        slot.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |msg| msg.as_ptr())
+    })
+}
+
+/// Clears the error returned by [`jsonmodem_last_error`].
+#[unsafe(no_mangle)]
+pub extern "C" fn jsonmodem_clear_error() {
+    clear_last_error();
+}
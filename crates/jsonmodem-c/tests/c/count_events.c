@@ -0,0 +1,41 @@
+/* Reads JSON from stdin, feeds it to the parser in chunks and prints
+ * the number of events produced. */
+#include <stdio.h>
+#include <stdlib.h>
+
+#include "jsonmodem.h"
+
+static int drain(JsonModemParser *parser, size_t *count) {
+    JsonModemEvent event;
+    int rc;
+    while ((rc = jsonmodem_next_event(parser, &event)) == 1) {
+        (*count)++;
+        jsonmodem_event_free(&event);
+    }
+    return rc;
+}
+
+int main(void) {
+    JsonModemParser *parser = jsonmodem_parser_create();
+    unsigned char buf[4096];
+    size_t count = 0;
+    size_t n;
+
+    while ((n = fread(buf, 1, sizeof buf, stdin)) > 0) {
+        if (jsonmodem_feed(parser, buf, n) != 0 || drain(parser, &count) != 0) {
+            goto fail;
+        }
+    }
+    if (jsonmodem_finish(parser) != 0 || drain(parser, &count) != 0) {
+        goto fail;
+    }
+
+    printf("%zu\n", count);
+    jsonmodem_parser_free(parser);
+    return 0;
+
+fail:
+    fprintf(stderr, "error: %s\n", jsonmodem_last_error());
+    jsonmodem_parser_free(parser);
+    return 1;
+}
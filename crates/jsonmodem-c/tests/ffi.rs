@@ -0,0 +1,118 @@
+use std::{ffi::CStr, mem::MaybeUninit};
+
+use jsonmodem_c::{
+    JsonModemEvent, JsonModemEventKind, JsonModemParser, JsonModemPathKind, jsonmodem_event_free,
+    jsonmodem_feed, jsonmodem_finish, jsonmodem_last_error, jsonmodem_next_event,
+    jsonmodem_parser_create, jsonmodem_parser_free,
+};
+
+/// A simplified, owned view of an event for assertions.
+#[derive(Debug, PartialEq)]
+struct Seen {
+    kind: JsonModemEventKind,
+    path: String,
+    string: Option<(String, bool)>,
+}
+
+fn drain(parser: *mut JsonModemParser, seen: &mut Vec<Seen>) -> i32 {
+    loop {
+        let mut event = MaybeUninit::<JsonModemEvent>::uninit();
+        let rc = unsafe { jsonmodem_next_event(parser, event.as_mut_ptr()) };
+        if rc != 1 {
+            return rc;
+        }
+        let mut event = unsafe { event.assume_init() };
+        let path = unsafe { std::slice::from_raw_parts(event.path, event.path_len) }
+            .iter()
+            .map(|component| match component.kind {
+                JsonModemPathKind::Key => {
+                    let key = unsafe {
+                        std::slice::from_raw_parts(component.key.cast::<u8>(), component.key_len)
+                    };
+                    format!(".{}", std::str::from_utf8(key).unwrap())
+                }
+                JsonModemPathKind::Index => format!("[{}]", component.index),
+            })
+            .collect();
+        let string = (event.kind == JsonModemEventKind::String).then(|| {
+            let bytes =
+                unsafe { std::slice::from_raw_parts(event.string.cast::<u8>(), event.string_len) };
+            (String::from_utf8(bytes.to_vec()).unwrap(), event.is_final)
+        });
+        seen.push(Seen {
+            kind: event.kind,
+            path,
+            string,
+        });
+        unsafe { jsonmodem_event_free(&raw mut event) };
+    }
+}
+
+fn run(chunks: &[&[u8]]) -> Result<Vec<Seen>, String> {
+    let parser = jsonmodem_parser_create();
+    let mut seen = Vec::new();
+    let mut result = Ok(());
+    for chunk in chunks {
+        if unsafe { jsonmodem_feed(parser, chunk.as_ptr(), chunk.len()) } != 0
+            || drain(parser, &mut seen) != 0
+        {
+            result = Err(());
+            break;
+        }
+    }
+    if result.is_ok() && (unsafe { jsonmodem_finish(parser) } != 0 || drain(parser, &mut seen) != 0)
+    {
+        result = Err(());
+    }
+    unsafe { jsonmodem_parser_free(parser) };
+    result.map(|()| seen).map_err(|()| {
+        unsafe { CStr::from_ptr(jsonmodem_last_error()) }
+            .to_string_lossy()
+            .into_owned()
+    })
+}
+
+#[test]
+fn events_carry_paths_and_fragments() {
+    let seen = run(&[br#"{"a": [1, "x"#, br#"y"]}"#]).unwrap();
+    let kinds: Vec<_> = seen.iter().map(|event| event.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            JsonModemEventKind::ObjectBegin,
+            JsonModemEventKind::ArrayStart,
+            JsonModemEventKind::Number,
+            JsonModemEventKind::String,
+            JsonModemEventKind::String,
+            JsonModemEventKind::ArrayEnd,
+            JsonModemEventKind::ObjectEnd,
+        ]
+    );
+    assert_eq!(seen[3].path, ".a[1]");
+    assert_eq!(seen[3].string, Some(("x".into(), false)));
+    assert_eq!(seen[4].string, Some(("y".into(), true)));
+}
+
+#[test]
+fn multibyte_characters_may_be_split() {
+    let json = r#"["été"]"#.as_bytes();
+    let seen = run(&[&json[..3], &json[3..]]).unwrap();
+    let text: String = seen
+        .iter()
+        .filter_map(|event| event.string.as_ref())
+        .map(|(fragment, _)| fragment.as_str())
+        .collect();
+    assert_eq!(text, "été");
+}
+
+#[test]
+fn syntax_errors_are_reported() {
+    let err = run(&[b"[1, x]"]).unwrap_err();
+    assert!(!err.is_empty());
+}
+
+#[test]
+fn invalid_utf8_is_reported() {
+    let err = run(&[b"[\"\xff\"]"]).unwrap_err();
+    assert!(err.contains("invalid UTF-8"), "{err}");
+}
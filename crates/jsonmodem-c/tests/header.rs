@@ -0,0 +1,31 @@
+//! Checks that `include/jsonmodem.h` matches the FFI definitions in
+//! `src/lib.rs`.
+//!
+//! Run `make header` (or this test with `UPDATE_HEADER=1`) to regenerate the
+//! header after changing the FFI.
+use std::{env, fs, path::Path};
+
+#[test]
+fn header_is_up_to_date() {
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml"))
+        .expect("cbindgen.toml is valid");
+    let mut generated = Vec::new();
+    cbindgen::Builder::new()
+        .with_crate(crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("unable to generate C bindings")
+        .write(&mut generated);
+
+    let path = crate_dir.join("include").join("jsonmodem.h");
+    if env::var_os("UPDATE_HEADER").is_some() {
+        fs::write(&path, &generated).expect("header is writable");
+        return;
+    }
+    let checked_in = fs::read(&path).expect("header exists");
+    assert!(
+        checked_in == generated,
+        "include/jsonmodem.h is out of date; regenerate it with `make header`"
+    );
+}
@@ -0,0 +1,109 @@
+//! Parses JSON from standard input and prints counts of its events.
+//!
+//! Any number of whitespace-separated documents may be given, e.g. NDJSON.
+//! Input is read and fed to the parser in chunks, so it can be larger than
+//! memory. On a syntax error the error is printed to standard error, the
+//! counts up to that point are printed, and the exit status is 1.
+//!
+//! ```text
+//! $ echo '{"a": [1, "x"]}' | jsonmodem-cli
+//! objects: 1
+//! arrays: 1
+//! ...
+//! ```
+
+use std::{
+    io::{self, Read},
+    process::ExitCode,
+};
+
+use jsonmodem::{EventCounter, MetricsReport, ParserError, ParserOptions, StreamingParser};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+fn main() -> ExitCode {
+    let mut report = MetricsReport::default();
+    let result = count(io::stdin().lock(), &mut report);
+    print!("{report}");
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Error {
+    Io(io::Error),
+    Parser(ParserError),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(err) => err.fmt(f),
+            Error::Parser(err) => err.fmt(f),
+        }
+    }
+}
+
+/// Feeds all of `input` to a parser, adding the counts of its events to
+/// `report`, and stops at the first error.
+fn count(mut input: impl Read, report: &mut MetricsReport) -> Result<(), Error> {
+    let mut parser = StreamingParser::new(ParserOptions {
+        allow_multiple_json_values: true,
+        ..ParserOptions::default()
+    });
+    let mut buf = vec![0; CHUNK_SIZE];
+    loop {
+        let n = match input.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(Error::Io(err)),
+        };
+        let events = parser.feed_bytes(&buf[..n]).map_err(|err| {
+            report.n_errors += 1;
+            Error::Parser(err)
+        })?;
+        let mut events = EventCounter::new(events);
+        events.add_bytes_fed(n);
+        let error = events.by_ref().find_map(Result::err);
+        *report += events.report();
+        if let Some(err) = error {
+            return Err(Error::Parser(err));
+        }
+    }
+    let mut events = EventCounter::new(parser.finish());
+    let error = events.by_ref().find_map(Result::err);
+    *report += events.report();
+    error.map_or(Ok(()), |err| Err(Error::Parser(err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonmodem::MetricsReport;
+
+    use super::count;
+
+    #[test]
+    fn counts_documents_and_stops_at_errors() {
+        let mut report = MetricsReport::default();
+        count(&b"{\"a\": [1, \"x\"]}\n{\"b\": null}\n"[..], &mut report).unwrap();
+        assert_eq!(report.n_objects, 2);
+        assert_eq!(report.n_arrays, 1);
+        assert_eq!(report.n_bytes_fed, 28);
+
+        let mut report = MetricsReport::default();
+        let err = count(&b"[1, 2, x]"[..], &mut report).unwrap_err();
+        assert!(err.to_string().contains("invalid character"), "{err}");
+        assert_eq!(report.n_numbers, 2);
+        assert_eq!(report.n_errors, 1);
+
+        let mut report = MetricsReport::default();
+        assert!(count(&b"[1"[..], &mut report).is_err());
+        assert_eq!(report.n_errors, 1);
+    }
+}
@@ -1,7 +1,229 @@
-use pyo3::prelude::*;
+//! Python bindings for the `jsonmodem` streaming parser.
+//!
+//! The parser is exposed as the `jsonmodem.StreamingParser` class. The event
+//! iterators of the Rust parser borrow it, which Python objects cannot
+//! express, so `feed` and `finish` drive the iterator to completion while the
+//! GIL is held and return the events as a list. Each event is a `dict` with a
+//! `"type"` key naming the [`ParseEvent`] variant, a `"path"` key holding a
+//! list of keys and indices, and the fields of the variant:
+//!
+//! ```python
+//! import jsonmodem
+//!
+//! parser = jsonmodem.StreamingParser()
+//! events = parser.feed('{"a": [1, "x"')
+//! events += parser.feed(']}')
+//! events += parser.finish()
+//! assert events[2] == {"type": "Number", "path": ["a", 0], "value": 1.0}
+//! ```
+
+use ::jsonmodem::{
+    NonScalarValueMode, ParseEvent, ParserError, ParserOptions, PathComponent, StreamingParser,
+    StringValueMode, Value,
+};
+use pyo3::{
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+    types::{PyBool, PyDict, PyFloat, PyList, PyString},
+};
+
+/// A streaming JSON parser.
+///
+/// `feed` and `finish` return the events completed by the input so far as a
+/// list of dicts. A syntax error raises `ValueError`, after which the parser
+/// produces no more events.
+///
+/// The parser is not `Send`, so an instance can only be used from the thread
+/// that created it.
+#[pyclass(name = "StreamingParser", module = "jsonmodem", unsendable)]
+pub struct PyStreamingParser {
+    /// `None` once `finish` has been called.
+    parser: Option<StreamingParser>,
+}
+
+#[pymethods]
+impl PyStreamingParser {
+    #[new]
+    #[pyo3(signature = (
+        *,
+        allow_multiple_json_values = false,
+        string_value_mode = "none",
+        non_scalar_values = "none",
+        scalar_boundary_events = false,
+        emit_key_events = false,
+        lookahead_capacity_hints = false,
+    ))]
+    fn new(
+        allow_multiple_json_values: bool,
+        string_value_mode: &str,
+        non_scalar_values: &str,
+        scalar_boundary_events: bool,
+        emit_key_events: bool,
+        lookahead_capacity_hints: bool,
+    ) -> PyResult<Self> {
+        let string_value_mode = match string_value_mode {
+            "none" => StringValueMode::None,
+            "values" => StringValueMode::Values,
+            "prefixes" => StringValueMode::Prefixes,
+            other => {
+                return Err(PyValueError::new_err(format!(
+                    "string_value_mode must be 'none', 'values' or 'prefixes', not {other:?}"
+                )));
+            }
+        };
+        let non_scalar_values = match non_scalar_values {
+            "none" => NonScalarValueMode::None,
+            "all" => NonScalarValueMode::All,
+            "roots" => NonScalarValueMode::Roots,
+            other => {
+                return Err(PyValueError::new_err(format!(
+                    "non_scalar_values must be 'none', 'all' or 'roots', not {other:?}"
+                )));
+            }
+        };
+        let options = ParserOptions {
+            allow_multiple_json_values,
+            string_value_mode,
+            non_scalar_values,
+            scalar_boundary_events,
+            emit_key_events,
+            lookahead_capacity_hints,
+            ..ParserOptions::default()
+        };
+        Ok(Self {
+            parser: Some(StreamingParser::new(options)),
+        })
+    }
+
+    /// Feeds a chunk of JSON text and returns the events it completes.
+    fn feed<'py>(&mut self, py: Python<'py>, chunk: &str) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        let parser = self
+            .parser
+            .as_mut()
+            .ok_or_else(|| PyRuntimeError::new_err("the parser has been finished"))?;
+        collect_events(py, parser.feed(chunk))
+    }
+
+    /// Marks the end of input and returns the remaining events.
+    fn finish<'py>(&mut self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        let parser = self
+            .parser
+            .take()
+            .ok_or_else(|| PyRuntimeError::new_err("the parser has been finished"))?;
+        collect_events(py, parser.finish())
+    }
+}
+
+/// Drains `events`, converting each to a dict, and raises the first error.
+fn collect_events<'py>(
+    py: Python<'py>,
+    events: impl Iterator<Item = Result<ParseEvent, ParserError>>,
+) -> PyResult<Vec<Bound<'py, PyDict>>> {
+    events
+        .map(|event| {
+            let event = event.map_err(|err| PyValueError::new_err(err.to_string()))?;
+            event_to_py(py, event)
+        })
+        .collect()
+}
+
+fn event_to_py(py: Python<'_>, event: ParseEvent) -> PyResult<Bound<'_, PyDict>> {
+    let dict = PyDict::new(py);
+    let (kind, path) = match event {
+        ParseEvent::Null { path } => ("Null", path),
+        ParseEvent::Boolean { path, value } => {
+            dict.set_item("value", value)?;
+            ("Boolean", path)
+        }
+        ParseEvent::Number { path, value } => {
+            dict.set_item("value", value)?;
+            ("Number", path)
+        }
+        ParseEvent::String {
+            path,
+            value,
+            fragment,
+            is_final,
+        } => {
+            dict.set_item("fragment", fragment)?;
+            dict.set_item("is_final", is_final)?;
+            dict.set_item("value", value)?;
+            ("String", path)
+        }
+        ParseEvent::StringComplete { path, value } => {
+            dict.set_item("value", value)?;
+            ("StringComplete", path)
+        }
+        ParseEvent::ArrayStart {
+            path,
+            capacity_hint,
+            ..
+        } => {
+            dict.set_item("capacity_hint", capacity_hint)?;
+            ("ArrayStart", path)
+        }
+        ParseEvent::ArrayEnd { path, value } => {
+            let value = value.map(|items| value_to_py(py, &Value::Array(items)));
+            dict.set_item("value", value.transpose()?)?;
+            ("ArrayEnd", path)
+        }
+        ParseEvent::ObjectBegin {
+            path,
+            capacity_hint,
+            ..
+        } => {
+            dict.set_item("capacity_hint", capacity_hint)?;
+            ("ObjectBegin", path)
+        }
+        ParseEvent::ObjectEnd { path, value } => {
+            let value = value.map(|members| value_to_py(py, &Value::Object(members)));
+            dict.set_item("value", value.transpose()?)?;
+            ("ObjectEnd", path)
+        }
+        ParseEvent::ScalarBegin { path } => ("ScalarBegin", path),
+        ParseEvent::ScalarEnd { path } => ("ScalarEnd", path),
+        ParseEvent::ObjectKey { path, key } => {
+            dict.set_item("key", &*key)?;
+            ("ObjectKey", path)
+        }
+    };
+    dict.set_item("type", kind)?;
+    let path = path
+        .iter()
+        .map(|component| match component {
+            PathComponent::Key(key) => Ok(PyString::new(py, key).into_any()),
+            PathComponent::Index(index) => Ok(index.into_pyobject(py)?.into_any()),
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    dict.set_item("path", PyList::new(py, path)?)?;
+    Ok(dict)
+}
+
+fn value_to_py<'py>(py: Python<'py>, value: &Value) -> PyResult<Bound<'py, PyAny>> {
+    Ok(match value {
+        Value::Null => py.None().into_bound(py),
+        Value::Boolean(b) => PyBool::new(py, *b).to_owned().into_any(),
+        Value::Number(n) => PyFloat::new(py, *n).into_any(),
+        Value::String(s) => PyString::new(py, s).into_any(),
+        Value::Array(items) => {
+            let list = PyList::empty(py);
+            for item in items {
+                list.append(value_to_py(py, item)?)?;
+            }
+            list.into_any()
+        }
+        Value::Object(members) => {
+            let dict = PyDict::new(py);
+            for (key, member) in members {
+                dict.set_item(&**key, value_to_py(py, member)?)?;
+            }
+            dict.into_any()
+        }
+    })
+}
 
 #[pymodule]
-pub fn jsonmodem(_py: Python<'_>, _m: &Bound<'_, PyModule>) -> PyResult<()> {
-    // empty for now
+pub fn jsonmodem(_py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyStreamingParser>()?;
     Ok(())
 }
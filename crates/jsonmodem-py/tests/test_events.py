@@ -0,0 +1,88 @@
+import pytest
+
+from jsonmodem import StreamingParser
+
+
+def parse(chunks, **options):
+    parser = StreamingParser(**options)
+    events = []
+    for chunk in chunks:
+        events += parser.feed(chunk)
+    return events + parser.finish()
+
+
+def test_scalar_and_container_events():
+    events = parse(['{"a": [nu', 'll, true, 1.5, "x', 'y"], "b": {}}'])
+    assert events == [
+        {"type": "ObjectBegin", "path": [], "capacity_hint": None},
+        {"type": "ArrayStart", "path": ["a"], "capacity_hint": None},
+        {"type": "Null", "path": ["a", 0]},
+        {"type": "Boolean", "path": ["a", 1], "value": True},
+        {"type": "Number", "path": ["a", 2], "value": 1.5},
+        {"type": "String", "path": ["a", 3], "fragment": "x", "is_final": False, "value": None},
+        {"type": "String", "path": ["a", 3], "fragment": "y", "is_final": True, "value": None},
+        {"type": "ArrayEnd", "path": ["a"], "value": None},
+        {"type": "ObjectBegin", "path": ["b"], "capacity_hint": None},
+        {"type": "ObjectEnd", "path": ["b"], "value": None},
+        {"type": "ObjectEnd", "path": [], "value": None},
+    ]
+
+
+def test_string_and_container_values():
+    events = parse(
+        ['{"a": ["x', 'y", false]}'],
+        string_value_mode="values",
+        non_scalar_values="all",
+    )
+    strings = [event for event in events if event["type"] == "String"]
+    assert [event["value"] for event in strings] == [None, "xy"]
+    assert events[-2] == {"type": "ArrayEnd", "path": ["a"], "value": ["xy", False]}
+    assert events[-1] == {"type": "ObjectEnd", "path": [], "value": {"a": ["xy", False]}}
+
+
+def test_boundary_key_and_capacity_events():
+    events = parse(
+        ['{"k": 1, "l": [2]}'],
+        scalar_boundary_events=True,
+        emit_key_events=True,
+        lookahead_capacity_hints=True,
+    )
+    assert [event["type"] for event in events] == [
+        "ObjectBegin",
+        "ObjectKey",
+        "ScalarBegin",
+        "Number",
+        "ScalarEnd",
+        "ObjectKey",
+        "ArrayStart",
+        "ScalarBegin",
+        "Number",
+        "ScalarEnd",
+        "ArrayEnd",
+        "ObjectEnd",
+    ]
+    assert events[0]["capacity_hint"] == 2
+    assert events[1] == {"type": "ObjectKey", "path": ["k"], "key": "k"}
+    assert events[7] == {"type": "ScalarBegin", "path": ["l", 0]}
+
+
+def test_multiple_values():
+    events = parse(["1 2"], allow_multiple_json_values=True)
+    assert [event["value"] for event in events] == [1.0, 2.0]
+
+
+def test_errors():
+    parser = StreamingParser()
+    with pytest.raises(ValueError):
+        parser.feed("[1, x]")
+    assert parser.feed("]") == []
+
+    parser = StreamingParser()
+    parser.feed("[1")
+    with pytest.raises(ValueError):
+        parser.finish()
+    with pytest.raises(RuntimeError):
+        parser.feed("]")
+
+    with pytest.raises(ValueError):
+        StreamingParser(string_value_mode="all")
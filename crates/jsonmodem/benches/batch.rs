@@ -0,0 +1,76 @@
+//! Benchmark – events pulled one at a time versus in batches from
+//! `BatchParser`, for a large array of objects
+#![expect(missing_docs)]
+
+use std::time::Duration;
+
+use criterion::{BenchmarkId, Criterion, Throughput, black_box, criterion_group, criterion_main};
+use jsonmodem::{BatchParser, ParserOptions, StreamingParser};
+
+const OBJECTS: usize = 10_000;
+
+/// An array of `OBJECTS` small objects, and the number of events it produces.
+fn document() -> (String, u64) {
+    let object = r#"{"id": 1, "name": "x", "tags": ["a", "b"], "ok": true}"#;
+    let text = format!("[{}]", vec![object; OBJECTS].join(","));
+    // ObjectBegin, four members, two tags, ArrayStart/End and ObjectEnd.
+    (text, 2 + 9 * OBJECTS as u64)
+}
+
+fn single(text: &str) -> usize {
+    let mut parser = StreamingParser::new(ParserOptions::default());
+    let mut produced = 0;
+    for event in parser.feed(text) {
+        black_box(event.unwrap());
+        produced += 1;
+    }
+    for event in parser.finish() {
+        black_box(event.unwrap());
+        produced += 1;
+    }
+    produced
+}
+
+fn batched(text: &str, batch_size: usize) -> usize {
+    let mut parser = BatchParser::new(ParserOptions::default(), batch_size);
+    let mut produced = 0;
+    for batch in parser.feed(text) {
+        produced += black_box(batch.unwrap()).len();
+    }
+    for batch in parser.finish() {
+        produced += black_box(batch.unwrap()).len();
+    }
+    produced
+}
+
+fn bench_batch(c: &mut Criterion) {
+    let (text, events) = document();
+    let mut group = c.benchmark_group("batch");
+    group.throughput(Throughput::Elements(events));
+
+    group.bench_function("single", |b| b.iter(|| black_box(single(&text))));
+    for batch_size in [16usize, 256, 4096] {
+        group.bench_with_input(BenchmarkId::new("batched", batch_size), &text, |b, text| {
+            b.iter(|| black_box(batched(text, batch_size)));
+        });
+    }
+    group.finish();
+}
+
+fn criterion() -> Criterion {
+    let mut c = Criterion::default();
+    if cfg!(feature = "bench-fast") {
+        c = c
+            .warm_up_time(Duration::from_millis(10))
+            .measurement_time(Duration::from_millis(100))
+            .sample_size(10);
+    } else {
+        c = c
+            .warm_up_time(Duration::from_secs(5))
+            .measurement_time(Duration::from_secs(10));
+    }
+    c
+}
+
+criterion_group! { name = benches; config = criterion(); targets = bench_batch }
+criterion_main!(benches);
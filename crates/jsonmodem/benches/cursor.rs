@@ -0,0 +1,83 @@
+//! Benchmark – repeated lookups in one document through a `JsonCursor`
+//! versus parsing the whole document for every lookup
+#![expect(missing_docs)]
+
+use std::time::Duration;
+
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use jsonmodem::{JsonCursor, ParserOptions, PathComponent, StreamingParser, Value, path};
+
+const RECORDS: usize = 1_000;
+const LOOKUPS: usize = 100;
+
+fn document() -> String {
+    let records: Vec<String> = (0..RECORDS)
+        .map(|i| format!(r#"{{"id": {i}, "name": "record {i}", "tags": ["a", "b"], "ok": true}}"#))
+        .collect();
+    format!(
+        r#"{{"records": [{}], "total": {RECORDS}}}"#,
+        records.join(", ")
+    )
+}
+
+/// Paths spread over the document, visited round-robin.
+fn paths() -> Vec<Vec<PathComponent>> {
+    (0..10)
+        .map(|i| path!["records", i * RECORDS / 10, "name"])
+        .collect()
+}
+
+fn navigate<'a>(mut value: &'a Value, path: &[PathComponent]) -> Option<&'a Value> {
+    for component in path {
+        value = match (value, component) {
+            (Value::Object(map), PathComponent::Key(key)) => map.get(key)?,
+            (Value::Array(array), PathComponent::Index(index)) => array.get(*index)?,
+            _ => return None,
+        };
+    }
+    Some(value)
+}
+
+fn bench_cursor(c: &mut Criterion) {
+    let text = document();
+    let paths = paths();
+    let mut group = c.benchmark_group("cursor");
+
+    group.bench_function("cursor", |b| {
+        b.iter(|| {
+            let cursor = JsonCursor::new(text.clone());
+            for path in paths.iter().cycle().take(LOOKUPS) {
+                black_box(cursor.get_value(path));
+            }
+        });
+    });
+    group.bench_function("reparse", |b| {
+        b.iter(|| {
+            for path in paths.iter().cycle().take(LOOKUPS) {
+                let value = StreamingParser::new(ParserOptions::default())
+                    .into_value(&text)
+                    .unwrap();
+                black_box(navigate(&value, path).cloned());
+            }
+        });
+    });
+    group.finish();
+}
+
+fn criterion() -> Criterion {
+    let mut c = Criterion::default();
+    if cfg!(feature = "bench-fast") {
+        c = c
+            .warm_up_time(Duration::from_millis(10))
+            .measurement_time(Duration::from_millis(100))
+            .sample_size(10);
+    } else {
+        c = c
+            .warm_up_time(Duration::from_secs(5))
+            .measurement_time(Duration::from_secs(10));
+    }
+    c
+}
+
+criterion_group! { name = benches; config = criterion(); targets = bench_cursor }
+criterion_main!(benches);
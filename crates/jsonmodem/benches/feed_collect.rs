@@ -0,0 +1,65 @@
+//! Benchmark – `StreamingParser::feed` iteration versus `feed_collect`
+#![expect(missing_docs)]
+
+use std::time::Duration;
+
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use jsonmodem::{ParserOptions, StreamingParser, produce_chunks};
+
+const JSON: &str = include_str!("jiter_data/response_large.json");
+
+fn run_iterator(chunks: &[&str]) -> usize {
+    let mut parser = StreamingParser::new(ParserOptions::default());
+    let mut produced = 0usize;
+    for chunk in chunks {
+        for res in parser.feed(chunk) {
+            black_box(res.unwrap());
+            produced += 1;
+        }
+    }
+    produced
+}
+
+fn run_collect(chunks: &[&str]) -> usize {
+    let mut parser = StreamingParser::new(ParserOptions::default());
+    let mut produced = 0usize;
+    for chunk in chunks {
+        let events = parser.feed_collect(chunk).unwrap();
+        produced += events.len();
+        black_box(events);
+    }
+    produced
+}
+
+fn bench_feed_collect(c: &mut Criterion) {
+    let mut group = c.benchmark_group("feed_collect");
+
+    for &parts in &[100usize, 1_000, 5_000] {
+        let chunks = produce_chunks(JSON, parts);
+        group.bench_with_input(BenchmarkId::new("iterator", parts), &chunks, |b, chunks| {
+            b.iter(|| black_box(run_iterator(chunks)));
+        });
+        group.bench_with_input(BenchmarkId::new("collect", parts), &chunks, |b, chunks| {
+            b.iter(|| black_box(run_collect(chunks)));
+        });
+    }
+    group.finish();
+}
+
+fn criterion() -> Criterion {
+    let mut c = Criterion::default();
+    if cfg!(feature = "bench-fast") {
+        c = c
+            .warm_up_time(Duration::from_millis(10))
+            .measurement_time(Duration::from_millis(100))
+            .sample_size(10);
+    } else {
+        c = c
+            .warm_up_time(Duration::from_secs(5))
+            .measurement_time(Duration::from_secs(10));
+    }
+    c
+}
+
+criterion_group! { name = benches; config = criterion(); targets = bench_feed_collect }
+criterion_main!(benches);
@@ -0,0 +1,52 @@
+//! Benchmark – `StreamingParser::into_value` versus `serde_json::from_str`
+//! for building a whole document of about 100 KB
+#![expect(missing_docs)]
+
+use std::time::Duration;
+
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use jsonmodem::{ParserOptions, StreamingParser};
+
+const RESPONSE: &str = include_str!("jiter_data/response_large.json");
+const STRINGS: &str = include_str!("jiter_data/string_array_unique.json");
+
+fn inputs() -> Vec<(&'static str, String)> {
+    // Six copies of a 16 KB response, about 97 KB in all.
+    let responses = format!("[{}]", [RESPONSE; 6].join(","));
+    vec![("responses", responses), ("strings", STRINGS.to_string())]
+}
+
+fn bench_into_value(c: &mut Criterion) {
+    let mut group = c.benchmark_group("into_value");
+
+    for (name, json) in inputs() {
+        group.bench_with_input(BenchmarkId::new("jsonmodem", name), &json, |b, json| {
+            b.iter(|| {
+                let parser = StreamingParser::new(ParserOptions::default());
+                black_box(parser.into_value(json).unwrap())
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("serde_json", name), &json, |b, json| {
+            b.iter(|| black_box(serde_json::from_str::<serde_json::Value>(json).unwrap()));
+        });
+    }
+    group.finish();
+}
+
+fn criterion() -> Criterion {
+    let mut c = Criterion::default();
+    if cfg!(feature = "bench-fast") {
+        c = c
+            .warm_up_time(Duration::from_millis(10))
+            .measurement_time(Duration::from_millis(100))
+            .sample_size(10);
+    } else {
+        c = c
+            .warm_up_time(Duration::from_secs(5))
+            .measurement_time(Duration::from_secs(10));
+    }
+    c
+}
+
+criterion_group! { name = benches; config = criterion(); targets = bench_into_value }
+criterion_main!(benches);
@@ -0,0 +1,80 @@
+//! Benchmark – a single string field of about 10 MB, fed in chunks
+#![expect(missing_docs)]
+
+use std::time::Duration;
+
+use criterion::{BenchmarkId, Criterion, Throughput, black_box, criterion_group, criterion_main};
+use jsonmodem::{ParserOptions, StreamingParser, StringValueMode};
+
+/// An object with one string member of `len` bytes, mixing ASCII and
+/// multi-byte characters, with an escape every few hundred bytes.
+fn document(len: usize) -> String {
+    let run = "lorem ipsum dolor sit amet, consectetur adipiscing elit ☕ héllo wörld ";
+    let mut text = String::with_capacity(len + 16);
+    text.push_str(r#"{"content": ""#);
+    while text.len() < len {
+        for _ in 0..4 {
+            text.push_str(run);
+        }
+        text.push_str(r"\n");
+    }
+    text.push_str(r#""}"#);
+    text
+}
+
+fn run(text: &str, chunk_size: usize, mode: StringValueMode) -> usize {
+    let mut parser = StreamingParser::new(ParserOptions {
+        string_value_mode: mode,
+        ..Default::default()
+    });
+    let mut produced = 0usize;
+    for chunk in text.as_bytes().chunks(chunk_size) {
+        for res in parser.feed_bytes(chunk).unwrap() {
+            black_box(res.unwrap());
+            produced += 1;
+        }
+    }
+    for res in parser.finish() {
+        black_box(res.unwrap());
+        produced += 1;
+    }
+    produced
+}
+
+fn bench_long_string(c: &mut Criterion) {
+    let text = document(10 << 20);
+    let mut group = c.benchmark_group("long_string");
+    group.throughput(Throughput::Bytes(text.len() as u64));
+    group.sample_size(10);
+
+    for &chunk_size in &[4096usize, 1 << 20] {
+        for (name, mode) in [
+            ("none", StringValueMode::None),
+            ("values", StringValueMode::Values),
+        ] {
+            let id = format!("{name}/chunk{chunk_size}");
+            group.bench_with_input(BenchmarkId::from_parameter(id), &text, |b, text| {
+                b.iter(|| black_box(run(text, chunk_size, mode)));
+            });
+        }
+    }
+    group.finish();
+}
+
+fn criterion() -> Criterion {
+    let mut c = Criterion::default();
+    if cfg!(feature = "bench-fast") {
+        c = c
+            .warm_up_time(Duration::from_millis(10))
+            .measurement_time(Duration::from_millis(100))
+            .sample_size(10);
+    } else {
+        c = c
+            .warm_up_time(Duration::from_secs(5))
+            .measurement_time(Duration::from_secs(10));
+    }
+    c
+}
+
+criterion_group! { name = benches; config = criterion(); targets = bench_long_string }
+criterion_main!(benches);
@@ -0,0 +1,68 @@
+//! Benchmark – the overhead of counting events with `EventCounter` while
+//! parsing the large fixture
+//!
+//! Run with `--features metrics` to measure counting; without it, release
+//! builds compile `EventCounter` to a passthrough.
+#![expect(missing_docs)]
+
+use std::time::Duration;
+
+use criterion::{Criterion, Throughput, black_box, criterion_group, criterion_main};
+use jsonmodem::{EventCounter, MetricsReport, ParserOptions, StreamingParser, produce_chunks};
+
+fn bench_metrics(c: &mut Criterion) {
+    let payload = std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/benches/jiter_data/response_large.json"
+    ))
+    .unwrap();
+    let chunks = produce_chunks(&payload, 100);
+    let mut group = c.benchmark_group("metrics");
+    group.throughput(Throughput::Bytes(payload.len() as u64));
+
+    group.bench_function("plain", |b| {
+        b.iter(|| {
+            let mut parser = StreamingParser::new(ParserOptions::default());
+            let mut n = 0;
+            for chunk in &chunks {
+                n += parser.feed(black_box(chunk)).count();
+            }
+            black_box(n + parser.finish().count())
+        });
+    });
+    group.bench_function("event_counter", |b| {
+        b.iter(|| {
+            let mut parser = StreamingParser::new(ParserOptions::default());
+            let mut report = MetricsReport::default();
+            for chunk in &chunks {
+                let mut events = EventCounter::new(parser.feed(black_box(chunk)));
+                events.add_bytes_fed(chunk.len());
+                events.by_ref().for_each(drop);
+                report += events.report();
+            }
+            let mut events = EventCounter::new(parser.finish());
+            events.by_ref().for_each(drop);
+            report += events.report();
+            black_box(report)
+        });
+    });
+    group.finish();
+}
+
+fn criterion() -> Criterion {
+    let mut c = Criterion::default();
+    if cfg!(feature = "bench-fast") {
+        c = c
+            .warm_up_time(Duration::from_millis(10))
+            .measurement_time(Duration::from_millis(100))
+            .sample_size(10);
+    } else {
+        c = c
+            .warm_up_time(Duration::from_secs(5))
+            .measurement_time(Duration::from_secs(10));
+    }
+    c
+}
+
+criterion_group! { name = benches; config = criterion(); targets = bench_metrics }
+criterion_main!(benches);
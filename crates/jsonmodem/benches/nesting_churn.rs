@@ -0,0 +1,70 @@
+//! Benchmark – repeated opening and closing of containers in tiny chunks
+#![expect(missing_docs)]
+
+use std::time::Duration;
+
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use jsonmodem::{ParserOptions, StreamingParser};
+
+/// An array holding `pairs` empty arrays, each nested `depth` levels deep.
+fn document(pairs: usize, depth: usize) -> String {
+    let pair = format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+    format!("[{}]", vec![pair; pairs].join(","))
+}
+
+fn run(text: &str, chunk_size: usize, options: ParserOptions) -> usize {
+    let mut parser = StreamingParser::new(options);
+    let mut produced = 0usize;
+    for chunk in text.as_bytes().chunks(chunk_size) {
+        for res in parser.feed_bytes(chunk).unwrap() {
+            black_box(res.unwrap());
+            produced += 1;
+        }
+    }
+    for res in parser.finish() {
+        black_box(res.unwrap());
+        produced += 1;
+    }
+    produced
+}
+
+fn bench_nesting_churn(c: &mut Criterion) {
+    let mut group = c.benchmark_group("nesting_churn");
+
+    for &depth in &[1usize, 8] {
+        let text = document(10_000 / depth, depth);
+        for &chunk_size in &[1usize, 4, 64] {
+            let id = format!("depth{depth}/chunk{chunk_size}");
+            group.bench_with_input(BenchmarkId::new("unlimited", &id), &text, |b, text| {
+                b.iter(|| black_box(run(text, chunk_size, ParserOptions::default())));
+            });
+            let options = ParserOptions {
+                // The opening and closing runs of the first and last pairs.
+                max_nesting_change_per_token: Some(2 * depth + 1),
+                ..Default::default()
+            };
+            group.bench_with_input(BenchmarkId::new("limited", &id), &text, |b, text| {
+                b.iter(|| black_box(run(text, chunk_size, options)));
+            });
+        }
+    }
+    group.finish();
+}
+
+fn criterion() -> Criterion {
+    let mut c = Criterion::default();
+    if cfg!(feature = "bench-fast") {
+        c = c
+            .warm_up_time(Duration::from_millis(10))
+            .measurement_time(Duration::from_millis(100))
+            .sample_size(10);
+    } else {
+        c = c
+            .warm_up_time(Duration::from_secs(5))
+            .measurement_time(Duration::from_secs(10));
+    }
+    c
+}
+
+criterion_group! { name = benches; config = criterion(); targets = bench_nesting_churn }
+criterion_main!(benches);
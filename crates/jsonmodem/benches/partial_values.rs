@@ -0,0 +1,66 @@
+//! Benchmark – latency to the first value of a 1 MB chunk with
+//! `JsonModemValues`, which yields after the first event, versus
+//! `StreamingValuesParser`, which yields once the whole chunk is parsed
+#![expect(missing_docs)]
+
+use std::time::Duration;
+
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use jsonmodem::{JsonModemValues, NonScalarValueMode, ParserOptions, StreamingValuesParser, Value};
+
+const TARGET_LEN: usize = 1 << 20;
+
+fn document() -> String {
+    let mut items = Vec::new();
+    let mut len = 0;
+    while len < TARGET_LEN {
+        let i = items.len();
+        let item = format!(r#"{{"id": {i}, "name": "product {i}", "tags": ["new", "sale"]}}"#);
+        len += item.len() + 2;
+        items.push(item);
+    }
+    format!(r#"{{"items": [{}]}}"#, items.join(", "))
+}
+
+fn bench_first_value(c: &mut Criterion) {
+    let text = document();
+    let mut group = c.benchmark_group("partial_values_first_yield");
+
+    group.bench_function("json_modem_values", |b| {
+        b.iter(|| {
+            let mut values = JsonModemValues::new(ParserOptions::default());
+            values.feed(black_box(&text));
+            let (_, value) = values.next().unwrap().unwrap();
+            black_box(value.into_value())
+        });
+    });
+    group.bench_function("streaming_values_parser", |b| {
+        b.iter(|| {
+            let mut parser = StreamingValuesParser::new(ParserOptions {
+                non_scalar_values: NonScalarValueMode::Roots,
+                ..ParserOptions::default()
+            });
+            let values = parser.feed(black_box(&text)).unwrap();
+            black_box::<Value>(values.into_iter().next().unwrap().value)
+        });
+    });
+    group.finish();
+}
+
+fn criterion() -> Criterion {
+    let mut c = Criterion::default();
+    if cfg!(feature = "bench-fast") {
+        c = c
+            .warm_up_time(Duration::from_millis(10))
+            .measurement_time(Duration::from_millis(100))
+            .sample_size(10);
+    } else {
+        c = c
+            .warm_up_time(Duration::from_secs(5))
+            .measurement_time(Duration::from_secs(10));
+    }
+    c
+}
+
+criterion_group! { name = benches; config = criterion(); targets = bench_first_value }
+criterion_main!(benches);
@@ -0,0 +1,101 @@
+//! Benchmark – replaying recorded events versus parsing the input again
+#![expect(missing_docs)]
+
+use std::time::Duration;
+
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use jsonmodem::{
+    CompressedRecorder, EventRecorder, ParseEvent, ParserOptions, StreamingParser, produce_chunks,
+};
+
+const JSON: &str = include_str!("jiter_data/response_large.json");
+
+fn reparse(chunks: &[&str]) -> usize {
+    let mut parser = StreamingParser::new(ParserOptions::default());
+    let mut produced = 0usize;
+    for chunk in chunks {
+        for res in parser.feed(chunk) {
+            black_box(res.unwrap());
+            produced += 1;
+        }
+    }
+    for res in parser.finish() {
+        black_box(res.unwrap());
+        produced += 1;
+    }
+    produced
+}
+
+#[expect(clippy::cast_precision_loss)]
+fn bench_replay(c: &mut Criterion) {
+    let chunks = produce_chunks(JSON, 1_000);
+    let mut plain = EventRecorder::new();
+    let mut compressed = CompressedRecorder::new();
+    let mut parser = StreamingParser::new(ParserOptions::default());
+    for chunk in &chunks {
+        for event in parser.feed(chunk) {
+            let event = event.unwrap();
+            compressed.record(event.clone());
+            plain.record(event);
+        }
+    }
+    for event in parser.finish() {
+        let event = event.unwrap();
+        compressed.record(event.clone());
+        plain.record(event);
+    }
+
+    let components: usize = plain.replay().map(path_len).sum();
+    println!(
+        "replay: {} events, {} path components recorded, {} stored compressed ({:.1}x)",
+        plain.len(),
+        components,
+        compressed.stored_components(),
+        components as f64 / compressed.stored_components() as f64,
+    );
+
+    let mut group = c.benchmark_group("replay");
+    group.bench_function("reparse", |b| b.iter(|| black_box(reparse(&chunks))));
+    group.bench_function("event_recorder", |b| {
+        b.iter(|| black_box(plain.replay().map(black_box).count()));
+    });
+    group.bench_function("compressed_recorder", |b| {
+        b.iter(|| black_box(compressed.replay().map(black_box).count()));
+    });
+    group.finish();
+}
+
+fn path_len(event: &ParseEvent) -> usize {
+    match event {
+        ParseEvent::Null { path }
+        | ParseEvent::Boolean { path, .. }
+        | ParseEvent::Number { path, .. }
+        | ParseEvent::String { path, .. }
+        | ParseEvent::StringComplete { path, .. }
+        | ParseEvent::ArrayStart { path, .. }
+        | ParseEvent::ArrayEnd { path, .. }
+        | ParseEvent::ObjectBegin { path, .. }
+        | ParseEvent::ObjectEnd { path, .. }
+        | ParseEvent::ScalarBegin { path }
+        | ParseEvent::ScalarEnd { path }
+        | ParseEvent::ObjectKey { path, .. } => path.len(),
+    }
+}
+
+fn criterion() -> Criterion {
+    let mut c = Criterion::default();
+    if cfg!(feature = "bench-fast") {
+        c = c
+            .warm_up_time(Duration::from_millis(10))
+            .measurement_time(Duration::from_millis(100))
+            .sample_size(10);
+    } else {
+        c = c
+            .warm_up_time(Duration::from_secs(5))
+            .measurement_time(Duration::from_secs(10));
+    }
+    c
+}
+
+criterion_group! { name = benches; config = criterion(); targets = bench_replay }
+criterion_main!(benches);
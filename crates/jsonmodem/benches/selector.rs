@@ -0,0 +1,67 @@
+//! Benchmark – selecting every `items[*].name` of a 1 MB document with
+//! `select_all` versus `jsonpath_lib` over a `serde_json` value
+#![expect(missing_docs)]
+
+use std::time::Duration;
+
+use criterion::{Criterion, Throughput, black_box, criterion_group, criterion_main};
+use jsonmodem::{Glob, select_all};
+
+const TARGET_LEN: usize = 1 << 20;
+
+fn document() -> String {
+    let mut items = Vec::new();
+    let mut len = 0;
+    while len < TARGET_LEN {
+        let i = items.len();
+        let item = format!(
+            r#"{{"id": {i}, "name": "product {i}", "price": {}.99, "tags": ["new", "sale"], "stock": {{"warehouse": "w{}", "count": {}}}}}"#,
+            i % 100,
+            i % 7,
+            i * 3
+        );
+        len += item.len() + 2;
+        items.push(item);
+    }
+    format!(r#"{{"items": [{}]}}"#, items.join(", "))
+}
+
+fn bench_selector(c: &mut Criterion) {
+    let text = document();
+    let glob = Glob::compile("items[*].name").unwrap();
+    let mut group = c.benchmark_group("selector");
+    group.throughput(Throughput::Bytes(text.len() as u64));
+
+    group.bench_function("select_all", |b| {
+        b.iter(|| black_box(select_all(&text, &glob).unwrap().len()));
+    });
+    group.bench_function("jsonpath_lib", |b| {
+        b.iter(|| {
+            let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+            black_box(
+                jsonpath_lib::select(&value, "$.items[*].name")
+                    .unwrap()
+                    .len(),
+            )
+        });
+    });
+    group.finish();
+}
+
+fn criterion() -> Criterion {
+    let mut c = Criterion::default();
+    if cfg!(feature = "bench-fast") {
+        c = c
+            .warm_up_time(Duration::from_millis(10))
+            .measurement_time(Duration::from_millis(100))
+            .sample_size(10);
+    } else {
+        c = c
+            .warm_up_time(Duration::from_secs(5))
+            .measurement_time(Duration::from_secs(10));
+    }
+    c
+}
+
+criterion_group! { name = benches; config = criterion(); targets = bench_selector }
+criterion_main!(benches);
@@ -0,0 +1,96 @@
+//! Benchmark – parsing many short strings, with and without a reserved
+//! string capacity
+#![expect(missing_docs)]
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use jsonmodem::{ParserOptions, StreamingParser};
+
+/// Counts the allocations and reallocations made by the benchmark.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const STRINGS: usize = 10_000;
+const CAPACITY: usize = 256;
+
+/// An array of `STRINGS` strings of 100 to 200 bytes. The escapes make the
+/// parser build each string in several steps.
+fn document() -> String {
+    let strings: Vec<String> = (0..STRINGS)
+        .map(|i| format!("\"{}\"", "lorem\\tipsum ".repeat(9 + i % 8)))
+        .collect();
+    format!("[{}]", strings.join(","))
+}
+
+fn run(text: &str, capacity: usize) -> usize {
+    let mut parser = StreamingParser::new(ParserOptions::default()).with_string_capacity(capacity);
+    let mut count = 0;
+    for event in parser.feed(text) {
+        black_box(event.unwrap());
+        count += 1;
+    }
+    count + parser.finish().count()
+}
+
+fn allocations(f: impl FnOnce() -> usize) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    black_box(f());
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+fn bench_string_capacity(c: &mut Criterion) {
+    let text = document();
+    println!(
+        "string_capacity: {STRINGS} strings, {} allocations without a capacity, {} with {CAPACITY} bytes",
+        allocations(|| run(&text, 0)),
+        allocations(|| run(&text, CAPACITY)),
+    );
+
+    let mut group = c.benchmark_group("string_capacity");
+    group.bench_function("none", |b| b.iter(|| black_box(run(&text, 0))));
+    group.bench_function("reserved", |b| b.iter(|| black_box(run(&text, CAPACITY))));
+    group.finish();
+}
+
+fn criterion() -> Criterion {
+    let mut c = Criterion::default();
+    if cfg!(feature = "bench-fast") {
+        c = c
+            .warm_up_time(Duration::from_millis(10))
+            .measurement_time(Duration::from_millis(100))
+            .sample_size(10);
+    } else {
+        c = c
+            .warm_up_time(Duration::from_secs(5))
+            .measurement_time(Duration::from_secs(10));
+    }
+    c
+}
+
+criterion_group! { name = benches; config = criterion(); targets = bench_string_capacity }
+criterion_main!(benches);
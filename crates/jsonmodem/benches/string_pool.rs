@@ -0,0 +1,98 @@
+//! Benchmark – parsing many objects with the same keys, with and without a
+//! `StringPool`
+#![expect(missing_docs)]
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use jsonmodem::{JsonValueFactory, ParserOptions, StdValueFactory, StreamingParser, StringPool};
+
+/// Counts the allocations made by the benchmark.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const OBJECTS: usize = 10_000;
+
+/// A stream of `OBJECTS` objects with the same keys.
+fn document() -> String {
+    let object = r#"{"id": 1, "name": "x", "email": "y", "address": {"street": "z", "city": "w"}}"#;
+    vec![object; OBJECTS].join("\n")
+}
+
+/// Parses every object, keeping the events so that their keys stay alive.
+fn run<F: JsonValueFactory<Value = jsonmodem::Value>>(text: &str, mut factory: F) -> usize {
+    let mut parser = StreamingParser::new(ParserOptions {
+        allow_multiple_json_values: true,
+        ..Default::default()
+    });
+    let mut events = Vec::new();
+    events.extend(parser.feed_with(&mut factory, text).map(Result::unwrap));
+    events.extend(parser.finish_with(&mut factory).map(Result::unwrap));
+    black_box(&events);
+    events.len()
+}
+
+fn allocations(f: impl FnOnce() -> usize) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    black_box(f());
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+fn bench_string_pool(c: &mut Criterion) {
+    let text = document();
+    println!(
+        "string_pool: {OBJECTS} objects, {} allocations without a pool, {} with one",
+        allocations(|| run(&text, StdValueFactory)),
+        allocations(|| run(&text, StringPool::new())),
+    );
+
+    let mut group = c.benchmark_group("string_pool");
+    group.bench_function("std_factory", |b| {
+        b.iter(|| black_box(run(&text, StdValueFactory)));
+    });
+    group.bench_function("fresh_pool", |b| {
+        b.iter(|| black_box(run(&text, StringPool::new())));
+    });
+    let mut pool = StringPool::new();
+    group.bench_function("reused_pool", |b| {
+        b.iter(|| black_box(run(&text, &mut pool)));
+    });
+    group.finish();
+}
+
+fn criterion() -> Criterion {
+    let mut c = Criterion::default();
+    if cfg!(feature = "bench-fast") {
+        c = c
+            .warm_up_time(Duration::from_millis(10))
+            .measurement_time(Duration::from_millis(100))
+            .sample_size(10);
+    } else {
+        c = c
+            .warm_up_time(Duration::from_secs(5))
+            .measurement_time(Duration::from_secs(10));
+    }
+    c
+}
+
+criterion_group! { name = benches; config = criterion(); targets = bench_string_pool }
+criterion_main!(benches);
@@ -0,0 +1,59 @@
+//! Benchmark – checking a 1 MB document with `validate_json` versus parsing
+//! it into a `serde_json::Value`
+#![expect(missing_docs)]
+
+use std::time::Duration;
+
+use criterion::{Criterion, Throughput, black_box, criterion_group, criterion_main};
+use jsonmodem::validate_json;
+
+const TARGET_LEN: usize = 1 << 20;
+
+fn document() -> String {
+    let mut items = Vec::new();
+    let mut len = 0;
+    while len < TARGET_LEN {
+        let i = items.len();
+        let item = format!(
+            r#"{{"id": {i}, "name": "product {i}", "price": {}.99, "tags": ["new", "sale"], "stock": {{"warehouse": "w{}", "count": {}}}}}"#,
+            i % 100,
+            i % 7,
+            i * 3
+        );
+        len += item.len() + 2;
+        items.push(item);
+    }
+    format!(r#"{{"items": [{}]}}"#, items.join(", "))
+}
+
+fn bench_validate(c: &mut Criterion) {
+    let text = document();
+    let mut group = c.benchmark_group("validate");
+    group.throughput(Throughput::Bytes(text.len() as u64));
+
+    group.bench_function("validate_json", |b| {
+        b.iter(|| black_box(validate_json(&text).is_ok()));
+    });
+    group.bench_function("serde_json_value", |b| {
+        b.iter(|| black_box(serde_json::from_str::<serde_json::Value>(&text).is_ok()));
+    });
+    group.finish();
+}
+
+fn criterion() -> Criterion {
+    let mut c = Criterion::default();
+    if cfg!(feature = "bench-fast") {
+        c = c
+            .warm_up_time(Duration::from_millis(10))
+            .measurement_time(Duration::from_millis(100))
+            .sample_size(10);
+    } else {
+        c = c
+            .warm_up_time(Duration::from_secs(5))
+            .measurement_time(Duration::from_secs(10));
+    }
+    c
+}
+
+criterion_group! { name = benches; config = criterion(); targets = bench_validate }
+criterion_main!(benches);
@@ -0,0 +1,213 @@
+//! Parsing into batches of events.
+//!
+//! Pulling events one at a time costs an iterator step, and usually a branch
+//! in the consumer, per event. [`BatchParser`] collects the events of the
+//! input into `Vec`s of a fixed size and yields one whole batch per step, so
+//! a pipeline that hands events on in bulk, e.g. over a channel, pays that
+//! cost once per batch.
+//!
+//! Events that do not fill a batch stay in the parser until more input fills
+//! it, or until [`BatchParser::drain`] or [`BatchParser::finish`] flushes
+//! them.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{BatchParser, ParserOptions};
+//!
+//! let mut parser = BatchParser::new(ParserOptions::default(), 4);
+//! let batches: Vec<_> = parser.feed("[1, 2, 3, 4, 5,").collect::<Result<_, _>>()?;
+//! assert_eq!(batches.len(), 1);
+//! assert_eq!(batches[0].len(), 4);
+//! assert_eq!(parser.drain().len(), 2);
+//!
+//! assert_eq!(parser.feed(" 6]").count(), 0);
+//! let batches: Vec<_> = parser.finish().collect::<Result<_, _>>()?;
+//! assert_eq!(batches.len(), 1);
+//! assert_eq!(batches[0].len(), 2);
+//! # Ok::<(), jsonmodem::ParserError>(())
+//! ```
+use alloc::vec::Vec;
+
+use crate::{
+    ParseEvent, ParserError, ParserOptions, StdValueFactory, StreamingParser,
+    parser::{ClosedStreamingParser, StreamingParserIteratorWith},
+};
+
+/// A streaming parser that yields its events in batches.
+#[derive(Debug, Clone)]
+pub struct BatchParser {
+    parser: StreamingParser,
+    batch_size: usize,
+    /// The events of the batch being filled.
+    pending: Vec<ParseEvent>,
+}
+
+impl BatchParser {
+    /// Creates a parser that yields batches of `batch_size` events. A batch
+    /// size of zero is treated as one.
+    #[must_use]
+    pub fn new(options: ParserOptions, batch_size: usize) -> Self {
+        Self {
+            parser: StreamingParser::new(options),
+            batch_size: batch_size.max(1),
+            pending: Vec::new(),
+        }
+    }
+
+    /// The number of events in each full batch.
+    #[must_use]
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// Changes the size of the batches yielded from now on. The events
+    /// already pending count towards the next batch, which is yielded as soon
+    /// as it holds at least `batch_size` events.
+    pub fn set_batch_size(&mut self, batch_size: usize) {
+        self.batch_size = batch_size.max(1);
+    }
+
+    /// Feeds a chunk of JSON text and returns an iterator over the full
+    /// batches it completes.
+    ///
+    /// Events are produced lazily, as the iterator is advanced. A syntax
+    /// error is yielded on its own; the events before it stay pending.
+    pub fn feed<'a>(&'a mut self, text: &str) -> Batches<'a> {
+        Batches {
+            events: self.parser.feed(text),
+            pending: &mut self.pending,
+            batch_size: self.batch_size,
+        }
+    }
+
+    /// Returns the events of the batch being filled, leaving it empty.
+    pub fn drain(&mut self) -> Vec<ParseEvent> {
+        core::mem::take(&mut self.pending)
+    }
+
+    /// Marks the end of input and returns an iterator over the remaining
+    /// batches, the last of which may be partial.
+    #[must_use]
+    pub fn finish(self) -> FinishedBatches {
+        FinishedBatches {
+            events: self.parser.finish(),
+            pending: self.pending,
+            batch_size: self.batch_size,
+        }
+    }
+}
+
+/// Iterator returned by [`BatchParser::feed`].
+pub struct Batches<'a> {
+    events: StreamingParserIteratorWith<'a, StdValueFactory>,
+    pending: &'a mut Vec<ParseEvent>,
+    batch_size: usize,
+}
+
+impl Iterator for Batches<'_> {
+    type Item = Result<Vec<ParseEvent>, ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        next_batch(&mut self.events, self.pending, self.batch_size)
+    }
+}
+
+/// Iterator returned by [`BatchParser::finish`].
+pub struct FinishedBatches {
+    events: ClosedStreamingParser<StdValueFactory>,
+    pending: Vec<ParseEvent>,
+    batch_size: usize,
+}
+
+impl Iterator for FinishedBatches {
+    type Item = Result<Vec<ParseEvent>, ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        next_batch(&mut self.events, &mut self.pending, self.batch_size)
+            .or_else(|| (!self.pending.is_empty()).then(|| Ok(core::mem::take(&mut self.pending))))
+    }
+}
+
+/// Moves events into `pending` until it holds `batch_size` of them, and
+/// returns them. Returns `None` once `events` runs out.
+fn next_batch(
+    events: &mut impl Iterator<Item = Result<ParseEvent, ParserError>>,
+    pending: &mut Vec<ParseEvent>,
+    batch_size: usize,
+) -> Option<Result<Vec<ParseEvent>, ParserError>> {
+    while pending.len() < batch_size {
+        match events.next()? {
+            Ok(event) => {
+                if pending.capacity() == 0 {
+                    pending.reserve_exact(batch_size);
+                }
+                pending.push(event);
+            }
+            Err(err) => return Some(Err(err)),
+        }
+    }
+    Some(Ok(core::mem::take(pending)))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::BatchParser;
+    use crate::{ParserOptions, produce_chunks, tests::events};
+
+    const JSON: &str = r#"[{"a": 1, "b": "two"}, [true, null], "x", {"c": [3, 4]}]"#;
+
+    #[test]
+    fn batches_hold_every_event_in_order() {
+        for batch_size in [1, 3, 8, 100] {
+            for parts in [1, 4, JSON.len()] {
+                let chunks = produce_chunks(JSON, parts);
+                let mut parser = BatchParser::new(ParserOptions::default(), batch_size);
+                let mut batches = Vec::new();
+                for chunk in &chunks {
+                    batches.extend(parser.feed(chunk).map(Result::unwrap));
+                }
+                batches.extend(parser.finish().map(Result::unwrap));
+
+                let (last, full) = batches.split_last().unwrap();
+                assert!(full.iter().all(|batch| batch.len() == batch_size));
+                assert!(!last.is_empty() && last.len() <= batch_size);
+                assert_eq!(
+                    batches.concat(),
+                    events(JSON, parts, ParserOptions::default()),
+                    "{batch_size} {parts}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn drain_and_resize_flush_partial_batches() {
+        let mut parser = BatchParser::new(ParserOptions::default(), 0);
+        assert_eq!(parser.batch_size(), 1);
+        parser.set_batch_size(4);
+        assert_eq!(parser.feed("[1, 2, 3,").count(), 1);
+        assert_eq!(parser.drain().len(), 0);
+        assert_eq!(parser.feed(" 4, 5,").count(), 0);
+        let drained = parser.drain();
+        assert_eq!(drained.len(), 2);
+        assert!(parser.drain().is_empty());
+
+        // A smaller batch size takes effect with the next batch.
+        parser.set_batch_size(1);
+        let batches: Vec<_> = parser.feed(" 6]").map(Result::unwrap).collect();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(parser.finish().count(), 0);
+    }
+
+    #[test]
+    fn errors_are_yielded_alone() {
+        let mut parser = BatchParser::new(ParserOptions::default(), 10);
+        let batches: Vec<_> = parser.feed("[1, 2,, 3]").collect();
+        assert_eq!(batches.len(), 1);
+        assert!(batches[0].is_err());
+        assert_eq!(parser.drain().len(), 3);
+    }
+}
@@ -1,28 +1,325 @@
 #![expect(clippy::inline_always)]
 
-use alloc::{collections::VecDeque, string::String};
+use alloc::{collections::VecDeque, string::String, vec::Vec};
+use core::fmt;
 
-#[derive(Debug)]
+use crate::{
+    DecodeMode,
+    checkpoint::{CheckpointError, Reader, write_str, write_varint},
+};
+
+/// The number of pending characters shown by the `Debug` impl of [`Buffer`].
+const DEBUG_PENDING_CHARS: usize = 80;
+
+/// The input not yet lexed.
+///
+/// Input is decoded as it is pushed, so the lexer reads whole characters:
+/// a multi-byte character is peeked and consumed in one call, and only
+/// `push_bytes` deals with characters split across chunks.
+#[derive(Clone)]
 pub(crate) struct Buffer {
-    data: VecDeque<char>,
+    data: CharQueue,
+    /// The number of characters appended so far.
+    pushed: usize,
+    /// The leading bytes of a UTF-8 or CESU-8 sequence split across calls to
+    /// `push_bytes`.
+    partial: [u8; 6],
+    partial_len: u8,
+    /// How many bytes longer U+FFFD is than the incomplete character it
+    /// replaced at end of input.
+    replaced_excess: u8,
+    /// Whether `push_bytes` accepts surrogate pairs encoded as CESU-8.
+    cesu8: bool,
+    /// Whether `push_bytes` replaces surrogates encoded as three-byte
+    /// sequences, as in WTF-8, with U+FFFD instead of rejecting them.
+    replace_surrogates: bool,
+    /// For each character not yet consumed whose input was longer than its
+    /// UTF-8 encoding, its index among all characters appended and how many
+    /// bytes longer its input was.
+    widened: VecDeque<(usize, u8)>,
+    /// The sum of the excess lengths of the widened characters dropped from
+    /// `widened` once consumed.
+    widened_consumed: usize,
 }
 
 impl Buffer {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(decode_mode: DecodeMode) -> Self {
         Self {
-            data: VecDeque::new(),
+            data: CharQueue::default(),
+            pushed: 0,
+            partial: [0; 6],
+            partial_len: 0,
+            replaced_excess: 0,
+            cesu8: decode_mode == DecodeMode::Cesu8,
+            replace_surrogates: decode_mode == DecodeMode::ReplaceInvalid,
+            widened: VecDeque::new(),
+            widened_consumed: 0,
+        }
+    }
+
+    /// Appends UTF-8 input that may start or end in the middle of a
+    /// character. The bytes of an incomplete character at the end are carried
+    /// until the next call.
+    ///
+    /// On invalid UTF-8 nothing is appended, and the error holds the offset
+    /// of the invalid sequence, counted from the first carried byte if any.
+    pub(crate) fn push_bytes(&mut self, bytes: &[u8]) -> Result<(), usize> {
+        let consumed = self.consumed();
+        while let Some(&(_, excess)) = self.widened.front().filter(|&&(index, _)| index < consumed)
+        {
+            self.widened_consumed += usize::from(excess);
+            self.widened.pop_front();
+        }
+
+        let (data_len, pushed, widened_len) = (self.data.len(), self.pushed, self.widened.len());
+        let result = self.decode_bytes(bytes);
+        if result.is_err() {
+            self.data.truncate(data_len);
+            self.pushed = pushed;
+            self.widened.truncate(widened_len);
+        }
+        result
+    }
+
+    fn decode_bytes(&mut self, bytes: &[u8]) -> Result<(), usize> {
+        // Complete the carried character first.
+        let mut head = self.partial;
+        let mut head_len = usize::from(self.partial_len);
+        let carried = head_len;
+        if carried > 0 {
+            while head_len < self.sequence_width(&head[..head_len])
+                && head_len - carried < bytes.len()
+            {
+                head[head_len] = bytes[head_len - carried];
+                head_len += 1;
+            }
+            let complete = head_len == self.sequence_width(&head[..head_len]);
+            if self.sequence_width(&head[..head_len]) == 6 {
+                if !is_cesu8_prefix(&head[..head_len]) {
+                    return Err(0);
+                }
+                if complete {
+                    self.push_widened(decode_cesu8(&head));
+                }
+            } else if self.replace_surrogates && is_surrogate_prefix(&head[..head_len]) {
+                if complete {
+                    self.push_char(char::REPLACEMENT_CHARACTER);
+                }
+            } else {
+                match core::str::from_utf8(&head[..head_len]) {
+                    Ok(s) => self.push(s),
+                    // Still incomplete; all of `bytes` was taken.
+                    Err(err) if err.error_len().is_none() => {}
+                    Err(_) => return Err(0),
+                }
+            }
+            if !complete {
+                self.partial = head;
+                self.partial_len = u8::try_from(head_len).unwrap_or_default();
+                return Ok(());
+            }
+        }
+
+        let mut offset = head_len;
+        let mut rest = &bytes[head_len - carried..];
+        let tail = loop {
+            let err = match core::str::from_utf8(rest) {
+                Ok(s) => {
+                    self.push(s);
+                    break &[][..];
+                }
+                Err(err) => err,
+            };
+            let (valid, tail) = rest.split_at(err.valid_up_to());
+            self.push(core::str::from_utf8(valid).unwrap_or_default());
+            offset += valid.len();
+            if self.sequence_width(tail) == 6 && is_cesu8_prefix(&tail[..tail.len().min(6)]) {
+                if tail.len() < 6 {
+                    break tail;
+                }
+                self.push_widened(decode_cesu8(tail));
+                offset += 6;
+                rest = &tail[6..];
+            } else if self.replace_surrogates && is_surrogate_prefix(&tail[..tail.len().min(3)]) {
+                if tail.len() < 3 {
+                    break tail;
+                }
+                self.push_char(char::REPLACEMENT_CHARACTER);
+                offset += 3;
+                rest = &tail[3..];
+            } else if err.error_len().is_none() {
+                break tail;
+            } else {
+                return Err(offset);
+            }
+        };
+        self.partial[..tail.len()].copy_from_slice(tail);
+        self.partial_len = u8::try_from(tail.len()).unwrap_or_default();
+        Ok(())
+    }
+
+    /// The length of the sequence that starts with `prefix`: at most 4 for a
+    /// UTF-8 character, or 6 for a CESU-8 surrogate pair.
+    fn sequence_width(&self, prefix: &[u8]) -> usize {
+        match prefix {
+            [0xED, 0xA0..=0xAF, ..] if self.cesu8 => 6,
+            [0xC0..=0xDF, ..] => 2,
+            [0xE0..=0xEF, ..] => 3,
+            _ => 4,
         }
     }
 
+    /// Appends the character decoded from a CESU-8 surrogate pair, which is
+    /// two bytes shorter in UTF-8.
+    fn push_widened(&mut self, c: char) {
+        self.widened.push_back((self.pushed, 2));
+        self.push_char(c);
+    }
+
+    /// Replaces a character left incomplete at end of input with U+FFFD, so
+    /// that the lexer reports it.
+    pub(crate) fn finish(&mut self) {
+        if self.partial_len > 3 {
+            self.widened.push_back((self.pushed, self.partial_len - 3));
+        } else if self.partial_len > 0 {
+            self.replaced_excess = 3 - self.partial_len;
+        }
+        if self.partial_len > 0 {
+            self.partial_len = 0;
+            self.push_char(char::REPLACEMENT_CHARACTER);
+        }
+    }
+
+    /// The number of bytes to subtract from the UTF-8 length of the consumed
+    /// characters to get the length of the input they came from: non-zero
+    /// once the U+FFFD added by [`finish`](Self::finish) has been consumed.
+    pub(crate) fn replaced_excess(&self) -> usize {
+        if self.data.is_empty() {
+            usize::from(self.replaced_excess)
+        } else {
+            0
+        }
+    }
+
+    /// The number of bytes to add to the UTF-8 length of the consumed
+    /// characters to get the length of the input they came from, for
+    /// characters decoded from CESU-8.
+    pub(crate) fn widened_excess(&self) -> usize {
+        let consumed = self.consumed();
+        self.widened_consumed
+            + self
+                .widened
+                .iter()
+                .take_while(|&&(index, _)| index < consumed)
+                .map(|&(_, excess)| usize::from(excess))
+                .sum::<usize>()
+    }
+
+    /// The length of the input of the characters appended but not yet
+    /// consumed, not counting carried bytes.
+    pub(crate) fn pending_bytes(&self) -> usize {
+        let consumed = self.consumed();
+        self.data
+            .as_slice()
+            .iter()
+            .map(|c| c.len_utf8())
+            .sum::<usize>()
+            + self
+                .widened
+                .iter()
+                .filter(|&&(index, _)| index >= consumed)
+                .map(|&(_, excess)| usize::from(excess))
+                .sum::<usize>()
+    }
+
+    /// Appends the state of the buffer to a parser checkpoint.
+    pub(crate) fn write_compact(&self, out: &mut Vec<u8>) {
+        write_str(out, &self.pending().collect::<String>());
+        write_varint(out, self.pushed);
+        out.push(self.partial_len);
+        out.extend_from_slice(&self.partial[..usize::from(self.partial_len)]);
+        out.push(self.replaced_excess);
+        write_varint(out, self.widened.len());
+        for &(index, excess) in &self.widened {
+            write_varint(out, index);
+            out.push(excess);
+        }
+        write_varint(out, self.widened_consumed);
+    }
+
+    /// Restores the state written by [`write_compact`](Self::write_compact).
+    pub(crate) fn read_compact(&mut self, reader: &mut Reader<'_>) -> Result<(), CheckpointError> {
+        self.data.clear();
+        self.data.extend(reader.str()?);
+        self.pushed = reader.varint()?;
+        if self.pushed < self.data.len() {
+            return Err(CheckpointError::Invalid);
+        }
+        self.partial_len = reader.byte()?;
+        let partial = reader.bytes(usize::from(self.partial_len))?;
+        self.partial
+            .get_mut(..partial.len())
+            .ok_or(CheckpointError::Invalid)?
+            .copy_from_slice(partial);
+        self.replaced_excess = reader.byte()?;
+        let widened = reader.varint()?;
+        self.widened.clear();
+        for _ in 0..widened {
+            let (index, excess) = (reader.varint()?, reader.byte()?);
+            // Indices ascend, and an input character is at most three bytes
+            // longer than its UTF-8 encoding.
+            if index >= self.pushed
+                || !(1..=3).contains(&excess)
+                || self.widened.back().is_some_and(|&(last, _)| last >= index)
+            {
+                return Err(CheckpointError::Invalid);
+            }
+            self.widened.push_back((index, excess));
+        }
+        self.widened_consumed = reader.varint()?;
+        if self.replaced_excess > 2
+            || self
+                .consumed()
+                .checked_mul(3)
+                .is_none_or(|max| self.widened_consumed > max)
+        {
+            return Err(CheckpointError::Invalid);
+        }
+        Ok(())
+    }
+
+    /// The number of characters consumed so far.
+    pub(crate) fn consumed(&self) -> usize {
+        self.pushed - self.data.len()
+    }
+
+    fn push_char(&mut self, c: char) {
+        self.data.push_back(c);
+        self.pushed += 1;
+    }
+
+    /// Appends input that is already known to be valid UTF-8. Prefer this
+    /// over [`Buffer::push_bytes`] whenever the input is a `&str`.
     pub(crate) fn push(&mut self, text: &str) {
-        // Reserve the byte length as an upper bound on additional chars
-        self.data.reserve(text.len());
-        self.data.extend(text.chars());
+        let len = self.data.len();
+        self.data.extend(text);
+        self.pushed += self.data.len() - len;
+    }
+
+    /// The characters appended but not yet consumed, as a string.
+    #[cfg(test)]
+    pub(crate) fn pending_string(&self) -> String {
+        self.pending().collect()
+    }
+
+    /// The characters appended but not yet consumed, without consuming them.
+    pub(crate) fn pending(&self) -> impl Iterator<Item = char> + '_ {
+        self.data.as_slice().iter().copied()
     }
 
     #[inline(always)]
     pub(crate) fn peek(&self) -> Option<char> {
-        self.data.front().copied()
+        self.data.as_slice().first().copied()
     }
 
     #[inline(always)]
@@ -35,38 +332,39 @@ impl Buffer {
     where
         F: FnMut(char) -> bool,
     {
-        let mut copied = 0;
-        loop {
-            let (front_len, prefix) = {
-                let (front, _) = self.data.as_slices();
-                if front.is_empty() {
-                    break;
-                }
+        let pending = self.data.as_slice();
+        let prefix = pending
+            .iter()
+            .position(|&ch| !predicate(ch))
+            .unwrap_or(pending.len());
+        dst.extend(self.data.take(prefix));
+        prefix
+    }
+}
 
-                let mut prefix = 0;
-                for &ch in front {
-                    if predicate(ch) {
-                        prefix += 1;
-                    } else {
-                        break;
-                    }
-                }
+/// Shows the pending characters as text, cut after the first
+/// `DEBUG_PENDING_CHARS`, rather than as a list of `char`s.
+impl fmt::Debug for Buffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct Pending<'a>(&'a [char]);
 
-                if prefix == 0 {
-                    break;
+        impl fmt::Debug for Pending<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let shown: String = self.0.iter().take(DEBUG_PENDING_CHARS).collect();
+                fmt::Debug::fmt(&shown, f)?;
+                if self.0.len() > DEBUG_PENDING_CHARS {
+                    write!(f, "... ({} chars)", self.0.len())?;
                 }
-
-                (front.len(), prefix)
-            };
-
-            dst.extend(self.data.drain(..prefix));
-            copied += prefix;
-
-            if prefix < front_len {
-                break;
+                Ok(())
             }
         }
-        copied
+
+        f.debug_struct("Buffer")
+            .field("pending", &Pending(self.data.as_slice()))
+            .field("consumed", &self.consumed())
+            .field("partial", &&self.partial[..usize::from(self.partial_len)])
+            .field("cesu8", &self.cesu8)
+            .finish_non_exhaustive()
     }
 }
 
@@ -78,3 +376,185 @@ impl Iterator for Buffer {
         self.consume_char()
     }
 }
+
+/// A first-in, first-out queue of characters kept in one contiguous slice.
+///
+/// Unlike a `VecDeque`, whose contents may wrap around the end of its
+/// allocation, the pending characters are always a single slice, so scans
+/// such as [`Buffer::copy_while`] run over them in one pass. Consumed
+/// characters stay in place until they are as many as the pending ones, and
+/// are then dropped by moving the pending ones to the front, which keeps the
+/// amortized cost per character constant.
+#[derive(Clone, Default)]
+struct CharQueue {
+    chars: Vec<char>,
+    /// The index of the first pending character.
+    head: usize,
+}
+
+impl CharQueue {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.chars.len() - self.head
+    }
+
+    #[inline(always)]
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline(always)]
+    fn as_slice(&self) -> &[char] {
+        &self.chars[self.head..]
+    }
+
+    #[inline(always)]
+    fn pop_front(&mut self) -> Option<char> {
+        let c = *self.chars.get(self.head)?;
+        self.head += 1;
+        Some(c)
+    }
+
+    /// Removes and returns the first `n` pending characters.
+    fn take(&mut self, n: usize) -> &[char] {
+        let start = self.head;
+        self.head += n;
+        &self.chars[start..self.head]
+    }
+
+    fn push_back(&mut self, c: char) {
+        self.compact();
+        self.chars.push(c);
+    }
+
+    fn extend(&mut self, text: &str) {
+        self.compact();
+        // The byte length is an upper bound on the number of characters.
+        self.chars.reserve(text.len());
+        self.chars.extend(text.chars());
+    }
+
+    /// Keeps the first `len` pending characters.
+    fn truncate(&mut self, len: usize) {
+        self.chars.truncate(self.head + len);
+    }
+
+    fn clear(&mut self) {
+        self.chars.clear();
+        self.head = 0;
+    }
+
+    /// Drops the consumed characters once they are as many as the pending
+    /// ones.
+    fn compact(&mut self) {
+        if self.head > 0 && self.head >= self.len() {
+            self.chars.drain(..self.head);
+            self.head = 0;
+        }
+    }
+}
+
+/// Whether `bytes` is a prefix of a CESU-8 surrogate pair: a high surrogate
+/// `ED A0..=AF 80..=BF` followed by a low surrogate `ED B0..=BF 80..=BF`.
+fn is_cesu8_prefix(bytes: &[u8]) -> bool {
+    let ranges = [
+        0xED..=0xED,
+        0xA0..=0xAF,
+        0x80..=0xBF,
+        0xED..=0xED,
+        0xB0..=0xBF,
+        0x80..=0xBF,
+    ];
+    bytes.len() <= 6
+        && bytes
+            .iter()
+            .zip(ranges)
+            .all(|(byte, range)| range.contains(byte))
+}
+
+/// Whether `bytes` is a prefix of a surrogate encoded in the style of UTF-8:
+/// `ED A0..=BF 80..=BF`.
+fn is_surrogate_prefix(bytes: &[u8]) -> bool {
+    let ranges = [0xED..=0xED, 0xA0..=0xBF, 0x80..=0xBF];
+    bytes.len() <= 3
+        && bytes
+            .iter()
+            .zip(ranges)
+            .all(|(byte, range)| range.contains(byte))
+}
+
+/// Decodes a complete CESU-8 surrogate pair.
+fn decode_cesu8(bytes: &[u8]) -> char {
+    let unit = |b: &[u8]| {
+        (u32::from(b[0] & 0x0F) << 12) | (u32::from(b[1] & 0x3F) << 6) | u32::from(b[2] & 0x3F)
+    };
+    let code = 0x10000 + ((unit(&bytes[..3]) - 0xD800) << 10) + (unit(&bytes[3..6]) - 0xDC00);
+    // A valid surrogate pair always decodes to a valid scalar value.
+    char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, string::String};
+
+    use super::{Buffer, CharQueue};
+    use crate::DecodeMode;
+
+    #[test]
+    fn char_queue_compacts_consumed_chars() {
+        let mut queue = CharQueue::default();
+        queue.extend("abcd");
+        assert_eq!(queue.take(3), ['a', 'b', 'c']);
+        queue.push_back('e');
+        // Three consumed and one pending: moved to the front.
+        assert_eq!((queue.head, queue.as_slice()), (0, &['d', 'e'][..]));
+        assert_eq!(queue.pop_front(), Some('d'));
+        queue.extend("fg");
+        assert_eq!((queue.head, queue.as_slice()), (0, &['e', 'f', 'g'][..]));
+        // One consumed and two pending: kept in place.
+        assert_eq!(queue.pop_front(), Some('e'));
+        queue.push_back('h');
+        assert_eq!((queue.head, queue.as_slice()), (1, &['f', 'g', 'h'][..]));
+        queue.truncate(1);
+        assert_eq!(queue.as_slice(), ['f']);
+    }
+
+    #[test]
+    fn debug_shows_pending_text() {
+        let mut buffer = Buffer::new(DecodeMode::StrictUnicode);
+        buffer.push("[\"a\n\", 1]");
+        buffer.push_bytes(&[0xE2, 0x98]).unwrap();
+        assert_eq!(buffer.by_ref().take(3).count(), 3);
+        assert_eq!(buffer.pending_string(), "\n\", 1]");
+        assert_eq!(
+            format!("{buffer:?}"),
+            r#"Buffer { pending: "\n\", 1]", consumed: 3, partial: [226, 152], cesu8: false, .. }"#
+        );
+
+        let mut buffer = Buffer::new(DecodeMode::Cesu8);
+        buffer.push(&"x".repeat(100));
+        let shown: String = "x".repeat(80);
+        assert_eq!(
+            format!("{buffer:?}"),
+            format!(
+                r#"Buffer {{ pending: "{shown}"... (100 chars), consumed: 0, partial: [], cesu8: true, .. }}"#
+            )
+        );
+    }
+
+    #[test]
+    fn rejected_bytes_leave_pushed_text_intact() {
+        let mut buffer = Buffer::new(DecodeMode::StrictUnicode);
+        buffer.push("[\"é");
+        assert_eq!(buffer.push_bytes(&[b'x', 0xFF]), Err(1));
+        buffer.push("\"]");
+        assert_eq!(buffer.pending_string(), "[\"é\"]");
+
+        // Lone surrogates, as found in WTF-8, are only accepted when replaced.
+        let mut buffer = Buffer::new(DecodeMode::ReplaceInvalid);
+        buffer.push("a");
+        buffer.push_bytes(&[0xED, 0xA0, 0x80]).unwrap();
+        buffer.push("b");
+        assert_eq!(buffer.pending_string(), "a\u{FFFD}b");
+    }
+}
@@ -0,0 +1,246 @@
+//! String buffering on top of the core parser.
+//!
+//! The core [`StreamingParser`] is happiest in [`StringValueMode::None`], where
+//! strings are reported as a series of fragments and nothing is retained
+//! between calls. [`JsonModemBuffers`] is the second layer: it runs the core
+//! parser in that mode and coalesces the fragments of each string itself, so
+//! that the `value` of a [`ParseEvent::String`] can carry either the complete
+//! string (once it is final) or the prefix received so far.
+//!
+//! Strings too large to hold in memory, such as base64 blobs, can be routed
+//! to a [`StringSink`] instead with [`JsonModemBuffers::with_string_sink`].
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{BufferOptions, JsonModemBuffers, ParseEvent, ParserOptions, StringValueMode};
+//!
+//! let mut modem = JsonModemBuffers::new(
+//!     ParserOptions::default(),
+//!     BufferOptions {
+//!         string_buffer_mode: StringValueMode::Prefixes,
+//!     },
+//! );
+//!
+//! let mut prefixes = Vec::new();
+//! for chunk in [r#"{"msg": "Hel"#, r#"lo"}"#] {
+//!     for event in modem.feed(chunk) {
+//!         if let ParseEvent::String { value, .. } = event.unwrap() {
+//!             prefixes.push(value.unwrap());
+//!         }
+//!     }
+//! }
+//! assert_eq!(prefixes, ["Hel", "Hello"]);
+//! ```
+use alloc::{rc::Rc, string::String, vec::Vec};
+use core::{cell::RefCell, fmt};
+
+use crate::{
+    ParseEvent, ParserOptions, PathComponent, StdValueFactory, StreamingParser, StringValueMode,
+    error::ParserError,
+    parser::{ClosedStreamingParser, StreamingParserIteratorWith},
+};
+
+/// The `value` of the events of a string routed to a [`StringSink`]: U+FFFC
+/// OBJECT REPLACEMENT CHARACTER.
+pub const STRING_SINK_PLACEHOLDER: &str = "\u{FFFC}";
+
+/// A destination for the contents of a string that should not be buffered.
+pub trait StringSink {
+    /// Receives the next fragment of the string.
+    fn write_fragment(&mut self, fragment: &str);
+}
+
+impl StringSink for String {
+    fn write_fragment(&mut self, fragment: &str) {
+        self.push_str(fragment);
+    }
+}
+
+impl StringSink for Vec<u8> {
+    fn write_fragment(&mut self, fragment: &str) {
+        self.extend_from_slice(fragment.as_bytes());
+    }
+}
+
+/// An event produced by [`JsonModemBuffers`].
+///
+/// The `value` of [`ParseEvent::String`] is filled in according to
+/// [`BufferOptions::string_buffer_mode`].
+pub type BufferedEvent = ParseEvent;
+
+/// Configuration for [`JsonModemBuffers`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BufferOptions {
+    /// Determines what the `value` field of string events carries.
+    ///
+    /// - `None`: the `value` field is always `None`.
+    /// - `Values`: the complete string, on the final fragment only.
+    /// - `Prefixes`: the string received so far, on every fragment.
+    ///
+    /// # Default
+    ///
+    /// `StringValueMode::None`
+    pub string_buffer_mode: StringValueMode,
+}
+
+/// A streaming parser that buffers string fragments per value.
+///
+/// The `string_value_mode` of the [`ParserOptions`] passed to
+/// [`JsonModemBuffers::new`] is ignored: the inner parser always runs in
+/// [`StringValueMode::None`] and buffering is controlled by
+/// [`BufferOptions::string_buffer_mode`] instead.
+#[derive(Debug, Clone)]
+pub struct JsonModemBuffers {
+    parser: StreamingParser,
+    buffers: StringBuffers,
+}
+
+/// A string path whose contents go to a sink.
+#[derive(Clone)]
+struct SinkRoute {
+    path: Vec<PathComponent>,
+    sink: Rc<RefCell<dyn StringSink>>,
+}
+
+impl fmt::Debug for SinkRoute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SinkRoute")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The string currently being assembled. Strings are leaves, so at most one
+/// is in progress at any time.
+#[derive(Debug, Clone, Default)]
+struct StringBuffers {
+    mode: StringValueMode,
+    current: String,
+    sinks: Vec<SinkRoute>,
+}
+
+impl StringBuffers {
+    fn apply(&mut self, mut event: BufferedEvent) -> BufferedEvent {
+        let ParseEvent::String {
+            path,
+            value,
+            fragment,
+            is_final,
+            ..
+        } = &mut event
+        else {
+            return event;
+        };
+        if let Some(route) = self.sinks.iter().find(|route| route.path == *path) {
+            route
+                .sink
+                .borrow_mut()
+                .write_fragment(core::mem::take(fragment).as_str());
+            if self.mode != StringValueMode::None {
+                *value = Some(STRING_SINK_PLACEHOLDER.into());
+            }
+            return event;
+        }
+        match self.mode {
+            StringValueMode::None => {}
+            StringValueMode::Values => {
+                self.current.push_str(fragment);
+                if *is_final {
+                    *value = Some(core::mem::take(&mut self.current));
+                }
+            }
+            StringValueMode::Prefixes => {
+                self.current.push_str(fragment);
+                *value = Some(if *is_final {
+                    core::mem::take(&mut self.current)
+                } else {
+                    self.current.clone()
+                });
+            }
+        }
+        event
+    }
+}
+
+impl JsonModemBuffers {
+    /// Creates a new buffered parser.
+    #[must_use]
+    pub fn new(mut parser_options: ParserOptions, buffer_options: BufferOptions) -> Self {
+        parser_options.string_value_mode = StringValueMode::None;
+        Self {
+            parser: StreamingParser::new(parser_options),
+            buffers: StringBuffers {
+                mode: buffer_options.string_buffer_mode,
+                current: String::new(),
+                sinks: Vec::new(),
+            },
+        }
+    }
+
+    /// Routes the string at `path` to `sink` instead of buffering it.
+    ///
+    /// The fragments of the string are written to the sink as they arrive and
+    /// removed from the events. The `value` of the events is
+    /// [`STRING_SINK_PLACEHOLDER`] unless the buffer mode is
+    /// `StringValueMode::None`. If the path is reached more than once, e.g.
+    /// with `allow_multiple_json_values`, every string there goes to the sink.
+    ///
+    /// The sink is shared, so the caller can keep a handle to it; clones of
+    /// this parser write to the same sink. Values built by the inner parser
+    /// through `non_scalar_values` still contain the whole string.
+    #[must_use]
+    pub fn with_string_sink<S: StringSink + 'static>(
+        mut self,
+        path: Vec<PathComponent>,
+        sink: Rc<RefCell<S>>,
+    ) -> Self {
+        self.buffers.sinks.push(SinkRoute { path, sink });
+        self
+    }
+
+    /// Feeds a chunk of JSON text and returns the events it completes.
+    pub fn feed<'a>(&'a mut self, chunk: &str) -> JsonModemBuffersIter<'a> {
+        JsonModemBuffersIter {
+            events: self.parser.feed(chunk),
+            buffers: &mut self.buffers,
+        }
+    }
+
+    /// Marks the end of input and returns the remaining events.
+    #[must_use]
+    pub fn finish(self) -> ClosedJsonModemBuffers {
+        ClosedJsonModemBuffers {
+            events: self.parser.finish(),
+            buffers: self.buffers,
+        }
+    }
+}
+
+/// Iterator returned by [`JsonModemBuffers::feed`].
+pub struct JsonModemBuffersIter<'a> {
+    events: StreamingParserIteratorWith<'a, StdValueFactory>,
+    buffers: &'a mut StringBuffers,
+}
+
+impl Iterator for JsonModemBuffersIter<'_> {
+    type Item = Result<BufferedEvent, ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.events.next()?.map(|event| self.buffers.apply(event)))
+    }
+}
+
+/// Iterator returned by [`JsonModemBuffers::finish`].
+pub struct ClosedJsonModemBuffers {
+    events: ClosedStreamingParser<StdValueFactory>,
+    buffers: StringBuffers,
+}
+
+impl Iterator for ClosedJsonModemBuffers {
+    type Item = Result<BufferedEvent, ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.events.next()?.map(|event| self.buffers.apply(event)))
+    }
+}
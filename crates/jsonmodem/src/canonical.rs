@@ -0,0 +1,456 @@
+//! Canonical forms of documents.
+//!
+//! [`CanonicalParser`] wraps [`StreamingParser`] with
+//! [`ParserOptions::object_key_sort`] and
+//! [`ParserOptions::allow_duplicate_keys_last_wins_events`] enabled, so that
+//! every object is reported once per key, with its members in sorted order,
+//! regardless of how the input was written. Two documents that are equal as
+//! values produce the same events (up to how strings are split into
+//! fragments).
+//!
+//! [`canonicalize`] writes those events as text in the JSON Canonicalization
+//! Scheme of RFC 8785, so that equal documents are also equal byte for byte,
+//! e.g. for hashing or signing them.
+//!
+//! Objects are buffered until they close: memory use grows with the size of
+//! the largest object, and the members of an object only arrive after its
+//! closing `}` has been read. Arrays and scalars outside of objects are still
+//! streamed as they are parsed.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{CanonicalParser, ParseEvent, ParserOptions, path};
+//!
+//! let mut parser = CanonicalParser::new(ParserOptions::default());
+//! let keys: Vec<_> = parser
+//!     .feed(r#"{"z": 1, "a": 2}"#)
+//!     .filter_map(|event| match event.unwrap() {
+//!         ParseEvent::Number { path, .. } => Some(path),
+//!         _ => None,
+//!     })
+//!     .collect();
+//! assert_eq!(keys, [path!["a"], path!["z"]]);
+//! ```
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+use crate::{
+    DecodeMode, KeySortMode, ParseEvent, ParserError, ParserOptions, PathComponent,
+    StdValueFactory, StreamingParser,
+    parser::{ClosedStreamingParser, StreamingParserIteratorWith},
+    value::{write_jcs_number, write_jcs_string, write_jcs_string_contents},
+};
+
+/// A streaming parser that emits object members sorted by key, keeping only
+/// the last member for each key.
+#[derive(Debug)]
+pub struct CanonicalParser {
+    parser: StreamingParser,
+}
+
+impl CanonicalParser {
+    /// Creates a parser with the given options.
+    ///
+    /// Duplicate keys are always resolved last-wins. `object_key_sort`
+    /// defaults to [`KeySortMode::UnicodeCodePoint`] if it is not set.
+    #[must_use]
+    pub fn new(options: ParserOptions) -> Self {
+        Self {
+            parser: StreamingParser::new(canonical_options(options)),
+        }
+    }
+
+    /// Feeds a chunk of JSON text, returning the events that are ready.
+    pub fn feed<'a>(&'a mut self, text: &str) -> StreamingParserIteratorWith<'a, StdValueFactory> {
+        self.parser.feed(text)
+    }
+
+    /// Marks the end of input and returns the remaining events.
+    #[must_use]
+    pub fn finish(self) -> ClosedStreamingParser<StdValueFactory> {
+        self.parser.finish()
+    }
+}
+
+/// Returns `options` with the settings of [`CanonicalParser`] applied.
+fn canonical_options(options: ParserOptions) -> ParserOptions {
+    let object_key_sort = match options.object_key_sort {
+        KeySortMode::None => KeySortMode::UnicodeCodePoint,
+        mode => mode,
+    };
+    ParserOptions {
+        object_key_sort,
+        allow_duplicate_keys_last_wins_events: true,
+        ..options
+    }
+}
+
+/// Error returned by [`canonicalize`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum CanonicalizeError {
+    /// The document is not valid JSON, or contains an unpaired surrogate
+    /// escape.
+    Parser(ParserError),
+    /// A number is too large to be represented as a finite double, e.g.
+    /// `1e400`.
+    NumberOutOfRange {
+        /// The path to the number.
+        path: Vec<PathComponent>,
+    },
+}
+
+impl fmt::Display for CanonicalizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CanonicalizeError::Parser(err) => err.fmt(f),
+            CanonicalizeError::NumberOutOfRange { .. } => {
+                f.write_str("number is out of the range of a double")
+            }
+        }
+    }
+}
+
+impl core::error::Error for CanonicalizeError {}
+
+impl From<ParserError> for CanonicalizeError {
+    fn from(err: ParserError) -> Self {
+        CanonicalizeError::Parser(err)
+    }
+}
+
+/// Returns the canonical form of `json` under RFC 8785.
+///
+/// The output has no whitespace, object members are sorted by the UTF-16
+/// code units of their keys, numbers are written as the shortest decimal
+/// that reads back as the same double, in the notation of ECMAScript's
+/// `Number.prototype.toString`, and strings escape only what JSON requires,
+/// with the short escapes where there is one. As with [`CanonicalParser`],
+/// the last member wins when an object has duplicate keys.
+///
+/// # Errors
+///
+/// Returns an error if `json` is not one valid document, contains an
+/// unpaired surrogate escape, or contains a number that overflows a double.
+///
+/// # Examples
+///
+/// ```
+/// use jsonmodem::canonicalize;
+///
+/// let json = r#"{"b": [4.50, 1E30, 2e-3], "a": "\u00e9\u000a"}"#;
+/// assert_eq!(canonicalize(json)?, r#"{"a":"é\n","b":[4.5,1e+30,0.002]}"#);
+/// # Ok::<(), jsonmodem::CanonicalizeError>(())
+/// ```
+pub fn canonicalize(json: &str) -> Result<String, CanonicalizeError> {
+    let mut parser = CanonicalParser::new(ParserOptions {
+        object_key_sort: KeySortMode::Utf16CodeUnit,
+        decode_mode: DecodeMode::StrictUnicode,
+        ..ParserOptions::default()
+    });
+    let mut writer = CanonicalWriter::default();
+    for event in parser.feed(json) {
+        writer.write_event(&event?)?;
+    }
+    for event in parser.finish() {
+        writer.write_event(&event?)?;
+    }
+    Ok(writer.out)
+}
+
+/// Writes events as canonical JSON text.
+#[derive(Default)]
+struct CanonicalWriter {
+    out: String,
+    /// The number of values written in each open array or object.
+    frames: Vec<usize>,
+    /// Whether a string is open, i.e. its last fragment was not final.
+    in_string: bool,
+}
+
+impl CanonicalWriter {
+    fn write_event(&mut self, event: &ParseEvent) -> Result<(), CanonicalizeError> {
+        match event {
+            ParseEvent::String {
+                fragment, is_final, ..
+            } if self.in_string => {
+                write_jcs_string_contents(fragment, &mut self.out);
+                if *is_final {
+                    self.out.push('"');
+                    self.in_string = false;
+                }
+                return Ok(());
+            }
+            ParseEvent::ArrayEnd { .. } | ParseEvent::ObjectEnd { .. } => {
+                self.frames.pop();
+                let is_array = matches!(event, ParseEvent::ArrayEnd { .. });
+                self.out.push(if is_array { ']' } else { '}' });
+                return Ok(());
+            }
+            ParseEvent::ScalarBegin { .. }
+            | ParseEvent::ScalarEnd { .. }
+            | ParseEvent::ObjectKey { .. } => return Ok(()),
+            _ => {}
+        }
+
+        self.begin_value(event.path());
+        match event {
+            ParseEvent::Null { .. } => self.out.push_str("null"),
+            ParseEvent::Boolean { value, .. } => {
+                self.out.push_str(if *value { "true" } else { "false" });
+            }
+            ParseEvent::Number { path, value } => {
+                write_jcs_number(*value, &mut self.out)
+                    .map_err(|_| CanonicalizeError::NumberOutOfRange { path: path.clone() })?;
+            }
+            ParseEvent::String {
+                fragment, is_final, ..
+            } => {
+                self.out.push('"');
+                write_jcs_string_contents(fragment, &mut self.out);
+                if *is_final {
+                    self.out.push('"');
+                } else {
+                    self.in_string = true;
+                }
+            }
+            ParseEvent::StringComplete { value, .. } => write_jcs_string(value, &mut self.out),
+            ParseEvent::ArrayStart { .. } => {
+                self.out.push('[');
+                self.frames.push(0);
+            }
+            ParseEvent::ObjectBegin { .. } => {
+                self.out.push('{');
+                self.frames.push(0);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Writes the separator and key before a value at `path`.
+    fn begin_value(&mut self, path: &[PathComponent]) {
+        let Some(len) = self.frames.last_mut() else {
+            return;
+        };
+        if *len > 0 {
+            self.out.push(',');
+        }
+        *len += 1;
+        if let Some(PathComponent::Key(key)) = path.last() {
+            write_jcs_string(key, &mut self.out);
+            self.out.push(':');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        string::{String, ToString},
+        vec::Vec,
+    };
+
+    use super::{CanonicalizeError, canonical_options, canonicalize, write_jcs_number};
+    use crate::{
+        KeySortMode, ParseEvent, ParserOptions, PathComponent, StreamingParser, path, tests::events,
+    };
+
+    /// The events of `json` as `CanonicalParser` reports them.
+    fn canonical_events(json: &str, parts: usize, options: ParserOptions) -> Vec<ParseEvent> {
+        events(json, parts, canonical_options(options))
+    }
+
+    fn paths(events: &[ParseEvent]) -> Vec<Vec<PathComponent>> {
+        events
+            .iter()
+            .filter(|event| {
+                !matches!(
+                    event,
+                    ParseEvent::String {
+                        is_final: false,
+                        ..
+                    }
+                )
+            })
+            .map(|event| event.path().to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn keys_are_sorted() {
+        let events = canonical_events(r#"{"z":1,"a":2}"#, 1, ParserOptions::default());
+        assert_eq!(
+            paths(&events),
+            [Vec::new(), path!["a"], path!["z"], Vec::new()]
+        );
+    }
+
+    #[test]
+    fn nested_objects_are_sorted_and_deduplicated() {
+        let json = r#"[{"b": {"y": [1, {"q": 1, "p": 2}], "x": "long value"}, "a": null, "b": 3}, {"é": 1, "e": 2}]"#;
+        let expected = [
+            Vec::new(),
+            path![0],
+            path![0, "a"],
+            path![0, "b"],
+            path![0],
+            path![1],
+            path![1, "e"],
+            path![1, "é"],
+            path![1],
+            Vec::new(),
+        ];
+        for parts in [1, 7, json.len()] {
+            assert_eq!(
+                paths(&canonical_events(json, parts, ParserOptions::default())),
+                expected
+            );
+        }
+
+        let json = r#"{"b": {"y": [1, {"q": 1, "p": 2}], "x": "s"}}"#;
+        assert_eq!(
+            paths(&canonical_events(json, 3, ParserOptions::default())),
+            [
+                Vec::new(),
+                path!["b"],
+                path!["b", "x"],
+                path!["b", "y"],
+                path!["b", "y", 0],
+                path!["b", "y", 1],
+                path!["b", "y", 1, "p"],
+                path!["b", "y", 1, "q"],
+                path!["b", "y", 1],
+                path!["b", "y"],
+                path!["b"],
+                Vec::new(),
+            ]
+        );
+    }
+
+    #[test]
+    fn equal_documents_give_equal_events() {
+        let a = r#"{"id": 1, "tags": ["x", "y"], "meta": {"k": true, "j": null}}"#;
+        let b = r#"{ "meta": {"j": null, "k": false, "k": true}, "tags": ["x", "y"], "id": 1 }"#;
+        assert_eq!(
+            canonical_events(a, 1, ParserOptions::default()),
+            canonical_events(b, 1, ParserOptions::default())
+        );
+    }
+
+    #[test]
+    fn sorting_without_the_wrapper_keeps_duplicates() {
+        let mut parser = StreamingParser::new(ParserOptions {
+            object_key_sort: KeySortMode::LexicographicAscii,
+            ..Default::default()
+        });
+        let events: Vec<_> = parser
+            .feed(r#"{"b": 1, "a": 2, "b": 3}"#)
+            .map(Result::unwrap)
+            .filter_map(|event| match event {
+                ParseEvent::Number { path, value } => Some((path, value)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            events,
+            [(path!["a"], 2.0), (path!["b"], 1.0), (path!["b"], 3.0)]
+        );
+    }
+
+    #[test]
+    fn canonicalize_rfc_8785_examples() {
+        let json = r#"{
+            "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+            "string": "€$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+            "literals": [null, true, false]
+        }"#;
+        assert_eq!(
+            canonicalize(json).unwrap(),
+            concat!(
+                r#"{"literals":[null,true,false],"#,
+                r#""numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"#,
+                r#""string":"€$\u000f\nA'B\"\\\\\"/"}"#
+            )
+        );
+
+        let json = r#"{
+            "€": "Euro Sign",
+            "\r": "Carriage Return",
+            "\ufb33": "Hebrew Letter Dalet With Dagesh",
+            "1": "One",
+            "😀": "Emoji: Grinning Face",
+            "\u0080": "Control",
+            "ö": "Latin Small Letter O With Diaeresis"
+        }"#;
+        let values: Vec<_> = canonicalize(json)
+            .unwrap()
+            .split(',')
+            .map(|member| member.split_once(':').unwrap().1.to_string())
+            .collect();
+        assert_eq!(
+            values,
+            [
+                r#""Carriage Return""#,
+                r#""One""#,
+                r#""Control""#,
+                r#""Latin Small Letter O With Diaeresis""#,
+                r#""Euro Sign""#,
+                r#""Emoji: Grinning Face""#,
+                r#""Hebrew Letter Dalet With Dagesh"}"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn numbers_match_rfc_8785_appendix_b() {
+        for (bits, expected) in [
+            (0x0000_0000_0000_0000, "0"),
+            (0x8000_0000_0000_0000, "0"),
+            (0x0000_0000_0000_0001, "5e-324"),
+            (0x8000_0000_0000_0001, "-5e-324"),
+            (0x7fef_ffff_ffff_ffff, "1.7976931348623157e+308"),
+            (0xffef_ffff_ffff_ffff, "-1.7976931348623157e+308"),
+            (0x4340_0000_0000_0000, "9007199254740992"),
+            (0xc340_0000_0000_0000, "-9007199254740992"),
+            (0x4430_0000_0000_0000, "295147905179352830000"),
+            (0x44b5_2d02_c7e1_4af5, "9.999999999999997e+22"),
+            (0x44b5_2d02_c7e1_4af6, "1e+23"),
+            (0x44b5_2d02_c7e1_4af7, "1.0000000000000001e+23"),
+            (0x444b_1ae4_d6e2_ef4e, "999999999999999700000"),
+            (0x444b_1ae4_d6e2_ef4f, "999999999999999900000"),
+            (0x444b_1ae4_d6e2_ef50, "1e+21"),
+            (0x3eb0_c6f7_a0b5_ed8c, "9.999999999999997e-7"),
+            (0x3eb0_c6f7_a0b5_ed8d, "0.000001"),
+            (0x41b3_de43_5555_5553, "333333333.3333332"),
+            (0x41b3_de43_5555_5554, "333333333.33333325"),
+            (0x41b3_de43_5555_5555, "333333333.3333333"),
+            (0x41b3_de43_5555_5556, "333333333.3333334"),
+            (0x41b3_de43_5555_5557, "333333333.33333343"),
+            (0xbecb_f647_612f_3696, "-0.0000033333333333333333"),
+            (0x4314_3ff3_c1cb_0959, "1424953923781206.2"),
+        ] {
+            let mut out = String::new();
+            write_jcs_number(f64::from_bits(bits), &mut out).unwrap();
+            assert_eq!(out, expected, "{bits:#x}");
+        }
+    }
+
+    #[test]
+    fn canonicalize_errors() {
+        assert_eq!(
+            canonicalize(r#"{"a": [1e400]}"#),
+            Err(CanonicalizeError::NumberOutOfRange {
+                path: path!["a", 0]
+            })
+        );
+        assert!(matches!(
+            canonicalize(r#""\ud800""#),
+            Err(CanonicalizeError::Parser(_))
+        ));
+        assert!(matches!(
+            canonicalize("[1] [2]"),
+            Err(CanonicalizeError::Parser(_))
+        ));
+    }
+}
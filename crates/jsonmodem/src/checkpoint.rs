@@ -0,0 +1,307 @@
+//! Compact binary checkpoints of the parser state.
+//!
+//! A long-lived stream, e.g. a model response relayed over a connection that
+//! may drop, can be resumed from where it stopped if the parser state is
+//! saved along with it. [`StreamingParser::serialize_compact`] writes the
+//! state of the lexer and parser to a few bytes: positions and counters as
+//! varints, the state machines as small integers, and the buffered input and
+//! scratch string length-prefixed. [`StreamingParser::deserialize_compact`]
+//! restores a parser that produces the same events as the original.
+//!
+//! A checkpoint does not hold the parser options; restore it with the options
+//! the original parser was created with. Partially built values and held-back
+//! object members are not saved either, so a parser that has them, or has
+//! events not yet pulled, cannot be checkpointed.
+//!
+//! [`StreamingParser::serialize_compact`]: crate::StreamingParserImpl::serialize_compact
+//! [`StreamingParser::deserialize_compact`]: crate::StreamingParserImpl::deserialize_compact
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{ParserOptions, StreamingParser};
+//!
+//! let mut parser = StreamingParser::new(ParserOptions::default());
+//! let mut events: Vec<_> = parser.feed(r#"{"text": "hel"#).collect::<Result<_, _>>()?;
+//! let checkpoint = parser.serialize_compact()?;
+//!
+//! let mut restored = StreamingParser::deserialize_compact(ParserOptions::default(), &checkpoint)?;
+//! events.extend(restored.feed(r#"lo"}"#).collect::<Result<Vec<_>, _>>()?);
+//! assert_eq!(events.len(), 4);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+/// Error returned when a parser checkpoint cannot be written or read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckpointError {
+    /// The parser holds state that checkpoints do not capture: events not yet
+    /// pulled, a value partially built for `non_scalar_values`, or object
+    /// members held back by `allow_duplicate_keys_last_wins_events` or
+    /// `object_key_sort`.
+    Unsupported,
+    /// The bytes are not a checkpoint, or were truncated.
+    Invalid,
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckpointError::Unsupported => {
+                f.write_str("parser state cannot be checkpointed at this point")
+            }
+            CheckpointError::Invalid => f.write_str("invalid parser checkpoint"),
+        }
+    }
+}
+
+impl core::error::Error for CheckpointError {}
+
+/// The first byte of every checkpoint, changed whenever the format changes.
+pub(crate) const FORMAT_VERSION: u8 = 1;
+
+/// Appends `n` as an unsigned LEB128 varint.
+pub(crate) fn write_varint(out: &mut Vec<u8>, n: usize) {
+    let mut n = n as u64;
+    loop {
+        let byte = (n & 0x7f) as u8;
+        n >>= 7;
+        if n == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+/// Appends `s` prefixed with its length in bytes.
+pub(crate) fn write_str(out: &mut Vec<u8>, s: &str) {
+    write_varint(out, s.len());
+    out.extend_from_slice(s.as_bytes());
+}
+
+/// Reads the values written by the `write_` functions.
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    pub(crate) fn byte(&mut self) -> Result<u8, CheckpointError> {
+        let (&byte, rest) = self.bytes.split_first().ok_or(CheckpointError::Invalid)?;
+        self.bytes = rest;
+        Ok(byte)
+    }
+
+    pub(crate) fn varint(&mut self) -> Result<usize, CheckpointError> {
+        let mut n: u64 = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            n |= u64::from(byte & 0x7f)
+                .checked_shl(shift)
+                .filter(|bits| bits >> shift == u64::from(byte & 0x7f))
+                .ok_or(CheckpointError::Invalid)?;
+            if byte & 0x80 == 0 {
+                return usize::try_from(n).map_err(|_| CheckpointError::Invalid);
+            }
+        }
+        Err(CheckpointError::Invalid)
+    }
+
+    pub(crate) fn bytes(&mut self, len: usize) -> Result<&'a [u8], CheckpointError> {
+        if len > self.bytes.len() {
+            return Err(CheckpointError::Invalid);
+        }
+        let (bytes, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(bytes)
+    }
+
+    pub(crate) fn str(&mut self) -> Result<&'a str, CheckpointError> {
+        let len = self.varint()?;
+        core::str::from_utf8(self.bytes(len)?).map_err(|_| CheckpointError::Invalid)
+    }
+
+    pub(crate) fn string(&mut self) -> Result<String, CheckpointError> {
+        self.str().map(String::from)
+    }
+
+    /// Checks that every byte was read.
+    pub(crate) fn finish(self) -> Result<(), CheckpointError> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(CheckpointError::Invalid)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::{CheckpointError, Reader, write_varint};
+    use crate::{
+        KeySortMode, NonScalarValueMode, ParseEvent, ParserOptions, StreamingParser,
+        StringValueMode,
+    };
+
+    const JSON: &str =
+        r#"{"a": [1, -2.5e3, true, null], "bé": "x\"y😀z", "c": {"d": []}, "é": "☕"}"#;
+
+    fn drain(
+        events: impl Iterator<Item = Result<ParseEvent, crate::ParserError>>,
+    ) -> Vec<ParseEvent> {
+        events.map(Result::unwrap).collect()
+    }
+
+    #[test]
+    fn varints_round_trip() {
+        for n in [0, 1, 127, 128, 300, 1 << 35, usize::MAX] {
+            let mut out = Vec::new();
+            write_varint(&mut out, n);
+            let mut reader = Reader::new(&out);
+            assert_eq!(reader.varint(), Ok(n));
+            assert_eq!(reader.finish(), Ok(()));
+        }
+        assert_eq!(
+            Reader::new(&[0xff; 11]).varint(),
+            Err(CheckpointError::Invalid)
+        );
+    }
+
+    /// Checkpoints after every prefix of `JSON`, including mid-string,
+    /// mid-escape and mid-number, and checks that the restored parser
+    /// produces the same events as one that was never interrupted.
+    #[test]
+    fn restored_parser_produces_identical_events() {
+        let all_options = [
+            ParserOptions::default(),
+            ParserOptions {
+                string_value_mode: StringValueMode::Prefixes,
+                ..Default::default()
+            },
+            ParserOptions {
+                string_value_mode: StringValueMode::Values,
+                scalar_boundary_events: true,
+                emit_key_events: true,
+                ..Default::default()
+            },
+        ];
+        let splits = (0..=JSON.len()).filter(|&i| JSON.is_char_boundary(i));
+        for (options, split) in all_options
+            .into_iter()
+            .flat_map(|options| splits.clone().map(move |split| (options, split)))
+        {
+            let (head, tail) = JSON.split_at(split);
+            let mut original = StreamingParser::new(options);
+            let mut events = drain(original.feed(head));
+            let checkpoint = original.serialize_compact().unwrap();
+            let mut restored = StreamingParser::deserialize_compact(options, &checkpoint).unwrap();
+            assert_eq!(restored.bytes_consumed(), original.bytes_consumed());
+
+            let mut expected = events.clone();
+            expected.extend(drain(original.feed(tail)));
+            expected.extend(drain(original.finish()));
+            events.extend(drain(restored.feed(tail)));
+            events.extend(drain(restored.finish()));
+            assert_eq!(events, expected, "split at {split}");
+        }
+    }
+
+    #[test]
+    fn split_characters_are_restored() {
+        let bytes = "[\"é☕\"]".as_bytes();
+        let mut original = StreamingParser::new(ParserOptions::default());
+        let mut events = drain(original.feed_bytes(&bytes[..3]).unwrap());
+        let checkpoint = original.serialize_compact().unwrap();
+        let mut restored =
+            StreamingParser::deserialize_compact(ParserOptions::default(), &checkpoint).unwrap();
+        events.extend(drain(restored.feed_bytes(&bytes[3..]).unwrap()));
+        events.extend(drain(restored.finish()));
+        assert!(matches!(&events[1], ParseEvent::String { fragment, .. } if fragment == "é☕"));
+    }
+
+    #[test]
+    fn unsupported_and_invalid_states() {
+        // Input fed but not yet parsed is kept.
+        let mut parser = StreamingParser::new(ParserOptions::default());
+        let _ = parser.feed("[1, 2]");
+        let checkpoint = parser.serialize_compact().unwrap();
+        let restored =
+            StreamingParser::deserialize_compact(ParserOptions::default(), &checkpoint).unwrap();
+        assert_eq!(drain(restored.finish()).len(), 4);
+
+        // A partially built value.
+        let mut parser = StreamingParser::new(ParserOptions {
+            non_scalar_values: NonScalarValueMode::Roots,
+            ..Default::default()
+        });
+        parser.feed("[1, ").for_each(drop);
+        assert_eq!(
+            parser.serialize_compact(),
+            Err(CheckpointError::Unsupported)
+        );
+
+        // Held-back object members.
+        let mut parser = StreamingParser::new(ParserOptions {
+            object_key_sort: KeySortMode::LexicographicAscii,
+            ..Default::default()
+        });
+        parser.feed(r#"{"b": 1, "#).for_each(drop);
+        assert_eq!(
+            parser.serialize_compact(),
+            Err(CheckpointError::Unsupported)
+        );
+
+        let mut parser = StreamingParser::new(ParserOptions::default());
+        parser.feed("[1, ").for_each(drop);
+        let checkpoint = parser.serialize_compact().unwrap();
+        for len in 0..checkpoint.len() {
+            assert!(
+                StreamingParser::deserialize_compact(ParserOptions::default(), &checkpoint[..len])
+                    .is_err()
+            );
+        }
+        let mut trailing = checkpoint.clone();
+        trailing.push(0);
+        assert!(StreamingParser::deserialize_compact(ParserOptions::default(), &trailing).is_err());
+    }
+
+    /// Replaces each counter of a checkpoint with one too large for the
+    /// input it has seen, which must be rejected rather than trusted.
+    #[test]
+    fn hostile_counters_are_invalid() {
+        let mut parser = StreamingParser::new(ParserOptions::default());
+        parser.feed(r#"[1, "ab"#).for_each(drop);
+        let checkpoint = parser.serialize_compact().unwrap();
+        // The version and flags are followed by nine counters.
+        let mut reader = Reader::new(&checkpoint[2..]);
+        let counters: Vec<usize> = (0..9).map(|_| reader.varint().unwrap()).collect();
+        let rest = reader.bytes;
+        let restore = |counters: &[usize]| {
+            let mut bytes = checkpoint[..2].to_vec();
+            for &n in counters {
+                write_varint(&mut bytes, n);
+            }
+            bytes.extend_from_slice(rest);
+            StreamingParser::deserialize_compact(ParserOptions::default(), &bytes)
+        };
+        assert!(restore(&counters).is_ok());
+        for i in 0..counters.len() {
+            for n in [usize::MAX / 2, usize::MAX] {
+                let mut hostile = counters.clone();
+                hostile[i] = n;
+                assert_eq!(
+                    restore(&hostile).err(),
+                    Some(CheckpointError::Invalid),
+                    "counter {i}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,220 @@
+//! Joining the fragments of a string into one event.
+//!
+//! The parser emits a string as soon as part of it is available, so a string
+//! split across chunks arrives as several `String` events, and a consumer
+//! that only wants whole strings has to accumulate them itself.
+//! [`StringCoalescingAdapter`] does that accumulation: it wraps an iterator of
+//! events and replaces the fragments of each such string by a single
+//! [`ParseEvent::StringComplete`] once its final fragment arrives. A string
+//! delivered in a single fragment is passed through unchanged.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{ParseEvent, ParserOptions, StreamingParser, StringCoalescingAdapter, path};
+//!
+//! let mut parser = StreamingParser::new(ParserOptions::default());
+//! let mut events = Vec::new();
+//! for chunk in [r#"["hel"#, r#"lo", "world"]"#] {
+//!     events.extend(parser.feed(chunk));
+//! }
+//! let events: Vec<_> = StringCoalescingAdapter::new(events.into_iter())
+//!     .collect::<Result<_, _>>()
+//!     .unwrap();
+//! assert_eq!(
+//!     events[1],
+//!     ParseEvent::StringComplete {
+//!         path: path![0],
+//!         value: "hello".into(),
+//!     }
+//! );
+//! assert!(matches!(
+//!     events[2],
+//!     ParseEvent::String { is_final: true, .. }
+//! ));
+//! ```
+use alloc::string::String;
+
+use crate::ParseEvent;
+
+/// Replaces the `String` fragments of each string by one
+/// [`ParseEvent::StringComplete`] event.
+///
+/// Errors and all other events are passed through in order. Memory use is
+/// proportional to the longest string split across fragments.
+#[derive(Debug, Clone)]
+pub struct StringCoalescingAdapter<I> {
+    inner: I,
+    /// The fragments so far of a string not yet complete.
+    pending: Option<String>,
+}
+
+impl<I> StringCoalescingAdapter<I> {
+    /// Wraps an iterator of events.
+    pub fn new(inner: I) -> Self {
+        Self {
+            inner,
+            pending: None,
+        }
+    }
+
+    /// Returns the wrapped iterator, dropping any incomplete string.
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+}
+
+impl<I, E> Iterator for StringCoalescingAdapter<I>
+where
+    I: Iterator<Item = Result<ParseEvent, E>>,
+{
+    type Item = Result<ParseEvent, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (path, value, fragment, is_final) = match self.inner.next()? {
+                Ok(ParseEvent::String {
+                    path,
+                    value,
+                    fragment,
+                    is_final,
+                }) => (path, value, fragment, is_final),
+                other => return Some(other),
+            };
+            match (self.pending.take(), is_final) {
+                (None, true) => {
+                    return Some(Ok(ParseEvent::String {
+                        path,
+                        value,
+                        fragment,
+                        is_final,
+                    }));
+                }
+                (None, false) => self.pending = Some(fragment),
+                (Some(mut string), _) => {
+                    string.push_str(&fragment);
+                    if is_final {
+                        return Some(Ok(ParseEvent::StringComplete {
+                            path,
+                            value: string,
+                        }));
+                    }
+                    self.pending = Some(string);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::StringCoalescingAdapter;
+    use crate::{
+        ParseEvent, ParserError, ParserOptions, StreamingParser, StringValueMode, path,
+        produce_chunks,
+    };
+
+    const JSON: &str = r#"{"a": "héllo wörld", "b": ["", "x", "long ☕ string"], "c": 1}"#;
+
+    fn coalesce(chunks: &[&str], mode: StringValueMode) -> Vec<ParseEvent> {
+        let mut parser = StreamingParser::new(ParserOptions {
+            string_value_mode: mode,
+            ..Default::default()
+        });
+        let mut events = Vec::new();
+        for chunk in chunks {
+            events.extend(parser.feed(chunk));
+        }
+        events.extend(parser.finish());
+        StringCoalescingAdapter::new(events.into_iter())
+            .collect::<Result<_, ParserError>>()
+            .unwrap()
+    }
+
+    /// The path and value of every string, whether whole or coalesced.
+    fn strings(events: &[ParseEvent]) -> Vec<(Vec<crate::PathComponent>, &str)> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                ParseEvent::String {
+                    path,
+                    fragment,
+                    is_final: true,
+                    ..
+                }
+                | ParseEvent::StringComplete {
+                    path,
+                    value: fragment,
+                } => Some((path.clone(), fragment.as_str())),
+                ParseEvent::String { .. } => panic!("fragment not coalesced: {event:?}"),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn strings_are_whole_in_any_chunking() {
+        let expected = [
+            (path!["a"], "héllo wörld"),
+            (path!["b", 0], ""),
+            (path!["b", 1], "x"),
+            (path!["b", 2], "long ☕ string"),
+        ];
+        for mode in [
+            StringValueMode::None,
+            StringValueMode::Values,
+            StringValueMode::Prefixes,
+        ] {
+            for parts in [1, 5, JSON.len()] {
+                let chunks = produce_chunks(JSON, parts);
+                let events = coalesce(&chunks, mode);
+                assert_eq!(strings(&events), expected, "{mode:?} {parts}");
+                assert_eq!(events.len(), 9, "{mode:?} {parts}");
+            }
+        }
+    }
+
+    #[test]
+    fn single_fragments_pass_through() {
+        let events = coalesce(&[JSON], StringValueMode::Values);
+        assert!(
+            !events
+                .iter()
+                .any(|event| matches!(event, ParseEvent::StringComplete { .. }))
+        );
+        assert!(events.contains(&ParseEvent::String {
+            path: path!["a"],
+            value: Some("héllo wörld".into()),
+            fragment: "héllo wörld".into(),
+            is_final: true,
+        }));
+
+        let events = coalesce(&[r#"["ab"#, r#"c"]"#], StringValueMode::None);
+        assert_eq!(
+            events[1],
+            ParseEvent::StringComplete {
+                path: path![0],
+                value: "abc".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn errors_pass_through() {
+        let mut parser = StreamingParser::new(ParserOptions::default());
+        let mut events: Vec<_> = parser.feed(r#"["ab"#).collect();
+        events.extend(parser.feed("\"}"));
+        let mut adapter = StringCoalescingAdapter::new(events.into_iter());
+        assert!(matches!(
+            adapter.next(),
+            Some(Ok(ParseEvent::ArrayStart { .. }))
+        ));
+        assert!(matches!(
+            adapter.next(),
+            Some(Ok(ParseEvent::StringComplete { .. }))
+        ));
+        assert!(matches!(adapter.next(), Some(Err(_))));
+    }
+}
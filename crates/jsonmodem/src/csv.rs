@@ -0,0 +1,562 @@
+//! Conversion between CSV and streams of flat JSON objects.
+//!
+//! [`CsvEmitter`] consumes the events of a root array whose elements are
+//! objects with scalar members, and writes one CSV line per element as soon
+//! as the element's closing `}` has been parsed.
+//!
+//! The columns are either given up front or taken from the keys of the first
+//! element, in document order. Every row has exactly one field per column:
+//! a column missing from an element is written as an empty field, and members
+//! whose key is not a column are ignored. `null` is written as an empty field,
+//! and fields containing a comma, quote or line break are quoted.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{CsvEmitter, ParserOptions, StreamingParser};
+//!
+//! let mut parser = StreamingParser::new(ParserOptions::default());
+//! let mut csv = CsvEmitter::new(String::new(), None);
+//! for event in parser.feed(r#"[{"a":1,"b":"x"},{"a":2,"b":"y,z"}]"#) {
+//!     csv.write_event(&event.unwrap())?;
+//! }
+//! assert_eq!(csv.into_inner(), "a,b\n1,x\n2,\"y,z\"\n");
+//! # Ok::<(), jsonmodem::CsvError>(())
+//! ```
+//!
+//! In the other direction, [`CsvToJsonLines`] reads CSV as defined by RFC 4180
+//! in chunks of bytes and writes one JSON object per record, one per line, with
+//! the keys taken from the header record. Fields are strings, or, with
+//! `infer_types`, numbers if they are written as JSON numbers. A record with
+//! fewer fields than the header has `null` for the missing ones, and fields
+//! beyond the header are ignored. Records end with `\n` or `\r\n`, and blank
+//! lines are skipped.
+//!
+//! ```
+//! use jsonmodem::CsvToJsonLines;
+//!
+//! let mut converter = CsvToJsonLines::new(String::new(), true);
+//! converter.feed_chunk(b"id,name\n1,\"Smith, J\"\n2")?;
+//! converter.feed_chunk(b",\"say \"\"hi\"\"\"\n")?;
+//! assert_eq!(
+//!     converter.finish()?,
+//!     "{\"id\":1,\"name\":\"Smith, J\"}\n{\"id\":2,\"name\":\"say \\\"hi\\\"\"}\n"
+//! );
+//! # Ok::<(), jsonmodem::CsvError>(())
+//! ```
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::{JsonValue, ParseEvent, ParseEventWriter, PathComponent, value::write_escaped_string};
+
+/// Error returned by [`CsvEmitter`] and [`CsvToJsonLines`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum CsvError {
+    /// The root value is not an array, or an element is not an object.
+    NotAnArrayOfObjects,
+    /// A member of an element is an array or object.
+    NestedValue(Vec<PathComponent>),
+    /// A CSV field is not valid UTF-8. The record number counts from 1 for
+    /// the header.
+    InvalidUtf8 {
+        /// The record holding the field.
+        record: usize,
+    },
+    /// The CSV input ended inside a quoted field.
+    UnterminatedQuote,
+    /// The underlying writer failed.
+    Write(fmt::Error),
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvError::NotAnArrayOfObjects => f.write_str("expected an array of objects"),
+            CsvError::NestedValue(path) => write!(f, "nested value at {path:?}"),
+            CsvError::InvalidUtf8 { record } => write!(f, "invalid UTF-8 in record {record}"),
+            CsvError::UnterminatedQuote => f.write_str("unterminated quoted field"),
+            CsvError::Write(err) => err.fmt(f),
+        }
+    }
+}
+
+impl core::error::Error for CsvError {}
+
+impl From<fmt::Error> for CsvError {
+    fn from(err: fmt::Error) -> Self {
+        CsvError::Write(err)
+    }
+}
+
+/// Writes CSV rows for a streamed array of flat objects.
+#[derive(Debug)]
+pub struct CsvEmitter<W: fmt::Write> {
+    writer: W,
+    /// The columns, once known.
+    header: Option<Vec<String>>,
+    header_written: bool,
+    /// Members of the current element, in document order.
+    row: Vec<(String, String)>,
+    string: String,
+}
+
+impl<W: fmt::Write> CsvEmitter<W> {
+    /// Creates an emitter writing to `writer`.
+    ///
+    /// If `header` is `None`, the columns are the keys of the first element.
+    pub fn new(writer: W, header: Option<Vec<String>>) -> Self {
+        Self {
+            writer,
+            header,
+            header_written: false,
+            row: Vec::new(),
+            string: String::new(),
+        }
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Processes one event, writing the header and a row when an element
+    /// closes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the events do not describe an array of flat
+    /// objects, or if the writer fails.
+    pub fn write_event<V>(&mut self, event: &ParseEvent<V>) -> Result<(), CsvError>
+    where
+        V: JsonValue,
+        V::Str: AsRef<str>,
+        V::Num: Into<f64>,
+        V::Bool: Into<bool>,
+    {
+        let path = event.path();
+        match (event, path.len()) {
+            (ParseEvent::ArrayStart { .. } | ParseEvent::ArrayEnd { .. }, 0)
+            | (ParseEvent::ObjectBegin { .. }, 1) => Ok(()),
+            (ParseEvent::ObjectEnd { .. }, 1) => self.write_row(),
+            (ParseEvent::ArrayStart { .. } | ParseEvent::ObjectBegin { .. }, 2) => {
+                Err(CsvError::NestedValue(path.to_vec()))
+            }
+            (_, 0 | 1) => Err(CsvError::NotAnArrayOfObjects),
+            (ParseEvent::Null { .. }, 2) => {
+                self.field(path, String::new());
+                Ok(())
+            }
+            (ParseEvent::Boolean { value, .. }, 2) => {
+                self.field(path, (*value).into().to_string());
+                Ok(())
+            }
+            (ParseEvent::Number { value, .. }, 2) => {
+                let n: f64 = (*value).into();
+                self.field(path, n.to_string());
+                Ok(())
+            }
+            (
+                ParseEvent::String {
+                    fragment, is_final, ..
+                },
+                2,
+            ) => {
+                self.string.push_str(fragment.as_ref());
+                if *is_final {
+                    let text = core::mem::take(&mut self.string);
+                    self.field(path, text);
+                }
+                Ok(())
+            }
+            (ParseEvent::StringComplete { value, .. }, 2) => {
+                self.field(path, String::from(value.as_ref()));
+                Ok(())
+            }
+            // Everything deeper is inside a nested value, already reported.
+            _ => Ok(()),
+        }
+    }
+
+    fn field(&mut self, path: &[PathComponent], text: String) {
+        if let Some(PathComponent::Key(key)) = path.last() {
+            self.row.push((key.to_string(), text));
+        }
+    }
+
+    fn write_row(&mut self) -> Result<(), CsvError> {
+        let row = core::mem::take(&mut self.row);
+        let header = self
+            .header
+            .get_or_insert_with(|| row.iter().map(|(key, _)| key.clone()).collect());
+        if !self.header_written {
+            self.header_written = true;
+            write_line(&mut self.writer, header.iter().map(String::as_str))?;
+        }
+        // With duplicate keys the last member wins, as in a built value.
+        let fields = header.iter().map(|column| {
+            row.iter()
+                .rev()
+                .find(|(key, _)| key == column)
+                .map_or("", |(_, text)| text.as_str())
+        });
+        write_line(&mut self.writer, fields)?;
+        Ok(())
+    }
+}
+
+impl<V, W> ParseEventWriter<V> for CsvEmitter<W>
+where
+    V: JsonValue,
+    V::Str: AsRef<str>,
+    V::Num: Into<f64>,
+    V::Bool: Into<bool>,
+    W: fmt::Write,
+{
+    type Error = CsvError;
+
+    fn write_event(&mut self, event: ParseEvent<V>) -> Result<(), Self::Error> {
+        CsvEmitter::write_event(self, &event)
+    }
+}
+
+fn write_line<'a, W: fmt::Write>(
+    writer: &mut W,
+    fields: impl Iterator<Item = &'a str>,
+) -> fmt::Result {
+    for (i, field) in fields.enumerate() {
+        if i > 0 {
+            writer.write_char(',')?;
+        }
+        if field.contains([',', '"', '\n', '\r']) {
+            writer.write_char('"')?;
+            for c in field.chars() {
+                if c == '"' {
+                    writer.write_char('"')?;
+                }
+                writer.write_char(c)?;
+            }
+            writer.write_char('"')?;
+        } else {
+            writer.write_str(field)?;
+        }
+    }
+    writer.write_char('\n')
+}
+
+/// Where a [`CsvToJsonLines`] is within a record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CsvState {
+    /// At the start of a field.
+    FieldStart,
+    /// In a field without quotes.
+    Unquoted,
+    /// In a quoted field.
+    Quoted,
+    /// After a `"` in a quoted field, which either closes it or is the first
+    /// of a doubled `""`.
+    QuoteInQuoted,
+    /// After the `\r` ending a record, which a `\n` may follow.
+    AfterCr,
+}
+
+/// Converts CSV with a header record to newline-delimited JSON objects.
+#[derive(Debug)]
+pub struct CsvToJsonLines<W: fmt::Write> {
+    writer: W,
+    infer_types: bool,
+    state: CsvState,
+    header: Option<Vec<String>>,
+    /// The fields of the current record.
+    record: Vec<String>,
+    /// The bytes of the current field, which may end partway through a
+    /// character until the field is complete.
+    field: Vec<u8>,
+    records: usize,
+}
+
+impl<W: fmt::Write> CsvToJsonLines<W> {
+    /// Creates a converter writing to `writer`.
+    ///
+    /// With `infer_types`, fields written as JSON numbers, e.g. `-1.5e3`, are
+    /// converted to numbers rather than strings.
+    pub fn new(writer: W, infer_types: bool) -> Self {
+        Self {
+            writer,
+            infer_types,
+            state: CsvState::FieldStart,
+            header: None,
+            record: Vec::new(),
+            field: Vec::new(),
+            records: 0,
+        }
+    }
+
+    /// Processes a chunk of CSV, writing a line for each record it completes.
+    /// Chunks may split records, fields and characters anywhere.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a field is not valid UTF-8, or if the writer
+    /// fails. The converter should not be used after an error.
+    pub fn feed_chunk(&mut self, bytes: &[u8]) -> Result<(), CsvError> {
+        for &byte in bytes {
+            self.state = match (self.state, byte) {
+                (CsvState::AfterCr, b'\n') => CsvState::FieldStart,
+                (CsvState::FieldStart | CsvState::AfterCr, b'"') => CsvState::Quoted,
+                (CsvState::Quoted, b'"') => CsvState::QuoteInQuoted,
+                (CsvState::Quoted, _) | (CsvState::QuoteInQuoted, b'"') => {
+                    self.field.push(byte);
+                    CsvState::Quoted
+                }
+                (_, b',') => {
+                    self.end_field()?;
+                    CsvState::FieldStart
+                }
+                (_, b'\r' | b'\n') => {
+                    self.end_record()?;
+                    if byte == b'\r' {
+                        CsvState::AfterCr
+                    } else {
+                        CsvState::FieldStart
+                    }
+                }
+                // Text after a closing quote is kept, as most readers do.
+                _ => {
+                    self.field.push(byte);
+                    CsvState::Unquoted
+                }
+            };
+        }
+        Ok(())
+    }
+
+    /// Ends the input, writing the last record if it has no line ending, and
+    /// returns the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input ends inside a quoted field, if a field
+    /// is not valid UTF-8, or if the writer fails.
+    pub fn finish(mut self) -> Result<W, CsvError> {
+        if self.state == CsvState::Quoted {
+            return Err(CsvError::UnterminatedQuote);
+        }
+        self.end_record()?;
+        Ok(self.writer)
+    }
+
+    fn end_field(&mut self) -> Result<(), CsvError> {
+        let field = String::from_utf8(core::mem::take(&mut self.field)).map_err(|_| {
+            CsvError::InvalidUtf8 {
+                record: self.records + 1,
+            }
+        })?;
+        self.record.push(field);
+        Ok(())
+    }
+
+    fn end_record(&mut self) -> Result<(), CsvError> {
+        let blank = matches!(self.state, CsvState::FieldStart | CsvState::AfterCr)
+            && self.record.is_empty();
+        if blank {
+            return Ok(());
+        }
+        self.end_field()?;
+        self.records += 1;
+        let record = core::mem::take(&mut self.record);
+        let Some(header) = &self.header else {
+            self.header = Some(record);
+            return Ok(());
+        };
+        let w = &mut self.writer;
+        w.write_char('{')?;
+        for (i, key) in header.iter().enumerate() {
+            if i > 0 {
+                w.write_char(',')?;
+            }
+            w.write_char('"')?;
+            write_escaped_string(key, w)?;
+            w.write_str("\":")?;
+            match record.get(i) {
+                None => w.write_str("null")?,
+                Some(field) if self.infer_types && is_json_number(field) => w.write_str(field)?,
+                Some(field) => {
+                    w.write_char('"')?;
+                    write_escaped_string(field, w)?;
+                    w.write_char('"')?;
+                }
+            }
+        }
+        w.write_str("}\n")?;
+        Ok(())
+    }
+}
+
+/// Returns `true` if `text` is a number in JSON syntax.
+fn is_json_number(text: &str) -> bool {
+    fn digits(bytes: &[u8]) -> usize {
+        bytes.iter().take_while(|b| b.is_ascii_digit()).count()
+    }
+    let mut rest = text.strip_prefix('-').unwrap_or(text).as_bytes();
+    match digits(rest) {
+        0 => return false,
+        n if n > 1 && rest[0] == b'0' => return false,
+        n => rest = &rest[n..],
+    }
+    if let Some(fraction) = rest.strip_prefix(b".") {
+        let n = digits(fraction);
+        if n == 0 {
+            return false;
+        }
+        rest = &fraction[n..];
+    }
+    if let Some(exponent) = rest.strip_prefix(b"e").or_else(|| rest.strip_prefix(b"E")) {
+        let exponent = exponent
+            .strip_prefix(b"+")
+            .or_else(|| exponent.strip_prefix(b"-"))
+            .unwrap_or(exponent);
+        let n = digits(exponent);
+        if n == 0 {
+            return false;
+        }
+        rest = &exponent[n..];
+    }
+    rest.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::String, vec, vec::Vec};
+
+    use super::{CsvEmitter, CsvError, CsvToJsonLines};
+    use crate::{ParserOptions, StreamingParser, Value, path, pipe_to, produce_chunks};
+
+    fn csv(json: &str, parts: usize, header: Option<Vec<String>>) -> Result<String, CsvError> {
+        let mut parser = StreamingParser::new(ParserOptions::default());
+        let mut emitter = CsvEmitter::new(String::new(), header);
+        for chunk in produce_chunks(json, parts) {
+            for event in parser.feed(chunk) {
+                emitter.write_event(&event.unwrap())?;
+            }
+        }
+        for event in parser.finish() {
+            emitter.write_event(&event.unwrap())?;
+        }
+        Ok(emitter.into_inner())
+    }
+
+    #[test]
+    fn quotes_fields_as_needed() {
+        let json = r#"[{"a":1,"b":"x"},{"a":2,"b":"y,z"}]"#;
+        for parts in [1, 4, json.len()] {
+            assert_eq!(csv(json, parts, None).unwrap(), "a,b\n1,x\n2,\"y,z\"\n");
+        }
+
+        let json = r#"[{"s": "say \"hi\""}, {"s": "two\nlines"}, {"s": "plain text"}]"#;
+        assert_eq!(
+            csv(json, 3, None).unwrap(),
+            "s\n\"say \"\"hi\"\"\"\n\"two\nlines\"\nplain text\n"
+        );
+    }
+
+    #[test]
+    fn missing_and_extra_keys() {
+        let json = r#"[
+            {"id": 1, "name": "a", "ok": true},
+            {"name": "b", "extra": 5, "id": 2},
+            {"id": null},
+            {}
+        ]"#;
+        assert_eq!(
+            csv(json, 5, None).unwrap(),
+            "id,name,ok\n1,a,true\n2,b,\n,,\n,,\n"
+        );
+
+        let header = vec!["name".into(), "missing".into()];
+        assert_eq!(
+            csv(json, 1, Some(header)).unwrap(),
+            "name,missing\na,\nb,\n,\n,\n"
+        );
+    }
+
+    #[test]
+    fn rejects_other_shapes() {
+        assert_eq!(csv("{}", 1, None), Err(CsvError::NotAnArrayOfObjects));
+        assert_eq!(csv("[1]", 1, None), Err(CsvError::NotAnArrayOfObjects));
+        assert_eq!(
+            csv(r#"[{"a": [1]}]"#, 1, None),
+            Err(CsvError::NestedValue(path![0, "a"]))
+        );
+    }
+
+    #[test]
+    fn works_as_a_pipe_writer() {
+        let mut parser = StreamingParser::new(ParserOptions::default());
+        let mut emitter = CsvEmitter::new(String::new(), None);
+        pipe_to(parser.feed(r#"[{"x": 1.5}, {"x": -2}]"#), &mut emitter).unwrap();
+        assert_eq!(emitter.into_inner(), "x\n1.5\n-2\n");
+    }
+
+    fn json_lines(csv: &[u8], step: usize, infer_types: bool) -> Result<String, CsvError> {
+        let mut converter = CsvToJsonLines::new(String::new(), infer_types);
+        for chunk in csv.chunks(step) {
+            converter.feed_chunk(chunk)?;
+        }
+        converter.finish()
+    }
+
+    #[test]
+    fn csv_records_become_json_lines() {
+        let csv = "name,note,n\r\n\"Smith, J\",\"line one\nline \"\"two\"\"\",1\r\n\r\nÉmile,☕,-2.5e3\nshort\n\"\",x,007,extra\n";
+        let expected = concat!(
+            r#"{"name":"Smith, J","note":"line one\u000Aline \"two\"","n":"1"}"#,
+            "\n",
+            r#"{"name":"Émile","note":"☕","n":"-2.5e3"}"#,
+            "\n",
+            r#"{"name":"short","note":null,"n":null}"#,
+            "\n",
+            r#"{"name":"","note":"x","n":"007"}"#,
+            "\n",
+        );
+        for step in [1, 2, 5, csv.len()] {
+            assert_eq!(json_lines(csv.as_bytes(), step, false).unwrap(), expected);
+        }
+
+        // Every line is a JSON value, and only valid numbers are inferred.
+        let lines = json_lines(csv.as_bytes(), 3, true).unwrap();
+        let mut parser = StreamingParser::new(ParserOptions {
+            allow_multiple_json_values: true,
+            ..Default::default()
+        });
+        let _ = parser.feed(&lines);
+        let values: Vec<Value> = parser
+            .finish()
+            .filter_map(|event| match event.unwrap() {
+                crate::ParseEvent::Number { value, .. } => Some(Value::Number(value)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(values, [Value::Number(1.0), Value::Number(-2500.0)]);
+    }
+
+    #[test]
+    fn csv_errors_and_edge_cases() {
+        assert_eq!(
+            json_lines(b"a\n\"open", 1, false),
+            Err(CsvError::UnterminatedQuote)
+        );
+        assert_eq!(
+            json_lines(b"a\n\xff\n", 1, false),
+            Err(CsvError::InvalidUtf8 { record: 2 })
+        );
+        assert_eq!(json_lines(b"", 1, false).unwrap(), "");
+        assert_eq!(json_lines(b"a,b", 1, false).unwrap(), "");
+        assert_eq!(
+            json_lines(b"a,b\n,", 1, false).unwrap(),
+            "{\"a\":\"\",\"b\":\"\"}\n"
+        );
+    }
+}
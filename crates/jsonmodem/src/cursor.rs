@@ -0,0 +1,201 @@
+//! Random access into a JSON document.
+//!
+//! Querying one document at many paths, e.g. picking fields out of a large
+//! configuration or API response, should not parse it again for every
+//! query. A [`JsonCursor`] owns the text of a document and indexes it lazily:
+//! the first lookup of a path parses only as far as the end of that value,
+//! recording the byte range of every value completed on the way, and later
+//! lookups of those paths are answered from the index without parsing.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{JsonCursor, Value, path};
+//!
+//! let cursor = JsonCursor::new(r#"{"user": {"name": "Ada", "langs": ["en", "fr"]}}"#.into());
+//! assert_eq!(
+//!     cursor.get_raw(&path!["user", "langs"]),
+//!     Some(r#"["en", "fr"]"#)
+//! );
+//! assert_eq!(
+//!     cursor.get_value(&path!["user", "name"]),
+//!     Some(Value::String("Ada".into()))
+//! );
+//! assert_eq!(cursor.get_raw(&path!["missing"]), None);
+//! ```
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use core::{cell::RefCell, ops::Range};
+
+use crate::{
+    ParseEvent, ParserOptions, PathComponent, StreamingParser, Value,
+    inspect::{EventByteSpans, event_byte_spans},
+};
+
+/// A JSON document with a lazily built index from paths to the byte ranges
+/// of their values.
+///
+/// Lookups take `&self`; the index is kept in a `RefCell`, so a cursor can
+/// be shared within a thread but is not `Sync`.
+#[derive(Debug)]
+pub struct JsonCursor {
+    json: String,
+    index: RefCell<Index>,
+}
+
+#[derive(Debug)]
+struct Index {
+    spans: BTreeMap<Vec<PathComponent>, Range<usize>>,
+    /// The events of the part of the document not yet indexed, or `None`
+    /// once all of it is, or a syntax error was found.
+    events: Option<EventByteSpans>,
+    /// Start offsets of the containers open where indexing stopped.
+    open: Vec<usize>,
+}
+
+impl JsonCursor {
+    /// Creates a cursor over `json`. Nothing is parsed until the first
+    /// lookup.
+    #[must_use]
+    pub fn new(json: String) -> Self {
+        let events = event_byte_spans(&json);
+        Self {
+            json,
+            index: RefCell::new(Index {
+                spans: BTreeMap::new(),
+                events: Some(events),
+                open: Vec::new(),
+            }),
+        }
+    }
+
+    /// The text of the document.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.json
+    }
+
+    /// Returns the text of the document.
+    #[must_use]
+    pub fn into_inner(self) -> String {
+        self.json
+    }
+
+    /// Returns the text of the value at `path`, without surrounding
+    /// whitespace, or `None` if there is no such value.
+    ///
+    /// Values after a syntax error in the document are not found.
+    #[must_use]
+    pub fn get_raw(&self, path: &[PathComponent]) -> Option<&str> {
+        let span = self.index.borrow_mut().find(path)?;
+        self.json.get(span)
+    }
+
+    /// Parses the value at `path`, or returns `None` if there is no such
+    /// value.
+    #[must_use]
+    pub fn get_value(&self, path: &[PathComponent]) -> Option<Value> {
+        let raw = self.get_raw(path)?;
+        StreamingParser::new(ParserOptions::default())
+            .into_value(raw)
+            .ok()
+    }
+
+    /// The number of values indexed so far.
+    #[must_use]
+    pub fn indexed_len(&self) -> usize {
+        self.index.borrow().spans.len()
+    }
+}
+
+impl Index {
+    /// Returns the span of the value at `path`, indexing the document until
+    /// that value ends if it is not indexed yet.
+    fn find(&mut self, path: &[PathComponent]) -> Option<Range<usize>> {
+        loop {
+            if let Some(span) = self.spans.get(path) {
+                return Some(span.clone());
+            }
+            let Some(Ok((event, span))) = self.events.as_mut()?.next() else {
+                self.events = None;
+                return None;
+            };
+            self.record(event, span);
+        }
+    }
+
+    /// Records the span of the value that `event` completes, if any.
+    fn record(&mut self, event: ParseEvent, span: Range<usize>) {
+        let (path, start) = match event {
+            ParseEvent::ArrayStart { .. } | ParseEvent::ObjectBegin { .. } => {
+                self.open.push(span.start);
+                return;
+            }
+            ParseEvent::String {
+                is_final: false, ..
+            } => {
+                // Later fragments span from the same opening quote.
+                return;
+            }
+            ParseEvent::ArrayEnd { path, .. } | ParseEvent::ObjectEnd { path, .. } => {
+                let Some(start) = self.open.pop() else {
+                    return;
+                };
+                (path, start)
+            }
+            ParseEvent::Null { path }
+            | ParseEvent::Boolean { path, .. }
+            | ParseEvent::Number { path, .. }
+            | ParseEvent::String { path, .. }
+            | ParseEvent::StringComplete { path, .. } => (path, span.start),
+            ParseEvent::ScalarBegin { .. }
+            | ParseEvent::ScalarEnd { .. }
+            | ParseEvent::ObjectKey { .. } => return,
+        };
+        // With duplicate keys, the first value at a path is the one found.
+        self.spans.entry(path).or_insert(start..span.end);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec::Vec};
+
+    use super::JsonCursor;
+    use crate::{ParserOptions, StreamingParser, Value, path};
+
+    const JSON: &str = r#" {"a": [1, "two", {"b": null}], "c": {"d": [true, []]}, "e": "é\"☕"} "#;
+
+    #[test]
+    fn values_are_found_at_their_paths() {
+        let cursor = JsonCursor::new(JSON.to_string());
+        assert_eq!(cursor.indexed_len(), 0);
+        assert_eq!(cursor.get_raw(&path!["a", 1]), Some(r#""two""#));
+        let indexed = cursor.indexed_len();
+        assert_eq!(cursor.get_raw(&path!["a", 0]), Some("1"));
+        assert_eq!(cursor.indexed_len(), indexed);
+
+        assert_eq!(cursor.get_raw(&path!["a", 2]), Some(r#"{"b": null}"#));
+        assert_eq!(cursor.get_raw(&path!["c", "d", 1]), Some("[]"));
+        assert_eq!(cursor.get_raw(&path!["e"]), Some(r#""é\"☕""#));
+        assert_eq!(cursor.get_raw(&Vec::new()), Some(JSON.trim()));
+        assert_eq!(cursor.get_raw(&path!["a", 3]), None);
+        assert_eq!(cursor.get_raw(&path!["c"]), Some(r#"{"d": [true, []]}"#));
+
+        let whole = StreamingParser::new(ParserOptions::default())
+            .into_value(JSON)
+            .unwrap();
+        assert_eq!(cursor.get_value(&Vec::new()), Some(whole));
+        assert_eq!(
+            cursor.get_value(&path!["c", "d", 0]),
+            Some(Value::Boolean(true))
+        );
+        assert_eq!(cursor.into_inner(), JSON);
+    }
+
+    #[test]
+    fn values_after_an_error_are_not_found() {
+        let cursor = JsonCursor::new(r#"{"a": 1, "b": ]}"#.to_string());
+        assert_eq!(cursor.get_raw(&path!["b"]), None);
+        assert_eq!(cursor.get_raw(&path!["a"]), Some("1"));
+    }
+}
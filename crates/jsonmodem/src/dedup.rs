@@ -0,0 +1,233 @@
+//! Keeping only the last value at each path of a document.
+//!
+//! JSON allows an object to repeat a key, and objects merged on the fly, e.g.
+//! by concatenating the members of several partial objects, often do. Most
+//! consumers then want only the last value for each key. A
+//! [`PathDeduplicator`] holds back the events of each root value, replacing
+//! the events of a member when a later member of the same object has the same
+//! key, and releases them once the root value is complete.
+//!
+//! The released events are in path order: the members of each object are
+//! sorted by key, and array items stay in order, each container's end event
+//! following its members. Memory use is proportional to the events of the
+//! largest root value; [`ParserOptions::allow_duplicate_keys_last_wins_events`]
+//! keeps the document order and holds back one object at a time instead.
+//!
+//! [`ParserOptions::allow_duplicate_keys_last_wins_events`]:
+//!     crate::ParserOptions::allow_duplicate_keys_last_wins_events
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{ParseEvent, ParserOptions, PathDeduplicator, StreamingParser};
+//!
+//! let mut parser = StreamingParser::new(ParserOptions::default());
+//! let mut dedup = PathDeduplicator::new();
+//! let mut events = Vec::new();
+//! for event in parser.feed(r#"{"b": 1, "a": [true], "b": 2}"#) {
+//!     events.extend(dedup.push(event?));
+//! }
+//! let numbers: Vec<_> = events
+//!     .iter()
+//!     .filter_map(|event| match event {
+//!         ParseEvent::Number { value, .. } => Some(*value),
+//!         _ => None,
+//!     })
+//!     .collect();
+//! assert_eq!(numbers, [2.0]);
+//! assert_eq!(events.len(), 6);
+//! # Ok::<(), jsonmodem::ParserError>(())
+//! ```
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use crate::{JsonValue, ParseEvent, PathComponent, Value};
+
+/// Holds back the events of each root value, keeping only the last value at
+/// each path.
+///
+/// Push every event of the stream, across as many `feed` calls as needed.
+/// See the [module documentation](self) for the order of the released
+/// events.
+#[derive(Debug, Clone)]
+pub struct PathDeduplicator<V: JsonValue = Value> {
+    /// The events of the current root value by path.
+    buffered: BTreeMap<Vec<PathComponent>, Vec<ParseEvent<V>>>,
+    /// Events released and not yet taken.
+    ready: Vec<ParseEvent<V>>,
+    /// Whether the next event continues the value of the last one: after an
+    /// `ObjectKey`, a `ScalarBegin` or a string fragment that is not final.
+    continuing: bool,
+    /// Whether a `ScalarBegin` is waiting for its `ScalarEnd`.
+    bounded: bool,
+}
+
+impl<V: JsonValue> Default for PathDeduplicator<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: JsonValue> PathDeduplicator<V> {
+    /// Creates a deduplicator with nothing buffered.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            buffered: BTreeMap::new(),
+            ready: Vec::new(),
+            continuing: false,
+            bounded: false,
+        }
+    }
+
+    /// Returns `true` if no events of an incomplete root value are held back.
+    #[must_use]
+    pub fn is_idle(&self) -> bool {
+        self.buffered.is_empty()
+    }
+
+    /// Accounts for one event, returning the events released by it: all the
+    /// events of a root value once it is complete, and none before.
+    pub fn push(&mut self, event: ParseEvent<V>) -> impl Iterator<Item = ParseEvent<V>> + '_ {
+        let is_end = matches!(
+            event,
+            ParseEvent::ArrayEnd { .. }
+                | ParseEvent::ObjectEnd { .. }
+                | ParseEvent::ScalarEnd { .. }
+        );
+        let path = event.path_clone();
+        if !is_end && !self.continuing {
+            // A new value replaces every event at or below its path.
+            let stale: Vec<_> = self
+                .buffered
+                .range(path.clone()..)
+                .map(|(key, _)| key)
+                .take_while(|key| key.starts_with(&path))
+                .cloned()
+                .collect();
+            for key in stale {
+                self.buffered.remove(&key);
+            }
+        }
+        match event {
+            ParseEvent::ScalarBegin { .. } => self.bounded = true,
+            ParseEvent::ScalarEnd { .. } => self.bounded = false,
+            _ => {}
+        }
+        self.continuing = self.bounded
+            || matches!(
+                event,
+                ParseEvent::ObjectKey { .. }
+                    | ParseEvent::String {
+                        is_final: false,
+                        ..
+                    }
+            );
+        let ends_root = path.is_empty()
+            && !self.continuing
+            && !matches!(
+                event,
+                ParseEvent::ArrayStart { .. } | ParseEvent::ObjectBegin { .. }
+            );
+        self.buffered.entry(path).or_default().push(event);
+        if ends_root {
+            self.release();
+        }
+        self.ready.drain(..)
+    }
+
+    /// Moves the events of the complete root value to `ready`, deferring the
+    /// end of each container until after its members.
+    fn release(&mut self) {
+        let mut ends: Vec<ParseEvent<V>> = Vec::new();
+        for (path, events) in core::mem::take(&mut self.buffered) {
+            while ends.last().is_some_and(|end| !path.starts_with(end.path())) {
+                self.ready.extend(ends.pop());
+            }
+            for event in events {
+                if matches!(
+                    event,
+                    ParseEvent::ArrayEnd { .. }
+                        | ParseEvent::ObjectEnd { .. }
+                        | ParseEvent::ScalarEnd { .. }
+                ) {
+                    ends.push(event);
+                } else {
+                    self.ready.push(event);
+                }
+            }
+        }
+        self.ready.extend(ends.into_iter().rev());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::PathDeduplicator;
+    use crate::{ParseEvent, ParserOptions, Value, event::reconstruct_values, tests::events};
+
+    fn dedup(json: &str, parts: usize, options: ParserOptions) -> Vec<ParseEvent> {
+        let options = ParserOptions {
+            allow_multiple_json_values: true,
+            ..options
+        };
+        let mut dedup = PathDeduplicator::new();
+        let mut deduplicated = Vec::new();
+        for event in events(json, parts, options) {
+            deduplicated.extend(dedup.push(event));
+        }
+        assert!(dedup.is_idle());
+        deduplicated
+    }
+
+    fn values(json: &str) -> Vec<Value> {
+        let options = ParserOptions {
+            allow_multiple_json_values: true,
+            ..ParserOptions::default()
+        };
+        reconstruct_values(events(json, 1, options))
+    }
+
+    #[test]
+    fn last_value_wins() {
+        let json = r#"{"b": {"x": [1, 2], "y": "old"}, "a": "first", "b": {"y": "new ☕"},
+            "a": [{"c": 1, "c": null}], "d": 1} [1, {"k": 1, "k": 2}] "s" 3"#;
+        let expected =
+            values(r#"{"a": [{"c": null}], "b": {"y": "new ☕"}, "d": 1} [1, {"k": 2}] "s" 3"#);
+        for parts in [1, 4, json.len()] {
+            let events = dedup(json, parts, ParserOptions::default());
+            assert_eq!(reconstruct_values(events), expected, "{parts}");
+        }
+    }
+
+    #[test]
+    fn events_nest_in_path_order() {
+        let options = ParserOptions {
+            scalar_boundary_events: true,
+            emit_key_events: true,
+            ..ParserOptions::default()
+        };
+        let events = dedup(r#"{"b": 1, "a": [2], "b": {"c": 3}}"#, 1, options);
+        let kinds: Vec<_> = events
+            .iter()
+            .map(|event| match event {
+                ParseEvent::ObjectBegin { .. } => "{",
+                ParseEvent::ObjectEnd { .. } => "}",
+                ParseEvent::ArrayStart { .. } => "[",
+                ParseEvent::ArrayEnd { .. } => "]",
+                ParseEvent::ObjectKey { .. } => "key",
+                ParseEvent::ScalarBegin { .. } => "<",
+                ParseEvent::ScalarEnd { .. } => ">",
+                ParseEvent::Number { .. } => "n",
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                "{", "key", "[", "<", "n", ">", "]", "key", "{", "key", "<", "n", ">", "}", "}"
+            ]
+        );
+    }
+}
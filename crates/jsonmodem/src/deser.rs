@@ -0,0 +1,792 @@
+//! Deserialization of whole documents into Rust types.
+//!
+//! [`from_str`] and [`from_reader`] parse a complete document and pass it to a
+//! [`serde::Deserialize`] implementation, for callers that want the finished
+//! value rather than the events. They take the place of
+//! `serde_json::from_str` and `serde_json::from_reader`.
+//!
+//! Integer literals that fit in an `i64` or `u64` are handed to the
+//! deserializer exactly; other numbers are parsed as `f64`, and those without
+//! a fractional part are handed over as integers, so that they fit integer
+//! fields. Strings are always owned, so fields borrowing `&str` from the input
+//! are not supported.
+//!
+//! # Examples
+//!
+//! ```
+//! #[derive(serde::Deserialize)]
+//! struct Point {
+//!     x: i32,
+//!     y: i32,
+//! }
+//!
+//! let point: Point = jsonmodem::from_str(r#"{"x": 1, "y": -2}"#)?;
+//! assert_eq!((point.x, point.y), (1, -2));
+//! # Ok::<(), jsonmodem::DeserError>(())
+//! ```
+use alloc::{
+    collections::{BTreeMap, btree_map},
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{cell::Cell, fmt};
+
+use serde::{
+    Deserialize,
+    de::{
+        self, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, Unexpected,
+        VariantAccess, Visitor,
+    },
+};
+
+use crate::{
+    JsonValue, JsonValueFactory, NonScalarValueMode, ParseEvent, ParserError, ParserOptions,
+    PathComponent, StdValueFactory, StreamingParser, StringValueMode, Value, event::Key,
+    pool::delegate_to_std_factory, value::Map,
+};
+
+/// The number of bytes [`from_reader`] reads at a time.
+#[cfg(any(test, feature = "std"))]
+const READ_CHUNK_BYTES: usize = 8 * 1024;
+
+/// Error returned by [`from_str`] and [`from_reader`].
+#[derive(Debug)]
+pub enum DeserError {
+    /// The input is not valid JSON.
+    Parser(ParserError),
+    /// The document does not match the target type.
+    Data {
+        /// The path to the value that does not match.
+        path: Vec<PathComponent>,
+        /// What the target type expected.
+        message: String,
+    },
+    /// The input is not valid UTF-8.
+    Utf8 {
+        /// Offset in bytes of the first invalid byte.
+        byte_offset: usize,
+    },
+    /// Reading the input failed.
+    #[cfg(any(test, feature = "std"))]
+    Io(std::io::Error),
+}
+
+impl fmt::Display for DeserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeserError::Parser(err) => {
+                write!(f, "{err} at line {} column {}", err.line, err.column)
+            }
+            DeserError::Data { path, message } if path.is_empty() => f.write_str(message),
+            DeserError::Data { path, message } => write!(f, "{message} at {path:?}"),
+            DeserError::Utf8 { byte_offset } => write!(f, "invalid UTF-8 at byte {byte_offset}"),
+            #[cfg(any(test, feature = "std"))]
+            DeserError::Io(err) => err.fmt(f),
+        }
+    }
+}
+
+impl core::error::Error for DeserError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            DeserError::Parser(err) => Some(err),
+            #[cfg(any(test, feature = "std"))]
+            DeserError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParserError> for DeserError {
+    fn from(err: ParserError) -> Self {
+        DeserError::Parser(err)
+    }
+}
+
+impl de::Error for DeserError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        // The path is filled in by `deserialize`.
+        DeserError::Data {
+            path: Vec::new(),
+            message: msg.to_string(),
+        }
+    }
+}
+
+/// Parses `s` as a single JSON document and deserializes it as `T`.
+///
+/// # Errors
+///
+/// Returns [`DeserError::Parser`] if `s` is not valid JSON and
+/// [`DeserError::Data`] if the document does not match `T`.
+pub fn from_str<'de, T: Deserialize<'de>>(s: &'de str) -> Result<T, DeserError> {
+    let mut parser = StreamingParser::new(options());
+    let mut document = Document::default();
+    let integer = Cell::new(None);
+    document.collect(&integer, parser.feed_with(IntegerFactory(&integer), s))?;
+    document.collect(&integer, parser.finish_with(IntegerFactory(&integer)))?;
+    document.deserialize()
+}
+
+/// Reads a single JSON document from `reader` and deserializes it as `T`.
+///
+/// The input is read and parsed in chunks of 8 KiB.
+///
+/// # Errors
+///
+/// Returns [`DeserError::Io`] if reading fails, [`DeserError::Utf8`] if the
+/// input is not UTF-8, and otherwise the errors of [`from_str`].
+#[cfg(any(test, feature = "std"))]
+pub fn from_reader<T, R>(mut reader: R) -> Result<T, DeserError>
+where
+    T: serde::de::DeserializeOwned,
+    R: std::io::Read,
+{
+    let mut parser = StreamingParser::new(options());
+    let mut document = Document::default();
+    let integer = Cell::new(None);
+    let mut buf = [0; READ_CHUNK_BYTES];
+    // Bytes at the start of `buf` that begin a character split across reads.
+    let mut pending = 0;
+    let mut byte_offset = 0;
+    loop {
+        let read = match reader.read(&mut buf[pending..]) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(DeserError::Io(err)),
+        };
+        let filled = pending + read;
+        let valid = match core::str::from_utf8(&buf[..filled]) {
+            Ok(_) => filled,
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            Err(err) => {
+                return Err(DeserError::Utf8 {
+                    byte_offset: byte_offset + err.valid_up_to(),
+                });
+            }
+        };
+        // Validated above.
+        let text = core::str::from_utf8(&buf[..valid]).unwrap_or_default();
+        document.collect(&integer, parser.feed_with(IntegerFactory(&integer), text))?;
+        buf.copy_within(valid..filled, 0);
+        pending = filled - valid;
+        byte_offset += valid;
+    }
+    if pending > 0 {
+        return Err(DeserError::Utf8 { byte_offset });
+    }
+    document.collect(&integer, parser.finish_with(IntegerFactory(&integer)))?;
+    document.deserialize()
+}
+
+fn options() -> ParserOptions {
+    ParserOptions {
+        non_scalar_values: NonScalarValueMode::None,
+        string_value_mode: StringValueMode::Values,
+        ..Default::default()
+    }
+}
+
+/// An integer literal that an `f64` cannot hold exactly.
+#[derive(Debug, Clone, Copy)]
+enum Integer {
+    I64(i64),
+    U64(u64),
+}
+
+/// Builds [`Value`]s, keeping the last integer literal that its `f64`
+/// rounds.
+struct IntegerFactory<'a>(&'a Cell<Option<Integer>>);
+
+impl JsonValueFactory for IntegerFactory<'_> {
+    delegate_to_std_factory!();
+
+    fn new_number_i64(&mut self, n: i64) -> f64 {
+        // Every integer up to 2^53 is an `f64`.
+        if n.unsigned_abs() > 1 << f64::MANTISSA_DIGITS {
+            self.0.set(Some(Integer::I64(n)));
+        }
+        StdValueFactory.new_number_i64(n)
+    }
+
+    fn new_number_u64(&mut self, n: u64) -> f64 {
+        self.0.set(Some(Integer::U64(n)));
+        StdValueFactory.new_number_u64(n)
+    }
+}
+
+/// The root value of a document, and the integer literals in it that its
+/// numbers round, by path.
+#[derive(Default)]
+struct Document {
+    root: Option<Value>,
+    /// The arrays and objects that are still open, outermost first.
+    open: Vec<Value>,
+    integers: BTreeMap<Vec<PathComponent>, Integer>,
+}
+
+impl Document {
+    /// Builds the root value from `events`. `integer` holds the integer
+    /// literal of the number event just read, if its `f64` rounds it: the
+    /// parser yields the event of a number before it reads the next token.
+    fn collect(
+        &mut self,
+        integer: &Cell<Option<Integer>>,
+        events: impl IntoIterator<Item = Result<ParseEvent, ParserError>>,
+    ) -> Result<(), ParserError> {
+        for event in events {
+            let (path, value) = match event? {
+                ParseEvent::ArrayStart { .. } => {
+                    self.open.push(Value::Array(Vec::new()));
+                    continue;
+                }
+                ParseEvent::ObjectBegin { .. } => {
+                    self.open.push(Value::Object(Map::new()));
+                    continue;
+                }
+                ParseEvent::ArrayEnd { path, .. } | ParseEvent::ObjectEnd { path, .. } => {
+                    let Some(value) = self.open.pop() else {
+                        continue;
+                    };
+                    (path, value)
+                }
+                ParseEvent::Number { path, value } => {
+                    // A later duplicate key replaces the number at `path`.
+                    match integer.take() {
+                        Some(integer) => self.integers.insert(path.clone(), integer),
+                        None => self.integers.remove(&path),
+                    };
+                    (path, Value::Number(value))
+                }
+                ParseEvent::Null { path } => (path, Value::Null),
+                ParseEvent::Boolean { path, value } => (path, Value::Boolean(value)),
+                ParseEvent::String {
+                    path,
+                    value: Some(value),
+                    is_final: true,
+                    ..
+                } => (path, Value::String(value)),
+                _ => continue,
+            };
+            match (self.open.last_mut(), path.last()) {
+                (Some(Value::Array(items)), _) => items.push(value),
+                (Some(Value::Object(members)), Some(PathComponent::Key(key))) => {
+                    members.insert(key.clone(), value);
+                }
+                _ => self.root = Some(value),
+            }
+        }
+        Ok(())
+    }
+
+    fn deserialize<'de, T: Deserialize<'de>>(self) -> Result<T, DeserError> {
+        let Some(value) = self.root else {
+            return Err(de::Error::custom("expected a JSON value"));
+        };
+        let mut state = State {
+            integers: self.integers,
+            path: Vec::new(),
+            error_path: None,
+        };
+        let result = T::deserialize(ValueDeserializer {
+            value,
+            state: &mut state,
+        });
+        result.map_err(|err| match err {
+            DeserError::Data { message, .. } => DeserError::Data {
+                path: state.error_path.unwrap_or_default(),
+                message,
+            },
+            err => err,
+        })
+    }
+}
+
+/// What the deserializers of the values in a document share.
+struct State {
+    integers: BTreeMap<Vec<PathComponent>, Integer>,
+    /// The path to the value being deserialized.
+    path: Vec<PathComponent>,
+    /// The path to the innermost value that failed.
+    error_path: Option<Vec<PathComponent>>,
+}
+
+impl State {
+    /// Records the current path as that of the error in `result`, unless a
+    /// value inside it failed first.
+    fn locate<T>(&mut self, result: Result<T, DeserError>) -> Result<T, DeserError> {
+        match result {
+            // A failure the visitor recovered from is not this error.
+            Ok(_) => self.error_path = None,
+            Err(_) if self.error_path.is_none() => self.error_path = Some(self.path.clone()),
+            Err(_) => {}
+        }
+        result
+    }
+
+    /// Runs `f` with `component` appended to the current path.
+    fn at<T>(&mut self, component: PathComponent, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.path.push(component);
+        let result = f(self);
+        self.path.pop();
+        result
+    }
+}
+
+/// Deserializes a value of a document at the current path.
+struct ValueDeserializer<'a> {
+    value: Value,
+    state: &'a mut State,
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'_> {
+    type Error = DeserError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeserError> {
+        let Self { value, state } = self;
+        let result = match value {
+            Value::Null => visitor.visit_unit(),
+            Value::Boolean(b) => visitor.visit_bool(b),
+            Value::Number(n) => match state.integers.get(&state.path) {
+                Some(&Integer::I64(n)) => visitor.visit_i64(n),
+                Some(&Integer::U64(n)) => visitor.visit_u64(n),
+                None => visit_number(n, visitor),
+            },
+            Value::String(s) => visitor.visit_string(s),
+            Value::Array(items) => {
+                let len = items.len();
+                let mut seq = SeqDeserializer {
+                    items: items.into_iter(),
+                    index: 0,
+                    state: &mut *state,
+                };
+                match visitor.visit_seq(&mut seq) {
+                    Ok(_) if seq.items.len() > 0 => {
+                        Err(de::Error::invalid_length(len, &"fewer elements in array"))
+                    }
+                    result => result,
+                }
+            }
+            Value::Object(members) => {
+                let len = members.len();
+                let mut map = MapDeserializer {
+                    members: members.into_iter(),
+                    value: None,
+                    state: &mut *state,
+                };
+                match visitor.visit_map(&mut map) {
+                    Ok(_) if map.members.len() > 0 => {
+                        Err(de::Error::invalid_length(len, &"fewer elements in map"))
+                    }
+                    result => result,
+                }
+            }
+        };
+        state.locate(result)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeserError> {
+        match self.value {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeserError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeserError> {
+        let Self { value, state } = self;
+        let result = match value {
+            Value::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            Value::Object(members) if members.len() == 1 => {
+                let mut members = members.into_iter();
+                match members.next() {
+                    Some((variant, value)) => visitor.visit_enum(EnumDeserializer {
+                        variant,
+                        value,
+                        state: &mut *state,
+                    }),
+                    None => unreachable!("the object has one member"),
+                }
+            }
+            other => Err(de::Error::invalid_type(
+                unexpected(&other),
+                &"a string or an object with one member",
+            )),
+        };
+        state.locate(result)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeserError> {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier
+    }
+}
+
+/// Hands `n` to `visitor` as an integer if it has no fractional part and is in
+/// range.
+#[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn visit_number<'de, V: Visitor<'de>>(n: f64, visitor: V) -> Result<V::Value, DeserError> {
+    // 2^63, the first value past the range of `i64`.
+    const I64_END: f64 = 9_223_372_036_854_775_808.0;
+    if n.fract() == 0.0 && (-I64_END..I64_END).contains(&n) {
+        visitor.visit_i64(n as i64)
+    } else if n.fract() == 0.0 && (0.0..2.0 * I64_END).contains(&n) {
+        visitor.visit_u64(n as u64)
+    } else {
+        visitor.visit_f64(n)
+    }
+}
+
+fn unexpected(value: &Value) -> Unexpected<'_> {
+    match value {
+        Value::Null => Unexpected::Unit,
+        Value::Boolean(b) => Unexpected::Bool(*b),
+        Value::Number(n) => Unexpected::Float(*n),
+        Value::String(s) => Unexpected::Str(s),
+        Value::Array(_) => Unexpected::Seq,
+        Value::Object(_) => Unexpected::Map,
+    }
+}
+
+struct SeqDeserializer<'a> {
+    items: vec::IntoIter<Value>,
+    index: usize,
+    state: &'a mut State,
+}
+
+impl<'de> SeqAccess<'de> for SeqDeserializer<'_> {
+    type Error = DeserError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, DeserError> {
+        let Some(value) = self.items.next() else {
+            return Ok(None);
+        };
+        let index = PathComponent::Index(self.index);
+        self.index += 1;
+        self.state
+            .at(index, |state| {
+                seed.deserialize(ValueDeserializer { value, state })
+            })
+            .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+struct MapDeserializer<'a> {
+    members: btree_map::IntoIter<Key, Value>,
+    /// The value of the member whose key was read last, with its key.
+    value: Option<(Key, Value)>,
+    state: &'a mut State,
+}
+
+impl<'de> MapAccess<'de> for MapDeserializer<'_> {
+    type Error = DeserError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, DeserError> {
+        let Some((key, value)) = self.members.next() else {
+            return Ok(None);
+        };
+        let result = self.state.at(PathComponent::Key(key.clone()), |state| {
+            let result = seed.deserialize(KeyDeserializer(&key));
+            state.locate(result)
+        });
+        self.value = Some((key, value));
+        result.map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, DeserError> {
+        let Some((key, value)) = self.value.take() else {
+            return Err(de::Error::custom("value is missing"));
+        };
+        self.state.at(PathComponent::Key(key), |state| {
+            seed.deserialize(ValueDeserializer { value, state })
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.members.len())
+    }
+}
+
+/// Deserializes an object key, as a string or, for maps with integer keys, as
+/// the integer it spells.
+struct KeyDeserializer<'a>(&'a str);
+
+macro_rules! deserialize_integer_key {
+    ($($method:ident => $visit:ident),*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeserError> {
+                match self.0.parse() {
+                    Ok(n) => visitor.$visit(n),
+                    Err(_) => Err(de::Error::invalid_value(Unexpected::Str(self.0), &visitor)),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for KeyDeserializer<'_> {
+    type Error = DeserError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeserError> {
+        visitor.visit_str(self.0)
+    }
+
+    deserialize_integer_key! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i128 u128 f32 f64 char str string bytes byte_buf option unit unit_struct
+        newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Deserializes the variant of an enum written as an object with one member.
+struct EnumDeserializer<'a> {
+    variant: Key,
+    value: Value,
+    state: &'a mut State,
+}
+
+impl<'de, 'a> EnumAccess<'de> for EnumDeserializer<'a> {
+    type Error = DeserError;
+    type Variant = VariantDeserializer<'a>;
+
+    fn variant_seed<S: DeserializeSeed<'de>>(
+        self,
+        seed: S,
+    ) -> Result<(S::Value, VariantDeserializer<'a>), DeserError> {
+        let variant = seed.deserialize(KeyDeserializer(&self.variant))?;
+        let value = VariantDeserializer {
+            variant: self.variant,
+            value: self.value,
+            state: self.state,
+        };
+        Ok((variant, value))
+    }
+}
+
+struct VariantDeserializer<'a> {
+    variant: Key,
+    value: Value,
+    state: &'a mut State,
+}
+
+impl VariantDeserializer<'_> {
+    /// Deserializes the value of the variant with `f`.
+    fn value<T>(
+        self,
+        f: impl FnOnce(ValueDeserializer<'_>) -> Result<T, DeserError>,
+    ) -> Result<T, DeserError> {
+        let value = self.value;
+        self.state.at(PathComponent::Key(self.variant), |state| {
+            f(ValueDeserializer { value, state })
+        })
+    }
+}
+
+impl<'de> VariantAccess<'de> for VariantDeserializer<'_> {
+    type Error = DeserError;
+
+    fn unit_variant(self) -> Result<(), DeserError> {
+        self.value(|value| <()>::deserialize(value))
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, DeserError> {
+        self.value(|value| seed.deserialize(value))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, DeserError> {
+        self.value(|value| de::Deserializer::deserialize_seq(value, visitor))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeserError> {
+        self.value(|value| de::Deserializer::deserialize_map(value, visitor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        collections::BTreeMap,
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+
+    use serde::Deserialize;
+
+    use super::{DeserError, from_reader, from_str};
+    use crate::path;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Item {
+        id: u64,
+        name: String,
+        score: f64,
+        tags: Vec<String>,
+        parent: Option<i32>,
+    }
+
+    /// Returns at most `self.1` bytes per read.
+    struct Trickle<'a>(&'a [u8], usize);
+
+    impl std::io::Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.0.len().min(buf.len()).min(self.1);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    const JSON: &str = r#"[
+        {"id": 1, "name": "café ☕", "score": 2.5, "tags": ["a"], "parent": null},
+        {"id": 18446744073709549568, "name": "", "score": -3, "tags": [], "parent": -7}
+    ]"#;
+
+    fn expected() -> Vec<Item> {
+        vec![
+            Item {
+                id: 1,
+                name: "café ☕".into(),
+                score: 2.5,
+                tags: vec!["a".into()],
+                parent: None,
+            },
+            Item {
+                id: 18_446_744_073_709_549_568,
+                name: String::new(),
+                score: -3.0,
+                tags: Vec::new(),
+                parent: Some(-7),
+            },
+        ]
+    }
+
+    #[test]
+    fn from_str_deserializes() {
+        assert_eq!(from_str::<Vec<Item>>(JSON).unwrap(), expected());
+        assert_eq!(from_str::<String>(r#""x\ny""#).unwrap(), "x\ny");
+        assert_eq!(from_str::<Option<bool>>(" null ").unwrap(), None);
+    }
+
+    #[test]
+    fn integers_keep_their_precision() {
+        assert_eq!(from_str::<u64>("18446744073709551615").unwrap(), u64::MAX);
+        assert_eq!(from_str::<i64>("-9223372036854775808").unwrap(), i64::MIN);
+        let ids: Vec<u64> = from_str("[9007199254740993, 1e3, 18446744073709551615]").unwrap();
+        assert_eq!(ids, [9_007_199_254_740_993, 1000, u64::MAX]);
+        // A later duplicate key replaces the integer.
+        let map: BTreeMap<String, u64> =
+            from_str(r#"{"a": 18446744073709551615, "a": 5}"#).unwrap();
+        assert_eq!(map["a"], 5);
+        assert!(from_str::<i64>("18446744073709551615").is_err());
+    }
+
+    #[test]
+    fn enums_and_integer_keys() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        enum Shape {
+            Empty,
+            Circle(f64),
+            Rect { w: u32, h: u32 },
+        }
+
+        let shapes: Vec<Shape> =
+            from_str(r#"["Empty", {"Circle": 1.5}, {"Rect": {"w": 2, "h": 3}}]"#).unwrap();
+        assert_eq!(
+            shapes,
+            [Shape::Empty, Shape::Circle(1.5), Shape::Rect { w: 2, h: 3 }]
+        );
+        let map: BTreeMap<u8, bool> = from_str(r#"{"1": true, "20": false}"#).unwrap();
+        assert_eq!(map, BTreeMap::from([(1, true), (20, false)]));
+    }
+
+    #[test]
+    fn from_reader_handles_split_characters() {
+        for step in [1, 2, 5, 64] {
+            let items: Vec<Item> = from_reader(Trickle(JSON.as_bytes(), step)).unwrap();
+            assert_eq!(items, expected());
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let err = from_str::<Vec<u32>>("[1,\n 2,, 3]").unwrap_err();
+        assert!(matches!(&err, DeserError::Parser(err) if err.line == 2));
+        assert!(err.to_string().ends_with("at line 2 column 4"));
+
+        let err = from_str::<Vec<u32>>("[1, -2]").unwrap_err();
+        assert!(matches!(&err, DeserError::Data { path, .. } if *path == path![1]));
+        assert!(from_str::<u8>("1.5").is_err());
+
+        // The path is that of the innermost value that does not match.
+        let json = r#"[{"id": 1, "name": "a", "score": 0, "tags": [], "parent": null},
+            {"id": 2, "name": "b", "score": 0, "tags": ["x", 7], "parent": null}]"#;
+        let err = from_str::<Vec<Item>>(json).unwrap_err();
+        assert!(matches!(&err, DeserError::Data { path, .. } if *path == path![1, "tags", 1]));
+        assert_eq!(
+            err.to_string(),
+            r#"invalid type: integer `7`, expected a string at [Index(1), Key("tags"), Index(1)]"#
+        );
+        let err = from_str::<BTreeMap<u8, bool>>(r#"{"x": true}"#).unwrap_err();
+        assert!(matches!(&err, DeserError::Data { path, .. } if *path == path!["x"]));
+
+        let bytes = b"[\"ok\", \"\xFF\"]";
+        let err = from_reader::<Vec<String>, _>(Trickle(bytes, 3)).unwrap_err();
+        assert!(matches!(err, DeserError::Utf8 { byte_offset: 8 }));
+
+        let err = from_reader::<String, _>(Trickle(b"\"\xC3", 1)).unwrap_err();
+        assert!(matches!(err, DeserError::Utf8 { byte_offset: 1 }));
+    }
+}
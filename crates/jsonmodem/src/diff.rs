@@ -0,0 +1,375 @@
+//! Structural diffing of two `ParseEvent` streams.
+//!
+//! [`json_diff`] walks two event streams side by side and reports, for every
+//! node in either document, whether it was added, removed, changed or left
+//! unchanged. Nodes are matched by path, so object keys may appear in a
+//! different order on each side. Neither document is materialized: only the
+//! nodes whose paths have diverged are buffered until their counterpart shows
+//! up or the enclosing container has been closed on both sides.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{DiffEvent, DiffValue, ParserOptions, StreamingParser, json_diff, path};
+//!
+//! let events = |json: &str| {
+//!     let mut parser = StreamingParser::new(ParserOptions::default());
+//!     parser.feed(json);
+//!     parser.finish().map(Result::unwrap).collect::<Vec<_>>()
+//! };
+//!
+//! let changes: Vec<_> = json_diff(
+//!     events(r#"{"a": 1, "b": true}"#),
+//!     events(r#"{"b": true, "a": 2}"#),
+//! )
+//! .filter(|event| !matches!(event, DiffEvent::Unchanged { .. }))
+//! .collect();
+//!
+//! assert_eq!(
+//!     changes,
+//!     vec![DiffEvent::Changed {
+//!         path: path!["a"],
+//!         old: DiffValue::Number(1.0),
+//!         new: DiffValue::Number(2.0),
+//!     }]
+//! );
+//! ```
+use alloc::{collections::VecDeque, vec::Vec};
+
+use crate::{JsonValue, ParseEvent, PathComponent, Value};
+
+/// A single node of a JSON document as seen by [`json_diff`].
+///
+/// Scalars carry their value. Arrays and objects are reported without their
+/// contents; their members are reported as separate nodes.
+#[cfg_attr(
+    any(test, feature = "serde"),
+    derive(serde::Serialize, serde::Deserialize)
+)]
+#[cfg_attr(
+    any(test, feature = "serde"),
+    serde(bound = "
+        V::Str : serde::Serialize + serde::de::DeserializeOwned,
+        V::Num : serde::Serialize + serde::de::DeserializeOwned,
+        V::Bool: serde::Serialize + serde::de::DeserializeOwned
+    ")
+)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffValue<V: JsonValue = Value> {
+    Null,
+    Boolean(V::Bool),
+    Number(V::Num),
+    String(V::Str),
+    Array,
+    Object,
+}
+
+/// An event produced by [`json_diff`].
+#[cfg_attr(
+    any(test, feature = "serde"),
+    derive(serde::Serialize, serde::Deserialize)
+)]
+#[cfg_attr(
+    any(test, feature = "serde"),
+    serde(
+        tag = "kind",
+        bound = "
+            V::Str : serde::Serialize + serde::de::DeserializeOwned,
+            V::Num : serde::Serialize + serde::de::DeserializeOwned,
+            V::Bool: serde::Serialize + serde::de::DeserializeOwned
+        "
+    )
+)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffEvent<V: JsonValue = Value> {
+    /// A node that only exists in the right-hand document.
+    Added {
+        /// The path to the node.
+        path: Vec<PathComponent>,
+        /// The node in the right-hand document.
+        value: DiffValue<V>,
+    },
+    /// A node that only exists in the left-hand document.
+    Removed {
+        /// The path to the node.
+        path: Vec<PathComponent>,
+        /// The node in the left-hand document.
+        value: DiffValue<V>,
+    },
+    /// A node that exists in both documents with a different value or type.
+    Changed {
+        /// The path to the node.
+        path: Vec<PathComponent>,
+        /// The node in the left-hand document.
+        old: DiffValue<V>,
+        /// The node in the right-hand document.
+        new: DiffValue<V>,
+    },
+    /// A node that is identical in both documents.
+    ///
+    /// Arrays and objects are unchanged when both sides have the same kind of
+    /// container; differences in their members are reported separately.
+    Unchanged {
+        /// The path to the node.
+        path: Vec<PathComponent>,
+        /// The node, as found in both documents.
+        value: DiffValue<V>,
+    },
+}
+
+/// Compares two event streams and lazily yields a [`DiffEvent`] for every node
+/// of either document.
+///
+/// Each stream is expected to describe a single root value. Nodes found on
+/// both sides are reported as soon as the second one is seen. Nodes missing
+/// from one side are reported as [`DiffEvent::Removed`] or
+/// [`DiffEvent::Added`] once their parent container has been closed in both
+/// documents.
+///
+/// String fragments are concatenated before comparison, so the two streams
+/// may be produced with any chunking and any `string_value_mode`.
+pub fn json_diff<V, L, R>(left: L, right: R) -> JsonDiff<V, L::IntoIter, R::IntoIter>
+where
+    V: JsonValue,
+    V::Str: Extend<V::Str>,
+    L: IntoIterator<Item = ParseEvent<V>>,
+    R: IntoIterator<Item = ParseEvent<V>>,
+{
+    JsonDiff {
+        left: Side::new(left.into_iter()),
+        right: Side::new(right.into_iter()),
+        ready: VecDeque::new(),
+        done: false,
+    }
+}
+
+/// Iterator returned by [`json_diff`].
+#[derive(Debug)]
+pub struct JsonDiff<V: JsonValue, L, R> {
+    left: Side<V, L>,
+    right: Side<V, R>,
+    ready: VecDeque<DiffEvent<V>>,
+    done: bool,
+}
+
+/// What a side contributed after consuming one event.
+enum Step<V: JsonValue> {
+    Node(Vec<PathComponent>, DiffValue<V>),
+    Close(Vec<PathComponent>),
+    Nothing,
+    Exhausted,
+}
+
+/// Per-stream state: the source events, a string being assembled from
+/// fragments, and the nodes and closed containers not yet matched against the
+/// other side.
+#[derive(Debug)]
+struct Side<V: JsonValue, I> {
+    events: I,
+    string: Option<V::Str>,
+    pending: Vec<(Vec<PathComponent>, DiffValue<V>)>,
+    closed: Vec<Vec<PathComponent>>,
+    exhausted: bool,
+}
+
+impl<V, I> Side<V, I>
+where
+    V: JsonValue,
+    V::Str: Extend<V::Str>,
+    I: Iterator<Item = ParseEvent<V>>,
+{
+    fn new(events: I) -> Self {
+        Self {
+            events,
+            string: None,
+            pending: Vec::new(),
+            closed: Vec::new(),
+            exhausted: false,
+        }
+    }
+
+    fn step(&mut self) -> Step<V> {
+        if self.exhausted {
+            return Step::Exhausted;
+        }
+        let Some(event) = self.events.next() else {
+            self.exhausted = true;
+            return Step::Exhausted;
+        };
+        match event {
+            ParseEvent::Null { path } => Step::Node(path, DiffValue::Null),
+            ParseEvent::Boolean { path, value } => Step::Node(path, DiffValue::Boolean(value)),
+            ParseEvent::Number { path, value } => Step::Node(path, DiffValue::Number(value)),
+            ParseEvent::String {
+                path,
+                value,
+                fragment,
+                is_final,
+            } => {
+                match &mut self.string {
+                    Some(string) => string.extend(core::iter::once(fragment)),
+                    None => self.string = Some(fragment),
+                }
+                if !is_final {
+                    return Step::Nothing;
+                }
+                let assembled = self.string.take().unwrap_or_default();
+                Step::Node(path, DiffValue::String(value.unwrap_or(assembled)))
+            }
+            ParseEvent::StringComplete { path, value } => {
+                Step::Node(path, DiffValue::String(value))
+            }
+            ParseEvent::ArrayStart { path, .. } => Step::Node(path, DiffValue::Array),
+            ParseEvent::ObjectBegin { path, .. } => Step::Node(path, DiffValue::Object),
+            ParseEvent::ArrayEnd { path, .. } | ParseEvent::ObjectEnd { path, .. } => {
+                Step::Close(path)
+            }
+            ParseEvent::ScalarBegin { .. }
+            | ParseEvent::ScalarEnd { .. }
+            | ParseEvent::ObjectKey { .. } => Step::Nothing,
+        }
+    }
+
+    /// Removes and returns the pending node at `path`, if any.
+    fn take_pending(&mut self, path: &[PathComponent]) -> Option<DiffValue<V>> {
+        let pos = self.pending.iter().position(|(p, _)| p == path)?;
+        Some(self.pending.remove(pos).1)
+    }
+
+    /// Removes and returns all pending nodes strictly below `parent`, in
+    /// document order, and forgets any closed containers below it.
+    fn drain_below(&mut self, parent: &[PathComponent]) -> Vec<(Vec<PathComponent>, DiffValue<V>)> {
+        let is_below =
+            |path: &[PathComponent]| path.len() > parent.len() && path.starts_with(parent);
+        self.closed.retain(|path| !is_below(path));
+        let mut drained = Vec::new();
+        let mut kept = Vec::new();
+        for entry in self.pending.drain(..) {
+            if is_below(&entry.0) {
+                drained.push(entry);
+            } else {
+                kept.push(entry);
+            }
+        }
+        self.pending = kept;
+        drained
+    }
+}
+
+fn compare<V: JsonValue>(
+    path: Vec<PathComponent>,
+    old: DiffValue<V>,
+    new: DiffValue<V>,
+) -> DiffEvent<V> {
+    if old == new {
+        DiffEvent::Unchanged { path, value: new }
+    } else {
+        DiffEvent::Changed { path, old, new }
+    }
+}
+
+impl<V, L, R> JsonDiff<V, L, R>
+where
+    V: JsonValue,
+    V::Str: Extend<V::Str>,
+    L: Iterator<Item = ParseEvent<V>>,
+    R: Iterator<Item = ParseEvent<V>>,
+{
+    fn left_node(&mut self, path: Vec<PathComponent>, value: DiffValue<V>) {
+        match self.right.take_pending(&path) {
+            Some(new) => self.ready.push_back(compare(path, value, new)),
+            None => self.left.pending.push((path, value)),
+        }
+    }
+
+    fn right_node(&mut self, path: Vec<PathComponent>, value: DiffValue<V>) {
+        match self.left.take_pending(&path) {
+            Some(old) => self.ready.push_back(compare(path, old, value)),
+            None => self.right.pending.push((path, value)),
+        }
+    }
+
+    fn left_close(&mut self, path: Vec<PathComponent>) {
+        if let Some(pos) = self.right.closed.iter().position(|p| *p == path) {
+            self.right.closed.remove(pos);
+            self.flush_below(&path);
+        } else {
+            self.left.closed.push(path);
+        }
+    }
+
+    fn right_close(&mut self, path: Vec<PathComponent>) {
+        if let Some(pos) = self.left.closed.iter().position(|p| *p == path) {
+            self.left.closed.remove(pos);
+            self.flush_below(&path);
+        } else {
+            self.right.closed.push(path);
+        }
+    }
+
+    /// Reports every unmatched node below `parent` now that neither side can
+    /// produce another node inside it.
+    fn flush_below(&mut self, parent: &[PathComponent]) {
+        for (path, value) in self.left.drain_below(parent) {
+            self.ready.push_back(DiffEvent::Removed { path, value });
+        }
+        for (path, value) in self.right.drain_below(parent) {
+            self.ready.push_back(DiffEvent::Added { path, value });
+        }
+    }
+
+    fn flush_all(&mut self) {
+        for (path, value) in self.left.pending.drain(..) {
+            self.ready.push_back(DiffEvent::Removed { path, value });
+        }
+        for (path, value) in self.right.pending.drain(..) {
+            self.ready.push_back(DiffEvent::Added { path, value });
+        }
+        self.left.closed.clear();
+        self.right.closed.clear();
+    }
+}
+
+impl<V, L, R> Iterator for JsonDiff<V, L, R>
+where
+    V: JsonValue,
+    V::Str: Extend<V::Str>,
+    L: Iterator<Item = ParseEvent<V>>,
+    R: Iterator<Item = ParseEvent<V>>,
+{
+    type Item = DiffEvent<V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.ready.is_empty() && !self.done {
+            let left = self.left.step();
+            let right = self.right.step();
+
+            if let (Step::Node(lp, _), Step::Node(rp, _)) = (&left, &right) {
+                if lp == rp {
+                    let (Step::Node(path, old), Step::Node(_, new)) = (left, right) else {
+                        unreachable!()
+                    };
+                    self.ready.push_back(compare(path, old, new));
+                    continue;
+                }
+            }
+
+            let exhausted = matches!(left, Step::Exhausted) && matches!(right, Step::Exhausted);
+            match left {
+                Step::Node(path, value) => self.left_node(path, value),
+                Step::Close(path) => self.left_close(path),
+                Step::Nothing | Step::Exhausted => {}
+            }
+            match right {
+                Step::Node(path, value) => self.right_node(path, value),
+                Step::Close(path) => self.right_close(path),
+                Step::Nothing | Step::Exhausted => {}
+            }
+            if exhausted {
+                self.flush_all();
+                self.done = true;
+            }
+        }
+        self.ready.pop_front()
+    }
+}
@@ -1,11 +1,14 @@
 use alloc::string::String;
 use core::fmt;
 
+/// A syntax error, reported with the position at which it was detected.
 #[derive(Debug, Clone, PartialEq)]
 pub struct ParserError {
-    msg: String,
+    pub(crate) msg: String,
     pub line: usize,
     pub column: usize,
+    /// Offset in bytes from the start of the input.
+    pub byte_offset: usize,
 }
 
 impl fmt::Display for ParserError {
@@ -15,3 +18,37 @@ impl fmt::Display for ParserError {
 }
 
 impl core::error::Error for ParserError {}
+
+/// Parse errors surface as [`std::io::ErrorKind::InvalidData`], so that they
+/// can be returned from `io::Result`-based readers. The `ParserError` is kept
+/// as the source of the `io::Error`.
+#[cfg(any(test, feature = "std"))]
+impl From<ParserError> for std::io::Error {
+    fn from(err: ParserError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use crate::{ParserError, ParserOptions, StreamingParser};
+
+    #[test]
+    fn converts_into_io_error() {
+        let mut parser = StreamingParser::new(ParserOptions::default());
+        let err = parser.feed("[1, x]").find_map(Result::err).unwrap();
+        let message = err.to_string();
+
+        let io_err = std::io::Error::from(err.clone());
+        assert_eq!(io_err.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(io_err.to_string(), message);
+        let source = io_err
+            .into_inner()
+            .unwrap()
+            .downcast::<ParserError>()
+            .unwrap();
+        assert_eq!(*source, err);
+    }
+}
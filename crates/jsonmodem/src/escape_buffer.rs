@@ -1,37 +1,32 @@
 //! Utilities for buffering and decoding four-digit Unicode escape sequences.
 //!
 //! The [`UnicodeEscapeBuffer`] type accumulates up to four ASCII hexadecimal
-//! digits (`0-9`, `A-F`, `a-f`) representing a Unicode code point and converts
-//! them to a [`char`] once exactly four digits have been provided. After a
-//! successful conversion, the buffer resets automatically to begin a new escape
-//! sequence.
+//! digits (`0-9`, `A-F`, `a-f`) representing a UTF-16 code unit. The value of
+//! the digits seen so far is available at any time, and the complete code unit
+//! once exactly four digits have been provided.
+//!
+//! Code units in the surrogate range are returned as-is; pairing them into a
+//! single `char` is left to the parser.
 //!
 //! # Errors
 //!
 //! - Feeding a non-hexadecimal character returns an `Err` with a descriptive
 //!   message.
-//! - If more than four digits are provided without a successful conversion
-//!   (buffer overflow), an `Err` is returned.
-//! - If parsing the four-digit hexadecimal string into a `u32` fails, an `Err`
-//!   is returned.
-//!
-//! # Panics
-//!
-//! If the parsed code point is not a valid Unicode scalar value (for example,
-//! a surrogate or out of range), this will panic.
+//! - If more than four digits are provided without a reset (buffer overflow),
+//!   an `Err` is returned.
 use alloc::{
     format,
     string::{String, ToString},
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 /// Buffer for accumulating up to four hexadecimal digits (`0-9`, `A-F`, `a-f`)
-/// and decoding them into a Unicode character.
+/// and decoding them into a UTF-16 code unit.
 ///
 /// This type is useful for JSON parsers or similar, where Unicode escapes
-/// (e.g. `"\u0041"`) must be interpreted as `char` values.
+/// (e.g. `"\u0041"`) must be interpreted as UTF-16 code units.
 pub(crate) struct UnicodeEscapeBuffer {
-    buffer: [u8; 4],
+    value: u32,
     len: u8,
 }
 
@@ -40,54 +35,53 @@ impl UnicodeEscapeBuffer {
     ///
     /// The buffer will accept up to four hexadecimal digits before decoding.
     pub fn new() -> Self {
-        Self {
-            buffer: [0; 4],
-            len: 0,
-        }
+        Self { value: 0, len: 0 }
     }
 
     /// Clears any accumulated digits, returning the buffer to its initial
     /// state.
     pub fn reset(&mut self) {
+        self.value = 0;
         self.len = 0;
     }
 
     /// Feeds a single ASCII hexadecimal digit (`0-9`, `A-F`, `a-f`) into the
     /// buffer.
     ///
-    /// - Returns `Ok(None)` if fewer than four digits have been provided so
-    ///   far.
-    /// - Returns `Ok(Some(ch))` once exactly four digits have been accumulated,
-    ///   decoding them to the corresponding `char` and resetting the buffer.
-    /// - Returns `Err` if `c` is not an ASCII hex digit, if more than four
-    ///   digits are provided before a reset, or if parsing the digits into a
-    ///   `u32` fails.
-    pub fn feed(&mut self, c: char) -> Result<Option<char>, String> {
-        if !c.is_ascii_hexdigit() {
+    /// Returns `Err` if `c` is not an ASCII hex digit, or if four digits have
+    /// already been provided since the last reset.
+    pub fn feed(&mut self, c: char) -> Result<(), String> {
+        let Some(digit) = c.to_digit(16) else {
             return Err(format!("Invalid unicode escape character: {c}"));
-        }
+        };
 
         if self.len >= 4 {
             return Err("Unicode escape buffer overflow".to_string());
         }
-        self.buffer[self.len as usize] = c as u8;
+        self.value = (self.value << 4) | digit;
         self.len += 1;
+        Ok(())
+    }
 
-        if self.len == 4 {
-            let hex_str = core::str::from_utf8(&self.buffer).unwrap();
-            match u32::from_str_radix(hex_str, 16) {
-                Ok(code) => {
-                    self.reset(); // Reset after successful conversion
-                    Ok(Some(
-                        core::char::from_u32(code)
-                            .ok_or(format!("Invalid Unicode scalar value: {code}"))?,
-                    ))
-                }
-                Err(e) => Err(format!("Failed to parse unicode escape: {e}")),
-            }
-        } else {
-            Ok(None)
-        }
+    /// The value of the digits accumulated so far, e.g. `0xD8` after `D`, `8`.
+    pub fn as_partial_u32(&self) -> u32 {
+        self.value
+    }
+
+    /// The value and number of the digits accumulated so far.
+    pub fn to_parts(&self) -> (u32, u8) {
+        (self.value, self.len)
+    }
+
+    /// Creates a buffer from the parts returned by
+    /// [`to_parts`](Self::to_parts), or `None` if they are out of range.
+    pub fn from_parts(value: u32, len: u8) -> Option<Self> {
+        (len <= 4 && value >> (4 * u32::from(len)) == 0).then_some(Self { value, len })
+    }
+
+    /// The decoded UTF-16 code unit, once all four digits have been provided.
+    pub fn as_code_point(&self) -> Option<u32> {
+        (self.len == 4).then(|| self.as_partial_u32())
     }
 }
 
@@ -95,35 +89,49 @@ impl UnicodeEscapeBuffer {
 mod tests {
     use super::UnicodeEscapeBuffer;
 
+    fn feed_all(buf: &mut UnicodeEscapeBuffer, digits: &str) {
+        for ch in digits.chars() {
+            buf.feed(ch).unwrap();
+        }
+    }
+
     #[test]
     fn basic_decoding() {
         let mut buf = UnicodeEscapeBuffer::new();
-        assert_eq!(buf.feed('0').unwrap(), None);
-        assert_eq!(buf.feed('0').unwrap(), None);
-        assert_eq!(buf.feed('4').unwrap(), None);
-        assert_eq!(buf.feed('1').unwrap(), Some('A'));
+        for ch in "004".chars() {
+            buf.feed(ch).unwrap();
+            assert_eq!(buf.as_code_point(), None);
+        }
+        buf.feed('1').unwrap();
+        assert_eq!(buf.as_code_point(), Some(u32::from(b'A')));
     }
 
     #[test]
     fn mixed_case_hex() {
         let mut buf = UnicodeEscapeBuffer::new();
-        for ch in "AbCd".chars() {
-            let res = buf.feed(ch).unwrap();
-            if ch == 'd' {
-                assert_eq!(res, Some(char::from_u32(0xABCD).unwrap()));
-            } else {
-                assert!(res.is_none());
-            }
-        }
+        feed_all(&mut buf, "AbCd");
+        assert_eq!(buf.as_code_point(), Some(0xABCD));
+    }
+
+    #[test]
+    fn partial_value_is_available() {
+        let mut buf = UnicodeEscapeBuffer::new();
+        assert_eq!(buf.as_partial_u32(), 0);
+        feed_all(&mut buf, "D8");
+        assert_eq!(buf.as_partial_u32(), 0xD8);
+        feed_all(&mut buf, "3");
+        assert_eq!(buf.as_partial_u32(), 0xD83);
+        assert_eq!(buf.as_code_point(), None);
     }
 
     #[test]
     fn reset_clears_buffer() {
         let mut buf = UnicodeEscapeBuffer::new();
-        assert!(buf.feed('F').unwrap().is_none());
+        feed_all(&mut buf, "FFF");
         buf.reset();
         // After reset, previous input is discarded
-        assert_eq!(buf.feed('0').unwrap(), None);
+        feed_all(&mut buf, "0041");
+        assert_eq!(buf.as_code_point(), Some(0x41));
     }
 
     #[test]
@@ -134,12 +142,18 @@ mod tests {
     }
 
     #[test]
-    fn invalid_scalar_errors() {
-        // 'D800' is a surrogate range high half and not a valid scalar
+    fn overflow_error() {
         let mut buf = UnicodeEscapeBuffer::new();
-        for ch in "D80".chars() {
-            let _ = buf.feed(ch).unwrap();
-        }
-        let _ = buf.feed('0').unwrap_err();
+        feed_all(&mut buf, "0000");
+        let err = buf.feed('0').unwrap_err();
+        assert!(err.contains("overflow"));
+    }
+
+    #[test]
+    fn surrogates_are_returned_as_code_units() {
+        // 'D800' is a high surrogate; pairing is the parser's job.
+        let mut buf = UnicodeEscapeBuffer::new();
+        feed_all(&mut buf, "D800");
+        assert_eq!(buf.as_code_point(), Some(0xD800));
     }
 }
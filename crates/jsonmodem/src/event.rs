@@ -19,7 +19,11 @@
 //! assert_eq!(
 //!     events,
 //!     vec![
-//!         Ok(ParseEvent::ArrayStart { path: vec![] }),
+//!         Ok(ParseEvent::ArrayStart {
+//!             path: vec![],
+//!             capacity_hint: None,
+//!             parent_key: None,
+//!         }),
 //!         Ok(ParseEvent::String {
 //!             path: vec![PathComponent::Index(0)],
 //!             value: None,
@@ -54,7 +58,11 @@ pub type Index = usize;
 /// Paths are sequences of keys or indices (for objects and arrays,
 /// respectively) used in `ParseEvent` to indicate the location of a value
 /// within a JSON document.
-#[derive(Debug, Clone, PartialEq)]
+///
+/// Components are ordered with every key before every index, keys by code
+/// point and indices numerically, so that paths can be used as keys of a
+/// `BTreeMap`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum PathComponent {
     Key(Key),
     Index(Index),
@@ -195,23 +203,126 @@ mod serde_impls {
 }
 
 impl PathComponent {
+    /// Returns the key, or `None` for an index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jsonmodem::PathComponent;
+    ///
+    /// assert_eq!(PathComponent::from("name").as_key(), Some("name"));
+    /// assert_eq!(PathComponent::from(0usize).as_key(), None);
+    /// ```
+    #[must_use]
+    pub fn as_key(&self) -> Option<&str> {
+        match self {
+            Self::Key(key) => Some(key),
+            Self::Index(_) => None,
+        }
+    }
+
+    /// Returns the index, or `None` for a key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jsonmodem::PathComponent;
+    ///
+    /// assert_eq!(PathComponent::from(3usize).as_index(), Some(3));
+    /// assert_eq!(PathComponent::from("3").as_index(), None);
+    /// ```
     #[must_use]
-    /// Returns the index if this component is an index, otherwise `None`.
     pub fn as_index(&self) -> Option<Index> {
-        if let Self::Index(v) = self {
-            Some(*v)
-        } else {
-            None
+        match self {
+            Self::Key(_) => None,
+            Self::Index(index) => Some(*index),
         }
     }
 
+    /// Returns `true` for the key of an object member.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jsonmodem::PathComponent;
+    ///
+    /// assert!(PathComponent::from("name").is_key());
+    /// assert!(!PathComponent::from(0usize).is_key());
+    /// ```
+    #[must_use]
+    pub fn is_key(&self) -> bool {
+        matches!(self, Self::Key(_))
+    }
+
+    /// Returns `true` for the index of an array element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jsonmodem::PathComponent;
+    ///
+    /// assert!(PathComponent::from(0usize).is_index());
+    /// assert!(!PathComponent::from("name").is_index());
+    /// ```
     #[must_use]
-    /// Returns the key if this component is a key, otherwise `None`.
-    pub fn as_key(&self) -> Option<Key> {
-        if let Self::Key(v) = self {
-            Some(v.clone())
-        } else {
-            None
+    pub fn is_index(&self) -> bool {
+        matches!(self, Self::Index(_))
+    }
+
+    /// Returns the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the component is an index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jsonmodem::{ParseEvent, ParserOptions, StreamingParser};
+    ///
+    /// let mut parser = StreamingParser::new(ParserOptions::default());
+    /// let keys: Vec<String> = parser
+    ///     .feed(r#"{"a": 1, "b": 2}"#)
+    ///     .filter_map(|event| match event.unwrap() {
+    ///         ParseEvent::Number { path, .. } => Some(path[0].unwrap_key().to_string()),
+    ///         _ => None,
+    ///     })
+    ///     .collect();
+    /// assert_eq!(keys, ["a", "b"]);
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn unwrap_key(&self) -> &str {
+        match self {
+            Self::Key(key) => key,
+            Self::Index(index) => {
+                panic!("called `PathComponent::unwrap_key` on index {index}")
+            }
+        }
+    }
+
+    /// Returns the index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the component is a key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jsonmodem::path;
+    ///
+    /// let path = path!["items", 2];
+    /// assert_eq!(path[1].unwrap_index(), 2);
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn unwrap_index(&self) -> Index {
+        match self {
+            Self::Key(key) => {
+                panic!("called `PathComponent::unwrap_index` on key {key:?}")
+            }
+            Self::Index(index) => *index,
         }
     }
 }
@@ -297,10 +408,39 @@ pub enum ParseEvent<V: JsonValue = Value> {
         )]
         is_final: bool,
     },
+    /// A whole JSON string value that arrived in several fragments.
+    ///
+    /// Never emitted by the parser itself; [`StringCoalescingAdapter`]
+    /// replaces the `String` fragments of a value with this event.
+    ///
+    /// [`StringCoalescingAdapter`]: crate::StringCoalescingAdapter
+    StringComplete {
+        /// The path to the string value.
+        path: Vec<PathComponent>,
+        /// The value of the string.
+        value: V::Str,
+    },
     /// Marks the start of a JSON array.
     ArrayStart {
         /// The path to the value.
         path: Vec<PathComponent>,
+        /// A lower bound on the number of elements, for reserving capacity.
+        ///
+        /// Only set when option `lookahead_capacity_hints` is set.
+        #[cfg_attr(
+            any(test, feature = "serde"),
+            serde(default, skip_serializing_if = "Option::is_none")
+        )]
+        capacity_hint: Option<usize>,
+        /// The key of the object member whose value this array is, or `None`
+        /// for an array element or a root.
+        ///
+        /// Only set when option `attach_parent_keys` is set.
+        #[cfg_attr(
+            any(test, feature = "serde"),
+            serde(default, skip_serializing_if = "Option::is_none")
+        )]
+        parent_key: Option<Key>,
     },
     /// Marks the end of a JSON array, optionally including its value.
     ArrayEnd {
@@ -319,6 +459,23 @@ pub enum ParseEvent<V: JsonValue = Value> {
     ObjectBegin {
         /// The path to the value.
         path: Vec<PathComponent>,
+        /// A lower bound on the number of members, for reserving capacity.
+        ///
+        /// Only set when option `lookahead_capacity_hints` is set.
+        #[cfg_attr(
+            any(test, feature = "serde"),
+            serde(default, skip_serializing_if = "Option::is_none")
+        )]
+        capacity_hint: Option<usize>,
+        /// The key of the object member whose value this object is, or `None`
+        /// for an array element or a root.
+        ///
+        /// Only set when option `attach_parent_keys` is set.
+        #[cfg_attr(
+            any(test, feature = "serde"),
+            serde(default, skip_serializing_if = "Option::is_none")
+        )]
+        parent_key: Option<Key>,
     },
     /// Marks the end of a JSON object, optionally including its value.
     ObjectEnd {
@@ -333,6 +490,166 @@ pub enum ParseEvent<V: JsonValue = Value> {
         )]
         value: Option<V::Object>,
     },
+    /// Marks the start of a scalar value, before its own event (or the first
+    /// fragment of a string).
+    ///
+    /// Only emitted when option `scalar_boundary_events` is set.
+    ScalarBegin {
+        /// The path to the value.
+        path: Vec<PathComponent>,
+    },
+    /// Marks the end of a scalar value, after its own event (or the final
+    /// fragment of a string).
+    ///
+    /// Only emitted when option `scalar_boundary_events` is set.
+    ScalarEnd {
+        /// The path to the value.
+        path: Vec<PathComponent>,
+    },
+    /// Marks the key of an object member, before the first event of its
+    /// value.
+    ///
+    /// Only emitted when option `emit_key_events` is set.
+    ObjectKey {
+        /// The path to the member's value, ending with its key.
+        path: Vec<PathComponent>,
+        /// The key, shared with the last component of `path`.
+        key: Key,
+    },
+}
+
+impl<V: JsonValue> ParseEvent<V> {
+    /// Returns the path of the value the event refers to, which every
+    /// variant carries.
+    ///
+    /// The path borrows from the event; use
+    /// [`path_clone`](Self::path_clone) to keep it after the event is
+    /// dropped.
+    #[must_use]
+    pub fn path(&self) -> &[PathComponent] {
+        match self {
+            Self::Null { path }
+            | Self::Boolean { path, .. }
+            | Self::Number { path, .. }
+            | Self::String { path, .. }
+            | Self::StringComplete { path, .. }
+            | Self::ArrayStart { path, .. }
+            | Self::ArrayEnd { path, .. }
+            | Self::ObjectBegin { path, .. }
+            | Self::ObjectEnd { path, .. }
+            | Self::ScalarBegin { path }
+            | Self::ScalarEnd { path }
+            | Self::ObjectKey { path, .. } => path,
+        }
+    }
+
+    /// Returns an owned copy of the event's [`path`](Self::path), e.g. to use
+    /// as a map key.
+    ///
+    /// # Examples
+    ///
+    /// Summing the numbers at each path across several documents:
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use jsonmodem::{ParseEvent, ParserOptions, PathComponent, StreamingParser, path};
+    ///
+    /// let mut parser = StreamingParser::new(ParserOptions {
+    ///     allow_multiple_json_values: true,
+    ///     ..ParserOptions::default()
+    /// });
+    /// let mut totals: HashMap<Vec<PathComponent>, f64> = HashMap::new();
+    /// for event in parser.feed(r#"{"a": 1, "b": [2]} {"a": 3} "#) {
+    ///     let event = event?;
+    ///     if let ParseEvent::Number { value, .. } = event {
+    ///         *totals.entry(event.path_clone()).or_default() += value;
+    ///     }
+    /// }
+    /// assert_eq!(totals[&path!["a"]], 4.0);
+    /// assert_eq!(totals[&path!["b", 0]], 2.0);
+    /// # Ok::<(), jsonmodem::ParserError>(())
+    /// ```
+    #[must_use]
+    pub fn path_clone(&self) -> Vec<PathComponent> {
+        self.path().to_vec()
+    }
+
+    /// Returns every prefix of the event's path, from the root (the empty
+    /// path) to the path itself.
+    ///
+    /// This is the chain of containers enclosing the value, followed by the
+    /// value's own path, e.g. to unwind several levels of a consumer's own
+    /// stack on an `ObjectEnd`.
+    ///
+    /// # Examples
+    ///
+    /// A breadcrumb trail for the value an event refers to:
+    ///
+    /// ```
+    /// use jsonmodem::{ParseEvent, PathComponent, Value, path};
+    ///
+    /// let event = ParseEvent::<Value>::Null {
+    ///     path: path!["users", 0, "name"],
+    /// };
+    /// let trail: Vec<String> = event
+    ///     .ancestors()
+    ///     .map(|prefix| match prefix.last() {
+    ///         None => "root".to_string(),
+    ///         Some(PathComponent::Key(key)) => key.to_string(),
+    ///         Some(PathComponent::Index(index)) => format!("#{index}"),
+    ///     })
+    ///     .collect();
+    /// assert_eq!(trail.join(" › "), "root › users › #0 › name");
+    /// ```
+    pub fn ancestors(&self) -> impl DoubleEndedIterator<Item = &[PathComponent]> + '_ {
+        let path = self.path();
+        (0..=path.len()).map(move |len| &path[..len])
+    }
+
+    /// Returns the nesting depth of the value the event refers to: the
+    /// number of components in its path.
+    ///
+    /// A root value has depth 0 and a member of the root container depth 1.
+    /// The begin and end events of a container both carry the container's own
+    /// path, so they report the same depth, one less than the depth of its
+    /// members. This is the indentation level of the value's first line in
+    /// pretty-printed output.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jsonmodem::{ParserOptions, StreamingParser};
+    ///
+    /// let mut parser = StreamingParser::new(ParserOptions::default());
+    /// let depths: Vec<usize> = parser
+    ///     .feed(r#"{"a": [1]}"#)
+    ///     .map(|event| event.unwrap().depth())
+    ///     .collect();
+    /// assert_eq!(depths, [0, 1, 2, 1, 0]);
+    /// ```
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.path().len()
+    }
+
+    /// Mutable access to the path carried by every event variant.
+    pub(crate) fn path_mut(&mut self) -> &mut Vec<PathComponent> {
+        match self {
+            Self::Null { path }
+            | Self::Boolean { path, .. }
+            | Self::Number { path, .. }
+            | Self::String { path, .. }
+            | Self::StringComplete { path, .. }
+            | Self::ArrayStart { path, .. }
+            | Self::ArrayEnd { path, .. }
+            | Self::ObjectBegin { path, .. }
+            | Self::ObjectEnd { path, .. }
+            | Self::ScalarBegin { path }
+            | Self::ScalarEnd { path }
+            | Self::ObjectKey { path, .. } => path,
+        }
+    }
 }
 
 /// Reconstructs the fully materialised JSON root values from a stream of
@@ -385,13 +702,13 @@ where
             // ----------------------------------------------------------------------------------
             // Container open – insert an empty placeholder so that later children have a slot to
             // land in.
-            ParseEvent::ArrayStart { path } => {
+            ParseEvent::ArrayStart { path, .. } => {
                 insert_at_path(&mut current_root, path, Value::Array(Vec::new()));
                 if path.is_empty() {
                     building_root = true;
                 }
             }
-            ParseEvent::ObjectBegin { path } => {
+            ParseEvent::ObjectBegin { path, .. } => {
                 insert_at_path(&mut current_root, path, Value::Object(Map::new()));
                 if path.is_empty() {
                     building_root = true;
@@ -450,6 +767,14 @@ where
                     building_root = true;
                 }
             }
+            ParseEvent::StringComplete { path, value } => {
+                insert_at_path(&mut current_root, path, Value::String(value.clone()));
+                if path.is_empty() {
+                    finished_roots.push(Value::String(value.clone()));
+                    current_root = Value::Null;
+                    building_root = false;
+                }
+            }
 
             // ----------------------------------------------------------------------------------
             // Container close – push the fully built root when the closed container sits at the top
@@ -461,6 +786,9 @@ where
                     building_root = false;
                 }
             }
+            ParseEvent::ScalarBegin { .. }
+            | ParseEvent::ScalarEnd { .. }
+            | ParseEvent::ObjectKey { .. } => {}
         }
     }
 
@@ -640,6 +968,65 @@ fn append_string_at_path(target: &mut Value, path: &[PathComponent], fragment: &
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::path;
+
+    #[test]
+    fn accessors_match_variants() {
+        let key = PathComponent::from("a");
+        let index = PathComponent::from(7usize);
+        assert_eq!((key.as_key(), key.as_index()), (Some("a"), None));
+        assert_eq!((index.as_key(), index.as_index()), (None, Some(7)));
+        assert!(key.is_key() && !key.is_index());
+        assert!(index.is_index() && !index.is_key());
+        assert_eq!(key.unwrap_key(), "a");
+        assert_eq!(index.unwrap_index(), 7);
+    }
+
+    #[test]
+    #[should_panic(expected = "called `PathComponent::unwrap_key` on index 7")]
+    fn unwrap_key_panics_on_index() {
+        let _ = PathComponent::from(7usize).unwrap_key();
+    }
+
+    #[test]
+    #[should_panic(expected = "called `PathComponent::unwrap_index` on key \"a\"")]
+    fn unwrap_index_panics_on_key() {
+        let _ = PathComponent::from("a").unwrap_index();
+    }
+
+    #[test]
+    fn depth_is_path_length() {
+        let begin = ParseEvent::<Value>::ObjectBegin {
+            path: path!["a", "b"],
+            capacity_hint: None,
+            parent_key: None,
+        };
+        assert_eq!(begin.depth(), 2);
+        let end = ParseEvent::<Value>::ObjectEnd {
+            path: path!["a", "b"],
+            value: None,
+        };
+        assert_eq!(end.depth(), begin.depth());
+        assert_eq!(ParseEvent::<Value>::Null { path: Vec::new() }.depth(), 0);
+    }
+
+    #[test]
+    fn ancestors_run_from_root_to_path() {
+        let event = ParseEvent::<Value>::ObjectEnd {
+            path: path!["a", 1],
+            value: None,
+        };
+        let prefixes: Vec<_> = event.ancestors().collect();
+        assert_eq!(prefixes, [&[][..], &path!["a"], &path!["a", 1]]);
+        assert_eq!(event.ancestors().next_back(), Some(&path!["a", 1][..]));
+
+        let root = ParseEvent::<Value>::ArrayStart {
+            path: Vec::new(),
+            capacity_hint: None,
+            parent_key: None,
+        };
+        assert_eq!(root.ancestors().count(), 1);
+    }
 
     #[test]
     fn size_of_path_component() {
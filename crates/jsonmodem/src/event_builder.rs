@@ -0,0 +1,342 @@
+//! Construction of `ParseEvent` sequences without parsing.
+//!
+//! Tests of adapters and writers need event sequences, and writing out every
+//! `ParseEvent` by hand is verbose and easy to get subtly wrong. An
+//! [`EventBuilder`] appends events through a fluent API and checks, as they
+//! are added, that they describe well-nested values with the paths the parser
+//! would give them. Built events carry no container or string `value`s, as
+//! with the default `ParserOptions`.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{EventBuilder, ParserOptions, StreamingParser, path};
+//!
+//! let events = EventBuilder::new()
+//!     .begin_object(path![])
+//!     .string(path!["key"], "val", false)
+//!     .string(path!["key"], "ue", true)
+//!     .begin_array(path!["list"])
+//!     .number(path!["list", 0], 1.0)
+//!     .end_array(path!["list"])
+//!     .end_object(path![])
+//!     .build();
+//!
+//! let mut parser = StreamingParser::new(ParserOptions::default());
+//! let mut parsed = Vec::new();
+//! for chunk in [r#"{"key": "val"#, r#"ue", "list": [1]}"#] {
+//!     for event in parser.feed(chunk) {
+//!         parsed.push(event?);
+//!     }
+//! }
+//! assert_eq!(events, parsed);
+//! # Ok::<(), jsonmodem::ParserError>(())
+//! ```
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{JsonValue, ParseEvent, PathComponent, Value};
+
+/// Error returned by [`EventBuilder::try_build`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventBuilderError {
+    /// An event does not continue the sequence, e.g. a mismatched end event,
+    /// or an event whose path is not that of the next value.
+    Unexpected {
+        /// The position of the event in the sequence, from zero.
+        index: usize,
+    },
+    /// The sequence ended inside an array, an object or a string.
+    Incomplete,
+}
+
+impl fmt::Display for EventBuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventBuilderError::Unexpected { index } => {
+                write!(f, "event {index} does not continue the sequence")
+            }
+            EventBuilderError::Incomplete => f.write_str("sequence ended inside a value"),
+        }
+    }
+}
+
+impl core::error::Error for EventBuilderError {}
+
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    is_array: bool,
+    len: usize,
+}
+
+/// A builder of well-nested [`ParseEvent`] sequences.
+///
+/// A sequence may hold several root values, as when the parser allows
+/// multiple values. Once an event is rejected, later events are ignored and
+/// the error is reported by [`build`](Self::build) or
+/// [`try_build`](Self::try_build).
+#[derive(Debug, Clone)]
+pub struct EventBuilder<V: JsonValue = Value> {
+    events: Vec<ParseEvent<V>>,
+    frames: Vec<Frame>,
+    in_string: bool,
+    error: Option<EventBuilderError>,
+}
+
+impl<V: JsonValue> Default for EventBuilder<V> {
+    fn default() -> Self {
+        Self {
+            events: Vec::new(),
+            frames: Vec::new(),
+            in_string: false,
+            error: None,
+        }
+    }
+}
+
+impl EventBuilder {
+    /// Creates an empty builder of events with [`Value`]s. Use
+    /// `EventBuilder::default()` for other value types.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<V: JsonValue> EventBuilder<V> {
+    /// Appends a `Null` event.
+    #[must_use]
+    pub fn null(self, path: Vec<PathComponent>) -> Self {
+        self.event(ParseEvent::Null { path })
+    }
+
+    /// Appends a `Boolean` event.
+    #[must_use]
+    pub fn boolean(self, path: Vec<PathComponent>, value: V::Bool) -> Self {
+        self.event(ParseEvent::Boolean { path, value })
+    }
+
+    /// Appends a `Number` event.
+    #[must_use]
+    pub fn number(self, path: Vec<PathComponent>, value: V::Num) -> Self {
+        self.event(ParseEvent::Number { path, value })
+    }
+
+    /// Appends a `String` event holding one fragment of a string. Further
+    /// fragments of the same string follow until one is final.
+    #[must_use]
+    pub fn string(
+        self,
+        path: Vec<PathComponent>,
+        fragment: impl Into<V::Str>,
+        is_final: bool,
+    ) -> Self {
+        self.event(ParseEvent::String {
+            path,
+            value: None,
+            fragment: fragment.into(),
+            is_final,
+        })
+    }
+
+    /// Appends an `ArrayStart` event.
+    #[must_use]
+    pub fn begin_array(self, path: Vec<PathComponent>) -> Self {
+        self.event(ParseEvent::ArrayStart {
+            path,
+            capacity_hint: None,
+            parent_key: None,
+        })
+    }
+
+    /// Appends an `ArrayEnd` event.
+    #[must_use]
+    pub fn end_array(self, path: Vec<PathComponent>) -> Self {
+        self.event(ParseEvent::ArrayEnd { path, value: None })
+    }
+
+    /// Appends an `ObjectBegin` event.
+    #[must_use]
+    pub fn begin_object(self, path: Vec<PathComponent>) -> Self {
+        self.event(ParseEvent::ObjectBegin {
+            path,
+            capacity_hint: None,
+            parent_key: None,
+        })
+    }
+
+    /// Appends an `ObjectEnd` event.
+    #[must_use]
+    pub fn end_object(self, path: Vec<PathComponent>) -> Self {
+        self.event(ParseEvent::ObjectEnd { path, value: None })
+    }
+
+    /// Appends any event, e.g. one carrying a `value`, after checking that it
+    /// continues the sequence.
+    #[must_use]
+    pub fn event(mut self, event: ParseEvent<V>) -> Self {
+        if self.error.is_none() && !self.accept(&event) {
+            self.error = Some(EventBuilderError::Unexpected {
+                index: self.events.len(),
+            });
+        }
+        if self.error.is_none() {
+            self.events.push(event);
+        }
+        self
+    }
+
+    /// Returns the events.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error if an event was rejected, or
+    /// [`EventBuilderError::Incomplete`] if a value is still open.
+    pub fn try_build(self) -> Result<Vec<ParseEvent<V>>, EventBuilderError> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        if !self.frames.is_empty() || self.in_string {
+            return Err(EventBuilderError::Incomplete);
+        }
+        Ok(self.events)
+    }
+
+    /// Returns the events.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`try_build`](Self::try_build) would return an error.
+    #[must_use]
+    #[track_caller]
+    pub fn build(self) -> Vec<ParseEvent<V>> {
+        match self.try_build() {
+            Ok(events) => events,
+            Err(err) => panic!("unbalanced events: {err}"),
+        }
+    }
+
+    /// Updates the open containers for `event`, or returns `false` if it
+    /// does not continue the sequence.
+    fn accept(&mut self, event: &ParseEvent<V>) -> bool {
+        let is_string = matches!(event, ParseEvent::String { .. });
+        if self.in_string {
+            if !is_string || event.path().len() != self.frames.len() {
+                return false;
+            }
+        } else if !matches!(
+            event,
+            ParseEvent::ArrayEnd { .. }
+                | ParseEvent::ObjectEnd { .. }
+                | ParseEvent::ScalarBegin { .. }
+                | ParseEvent::ScalarEnd { .. }
+                | ParseEvent::ObjectKey { .. }
+        ) && !self.begin_value(event.path())
+        {
+            return false;
+        }
+        match event {
+            ParseEvent::String { is_final, .. } => self.in_string = !*is_final,
+            ParseEvent::ArrayStart { .. } | ParseEvent::ObjectBegin { .. } => {
+                self.frames.push(Frame {
+                    is_array: matches!(event, ParseEvent::ArrayStart { .. }),
+                    len: 0,
+                });
+            }
+            ParseEvent::ArrayEnd { path, .. } | ParseEvent::ObjectEnd { path, .. } => {
+                let is_array = matches!(event, ParseEvent::ArrayEnd { .. });
+                return self.frames.pop().is_some_and(|frame| {
+                    frame.is_array == is_array && path.len() == self.frames.len()
+                });
+            }
+            ParseEvent::ScalarBegin { path } | ParseEvent::ScalarEnd { path } => {
+                return path.len() == self.frames.len();
+            }
+            ParseEvent::ObjectKey { path, key } => {
+                return path.len() == self.frames.len()
+                    && self.frames.last().is_some_and(|frame| !frame.is_array)
+                    && path.last().and_then(PathComponent::as_key) == Some(&**key);
+            }
+            _ => {}
+        }
+        true
+    }
+
+    /// Counts a new value in the innermost container, or returns `false` if
+    /// `path` is not the path of its next value.
+    fn begin_value(&mut self, path: &[PathComponent]) -> bool {
+        if path.len() != self.frames.len() {
+            return false;
+        }
+        let Some(frame) = self.frames.last_mut() else {
+            return true;
+        };
+        let valid = match path.last() {
+            Some(PathComponent::Index(index)) => frame.is_array && *index == frame.len,
+            Some(PathComponent::Key(_)) => !frame.is_array,
+            None => false,
+        };
+        frame.len += usize::from(valid);
+        valid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::{EventBuilder, EventBuilderError};
+    use crate::{ParseEvent, path};
+
+    #[test]
+    fn multiple_roots_and_value_events() {
+        let events = EventBuilder::new()
+            .null(Vec::new())
+            .begin_array(Vec::new())
+            .boolean(path![0], true)
+            .event(ParseEvent::ArrayEnd {
+                path: Vec::new(),
+                value: Some(crate::vec![true.into()]),
+            })
+            .build();
+        assert_eq!(events.len(), 4);
+    }
+
+    #[test]
+    fn imbalance_is_rejected() {
+        let builder = || EventBuilder::new().begin_array(Vec::new());
+        assert_eq!(
+            builder().end_object(Vec::new()).try_build(),
+            Err(EventBuilderError::Unexpected { index: 1 })
+        );
+        assert_eq!(
+            builder().null(path![1]).end_array(Vec::new()).try_build(),
+            Err(EventBuilderError::Unexpected { index: 1 })
+        );
+        assert_eq!(
+            builder().null(path!["a"]).try_build(),
+            Err(EventBuilderError::Unexpected { index: 1 })
+        );
+        assert_eq!(
+            builder()
+                .string(path![0], "a", false)
+                .null(path![1])
+                .try_build(),
+            Err(EventBuilderError::Unexpected { index: 2 })
+        );
+        assert_eq!(
+            builder().null(path![0]).try_build(),
+            Err(EventBuilderError::Incomplete)
+        );
+        assert_eq!(
+            EventBuilder::new().end_array(Vec::new()).try_build(),
+            Err(EventBuilderError::Unexpected { index: 0 })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "unbalanced events: sequence ended inside a value")]
+    fn build_panics_on_imbalance() {
+        let _ = EventBuilder::new().begin_object(Vec::new()).build();
+    }
+}
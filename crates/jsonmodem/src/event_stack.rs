@@ -4,7 +4,7 @@ use alloc::vec::Vec;
 use core::fmt::Debug;
 
 use crate::{
-    JsonValue, JsonValueFactory, ParseEvent,
+    JsonValue, JsonValueFactory, ParseEvent, PathComponent,
     value_zipper::{ValueBuilder, ZipperError},
 };
 
@@ -19,12 +19,31 @@ impl<V: JsonValue> EventStack<V> {
         Self { events, builder }
     }
 
+    /// Deep-copies the stack. `path` is the path to the innermost open
+    /// container, used to rebuild the value builder's cursor in the copy.
+    pub(crate) fn clone_along(&self, path: &[PathComponent]) -> Self {
+        Self {
+            events: self.events.clone(),
+            builder: self.builder.as_ref().map(|b| b.clone_along(path)),
+        }
+    }
+
     #[cfg(any(test, feature = "fuzzing"))]
     #[inline(always)]
     pub(crate) fn len(&self) -> usize {
         self.events.len()
     }
 
+    /// The events waiting to be pulled.
+    pub(crate) fn pending(&self) -> &[ParseEvent<V>] {
+        &self.events
+    }
+
+    /// Returns `true` if the stack builds values from its events.
+    pub(crate) fn builds_values(&self) -> bool {
+        self.builder.is_some()
+    }
+
     #[inline(always)]
     pub(crate) fn pop(&mut self) -> Option<ParseEvent<V>> {
         self.events.pop()
@@ -71,13 +90,13 @@ impl<V: JsonValue> EventStack<V> {
                 }
 
                 // ── container starts ───────────────────────────────────────
-                ParseEvent::ObjectBegin { path } => {
+                ParseEvent::ObjectBegin { path, .. } => {
                     builder.enter_with(path.last(), f, |fac| {
                         let v = fac.new_object();
                         fac.build_from_object(v)
                     })?;
                 }
-                ParseEvent::ArrayStart { path } => {
+                ParseEvent::ArrayStart { path, .. } => {
                     builder.enter_with(path.last(), f, |fac| {
                         let v = fac.new_array();
                         fac.build_from_array(v)
@@ -122,6 +141,12 @@ impl<V: JsonValue> EventStack<V> {
                         return Err(ZipperError::ExpectedObject);
                     }
                 }
+
+                // Added after the parser, so never seen here.
+                ParseEvent::StringComplete { .. }
+                | ParseEvent::ScalarBegin { .. }
+                | ParseEvent::ScalarEnd { .. }
+                | ParseEvent::ObjectKey { .. } => {}
             }
         }
 
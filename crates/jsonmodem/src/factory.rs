@@ -45,8 +45,79 @@ pub trait JsonValueFactory {
 
     fn new_null(&mut self) -> <Self::Value as JsonValue>::Null;
     fn new_bool(&mut self, b: bool) -> <Self::Value as JsonValue>::Bool;
+
+    /// Creates the `true` and `false` values, in that order.
+    ///
+    /// A parser calls this once, at its first boolean, and reuses the pair
+    /// for every boolean after it instead of calling [`new_bool`]. Backends
+    /// whose booleans are shared singletons, such as Python's `True` and
+    /// `False`, can override this to fetch them once. The default calls
+    /// [`new_bool`] for each.
+    ///
+    /// [`new_bool`]: JsonValueFactory::new_bool
+    #[inline(always)]
+    fn init_bools(
+        &mut self,
+    ) -> (
+        <Self::Value as JsonValue>::Bool,
+        <Self::Value as JsonValue>::Bool,
+    ) {
+        (self.new_bool(true), self.new_bool(false))
+    }
     fn new_number(&mut self, n: f64) -> <Self::Value as JsonValue>::Num;
+
+    /// Creates a number from an integer literal that fits in an `i64`.
+    ///
+    /// Integers beyond 2^53 are not exactly representable as `f64`, so
+    /// factories whose number type can hold integers should override this.
+    /// The default rounds to the nearest `f64` and calls [`new_number`], which
+    /// is the same value the literal would parse to.
+    ///
+    /// [`new_number`]: JsonValueFactory::new_number
+    #[inline(always)]
+    #[expect(clippy::cast_precision_loss)]
+    fn new_number_i64(&mut self, n: i64) -> <Self::Value as JsonValue>::Num {
+        self.new_number(n as f64)
+    }
+
+    /// Creates a number from an integer literal that is too large for an `i64`
+    /// but fits in a `u64`.
+    ///
+    /// See [`new_number_i64`] for the default behavior.
+    ///
+    /// [`new_number_i64`]: JsonValueFactory::new_number_i64
+    #[inline(always)]
+    #[expect(clippy::cast_precision_loss)]
+    fn new_number_u64(&mut self, n: u64) -> <Self::Value as JsonValue>::Num {
+        self.new_number(n as f64)
+    }
     fn new_string(&mut self, s: &str) -> <Self::Value as JsonValue>::Str;
+
+    /// Creates a string holding a whole string value, read in one piece.
+    ///
+    /// A string that arrives in several fragments is created piecewise with
+    /// [`new_string`]; one that is complete in a single event, or the `value`
+    /// of a final event, is created here instead. A factory whose string type
+    /// can be shared, e.g. `Arc<str>`, can override this to hand out one copy
+    /// of values that repeat, such as enum-like fields. The default calls
+    /// [`new_string`].
+    ///
+    /// [`new_string`]: JsonValueFactory::new_string
+    #[inline(always)]
+    fn new_string_interned(&mut self, s: &str) -> <Self::Value as JsonValue>::Str {
+        self.new_string(s)
+    }
+
+    /// Creates the key of an object member from its property name.
+    ///
+    /// The key is shared by the paths of the member's events and by any
+    /// object built from them. The default allocates a new key every time;
+    /// a factory can override this to hand out a shared copy of keys it has
+    /// seen before, as [`StringPool`](crate::StringPool) does.
+    #[inline(always)]
+    fn new_key(&mut self, key: &str) -> Key {
+        key.into()
+    }
     fn new_array(&mut self) -> <Self::Value as JsonValue>::Array;
     fn new_object(&mut self) -> <Self::Value as JsonValue>::Object;
 
@@ -82,6 +153,24 @@ pub trait JsonValueFactory {
         arr: &'b mut <Self::Value as JsonValue>::Array,
         val: Self::Value,
     ) -> &'b mut Self::Value;
+
+    /// Called when an array or object is opened, before its `ArrayStart` or
+    /// `ObjectBegin` event is produced.
+    ///
+    /// `kind` is either [`ValueKind::Array`] or [`ValueKind::Object`], and
+    /// `path` is the path of the container itself. The default does nothing.
+    #[inline(always)]
+    fn on_container_begin(&mut self, _kind: ValueKind, _path: &[PathComponent]) {}
+
+    /// Called when an array or object is closed, before its `ArrayEnd` or
+    /// `ObjectEnd` event is produced.
+    ///
+    /// Every call is paired with an earlier [`on_container_begin`] for the
+    /// same path. The default does nothing.
+    ///
+    /// [`on_container_begin`]: JsonValueFactory::on_container_begin
+    #[inline(always)]
+    fn on_container_end(&mut self, _kind: ValueKind, _path: &[PathComponent]) {}
 }
 
 /// Standard zero-cost factory for the built-in [`Value`] type.
@@ -92,7 +181,7 @@ use alloc::{collections::BTreeMap, vec::Vec};
 
 use crate::{
     Str,
-    event::{Index, Key},
+    event::{Index, Key, PathComponent},
     value::Value,
 };
 
@@ -314,16 +403,46 @@ impl<F: JsonValueFactory + ?Sized> JsonValueFactory for &mut F {
         (**self).new_bool(b)
     }
 
+    #[inline(always)]
+    fn init_bools(
+        &mut self,
+    ) -> (
+        <Self::Value as JsonValue>::Bool,
+        <Self::Value as JsonValue>::Bool,
+    ) {
+        (**self).init_bools()
+    }
+
     #[inline(always)]
     fn new_number(&mut self, n: f64) -> <Self::Value as JsonValue>::Num {
         (**self).new_number(n)
     }
 
+    #[inline(always)]
+    fn new_number_i64(&mut self, n: i64) -> <Self::Value as JsonValue>::Num {
+        (**self).new_number_i64(n)
+    }
+
+    #[inline(always)]
+    fn new_number_u64(&mut self, n: u64) -> <Self::Value as JsonValue>::Num {
+        (**self).new_number_u64(n)
+    }
+
     #[inline(always)]
     fn new_string(&mut self, s: &str) -> <Self::Value as JsonValue>::Str {
         (**self).new_string(s)
     }
 
+    #[inline(always)]
+    fn new_string_interned(&mut self, s: &str) -> <Self::Value as JsonValue>::Str {
+        (**self).new_string_interned(s)
+    }
+
+    #[inline(always)]
+    fn new_key(&mut self, key: &str) -> Key {
+        (**self).new_key(key)
+    }
+
     #[inline(always)]
     fn new_array(&mut self) -> <Self::Value as JsonValue>::Array {
         (**self).new_array()
@@ -411,4 +530,311 @@ impl<F: JsonValueFactory + ?Sized> JsonValueFactory for &mut F {
     ) -> &'b mut Self::Value {
         (**self).array_push(arr, val)
     }
+
+    #[inline(always)]
+    fn on_container_begin(&mut self, kind: ValueKind, path: &[PathComponent]) {
+        (**self).on_container_begin(kind, path);
+    }
+
+    #[inline(always)]
+    fn on_container_end(&mut self, kind: ValueKind, path: &[PathComponent]) {
+        (**self).on_container_end(kind, path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::String, vec::Vec};
+
+    use super::*;
+    use crate::{ParserOptions, StreamingParser, path};
+
+    /// Delegates to [`StdValueFactory`] and records container hooks, integer
+    /// literals and the booleans created.
+    #[derive(Default)]
+    struct CountingFactory {
+        interned: Vec<String>,
+        i64s: Vec<i64>,
+        u64s: Vec<u64>,
+        opened: usize,
+        closed: usize,
+        stack: Vec<(ValueKind, Vec<PathComponent>)>,
+        max_depth: usize,
+        bools: usize,
+    }
+
+    impl JsonValueFactory for CountingFactory {
+        type Value = Value;
+
+        fn new_null(&mut self) {}
+
+        fn new_bool(&mut self, b: bool) -> bool {
+            self.bools += 1;
+            b
+        }
+
+        fn new_number(&mut self, n: f64) -> f64 {
+            n
+        }
+
+        #[expect(clippy::cast_precision_loss)]
+        fn new_number_i64(&mut self, n: i64) -> f64 {
+            self.i64s.push(n);
+            n as f64
+        }
+
+        #[expect(clippy::cast_precision_loss)]
+        fn new_number_u64(&mut self, n: u64) -> f64 {
+            self.u64s.push(n);
+            n as f64
+        }
+
+        fn new_string(&mut self, s: &str) -> Str {
+            StdValueFactory.new_string(s)
+        }
+
+        fn new_string_interned(&mut self, s: &str) -> Str {
+            self.interned.push(s.into());
+            StdValueFactory.new_string(s)
+        }
+
+        fn new_array(&mut self) -> Vec<Value> {
+            Vec::new()
+        }
+
+        fn new_object(&mut self) -> BTreeMap<Key, Value> {
+            BTreeMap::new()
+        }
+
+        fn push_string(&mut self, string: &mut Str, val: &Str) {
+            StdValueFactory.push_string(string, val);
+        }
+
+        fn push_str(&mut self, string: &mut Str, val: &str) {
+            StdValueFactory.push_str(string, val);
+        }
+
+        fn push_array(&mut self, array: &mut Vec<Value>, val: Value) {
+            array.push(val);
+        }
+
+        fn insert_object(&mut self, obj: &mut BTreeMap<Key, Value>, key: &str, val: Value) {
+            obj.insert(key.into(), val);
+        }
+
+        fn build_from_str(&mut self, s: Str) -> Value {
+            Value::String(s)
+        }
+
+        fn build_from_num(&mut self, n: f64) -> Value {
+            Value::Number(n)
+        }
+
+        fn build_from_bool(&mut self, b: bool) -> Value {
+            Value::Boolean(b)
+        }
+
+        fn build_from_null(&mut self, (): ()) -> Value {
+            Value::Null
+        }
+
+        fn build_from_array(&mut self, a: Vec<Value>) -> Value {
+            Value::Array(a)
+        }
+
+        fn build_from_object(&mut self, o: BTreeMap<Key, Value>) -> Value {
+            Value::Object(o)
+        }
+
+        fn object_insert<'a, 'b: 'a>(
+            &'a mut self,
+            obj: &'b mut BTreeMap<Key, Value>,
+            key: Key,
+            val: Value,
+        ) -> &'b mut Value {
+            StdValueFactory.object_insert(obj, key, val)
+        }
+
+        fn array_push<'a, 'b: 'a>(
+            &'a mut self,
+            arr: &'b mut Vec<Value>,
+            val: Value,
+        ) -> &'b mut Value {
+            StdValueFactory.array_push(arr, val)
+        }
+
+        fn on_container_begin(&mut self, kind: ValueKind, path: &[PathComponent]) {
+            self.opened += 1;
+            self.stack.push((kind, path.to_vec()));
+            self.max_depth = self.max_depth.max(self.stack.len());
+        }
+
+        fn on_container_end(&mut self, kind: ValueKind, path: &[PathComponent]) {
+            self.closed += 1;
+            assert_eq!(self.stack.pop(), Some((kind, path.to_vec())));
+        }
+    }
+
+    #[test]
+    fn container_hooks_are_balanced() {
+        let json = r#"{"a": [1, {"b": []}], "c": {"d": [[], {}]}, "e": "x"}"#;
+        for chunks in [1, 7, json.len()] {
+            let mut factory = CountingFactory::default();
+            let mut parser = StreamingParser::new(ParserOptions::default());
+            for chunk in crate::produce_chunks(json, chunks) {
+                for event in parser.feed_with(&mut factory, chunk) {
+                    event.unwrap();
+                }
+            }
+            for event in parser.finish_with(&mut factory) {
+                event.unwrap();
+            }
+
+            assert_eq!(factory.opened, 8);
+            assert_eq!(factory.closed, 8);
+            assert_eq!(factory.max_depth, 4);
+            assert!(factory.stack.is_empty());
+        }
+    }
+
+    #[test]
+    fn container_hooks_see_open_containers_mid_stream() {
+        let mut factory = CountingFactory::default();
+        let mut parser = StreamingParser::new(ParserOptions::default());
+        for event in parser.feed_with(&mut factory, r#"{"a": [{"b": 1"#) {
+            event.unwrap();
+        }
+
+        assert_eq!(
+            factory.stack,
+            [
+                (ValueKind::Object, Vec::new()),
+                (ValueKind::Array, path!["a"]),
+                (ValueKind::Object, path!["a", 0]),
+            ]
+        );
+        assert_eq!(factory.closed, 0);
+    }
+
+    #[test]
+    fn whole_strings_are_interned() {
+        let json = r#"["red", "green", {"k": "red"}, "a long string"]"#;
+        let modes = [
+            crate::StringValueMode::None,
+            crate::StringValueMode::Values,
+            crate::StringValueMode::Prefixes,
+        ];
+        for string_value_mode in modes {
+            let mut factory = CountingFactory::default();
+            let mut parser = StreamingParser::new(ParserOptions {
+                string_value_mode,
+                ..Default::default()
+            });
+            // The last string is split across chunks.
+            let (head, tail) = json.split_at(json.len() - 8);
+            let mut strings = String::new();
+            for event in parser.feed_with(&mut factory, head) {
+                if let crate::ParseEvent::String { fragment, .. } = event.unwrap() {
+                    strings.push_str(&fragment);
+                }
+            }
+            for event in parser.feed_with(&mut factory, tail) {
+                if let crate::ParseEvent::String { fragment, .. } = event.unwrap() {
+                    strings.push_str(&fragment);
+                }
+            }
+            assert_eq!(strings, "redgreenreda long string");
+
+            let expected: &[&str] = match string_value_mode {
+                crate::StringValueMode::None => &["red", "green", "red"],
+                _ => &[
+                    "red",
+                    "red",
+                    "green",
+                    "green",
+                    "red",
+                    "red",
+                    "a long string",
+                ],
+            };
+            assert_eq!(factory.interned, expected, "{string_value_mode:?}");
+        }
+    }
+
+    #[test]
+    fn integer_literals_use_integer_constructors() {
+        let json = "[0, 1, -2, 9007199254740993, -9223372036854775808, \
+                    18446744073709551615, 18446744073709551616, 1.0, 1e3, -0]";
+        for chunks in [1, 5, json.len()] {
+            let mut factory = CountingFactory::default();
+            let mut parser = StreamingParser::new(ParserOptions::default());
+            let mut numbers = Vec::new();
+            for chunk in crate::produce_chunks(json, chunks) {
+                for event in parser.feed_with(&mut factory, chunk) {
+                    if let crate::ParseEvent::Number { value, .. } = event.unwrap() {
+                        numbers.push(value);
+                    }
+                }
+            }
+            for event in parser.finish_with(&mut factory) {
+                event.unwrap();
+            }
+
+            assert_eq!(
+                factory.i64s,
+                [0, 1, -2, 9_007_199_254_740_993, i64::MIN],
+                "{chunks}"
+            );
+            assert_eq!(factory.u64s, [u64::MAX]);
+            assert_eq!(numbers.len(), 10);
+            assert!(numbers[9].is_sign_negative());
+        }
+    }
+
+    #[test]
+    fn numbers_split_across_two_chunks() {
+        // Each chunk is dropped before the next is fed, so a number must not
+        // borrow from the chunk its first digits arrived in.
+        let json = "[-12345678901, 18446744073709551615, 6.25e-2]";
+        for split in 1..json.len() {
+            let mut factory = CountingFactory::default();
+            let mut parser = StreamingParser::new(ParserOptions::default());
+            let mut numbers = Vec::new();
+            for chunk in [&json[..split], &json[split..]] {
+                let chunk = alloc::string::String::from(chunk);
+                for event in parser.feed_with(&mut factory, &chunk) {
+                    if let crate::ParseEvent::Number { value, .. } = event.unwrap() {
+                        numbers.push(value);
+                    }
+                }
+            }
+
+            assert_eq!(factory.i64s, [-12_345_678_901], "{split}");
+            assert_eq!(factory.u64s, [u64::MAX], "{split}");
+            assert_eq!(numbers[2].to_bits(), 0.0625f64.to_bits(), "{split}");
+        }
+    }
+
+    #[test]
+    fn booleans_are_created_once_per_parser() {
+        let json = "[true, false, {\"a\": true}, true, false]";
+        for chunks in [1, 4, json.len()] {
+            let mut factory = CountingFactory::default();
+            let mut parser = StreamingParser::new(ParserOptions::default());
+            let mut bools = Vec::new();
+            for chunk in crate::produce_chunks(json, chunks) {
+                for event in parser.feed_with(&mut factory, chunk) {
+                    if let crate::ParseEvent::Boolean { value, .. } = event.unwrap() {
+                        bools.push(value);
+                    }
+                }
+            }
+            for event in parser.finish_with(&mut factory) {
+                event.unwrap();
+            }
+
+            assert_eq!(bools, [true, false, true, true, false], "{chunks}");
+            assert_eq!(factory.bools, 2, "{chunks}");
+        }
+    }
 }
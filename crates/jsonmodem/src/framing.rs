@@ -0,0 +1,256 @@
+//! Parsing of length-prefixed JSON messages.
+//!
+//! Some protocols send each JSON message after its length in bytes, written
+//! in decimal and followed by a newline: `<len>\n<json>`. [`FramedParser`]
+//! reads the prefix, feeds exactly that many bytes to a fresh parser, and
+//! repeats for the next message. Prefixes and payloads may be split across
+//! chunks at any byte.
+//!
+//! Each message must hold one JSON value, and its events have paths relative
+//! to that value. A message is complete, and its parser finished, once its
+//! last byte has been fed.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{FramedParser, ParseEvent, ParserOptions};
+//!
+//! let mut parser = FramedParser::new(ParserOptions::default());
+//! let mut events = parser.feed(b"2\n[]4\nt")?;
+//! events.extend(parser.feed(b"rue")?);
+//! parser.finish()?;
+//! assert_eq!(events.len(), 3);
+//! assert_eq!(parser.messages(), 2);
+//! assert!(matches!(events[2], ParseEvent::Boolean { value: true, .. }));
+//! # Ok::<(), jsonmodem::FramingError>(())
+//! ```
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{ParseEvent, ParserError, ParserOptions, StreamingParser};
+
+/// The longest length prefix accepted, in digits.
+const MAX_PREFIX_DIGITS: usize = 19;
+
+/// Error returned by [`FramedParser`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum FramingError {
+    /// The length prefix is empty, too long, or contains a byte other than a
+    /// digit before its newline.
+    InvalidPrefix {
+        /// Offset in bytes of the offending byte from the start of the input.
+        byte_offset: usize,
+    },
+    /// The input ended inside a prefix or a payload.
+    Truncated,
+    /// A payload is not a single valid JSON value. The position is relative
+    /// to the start of the payload.
+    Parser(ParserError),
+}
+
+impl fmt::Display for FramingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FramingError::InvalidPrefix { byte_offset } => {
+                write!(f, "invalid length prefix at byte {byte_offset}")
+            }
+            FramingError::Truncated => f.write_str("input ended inside a message"),
+            FramingError::Parser(err) => err.fmt(f),
+        }
+    }
+}
+
+impl core::error::Error for FramingError {}
+
+impl From<ParserError> for FramingError {
+    fn from(err: ParserError) -> Self {
+        FramingError::Parser(err)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Reading the length prefix; holds the value of the digits so far.
+    Prefix { len: usize, digits: usize },
+    /// Reading a payload; holds the number of bytes still expected.
+    Payload { remaining: usize },
+}
+
+/// A streaming parser for a sequence of length-prefixed JSON messages.
+#[derive(Debug)]
+pub struct FramedParser {
+    options: ParserOptions,
+    parser: StreamingParser,
+    state: State,
+    /// Bytes of input consumed so far.
+    byte_pos: usize,
+    messages: usize,
+}
+
+impl FramedParser {
+    /// Creates a parser using `options` for every message.
+    ///
+    /// `allow_multiple_json_values` is turned off, since each message holds a
+    /// single value.
+    #[must_use]
+    pub fn new(options: ParserOptions) -> Self {
+        let options = ParserOptions {
+            allow_multiple_json_values: false,
+            ..options
+        };
+        Self {
+            options,
+            parser: StreamingParser::new(options),
+            state: State::Prefix { len: 0, digits: 0 },
+            byte_pos: 0,
+            messages: 0,
+        }
+    }
+
+    /// The number of messages completed so far.
+    #[must_use]
+    pub fn messages(&self) -> usize {
+        self.messages
+    }
+
+    /// Feeds a chunk of input, returning the events it completes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a length prefix is malformed or a payload is not a
+    /// valid JSON value. The parser should not be fed after an error.
+    pub fn feed(&mut self, mut bytes: &[u8]) -> Result<Vec<ParseEvent>, FramingError> {
+        let mut events = Vec::new();
+        while let Some((&byte, rest)) = bytes.split_first() {
+            match self.state {
+                State::Prefix { len, digits } => {
+                    let digit = byte.wrapping_sub(b'0');
+                    if byte == b'\n' && digits > 0 {
+                        self.state = State::Payload { remaining: len };
+                        if len == 0 {
+                            self.end_message(&mut events)?;
+                        }
+                    } else if digit < 10 && digits < MAX_PREFIX_DIGITS {
+                        self.state = State::Prefix {
+                            len: len * 10 + usize::from(digit),
+                            digits: digits + 1,
+                        };
+                    } else {
+                        return Err(FramingError::InvalidPrefix {
+                            byte_offset: self.byte_pos,
+                        });
+                    }
+                    self.byte_pos += 1;
+                    bytes = rest;
+                }
+                State::Payload { remaining } => {
+                    let (payload, rest) = bytes.split_at(remaining.min(bytes.len()));
+                    for event in self.parser.feed_bytes(payload)? {
+                        events.push(event?);
+                    }
+                    self.byte_pos += payload.len();
+                    self.state = State::Payload {
+                        remaining: remaining - payload.len(),
+                    };
+                    if remaining == payload.len() {
+                        self.end_message(&mut events)?;
+                    }
+                    bytes = rest;
+                }
+            }
+        }
+        Ok(events)
+    }
+
+    /// Marks the end of input.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FramingError::Truncated`] if the input ended inside a
+    /// message.
+    pub fn finish(&self) -> Result<(), FramingError> {
+        match self.state {
+            State::Prefix { digits: 0, .. } => Ok(()),
+            _ => Err(FramingError::Truncated),
+        }
+    }
+
+    /// Finishes the parser of the current message and starts the next one.
+    fn end_message(&mut self, events: &mut Vec<ParseEvent>) -> Result<(), FramingError> {
+        let parser = core::mem::replace(&mut self.parser, StreamingParser::new(self.options));
+        for event in parser.finish() {
+            events.push(event?);
+        }
+        self.state = State::Prefix { len: 0, digits: 0 };
+        self.messages += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::{FramedParser, FramingError};
+    use crate::{ParseEvent, ParserOptions, Value, event::reconstruct_values};
+
+    const FIRST: &str = r#"{"text": "héllo ☕", "n": [1, 2]}"#;
+    const SECOND: &str = "\"second\"";
+
+    fn input() -> Vec<u8> {
+        alloc::format!("{}\n{FIRST}{}\n{SECOND}", FIRST.len(), SECOND.len()).into_bytes()
+    }
+
+    #[test]
+    fn messages_split_at_every_boundary() {
+        let input = input();
+        let mut expected = reconstruct_values(
+            crate::StreamingParser::new(ParserOptions::default())
+                .feed(FIRST)
+                .map(Result::unwrap)
+                .collect::<Vec<_>>(),
+        );
+        expected.push(Value::String("second".into()));
+
+        // Splits inside each prefix, each multi-byte character and between
+        // the messages.
+        for first in 0..input.len() {
+            for second in first..input.len() {
+                let mut parser = FramedParser::new(ParserOptions::default());
+                let mut events: Vec<ParseEvent> = Vec::new();
+                events.extend(parser.feed(&input[..first]).unwrap());
+                events.extend(parser.feed(&input[first..second]).unwrap());
+                events.extend(parser.feed(&input[second..]).unwrap());
+                parser.finish().unwrap();
+                assert_eq!(parser.messages(), 2);
+                assert_eq!(reconstruct_values(events), expected, "{first} {second}");
+            }
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let mut parser = FramedParser::new(ParserOptions::default());
+        assert_eq!(
+            parser.feed(b"2\n[]x\n"),
+            Err(FramingError::InvalidPrefix { byte_offset: 4 })
+        );
+
+        let mut parser = FramedParser::new(ParserOptions::default());
+        assert_eq!(
+            parser.feed(b"\n"),
+            Err(FramingError::InvalidPrefix { byte_offset: 0 })
+        );
+
+        // A payload holding two values, or part of one.
+        let mut parser = FramedParser::new(ParserOptions::default());
+        assert!(matches!(
+            parser.feed(b"3\n1 2"),
+            Err(FramingError::Parser(_))
+        ));
+
+        let mut parser = FramedParser::new(ParserOptions::default());
+        parser.feed(b"5\n[1, ").unwrap();
+        assert_eq!(parser.finish(), Err(FramingError::Truncated));
+    }
+}
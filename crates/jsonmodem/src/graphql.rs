@@ -0,0 +1,516 @@
+//! Streaming parsing of GraphQL responses.
+//!
+//! A GraphQL response is an object with a `data` member holding the result
+//! of the query and an `errors` member listing what went wrong, either of
+//! which may be missing. [`GraphqlResponseParser`] checks that structure as
+//! the response arrives, passes the events of `data` to a handler as they
+//! are parsed, so that a large result can be rendered progressively, and
+//! collects each entry of `errors` into a [`GraphqlError`].
+//!
+//! The specification recommends sending `errors` after `data`, but a server
+//! may send it first, typically when the request failed and `data` is
+//! `null`. Errors that complete before any event of `data` are returned
+//! from [`GraphqlResponseParser::feed`] as soon as they are parsed, so that
+//! a client can report them without waiting for the rest of the response.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{GraphqlResponseParser, ParseEvent, ParserOptions, path};
+//!
+//! let mut names = Vec::new();
+//! let mut parser = GraphqlResponseParser::new(ParserOptions::default(), |event| {
+//!     if let ParseEvent::String { fragment, .. } = event {
+//!         names.push(fragment);
+//!     }
+//! });
+//! let early = parser.feed(
+//!     r#"{"errors": [{"message": "no access", "path": ["user", "email"]}],
+//!         "data": {"user": {"name": "Ann", "email": null}}}"#,
+//! )?;
+//! assert_eq!(early[0].message, "no access");
+//! assert_eq!(early[0].path, Some(path!["user", "email"]));
+//! assert_eq!(parser.finish()?.len(), 1);
+//! assert_eq!(names, ["Ann"]);
+//! # Ok::<(), jsonmodem::GraphqlResponseError>(())
+//! ```
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::{Map, ParseEvent, ParserError, ParserOptions, PathComponent, StreamingParser, Value};
+
+/// A position in the GraphQL document of the request, from the `locations`
+/// of an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GraphqlLocation {
+    /// The line, starting at 1.
+    pub line: usize,
+    /// The column, starting at 1.
+    pub column: usize,
+}
+
+/// An entry of the `errors` member of a GraphQL response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphqlError {
+    /// The description of the error.
+    pub message: String,
+    /// The positions in the request the error relates to, if given.
+    pub locations: Vec<GraphqlLocation>,
+    /// The path in `data` of the field that failed, if given.
+    pub path: Option<Vec<PathComponent>>,
+    /// Further information from the server, if given.
+    pub extensions: Option<Map>,
+}
+
+impl GraphqlError {
+    /// Reads an error from its value, or returns why it is malformed.
+    fn from_value(value: Value) -> Result<Self, &'static str> {
+        let Value::Object(mut members) = value else {
+            return Err("not an object");
+        };
+        let Some(Value::String(message)) = members.remove("message") else {
+            return Err("missing string `message`");
+        };
+        let locations = match members.remove("locations") {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::Array(locations)) => locations
+                .into_iter()
+                .map(|location| {
+                    let Value::Object(location) = location else {
+                        return None;
+                    };
+                    Some(GraphqlLocation {
+                        line: as_count(location.get("line")?)?,
+                        column: as_count(location.get("column")?)?,
+                    })
+                })
+                .collect::<Option<_>>()
+                .ok_or("invalid `locations`")?,
+            Some(_) => return Err("invalid `locations`"),
+        };
+        let path = match members.remove("path") {
+            None | Some(Value::Null) => None,
+            Some(Value::Array(path)) => Some(
+                path.into_iter()
+                    .map(|component| match component {
+                        Value::String(key) => Some(PathComponent::Key(key.into())),
+                        Value::Number(_) => as_count(&component).map(PathComponent::Index),
+                        _ => None,
+                    })
+                    .collect::<Option<_>>()
+                    .ok_or("invalid `path`")?,
+            ),
+            Some(_) => return Err("invalid `path`"),
+        };
+        let extensions = match members.remove("extensions") {
+            None | Some(Value::Null) => None,
+            Some(Value::Object(extensions)) => Some(extensions),
+            Some(_) => return Err("invalid `extensions`"),
+        };
+        Ok(Self {
+            message,
+            locations,
+            path,
+            extensions,
+        })
+    }
+}
+
+/// Returns `value` if it is a non-negative integer that fits in a `usize`.
+fn as_count(value: &Value) -> Option<usize> {
+    match *value {
+        // Integers above 2^53 cannot all be represented, so are not trusted.
+        #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        Value::Number(n) if n >= 0.0 && n % 1.0 == 0.0 && n <= 9_007_199_254_740_992.0 => {
+            Some(n as usize)
+        }
+        _ => None,
+    }
+}
+
+/// Error returned by [`GraphqlResponseParser`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphqlResponseError {
+    /// The response is not valid JSON.
+    Parser(ParserError),
+    /// The response is not an object.
+    NotAnObject,
+    /// `data` is neither an object nor `null`.
+    InvalidData,
+    /// `errors` is not an array.
+    InvalidErrors,
+    /// An entry of `errors` is not an error object.
+    InvalidError {
+        /// The position of the entry in `errors`.
+        index: usize,
+        /// What is wrong with it.
+        reason: &'static str,
+    },
+    /// With [`GraphqlResponseParser::with_strict`], the response has a member
+    /// other than `data`, `errors` and `extensions`.
+    UnknownKey(String),
+}
+
+impl fmt::Display for GraphqlResponseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphqlResponseError::Parser(err) => err.fmt(f),
+            GraphqlResponseError::NotAnObject => f.write_str("response is not an object"),
+            GraphqlResponseError::InvalidData => f.write_str("`data` is not an object or null"),
+            GraphqlResponseError::InvalidErrors => f.write_str("`errors` is not an array"),
+            GraphqlResponseError::InvalidError { index, reason } => {
+                write!(f, "invalid error at index {index}: {reason}")
+            }
+            GraphqlResponseError::UnknownKey(key) => {
+                write!(f, "unknown member {key:?} in response")
+            }
+        }
+    }
+}
+
+impl core::error::Error for GraphqlResponseError {}
+
+impl From<ParserError> for GraphqlResponseError {
+    fn from(err: ParserError) -> Self {
+        GraphqlResponseError::Parser(err)
+    }
+}
+
+/// Assembles one value from its events.
+#[derive(Debug, Default)]
+pub(crate) struct ValueBuilder {
+    /// The open containers, each with its component in its parent.
+    stack: Vec<(Option<PathComponent>, Value)>,
+    /// Fragments of the string in progress.
+    string: String,
+}
+
+impl ValueBuilder {
+    /// Adds an event of the value whose path has `depth` components,
+    /// returning the value once it is complete.
+    pub(crate) fn push(&mut self, event: ParseEvent, depth: usize) -> Option<Value> {
+        let component = match event.path() {
+            path if path.len() > depth => path.last().cloned(),
+            _ => None,
+        };
+        let value = match event {
+            ParseEvent::Null { .. } => Value::Null,
+            ParseEvent::Boolean { value, .. } => Value::Boolean(value),
+            ParseEvent::Number { value, .. } => Value::Number(value),
+            ParseEvent::String {
+                fragment, is_final, ..
+            } => {
+                self.string.push_str(&fragment);
+                if !is_final {
+                    return None;
+                }
+                Value::String(core::mem::take(&mut self.string))
+            }
+            ParseEvent::StringComplete { value, .. } => Value::String(value),
+            ParseEvent::ArrayStart { .. } => {
+                self.stack.push((component, Value::Array(Vec::new())));
+                return None;
+            }
+            ParseEvent::ObjectBegin { .. } => {
+                self.stack.push((component, Value::Object(Map::new())));
+                return None;
+            }
+            ParseEvent::ArrayEnd { .. } | ParseEvent::ObjectEnd { .. } => {
+                let (component, value) = self.stack.pop()?;
+                return self.insert(component, value);
+            }
+            ParseEvent::ScalarBegin { .. }
+            | ParseEvent::ScalarEnd { .. }
+            | ParseEvent::ObjectKey { .. } => return None,
+        };
+        self.insert(component, value)
+    }
+
+    fn insert(&mut self, component: Option<PathComponent>, value: Value) -> Option<Value> {
+        match (self.stack.last_mut(), component) {
+            (None, _) => return Some(value),
+            (Some((_, Value::Array(items))), _) => items.push(value),
+            (Some((_, Value::Object(members))), Some(PathComponent::Key(key))) => {
+                members.insert(key, value);
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Routes the events of a response.
+#[derive(Debug)]
+struct Router<B> {
+    handler: B,
+    strict: bool,
+    errors: Vec<GraphqlError>,
+    /// The entry of `errors` being assembled.
+    error: ValueBuilder,
+    seen_data: bool,
+}
+
+impl<B: FnMut(ParseEvent)> Router<B> {
+    /// Handles one event, adding errors that complete before `data` to
+    /// `early`.
+    fn route(
+        &mut self,
+        event: ParseEvent,
+        early: &mut Vec<GraphqlError>,
+    ) -> Result<(), GraphqlResponseError> {
+        let path = event.path();
+        let Some(PathComponent::Key(member)) = path.first() else {
+            return match event {
+                ParseEvent::ObjectBegin { .. } | ParseEvent::ObjectEnd { .. } => Ok(()),
+                _ => Err(GraphqlResponseError::NotAnObject),
+            };
+        };
+        match &**member {
+            "data" => {
+                if path.len() == 1
+                    && !matches!(
+                        event,
+                        ParseEvent::Null { .. }
+                            | ParseEvent::ObjectBegin { .. }
+                            | ParseEvent::ObjectEnd { .. }
+                            | ParseEvent::ObjectKey { .. }
+                            | ParseEvent::ScalarBegin { .. }
+                            | ParseEvent::ScalarEnd { .. }
+                    )
+                {
+                    return Err(GraphqlResponseError::InvalidData);
+                }
+                self.seen_data = true;
+                (self.handler)(event);
+            }
+            "errors" => match path.get(1) {
+                None => {
+                    if !matches!(
+                        event,
+                        ParseEvent::ArrayStart { .. }
+                            | ParseEvent::ArrayEnd { .. }
+                            | ParseEvent::ObjectKey { .. }
+                    ) {
+                        return Err(GraphqlResponseError::InvalidErrors);
+                    }
+                }
+                Some(&PathComponent::Index(index)) => {
+                    if let Some(value) = self.error.push(event, 2) {
+                        let error = GraphqlError::from_value(value).map_err(|reason| {
+                            GraphqlResponseError::InvalidError { index, reason }
+                        })?;
+                        if !self.seen_data {
+                            early.push(error.clone());
+                        }
+                        self.errors.push(error);
+                    }
+                }
+                Some(PathComponent::Key(_)) => return Err(GraphqlResponseError::InvalidErrors),
+            },
+            "extensions" => {}
+            _ if self.strict => return Err(GraphqlResponseError::UnknownKey(member.to_string())),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// A streaming parser for a single GraphQL response.
+///
+/// Events of `data`, including those of `data` itself, are passed to the
+/// handler with their paths unchanged, so they start with `data`. See the
+/// [module documentation](self) for how errors are reported.
+#[derive(Debug)]
+pub struct GraphqlResponseParser<B> {
+    parser: StreamingParser,
+    router: Router<B>,
+}
+
+impl<B: FnMut(ParseEvent)> GraphqlResponseParser<B> {
+    /// Creates a parser passing the events of `data` to `handler`.
+    ///
+    /// `allow_multiple_json_values` is turned off, since a response is a
+    /// single value. Unknown members of the response are ignored unless
+    /// [`with_strict`](Self::with_strict) is set.
+    #[must_use]
+    pub fn new(options: ParserOptions, handler: B) -> Self {
+        Self {
+            parser: StreamingParser::new(ParserOptions {
+                allow_multiple_json_values: false,
+                ..options
+            }),
+            router: Router {
+                handler,
+                strict: false,
+                errors: Vec::new(),
+                error: ValueBuilder::default(),
+                seen_data: false,
+            },
+        }
+    }
+
+    /// Sets whether top-level members of the response other than `data`,
+    /// `errors` and `extensions` are rejected, as the GraphQL specification
+    /// requires.
+    #[must_use]
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.router.strict = strict;
+        self
+    }
+
+    /// Feeds a chunk of the response, returning the errors it completes if
+    /// no event of `data` has been seen yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the response is not valid JSON or does not have
+    /// the structure of a GraphQL response. The parser should not be fed
+    /// after an error.
+    pub fn feed(&mut self, text: &str) -> Result<Vec<GraphqlError>, GraphqlResponseError> {
+        let mut early = Vec::new();
+        for event in self.parser.feed(text) {
+            self.router.route(event?, &mut early)?;
+        }
+        Ok(early)
+    }
+
+    /// Returns the errors of the response parsed so far.
+    #[must_use]
+    pub fn errors(&self) -> &[GraphqlError] {
+        &self.router.errors
+    }
+
+    /// Marks the end of the response, returning all of its errors.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the response is incomplete, or if its remaining
+    /// input is invalid as for [`feed`](Self::feed).
+    pub fn finish(self) -> Result<Vec<GraphqlError>, GraphqlResponseError> {
+        let Self { parser, mut router } = self;
+        let mut early = Vec::new();
+        for event in parser.finish() {
+            router.route(event?, &mut early)?;
+        }
+        Ok(router.errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::String, vec::Vec};
+
+    use super::{GraphqlError, GraphqlLocation, GraphqlResponseError, GraphqlResponseParser};
+    use crate::{
+        ParseEvent, ParserOptions, Value, event::reconstruct_values, path, produce_chunks,
+    };
+
+    type Parsed = (Vec<Vec<String>>, Vec<Value>, Vec<GraphqlError>);
+
+    /// Parses `json` in `parts` chunks, returning the messages of the errors
+    /// returned by each `feed`, the value of `data` and all the errors.
+    fn parse(json: &str, parts: usize, strict: bool) -> Result<Parsed, GraphqlResponseError> {
+        let mut data = Vec::new();
+        let mut parser =
+            GraphqlResponseParser::new(ParserOptions::default(), |mut event: ParseEvent| {
+                event.path_mut().remove(0);
+                data.push(event);
+            })
+            .with_strict(strict);
+        let mut early = Vec::new();
+        for chunk in produce_chunks(json, parts) {
+            let errors = parser.feed(chunk)?;
+            early.push(errors.into_iter().map(|error| error.message).collect());
+        }
+        let errors = parser.finish()?;
+        Ok((early, reconstruct_values(data), errors))
+    }
+
+    #[test]
+    fn routes_data_and_collects_errors() {
+        let json = r#"{"data": {"a": [1, {"b": "x"}]}, "extensions": {"cost": 1},
+            "errors": [{"message": "m", "locations": [{"line": 2, "column": 3}],
+            "path": ["a", 1, "b"], "extensions": {"code": "E"}}, {"message": "n"}]}"#;
+        for parts in [1, 7, json.len()] {
+            let (early, data, errors) = parse(json, parts, true).unwrap();
+            assert!(early.iter().all(Vec::is_empty), "{parts}");
+            assert_eq!(
+                data,
+                reconstruct_values(
+                    crate::StreamingParser::new(ParserOptions::default())
+                        .feed(r#"{"a": [1, {"b": "x"}]}"#)
+                        .map(Result::unwrap)
+                )
+            );
+            assert_eq!(errors.len(), 2);
+            assert_eq!(
+                errors[0].locations,
+                [GraphqlLocation { line: 2, column: 3 }]
+            );
+            assert_eq!(errors[0].path, Some(path!["a", 1, "b"]));
+            assert_eq!(
+                errors[0].extensions.as_ref().unwrap()["code"],
+                Value::String("E".into())
+            );
+            assert_eq!(errors[1].message, "n");
+            assert_eq!(errors[1].path, None);
+        }
+    }
+
+    #[test]
+    fn reports_errors_before_data_early() {
+        let json = r#"{"errors": [{"message": "first"}, {"message": "second"}], "data": null}"#;
+        let (early, data, errors) = parse(json, 1, false).unwrap();
+        assert_eq!(early, [["first", "second"]]);
+        assert_eq!(data, [Value::Null]);
+        assert_eq!(errors.len(), 2);
+
+        // Each error is returned by the `feed` that completes it.
+        let (early, _, _) = parse(json, json.len(), false).unwrap();
+        let early: Vec<_> = early.into_iter().filter(|e| !e.is_empty()).collect();
+        assert_eq!(early, [["first"], ["second"]]);
+    }
+
+    #[test]
+    fn rejects_malformed_responses() {
+        let err = |json: &str, strict: bool| parse(json, 1, strict).unwrap_err();
+        assert_eq!(err("[]", false), GraphqlResponseError::NotAnObject);
+        assert_eq!(
+            err(r#"{"data": [1]}"#, false),
+            GraphqlResponseError::InvalidData
+        );
+        assert_eq!(
+            err(r#"{"errors": {}}"#, false),
+            GraphqlResponseError::InvalidErrors
+        );
+        assert_eq!(
+            err(r#"{"errors": [{"message": 1}]}"#, false),
+            GraphqlResponseError::InvalidError {
+                index: 0,
+                reason: "missing string `message`"
+            }
+        );
+        assert_eq!(
+            err(
+                r#"{"errors": [{"message": "m"}, {"message": "m", "path": [true]}]}"#,
+                false
+            ),
+            GraphqlResponseError::InvalidError {
+                index: 1,
+                reason: "invalid `path`"
+            }
+        );
+        assert!(parse(r#"{"data": {}, "trace": 1}"#, 1, false).is_ok());
+        assert_eq!(
+            err(r#"{"data": {}, "trace": 1}"#, true),
+            GraphqlResponseError::UnknownKey("trace".into())
+        );
+        assert!(matches!(
+            err(r#"{"data": {}"#, false),
+            GraphqlResponseError::Parser(_)
+        ));
+    }
+}
@@ -0,0 +1,172 @@
+//! Limits on the shape of a document, checked while it streams.
+//!
+//! A consumer that collects the members of arrays or objects into its own
+//! collections can be made to allocate without bound by a document with a
+//! huge container, even when no single event is large. [`BreadthGuard`]
+//! counts the direct members of every open container as events arrive and
+//! reports the first container that grows past the limit, without building
+//! any values.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{BreadthGuard, ParserOptions, StreamingParser, path};
+//!
+//! let mut parser = StreamingParser::new(ParserOptions::default());
+//! let mut guard = BreadthGuard::new(2);
+//! let err = parser
+//!     .feed(r#"{"ok": [1, 2], "bad": [1, 2, 3]}"#)
+//!     .map(Result::unwrap)
+//!     .try_for_each(|event| guard.check(&event))
+//!     .unwrap_err();
+//! assert_eq!(err.path, path!["bad", 2]);
+//! ```
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{JsonValue, ParseEvent, PathComponent};
+
+/// Error returned by [`BreadthGuard::check`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BreadthError {
+    /// The path of the first member beyond the limit. Its parent is the
+    /// container that is too large.
+    pub path: Vec<PathComponent>,
+    /// The configured maximum number of members.
+    pub limit: usize,
+}
+
+impl fmt::Display for BreadthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "container has more than {} members at {:?}",
+            self.limit, self.path
+        )
+    }
+}
+
+impl core::error::Error for BreadthError {}
+
+/// Enforces a maximum number of direct members per array or object.
+///
+/// Feed every event of the stream to [`check`](Self::check), across as many
+/// `feed` calls as needed. Memory use is proportional to the nesting depth.
+#[derive(Debug, Clone)]
+pub struct BreadthGuard {
+    limit: usize,
+    /// Member counts of the open containers, innermost last.
+    counts: Vec<usize>,
+    /// Whether a string split across several events is in progress.
+    in_string: bool,
+}
+
+impl BreadthGuard {
+    /// Creates a guard allowing at most `limit` members per container.
+    #[must_use]
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            counts: Vec::new(),
+            in_string: false,
+        }
+    }
+
+    /// Accounts for one event.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the event starts a member that would take its
+    /// container past the limit. The guard is unchanged by a failed check.
+    pub fn check<V: JsonValue>(&mut self, event: &ParseEvent<V>) -> Result<(), BreadthError> {
+        let starts_value = match event {
+            ParseEvent::ArrayEnd { .. } | ParseEvent::ObjectEnd { .. } => {
+                self.counts.pop();
+                return Ok(());
+            }
+            ParseEvent::ScalarBegin { .. }
+            | ParseEvent::ScalarEnd { .. }
+            | ParseEvent::ObjectKey { .. } => return Ok(()),
+            ParseEvent::String { .. } => !self.in_string,
+            _ => true,
+        };
+        if let Some(count) = self.counts.last_mut().filter(|_| starts_value) {
+            if *count == self.limit {
+                return Err(BreadthError {
+                    path: event.path_clone(),
+                    limit: self.limit,
+                });
+            }
+            *count += 1;
+        }
+        match event {
+            ParseEvent::ArrayStart { .. } | ParseEvent::ObjectBegin { .. } => self.counts.push(0),
+            ParseEvent::String { is_final, .. } => self.in_string = !is_final,
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, string::String};
+
+    use super::BreadthGuard;
+    use crate::{ParserOptions, path, tests::events};
+
+    fn multiple_values() -> ParserOptions {
+        ParserOptions {
+            allow_multiple_json_values: true,
+            ..Default::default()
+        }
+    }
+
+    fn run(json: &str, parts: usize, limit: usize) -> Result<(), super::BreadthError> {
+        let mut guard = BreadthGuard::new(limit);
+        events(json, parts, multiple_values())
+            .iter()
+            .try_for_each(|event| guard.check(event))
+    }
+
+    #[test]
+    fn containers_at_the_limit_pass() {
+        let json = r#"[[1, 2, 3], {"a": "long string", "b": {}, "c": []}, null]"#;
+        for parts in [1, 4, json.len()] {
+            assert_eq!(run(json, parts, 3), Ok(()));
+        }
+    }
+
+    #[test]
+    fn arrays_beyond_the_limit_fail() {
+        let json = r#"{"a": [1, 2], "b": [[], [], [], []]}"#;
+        for parts in [1, 5, json.len()] {
+            let err = run(json, parts, 3).unwrap_err();
+            assert_eq!(err.path, path!["b", 3]);
+            assert_eq!(err.limit, 3);
+        }
+    }
+
+    #[test]
+    fn objects_beyond_the_limit_fail() {
+        let json = r#"[{"a": "x", "b": "y", "c": "zzzzzzzzzzzz"}]"#;
+        for parts in [1, 7, json.len()] {
+            assert_eq!(run(json, parts, 3), Ok(()));
+            assert_eq!(run(json, parts, 2).unwrap_err().path, path![0, "c"]);
+        }
+    }
+
+    #[test]
+    fn split_strings_count_once() {
+        let long: String = "x".repeat(64);
+        let json = format!(r#"["{long}", "{long}"]"#);
+        assert_eq!(run(&json, json.len(), 2), Ok(()));
+        assert_eq!(run(&json, json.len(), 1).unwrap_err().path, path![1]);
+    }
+
+    #[test]
+    fn roots_are_not_counted() {
+        assert_eq!(run("1 2 3 [] {}", 1, 0), Ok(()));
+        assert_eq!(run("1 [2]", 1, 0).unwrap_err().path, path![0]);
+    }
+}
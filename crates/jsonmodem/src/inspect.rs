@@ -0,0 +1,135 @@
+//! Mapping of events back to the input text.
+//!
+//! Tools that highlight or annotate JSON, e.g. an editor showing where a
+//! streamed value came from, need to know which part of the text each event
+//! stands for. [`event_byte_spans`] parses a complete document and pairs
+//! every event with the byte range of the token that produced it:
+//!
+//! * a scalar spans its literal, e.g. `true` or `-1.5e3`;
+//! * a string spans from its opening `"` to its closing `"`;
+//! * an array or object start or end spans its bracket.
+//!
+//! Whitespace, commas and colons belong to no span.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{ParseEvent, event_byte_spans};
+//!
+//! let json = r#"{"name": "Ada", "id": 7}"#;
+//! let spans: Vec<_> = event_byte_spans(json).collect::<Result<_, _>>()?;
+//! let texts: Vec<&str> = spans.iter().map(|(_, span)| &json[span.clone()]).collect();
+//! assert_eq!(texts, ["{", r#""Ada""#, "7", "}"]);
+//! assert!(matches!(spans[2].0, ParseEvent::Number { value: 7.0, .. }));
+//! # Ok::<(), jsonmodem::ParserError>(())
+//! ```
+use core::{fmt, ops::Range};
+
+use crate::{
+    ParseEvent, ParserError, ParserOptions, StdValueFactory, StreamingParser,
+    parser::ClosedStreamingParser,
+};
+
+/// Parses `json` as one complete document and returns an iterator over its
+/// events, each with the byte range in `json` of the token that produced it.
+///
+/// The document is parsed lazily, with the default [`ParserOptions`], as the
+/// iterator is advanced. A syntax error is yielded in place of the next
+/// event and ends the iteration.
+#[must_use]
+pub fn event_byte_spans(json: &str) -> EventByteSpans {
+    let mut parser = StreamingParser::new(ParserOptions::default());
+    // The input stays buffered until the events are pulled.
+    let _ = parser.feed(json);
+    EventByteSpans {
+        events: parser.finish(),
+        done: false,
+    }
+}
+
+/// Iterator returned by [`event_byte_spans`].
+pub struct EventByteSpans {
+    events: ClosedStreamingParser<StdValueFactory>,
+    done: bool,
+}
+
+impl fmt::Debug for EventByteSpans {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventByteSpans")
+            .field("done", &self.done)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Iterator for EventByteSpans {
+    type Item = Result<(ParseEvent, Range<usize>), ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.events.next()? {
+            Ok(event) => Some(Ok((event, self.events.last_token_span()))),
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::event_byte_spans;
+    use crate::ParseEvent;
+
+    fn texts(json: &str) -> Vec<&str> {
+        event_byte_spans(json)
+            .map(|span| &json[span.unwrap().1])
+            .collect()
+    }
+
+    #[test]
+    fn spans_cover_tokens() {
+        assert_eq!(
+            texts(r#" [1, -2.5e3,true , null,"a\"b", {"k": "é☕"}, []] "#),
+            [
+                "[",
+                "1",
+                "-2.5e3",
+                "true",
+                "null",
+                r#""a\"b""#,
+                "{",
+                r#""é☕""#,
+                "}",
+                "[",
+                "]",
+                "]",
+            ]
+        );
+        assert_eq!(texts("42"), ["42"]);
+        assert_eq!(texts(r#"{"a":{"b":0}}"#), ["{", "{", "0", "}", "}"]);
+    }
+
+    #[test]
+    fn spans_pair_with_events() {
+        let spans: Vec<_> = event_byte_spans(r#"{"a": [null]}"#)
+            .map(Result::unwrap)
+            .collect();
+        assert!(matches!(spans[0], (ParseEvent::ObjectBegin { .. }, ref r) if *r == (0..1)));
+        assert!(matches!(spans[1], (ParseEvent::ArrayStart { .. }, ref r) if *r == (6..7)));
+        assert!(matches!(spans[2], (ParseEvent::Null { .. }, ref r) if *r == (7..11)));
+        assert!(matches!(spans[3], (ParseEvent::ArrayEnd { .. }, ref r) if *r == (11..12)));
+        assert!(matches!(spans[4], (ParseEvent::ObjectEnd { .. }, ref r) if *r == (12..13)));
+    }
+
+    #[test]
+    fn errors_end_the_iteration() {
+        let results: Vec<_> = event_byte_spans("[1, }").collect();
+        assert_eq!(results.len(), 3);
+        assert!(results[2].is_err());
+    }
+}
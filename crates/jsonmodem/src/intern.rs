@@ -0,0 +1,160 @@
+//! Sharing of object keys across the paths of retained events.
+//!
+//! Every [`ParseEvent`] owns its path, so a consumer that keeps many events
+//! around (for example to replay or diff them later) ends up holding one
+//! allocation per key per event. [`PathInterner`] keeps a table of the keys it
+//! has seen and rewrites [`PathComponent::Key`] entries to point at a single
+//! shared `Arc<str>` for each distinct key.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{ParseEvent, ParserOptions, PathInterner, StreamingParser};
+//!
+//! let mut parser = StreamingParser::new(ParserOptions::default());
+//! parser.feed(r#"[{"id": 1}, {"id": 2}]"#);
+//!
+//! let mut interner = PathInterner::new();
+//! let events: Vec<ParseEvent> = parser
+//!     .finish()
+//!     .map(|event| interner.intern_event(event.unwrap()))
+//!     .collect();
+//!
+//! assert_eq!(events.len(), 8);
+//! assert_eq!(interner.len(), 1);
+//! ```
+use alloc::collections::BTreeSet;
+
+use crate::{
+    JsonValue, ParseEvent,
+    event::{Key, PathComponent},
+};
+
+/// A table of object keys used to deduplicate the keys in event paths.
+#[derive(Debug, Clone, Default)]
+pub struct PathInterner {
+    keys: BTreeSet<Key>,
+}
+
+impl PathInterner {
+    /// Creates an empty interner.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the shared copy of `key`, adding it to the table if it has not
+    /// been seen before.
+    pub fn intern_key(&mut self, key: &str) -> Key {
+        if let Some(existing) = self.keys.get(key) {
+            return existing.clone();
+        }
+        let key: Key = key.into();
+        self.keys.insert(key.clone());
+        key
+    }
+
+    /// Replaces every key in `path` with its shared copy.
+    pub fn intern_path(&mut self, path: &mut [PathComponent]) {
+        for component in path {
+            if let PathComponent::Key(key) = component {
+                *key = self.intern_key(key);
+            }
+        }
+    }
+
+    /// Returns `event` with every key in its path replaced by its shared copy.
+    #[must_use]
+    pub fn intern_event<V: JsonValue>(&mut self, mut event: ParseEvent<V>) -> ParseEvent<V> {
+        self.intern_path(event.path_mut());
+        event
+    }
+
+    /// The number of distinct keys in the table.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns `true` if no keys have been interned.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Removes all keys from the table.
+    ///
+    /// Events that were already interned keep their (now unshared) keys.
+    pub fn clear(&mut self) {
+        self.keys.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{sync::Arc, vec::Vec};
+
+    use super::PathInterner;
+    use crate::{ParseEvent, ParserOptions, StreamingParser, event::PathComponent};
+
+    fn key_at(path: &[PathComponent], depth: usize) -> Arc<str> {
+        match &path[depth] {
+            PathComponent::Key(key) => key.clone(),
+            PathComponent::Index(_) => panic!("expected a key at depth {depth}"),
+        }
+    }
+
+    #[test]
+    fn equal_keys_share_storage() {
+        let mut json = alloc::string::String::from("[");
+        for i in 0..100 {
+            if i > 0 {
+                json.push(',');
+            }
+            json.push_str(r#"{"name":"x","tags":{"name":true}}"#);
+        }
+        json.push(']');
+
+        let mut parser = StreamingParser::new(ParserOptions::default());
+        parser.feed(&json);
+
+        let mut interner = PathInterner::new();
+        let events: Vec<ParseEvent> = parser
+            .finish()
+            .map(|event| interner.intern_event(event.unwrap()))
+            .collect();
+
+        assert_eq!(interner.len(), 2);
+
+        let names: Vec<_> = events
+            .iter()
+            .filter_map(|event| match event {
+                ParseEvent::String { path, .. } => Some(key_at(path, 1)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(names.len(), 100);
+        assert!(names.iter().all(|key| Arc::ptr_eq(key, &names[0])));
+
+        let nested: Vec<_> = events
+            .iter()
+            .filter_map(|event| match event {
+                ParseEvent::Boolean { path, .. } => Some(key_at(path, 2)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(nested.len(), 100);
+        assert!(nested.iter().all(|key| Arc::ptr_eq(key, &names[0])));
+    }
+
+    #[test]
+    fn intern_key_returns_existing_copy() {
+        let mut interner = PathInterner::new();
+        let a = interner.intern_key("a");
+        let b = interner.intern_key("a");
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(!Arc::ptr_eq(&a, &interner.intern_key("b")));
+        interner.clear();
+        assert!(interner.is_empty());
+    }
+}
@@ -0,0 +1,317 @@
+//! Extraction of a single value by JSON Pointer (RFC 6901).
+//!
+//! [`extract`] streams the document through the parser and returns the JSON
+//! text of the value the pointer refers to, without building a [`Value`] for
+//! it or for anything else in the document.
+//!
+//! Scalars are returned as a slice of the input, exactly as written. Arrays
+//! and objects are returned as a slice of the input if they contain no
+//! whitespace, and otherwise minified into a new string.
+//!
+//! The whole document is parsed, so syntax errors after the value are still
+//! reported, and a pointer that matches more than one member because of
+//! duplicate keys is rejected.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::extract;
+//!
+//! let json = r#"{"users": [{"name": "Ada", "tags": [ "a", "b" ]}]}"#;
+//! assert_eq!(extract(json, "/users/0/name")?, r#""Ada""#);
+//! assert_eq!(extract(json, "/users/0/tags")?, r#"["a","b"]"#);
+//! # Ok::<(), jsonmodem::ExtractError>(())
+//! ```
+//!
+//! [`Value`]: crate::Value
+use alloc::{
+    borrow::Cow,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::{
+    JsonMinifier, MinifyOptions, ParseEvent, ParserError, ParserOptions, PathComponent,
+    StreamingParser,
+};
+
+/// Error returned by [`extract`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExtractError {
+    /// The pointer is not empty and does not start with `/`, or contains a
+    /// `~` not followed by `0` or `1`.
+    InvalidPointer,
+    /// The document has no value at the pointer.
+    PointerNotFound,
+    /// The pointer refers to more than one value, through duplicate keys.
+    AmbiguousPointer,
+    /// The document is not valid JSON.
+    Parser(ParserError),
+}
+
+impl fmt::Display for ExtractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtractError::InvalidPointer => f.write_str("invalid JSON pointer"),
+            ExtractError::PointerNotFound => f.write_str("no value at JSON pointer"),
+            ExtractError::AmbiguousPointer => {
+                f.write_str("JSON pointer matches more than one value")
+            }
+            ExtractError::Parser(err) => err.fmt(f),
+        }
+    }
+}
+
+impl core::error::Error for ExtractError {}
+
+impl From<ParserError> for ExtractError {
+    fn from(err: ParserError) -> Self {
+        ExtractError::Parser(err)
+    }
+}
+
+/// Returns the JSON text of the value at `pointer` in `json`.
+///
+/// # Errors
+///
+/// Returns [`ExtractError::Parser`] if `json` is not a valid document,
+/// [`ExtractError::InvalidPointer`] if `pointer` is malformed,
+/// [`ExtractError::PointerNotFound`] if there is no value at `pointer`, and
+/// [`ExtractError::AmbiguousPointer`] if there is more than one.
+pub fn extract<'src>(json: &'src str, pointer: &str) -> Result<Cow<'src, str>, ExtractError> {
+    let tokens = parse_pointer(pointer)?;
+    let mut search = Search {
+        json,
+        tokens: &tokens,
+        start: None,
+        found: None,
+    };
+    let mut parser = StreamingParser::new(ParserOptions::default());
+    let mut events = parser.feed(json);
+    while let Some(event) = events.next() {
+        search.event(&event?, events.bytes_consumed())?;
+    }
+    let mut events = parser.finish();
+    while let Some(event) = events.next() {
+        search.event(&event?, events.bytes_consumed())?;
+    }
+
+    let (start, end) = search.found.ok_or(ExtractError::PointerNotFound)?;
+    let text = &json[start..end];
+    if text.starts_with(['[', '{']) && text.contains(is_json_whitespace) {
+        let mut minifier = JsonMinifier::new(String::new(), MinifyOptions::default());
+        // Writing to a `String` cannot fail.
+        let _ = minifier.feed(text);
+        let _ = minifier.finish();
+        return Ok(Cow::Owned(minifier.into_inner()));
+    }
+    Ok(Cow::Borrowed(text))
+}
+
+/// Splits a pointer into its unescaped reference tokens.
+pub(crate) fn parse_pointer(pointer: &str) -> Result<Vec<String>, ExtractError> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let rest = pointer
+        .strip_prefix('/')
+        .ok_or(ExtractError::InvalidPointer)?;
+    rest.split('/')
+        .map(|token| {
+            let mut unescaped = String::with_capacity(token.len());
+            let mut chars = token.chars();
+            while let Some(c) = chars.next() {
+                if c != '~' {
+                    unescaped.push(c);
+                    continue;
+                }
+                match chars.next() {
+                    Some('0') => unescaped.push('~'),
+                    Some('1') => unescaped.push('/'),
+                    _ => return Err(ExtractError::InvalidPointer),
+                }
+            }
+            Ok(unescaped)
+        })
+        .collect()
+}
+
+fn is_json_whitespace(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\r')
+}
+
+/// Returns `true` if `component` is referred to by `token`. Array indices
+/// must be written without leading zeros.
+pub(crate) fn token_matches(token: &str, component: &PathComponent) -> bool {
+    match component {
+        PathComponent::Key(key) => **key == *token,
+        PathComponent::Index(index) => index.to_string() == token,
+    }
+}
+
+/// Locates the value at the pointer from the events and the parser position.
+struct Search<'a> {
+    json: &'a str,
+    tokens: &'a [String],
+    /// Where the array or object at the pointer starts, while it is open.
+    start: Option<usize>,
+    /// The byte range of the value at the pointer.
+    found: Option<(usize, usize)>,
+}
+
+impl Search<'_> {
+    /// Handles `event`, which was produced once `consumed` bytes of the input
+    /// had been lexed.
+    fn event(&mut self, event: &ParseEvent, consumed: usize) -> Result<(), ExtractError> {
+        let path = event.path();
+        if path.len() != self.tokens.len()
+            || !self
+                .tokens
+                .iter()
+                .zip(path)
+                .all(|(token, component)| token_matches(token, component))
+        {
+            return Ok(());
+        }
+        let span = match event {
+            // The opening bracket has just been lexed.
+            ParseEvent::ArrayStart { .. } | ParseEvent::ObjectBegin { .. } => {
+                self.start = Some(consumed - 1);
+                return Ok(());
+            }
+            ParseEvent::ArrayEnd { .. } | ParseEvent::ObjectEnd { .. } => {
+                (self.start.take().unwrap_or_default(), consumed)
+            }
+            ParseEvent::String {
+                is_final: false, ..
+            } => return Ok(()),
+            // The closing quote has just been lexed.
+            ParseEvent::String { .. } => (self.string_start(consumed), consumed),
+            // Numbers and literals may only be reported once the character
+            // after them has been lexed.
+            _ => self.word_span(consumed),
+        };
+        if self.found.replace(span).is_some() {
+            return Err(ExtractError::AmbiguousPointer);
+        }
+        Ok(())
+    }
+
+    /// Finds the opening quote of the string whose closing quote ends before
+    /// `end`: the last quote before it that is not escaped.
+    fn string_start(&self, end: usize) -> usize {
+        let bytes = self.json.as_bytes();
+        let mut pos = end - 1;
+        loop {
+            pos = bytes[..pos]
+                .iter()
+                .rposition(|&b| b == b'"')
+                .unwrap_or_default();
+            let backslashes = bytes[..pos]
+                .iter()
+                .rev()
+                .take_while(|&&b| b == b'\\')
+                .count();
+            if backslashes % 2 == 0 {
+                return pos;
+            }
+        }
+    }
+
+    /// Finds the number or literal ending at or shortly before `end`.
+    fn word_span(&self, end: usize) -> (usize, usize) {
+        let is_word = |b: &u8| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'-' | b'.');
+        let bytes = &self.json.as_bytes()[..end];
+        let end = bytes.iter().rposition(is_word).map_or(0, |pos| pos + 1);
+        let start = bytes[..end]
+            .iter()
+            .rposition(|b| !is_word(b))
+            .map_or(0, |pos| pos + 1);
+        (start, end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::borrow::Cow;
+
+    use super::{ExtractError, extract};
+
+    const JSON: &str = r#"{
+        "users": [
+            {"name": "Ada", "id": 1, "admin": true},
+            {"name": "Gr\"ace\\", "id": -2.5e3, "admin": null, "tags": []}
+        ],
+        "a/b": {"m~n": [1, [2, 3]]},
+        "": 0,
+        "10": "ten"
+    }"#;
+
+    #[test]
+    fn scalars_are_borrowed() {
+        for (pointer, expected) in [
+            ("/users/0/name", r#""Ada""#),
+            ("/users/0/id", "1"),
+            ("/users/0/admin", "true"),
+            ("/users/1/name", r#""Gr\"ace\\""#),
+            ("/users/1/id", "-2.5e3"),
+            ("/users/1/admin", "null"),
+            ("/users/1/tags", "[]"),
+            ("/", "0"),
+            ("/10", r#""ten""#),
+            ("/a~1b/m~0n/1/0", "2"),
+        ] {
+            let text = extract(JSON, pointer).unwrap();
+            assert!(matches!(text, Cow::Borrowed(_)), "{pointer}");
+            assert_eq!(text, expected, "{pointer}");
+        }
+        assert_eq!(extract(" 42 ", "").unwrap(), "42");
+        assert_eq!(extract("[1,2]", "/1").unwrap(), "2");
+    }
+
+    #[test]
+    fn containers_are_minified() {
+        assert_eq!(
+            extract(JSON, "/users/0").unwrap(),
+            r#"{"name":"Ada","id":1,"admin":true}"#
+        );
+        assert_eq!(extract(JSON, "/a~1b").unwrap(), r#"{"m~n":[1,[2,3]]}"#);
+        assert_eq!(extract(JSON, "/a~1b/m~0n/1").unwrap(), "[2,3]");
+        assert_eq!(
+            extract(JSON, "").unwrap(),
+            concat!(
+                r#"{"users":[{"name":"Ada","id":1,"admin":true},"#,
+                r#"{"name":"Gr\"ace\\","id":-2.5e3,"admin":null,"tags":[]}],"#,
+                r#""a/b":{"m~n":[1,[2,3]]},"":0,"10":"ten"}"#
+            )
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(extract(JSON, "users"), Err(ExtractError::InvalidPointer));
+        assert_eq!(extract(JSON, "/a~2b"), Err(ExtractError::InvalidPointer));
+        for pointer in [
+            "/missing",
+            "/users/2",
+            "/users/01",
+            "/users/-",
+            "/users/0/name/0",
+        ] {
+            assert_eq!(
+                extract(JSON, pointer),
+                Err(ExtractError::PointerNotFound),
+                "{pointer}"
+            );
+        }
+        assert_eq!(
+            extract(r#"{"a": 1, "a": [2]}"#, "/a"),
+            Err(ExtractError::AmbiguousPointer)
+        );
+        assert!(matches!(
+            extract(r#"{"a": 1, "b": }"#, "/a"),
+            Err(ExtractError::Parser(_))
+        ));
+    }
+}
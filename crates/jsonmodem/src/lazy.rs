@@ -0,0 +1,299 @@
+//! Deferred materialization of subtrees.
+//!
+//! Building a [`Value`] for every part of a large document is wasted work when
+//! only a few fields are read. [`LazyValue`] keeps the events of a subtree and
+//! builds the `Value` the first time it is accessed. [`LazyObject`] splits a
+//! root object into one `LazyValue` per member.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{LazyObject, ParserOptions, StreamingParser, Value};
+//!
+//! let mut parser = StreamingParser::new(ParserOptions::default());
+//! let mut events: Vec<_> = parser
+//!     .feed(r#"{"id": 7, "payload": [1, 2, 3]}"#)
+//!     .map(Result::unwrap)
+//!     .collect();
+//! events.extend(parser.finish().map(Result::unwrap));
+//!
+//! let object = LazyObject::from_events(events).unwrap();
+//! assert_eq!(object.get("id"), Some(&Value::Number(7.0)));
+//! assert_eq!(object.materialized(), 1);
+//! ```
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use core::cell::OnceCell;
+
+use crate::{ParseEvent, PathComponent, Value, event::Key};
+
+/// A JSON value that is built from its events on first access.
+#[derive(Debug, Clone, Default)]
+pub struct LazyValue {
+    events: Vec<ParseEvent>,
+    value: OnceCell<Value>,
+}
+
+impl LazyValue {
+    /// Creates a lazy value from the events of a single root value.
+    #[must_use]
+    pub fn new(events: Vec<ParseEvent>) -> Self {
+        Self {
+            events,
+            value: OnceCell::new(),
+        }
+    }
+
+    /// Returns the value, building it if this is the first access.
+    pub fn get(&self) -> &Value {
+        self.value.get_or_init(|| build(&self.events))
+    }
+
+    /// Returns `true` once the value has been built.
+    #[must_use]
+    pub fn is_materialized(&self) -> bool {
+        self.value.get().is_some()
+    }
+
+    /// Consumes the handle and returns the value, building it if needed.
+    #[must_use]
+    pub fn into_value(self) -> Value {
+        match self.value.into_inner() {
+            Some(value) => value,
+            None => build(&self.events),
+        }
+    }
+}
+
+/// A JSON object whose members are built on first access.
+#[derive(Debug, Clone, Default)]
+pub struct LazyObject {
+    members: BTreeMap<Key, LazyValue>,
+}
+
+impl LazyObject {
+    /// Splits the events of a root object into lazily built members.
+    ///
+    /// Returns `None` if the first event does not begin an object at the
+    /// root. Events after the end of the root object are ignored. As with
+    /// [`Value`], a repeated key keeps its last value.
+    pub fn from_events<I>(events: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = ParseEvent>,
+    {
+        let mut events = events.into_iter();
+        let Some(ParseEvent::ObjectBegin { path, .. }) = events.next() else {
+            return None;
+        };
+        if !path.is_empty() {
+            return None;
+        }
+
+        let mut members = BTreeMap::new();
+        let mut current: Option<(Key, Vec<ParseEvent>)> = None;
+        for mut event in events {
+            let key = match event.path_mut().first() {
+                None => break,
+                Some(PathComponent::Key(key)) => key,
+                Some(PathComponent::Index(_)) => return None,
+            };
+            match &mut current {
+                Some((current_key, member)) if current_key == key => member.push(event),
+                _ => {
+                    let key = key.clone();
+                    if let Some((key, member)) = current.take() {
+                        members.insert(key, LazyValue::new(member));
+                    }
+                    current = Some((key, alloc::vec![event]));
+                }
+            }
+        }
+        if let Some((key, member)) = current {
+            members.insert(key, LazyValue::new(member));
+        }
+        Some(Self { members })
+    }
+
+    /// Returns the member named `key`, building it if this is the first
+    /// access.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.members.get(key).map(LazyValue::get)
+    }
+
+    /// Returns the lazy handle for the member named `key` without building it.
+    #[must_use]
+    pub fn get_lazy(&self, key: &str) -> Option<&LazyValue> {
+        self.members.get(key)
+    }
+
+    /// Iterates over the member names in sorted order.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.members.keys().map(|key| &**key)
+    }
+
+    /// The number of members.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Returns `true` if the object has no members.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// The number of members that have been built so far.
+    #[must_use]
+    pub fn materialized(&self) -> usize {
+        self.members
+            .values()
+            .filter(|member| member.is_materialized())
+            .count()
+    }
+
+    /// Builds every member and returns the whole object.
+    #[must_use]
+    pub fn into_value(self) -> Value {
+        Value::Object(
+            self.members
+                .into_iter()
+                .map(|(key, member)| (key, member.into_value()))
+                .collect(),
+        )
+    }
+}
+
+/// Builds the value described by the events of a single value.
+fn build(events: &[ParseEvent]) -> Value {
+    let mut stack: Vec<Value> = Vec::new();
+    let mut root = Value::Null;
+    let mut string = String::new();
+
+    let mut attach = |stack: &mut Vec<Value>, path: &[PathComponent], value: Value| match (
+        stack.last_mut(),
+        path.last(),
+    ) {
+        (Some(Value::Array(items)), _) => items.push(value),
+        (Some(Value::Object(map)), Some(PathComponent::Key(key))) => {
+            map.insert(key.clone(), value);
+        }
+        (None, _) => root = value,
+        _ => {}
+    };
+
+    for event in events {
+        match event {
+            ParseEvent::Null { path } => attach(&mut stack, path, Value::Null),
+            ParseEvent::Boolean { path, value } => attach(&mut stack, path, Value::Boolean(*value)),
+            ParseEvent::Number { path, value } => attach(&mut stack, path, Value::Number(*value)),
+            ParseEvent::String {
+                path,
+                value,
+                fragment,
+                is_final,
+            } => {
+                string.push_str(fragment);
+                if *is_final {
+                    let full = core::mem::take(&mut string);
+                    attach(
+                        &mut stack,
+                        path,
+                        Value::String(value.clone().unwrap_or(full)),
+                    );
+                }
+            }
+            ParseEvent::StringComplete { path, value } => {
+                attach(&mut stack, path, Value::String(value.clone()));
+            }
+            ParseEvent::ArrayStart { .. } => stack.push(Value::Array(Vec::new())),
+            ParseEvent::ObjectBegin { .. } => stack.push(Value::Object(BTreeMap::new())),
+            ParseEvent::ArrayEnd { path, .. } | ParseEvent::ObjectEnd { path, .. } => {
+                if let Some(value) = stack.pop() {
+                    attach(&mut stack, path, value);
+                }
+            }
+            ParseEvent::ScalarBegin { .. }
+            | ParseEvent::ScalarEnd { .. }
+            | ParseEvent::ObjectKey { .. } => {}
+        }
+    }
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::{LazyObject, LazyValue};
+    use crate::{ParserOptions, Value, tests::events};
+
+    const JSON: &str = r#"{
+        "id": 42,
+        "name": "lazy",
+        "big": {"rows": [[1, 2], [3, 4]], "note": "not needed"},
+        "tags": ["a", "b"],
+        "empty": {}
+    }"#;
+
+    /// The value built eagerly from the same events.
+    fn eager(json: &str) -> Value {
+        let mut values =
+            crate::event::reconstruct_values(events(json, 1, ParserOptions::default()));
+        assert_eq!(values.len(), 1);
+        values.pop().unwrap()
+    }
+
+    #[test]
+    fn accessed_members_match_eager_value() {
+        let Value::Object(expected) = eager(JSON) else {
+            unreachable!()
+        };
+        for parts in [1, 9, JSON.len()] {
+            let object =
+                LazyObject::from_events(events(JSON, parts, ParserOptions::default())).unwrap();
+            assert_eq!(object.len(), expected.len());
+            for (key, value) in &expected {
+                assert_eq!(object.get(key), Some(value), "{key}");
+            }
+            assert_eq!(object.materialized(), expected.len());
+        }
+    }
+
+    #[test]
+    fn unaccessed_members_are_never_built() {
+        let object = LazyObject::from_events(events(JSON, 4, ParserOptions::default())).unwrap();
+        assert_eq!(object.materialized(), 0);
+
+        assert_eq!(object.get("id"), Some(&Value::Number(42.0)));
+        assert_eq!(object.get("missing"), None);
+        assert_eq!(object.materialized(), 1);
+        assert!(!object.get_lazy("big").unwrap().is_materialized());
+
+        // Repeated access reuses the built value.
+        assert_eq!(object.get("id"), Some(&Value::Number(42.0)));
+        assert_eq!(object.materialized(), 1);
+    }
+
+    #[test]
+    fn into_value_builds_everything() {
+        let object = LazyObject::from_events(events(JSON, 1, ParserOptions::default())).unwrap();
+        assert_eq!(object.into_value(), eager(JSON));
+    }
+
+    #[test]
+    fn lazy_value_of_any_root() {
+        for json in ["[1, [2, {}], \"x\"]", "\"root\"", "null", "{}"] {
+            let value = LazyValue::new(events(json, 2, ParserOptions::default()));
+            assert!(!value.is_materialized());
+            assert_eq!(value.get(), &eager(json));
+            assert!(value.is_materialized());
+        }
+    }
+
+    #[test]
+    fn non_object_roots_are_rejected() {
+        assert!(LazyObject::from_events(events("[1]", 1, ParserOptions::default())).is_none());
+        assert!(LazyObject::from_events(Vec::new()).is_none());
+    }
+}
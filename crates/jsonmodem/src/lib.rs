@@ -5,22 +5,70 @@
 #![expect(missing_docs)]
 extern crate alloc;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "std"))]
 extern crate std;
 
+mod batch;
 mod buffer;
+mod buffered;
+mod canonical;
+mod checkpoint;
+mod coalesce;
+mod csv;
+mod cursor;
+mod dedup;
+#[cfg(any(test, feature = "serde"))]
+mod deser;
+mod diff;
 mod escape_buffer;
 mod event;
+mod event_builder;
 mod factory;
+mod framing;
+mod graphql;
+mod guard;
+mod inspect;
+mod intern;
+mod json_pointer;
+mod lazy;
+mod limits;
 mod literal_buffer;
+mod merge;
+mod metrics;
+mod minify;
+#[cfg(any(test, feature = "msgpack"))]
+mod msgpack;
+mod multipart;
+mod ndjson;
+mod object_buffer;
+mod patch;
+mod pipe;
+mod pool;
+mod pretty;
+mod progress;
+mod replay;
+mod schema;
+mod selector;
+mod size_limit;
+mod snapshot;
+mod sse;
+#[cfg(any(test, feature = "tar"))]
+mod tar;
+mod tokenize;
+#[cfg(any(test, feature = "tracing"))]
+mod tracing_integration;
+mod transform;
+mod validate;
 mod value;
 mod value_zipper;
+mod xml;
 
 mod chunk_utils;
 mod error;
 mod event_stack;
 mod options;
 mod parser;
+mod path;
 mod streaming_values;
 
 #[cfg(test)]
@@ -29,14 +77,83 @@ mod tests;
 #[doc(hidden)]
 pub use alloc::vec;
 
+pub use batch::{BatchParser, Batches, FinishedBatches};
+pub use buffered::{
+    BufferOptions, BufferedEvent, ClosedJsonModemBuffers, JsonModemBuffers, JsonModemBuffersIter,
+    STRING_SINK_PLACEHOLDER, StringSink,
+};
+pub use canonical::{CanonicalParser, CanonicalizeError, canonicalize};
+pub use checkpoint::CheckpointError;
 pub use chunk_utils::{produce_chunks, produce_prefixes};
+pub use coalesce::StringCoalescingAdapter;
+pub use csv::{CsvEmitter, CsvError, CsvToJsonLines};
+pub use cursor::JsonCursor;
+pub use dedup::PathDeduplicator;
+#[cfg(any(test, all(feature = "serde", feature = "std")))]
+pub use deser::from_reader;
+#[cfg(any(test, feature = "serde"))]
+pub use deser::{DeserError, from_str};
+pub use diff::{DiffEvent, DiffValue, JsonDiff, json_diff};
 pub use error::ParserError;
 pub use event::{ParseEvent, PathComponent, PathComponentFrom};
+pub use event_builder::{EventBuilder, EventBuilderError};
 pub use factory::{JsonValue, JsonValueFactory, StdValueFactory, ValueKind};
-pub use options::{NonScalarValueMode, ParserOptions, StringValueMode};
+pub use framing::{FramedParser, FramingError};
+pub use graphql::{GraphqlError, GraphqlLocation, GraphqlResponseError, GraphqlResponseParser};
+pub use guard::{BreadthError, BreadthGuard};
+pub use inspect::{EventByteSpans, event_byte_spans};
+pub use intern::PathInterner;
+pub use json_pointer::{ExtractError, extract};
+pub use lazy::{LazyObject, LazyValue};
+pub use limits::{FinishedLimitedEvents, LimitError, LimitedEvents, LimitingParser, ParserLimits};
+pub use merge::{MergeError, json_merge_patch};
+pub use metrics::{EventCounter, MetricsReport};
+pub use minify::{JsonMinifier, MinifyOptions};
+#[cfg(any(test, feature = "msgpack"))]
+pub use msgpack::MessagePackEncoder;
+pub use multipart::{MultipartError, MultipartJsonParser};
+pub use ndjson::{NdjsonError, NdjsonWriter};
+pub use options::{
+    BomHandling, DecodeMode, KeySortMode, NonScalarValueMode, ParserOptions, StringValueMode,
+};
 pub use parser::StreamingParser;
-pub use streaming_values::{StreamingValue, StreamingValuesParser};
+pub use patch::{PatchError, PatchOperation, apply_json_patch, parse_json_patch};
+pub use path::{
+    Glob, GlobError, GlobFilter, GlobSegment, PathTrie, TrieFilter, closest_match, common_prefix,
+    filter_by_glob, filter_by_trie, is_prefix_of, path_similarity,
+};
+pub use pipe::{ParseEventWriter, PipeError, pipe_to};
+#[cfg(any(test, feature = "std"))]
+pub use pool::SharedStringPool;
+pub use pool::StringPool;
+pub use pretty::{JsonPrettyPrinter, PrettyOptions};
+pub use progress::{FinishedProgressEvents, ProgressEvents, ProgressParser, StreamPosition};
+pub use replay::{CompressedRecorder, EventRecorder};
+pub use schema::infer_typescript_types;
+#[cfg(any(test, feature = "avro"))]
+pub use schema::{AvroEncoder, AvroError, AvroField, AvroSchema};
+#[cfg(any(test, feature = "json-schema"))]
+pub use schema::{
+    JsonSchema, SchemaError, SchemaValidator, ValidatingEvents, ValidationError, ValidationIssue,
+    compile_schema, validate_streaming,
+};
+pub use selector::select_all;
+pub use size_limit::{SizeLimitError, SizeLimitedWriter, TruncateMode};
+pub use snapshot::PathSnapshot;
+pub use sse::{SseError, SseJsonParser};
+pub use streaming_values::{JsonModemValues, PartialValue, StreamingValue, StreamingValuesParser};
+#[cfg(any(test, feature = "tar"))]
+pub use tar::{TarError, TarEvent, TarJsonExtractor};
+pub use tokenize::{Token, Tokenizer};
+#[cfg(any(test, feature = "tracing"))]
+pub use tracing_integration::{FinishedTracedEvents, TracedEvents, TracingStreamingParser};
+pub use transform::{
+    FinishedRedactedEvents, MapStringFragments, MapStringValues, RedactMode, RedactedEvents,
+    RedactingParser, map_string_fragments, map_string_values,
+};
+pub use validate::{is_valid_json, validate_json};
 pub use value::{Array, Map, Str, Value};
+pub use xml::{JsonToXml, JsonToXmlOptions, XmlError};
 
 /// Macro to build a `Vec<PathComponent>` from a heterogeneous list of keys and
 /// indices.
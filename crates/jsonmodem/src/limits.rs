@@ -0,0 +1,509 @@
+//! Limits on the size and shape of a document, enforced while parsing.
+//!
+//! A service that parses untrusted input wants to reject documents that are
+//! too deep, too wide or hold oversized values before its consumers see them,
+//! and to tell callers which limit was hit. [`LimitingParser`] wraps a
+//! [`StreamingParserImpl`] and checks every event against a set of
+//! [`ParserLimits`], reporting the first violation as a [`LimitError`] that
+//! names the limit and the path of the offending value.
+//!
+//! Array items and object keys are counted per container, so a limit applies
+//! to each array or object on its own, not to the document as a whole.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{
+//!     LimitError, LimitingParser, ParserLimits, ParserOptions, StreamingParser, path,
+//! };
+//!
+//! let limits = ParserLimits {
+//!     max_array_items: Some(2),
+//!     ..Default::default()
+//! };
+//! let mut parser = LimitingParser::new(StreamingParser::new(ParserOptions::default()), limits);
+//! let err = parser
+//!     .feed(r#"{"a": [1, 2], "b": [1, 2, 3]}"#)
+//!     .find_map(Result::err)
+//!     .unwrap();
+//! assert_eq!(
+//!     err,
+//!     LimitError::ArrayItems {
+//!         path: path!["b", 2],
+//!         limit: 2
+//!     }
+//! );
+//! ```
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{
+    JsonValue, JsonValueFactory, ParseEvent, ParserError, PathComponent, StdValueFactory, Value,
+    parser::{ClosedStreamingParser, StreamingParserImpl, StreamingParserIteratorWith},
+};
+
+/// The limits checked by a [`LimitingParser`]. `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParserLimits {
+    /// The maximum number of nested arrays and objects. A root container has
+    /// depth 1.
+    pub max_depth: Option<usize>,
+    /// The maximum length of a string value in UTF-8 bytes, after unescaping.
+    /// Object keys are not counted.
+    pub max_string_bytes: Option<usize>,
+    /// The maximum length of a number, in bytes of input.
+    pub max_number_len: Option<usize>,
+    /// The maximum number of items in each array.
+    pub max_array_items: Option<usize>,
+    /// The maximum number of members in each object.
+    pub max_object_keys: Option<usize>,
+}
+
+/// Error returned by a [`LimitingParser`]: a limit was exceeded, or the input
+/// is not valid JSON.
+///
+/// Every limit variant holds the path of the value that exceeded it and the
+/// configured limit.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LimitError {
+    /// An array or object is nested too deeply.
+    Depth {
+        /// The path of the container.
+        path: Vec<PathComponent>,
+        /// The configured limit.
+        limit: usize,
+    },
+    /// A string is too long.
+    StringBytes {
+        /// The path of the string.
+        path: Vec<PathComponent>,
+        /// The configured limit.
+        limit: usize,
+    },
+    /// A number is too long.
+    NumberLength {
+        /// The path of the number.
+        path: Vec<PathComponent>,
+        /// The configured limit.
+        limit: usize,
+    },
+    /// An array has too many items.
+    ArrayItems {
+        /// The path of the first item beyond the limit.
+        path: Vec<PathComponent>,
+        /// The configured limit.
+        limit: usize,
+    },
+    /// An object has too many members.
+    ObjectKeys {
+        /// The path of the first member beyond the limit.
+        path: Vec<PathComponent>,
+        /// The configured limit.
+        limit: usize,
+    },
+    /// The input is not valid JSON.
+    Parser(ParserError),
+}
+
+impl fmt::Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (what, path, limit) = match self {
+            LimitError::Depth { path, limit } => ("nesting depth", path, limit),
+            LimitError::StringBytes { path, limit } => ("string length", path, limit),
+            LimitError::NumberLength { path, limit } => ("number length", path, limit),
+            LimitError::ArrayItems { path, limit } => ("array items", path, limit),
+            LimitError::ObjectKeys { path, limit } => ("object keys", path, limit),
+            LimitError::Parser(err) => return err.fmt(f),
+        };
+        write!(f, "{what} exceeds the limit of {limit} at {path:?}")
+    }
+}
+
+impl core::error::Error for LimitError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            LimitError::Parser(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParserError> for LimitError {
+    fn from(err: ParserError) -> Self {
+        LimitError::Parser(err)
+    }
+}
+
+/// The counters behind the limits.
+#[derive(Debug, Clone, Default)]
+struct LimitState {
+    limits: ParserLimits,
+    /// For each open container, innermost last, whether it is an array and
+    /// how many members it has.
+    containers: Vec<(bool, usize)>,
+    /// The bytes of the string in progress, if any.
+    string_bytes: Option<usize>,
+    /// Set once a limit is exceeded; no further events are produced.
+    failed: bool,
+}
+
+impl LimitState {
+    /// Checks `event`, whose token spans `token_len` bytes of input.
+    fn check<V: JsonValue>(
+        &mut self,
+        event: &ParseEvent<V>,
+        token_len: usize,
+    ) -> Result<(), LimitError>
+    where
+        V::Str: AsRef<str>,
+    {
+        let limits = self.limits;
+        let exceeded = |limit: Option<usize>, value: usize| limit.filter(|&limit| value > limit);
+        let path = || event.path_clone();
+
+        let starts_value = match event {
+            ParseEvent::ArrayEnd { .. } | ParseEvent::ObjectEnd { .. } => {
+                self.containers.pop();
+                return Ok(());
+            }
+            ParseEvent::ScalarBegin { .. }
+            | ParseEvent::ScalarEnd { .. }
+            | ParseEvent::ObjectKey { .. } => return Ok(()),
+            ParseEvent::String { .. } => self.string_bytes.is_none(),
+            _ => true,
+        };
+        if let Some((is_array, count)) = self.containers.last_mut().filter(|_| starts_value) {
+            *count += 1;
+            if *is_array {
+                if let Some(limit) = exceeded(limits.max_array_items, *count) {
+                    return Err(LimitError::ArrayItems {
+                        path: path(),
+                        limit,
+                    });
+                }
+            } else if let Some(limit) = exceeded(limits.max_object_keys, *count) {
+                return Err(LimitError::ObjectKeys {
+                    path: path(),
+                    limit,
+                });
+            }
+        }
+
+        match event {
+            ParseEvent::ArrayStart { .. } | ParseEvent::ObjectBegin { .. } => {
+                let is_array = matches!(event, ParseEvent::ArrayStart { .. });
+                self.containers.push((is_array, 0));
+                if let Some(limit) = exceeded(limits.max_depth, self.containers.len()) {
+                    return Err(LimitError::Depth {
+                        path: path(),
+                        limit,
+                    });
+                }
+            }
+            ParseEvent::Number { .. } => {
+                if let Some(limit) = exceeded(limits.max_number_len, token_len) {
+                    return Err(LimitError::NumberLength {
+                        path: path(),
+                        limit,
+                    });
+                }
+            }
+            ParseEvent::String {
+                fragment, is_final, ..
+            } => {
+                let bytes = self.string_bytes.unwrap_or(0) + fragment.as_ref().len();
+                self.string_bytes = (!is_final).then_some(bytes);
+                if let Some(limit) = exceeded(limits.max_string_bytes, bytes) {
+                    return Err(LimitError::StringBytes {
+                        path: path(),
+                        limit,
+                    });
+                }
+            }
+            ParseEvent::StringComplete { value, .. } => {
+                if let Some(limit) = exceeded(limits.max_string_bytes, value.as_ref().len()) {
+                    return Err(LimitError::StringBytes {
+                        path: path(),
+                        limit,
+                    });
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Checks the next event of `events`, ending the stream at the first
+    /// error.
+    fn next<V: JsonValue>(
+        &mut self,
+        event: Option<Result<ParseEvent<V>, ParserError>>,
+        token_len: usize,
+    ) -> Option<Result<ParseEvent<V>, LimitError>>
+    where
+        V::Str: AsRef<str>,
+    {
+        if self.failed {
+            return None;
+        }
+        let result = event?
+            .map_err(LimitError::from)
+            .and_then(|event| self.check(&event, token_len).map(|()| event));
+        self.failed = result.is_err();
+        Some(result)
+    }
+}
+
+/// A streaming parser that enforces [`ParserLimits`].
+///
+/// After the first error, from a limit or a syntax error, no more events
+/// are produced.
+#[derive(Debug)]
+pub struct LimitingParser<V: JsonValue = Value> {
+    parser: StreamingParserImpl<V>,
+    state: LimitState,
+}
+
+impl<V: JsonValue> LimitingParser<V>
+where
+    V::Str: AsRef<str>,
+{
+    /// Wraps `parser`, which should not have been fed yet, so that its events
+    /// are checked against `limits`.
+    #[must_use]
+    pub fn new(parser: StreamingParserImpl<V>, limits: ParserLimits) -> Self {
+        Self {
+            parser,
+            state: LimitState {
+                limits,
+                ..LimitState::default()
+            },
+        }
+    }
+
+    /// The limits being enforced.
+    #[must_use]
+    pub fn limits(&self) -> &ParserLimits {
+        &self.state.limits
+    }
+
+    /// Feeds a chunk of JSON text and returns an iterator over the checked
+    /// events it completes, building values with `factory`.
+    pub fn feed_with<'a, F: JsonValueFactory<Value = V>>(
+        &'a mut self,
+        factory: F,
+        text: &str,
+    ) -> LimitedEvents<'a, F> {
+        LimitedEvents {
+            events: self.parser.feed_with(factory, text),
+            state: &mut self.state,
+        }
+    }
+
+    /// Marks the end of input and returns an iterator over the remaining
+    /// checked events, building values with `factory`.
+    pub fn finish_with<F: JsonValueFactory<Value = V>>(
+        self,
+        factory: F,
+    ) -> FinishedLimitedEvents<F> {
+        FinishedLimitedEvents {
+            events: self.parser.finish_with(factory),
+            state: self.state,
+        }
+    }
+}
+
+impl LimitingParser {
+    /// Feeds a chunk of JSON text and returns an iterator over the checked
+    /// events it completes.
+    pub fn feed<'a>(&'a mut self, text: &str) -> LimitedEvents<'a, StdValueFactory> {
+        self.feed_with(StdValueFactory, text)
+    }
+
+    /// Marks the end of input and returns an iterator over the remaining
+    /// checked events.
+    #[must_use]
+    pub fn finish(self) -> FinishedLimitedEvents<StdValueFactory> {
+        self.finish_with(StdValueFactory)
+    }
+}
+
+/// Iterator returned by [`LimitingParser::feed`].
+pub struct LimitedEvents<'a, F: JsonValueFactory> {
+    events: StreamingParserIteratorWith<'a, F>,
+    state: &'a mut LimitState,
+}
+
+impl<F: JsonValueFactory> Iterator for LimitedEvents<'_, F>
+where
+    <F::Value as JsonValue>::Str: AsRef<str>,
+{
+    type Item = Result<ParseEvent<F::Value>, LimitError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.state.failed {
+            return None;
+        }
+        let event = self.events.next();
+        self.state.next(event, self.events.last_token_span().len())
+    }
+}
+
+/// Iterator returned by [`LimitingParser::finish`].
+pub struct FinishedLimitedEvents<F: JsonValueFactory> {
+    events: ClosedStreamingParser<F>,
+    state: LimitState,
+}
+
+impl<F: JsonValueFactory> Iterator for FinishedLimitedEvents<F>
+where
+    <F::Value as JsonValue>::Str: AsRef<str>,
+{
+    type Item = Result<ParseEvent<F::Value>, LimitError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.state.failed {
+            return None;
+        }
+        let event = self.events.next();
+        self.state.next(event, self.events.last_token_span().len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, vec::Vec};
+
+    use super::{LimitError, LimitingParser, ParserLimits};
+    use crate::{ParseEvent, ParserOptions, StreamingParser, path, produce_chunks};
+
+    const JSON: &str = r#"{"a": [1, 2, [3, 4, 5]], "b": {"c": "four", "d": -12.5e10}, "e": []}"#;
+
+    fn run(json: &str, parts: usize, limits: ParserLimits) -> Result<Vec<ParseEvent>, LimitError> {
+        let mut parser =
+            LimitingParser::new(StreamingParser::new(ParserOptions::default()), limits);
+        let mut events = Vec::new();
+        for chunk in produce_chunks(json, parts) {
+            for event in parser.feed(chunk) {
+                events.push(event?);
+            }
+        }
+        for event in parser.finish() {
+            events.push(event?);
+        }
+        Ok(events)
+    }
+
+    /// Checks that `limits` pass at exactly the sizes in `JSON`, and that
+    /// lowering the limit by one fails with `expected`.
+    fn check(at_limit: ParserLimits, below: ParserLimits, expected: &LimitError) {
+        for parts in [1, 6, JSON.len()] {
+            assert!(run(JSON, parts, at_limit).is_ok(), "{parts}");
+            assert_eq!(run(JSON, parts, below).as_ref(), Err(expected), "{parts}");
+        }
+    }
+
+    #[test]
+    fn each_limit_triggers_independently() {
+        let limits = |f: fn(&mut ParserLimits, usize), n| {
+            let mut limits = ParserLimits::default();
+            f(&mut limits, n);
+            limits
+        };
+        let depth: fn(&mut ParserLimits, usize) = |l, n| l.max_depth = Some(n);
+        check(
+            limits(depth, 3),
+            limits(depth, 2),
+            &LimitError::Depth {
+                path: path!["a", 2],
+                limit: 2,
+            },
+        );
+        let strings: fn(&mut ParserLimits, usize) = |l, n| l.max_string_bytes = Some(n);
+        check(
+            limits(strings, 4),
+            limits(strings, 3),
+            &LimitError::StringBytes {
+                path: path!["b", "c"],
+                limit: 3,
+            },
+        );
+        let numbers: fn(&mut ParserLimits, usize) = |l, n| l.max_number_len = Some(n);
+        check(
+            limits(numbers, 8),
+            limits(numbers, 7),
+            &LimitError::NumberLength {
+                path: path!["b", "d"],
+                limit: 7,
+            },
+        );
+        let items: fn(&mut ParserLimits, usize) = |l, n| l.max_array_items = Some(n);
+        check(
+            limits(items, 3),
+            limits(items, 2),
+            &LimitError::ArrayItems {
+                path: path!["a", 2],
+                limit: 2,
+            },
+        );
+        let keys: fn(&mut ParserLimits, usize) = |l, n| l.max_object_keys = Some(n);
+        check(
+            limits(keys, 3),
+            limits(keys, 2),
+            &LimitError::ObjectKeys {
+                path: path!["e"],
+                limit: 2,
+            },
+        );
+    }
+
+    #[test]
+    fn counts_are_per_container() {
+        // Five items in total, but no array holds more than two.
+        let json = "[[1, 2], [[3, 4], 5]]";
+        let limits = ParserLimits {
+            max_array_items: Some(2),
+            ..Default::default()
+        };
+        assert!(run(json, 1, limits).is_ok());
+        assert_eq!(
+            run("[[1, 2], [[3, 4, 5]]]", 1, limits),
+            Err(LimitError::ArrayItems {
+                path: path![1, 0, 2],
+                limit: 2
+            })
+        );
+    }
+
+    #[test]
+    fn split_strings_are_counted_whole() {
+        let json = format!(r#"["{}"]"#, "x".repeat(40));
+        let limits = |max| ParserLimits {
+            max_string_bytes: Some(max),
+            ..Default::default()
+        };
+        assert!(run(&json, json.len(), limits(40)).is_ok());
+        assert!(matches!(
+            run(&json, json.len(), limits(39)),
+            Err(LimitError::StringBytes { .. })
+        ));
+    }
+
+    #[test]
+    fn errors_end_the_stream() {
+        let limits = ParserLimits {
+            max_depth: Some(1),
+            ..Default::default()
+        };
+        let mut parser =
+            LimitingParser::new(StreamingParser::new(ParserOptions::default()), limits);
+        let results: Vec<_> = parser.feed("[[1], 2]").collect();
+        assert_eq!(results.len(), 2);
+        assert!(parser.feed("").next().is_none());
+
+        let mut parser =
+            LimitingParser::new(StreamingParser::new(ParserOptions::default()), limits);
+        let results: Vec<_> = parser.feed("[1,,").collect();
+        assert!(matches!(results.last(), Some(Err(LimitError::Parser(_)))));
+    }
+}
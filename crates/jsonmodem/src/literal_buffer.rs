@@ -38,6 +38,36 @@ impl ExpectedLiteralBuffer {
         }
     }
 
+    /// The literal being matched, as 1, 2 or 3 for `null`, `true` or `false`
+    /// (0 if none), and the number of its bytes still expected.
+    pub fn to_parts(self) -> (u8, u8) {
+        match self.0 {
+            None => (0, 0),
+            Some((rest, kind)) => (
+                match kind {
+                    ExpectedLiteralValue::Null => 1,
+                    ExpectedLiteralValue::True => 2,
+                    ExpectedLiteralValue::False => 3,
+                },
+                u8::try_from(rest.len()).unwrap_or_default(),
+            ),
+        }
+    }
+
+    /// Creates a matcher from the parts returned by
+    /// [`to_parts`](Self::to_parts), or `None` if they are out of range.
+    pub fn from_parts(kind: u8, remaining: u8) -> Option<Self> {
+        let (literal, kind): (&'static [u8], _) = match kind {
+            0 => return (remaining == 0).then_some(Self::none()),
+            1 => (b"ull", ExpectedLiteralValue::Null),
+            2 => (b"rue", ExpectedLiteralValue::True),
+            3 => (b"alse", ExpectedLiteralValue::False),
+            _ => return None,
+        };
+        let start = literal.len().checked_sub(usize::from(remaining))?;
+        (remaining > 0).then_some(ExpectedLiteralBuffer(Some((&literal[start..], kind))))
+    }
+
     /// Give the matcher the next input character and learn what to do next.
     pub fn step(&mut self, c: char) -> Step {
         // If we are not in the middle of a literal, any char is a reject
@@ -0,0 +1,307 @@
+//! JSON Merge Patch (RFC 7396).
+//!
+//! [`json_merge_patch`] applies a patch document to a base document: members
+//! of the patch replace the members of the base with the same key, objects
+//! are merged recursively, and members whose patch value is `null` are
+//! removed. Arrays and scalars in the patch replace the base value entirely.
+//!
+//! The patch is read in full first, since its members may appear in any
+//! order. The base is then streamed through a second parser and rewritten
+//! event by event, so only the patch and the output are held in memory. Base
+//! members keep their order; members added by the patch are written at the
+//! end of their object.
+//!
+//! The output is compact JSON text.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::json_merge_patch;
+//!
+//! let base = r#"{"title": "Goodbye!", "author": {"given": "John", "family": "Doe"}, "tags": ["a", "b"]}"#;
+//! let patch = r#"{"title": "Hello!", "phone": "+01-123", "author": {"family": null}, "tags": ["c"]}"#;
+//! assert_eq!(
+//!     json_merge_patch(base, patch)?,
+//!     r#"{"title":"Hello!","author":{"given":"John"},"tags":["c"],"phone":"+01-123"}"#
+//! );
+//! # Ok::<(), jsonmodem::MergeError>(())
+//! ```
+use alloc::{string::String, vec::Vec};
+use core::fmt::{self, Write};
+
+use crate::{
+    Map, NonScalarValueMode, ParseEvent, ParserError, ParserOptions, PathComponent,
+    StreamingParser, StringValueMode, Value, ndjson::CompactWriter,
+};
+
+/// Error returned by [`json_merge_patch`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeError {
+    /// The base document is not valid JSON.
+    Base(ParserError),
+    /// The patch document is not valid JSON.
+    Patch(ParserError),
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeError::Base(err) => write!(f, "invalid base document: {err}"),
+            MergeError::Patch(err) => write!(f, "invalid patch document: {err}"),
+        }
+    }
+}
+
+impl core::error::Error for MergeError {}
+
+/// Applies the merge patch `patch` to `base`, returning the patched document.
+///
+/// # Errors
+///
+/// Returns [`MergeError::Patch`] or [`MergeError::Base`] if either document
+/// is not valid JSON. The base is parsed in full even when the patch replaces
+/// it entirely.
+pub fn json_merge_patch(base: &str, patch: &str) -> Result<String, MergeError> {
+    let patch = parse_value(patch).map_err(MergeError::Patch)?;
+    let mut merger = Merger {
+        patch: &patch,
+        out: String::new(),
+        compact: CompactWriter::default(),
+        frames: Vec::new(),
+        skip: None,
+        in_string: false,
+    };
+    let mut parser = StreamingParser::new(ParserOptions::default());
+    for event in parser.feed(base) {
+        merger.event(&event.map_err(MergeError::Base)?);
+    }
+    for event in parser.finish() {
+        merger.event(&event.map_err(MergeError::Base)?);
+    }
+    Ok(merger.out)
+}
+
+/// Parses a whole document into a [`Value`].
+fn parse_value(text: &str) -> Result<Value, ParserError> {
+    let mut parser = StreamingParser::new(ParserOptions {
+        non_scalar_values: NonScalarValueMode::Roots,
+        string_value_mode: StringValueMode::Values,
+        ..Default::default()
+    });
+    let mut root = Value::Null;
+    let events = parser.feed(text).collect::<Vec<_>>();
+    for event in events.into_iter().chain(parser.finish()) {
+        root = match event? {
+            ParseEvent::Boolean { path, value } if path.is_empty() => Value::Boolean(value),
+            ParseEvent::Number { path, value } if path.is_empty() => Value::Number(value),
+            ParseEvent::String {
+                path,
+                value: Some(value),
+                is_final: true,
+                ..
+            } if path.is_empty() => Value::String(value),
+            ParseEvent::ArrayEnd {
+                path,
+                value: Some(value),
+            } if path.is_empty() => Value::Array(value),
+            ParseEvent::ObjectEnd {
+                path,
+                value: Some(value),
+            } if path.is_empty() => Value::Object(value),
+            _ => continue,
+        };
+    }
+    Ok(root)
+}
+
+/// Returns the result of applying `patch` to a value that is not an object:
+/// the patch itself, without the `null` members of its objects.
+fn without_nulls(patch: &Value) -> Value {
+    match patch {
+        Value::Object(members) => Value::Object(
+            members
+                .iter()
+                .filter(|(_, value)| **value != Value::Null)
+                .map(|(key, value)| (key.clone(), without_nulls(value)))
+                .collect(),
+        ),
+        value => value.clone(),
+    }
+}
+
+/// An open array or object of the base.
+struct Frame<'p> {
+    /// The members of the patch to merge into this object, if any.
+    patch: Option<&'p Map>,
+    /// Keys of `patch` already written.
+    applied: Vec<&'p str>,
+}
+
+/// Rewrites the events of the base as the patched document.
+struct Merger<'p> {
+    patch: &'p Value,
+    out: String,
+    compact: CompactWriter,
+    frames: Vec<Frame<'p>>,
+    /// The depth of the base value being dropped, if any.
+    skip: Option<usize>,
+    in_string: bool,
+}
+
+impl<'p> Merger<'p> {
+    fn event(&mut self, event: &ParseEvent) {
+        // Writing to a `String` cannot fail.
+        let _ = self.write_event(event);
+    }
+
+    fn write_event(&mut self, event: &ParseEvent) -> fmt::Result {
+        let path = event.path();
+        let ends_value = match event {
+            ParseEvent::String { is_final, .. } => *is_final,
+            ParseEvent::ArrayStart { .. } | ParseEvent::ObjectBegin { .. } => false,
+            _ => true,
+        };
+        if let Some(depth) = self.skip {
+            if path.len() == depth && ends_value {
+                self.skip = None;
+            }
+            return Ok(());
+        }
+        let starts_value = match event {
+            ParseEvent::String { .. } => !core::mem::replace(&mut self.in_string, !ends_value),
+            ParseEvent::ArrayEnd { .. } | ParseEvent::ObjectEnd { .. } => false,
+            _ => true,
+        };
+        if !starts_value {
+            return self.write_token(event, false);
+        }
+
+        let is_object = matches!(event, ParseEvent::ObjectBegin { .. });
+        let patched = match (self.frames.last_mut(), path.last()) {
+            // The root is replaced unless both documents are objects.
+            (None, _) => Some((false, self.patch)),
+            (Some(frame), Some(PathComponent::Key(key))) => frame
+                .patch
+                .and_then(|patch| patch.get_key_value(&**key))
+                .map(|(key, value)| {
+                    let applied = frame.applied.contains(&&**key);
+                    frame.applied.push(key);
+                    (applied, value)
+                }),
+            _ => None,
+        };
+        match patched {
+            None => {
+                self.compact.begin_value(path, &mut self.out)?;
+                return self.write_token(event, true);
+            }
+            // Drop the member, or a duplicate of a member already written.
+            Some((true, _) | (false, Value::Null)) if !path.is_empty() => {}
+            Some((false, Value::Object(members))) if is_object => {
+                self.compact.begin_value(path, &mut self.out)?;
+                self.compact.write_token(event, true, &mut self.out)?;
+                self.push_frame(Some(members));
+                return Ok(());
+            }
+            Some((_, value)) => {
+                self.compact.begin_value(path, &mut self.out)?;
+                write!(self.out, "{}", without_nulls(value))?;
+            }
+        }
+        self.in_string = false;
+        if !ends_value {
+            self.skip = Some(path.len());
+        }
+        Ok(())
+    }
+
+    fn push_frame(&mut self, patch: Option<&'p Map>) {
+        self.frames.push(Frame {
+            patch,
+            applied: Vec::new(),
+        });
+    }
+
+    /// Writes the text of a base event that is not patched. `starts_value`
+    /// is `true` for the first fragment of a string.
+    fn write_token(&mut self, event: &ParseEvent, starts_value: bool) -> fmt::Result {
+        match event {
+            ParseEvent::ArrayStart { .. } | ParseEvent::ObjectBegin { .. } => self.push_frame(None),
+            ParseEvent::ArrayEnd { .. } => {
+                self.frames.pop();
+            }
+            ParseEvent::ObjectEnd { .. } => {
+                if let Some(frame) = self.frames.pop() {
+                    for (key, value) in frame.patch.into_iter().flatten() {
+                        if *value == Value::Null || frame.applied.contains(&&**key) {
+                            continue;
+                        }
+                        self.compact.begin_member(Some(key), &mut self.out)?;
+                        write!(self.out, "{}", without_nulls(value))?;
+                    }
+                }
+            }
+            _ => {}
+        }
+        self.compact.write_token(event, starts_value, &mut self.out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MergeError, json_merge_patch};
+
+    fn merge(base: &str, patch: &str) -> alloc::string::String {
+        json_merge_patch(base, patch).unwrap()
+    }
+
+    #[test]
+    fn nested_objects_are_merged() {
+        let base = r#"{"a": {"b": "c", "d": {"e": 1, "f": [1, 2]}}, "g": "long string value"}"#;
+        let patch = r#"{"a": {"d": {"e": 2, "x": {"y": null, "z": true}}}, "h": "new"}"#;
+        assert_eq!(
+            merge(base, patch),
+            r#"{"a":{"b":"c","d":{"e":2,"f":[1,2],"x":{"z":true}}},"g":"long string value","h":"new"}"#
+        );
+        assert_eq!(
+            merge(base, "{}"),
+            r#"{"a":{"b":"c","d":{"e":1,"f":[1,2]}},"g":"long string value"}"#
+        );
+    }
+
+    #[test]
+    fn arrays_and_scalars_replace() {
+        let base = r#"{"a": [1, {"b": 2}], "c": {"d": 1}, "e": "x"}"#;
+        assert_eq!(
+            merge(base, r#"{"a": [3], "c": [4], "e": {"f": null, "g": 5}}"#),
+            r#"{"a":[3],"c":[4],"e":{"g":5}}"#
+        );
+        assert_eq!(merge(base, "[1, 2]"), "[1,2]");
+        assert_eq!(merge("[1, 2]", r#"{"a": "b"}"#), r#"{"a":"b"}"#);
+        assert_eq!(merge(r#""x""#, "null"), "null");
+    }
+
+    #[test]
+    fn null_members_are_removed() {
+        let base = r#"{"a": "b", "c": {"d": "e", "f": "g"}, "h": [null]}"#;
+        assert_eq!(
+            merge(base, r#"{"a": null, "c": {"f": null}, "missing": null}"#),
+            r#"{"c":{"d":"e"},"h":[null]}"#
+        );
+        assert_eq!(merge(r#"{"a": 1}"#, r#"{"a": null}"#), "{}");
+        // A duplicate key in the base is written once.
+        assert_eq!(merge(r#"{"a": 1, "a": 2}"#, r#"{"a": 3}"#), r#"{"a":3}"#);
+    }
+
+    #[test]
+    fn errors_name_the_document() {
+        assert!(matches!(
+            json_merge_patch("{]", "{}"),
+            Err(MergeError::Base(_))
+        ));
+        assert!(matches!(
+            json_merge_patch("{}", "[1,,]"),
+            Err(MergeError::Patch(_))
+        ));
+    }
+}
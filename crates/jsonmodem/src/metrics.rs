@@ -0,0 +1,220 @@
+//! Counting the events of a parse.
+//!
+//! [`EventCounter`] wraps an iterator of events and counts them by kind as
+//! they pass through, e.g. to log how much structure a stream contained or to
+//! compare the work done by different chunkings of the same input. The counts
+//! are read with [`EventCounter::report`].
+//!
+//! Counting is compiled in for debug builds and with the `metrics` feature.
+//! Otherwise an [`EventCounter`] passes events through without looking at
+//! them, and its report is always empty, so it can be left in place in code
+//! built both ways.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{EventCounter, MetricsReport, ParserOptions, StreamingParser};
+//!
+//! let mut parser = StreamingParser::new(ParserOptions::default());
+//! let mut report = MetricsReport::default();
+//! for chunk in [r#"{"a": ["x"#, r#"y", 1, null]}"#] {
+//!     let mut events = EventCounter::new(parser.feed(chunk));
+//!     events.add_bytes_fed(chunk.len());
+//!     events.by_ref().for_each(drop);
+//!     report += events.report();
+//! }
+//! # if cfg!(any(debug_assertions, feature = "metrics")) {
+//! assert_eq!(report.n_strings, 1);
+//! assert_eq!(report.n_string_fragments, 2);
+//! assert_eq!(report.n_bytes_fed, 22);
+//! # }
+//! ```
+use core::{fmt, ops::AddAssign};
+
+use crate::{JsonValue, ParseEvent};
+
+/// Whether [`EventCounter`] counts anything in this build.
+const COUNTING: bool = cfg!(any(debug_assertions, feature = "metrics"));
+
+/// The counts gathered by an [`EventCounter`].
+///
+/// Reports of successive chunks can be added together with `+=`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MetricsReport {
+    /// The number of objects begun.
+    pub n_objects: usize,
+    /// The number of arrays begun.
+    pub n_arrays: usize,
+    /// The number of complete strings, counting a string split into several
+    /// fragments once.
+    pub n_strings: usize,
+    /// The number of numbers.
+    pub n_numbers: usize,
+    /// The number of booleans.
+    pub n_booleans: usize,
+    /// The number of nulls.
+    pub n_nulls: usize,
+    /// The number of [`ParseEvent::String`] fragments.
+    pub n_string_fragments: usize,
+    /// The number of errors.
+    pub n_errors: usize,
+    /// The number of bytes recorded with [`EventCounter::add_bytes_fed`].
+    pub n_bytes_fed: usize,
+}
+
+impl AddAssign for MetricsReport {
+    fn add_assign(&mut self, other: Self) {
+        self.n_objects += other.n_objects;
+        self.n_arrays += other.n_arrays;
+        self.n_strings += other.n_strings;
+        self.n_numbers += other.n_numbers;
+        self.n_booleans += other.n_booleans;
+        self.n_nulls += other.n_nulls;
+        self.n_string_fragments += other.n_string_fragments;
+        self.n_errors += other.n_errors;
+        self.n_bytes_fed += other.n_bytes_fed;
+    }
+}
+
+impl fmt::Display for MetricsReport {
+    /// Writes one `name: count` line per count.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, count) in [
+            ("objects", self.n_objects),
+            ("arrays", self.n_arrays),
+            ("strings", self.n_strings),
+            ("numbers", self.n_numbers),
+            ("booleans", self.n_booleans),
+            ("nulls", self.n_nulls),
+            ("string fragments", self.n_string_fragments),
+            ("errors", self.n_errors),
+            ("bytes fed", self.n_bytes_fed),
+        ] {
+            writeln!(f, "{name}: {count}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Counts the events and errors of the wrapped iterator by kind.
+///
+/// Items are passed through unchanged. Without debug assertions or the
+/// `metrics` feature nothing is counted.
+#[derive(Debug, Clone)]
+pub struct EventCounter<I> {
+    inner: I,
+    report: MetricsReport,
+}
+
+impl<I> EventCounter<I> {
+    /// Wraps an iterator of events.
+    pub fn new(inner: I) -> Self {
+        Self {
+            inner,
+            report: MetricsReport::default(),
+        }
+    }
+
+    /// Records that `n` more bytes of input were fed to the parser.
+    pub fn add_bytes_fed(&mut self, n: usize) {
+        if COUNTING {
+            self.report.n_bytes_fed += n;
+        }
+    }
+
+    /// Returns the counts so far.
+    #[must_use]
+    pub fn report(&self) -> MetricsReport {
+        self.report
+    }
+
+    /// Returns the wrapped iterator.
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+}
+
+impl<I, V, E> Iterator for EventCounter<I>
+where
+    I: Iterator<Item = Result<ParseEvent<V>, E>>,
+    V: JsonValue,
+{
+    type Item = Result<ParseEvent<V>, E>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next()?;
+        if COUNTING {
+            let report = &mut self.report;
+            match &item {
+                Ok(ParseEvent::ObjectBegin { .. }) => report.n_objects += 1,
+                Ok(ParseEvent::ArrayStart { .. }) => report.n_arrays += 1,
+                Ok(ParseEvent::String { is_final, .. }) => {
+                    report.n_string_fragments += 1;
+                    report.n_strings += usize::from(*is_final);
+                }
+                Ok(ParseEvent::StringComplete { .. }) => report.n_strings += 1,
+                Ok(ParseEvent::Number { .. }) => report.n_numbers += 1,
+                Ok(ParseEvent::Boolean { .. }) => report.n_booleans += 1,
+                Ok(ParseEvent::Null { .. }) => report.n_nulls += 1,
+                Ok(_) => {}
+                Err(_) => report.n_errors += 1,
+            }
+        }
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::{EventCounter, MetricsReport};
+    use crate::{ParserOptions, StreamingParser, StringValueMode, produce_chunks};
+
+    #[test]
+    fn counts_events_across_chunks() {
+        let json = r#"{"a": [1, 2.5, "xyz", true, null], "b": {"c": false}, "d": "é"} [x"#;
+        for parts in [1, 5, json.len()] {
+            let mut parser = StreamingParser::new(ParserOptions {
+                allow_multiple_json_values: true,
+                string_value_mode: StringValueMode::Values,
+                ..Default::default()
+            });
+            let mut report = MetricsReport::default();
+            for chunk in produce_chunks(json, parts) {
+                let mut events = EventCounter::new(parser.feed(chunk));
+                events.add_bytes_fed(chunk.len());
+                events.by_ref().for_each(drop);
+                report += events.report();
+            }
+            assert_eq!(report.n_objects, 2);
+            assert_eq!(report.n_arrays, 2);
+            assert_eq!(report.n_strings, 2);
+            assert_eq!(report.n_numbers, 2);
+            assert_eq!(report.n_booleans, 2);
+            assert_eq!(report.n_nulls, 1);
+            assert!(report.n_string_fragments >= 2);
+            assert_eq!(report.n_errors, 1);
+            assert_eq!(report.n_bytes_fed, json.len());
+        }
+    }
+
+    #[test]
+    fn display_lists_every_count() {
+        let report = MetricsReport {
+            n_numbers: 3,
+            n_bytes_fed: 10,
+            ..MetricsReport::default()
+        };
+        assert_eq!(
+            report.to_string(),
+            "objects: 0\narrays: 0\nstrings: 0\nnumbers: 3\nbooleans: 0\nnulls: 0\n\
+             string fragments: 0\nerrors: 0\nbytes fed: 10\n"
+        );
+    }
+}
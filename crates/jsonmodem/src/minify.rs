@@ -0,0 +1,284 @@
+//! Streaming removal of insignificant whitespace and comments.
+//!
+//! [`JsonMinifier`] rewrites JSON text chunk by chunk without parsing it into
+//! events. It only tracks whether it is inside a string (so that whitespace
+//! there is preserved) or a comment, and copies everything else through
+//! verbatim. Input is not validated: well-formed JSON produces well-formed,
+//! parse-equivalent JSON, and anything else is passed along for the parser to
+//! reject.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{JsonMinifier, MinifyOptions};
+//!
+//! let mut out = String::new();
+//! let mut minifier = JsonMinifier::new(
+//!     &mut out,
+//!     MinifyOptions {
+//!         allow_comments: true,
+//!     },
+//! );
+//! minifier.feed("{ \"a b\" : [1, /* two */ 2")?;
+//! minifier.feed(" ] // done\n}")?;
+//! minifier.finish()?;
+//! assert_eq!(out, r#"{"a b":[1,2]}"#);
+//! # Ok::<(), core::fmt::Error>(())
+//! ```
+use core::fmt;
+
+/// Configuration for [`JsonMinifier`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MinifyOptions {
+    /// Strip `// line` and `/* block */` comments.
+    ///
+    /// When `false`, a `/` outside of a string is copied through unchanged.
+    ///
+    /// # Default
+    ///
+    /// `false`
+    pub allow_comments: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Default,
+    String,
+    StringEscape,
+    /// A `/` that may start a comment.
+    Slash,
+    LineComment,
+    BlockComment,
+    /// A `*` inside a block comment that may end it.
+    BlockCommentStar,
+}
+
+/// A streaming JSON minifier writing to a [`fmt::Write`].
+#[derive(Debug)]
+pub struct JsonMinifier<W: fmt::Write> {
+    writer: W,
+    allow_comments: bool,
+    state: State,
+    /// Whether the last character written was part of a number or literal.
+    after_word: bool,
+    /// Whether whitespace or a comment was skipped since the last character
+    /// written.
+    skipped: bool,
+}
+
+/// Characters that can appear in a number or a `true`/`false`/`null`
+/// literal. Two such tokens separated by whitespace must stay separated.
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')
+}
+
+impl<W: fmt::Write> JsonMinifier<W> {
+    /// Creates a minifier that writes its output to `writer`.
+    pub fn new(writer: W, options: MinifyOptions) -> Self {
+        Self {
+            writer,
+            allow_comments: options.allow_comments,
+            state: State::Default,
+            after_word: false,
+            skipped: false,
+        }
+    }
+
+    /// Minifies a chunk of JSON text.
+    ///
+    /// Strings, escapes and comments may be split across chunks.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the underlying writer fails.
+    pub fn feed(&mut self, chunk: &str) -> fmt::Result {
+        for c in chunk.chars() {
+            self.push(c)?;
+        }
+        Ok(())
+    }
+
+    /// Marks the end of input and flushes any pending output.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the underlying writer fails.
+    pub fn finish(&mut self) -> fmt::Result {
+        if self.state == State::Slash {
+            self.state = State::Default;
+            self.writer.write_char('/')?;
+        }
+        Ok(())
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn push(&mut self, c: char) -> fmt::Result {
+        match self.state {
+            State::Default => self.push_default(c),
+            State::String => {
+                match c {
+                    '"' => self.state = State::Default,
+                    '\\' => self.state = State::StringEscape,
+                    _ => {}
+                }
+                self.writer.write_char(c)
+            }
+            State::StringEscape => {
+                self.state = State::String;
+                self.writer.write_char(c)
+            }
+            State::Slash => match c {
+                '/' => {
+                    self.state = State::LineComment;
+                    Ok(())
+                }
+                '*' => {
+                    self.state = State::BlockComment;
+                    Ok(())
+                }
+                _ => {
+                    self.state = State::Default;
+                    self.write_token('/')?;
+                    self.push_default(c)
+                }
+            },
+            State::LineComment => {
+                if matches!(c, '\n' | '\r') {
+                    self.state = State::Default;
+                    self.skipped = true;
+                }
+                Ok(())
+            }
+            State::BlockComment => {
+                if c == '*' {
+                    self.state = State::BlockCommentStar;
+                }
+                Ok(())
+            }
+            State::BlockCommentStar => {
+                match c {
+                    '/' => {
+                        self.state = State::Default;
+                        self.skipped = true;
+                    }
+                    '*' => {}
+                    _ => self.state = State::BlockComment,
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn push_default(&mut self, c: char) -> fmt::Result {
+        match c {
+            ' ' | '\t' | '\n' | '\r' => {
+                self.skipped = true;
+                Ok(())
+            }
+            '/' if self.allow_comments => {
+                self.state = State::Slash;
+                Ok(())
+            }
+            '"' => {
+                self.state = State::String;
+                self.write_token(c)
+            }
+            _ => self.write_token(c),
+        }
+    }
+
+    /// Writes a character outside of a string, keeping a single space between
+    /// two numbers or literals that were separated in the input.
+    fn write_token(&mut self, c: char) -> fmt::Result {
+        let word = is_word_char(c);
+        if self.skipped && self.after_word && word {
+            self.writer.write_char(' ')?;
+        }
+        self.skipped = false;
+        self.after_word = word;
+        self.writer.write_char(c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::String, vec::Vec};
+
+    use super::{JsonMinifier, MinifyOptions};
+    use crate::{
+        NonScalarValueMode, ParseEvent, ParserOptions, Value, produce_chunks, tests::events,
+    };
+
+    fn minify(json: &str, parts: usize, allow_comments: bool) -> String {
+        let mut minifier = JsonMinifier::new(String::new(), MinifyOptions { allow_comments });
+        for chunk in produce_chunks(json, parts) {
+            minifier.feed(chunk).unwrap();
+        }
+        minifier.finish().unwrap();
+        minifier.into_inner()
+    }
+
+    fn parse_values(json: &str) -> Vec<Value> {
+        let options = ParserOptions {
+            allow_multiple_json_values: true,
+            non_scalar_values: NonScalarValueMode::Roots,
+            ..Default::default()
+        };
+        events(json, 1, options)
+            .into_iter()
+            .filter_map(|event| match event {
+                ParseEvent::ArrayEnd { value, .. } => value.map(Value::Array),
+                ParseEvent::ObjectEnd { value, .. } => value.map(Value::Object),
+                ParseEvent::Number { value, .. } => Some(Value::Number(value)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn large_fixture_is_parse_equivalent() {
+        let json = include_str!("../benches/jiter_data/response_large.json");
+        let expected = parse_values(json);
+        assert_eq!(expected.len(), 1);
+        for parts in [1, 17, 1000] {
+            let minified = minify(json, parts, false);
+            assert!(minified.len() < json.len());
+            assert!(!minified.contains('\n'));
+            assert_eq!(parse_values(&minified), expected);
+        }
+    }
+
+    #[test]
+    fn whitespace_in_strings_is_preserved() {
+        let json = r#" { "a \" b" : [ "x  y", "\\" , "\t" ] } "#;
+        for parts in 1..json.len() {
+            assert_eq!(
+                minify(json, parts, false),
+                r#"{"a \" b":["x  y","\\","\t"]}"#
+            );
+        }
+    }
+
+    #[test]
+    fn comments_are_stripped() {
+        let json = "// leading\n[1, /* a * / b **/ 2, \"// not a comment\" /**/]\n/* trailing */";
+        for parts in 1..json.len() {
+            assert_eq!(minify(json, parts, true), r#"[1,2,"// not a comment"]"#);
+        }
+    }
+
+    #[test]
+    fn comments_are_kept_when_not_allowed() {
+        assert_eq!(minify("[1 /* x */]", 1, false), "[1/*x*/]");
+    }
+
+    #[test]
+    fn separate_values_stay_separate() {
+        let json = "1 2\n true /**/ null {} -3.5e1";
+        assert_eq!(minify(json, 1, true), "1 2 true null{}-3.5e1");
+    }
+}
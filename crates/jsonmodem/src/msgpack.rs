@@ -0,0 +1,328 @@
+//! Encoding of event streams as [MessagePack](https://msgpack.org).
+//!
+//! [`MessagePackEncoder`] consumes the events of one or more root values and
+//! writes each as a msgpack object, one after another. Every value uses
+//! the smallest format that holds it:
+//!
+//! * numbers without a fractional part use the smallest integer format they fit
+//!   in, from a positive or negative fixint up to a 64-bit integer, and other
+//!   numbers, including `-0`, use float 64;
+//! * strings use fixstr, str 8, str 16 or str 32;
+//! * arrays and objects use fixarray or fixmap when they have fewer than 16
+//!   members, and the 16- or 32-bit formats otherwise.
+//!
+//! The length of a container comes before its members, so a container is
+//! encoded in memory and written out when its root value is complete; memory
+//! use is proportional to the encoding of the largest root.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{MessagePackEncoder, ParserOptions, StreamingParser};
+//!
+//! let mut parser = StreamingParser::new(ParserOptions::default());
+//! let mut encoder = MessagePackEncoder::new(Vec::new());
+//! for event in parser.feed(r#"{"a": [1, -1, 1.5]}"#) {
+//!     encoder.write_event(&event?)?;
+//! }
+//! assert_eq!(
+//!     encoder.into_inner(),
+//!     [
+//!         [0x81, 0xa1, b'a', 0x93, 0x01, 0xff, 0xcb].as_slice(),
+//!         &1.5f64.to_be_bytes(),
+//!     ]
+//!     .concat()
+//! );
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+use alloc::{string::String, vec::Vec};
+use std::io::{self, Write};
+
+use crate::{ParseEvent, ParseEventWriter, PathComponent, Value};
+
+/// An array or object whose members are being encoded.
+struct Frame {
+    is_array: bool,
+    len: usize,
+    bytes: Vec<u8>,
+}
+
+/// Writes the events of each root value as a msgpack object.
+pub struct MessagePackEncoder<W> {
+    writer: W,
+    frames: Vec<Frame>,
+    /// The fragments so far of a string split across events.
+    string: String,
+    values: usize,
+}
+
+impl<W: Write> MessagePackEncoder<W> {
+    /// Creates an encoder writing to `writer`.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            frames: Vec::new(),
+            string: String::new(),
+            values: 0,
+        }
+    }
+
+    /// The number of root values written so far.
+    pub fn values(&self) -> usize {
+        self.values
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Processes one event, writing a root value once it is complete.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the writer fails, or if a string or container is
+    /// too long for msgpack. The encoder should not be used after an
+    /// error.
+    pub fn write_event(&mut self, event: &ParseEvent) -> io::Result<()> {
+        let mut bytes = Vec::new();
+        match event {
+            ParseEvent::Null { .. } => bytes.push(0xc0),
+            ParseEvent::Boolean { value, .. } => bytes.push(if *value { 0xc3 } else { 0xc2 }),
+            ParseEvent::Number { value, .. } => write_number(&mut bytes, *value),
+            ParseEvent::String {
+                fragment, is_final, ..
+            } => {
+                self.string.push_str(fragment);
+                if !is_final {
+                    return Ok(());
+                }
+                write_str(&mut bytes, &self.string)?;
+                self.string.clear();
+            }
+            ParseEvent::StringComplete { value, .. } => write_str(&mut bytes, value)?,
+            ParseEvent::ArrayStart { .. } | ParseEvent::ObjectBegin { .. } => {
+                self.frames.push(Frame {
+                    is_array: matches!(event, ParseEvent::ArrayStart { .. }),
+                    len: 0,
+                    bytes: Vec::new(),
+                });
+                return Ok(());
+            }
+            ParseEvent::ArrayEnd { .. } | ParseEvent::ObjectEnd { .. } => {
+                let Some(frame) = self.frames.pop() else {
+                    return Ok(());
+                };
+                let (fix, codes) = if frame.is_array {
+                    (0x90, [0xdc, 0xdd])
+                } else {
+                    (0x80, [0xde, 0xdf])
+                };
+                write_header(&mut bytes, frame.len, fix, 16, None, codes)?;
+                bytes.extend_from_slice(&frame.bytes);
+            }
+            ParseEvent::ScalarBegin { .. }
+            | ParseEvent::ScalarEnd { .. }
+            | ParseEvent::ObjectKey { .. } => return Ok(()),
+        }
+        self.emit(event.path(), &bytes)
+    }
+
+    /// Hands the encoding of the complete value at `path` to its container,
+    /// or writes it out if it is a root.
+    fn emit(&mut self, path: &[PathComponent], bytes: &[u8]) -> io::Result<()> {
+        let Some(frame) = self.frames.last_mut() else {
+            self.writer.write_all(bytes)?;
+            self.values += 1;
+            return Ok(());
+        };
+        if let Some(PathComponent::Key(key)) = path.last() {
+            write_str(&mut frame.bytes, key)?;
+        }
+        frame.len += 1;
+        frame.bytes.extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+impl<W: Write> ParseEventWriter<Value> for MessagePackEncoder<W> {
+    type Error = io::Error;
+
+    fn write_event(&mut self, event: ParseEvent) -> Result<(), Self::Error> {
+        MessagePackEncoder::write_event(self, &event)
+    }
+}
+
+/// Writes a number as the smallest integer format that holds it exactly, or
+/// as a float 64.
+#[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn write_number(out: &mut Vec<u8>, n: f64) {
+    // 2^64, the first integer beyond the range of u64 and above that of i64.
+    const TWO_64: f64 = 18_446_744_073_709_551_616.0;
+    let is_integer = n.fract() == 0.0 && !(n == 0.0 && n.is_sign_negative());
+    if is_integer && (0.0..TWO_64).contains(&n) {
+        let n = n as u64;
+        match n {
+            0..=0x7f => out.push(n as u8),
+            0x80..=0xff => out.extend_from_slice(&[0xcc, n as u8]),
+            0x100..=0xffff => {
+                out.push(0xcd);
+                out.extend_from_slice(&(n as u16).to_be_bytes());
+            }
+            0x1_0000..=0xffff_ffff => {
+                out.push(0xce);
+                out.extend_from_slice(&(n as u32).to_be_bytes());
+            }
+            _ => {
+                out.push(0xcf);
+                out.extend_from_slice(&n.to_be_bytes());
+            }
+        }
+    } else if is_integer && (-TWO_64 / 2.0..0.0).contains(&n) {
+        let n = n as i64;
+        if n >= -32 {
+            out.push(n as u8);
+        } else if let Ok(n) = i8::try_from(n) {
+            out.extend_from_slice(&[0xd0, n as u8]);
+        } else if let Ok(n) = i16::try_from(n) {
+            out.push(0xd1);
+            out.extend_from_slice(&n.to_be_bytes());
+        } else if let Ok(n) = i32::try_from(n) {
+            out.push(0xd2);
+            out.extend_from_slice(&n.to_be_bytes());
+        } else {
+            out.push(0xd3);
+            out.extend_from_slice(&n.to_be_bytes());
+        }
+    } else {
+        out.push(0xcb);
+        out.extend_from_slice(&n.to_be_bytes());
+    }
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) -> io::Result<()> {
+    write_header(out, s.len(), 0xa0, 32, Some(0xd9), [0xda, 0xdb])?;
+    out.extend_from_slice(s.as_bytes());
+    Ok(())
+}
+
+/// Writes the length of a string or container: in the low bits of `fix` if
+/// it is below `fix_max`, otherwise after the code of the smallest of the 8-
+/// (for strings), 16- or 32-bit formats that holds it.
+fn write_header(
+    out: &mut Vec<u8>,
+    len: usize,
+    fix: u8,
+    fix_max: usize,
+    code8: Option<u8>,
+    [code16, code32]: [u8; 2],
+) -> io::Result<()> {
+    if let Some(small) = u8::try_from(len).ok().filter(|_| len < fix_max) {
+        out.push(fix | small);
+    } else if let (Some(code8), Ok(len)) = (code8, u8::try_from(len)) {
+        out.extend_from_slice(&[code8, len]);
+    } else if let Ok(len) = u16::try_from(len) {
+        out.push(code16);
+        out.extend_from_slice(&len.to_be_bytes());
+    } else if let Ok(len) = u32::try_from(len) {
+        out.push(code32);
+        out.extend_from_slice(&len.to_be_bytes());
+    } else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "value too long for msgpack",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, string::String, vec::Vec};
+    use core::fmt::Write;
+
+    use super::MessagePackEncoder;
+    use crate::{ParserOptions, StreamingParser, produce_chunks};
+
+    fn encode(json: &str) -> Vec<u8> {
+        let mut expected = None;
+        // The encoding does not depend on how the input is split.
+        for parts in [1, 3, json.len()] {
+            let mut parser = StreamingParser::new(ParserOptions {
+                allow_multiple_json_values: true,
+                ..Default::default()
+            });
+            let mut encoder = MessagePackEncoder::new(Vec::new());
+            for chunk in produce_chunks(json, parts) {
+                for event in parser.feed(chunk) {
+                    encoder.write_event(&event.unwrap()).unwrap();
+                }
+            }
+            for event in parser.finish() {
+                encoder.write_event(&event.unwrap()).unwrap();
+            }
+            let bytes = encoder.into_inner();
+            assert_eq!(*expected.get_or_insert_with(|| bytes.clone()), bytes);
+        }
+        expected.unwrap_or_default()
+    }
+
+    #[test]
+    fn smallest_formats() {
+        assert_eq!(encode("null true false"), [0xc0, 0xc3, 0xc2]);
+        assert_eq!(
+            encode("0 127 128 255 256 65536 4294967296"),
+            [
+                [0x00, 0x7f, 0xcc, 0x80, 0xcc, 0xff, 0xcd, 0x01, 0x00].as_slice(),
+                &[0xce, 0x00, 0x01, 0x00, 0x00],
+                &[0xcf, 0, 0, 0, 1, 0, 0, 0, 0],
+            ]
+            .concat()
+        );
+        assert_eq!(
+            encode("-1 -32 -33 -128 -129 -32769 -2147483649"),
+            [
+                [0xff, 0xe0, 0xd0, 0xdf, 0xd0, 0x80, 0xd1, 0xff, 0x7f].as_slice(),
+                &[0xd2, 0xff, 0xff, 0x7f, 0xff],
+                &[0xd3, 0xff, 0xff, 0xff, 0xff, 0x7f, 0xff, 0xff, 0xff],
+            ]
+            .concat()
+        );
+        assert_eq!(
+            encode("-0"),
+            [[0xcb].as_slice(), &(-0.0f64).to_be_bytes()].concat()
+        );
+        assert_eq!(
+            encode("1e20"),
+            [[0xcb].as_slice(), &1e20f64.to_be_bytes()].concat()
+        );
+
+        assert_eq!(encode(r#""""#), [0xa0]);
+        let long = "x".repeat(32);
+        assert_eq!(encode(&format!("{long:?}"))[..2], [0xd9, 32]);
+        assert_eq!(encode("[[], {}]"), [0x92, 0x90, 0x80]);
+        let items = ["0"; 16].join(",");
+        assert_eq!(encode(&format!("[{items}]"))[..3], [0xdc, 0x00, 0x10]);
+    }
+
+    #[test]
+    fn decodes_to_the_parsed_value() {
+        let mut members = String::new();
+        for i in 0..300 {
+            write!(members, r#""k{i}": [{i}, -{i}.5],"#).unwrap();
+        }
+        let documents = [
+            r#"{"name": "jsonmodem", "tags": ["a", "é☕"], "n": null, "ok": true}"#,
+            "[1.5, -2, 3e3, 18446744073709551615, -9223372036854775808, 1e300]",
+            &format!(r#"{{{members} "s": "{}"}}"#, "y".repeat(70_000)),
+        ];
+        for json in documents {
+            let bytes = encode(json);
+            let decoded: serde_json::Value = rmp_serde::from_slice(&bytes).unwrap();
+            let value = StreamingParser::new(ParserOptions::default())
+                .into_value(json)
+                .unwrap();
+            assert_eq!(value, decoded);
+        }
+    }
+}
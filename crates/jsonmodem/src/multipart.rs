@@ -0,0 +1,443 @@
+//! Parsing of the JSON parts of a multipart body.
+//!
+//! HTTP requests with `Content-Type: multipart/form-data` carry several
+//! parts, each with its own headers, separated by a boundary line
+//! ([RFC 2046], section 5.1). [`MultipartJsonParser`] reads such a body,
+//! feeds the content of every part whose `Content-Type` is
+//! `application/json` to a fresh parser and skips the other parts. Boundaries
+//! and headers may be split across chunks at any byte.
+//!
+//! Each JSON part must hold one JSON value, and its events have paths
+//! relative to that value. The preamble before the first boundary and the
+//! epilogue after the last are ignored.
+//!
+//! [RFC 2046]: https://www.rfc-editor.org/rfc/rfc2046#section-5.1
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{MultipartJsonParser, ParseEvent, ParserOptions};
+//!
+//! let mut parser = MultipartJsonParser::new(
+//!     "multipart/form-data; boundary=xyz",
+//!     ParserOptions::default(),
+//! )?;
+//! let body = "--xyz\r\n\
+//!     Content-Disposition: form-data; name=\"title\"\r\n\
+//!     \r\n\
+//!     not json\r\n\
+//!     --xyz\r\n\
+//!     Content-Disposition: form-data; name=\"meta\"\r\n\
+//!     Content-Type: application/json\r\n\
+//!     \r\n\
+//!     [true]\r\n\
+//!     --xyz--\r\n";
+//! let mut events = parser.feed(&body.as_bytes()[..50])?;
+//! events.extend(parser.feed(&body.as_bytes()[50..])?);
+//! parser.finish()?;
+//! assert_eq!(parser.json_parts(), 1);
+//! assert!(matches!(events[1], ParseEvent::Boolean { value: true, .. }));
+//! # Ok::<(), jsonmodem::MultipartError>(())
+//! ```
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{ParseEvent, ParserError, ParserOptions, StreamingParser};
+
+/// The longest boundary allowed by RFC 2046.
+const MAX_BOUNDARY_LEN: usize = 70;
+
+/// Error returned by [`MultipartJsonParser`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum MultipartError {
+    /// The content type is not `multipart/*` or has no valid `boundary`
+    /// parameter.
+    InvalidContentType,
+    /// A boundary line or a part header is malformed.
+    Malformed {
+        /// Offset in bytes of the offending text from the start of the body.
+        byte_offset: usize,
+    },
+    /// The body ended before its closing boundary.
+    Truncated,
+    /// A JSON part is not a single valid JSON value. The position is relative
+    /// to the start of the part's content.
+    Parser(ParserError),
+}
+
+impl fmt::Display for MultipartError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MultipartError::InvalidContentType => {
+                f.write_str("content type is not multipart with a boundary")
+            }
+            MultipartError::Malformed { byte_offset } => {
+                write!(f, "malformed multipart line at byte {byte_offset}")
+            }
+            MultipartError::Truncated => f.write_str("body ended before its closing boundary"),
+            MultipartError::Parser(err) => err.fmt(f),
+        }
+    }
+}
+
+impl core::error::Error for MultipartError {}
+
+impl From<ParserError> for MultipartError {
+    fn from(err: ParserError) -> Self {
+        MultipartError::Parser(err)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Skipping text up to the first boundary.
+    Preamble,
+    /// After a boundary, reading the rest of its line.
+    Boundary,
+    /// Reading the headers of a part; holds whether one of them marked it as
+    /// JSON.
+    Headers { json: bool },
+    /// Reading the content of a part.
+    Content { json: bool },
+    /// After the closing boundary.
+    Epilogue,
+}
+
+/// A streaming parser for the JSON parts of a multipart body.
+#[derive(Debug)]
+pub struct MultipartJsonParser {
+    options: ParserOptions,
+    parser: StreamingParser,
+    /// `CRLF--` followed by the boundary, which precedes every part.
+    delimiter: Vec<u8>,
+    state: State,
+    /// Input not consumed yet: a partial line, or the end of a part's content
+    /// that may be the start of a delimiter.
+    pending: Vec<u8>,
+    /// Offset in the body of the first byte of `pending`.
+    byte_pos: usize,
+    json_parts: usize,
+}
+
+impl MultipartJsonParser {
+    /// Creates a parser for a body with the given `Content-Type` header
+    /// value, using `options` for every JSON part.
+    ///
+    /// `allow_multiple_json_values` is turned off, since each part holds a
+    /// single value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MultipartError::InvalidContentType`] if `content_type` is
+    /// not a `multipart` type with a `boundary` parameter of 1 to 70
+    /// characters.
+    pub fn new(content_type: &str, options: ParserOptions) -> Result<Self, MultipartError> {
+        let boundary = boundary(content_type).ok_or(MultipartError::InvalidContentType)?;
+        let options = ParserOptions {
+            allow_multiple_json_values: false,
+            ..options
+        };
+        // The first boundary may open the body, without a line break before
+        // it, so the body is read as if it followed one.
+        let mut delimiter = b"\r\n--".to_vec();
+        delimiter.extend_from_slice(boundary.as_bytes());
+        Ok(Self {
+            options,
+            parser: StreamingParser::new(options),
+            delimiter,
+            state: State::Preamble,
+            pending: b"\r\n".to_vec(),
+            byte_pos: 0,
+            json_parts: 0,
+        })
+    }
+
+    /// The number of JSON parts completed so far.
+    #[must_use]
+    pub fn json_parts(&self) -> usize {
+        self.json_parts
+    }
+
+    /// Feeds a chunk of the body, returning the events it completes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a boundary line or header is malformed or a JSON
+    /// part is not a valid JSON value. The parser should not be fed after an
+    /// error.
+    pub fn feed(&mut self, bytes: &[u8]) -> Result<Vec<ParseEvent>, MultipartError> {
+        let mut events = Vec::new();
+        if self.state == State::Epilogue {
+            return Ok(events);
+        }
+        self.pending.extend_from_slice(bytes);
+        let mut start = 0;
+        while let Some(consumed) = self.step(start, &mut events)? {
+            start += consumed;
+        }
+        self.pending.drain(..start);
+        self.byte_pos += start;
+        Ok(events)
+    }
+
+    /// Marks the end of the body.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MultipartError::Truncated`] if the body ended before its
+    /// closing boundary.
+    pub fn finish(&self) -> Result<(), MultipartError> {
+        match self.state {
+            State::Epilogue => Ok(()),
+            _ => Err(MultipartError::Truncated),
+        }
+    }
+
+    /// Consumes what it can of `pending` from `start`, returning how many
+    /// bytes, or `None` if more input is needed.
+    fn step(
+        &mut self,
+        start: usize,
+        events: &mut Vec<ParseEvent>,
+    ) -> Result<Option<usize>, MultipartError> {
+        let input = &self.pending[start..];
+        // The offset in the body of `input`, less the line break `pending`
+        // starts with.
+        let offset = self.byte_pos + start;
+        match self.state {
+            State::Preamble => {
+                let Some(at) = find(input, &self.delimiter) else {
+                    // Keep what may be the start of the delimiter.
+                    let len = input.len().saturating_sub(self.delimiter.len() - 1);
+                    return Ok((len > 0).then_some(len));
+                };
+                self.state = State::Boundary;
+                Ok(Some(at + self.delimiter.len()))
+            }
+            State::Boundary => {
+                if input.starts_with(b"--") {
+                    self.state = State::Epilogue;
+                    return Ok(None);
+                }
+                let Some(end) = find(input, b"\r\n") else {
+                    return Ok(None);
+                };
+                // Only whitespace may follow the boundary on its line.
+                if !input[..end].iter().all(|&b| b == b' ' || b == b'\t') {
+                    return Err(MultipartError::Malformed {
+                        byte_offset: offset - 2,
+                    });
+                }
+                self.state = State::Headers { json: false };
+                Ok(Some(end + 2))
+            }
+            State::Headers { json } => {
+                let Some(end) = find(input, b"\r\n") else {
+                    return Ok(None);
+                };
+                let line = &input[..end];
+                if line.is_empty() {
+                    self.state = State::Content { json };
+                    return Ok(Some(2));
+                }
+                let (name, value) = core::str::from_utf8(line)
+                    .ok()
+                    .and_then(|line| line.split_once(':'))
+                    .ok_or(MultipartError::Malformed {
+                        byte_offset: offset - 2,
+                    })?;
+                let is_json = name.trim().eq_ignore_ascii_case("content-type")
+                    && media_type(value).eq_ignore_ascii_case("application/json");
+                self.state = State::Headers {
+                    json: json || is_json,
+                };
+                Ok(Some(end + 2))
+            }
+            State::Content { json } => {
+                let delimiter = find(input, &self.delimiter);
+                // Without a delimiter, keep what may be the start of one.
+                let len = delimiter
+                    .unwrap_or_else(|| input.len().saturating_sub(self.delimiter.len() - 1));
+                if json && len > 0 {
+                    for event in self.parser.feed_bytes(&input[..len])? {
+                        events.push(event?);
+                    }
+                }
+                if delimiter.is_none() {
+                    return Ok((len > 0).then_some(len));
+                }
+                if json {
+                    self.end_part(events)?;
+                }
+                self.state = State::Boundary;
+                Ok(Some(len + self.delimiter.len()))
+            }
+            State::Epilogue => Ok(None),
+        }
+    }
+
+    /// Finishes the parser of the current JSON part and starts the next one.
+    fn end_part(&mut self, events: &mut Vec<ParseEvent>) -> Result<(), MultipartError> {
+        let parser = core::mem::replace(&mut self.parser, StreamingParser::new(self.options));
+        for event in parser.finish() {
+            events.push(event?);
+        }
+        self.json_parts += 1;
+        Ok(())
+    }
+}
+
+/// Returns the `boundary` parameter of a `multipart` content type.
+fn boundary(content_type: &str) -> Option<&str> {
+    let mut params = content_type.split(';');
+    let (kind, _) = params.next()?.trim().split_once('/')?;
+    if !kind.eq_ignore_ascii_case("multipart") {
+        return None;
+    }
+    let boundary = params.find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim().eq_ignore_ascii_case("boundary").then(|| {
+            let value = value.trim();
+            value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .unwrap_or(value)
+        })
+    })?;
+    (1..=MAX_BOUNDARY_LEN)
+        .contains(&boundary.len())
+        .then_some(boundary)
+}
+
+/// Returns the media type of a `Content-Type` header value, without its
+/// parameters.
+fn media_type(value: &str) -> &str {
+    value.split(';').next().unwrap_or_default().trim()
+}
+
+/// Returns the offset of the first occurrence of `needle` in `haystack`.
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, string::String, vec::Vec};
+
+    use super::{MultipartError, MultipartJsonParser};
+    use crate::{ParseEvent, ParserOptions, Value, event::reconstruct_values};
+
+    const CONTENT_TYPE: &str = "multipart/form-data; boundary=\"simple boundary\"";
+
+    /// A body in the shape of the example in RFC 2046, section 5.1.1, with a
+    /// preamble, an epilogue, a part without headers and two JSON parts.
+    fn body() -> String {
+        [
+            "This is the preamble.  It is to be ignored, though it",
+            "is a handy place for composition agents to include an",
+            "explanatory note to non-MIME conformant readers.",
+            "",
+            "--simple boundary",
+            "",
+            "This is implicitly typed plain US-ASCII text.",
+            "It does NOT end with a linebreak.",
+            "--simple boundary",
+            "Content-Disposition: form-data; name=\"a\"",
+            "content-type: Application/JSON; charset=utf-8",
+            "",
+            "{\"text\": \"h\u{e9}llo \u{2615}\", \"crlf\": \"\\r\\n--simple\"}",
+            "--simple boundary  ",
+            "Content-Type: text/plain",
+            "",
+            "[not json",
+            "--simple boundary",
+            "Content-Type: application/json",
+            "",
+            "[1, 2]",
+            "",
+            "--simple boundary--",
+            "This is the epilogue.  It is also to be ignored.",
+        ]
+        .join("\r\n")
+    }
+
+    fn parse(chunks: &[&[u8]]) -> Result<(Vec<Value>, usize), MultipartError> {
+        let mut parser = MultipartJsonParser::new(CONTENT_TYPE, ParserOptions::default())?;
+        let mut events: Vec<ParseEvent> = Vec::new();
+        for chunk in chunks {
+            events.extend(parser.feed(chunk)?);
+        }
+        parser.finish()?;
+        Ok((reconstruct_values(events), parser.json_parts()))
+    }
+
+    #[test]
+    fn json_parts_split_at_every_byte() {
+        let body = body();
+        let body = body.as_bytes();
+        let expected = (
+            reconstruct_values(
+                crate::StreamingParser::new(ParserOptions {
+                    allow_multiple_json_values: true,
+                    ..ParserOptions::default()
+                })
+                .feed("{\"text\": \"h\u{e9}llo \u{2615}\", \"crlf\": \"\\r\\n--simple\"} [1, 2]")
+                .map(Result::unwrap)
+                .collect::<Vec<_>>(),
+            ),
+            2,
+        );
+        for split in 0..=body.len() {
+            let (first, second) = body.split_at(split);
+            assert_eq!(parse(&[first, second]), Ok(expected.clone()), "{split}");
+        }
+        let bytes: Vec<&[u8]> = body.chunks(1).collect();
+        assert_eq!(parse(&bytes), Ok(expected));
+    }
+
+    #[test]
+    fn one_json_part_opening_the_body() {
+        let body = b"--b\r\nContent-Type: application/json\r\n\r\nnull\r\n--b--";
+        let mut parser =
+            MultipartJsonParser::new("multipart/mixed; boundary=b", ParserOptions::default())
+                .unwrap();
+        let events = parser.feed(body).unwrap();
+        parser.finish().unwrap();
+        assert_eq!(parser.json_parts(), 1);
+        assert!(matches!(events[..], [ParseEvent::Null { .. }]));
+    }
+
+    #[test]
+    fn errors() {
+        for content_type in [
+            "text/plain; boundary=b",
+            "multipart/form-data",
+            &format!("multipart/form-data; boundary={}", "x".repeat(71)),
+        ] {
+            assert_eq!(
+                MultipartJsonParser::new(content_type, ParserOptions::default()).unwrap_err(),
+                MultipartError::InvalidContentType
+            );
+        }
+
+        let parse = |body: &[u8]| parse(&[body]).map(|_| ());
+        assert_eq!(
+            parse(b"--simple boundaryx\r\n"),
+            Err(MultipartError::Malformed { byte_offset: 17 })
+        );
+        assert_eq!(
+            parse(b"--simple boundary\r\nno colon\r\n"),
+            Err(MultipartError::Malformed { byte_offset: 19 })
+        );
+        assert!(matches!(
+            parse(b"--simple boundary\r\nContent-Type: application/json\r\n\r\n[1\r\n--simple boundary--"),
+            Err(MultipartError::Parser(_))
+        ));
+        assert_eq!(
+            parse(b"--simple boundary\r\n\r\ntext"),
+            Err(MultipartError::Truncated)
+        );
+    }
+}
@@ -0,0 +1,492 @@
+//! Writing of newline-delimited JSON.
+//!
+//! [`NdjsonWriter`] is the counterpart of parsing with
+//! `allow_multiple_json_values`: it writes one compact JSON value per line,
+//! either from the events of a value or from a built [`Value`]. Each record
+//! is checked to be exactly one complete, well-nested value before anything
+//! is written, so a bad record never leaves half a line in the output.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{NdjsonWriter, ParserOptions, StreamingParser, Value};
+//!
+//! let mut writer = NdjsonWriter::new(String::new());
+//! let mut parser = StreamingParser::new(ParserOptions::default());
+//! let events: Vec<_> = parser
+//!     .feed(r#"{"id": 1, "tags": ["a", "b"]}"#)
+//!     .collect::<Result<_, _>>()?;
+//! writer.write_record(events)?;
+//! writer.write_value(&Value::Boolean(true))?;
+//! assert_eq!(writer.records(), 2);
+//! assert_eq!(
+//!     writer.into_inner(),
+//!     "{\"id\":1,\"tags\":[\"a\",\"b\"]}\ntrue\n"
+//! );
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+use crate::{JsonValue, ParseEvent, PathComponent, Value, value::write_escaped_string};
+
+/// Error returned by [`NdjsonWriter`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NdjsonError {
+    /// The record has no events.
+    Empty,
+    /// The events ended before the value was complete.
+    Incomplete,
+    /// An event does not continue the value, e.g. a mismatched end event,
+    /// an event with the wrong path, or an event after the end of the value.
+    Unexpected {
+        /// The position of the event in the record, from zero.
+        index: usize,
+    },
+    /// The underlying writer failed.
+    Writer(fmt::Error),
+}
+
+impl fmt::Display for NdjsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NdjsonError::Empty => f.write_str("record has no events"),
+            NdjsonError::Incomplete => f.write_str("record ended inside a value"),
+            NdjsonError::Unexpected { index } => {
+                write!(f, "event {index} does not continue the record")
+            }
+            NdjsonError::Writer(err) => write!(f, "writer error: {err}"),
+        }
+    }
+}
+
+impl core::error::Error for NdjsonError {}
+
+impl From<fmt::Error> for NdjsonError {
+    fn from(err: fmt::Error) -> Self {
+        NdjsonError::Writer(err)
+    }
+}
+
+/// Writes one compact JSON value per line to a [`fmt::Write`].
+#[derive(Debug)]
+pub struct NdjsonWriter<W: fmt::Write> {
+    writer: W,
+    records: usize,
+    /// The record being rendered, written out once it is complete.
+    line: String,
+    compact: CompactWriter,
+}
+
+impl<W: fmt::Write> NdjsonWriter<W> {
+    /// Creates a writer that writes its output to `writer`.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            records: 0,
+            line: String::new(),
+            compact: CompactWriter::default(),
+        }
+    }
+
+    /// The number of records written so far.
+    #[must_use]
+    pub fn records(&self) -> usize {
+        self.records
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Writes the value described by `events`, from its first event to its
+    /// end event, followed by a newline.
+    ///
+    /// Paths must be those of a root value, as produced by the parser; string
+    /// fragments are joined. `ScalarBegin`, `ScalarEnd` and `ObjectKey`
+    /// events are ignored, and so is the `value` of every event but
+    /// `StringComplete`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, and writes nothing, if `events` are not exactly one
+    /// complete value. Returns [`NdjsonError::Writer`] if the underlying
+    /// writer fails.
+    pub fn write_record<V, I>(&mut self, events: I) -> Result<(), NdjsonError>
+    where
+        V: JsonValue,
+        V::Str: AsRef<str>,
+        V::Num: Into<f64>,
+        V::Bool: Into<bool>,
+        I: IntoIterator<Item = ParseEvent<V>>,
+    {
+        self.line.clear();
+        self.compact.clear();
+        let mut in_string = false;
+        let mut complete = false;
+        let mut len = 0;
+        for (index, event) in events.into_iter().enumerate() {
+            len += 1;
+            if complete || in_string && !matches!(event, ParseEvent::String { .. }) {
+                return Err(NdjsonError::Unexpected { index });
+            }
+            let path = event.path();
+            let (valid, starts_value) = match &event {
+                ParseEvent::String { .. } if in_string => {
+                    (path.len() == self.compact.depth(), false)
+                }
+                ParseEvent::ArrayEnd { .. } => (self.compact.is_end(true, path), false),
+                ParseEvent::ObjectEnd { .. } => (self.compact.is_end(false, path), false),
+                ParseEvent::ScalarBegin { .. }
+                | ParseEvent::ScalarEnd { .. }
+                | ParseEvent::ObjectKey { .. } => continue,
+                _ => (self.compact.is_next(path), true),
+            };
+            if !valid {
+                return Err(NdjsonError::Unexpected { index });
+            }
+            if starts_value {
+                self.compact.begin_value(path, &mut self.line)?;
+            }
+            self.compact
+                .write_token(&event, !in_string, &mut self.line)?;
+            if let ParseEvent::String { is_final, .. } = &event {
+                in_string = !*is_final;
+            }
+            complete = self.compact.depth() == 0 && !in_string;
+        }
+        if !complete {
+            return Err(if len == 0 {
+                NdjsonError::Empty
+            } else {
+                NdjsonError::Incomplete
+            });
+        }
+        self.line.push('\n');
+        self.writer.write_str(&self.line)?;
+        self.records += 1;
+        Ok(())
+    }
+
+    /// Writes `value` followed by a newline.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NdjsonError::Writer`] if the underlying writer fails.
+    pub fn write_value(&mut self, value: &Value) -> Result<(), NdjsonError> {
+        writeln!(self.writer, "{value}")?;
+        self.records += 1;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    is_array: bool,
+    len: usize,
+}
+
+/// Renders events as compact JSON text, keeping track of the open arrays and
+/// objects to place separators and keys.
+///
+/// Events are assumed to be well nested, as produced by the parser. Writers
+/// taking events from elsewhere check them with [`CompactWriter::is_next`]
+/// and [`CompactWriter::is_end`] first.
+#[derive(Debug, Default)]
+pub(crate) struct CompactWriter {
+    frames: Vec<Frame>,
+}
+
+impl CompactWriter {
+    /// The number of open arrays and objects.
+    pub(crate) fn depth(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// The number of values begun in the innermost open array or object.
+    pub(crate) fn values(&self) -> Option<usize> {
+        self.frames.last().map(|frame| frame.len)
+    }
+
+    /// Forgets the open arrays and objects.
+    pub(crate) fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    /// Whether `path` is the path of the next value: a root value, the next
+    /// index of an array, or a key of an object.
+    pub(crate) fn is_next(&self, path: &[PathComponent]) -> bool {
+        if path.len() != self.frames.len() {
+            return false;
+        }
+        match (self.frames.last(), path.last()) {
+            (None, _) => true,
+            (Some(frame), Some(PathComponent::Index(index))) => {
+                frame.is_array && *index == frame.len
+            }
+            (Some(frame), Some(PathComponent::Key(_))) => !frame.is_array,
+            (Some(_), None) => false,
+        }
+    }
+
+    /// Whether an end event at `path` closes the innermost open array, or
+    /// object if `is_array` is `false`.
+    pub(crate) fn is_end(&self, is_array: bool, path: &[PathComponent]) -> bool {
+        self.frames
+            .last()
+            .is_some_and(|frame| frame.is_array == is_array && path.len() + 1 == self.frames.len())
+    }
+
+    /// Writes the separator and (inside objects) key that precede a value
+    /// at `path`.
+    pub(crate) fn begin_value(
+        &mut self,
+        path: &[PathComponent],
+        out: &mut impl fmt::Write,
+    ) -> fmt::Result {
+        match path.last() {
+            Some(PathComponent::Key(key)) => self.begin_member(Some(key), out),
+            _ => self.begin_member(None, out),
+        }
+    }
+
+    /// Writes the separator and, if given, the key that precede a value in
+    /// the innermost open array or object.
+    pub(crate) fn begin_member(
+        &mut self,
+        key: Option<&str>,
+        out: &mut impl fmt::Write,
+    ) -> fmt::Result {
+        let Some(frame) = self.frames.last_mut() else {
+            return Ok(());
+        };
+        if frame.len > 0 {
+            out.write_char(',')?;
+        }
+        frame.len += 1;
+        if let Some(key) = key {
+            out.write_char('"')?;
+            write_escaped_string(key, out)?;
+            out.write_str("\":")?;
+        }
+        Ok(())
+    }
+
+    /// Writes the text of `event`, opening or closing an array or object.
+    /// `starts_value` is `true` for the first fragment of a string. Events
+    /// without text, such as `ScalarBegin`, write nothing.
+    pub(crate) fn write_token<V>(
+        &mut self,
+        event: &ParseEvent<V>,
+        starts_value: bool,
+        out: &mut impl fmt::Write,
+    ) -> fmt::Result
+    where
+        V: JsonValue,
+        V::Str: AsRef<str>,
+        V::Num: Into<f64>,
+        V::Bool: Into<bool>,
+    {
+        match event {
+            ParseEvent::Null { .. } => out.write_str("null"),
+            ParseEvent::Boolean { value, .. } => {
+                out.write_str(if (*value).into() { "true" } else { "false" })
+            }
+            ParseEvent::Number { value, .. } => {
+                let n: f64 = (*value).into();
+                write!(out, "{n}")
+            }
+            ParseEvent::String {
+                fragment, is_final, ..
+            } => {
+                if starts_value {
+                    out.write_char('"')?;
+                }
+                write_escaped_string(fragment.as_ref(), out)?;
+                if *is_final {
+                    out.write_char('"')?;
+                }
+                Ok(())
+            }
+            ParseEvent::StringComplete { value, .. } => {
+                out.write_char('"')?;
+                write_escaped_string(value.as_ref(), out)?;
+                out.write_char('"')
+            }
+            ParseEvent::ArrayStart { .. } | ParseEvent::ObjectBegin { .. } => {
+                let is_array = matches!(event, ParseEvent::ArrayStart { .. });
+                self.frames.push(Frame { is_array, len: 0 });
+                out.write_char(if is_array { '[' } else { '{' })
+            }
+            ParseEvent::ArrayEnd { .. } | ParseEvent::ObjectEnd { .. } => self.close(out).map(drop),
+            ParseEvent::ScalarBegin { .. }
+            | ParseEvent::ScalarEnd { .. }
+            | ParseEvent::ObjectKey { .. } => Ok(()),
+        }
+    }
+
+    /// Closes the innermost open array or object, returning `false` if none
+    /// is open.
+    pub(crate) fn close(&mut self, out: &mut impl fmt::Write) -> Result<bool, fmt::Error> {
+        let Some(frame) = self.frames.pop() else {
+            return Ok(false);
+        };
+        out.write_char(if frame.is_array { ']' } else { '}' })?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+
+    use serde_json::json;
+
+    use super::{NdjsonError, NdjsonWriter};
+    use crate::{
+        NonScalarValueMode, ParseEvent, ParserOptions, StreamingParser, StringValueMode, Value,
+        path, tests::events,
+    };
+
+    const JSON: &str = r#"{"a": ["x\ny", 1.5, null, true], "b": {"c\"": "é ☕"}, "d": []}"#;
+
+    fn options() -> ParserOptions {
+        ParserOptions {
+            string_value_mode: StringValueMode::Prefixes,
+            non_scalar_values: NonScalarValueMode::All,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn records_match_values() {
+        let value = StreamingParser::new(ParserOptions::default())
+            .into_value(JSON)
+            .unwrap();
+        let mut expected = NdjsonWriter::new(String::new());
+        expected.write_value(&value).unwrap();
+        let expected = expected.into_inner();
+        assert_eq!(
+            expected,
+            "{\"a\":[\"x\\u000Ay\",1.5,null,true],\"b\":{\"c\\\"\":\"é ☕\"},\"d\":[]}\n"
+        );
+
+        for parts in [1, 7, JSON.len()] {
+            let mut writer = NdjsonWriter::new(String::new());
+            writer.write_record(events(JSON, parts, options())).unwrap();
+            writer
+                .write_record(events("\"s\"", parts, options()))
+                .unwrap();
+            assert_eq!(writer.records(), 2);
+            assert_eq!(writer.into_inner(), alloc::format!("{expected}\"s\"\n"));
+        }
+    }
+
+    #[test]
+    fn round_trips_records() {
+        let records = vec![
+            json!({"id": 1, "name": "Ada", "tags": ["math", "engines"]}),
+            json!([]),
+            json!("line\nbreak \u{2028} and \"quotes\""),
+            json!(null),
+            json!({"nested": {"deep": [[1.5, -2], {"x": false}]}, "empty": {}}),
+            json!(42),
+        ];
+
+        // Write every record from its events, parsed in small chunks.
+        let mut writer = NdjsonWriter::new(String::new());
+        for record in &records {
+            let text = serde_json::to_string(record).unwrap();
+            let record = events(&text, 4, ParserOptions::default());
+            writer.write_record(record).unwrap();
+        }
+        assert_eq!(writer.records(), records.len());
+        let output = writer.into_inner();
+        assert_eq!(output.lines().count(), records.len());
+
+        // Each line is one record.
+        for (line, record) in output.lines().zip(&records) {
+            assert_eq!(
+                &serde_json::from_str::<serde_json::Value>(line).unwrap(),
+                record
+            );
+        }
+
+        // The whole output parses back as a stream of the same values.
+        let options = ParserOptions {
+            allow_multiple_json_values: true,
+            non_scalar_values: NonScalarValueMode::Roots,
+            string_value_mode: StringValueMode::Values,
+            ..Default::default()
+        };
+        let values: Vec<serde_json::Value> = events(&output, 1, options)
+            .into_iter()
+            .filter_map(|event| match event {
+                ParseEvent::ArrayEnd { value, .. } => Some(Value::Array(value.unwrap())),
+                ParseEvent::ObjectEnd { value, .. } => Some(Value::Object(value.unwrap())),
+                ParseEvent::Null { .. } => Some(Value::Null),
+                ParseEvent::Number { value, .. } => Some(Value::Number(value)),
+                ParseEvent::String {
+                    value: Some(value),
+                    is_final: true,
+                    ..
+                } => Some(Value::String(value)),
+                _ => None,
+            })
+            .map(|value| serde_json::from_str(&value.to_string()).unwrap())
+            .collect();
+        assert_eq!(values, records);
+    }
+
+    #[test]
+    fn unbalanced_records_write_nothing() {
+        let all = events(JSON, 3, options());
+        let mut writer = NdjsonWriter::new(String::new());
+        let no_events: [ParseEvent; 0] = [];
+        assert_eq!(writer.write_record(no_events), Err(NdjsonError::Empty));
+        assert_eq!(
+            writer.write_record(all[..all.len() - 1].to_vec()),
+            Err(NdjsonError::Incomplete)
+        );
+        let mut two = all.clone();
+        two.extend(all.iter().cloned());
+        assert_eq!(
+            writer.write_record(two),
+            Err(NdjsonError::Unexpected { index: all.len() })
+        );
+        let mismatched: [ParseEvent; 2] = [
+            ParseEvent::ArrayStart {
+                path: Vec::new(),
+                capacity_hint: None,
+                parent_key: None,
+            },
+            ParseEvent::ObjectEnd {
+                path: Vec::new(),
+                value: None,
+            },
+        ];
+        assert_eq!(
+            writer.write_record(mismatched),
+            Err(NdjsonError::Unexpected { index: 1 })
+        );
+        let wrong_index: [ParseEvent; 2] = [
+            ParseEvent::ArrayStart {
+                path: Vec::new(),
+                capacity_hint: None,
+                parent_key: None,
+            },
+            ParseEvent::Null { path: path![1] },
+        ];
+        assert_eq!(
+            writer.write_record(wrong_index),
+            Err(NdjsonError::Unexpected { index: 1 })
+        );
+        assert_eq!(writer.records(), 0);
+        assert_eq!(writer.into_inner(), "");
+    }
+}
@@ -0,0 +1,499 @@
+//! Buffering of object members for rewriting the event stream.
+//!
+//! Some options change which members of an object are reported, or in what
+//! order, and so cannot be decided until the object closes. [`ObjectBuffer`]
+//! holds back the events of each object and, when it closes:
+//!
+//! - drops the events of every member whose key appears again later in the same
+//!   object (`allow_duplicate_keys_last_wins_events`), and
+//! - reorders the members by key (`object_key_sort`).
+//!
+//! Events outside of objects pass straight through. On the way out, scalars
+//! are wrapped in `ScalarBegin` and `ScalarEnd` events if
+//! `scalar_boundary_events` is set, and object members are preceded by an
+//! `ObjectKey` event if `emit_key_events` is set; this needs no buffering,
+//! but shares the output queue.
+
+use alloc::{
+    collections::{BTreeSet, VecDeque},
+    vec::Vec,
+};
+use core::cmp::Ordering;
+
+use crate::{
+    JsonValue, KeySortMode, ParseEvent, ParserOptions, PathComponent,
+    checkpoint::{CheckpointError, Reader},
+    event::Key,
+};
+
+#[derive(Debug, Clone)]
+struct ObjectFrame {
+    /// Length of the object's own path.
+    depth: usize,
+    /// Key and event range (into the buffer) of each completed member.
+    members: Vec<(Key, usize, usize)>,
+    /// Key and first buffer index of the member being read.
+    current: Option<(Key, usize)>,
+}
+
+impl ObjectFrame {
+    fn close_member(&mut self, end: usize) {
+        if let Some((key, start)) = self.current.take() {
+            self.members.push((key, start, end));
+        }
+    }
+}
+
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone)]
+pub(crate) struct ObjectBuffer<V: JsonValue> {
+    last_wins: bool,
+    sort: KeySortMode,
+    scalar_boundaries: bool,
+    key_events: bool,
+    /// Events of the outermost open object; dropped events become `None`.
+    buffer: Vec<Option<ParseEvent<V>>>,
+    objects: Vec<ObjectFrame>,
+    ready: VecDeque<ParseEvent<V>>,
+    in_string: bool,
+    /// Whether the last string event emitted is a non-final fragment.
+    emitting_string: bool,
+}
+
+impl<V: JsonValue> ObjectBuffer<V> {
+    /// Returns a buffer if `options` need one.
+    pub(crate) fn for_options(options: &ParserOptions) -> Option<Self> {
+        let last_wins = options.allow_duplicate_keys_last_wins_events;
+        let sort = options.object_key_sort;
+        let scalar_boundaries = options.scalar_boundary_events;
+        let key_events = options.emit_key_events;
+        (last_wins || sort != KeySortMode::None || scalar_boundaries || key_events).then(|| Self {
+            last_wins,
+            sort,
+            scalar_boundaries,
+            key_events,
+            buffer: Vec::new(),
+            objects: Vec::new(),
+            ready: VecDeque::new(),
+            in_string: false,
+            emitting_string: false,
+        })
+    }
+
+    /// Returns `true` if no events are held back or waiting, so that the
+    /// rest of the state fits in a parser checkpoint.
+    pub(crate) fn is_idle(&self) -> bool {
+        self.buffer.is_empty() && self.objects.is_empty() && self.ready.is_empty()
+    }
+
+    /// Appends the string state of an idle buffer to a parser checkpoint.
+    pub(crate) fn write_compact(&self, out: &mut Vec<u8>) {
+        out.push(u8::from(self.in_string) | u8::from(self.emitting_string) << 1);
+    }
+
+    /// Restores the state written by [`write_compact`](Self::write_compact).
+    pub(crate) fn read_compact(&mut self, reader: &mut Reader<'_>) -> Result<(), CheckpointError> {
+        let flags = reader.byte()?;
+        if flags > 0b11 {
+            return Err(CheckpointError::Invalid);
+        }
+        self.in_string = flags & 1 != 0;
+        self.emitting_string = flags & 0b10 != 0;
+        Ok(())
+    }
+
+    /// Returns the next event that is ready to be emitted.
+    pub(crate) fn pop(&mut self) -> Option<ParseEvent<V>> {
+        self.ready.pop_front()
+    }
+
+    pub(crate) fn push(&mut self, event: ParseEvent<V>) {
+        // Members only need holding back to be dropped or reordered.
+        if !self.last_wins && self.sort == KeySortMode::None {
+            self.emit(event);
+            return;
+        }
+        let starts_value = match &event {
+            ParseEvent::ArrayEnd { .. } | ParseEvent::ObjectEnd { .. } => false,
+            ParseEvent::String { is_final, .. } => {
+                let starts = !self.in_string;
+                self.in_string = !is_final;
+                starts
+            }
+            _ => true,
+        };
+        let index = self.buffer.len();
+        if let Some(frame) = self.objects.last_mut() {
+            let path = event.path();
+            if starts_value && path.len() == frame.depth + 1 {
+                if let Some(PathComponent::Key(key)) = path.last() {
+                    frame.close_member(index);
+                    frame.current = Some((key.clone(), index));
+                }
+            }
+        }
+
+        match &event {
+            ParseEvent::ObjectBegin { path, .. } => self.objects.push(ObjectFrame {
+                depth: path.len(),
+                members: Vec::new(),
+                current: None,
+            }),
+            ParseEvent::ObjectEnd { .. } => {
+                if let Some(mut frame) = self.objects.pop() {
+                    frame.close_member(index);
+                    if self.last_wins {
+                        self.drop_overridden(&frame);
+                    }
+                    self.sort_members(&mut frame);
+                }
+            }
+            _ if self.objects.is_empty() => {
+                self.emit(event);
+                return;
+            }
+            _ => {}
+        }
+
+        self.buffer.push(Some(event));
+        if self.objects.is_empty() {
+            let mut buffer = core::mem::take(&mut self.buffer);
+            for event in buffer.drain(..).flatten() {
+                self.emit(event);
+            }
+            self.buffer = buffer;
+        }
+    }
+
+    /// Queues `event` for output, between boundary events if it is a scalar
+    /// and `scalar_boundaries` is set, and after a key event if it starts an
+    /// object member and `key_events` is set.
+    fn emit(&mut self, event: ParseEvent<V>) {
+        let starts_value = match &event {
+            ParseEvent::ArrayEnd { .. } | ParseEvent::ObjectEnd { .. } => false,
+            ParseEvent::String { is_final, .. } => {
+                let starts = !self.emitting_string;
+                self.emitting_string = !is_final;
+                starts
+            }
+            _ => true,
+        };
+        if self.key_events && starts_value {
+            if let Some(PathComponent::Key(key)) = event.path().last() {
+                self.ready.push_back(ParseEvent::ObjectKey {
+                    path: event.path_clone(),
+                    key: key.clone(),
+                });
+            }
+        }
+        let (begins, ends) = match &event {
+            _ if !self.scalar_boundaries => (false, false),
+            ParseEvent::Null { .. } | ParseEvent::Boolean { .. } | ParseEvent::Number { .. } => {
+                (true, true)
+            }
+            ParseEvent::String { is_final, .. } => (starts_value, *is_final),
+            _ => (false, false),
+        };
+        if !(begins || ends) {
+            self.ready.push_back(event);
+            return;
+        }
+        let path = event.path_clone();
+        if begins {
+            self.ready
+                .push_back(ParseEvent::ScalarBegin { path: path.clone() });
+        }
+        self.ready.push_back(event);
+        if ends {
+            self.ready.push_back(ParseEvent::ScalarEnd { path });
+        }
+    }
+
+    /// Drops the events of every member of `frame` that is followed by a
+    /// member with the same key.
+    fn drop_overridden(&mut self, frame: &ObjectFrame) {
+        let mut seen = BTreeSet::new();
+        for (key, start, end) in frame.members.iter().rev() {
+            if !seen.insert(key) {
+                self.buffer[*start..*end].fill(None);
+            }
+        }
+    }
+
+    /// Reorders the events of the members of `frame` by key. The member
+    /// ranges are contiguous and end where the object does.
+    fn sort_members(&mut self, frame: &mut ObjectFrame) {
+        let compare: fn(&Key, &Key) -> Ordering = match self.sort {
+            KeySortMode::None => return,
+            // UTF-8 preserves code point order.
+            KeySortMode::LexicographicAscii | KeySortMode::UnicodeCodePoint => {
+                |a: &Key, b: &Key| a.as_bytes().cmp(b.as_bytes())
+            }
+            KeySortMode::Utf16CodeUnit => |a: &Key, b: &Key| a.encode_utf16().cmp(b.encode_utf16()),
+        };
+        let Some(&(_, start, _)) = frame.members.first() else {
+            return;
+        };
+        frame.members.sort_by(|(a, ..), (b, ..)| compare(a, b));
+        let mut sorted = Vec::with_capacity(self.buffer.len() - start);
+        for (_, from, to) in &frame.members {
+            sorted.extend(self.buffer[*from..*to].iter_mut().map(Option::take));
+        }
+        for (slot, event) in self.buffer[start..].iter_mut().zip(sorted) {
+            *slot = event;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::{
+        KeySortMode, NonScalarValueMode, ParseEvent, ParserOptions, PathComponent, StreamingParser,
+        path, produce_chunks, tests::events,
+    };
+
+    /// The events of `json` with `allow_duplicate_keys_last_wins_events` set.
+    fn last_wins_events(json: &str, parts: usize, options: ParserOptions) -> Vec<ParseEvent> {
+        events(
+            json,
+            parts,
+            ParserOptions {
+                allow_duplicate_keys_last_wins_events: true,
+                ..options
+            },
+        )
+    }
+
+    #[test]
+    fn only_the_last_duplicate_is_forwarded() {
+        let json = r#"{"a": 1, "b": [1, 2], "a": {"x": "first"}, "b": "long string", "a": null}"#;
+        for parts in [1, 6, json.len()] {
+            let events = last_wins_events(json, parts, ParserOptions::default());
+            let summary: Vec<_> = events
+                .iter()
+                .filter(|event| {
+                    !matches!(
+                        event,
+                        ParseEvent::String {
+                            is_final: false,
+                            ..
+                        }
+                    )
+                })
+                .map(|event| event.path().to_vec())
+                .collect();
+            assert_eq!(
+                summary,
+                [Vec::new(), path!["b"], path!["a"], Vec::new()],
+                "{parts}"
+            );
+            assert!(matches!(events[1], ParseEvent::String { .. }));
+            assert!(matches!(events[events.len() - 2], ParseEvent::Null { .. }));
+        }
+    }
+
+    #[test]
+    fn nested_objects_are_filtered_independently() {
+        let json = r#"[{"k": 1, "k": 2}, 3, {"k": [{"d": 1, "d": {"e": 2, "e": 3}}], "m": 4}]"#;
+        let expected = crate::event::reconstruct_values(events(
+            r#"[{"k": 2}, 3, {"k": [{"d": {"e": 3}}], "m": 4}]"#,
+            1,
+            ParserOptions::default(),
+        ));
+        let events = last_wins_events(json, 1, ParserOptions::default());
+        assert_eq!(crate::event::reconstruct_values(events.clone()), expected);
+        assert_eq!(
+            events
+                .iter()
+                .filter(|event| matches!(event, ParseEvent::Number { .. }))
+                .count(),
+            4
+        );
+    }
+
+    #[test]
+    fn code_point_order_is_byte_order() {
+        let json = "{\"\u{10000}\": 1, \"\u{FF61}\": 2, \"e\": 3, \"é\": 4}";
+        let keys = |object_key_sort| -> Vec<_> {
+            events(
+                json,
+                1,
+                ParserOptions {
+                    object_key_sort,
+                    ..Default::default()
+                },
+            )
+            .into_iter()
+            .filter(|event| matches!(event, ParseEvent::Number { .. }))
+            .map(|event| event.path()[0].clone())
+            .collect()
+        };
+        let by_code_point = path!["e", "é", "\u{FF61}", "\u{10000}"];
+        assert_eq!(keys(KeySortMode::LexicographicAscii), by_code_point);
+        assert_eq!(keys(KeySortMode::UnicodeCodePoint), by_code_point);
+        assert_eq!(
+            keys(KeySortMode::Utf16CodeUnit),
+            path!["e", "é", "\u{10000}", "\u{FF61}"]
+        );
+    }
+
+    #[test]
+    fn built_values_are_unchanged() {
+        let json = r#"{"a": {"b": 1}, "c": [2], "a": 3}"#;
+        let options = ParserOptions {
+            non_scalar_values: NonScalarValueMode::Roots,
+            ..Default::default()
+        };
+        assert_eq!(
+            last_wins_events(json, 3, options).last(),
+            events(json, 1, options).last()
+        );
+    }
+
+    #[test]
+    fn scalars_are_entered_and_exited_like_containers() {
+        let json = r#"[1, "ab", {"k": null, "s": "a long string"}, [true], {}]"#;
+        for parts in [1, 5, json.len()] {
+            let mut parser = StreamingParser::new(ParserOptions {
+                scalar_boundary_events: true,
+                ..Default::default()
+            });
+            let mut plain = StreamingParser::new(ParserOptions::default());
+            let mut events = Vec::new();
+            let mut expected = Vec::new();
+            for chunk in produce_chunks(json, parts) {
+                events.extend(parser.feed(chunk).map(Result::unwrap));
+                expected.extend(plain.feed(chunk).map(Result::unwrap));
+            }
+            events.extend(parser.finish().map(Result::unwrap));
+            expected.extend(plain.finish().map(Result::unwrap));
+
+            // Every value is entered and exited at its own path, and scalar
+            // events arrive in between.
+            let mut open = Vec::new();
+            let mut scalars = 0;
+            for event in &events {
+                match event {
+                    ParseEvent::ArrayStart { path, .. } | ParseEvent::ObjectBegin { path, .. } => {
+                        open.push(path.clone());
+                    }
+                    ParseEvent::ScalarBegin { path } => {
+                        scalars += 1;
+                        open.push(path.clone());
+                    }
+                    ParseEvent::ArrayEnd { path, .. }
+                    | ParseEvent::ObjectEnd { path, .. }
+                    | ParseEvent::ScalarEnd { path } => {
+                        assert_eq!(open.pop().as_ref(), Some(path));
+                    }
+                    scalar => assert_eq!(open.last().map(Vec::as_slice), Some(scalar.path())),
+                }
+            }
+            assert!(open.is_empty());
+            assert_eq!(scalars, 5);
+
+            events.retain(|event| {
+                !matches!(
+                    event,
+                    ParseEvent::ScalarBegin { .. } | ParseEvent::ScalarEnd { .. }
+                )
+            });
+            assert_eq!(events, expected);
+        }
+    }
+
+    #[test]
+    fn boundary_and_key_events_do_not_hold_back_members() {
+        let mut parser = StreamingParser::new(ParserOptions {
+            scalar_boundary_events: true,
+            emit_key_events: true,
+            ..Default::default()
+        });
+        let events: Vec<_> = parser
+            .feed(r#"{"a": 1, "b": [2"#)
+            .map(Result::unwrap)
+            .collect();
+        assert!(matches!(
+            events.as_slice(),
+            [
+                ParseEvent::ObjectBegin { .. },
+                ParseEvent::ObjectKey { .. },
+                ParseEvent::ScalarBegin { .. },
+                ParseEvent::Number { .. },
+                ParseEvent::ScalarEnd { .. },
+                ParseEvent::ObjectKey { .. },
+                ParseEvent::ArrayStart { .. },
+            ]
+        ));
+    }
+
+    #[test]
+    fn keys_precede_their_members() {
+        let json = r#"{"a": "long string", "b": {"c": [1, {"d": null}]}, "a": true}"#;
+        for parts in [1, 5, json.len()] {
+            let options = ParserOptions {
+                scalar_boundary_events: true,
+                ..Default::default()
+            };
+            let mut parser = StreamingParser::new(ParserOptions {
+                emit_key_events: true,
+                ..options
+            });
+            let mut plain = StreamingParser::new(options);
+            let mut events = Vec::new();
+            let mut expected = Vec::new();
+            for chunk in produce_chunks(json, parts) {
+                events.extend(parser.feed(chunk).map(Result::unwrap));
+                expected.extend(plain.feed(chunk).map(Result::unwrap));
+            }
+            events.extend(parser.finish().map(Result::unwrap));
+            expected.extend(plain.finish().map(Result::unwrap));
+
+            // Each key event carries the path of the member's next event,
+            // which starts its value, and the key is the last component.
+            let mut keys = Vec::new();
+            for (i, event) in events.iter().enumerate() {
+                if let ParseEvent::ObjectKey { path, key } = event {
+                    assert_eq!(path.last().and_then(PathComponent::as_key), Some(&**key));
+                    assert_eq!(events[i + 1].path(), path.as_slice());
+                    assert!(!matches!(events[i + 1], ParseEvent::ObjectKey { .. }));
+                    keys.push(path.clone());
+                }
+            }
+            assert_eq!(
+                keys,
+                [
+                    path!["a"],
+                    path!["b"],
+                    path!["b", "c"],
+                    path!["b", "c", 1, "d"],
+                    path!["a"]
+                ],
+                "{parts}"
+            );
+
+            events.retain(|event| !matches!(event, ParseEvent::ObjectKey { .. }));
+            assert_eq!(events, expected);
+        }
+
+        // Only the keys of reported members get an event.
+        let events = last_wins_events(
+            r#"{"a": 1, "b": 2, "a": 3}"#,
+            1,
+            ParserOptions {
+                emit_key_events: true,
+                ..Default::default()
+            },
+        );
+        let keys: Vec<_> = events
+            .iter()
+            .filter_map(|event| match event {
+                ParseEvent::ObjectKey { key, .. } => Some(&**key),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(keys, ["b", "a"]);
+    }
+}
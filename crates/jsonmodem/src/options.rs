@@ -21,9 +21,10 @@
 /// proportional to the size of the largest string value, and network overhead
 /// proportional to the square of the total size of all string values - as each
 /// prefix may be transmitted many times.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum StringValueMode {
     /// The `value` field is always `None`.
+    #[default]
     None,
     /// The `value` field contains the full string, and is emitted only when the
     /// string has been fully parsed.
@@ -33,16 +34,42 @@ pub enum StringValueMode {
     Prefixes,
 }
 
-impl Default for StringValueMode {
-    fn default() -> Self {
-        Self::None
-    }
+/// Determines how invalid UTF-16 escape sequences in strings are decoded.
+///
+/// JSON strings may contain `\uXXXX` escapes for UTF-16 code units. Characters
+/// outside the Basic Multilingual Plane are written as a surrogate pair, e.g.
+/// `"\uD83D\uDE00"`. A surrogate that is not part of a valid pair cannot be
+/// represented in a Rust string.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DecodeMode {
+    /// Unpaired surrogates are a syntax error.
+    #[default]
+    StrictUnicode,
+    /// Unpaired surrogates are replaced with U+FFFD REPLACEMENT CHARACTER.
+    ///
+    /// This includes surrogates in input fed as bytes that are encoded as
+    /// three-byte sequences in the style of UTF-8, `ED A0..=BF 80..=BF`, as
+    /// in WTF-8 or text converted from UTF-16 without checking. Each such
+    /// sequence is replaced on its own, even if it is half of a pair. Other
+    /// invalid UTF-8 is still an error.
+    ReplaceInvalid,
+    /// Unpaired surrogates are a syntax error, and input fed as bytes may be
+    /// CESU-8, as stored by some databases and by Java's modified UTF-8.
+    ///
+    /// CESU-8 encodes a character above U+FFFF as its two UTF-16 surrogates,
+    /// each written as a three-byte sequence in the style of UTF-8: a high
+    /// surrogate `ED A0..=AF 80..=BF` followed by a low surrogate
+    /// `ED B0..=BF 80..=BF`. Each such pair is decoded to the character it
+    /// encodes. A surrogate sequence that is not part of a pair is invalid
+    /// UTF-8, as in the other modes.
+    Cesu8,
 }
 
 /// Controls emission of composite values during parsing.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum NonScalarValueMode {
     /// Do not emit composite values.
+    #[default]
     None,
     /// Emit events for all composite values.
     All,
@@ -50,10 +77,51 @@ pub enum NonScalarValueMode {
     Roots,
 }
 
-impl Default for NonScalarValueMode {
-    fn default() -> Self {
-        Self::None
-    }
+/// Determines the order in which object members are emitted.
+///
+/// Any mode other than `None` holds back the events of each object until it
+/// closes, so memory use grows with the size of the largest object, counting
+/// everything nested in it, and no member of an object is seen before the
+/// whole object has been read.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeySortMode {
+    /// Members are emitted in document order, as soon as they are parsed.
+    #[default]
+    None,
+    /// Members are sorted by comparing the UTF-8 encodings of their keys byte
+    /// by byte. For ASCII keys this is plain lexicographic order.
+    ///
+    /// UTF-8 preserves code point order, so this is the same order as
+    /// `UnicodeCodePoint` for every key.
+    LexicographicAscii,
+    /// Members are sorted by comparing their keys character by character, by
+    /// Unicode code point.
+    ///
+    /// This is the same order as `LexicographicAscii`; the two names are
+    /// kept for callers that state the order they rely on. RFC 8785 orders
+    /// keys by UTF-16 code units instead, which differs from code point
+    /// order only for keys that contain both characters above U+FFFF and
+    /// characters in U+E000..=U+FFFF.
+    UnicodeCodePoint,
+    /// Members are sorted by comparing the UTF-16 encodings of their keys
+    /// code unit by code unit, as RFC 8785 requires.
+    Utf16CodeUnit,
+}
+
+/// Determines how a byte order mark (U+FEFF) at the very start of the input
+/// is treated.
+///
+/// RFC 8259 does not allow a byte order mark, but many Windows tools write
+/// one at the start of UTF-8 files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BomHandling {
+    /// A leading byte order mark is a syntax error.
+    Reject,
+    /// A leading byte order mark is consumed before the first token.
+    ///
+    /// U+FEFF elsewhere in the input is whitespace regardless of this option.
+    #[default]
+    Skip,
 }
 
 /// Configuration options for the JSON streaming parser.
@@ -77,7 +145,9 @@ impl Default for NonScalarValueMode {
 ///
 /// # Default
 ///
-/// All options default to `false`.
+/// Boolean options default to `false`, limits to `None`, and the other
+/// options to the default of their type, as documented on each field.
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Clone, Copy, Default)]
 pub struct ParserOptions {
     /// Whether to allow any Unicode whitespace between JSON values.
@@ -113,6 +183,18 @@ pub struct ParserOptions {
     /// `false`
     pub allow_multiple_json_values: bool,
 
+    /// Whether strings and property names may be enclosed in single quotes,
+    /// as in JavaScript and Python, e.g. `{'a': 'it\'s'}`.
+    ///
+    /// Single-quoted strings accept the same escape sequences as
+    /// double-quoted ones, plus `\'`; a `"` inside them needs no escape.
+    /// Events are the same as for the equivalent double-quoted string.
+    ///
+    /// # Default
+    ///
+    /// `false`
+    pub allow_single_quoted_strings: bool,
+
     /// Determines how string values are emitted during parsing.
     ///
     /// This option configures the parser's behavior for emitting string tokens,
@@ -146,6 +228,141 @@ pub struct ParserOptions {
     /// `NonScalarValueMode::None`
     pub non_scalar_values: NonScalarValueMode,
 
+    /// The maximum length, in bytes of decoded UTF-8, of a single string or
+    /// property name.
+    ///
+    /// The limit applies to each string separately, including strings whose
+    /// contents arrive over many calls to `feed`. A string that grows past the
+    /// limit causes a syntax error, which guards against unbounded memory use
+    /// on untrusted input.
+    ///
+    /// # Default
+    ///
+    /// `None` (no limit)
+    pub max_string_bytes: Option<usize>,
+
+    /// The maximum number of arrays and objects opened or closed in a row,
+    /// with no other token (a scalar, key, `,` or `:`) between them.
+    ///
+    /// A run such as `[[[[[[` or `]]]]]]` that goes past the limit causes a
+    /// syntax error, which guards against bursts of nesting changes on
+    /// untrusted input. Wide documents such as `[[], [], []]` are not
+    /// affected, and the count does not depend on how the input is split into
+    /// chunks. Each root value starts a new count.
+    ///
+    /// # Default
+    ///
+    /// `None` (no limit)
+    pub max_nesting_change_per_token: Option<usize>,
+
+    /// How unpaired UTF-16 surrogate escapes in strings are decoded.
+    ///
+    /// # Default
+    ///
+    /// `DecodeMode::StrictUnicode`
+    pub decode_mode: DecodeMode,
+
+    /// Whether input that ends between the two halves of a surrogate pair
+    /// reports a dedicated "truncated surrogate pair" error.
+    ///
+    /// When `false`, a high surrogate still waiting for its low half at end of
+    /// input is handled according to [`decode_mode`](Self::decode_mode) before
+    /// the usual end-of-input error is reported.
+    ///
+    /// # Default
+    ///
+    /// `false`
+    pub error_on_truncated_surrogate_pair: bool,
+
+    /// Whether an object member is dropped from the event stream when a later
+    /// member of the same object has the same key.
+    ///
+    /// Duplicate keys are always accepted, and built values keep the last
+    /// member, but by default the events of every member are emitted. With
+    /// this option the events of an object are held back until the object
+    /// closes, and only the last member for each key is emitted, in its
+    /// original position. Objects are no longer streamed incrementally, and
+    /// memory use grows with the size of the largest object.
+    ///
+    /// # Default
+    ///
+    /// `false`
+    pub allow_duplicate_keys_last_wins_events: bool,
+
+    /// Emit the members of every object sorted by key, e.g. to produce
+    /// canonical output. See [`KeySortMode`] for the memory cost.
+    ///
+    /// Members with the same key keep their document order.
+    ///
+    /// # Default
+    ///
+    /// `KeySortMode::None`
+    pub object_key_sort: KeySortMode,
+
+    /// Whether every scalar value is wrapped in a `ScalarBegin` and a
+    /// `ScalarEnd` event, like the start and end events of arrays and
+    /// objects.
+    ///
+    /// Every value then has an event on entry and on exit, so that a consumer
+    /// building a tree can treat scalars and containers alike. For strings,
+    /// the fragments are emitted between the two.
+    ///
+    /// # Default
+    ///
+    /// `false`
+    pub scalar_boundary_events: bool,
+
+    /// Whether every member of an object is preceded by an `ObjectKey` event
+    /// carrying its key.
+    ///
+    /// The key is also the last component of the path of the member's
+    /// events; the separate event lets a consumer handle keys, e.g. to rename
+    /// them, without inspecting every value event. With
+    /// `allow_duplicate_keys_last_wins_events`, only the keys of the members
+    /// that are reported get an event.
+    ///
+    /// # Default
+    ///
+    /// `false`
+    pub emit_key_events: bool,
+
+    /// Whether `ArrayStart` and `ObjectBegin` events carry a
+    /// `capacity_hint`.
+    ///
+    /// When a container opens, the parser looks ahead through the input
+    /// already fed, up to a window of a few thousand characters, and counts
+    /// the members that start there. The count is exact if the container also
+    /// closes within that input, and a lower bound otherwise; it is `None` if
+    /// no member has arrived yet. Prettified documents fed in large chunks
+    /// get exact hints for most containers.
+    ///
+    /// # Default
+    ///
+    /// `false`
+    pub lookahead_capacity_hints: bool,
+
+    /// Whether `ArrayStart` and `ObjectBegin` events carry a `parent_key`:
+    /// the key of the object member whose value is the array or object.
+    ///
+    /// The key is the last component of the event's path; the field saves a
+    /// consumer that handles nested containers by the name of their member
+    /// from inspecting the path.
+    ///
+    /// # Default
+    ///
+    /// `false`
+    pub attach_parent_keys: bool,
+
+    /// How a byte order mark at the start of the input is treated.
+    ///
+    /// Only the first character of the input is affected; with
+    /// `allow_multiple_json_values`, later values are not checked.
+    ///
+    /// # Default
+    ///
+    /// `BomHandling::Skip`
+    pub bom_handling: BomHandling,
+
     #[cfg(any(test, feature = "fuzzing"))]
     /// Panic on syntax errors instead of returning them.
     ///
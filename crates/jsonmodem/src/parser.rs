@@ -23,20 +23,27 @@
 #![expect(clippy::inline_always)]
 
 use alloc::{
+    boxed::Box,
     format,
     string::{String, ToString},
     vec,
     vec::Vec,
 };
-use core::{f64, fmt};
+use core::{f64, ops::Range};
+
+use smallvec::SmallVec;
 
 use crate::{
-    JsonValue, JsonValueFactory, StdValueFactory, StringValueMode, Value,
+    BomHandling, DecodeMode, JsonValue, JsonValueFactory, StdValueFactory, StreamPosition,
+    StringValueMode, Value, ValueKind,
     buffer::Buffer,
+    checkpoint::{self, CheckpointError},
+    error::ParserError,
     escape_buffer::UnicodeEscapeBuffer,
     event::{Index, Key, ParseEvent, PathComponent},
     event_stack::EventStack,
     literal_buffer::{self, ExpectedLiteralBuffer},
+    object_buffer::ObjectBuffer,
     options::{NonScalarValueMode, ParserOptions},
     value_zipper::{ValueBuilder, ZipperError},
 };
@@ -57,11 +64,30 @@ pub(crate) enum Token {
     },
     Boolean(bool),
     Null,
-    Number(f64),
+    Number(Number),
     /// Must be one of: `{` `}` `[` `]` `:` `,`
     Punctuator(u8),
 }
 
+/// A lexed number. Integer literals that fit in an `i64` or `u64` are kept
+/// exact; everything else is an `f64`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Number {
+    F64(f64),
+    I64(i64),
+    U64(u64),
+}
+
+impl core::fmt::Display for Number {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Number::F64(n) => write!(f, "{n}"),
+            Number::I64(n) => write!(f, "{n}"),
+            Number::U64(n) => write!(f, "{n}"),
+        }
+    }
+}
+
 impl Token {
     /// Returns `true` if the token value is [`Eof`].
     ///
@@ -119,6 +145,13 @@ enum LexState {
     Start,
     StringEscape,
     StringEscapeUnicode,
+    /// A `\u` escape decoded to this high surrogate; a `\` should follow.
+    StringHighSurrogate(u16),
+    /// As above, after the `\`; a `u` should follow.
+    StringHighSurrogateEscape(u16),
+    /// Reading the digits of the `\u` escape that follows this high
+    /// surrogate.
+    StringLowSurrogateUnicode(u16),
     BeforePropertyName,
     AfterPropertyName,
     BeforePropertyValue,
@@ -129,6 +162,93 @@ enum LexState {
     Error,
 }
 
+/// The number of buffered characters searched for the members of a container
+/// by `lookahead_capacity_hints`.
+const CAPACITY_HINT_LOOKAHEAD: usize = 4096;
+
+/// Every `ParseState`, indexed by its discriminant, for reading checkpoints.
+const PARSE_STATES: [ParseState; 9] = [
+    ParseState::Start,
+    ParseState::BeforePropertyName,
+    ParseState::AfterPropertyName,
+    ParseState::BeforePropertyValue,
+    ParseState::BeforeArrayValue,
+    ParseState::AfterPropertyValue,
+    ParseState::AfterArrayValue,
+    ParseState::End,
+    ParseState::Error,
+];
+
+impl LexState {
+    /// The state as a small integer and, for the surrogate states, the high
+    /// surrogate they hold.
+    fn to_parts(self) -> (u8, u16) {
+        use LexState::*;
+        match self {
+            Default => (0, 0),
+            Value => (1, 0),
+            ValueLiteral => (2, 0),
+            Sign => (3, 0),
+            Zero => (4, 0),
+            DecimalInteger => (5, 0),
+            DecimalPoint => (6, 0),
+            DecimalFraction => (7, 0),
+            DecimalExponent => (8, 0),
+            DecimalExponentSign => (9, 0),
+            DecimalExponentInteger => (10, 0),
+            String => (11, 0),
+            Start => (12, 0),
+            StringEscape => (13, 0),
+            StringEscapeUnicode => (14, 0),
+            StringHighSurrogate(high) => (15, high),
+            StringHighSurrogateEscape(high) => (16, high),
+            StringLowSurrogateUnicode(high) => (17, high),
+            BeforePropertyName => (18, 0),
+            AfterPropertyName => (19, 0),
+            BeforePropertyValue => (20, 0),
+            BeforeArrayValue => (21, 0),
+            AfterPropertyValue => (22, 0),
+            AfterArrayValue => (23, 0),
+            End => (24, 0),
+            Error => (25, 0),
+        }
+    }
+
+    /// The inverse of [`to_parts`](Self::to_parts).
+    fn from_parts(tag: u8, high: u16) -> Option<Self> {
+        use LexState::*;
+        Some(match tag {
+            0 => Default,
+            1 => Value,
+            2 => ValueLiteral,
+            3 => Sign,
+            4 => Zero,
+            5 => DecimalInteger,
+            6 => DecimalPoint,
+            7 => DecimalFraction,
+            8 => DecimalExponent,
+            9 => DecimalExponentSign,
+            10 => DecimalExponentInteger,
+            11 => String,
+            12 => Start,
+            13 => StringEscape,
+            14 => StringEscapeUnicode,
+            15 => StringHighSurrogate(high),
+            16 => StringHighSurrogateEscape(high),
+            17 => StringLowSurrogateUnicode(high),
+            18 => BeforePropertyName,
+            19 => AfterPropertyName,
+            20 => BeforePropertyValue,
+            21 => BeforeArrayValue,
+            22 => AfterPropertyValue,
+            23 => AfterArrayValue,
+            24 => End,
+            25 => Error,
+            _ => return None,
+        })
+    }
+}
+
 impl From<ParseState> for LexState {
     fn from(state: ParseState) -> Self {
         match state {
@@ -253,11 +373,37 @@ impl FrameStack {
         path
     }
 
+    /// The path of the next value in the innermost container, allocated once
+    /// at its final size.
+    #[inline]
+    pub fn to_child_path_components(&self) -> Vec<PathComponent> {
+        let mut path = Vec::with_capacity(self.stack.len() + 1);
+        for (pc, _) in &self.stack {
+            path.push(pc.clone());
+        }
+        if let Some(frame) = self.last() {
+            path.push(frame.to_path_component());
+        }
+        path
+    }
+
+    /// The number of open containers.
+    pub fn depth(&self) -> usize {
+        usize::from(self.root.is_some()) + self.stack.len()
+    }
+
     #[inline]
     pub fn clear(&mut self) {
         self.root = None;
         self.stack.clear();
     }
+
+    /// The open containers, outermost first.
+    pub fn iter(&self) -> impl Iterator<Item = &Frame> {
+        self.root
+            .iter()
+            .chain(self.stack.iter().map(|(_, frame)| frame))
+    }
 }
 
 /// The streaming JSON parser. Uses the default `Value` type for JSON values.
@@ -290,6 +436,10 @@ pub struct StreamingParserImpl<V: JsonValue = Value> {
 
     /// Current *global* character position.
     pos: usize,
+    /// Current *global* byte position in the UTF-8 input.
+    byte_pos: usize,
+    /// Byte position of the first character of the last token lexed.
+    token_start: usize,
     line: usize,
     column: usize,
 
@@ -300,6 +450,8 @@ pub struct StreamingParserImpl<V: JsonValue = Value> {
     /// Lexer helpers
     buffer: String, // reused for numbers / literals / strings
     fragment_start: usize, // used to track string fragments start position within `buffer`
+    /// Capacity reserved for `buffer` up front
+    string_capacity: usize,
     unicode_escape_buffer: UnicodeEscapeBuffer, // for unicode escapes
     expected_literal: ExpectedLiteralBuffer,
     partial_lex: bool, // true ← we returned an *incomplete* token
@@ -309,8 +461,28 @@ pub struct StreamingParserImpl<V: JsonValue = Value> {
     events: EventStack<V>,
 
     multiple_values: bool,
+    allow_single_quoted_strings: bool,
+    /// Whether the string being lexed opened with `'` rather than `"`.
+    single_quoted: bool,
     string_value_mode: StringValueMode,
     non_scalar_values: NonScalarValueMode,
+    max_string_bytes: Option<usize>,
+    /// Bytes of the current string already handed out and dropped from `buffer`
+    string_bytes_flushed: usize,
+    max_nesting_change_per_token: Option<usize>,
+    /// Arrays and objects opened or closed since the last other token
+    nesting_changes: usize,
+    decode_mode: DecodeMode,
+    error_on_truncated_surrogate_pair: bool,
+    bom_handling: BomHandling,
+    lookahead_capacity_hints: bool,
+    attach_parent_keys: bool,
+    /// Holds back object members, present when
+    /// `allow_duplicate_keys_last_wins_events` or `object_key_sort` is set.
+    object_buffer: Option<Box<ObjectBuffer<V>>>,
+    /// The `true` and `false` values from [`JsonValueFactory::init_bools`],
+    /// created at the first boolean.
+    bools: Option<(V::Bool, V::Bool)>,
 
     /// Panic on syntax errors instead of returning them
     #[cfg(test)]
@@ -321,6 +493,54 @@ pub struct StreamingParserImpl<V: JsonValue = Value> {
     lexed_tokens: Vec<Token>,
 }
 
+/// Cloning a parser copies all of its state, including any buffered input
+/// that has not been consumed yet and the partially built value. The clone and
+/// the original can then be fed independently, e.g. to try two different
+/// continuations of the same prefix.
+impl<V: JsonValue> Clone for StreamingParserImpl<V> {
+    fn clone(&self) -> Self {
+        Self {
+            source: self.source.clone(),
+            end_of_input: self.end_of_input,
+            pos: self.pos,
+            byte_pos: self.byte_pos,
+            token_start: self.token_start,
+            line: self.line,
+            column: self.column,
+            parse_state: self.parse_state,
+            lex_state: self.lex_state,
+            buffer: self.buffer.clone(),
+            fragment_start: self.fragment_start,
+            string_capacity: self.string_capacity,
+            unicode_escape_buffer: self.unicode_escape_buffer.clone(),
+            expected_literal: self.expected_literal,
+            partial_lex: self.partial_lex,
+            events: self.events.clone_along(&self.frames.to_path_components()),
+            frames: self.frames.clone(),
+            multiple_values: self.multiple_values,
+            allow_single_quoted_strings: self.allow_single_quoted_strings,
+            single_quoted: self.single_quoted,
+            string_value_mode: self.string_value_mode,
+            non_scalar_values: self.non_scalar_values,
+            max_string_bytes: self.max_string_bytes,
+            string_bytes_flushed: self.string_bytes_flushed,
+            max_nesting_change_per_token: self.max_nesting_change_per_token,
+            nesting_changes: self.nesting_changes,
+            decode_mode: self.decode_mode,
+            error_on_truncated_surrogate_pair: self.error_on_truncated_surrogate_pair,
+            bom_handling: self.bom_handling,
+            lookahead_capacity_hints: self.lookahead_capacity_hints,
+            attach_parent_keys: self.attach_parent_keys,
+            object_buffer: self.object_buffer.clone(),
+            bools: self.bools,
+            #[cfg(test)]
+            panic_on_error: self.panic_on_error,
+            #[cfg(test)]
+            lexed_tokens: self.lexed_tokens.clone(),
+        }
+    }
+}
+
 impl<V: JsonValue> Default for StreamingParserImpl<V> {
     fn default() -> Self {
         Self::new(ParserOptions::default())
@@ -332,6 +552,25 @@ pub struct StreamingParserIteratorWith<'a, F: JsonValueFactory> {
     pub(crate) factory: F,
 }
 
+impl<F: JsonValueFactory> StreamingParserIteratorWith<'_, F> {
+    /// The number of bytes lexed so far; see
+    /// [`StreamingParserImpl::bytes_consumed`].
+    pub(crate) fn bytes_consumed(&self) -> usize {
+        self.parser.bytes_consumed()
+    }
+
+    /// The byte range of the token behind the last event; see
+    /// [`StreamingParserImpl::last_token_span`].
+    pub(crate) fn last_token_span(&self) -> Range<usize> {
+        self.parser.last_token_span()
+    }
+
+    /// How far parsing has got; see [`StreamingParserImpl::position`].
+    pub(crate) fn position(&self) -> StreamPosition {
+        self.parser.position()
+    }
+}
+
 impl<F: JsonValueFactory> Iterator for StreamingParserIteratorWith<'_, F> {
     type Item = Result<ParseEvent<F::Value>, ParserError>;
 
@@ -359,6 +598,56 @@ impl<F: JsonValueFactory> ClosedStreamingParser<F> {
     pub(crate) fn unstable_get_current_value_ref(&self) -> Option<&F::Value> {
         self.parser.unstable_get_current_value_ref()
     }
+
+    /// The number of bytes lexed so far; see
+    /// [`StreamingParserImpl::bytes_consumed`].
+    pub(crate) fn bytes_consumed(&self) -> usize {
+        self.parser.bytes_consumed()
+    }
+
+    /// The byte range of the last token lexed; see
+    /// [`StreamingParserImpl::last_token_span`].
+    pub(crate) fn last_token_span(&self) -> Range<usize> {
+        self.parser.last_token_span()
+    }
+
+    /// How far parsing has got; see [`StreamingParserImpl::position`].
+    pub(crate) fn position(&self) -> StreamPosition {
+        self.parser.position()
+    }
+
+    /// Returns the number of bytes of input not yet parsed.
+    ///
+    /// Once the iterator has returned `None` this is zero, unless parsing
+    /// stopped at a syntax error, in which case it counts the input from
+    /// the character at which the error was found.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use jsonmodem::{ParserOptions, StreamingParser};
+    /// let mut parser = StreamingParser::new(ParserOptions::default());
+    /// let _ = parser.feed("[1] [2]");
+    /// let mut closed = parser.finish();
+    /// assert_eq!(closed.remaining_bytes(), 7);
+    /// assert!(closed.by_ref().any(|event| event.is_err()));
+    /// assert_eq!(closed.remaining_bytes(), 3);
+    /// ```
+    #[must_use]
+    pub fn remaining_bytes(&self) -> usize {
+        self.parser.source.pending_bytes()
+    }
+
+    /// Returns `true` if every byte of input has been parsed, every event
+    /// pulled, and the input ended after a complete value, so that the
+    /// iterator is done and no syntax error was reported.
+    ///
+    /// This is `false` for input that stops partway through a value, e.g. a
+    /// truncated response, even after the resulting error has been pulled.
+    #[must_use]
+    pub fn is_exhausted(&self) -> bool {
+        self.parser.is_exhausted()
+    }
 }
 
 impl<F: JsonValueFactory> Iterator for ClosedStreamingParser<F> {
@@ -389,11 +678,13 @@ impl<V: JsonValue> StreamingParserImpl<V> {
     /// ```
     pub fn new(options: ParserOptions) -> Self {
         Self {
-            source: Buffer::new(),
+            source: Buffer::new(options.decode_mode),
             end_of_input: false,
             partial_lex: false,
 
             pos: 0,
+            byte_pos: 0,
+            token_start: 0,
             line: 1,
             column: 1,
 
@@ -402,6 +693,7 @@ impl<V: JsonValue> StreamingParserImpl<V> {
 
             buffer: String::new(),
             fragment_start: 0,
+            string_capacity: 0,
             unicode_escape_buffer: UnicodeEscapeBuffer::new(),
             expected_literal: ExpectedLiteralBuffer::none(),
             frames: FrameStack::new(),
@@ -416,8 +708,21 @@ impl<V: JsonValue> StreamingParserImpl<V> {
             ),
 
             multiple_values: options.allow_multiple_json_values,
+            allow_single_quoted_strings: options.allow_single_quoted_strings,
+            single_quoted: false,
             string_value_mode: options.string_value_mode,
             non_scalar_values: options.non_scalar_values,
+            max_string_bytes: options.max_string_bytes,
+            string_bytes_flushed: 0,
+            max_nesting_change_per_token: options.max_nesting_change_per_token,
+            nesting_changes: 0,
+            decode_mode: options.decode_mode,
+            error_on_truncated_surrogate_pair: options.error_on_truncated_surrogate_pair,
+            bom_handling: options.bom_handling,
+            lookahead_capacity_hints: options.lookahead_capacity_hints,
+            attach_parent_keys: options.attach_parent_keys,
+            object_buffer: ObjectBuffer::for_options(&options).map(Box::new),
+            bools: None,
             #[cfg(test)]
             panic_on_error: options.panic_on_error,
             #[cfg(test)]
@@ -425,6 +730,34 @@ impl<V: JsonValue> StreamingParserImpl<V> {
         }
     }
 
+    /// Reserves `capacity` bytes for the scratch buffer strings are built in.
+    ///
+    /// Each property name and string fragment is built in a scratch buffer
+    /// that the parser keeps between strings, and copied out of it when
+    /// produced. The buffer reallocates as it grows, e.g. at escape sequences
+    /// or chunk boundaries, until it fits the longest string seen. When
+    /// strings are typically of a known size, reserving that much up front
+    /// avoids those reallocations. Events are unaffected.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use jsonmodem::{ParseEvent, ParserOptions, StreamingParser};
+    ///
+    /// let mut parser = StreamingParser::new(ParserOptions::default()).with_string_capacity(256);
+    /// let events: Vec<_> = parser.feed(r#"["tab\tseparated\tvalues"]"#).collect();
+    /// assert!(matches!(
+    ///     &events[1],
+    ///     Ok(ParseEvent::String { fragment, .. }) if fragment == "tab\tseparated\tvalues"
+    /// ));
+    /// ```
+    #[must_use]
+    pub fn with_string_capacity(mut self, capacity: usize) -> Self {
+        self.string_capacity = capacity;
+        self.buffer.reserve(capacity);
+        self
+    }
+
     /// TODO - Update with concrete example following pyo3 integration
     #[doc(hidden)]
     pub fn feed_with<'a, F: JsonValueFactory<Value = V>>(
@@ -432,7 +765,7 @@ impl<V: JsonValue> StreamingParserImpl<V> {
         factory: F,
         text: &str,
     ) -> StreamingParserIteratorWith<'a, F> {
-        self.source.push(text);
+        self.push_chunk(text);
         StreamingParserIteratorWith {
             parser: self,
             factory,
@@ -450,13 +783,212 @@ impl<V: JsonValue> StreamingParserImpl<V> {
         mut self,
         factory: F,
     ) -> ClosedStreamingParser<F> {
-        self.end_of_input = true;
+        self.end_input();
         ClosedStreamingParser {
             parser: self,
             factory,
         }
     }
 
+    /// Parses the rest of the document from `text` and returns its value,
+    /// built with `factory`.
+    ///
+    /// Input fed earlier is parsed first, as long as the iterators returned
+    /// for it were not advanced: the value is assembled from events, and
+    /// events already taken from the parser cannot be seen again. The result
+    /// does not depend on `non_scalar_values`. With
+    /// `allow_multiple_json_values`, the first value is returned and the rest
+    /// of the input is only checked for errors.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first syntax error, or an error if the input ends before a
+    /// value is complete. Also returns an error, without parsing anything, if
+    /// the parser has already processed some of its input.
+    pub fn into_value_with<F: JsonValueFactory<Value = V>>(
+        mut self,
+        mut factory: F,
+        text: &str,
+    ) -> Result<V, ParserError> {
+        enum Open<V: JsonValue> {
+            Array(V::Array),
+            Object(V::Object),
+        }
+
+        if self.byte_pos > 0 {
+            return Err(self.syntax_error(
+                "into_value called after events were taken from the parser".to_string(),
+            ));
+        }
+        self.push_chunk(text);
+        self.end_input();
+
+        let mut open: Vec<Open<V>> = Vec::new();
+        let mut string: Option<V::Str> = None;
+        let mut root = None;
+        while let Some(event) = self.next_event_with(&mut factory) {
+            let (path, value) = match event? {
+                ParseEvent::Null { path } => {
+                    let null = factory.new_null();
+                    (path, factory.build_from_null(null))
+                }
+                ParseEvent::Boolean { path, value } => (path, factory.build_from_bool(value)),
+                ParseEvent::Number { path, value } => (path, factory.build_from_num(value)),
+                ParseEvent::String {
+                    path,
+                    fragment,
+                    is_final,
+                    ..
+                } => {
+                    let whole = match string.take() {
+                        Some(mut prefix) => {
+                            factory.push_string(&mut prefix, &fragment);
+                            prefix
+                        }
+                        None => fragment,
+                    };
+                    if !is_final {
+                        string = Some(whole);
+                        continue;
+                    }
+                    (path, factory.build_from_str(whole))
+                }
+                ParseEvent::StringComplete { path, value } => (path, factory.build_from_str(value)),
+                ParseEvent::ArrayStart { .. } => {
+                    open.push(Open::Array(factory.new_array()));
+                    continue;
+                }
+                ParseEvent::ObjectBegin { .. } => {
+                    open.push(Open::Object(factory.new_object()));
+                    continue;
+                }
+                ParseEvent::ArrayEnd { path, .. } | ParseEvent::ObjectEnd { path, .. } => {
+                    match open.pop() {
+                        Some(Open::Array(array)) => (path, factory.build_from_array(array)),
+                        Some(Open::Object(object)) => (path, factory.build_from_object(object)),
+                        None => continue,
+                    }
+                }
+                ParseEvent::ScalarBegin { .. }
+                | ParseEvent::ScalarEnd { .. }
+                | ParseEvent::ObjectKey { .. } => continue,
+            };
+            match (open.last_mut(), path.last()) {
+                (Some(Open::Array(array)), _) => factory.push_array(array, value),
+                (Some(Open::Object(object)), Some(PathComponent::Key(key))) => {
+                    factory.insert_object(object, key, value);
+                }
+                _ => {
+                    root.get_or_insert(value);
+                }
+            }
+        }
+        root.ok_or_else(|| self.invalid_eof())
+    }
+
+    /// Returns the number of bytes of input the parser has processed so far.
+    ///
+    /// Unlike character counts, this is measured in UTF-8 bytes, so it can be
+    /// used as an offset into the concatenation of all chunks passed to
+    /// [`feed`](Self::feed). Input is processed lazily: bytes are only counted
+    /// once the events they produce have been pulled from the iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use jsonmodem::{ParserOptions, StreamingParser};
+    /// let mut parser = StreamingParser::new(ParserOptions::default());
+    /// parser.feed(r#"["héllo"]"#).for_each(drop);
+    /// assert_eq!(parser.bytes_consumed(), 10);
+    /// ```
+    #[must_use]
+    pub fn bytes_consumed(&self) -> usize {
+        self.byte_pos + self.source.widened_excess() - self.source.replaced_excess()
+    }
+
+    /// Returns `true` if all input has been parsed and its events pulled,
+    /// ending after a complete value; see
+    /// [`ClosedStreamingParser::is_exhausted`].
+    fn is_exhausted(&self) -> bool {
+        let at_boundary = match self.parse_state {
+            ParseState::End => true,
+            ParseState::Start => self.multiple_values && self.lex_state == LexState::Default,
+            _ => false,
+        };
+        at_boundary
+            && self.source.pending_bytes() == 0
+            && self.events.pending().is_empty()
+            && self
+                .object_buffer
+                .as_ref()
+                .is_none_or(|buffer| buffer.is_idle())
+    }
+
+    /// Returns the byte range of the last token lexed, from its first
+    /// character to the last byte consumed.
+    pub(crate) fn last_token_span(&self) -> Range<usize> {
+        self.token_start..self.byte_pos
+    }
+
+    /// Returns how far parsing has got, for progress reports.
+    pub(crate) fn position(&self) -> StreamPosition {
+        StreamPosition {
+            bytes_consumed: self.bytes_consumed(),
+            line: self.line,
+            column: self.column,
+            depth: self.frames.depth(),
+        }
+    }
+
+    /// Returns how the `value` of string events is filled in.
+    pub(crate) fn string_value_mode(&self) -> StringValueMode {
+        self.string_value_mode
+    }
+
+    /// Returns the kind of the first value in the input fed so far, without
+    /// parsing any of it.
+    ///
+    /// Leading whitespace is skipped and only the first character of the
+    /// value is examined, so the value may still turn out to be invalid. All
+    /// of the input remains buffered, and its events are produced as usual
+    /// when the iterator returned by [`feed`](Self::feed) is drained.
+    ///
+    /// Returns `None` if no value has started in the input yet, if its first
+    /// character cannot start a value, or if the parser has already parsed
+    /// past the start of the first value.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use jsonmodem::{ParserOptions, StreamingParser, ValueKind};
+    /// let mut parser = StreamingParser::new(ParserOptions::default());
+    /// let _ = parser.feed("  ");
+    /// assert_eq!(parser.peek_root_kind(), None);
+    /// let _ = parser.feed(r#"{"a": 1}"#);
+    /// assert_eq!(parser.peek_root_kind(), Some(ValueKind::Object));
+    /// assert_eq!(parser.finish().count(), 3);
+    /// ```
+    #[must_use]
+    pub fn peek_root_kind(&self) -> Option<ValueKind> {
+        if !matches!(self.parse_state, ParseState::Start) {
+            return None;
+        }
+        let first = self
+            .source
+            .pending()
+            .find(|c| !c.is_whitespace() && *c != '\u{FEFF}')?;
+        match first {
+            '{' => Some(ValueKind::Object),
+            '[' => Some(ValueKind::Array),
+            '"' => Some(ValueKind::Str),
+            '\'' if self.allow_single_quoted_strings => Some(ValueKind::Str),
+            't' | 'f' => Some(ValueKind::Bool),
+            'n' => Some(ValueKind::Null),
+            '-' | '0'..='9' => Some(ValueKind::Num),
+            _ => None,
+        }
+    }
+
     /// Experimental helper that returns the *currently* fully-parsed JSON value
     /// (if any).
     ///
@@ -488,6 +1020,9 @@ impl<V: JsonValue> StreamingParserImpl<V> {
         &mut self,
         f: &mut F,
     ) -> Option<Result<ParseEvent<V>, ParserError>> {
+        if self.object_buffer.is_some() {
+            return self.next_event_buffered(f);
+        }
         match self.next_event_internal(f) {
             Some(Ok(event)) => Some(Ok(event)),
             None => None,
@@ -505,6 +1040,28 @@ impl<V: JsonValue> StreamingParserImpl<V> {
         }
     }
 
+    /// Like [`next_event_with`](Self::next_event_with), but routes events
+    /// through the object buffer.
+    fn next_event_buffered<F: JsonValueFactory<Value = V>>(
+        &mut self,
+        f: &mut F,
+    ) -> Option<Result<ParseEvent<V>, ParserError>> {
+        // While the filter is taken out, `next_event_with` reads events
+        // straight from the parser.
+        let mut object_buffer = self.object_buffer.take()?;
+        let result = loop {
+            if let Some(event) = object_buffer.pop() {
+                break Some(Ok(event));
+            }
+            match self.next_event_with(f) {
+                Some(Ok(event)) => object_buffer.push(event),
+                result => break result,
+            }
+        };
+        self.object_buffer = Some(object_buffer);
+        result
+    }
+
     fn next_event_internal<F: JsonValueFactory<Value = V>>(
         &mut self,
         f: &mut F,
@@ -528,19 +1085,7 @@ impl<V: JsonValue> StreamingParserImpl<V> {
                 return Some(Ok(ev));
             }
 
-            if self.multiple_values && matches!(self.parse_state, ParseState::End) {
-                self.lex_state = LexState::Default;
-                self.parse_state = ParseState::Start;
-                self.frames.clear();
-                self.events = EventStack::new(
-                    vec![],
-                    if matches!(self.non_scalar_values, NonScalarValueMode::None) {
-                        None
-                    } else {
-                        Some(ValueBuilder::default())
-                    },
-                );
-            }
+            self.start_next_value();
 
             let token = match self.lex() {
                 Ok(tok) => tok,
@@ -576,6 +1121,68 @@ impl<V: JsonValue> StreamingParserImpl<V> {
         None
     }
 
+    /// With `allow_multiple_json_values`, prepares for the next value once the
+    /// last one has ended.
+    fn start_next_value(&mut self) {
+        if self.multiple_values && matches!(self.parse_state, ParseState::End) {
+            self.lex_state = LexState::Default;
+            self.parse_state = ParseState::Start;
+            self.nesting_changes = 0;
+            self.frames.clear();
+            self.events = EventStack::new(
+                vec![],
+                if matches!(self.non_scalar_values, NonScalarValueMode::None) {
+                    None
+                } else {
+                    Some(ValueBuilder::default())
+                },
+            );
+        }
+    }
+
+    /// Lexes the next token of the input fed so far, for
+    /// [`Tokenizer`](crate::Tokenizer), along with whether it is a partial
+    /// string fragment.
+    ///
+    /// The token still drives the parse state, which tells the lexer what may
+    /// come next, but the events it produces are dropped. Returns `None` when
+    /// more input is needed, at the end of input, and after an error.
+    pub(crate) fn next_token<F: JsonValueFactory<Value = V>>(
+        &mut self,
+        f: &mut F,
+    ) -> Option<Result<(Token, bool), ParserError>> {
+        if self.parse_state == ParseState::Error {
+            return None;
+        }
+        while self.events.pop().is_some() {}
+        self.start_next_value();
+        let result = self.lex().and_then(|token| {
+            let partial = self.partial_lex;
+            self.dispatch_parse_state(token.clone(), f)?;
+            Ok((token, partial))
+        });
+        match result {
+            Ok((Token::Eof, _)) => None,
+            Ok(token) => Some(Ok(token)),
+            Err(err) => {
+                self.parse_state = ParseState::Error;
+                self.lex_state = LexState::Error;
+                Some(Err(err))
+            }
+        }
+    }
+
+    /// Appends a chunk of input without parsing it.
+    pub(crate) fn push_chunk(&mut self, text: &str) {
+        self.source.push(text);
+    }
+
+    /// Marks the end of input without giving up the parser.
+    pub(crate) fn end_input(&mut self) {
+        self.end_of_input = true;
+        self.source.finish();
+    }
+
     // ------------------------------------------------------------------------------------------------
     // Lexer
     // ------------------------------------------------------------------------------------------------
@@ -627,15 +1234,51 @@ impl<V: JsonValue> StreamingParserImpl<V> {
                 self.column += 1;
             }
             self.pos += 1;
+            self.byte_pos += ch.len_utf8();
         }
     }
 
+    /// Parses and clears the buffered number.
+    fn lex_number(&mut self) -> Result<Number, ParserError> {
+        let buffer = self.buffer.as_str();
+        // `-0` is only representable as a float.
+        let integer = if buffer.bytes().all(|b| b.is_ascii_digit() || b == b'-') && buffer != "-0" {
+            buffer
+                .parse()
+                .map(Number::I64)
+                .or_else(|_| buffer.parse().map(Number::U64))
+                .ok()
+        } else {
+            None
+        };
+        let num = match integer {
+            Some(num) => num,
+            None => match buffer.parse::<f64>() {
+                Ok(n) => Number::F64(n),
+                Err(_) => {
+                    return Err(self.syntax_error(format!("invalid number {}", self.buffer)));
+                }
+            },
+        };
+        self.buffer.clear();
+        Ok(num)
+    }
+
     #[inline(always)]
     fn new_token(&mut self, value: Token, partial: bool) -> Token {
         self.partial_lex = partial;
         value
     }
 
+    /// Copies out the contents of `buffer` and clears it, keeping its
+    /// allocation for the next string.
+    #[inline(always)]
+    fn take_buffer(&mut self) -> String {
+        let value = String::from(&*self.buffer);
+        self.buffer.clear();
+        value
+    }
+
     #[inline(always)]
     fn produce_string(&mut self, partial: bool) -> Token {
         use Token::{Eof, PropertyName, String};
@@ -654,14 +1297,15 @@ impl<V: JsonValue> StreamingParserImpl<V> {
                 return Eof;
             }
 
-            let value = core::mem::take(&mut self.buffer);
+            let value = self.take_buffer();
             return PropertyName { value };
         }
 
         match self.string_value_mode {
             _ if partial && self.buffer.len() == self.fragment_start => Eof,
             StringValueMode::None => {
-                let fragment = core::mem::take(&mut self.buffer);
+                self.string_bytes_flushed += self.buffer.len();
+                let fragment = self.take_buffer();
                 String {
                     fragment,
                     value: None,
@@ -677,7 +1321,7 @@ impl<V: JsonValue> StreamingParserImpl<V> {
             }
             StringValueMode::Values => {
                 let fragment = self.buffer[self.fragment_start..].to_string();
-                let value = core::mem::take(&mut self.buffer);
+                let value = self.take_buffer();
                 self.fragment_start = self.buffer.len(); // reset for next fragment
                 String {
                     fragment,
@@ -691,7 +1335,7 @@ impl<V: JsonValue> StreamingParserImpl<V> {
                     self.buffer.clone()
                 } else {
                     self.fragment_start = 0; // reset for next fragment
-                    core::mem::take(&mut self.buffer)
+                    self.take_buffer()
                 };
                 String {
                     fragment,
@@ -713,6 +1357,15 @@ impl<V: JsonValue> StreamingParserImpl<V> {
             Error => Ok(None),
             Default => {
                 match next_char {
+                    Char('\u{FEFF}') if self.byte_pos == 0 => match self.bom_handling {
+                        BomHandling::Reject => {
+                            Err(self.syntax_error("unexpected byte order mark".to_string()))
+                        }
+                        BomHandling::Skip => {
+                            self.advance_char();
+                            Ok(None)
+                        }
+                    },
                     Char(
                         '\t' | '\u{0B}' | '\u{0C}' | ' ' | '\u{00A0}' | '\u{FEFF}' | '\n' | '\r'
                         | '\u{2028}' | '\u{2029}',
@@ -731,7 +1384,10 @@ impl<V: JsonValue> StreamingParserImpl<V> {
                         Ok(Some(self.new_token(Token::Eof, false)))
                     }
 
-                    Char(_) => self.lex_state_step(self.parse_state.into(), next_char),
+                    Char(_) => {
+                        self.token_start = self.byte_pos;
+                        self.lex_state_step(self.parse_state.into(), next_char)
+                    }
                 }
             }
 
@@ -770,9 +1426,11 @@ impl<V: JsonValue> StreamingParserImpl<V> {
                     self.lex_state = DecimalInteger;
                     Ok(None)
                 }
-                Char('"') => {
+                Char(quote @ ('"' | '\'')) if quote == '"' || self.allow_single_quoted_strings => {
                     self.advance_char(); // consume quote
                     self.buffer.clear();
+                    self.string_bytes_flushed = 0;
+                    self.single_quoted = quote == '\'';
                     self.lex_state = LexState::String;
                     Ok(None)
                 }
@@ -831,10 +1489,7 @@ impl<V: JsonValue> StreamingParserImpl<V> {
                     Ok(None)
                 }
                 _ => {
-                    let Ok(num) = self.buffer.parse::<f64>() else {
-                        return Err(self.syntax_error(format!("invalid number {}", self.buffer)));
-                    };
-                    self.buffer.clear();
+                    let num = self.lex_number()?;
                     Ok(Some(self.new_token(Token::Number(num), false)))
                 }
             },
@@ -863,14 +1518,12 @@ impl<V: JsonValue> StreamingParserImpl<V> {
 
                     self.column += copied;
                     self.pos += copied;
+                    self.byte_pos += copied;
 
                     Ok(None)
                 }
                 _ => {
-                    let Ok(num) = self.buffer.parse::<f64>() else {
-                        return Err(self.syntax_error(format!("invalid number {}", self.buffer)));
-                    };
-                    self.buffer.clear();
+                    let num = self.lex_number()?;
                     Ok(Some(self.new_token(Token::Number(num), false)))
                 }
             },
@@ -894,6 +1547,7 @@ impl<V: JsonValue> StreamingParserImpl<V> {
 
                     self.column += copied;
                     self.pos += copied;
+                    self.byte_pos += copied;
 
                     Ok(None)
                 }
@@ -918,14 +1572,12 @@ impl<V: JsonValue> StreamingParserImpl<V> {
 
                     self.column += copied;
                     self.pos += copied;
+                    self.byte_pos += copied;
 
                     Ok(None)
                 }
                 _ => {
-                    let Ok(num) = self.buffer.parse::<f64>() else {
-                        return Err(self.syntax_error(format!("invalid number {}", self.buffer)));
-                    };
-                    self.buffer.clear();
+                    let num = self.lex_number()?;
                     Ok(Some(self.new_token(Token::Number(num), false)))
                 }
             },
@@ -949,6 +1601,7 @@ impl<V: JsonValue> StreamingParserImpl<V> {
 
                     self.column += copied;
                     self.pos += copied;
+                    self.byte_pos += copied;
 
                     Ok(None)
                 }
@@ -968,6 +1621,7 @@ impl<V: JsonValue> StreamingParserImpl<V> {
 
                     self.column += copied;
                     self.pos += copied;
+                    self.byte_pos += copied;
 
                     Ok(None)
                 }
@@ -986,19 +1640,53 @@ impl<V: JsonValue> StreamingParserImpl<V> {
 
                     self.column += copied;
                     self.pos += copied;
+                    self.byte_pos += copied;
 
                     Ok(None)
                 }
                 _ => {
-                    let Ok(num) = self.buffer.parse::<f64>() else {
-                        return Err(self.syntax_error(format!("invalid number {}", self.buffer)));
-                    };
-                    self.buffer.clear();
+                    let num = self.lex_number()?;
                     Ok(Some(self.new_token(Token::Number(num), false)))
                 }
             },
 
             // -------------------------- STRING -----------------------------
+            // A high surrogate not followed by another `\u` escape is unpaired;
+            // the character is then lexed as usual.
+            StringHighSurrogate(high) => match next_char {
+                Char('\\') => {
+                    self.advance_char();
+                    self.lex_state = LexState::StringHighSurrogateEscape(high);
+                    Ok(None)
+                }
+                Char(_) => {
+                    self.unpaired_surrogate(high)?;
+                    self.lex_state = LexState::String;
+                    Ok(None)
+                }
+                Empty => {
+                    self.check_string_len()?;
+                    Ok(Some(self.produce_string(true)))
+                }
+                EndOfInput => Err(self.truncated_string_error(EndOfInput)),
+            },
+
+            StringHighSurrogateEscape(high) => match next_char {
+                Char('u') => {
+                    self.advance_char();
+                    self.unicode_escape_buffer.reset();
+                    self.lex_state = LexState::StringLowSurrogateUnicode(high);
+                    Ok(None)
+                }
+                Char(_) => {
+                    self.unpaired_surrogate(high)?;
+                    self.lex_state = LexState::StringEscape;
+                    Ok(None)
+                }
+                Empty => Ok(Some(self.produce_string(true))),
+                EndOfInput => Err(self.truncated_string_error(EndOfInput)),
+            },
+
             LexState::String => match next_char {
                 // escape sequence
                 Char('\\') => {
@@ -1007,7 +1695,8 @@ impl<V: JsonValue> StreamingParserImpl<V> {
                     Ok(None)
                 }
                 // closing quote -> complete string
-                Char('"') => {
+                Char(quote) if quote == self.quote() => {
+                    self.check_string_len()?;
                     self.advance_char();
                     Ok(Some(self.produce_string(false)))
                 }
@@ -1015,12 +1704,17 @@ impl<V: JsonValue> StreamingParserImpl<V> {
                     // JSON spec allows 0x20 .. 0x10FFFF unescaped.
                     Err(self.read_and_invalid_char(Char(c)))
                 }
-                Empty => Ok(Some(self.produce_string(true))),
+                Empty => {
+                    self.check_string_len()?;
+                    Ok(Some(self.produce_string(true)))
+                }
                 Char(_c) => {
                     // Fast-path: copy as many consecutive non-escaped, non-terminating
                     // characters as possible in a single pass.
+                    let len_before = self.buffer.len();
+                    let quote = self.quote();
                     let copied = self.source.copy_while(&mut self.buffer, |ch| {
-                        ch != '\\' && ch != '"' && ch >= '\u{20}'
+                        ch != '\\' && ch != quote && ch >= '\u{20}'
                     });
 
                     // Update lexer coordinates – the copied characters cannot contain
@@ -1028,15 +1722,17 @@ impl<V: JsonValue> StreamingParserImpl<V> {
                     // predicate above, so we only need to move the column/pos counters.
                     self.column += copied;
                     self.pos += copied;
+                    self.byte_pos += self.buffer.len() - len_before;
 
+                    self.check_string_len()?;
                     Ok(None)
                 }
-                EndOfInput => Err(self.read_and_invalid_char(EndOfInput)),
+                EndOfInput => Err(self.truncated_string_error(EndOfInput)),
             },
 
             StringEscape => match next_char {
                 Empty => Ok(Some(self.produce_string(true))),
-                Char(ch) if matches!(ch, '"' | '\\' | '/') => {
+                Char(ch) if matches!(ch, '"' | '\\' | '/') || ch == '\'' && self.single_quoted => {
                     self.advance_char();
                     self.buffer.push(ch);
                     self.lex_state = LexState::String;
@@ -1078,33 +1774,31 @@ impl<V: JsonValue> StreamingParserImpl<V> {
                     self.lex_state = LexState::StringEscapeUnicode;
                     Ok(None)
                 }
-                c => Err(self.read_and_invalid_char(c)),
+                EndOfInput => Err(self.truncated_string_error(EndOfInput)),
+                c @ Char(_) => Err(self.read_and_invalid_char(c)),
             },
 
-            StringEscapeUnicode => {
+            StringEscapeUnicode | StringLowSurrogateUnicode(_) => {
                 match next_char {
                     Empty => Ok(Some(self.produce_string(true))),
                     Char(c) if c.is_ascii_hexdigit() => {
                         self.advance_char();
-                        match self.unicode_escape_buffer.feed(c) {
-                            Ok(Some(char)) => {
-                                self.buffer.push(char);
-                                self.lex_state = LexState::String;
-                                Ok(None)
-                            }
-                            Ok(None) => {
-                                // Still waiting for more hex digits
-                                Ok(None)
-                            }
-                            Err(err) => Err(self
-                                .syntax_error(format!("Invalid unicode escape sequence: {err}"))),
+                        if let Err(err) = self.unicode_escape_buffer.feed(c) {
+                            return Err(self
+                                .syntax_error(format!("Invalid unicode escape sequence: {err}")));
+                        }
+                        // Until the fourth digit we are still waiting for more.
+                        if let Some(code) = self.unicode_escape_buffer.as_code_point() {
+                            // Four hex digits always fit in a code unit.
+                            self.push_code_unit(u16::try_from(code).unwrap_or(u16::MAX))?;
                         }
+                        Ok(None)
                     }
                     EndOfInput => {
                         // consume EOF sentinel and advance column to match TS behavior
                         self.advance_char();
                         self.column += 1;
-                        Err(self.invalid_eof())
+                        Err(self.truncated_string_error(EndOfInput))
                     }
                     c @ Char(_) => Err(self.read_and_invalid_char(c)),
                 }
@@ -1127,9 +1821,11 @@ impl<V: JsonValue> StreamingParserImpl<V> {
                     Ok(Some(self.new_token(Token::Punctuator(b'}'), false)))
                 }
 
-                Char('"') => {
+                Char(quote @ ('"' | '\'')) if quote == '"' || self.allow_single_quoted_strings => {
                     self.advance_char();
                     self.buffer.clear();
+                    self.string_bytes_flushed = 0;
+                    self.single_quoted = quote == '\'';
                     self.lex_state = LexState::String;
                     Ok(None)
                 }
@@ -1194,6 +1890,12 @@ impl<V: JsonValue> StreamingParserImpl<V> {
     ) -> Result<(), ParserError> {
         use ParseState::*;
 
+        if !matches!(
+            token,
+            Token::Eof | Token::Punctuator(b'[' | b']' | b'{' | b'}')
+        ) {
+            self.nesting_changes = 0;
+        }
         match self.parse_state {
             // In single-value mode, EOF at start when end_of_input indicates unexpected end.
             Start => match token {
@@ -1209,7 +1911,7 @@ impl<V: JsonValue> StreamingParserImpl<V> {
                 Token::PropertyName { value } => {
                     match self.frames.last_mut() {
                         Some(Frame::Object { pending_key }) => {
-                            *pending_key = Some(value.into());
+                            *pending_key = Some(f.new_key(&value));
                         }
                         _ => Err(self
                             .syntax_error("Expected object frame for property name".to_string()))?,
@@ -1232,11 +1934,13 @@ impl<V: JsonValue> StreamingParserImpl<V> {
             },
 
             BeforePropertyValue => match token {
+                Token::Eof if self.end_of_input => return Err(self.invalid_eof()),
                 Token::Eof => (),
                 _ => self.push(token, f)?,
             },
 
             BeforeArrayValue => match token {
+                Token::Eof if self.end_of_input => return Err(self.invalid_eof()),
                 Token::Eof => (),
                 Token::Punctuator(b']') => self.pop(f)?,
                 _ => self.push(token, f)?,
@@ -1279,14 +1983,17 @@ impl<V: JsonValue> StreamingParserImpl<V> {
 
     #[inline(always)]
     fn pop<F: JsonValueFactory<Value = V>>(&mut self, f: &mut F) -> Result<(), ParserError> {
+        self.count_nesting_change()?;
         let path = self.frames.to_path_components();
         match self.frames.pop() {
             Some(Frame::Array { .. }) => {
+                f.on_container_end(ValueKind::Array, &path);
                 self.events
                     .push(f, ParseEvent::ArrayEnd { path, value: None })
                     .map_err(|err| self.zipper_error(err))?;
             }
             Some(Frame::Object { .. }) => {
+                f.on_container_end(ValueKind::Object, &path);
                 self.events
                     .push(f, ParseEvent::ObjectEnd { path, value: None })
                     .map_err(|err| self.zipper_error(err))?;
@@ -1308,6 +2015,90 @@ impl<V: JsonValue> StreamingParserImpl<V> {
         Ok(())
     }
 
+    /// The key of the member whose value is the container at `path`, if
+    /// `attach_parent_keys` is set.
+    fn parent_key(&self, path: &[PathComponent]) -> Option<Key> {
+        match path.last() {
+            Some(PathComponent::Key(key)) if self.attach_parent_keys => Some(key.clone()),
+            _ => None,
+        }
+    }
+
+    /// The quote that closes the string being lexed.
+    fn quote(&self) -> char {
+        if self.single_quoted { '\'' } else { '"' }
+    }
+
+    /// Counts the members of the container just opened that start in the
+    /// buffered input, if `lookahead_capacity_hints` is set. Returns `None`
+    /// if no member has started yet and the container is still open.
+    fn capacity_hint(&self) -> Option<usize> {
+        if !self.lookahead_capacity_hints {
+            return None;
+        }
+        let mut depth = 0usize;
+        let mut members = 0;
+        let mut in_member = false;
+        // The quote of the string being skipped, if any.
+        let mut in_string = None;
+        let mut escaped = false;
+        for c in self.source.pending().take(CAPACITY_HINT_LOOKAHEAD) {
+            if let Some(quote) = in_string {
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    _ if c == quote => in_string = None,
+                    _ => {}
+                }
+                continue;
+            }
+            match c {
+                ']' | '}' if depth == 0 => return Some(members),
+                ']' | '}' => depth -= 1,
+                ',' if depth == 0 => in_member = false,
+                ' ' | '\t' | '\n' | '\r' => {}
+                _ => {
+                    if depth == 0 && !in_member {
+                        in_member = true;
+                        members += 1;
+                    }
+                    match c {
+                        '"' => in_string = Some(c),
+                        '\'' if self.allow_single_quoted_strings => in_string = Some(c),
+                        '[' | '{' => depth += 1,
+                        _ => {}
+                    }
+                }
+            }
+        }
+        (members > 0).then_some(members)
+    }
+
+    /// Creates the fragment and value of a string event, letting the
+    /// factory share strings read in one piece.
+    fn new_strings<F: JsonValueFactory<Value = V>>(
+        &self,
+        f: &mut F,
+        fragment: &str,
+        value: Option<&str>,
+        partial: bool,
+    ) -> (V::Str, Option<V::Str>) {
+        let whole = !partial && value.map_or(self.string_bytes_flushed, str::len) == fragment.len();
+        let value = value.map(|s| {
+            if partial {
+                f.new_string(s)
+            } else {
+                f.new_string_interned(s)
+            }
+        });
+        let fragment = if whole {
+            f.new_string_interned(fragment)
+        } else {
+            f.new_string(fragment)
+        };
+        (fragment, value)
+    }
+
     #[inline(always)]
     fn push<F: JsonValueFactory<Value = V>>(
         &mut self,
@@ -1316,12 +2107,19 @@ impl<V: JsonValue> StreamingParserImpl<V> {
     ) -> Result<(), ParserError> {
         match token {
             Token::Punctuator(b'{') => {
+                self.count_nesting_change()?;
                 self.frames.push(Frame::new_object_frame());
+                let path = self.frames.to_path_components();
+                f.on_container_begin(ValueKind::Object, &path);
+                let capacity_hint = self.capacity_hint();
+                let parent_key = self.parent_key(&path);
                 self.events
                     .push(
                         f,
                         ParseEvent::ObjectBegin {
-                            path: self.frames.to_path_components(),
+                            path,
+                            capacity_hint,
+                            parent_key,
                         },
                     )
                     .map_err(|err| self.zipper_error(err))?;
@@ -1329,12 +2127,19 @@ impl<V: JsonValue> StreamingParserImpl<V> {
                 return Ok(());
             }
             Token::Punctuator(b'[') => {
+                self.count_nesting_change()?;
                 self.frames.push(Frame::new_array_frame());
+                let path = self.frames.to_path_components();
+                f.on_container_begin(ValueKind::Array, &path);
+                let capacity_hint = self.capacity_hint();
+                let parent_key = self.parent_key(&path);
                 self.events
                     .push(
                         f,
                         ParseEvent::ArrayStart {
-                            path: self.frames.to_path_components(),
+                            path,
+                            capacity_hint,
+                            parent_key,
                         },
                     )
                     .map_err(|err| self.zipper_error(err))?;
@@ -1346,10 +2151,7 @@ impl<V: JsonValue> StreamingParserImpl<V> {
             }
         }
 
-        let mut path = self.frames.to_path_components();
-        if let Some(frame) = self.frames.last() {
-            path.push(frame.to_path_component());
-        }
+        let path = self.frames.to_child_path_components();
 
         match (token, self.partial_lex) {
             (Token::Null, _) => {
@@ -1358,20 +2160,24 @@ impl<V: JsonValue> StreamingParserImpl<V> {
                     .map_err(|err| self.zipper_error(err))?;
             }
             (Token::Boolean(b), _) => {
-                let value = f.new_bool(b);
+                let (true_val, false_val) = *self.bools.get_or_insert_with(|| f.init_bools());
+                let value = if b { true_val } else { false_val };
                 self.events
                     .push(f, ParseEvent::Boolean { path, value })
                     .map_err(|err| self.zipper_error(err))?;
             }
             (Token::Number(n), _) => {
-                let value = f.new_number(n);
+                let value = match n {
+                    Number::F64(n) => f.new_number(n),
+                    Number::I64(n) => f.new_number_i64(n),
+                    Number::U64(n) => f.new_number_u64(n),
+                };
                 self.events
                     .push(f, ParseEvent::Number { path, value })
                     .map_err(|err| self.zipper_error(err))?;
             }
             (Token::String { fragment, value }, partial) => {
-                let value = value.as_ref().map(|s| f.new_string(s));
-                let fragment = f.new_string(&fragment);
+                let (fragment, value) = self.new_strings(f, &fragment, value.as_deref(), partial);
                 self.events
                     .push(
                         f,
@@ -1423,6 +2229,92 @@ impl<V: JsonValue> StreamingParserImpl<V> {
         }
     }
 
+    /// Appends a UTF-16 code unit decoded from a `\u` escape, pairing
+    /// surrogates into a single character.
+    ///
+    /// Sets the lexer state: a high surrogate waits for its low half, anything
+    /// else returns to the string body.
+    fn push_code_unit(&mut self, unit: u16) -> Result<(), ParserError> {
+        let state = core::mem::replace(&mut self.lex_state, LexState::String);
+        if let LexState::StringLowSurrogateUnicode(high) = state {
+            if (0xDC00..=0xDFFF).contains(&unit) {
+                let code =
+                    0x10000 + ((u32::from(high) - 0xD800) << 10) + (u32::from(unit) - 0xDC00);
+                // A valid surrogate pair always decodes to a valid scalar value.
+                self.buffer
+                    .push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                return Ok(());
+            }
+            self.unpaired_surrogate(high)?;
+        }
+
+        match unit {
+            0xD800..=0xDBFF => {
+                self.lex_state = LexState::StringHighSurrogate(unit);
+                Ok(())
+            }
+            0xDC00..=0xDFFF => self.unpaired_surrogate(unit),
+            _ => {
+                self.buffer
+                    .push(char::from_u32(u32::from(unit)).unwrap_or(char::REPLACEMENT_CHARACTER));
+                Ok(())
+            }
+        }
+    }
+
+    fn unpaired_surrogate(&mut self, unit: u16) -> Result<(), ParserError> {
+        match self.decode_mode {
+            DecodeMode::StrictUnicode | DecodeMode::Cesu8 => Err(self.syntax_error(format!(
+                "Invalid unicode escape sequence: unpaired surrogate \\u{unit:04X}"
+            ))),
+            DecodeMode::ReplaceInvalid => {
+                self.buffer.push(char::REPLACEMENT_CHARACTER);
+                Ok(())
+            }
+        }
+    }
+
+    /// The error reported when input ends inside a string.
+    fn truncated_string_error(&mut self, c: PeekedChar) -> ParserError {
+        if let LexState::StringHighSurrogate(high)
+        | LexState::StringHighSurrogateEscape(high)
+        | LexState::StringLowSurrogateUnicode(high) = self.lex_state
+        {
+            if self.error_on_truncated_surrogate_pair {
+                return self.syntax_error("truncated surrogate pair at end of input".to_string());
+            }
+            if let Err(err) = self.unpaired_surrogate(high) {
+                return err;
+            }
+        }
+        self.read_and_invalid_char(c)
+    }
+
+    /// Fails if the string being lexed is longer than `max_string_bytes`.
+    #[inline(always)]
+    fn check_string_len(&self) -> Result<(), ParserError> {
+        match self.max_string_bytes {
+            Some(limit) if self.string_bytes_flushed + self.buffer.len() > limit => Err(self
+                .syntax_error(format!(
+                    "string exceeds the maximum length of {limit} bytes"
+                ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Counts an array or object opened or closed, failing once more than
+    /// `max_nesting_change_per_token` of them have happened in a row.
+    #[inline(always)]
+    fn count_nesting_change(&mut self) -> Result<(), ParserError> {
+        self.nesting_changes += 1;
+        match self.max_nesting_change_per_token {
+            Some(limit) if self.nesting_changes > limit => Err(self.syntax_error(format!(
+                "more than {limit} arrays or objects opened or closed in a row"
+            ))),
+            _ => Ok(()),
+        }
+    }
+
     fn invalid_eof(&self) -> ParserError {
         self.syntax_error("JSON5: invalid end of input".to_string())
     }
@@ -1432,6 +2324,7 @@ impl<V: JsonValue> StreamingParserImpl<V> {
             msg,
             line: self.line,
             column: self.column,
+            byte_offset: self.bytes_consumed(),
         };
         #[cfg(test)]
         assert!(!self.panic_on_error, "{err}");
@@ -1449,10 +2342,14 @@ impl<V: JsonValue> StreamingParserImpl<V> {
             | Boolean { path, .. }
             | Number { path, .. }
             | String { path, .. }
-            | ArrayStart { path }
+            | StringComplete { path, .. }
+            | ArrayStart { path, .. }
             | ArrayEnd { path, .. }
-            | ObjectBegin { path }
-            | ObjectEnd { path, .. } => path.is_empty(),
+            | ObjectBegin { path, .. }
+            | ObjectEnd { path, .. }
+            | ScalarBegin { path }
+            | ScalarEnd { path }
+            | ObjectKey { path, .. } => path.is_empty(),
         }
     }
 
@@ -1487,6 +2384,241 @@ impl<V: JsonValue> StreamingParserImpl<V> {
 }
 
 impl StreamingParserImpl<Value> {
+    /// Writes the state of the parser to a compact binary checkpoint, from
+    /// which [`deserialize_compact`](Self::deserialize_compact) restores a
+    /// parser that continues where this one is.
+    ///
+    /// The checkpoint holds the buffered input, the lexer and parser states,
+    /// the open containers and the positions, but not the options. See the
+    /// [`CheckpointError`] variants for the states that cannot be saved.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CheckpointError::Unsupported`] if a value is partially
+    /// built or object members are held back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jsonmodem::{ParserOptions, StreamingParser};
+    ///
+    /// let mut parser = StreamingParser::new(ParserOptions::default());
+    /// parser.feed("[1, 23").for_each(drop);
+    /// let checkpoint = parser.serialize_compact()?;
+    /// assert!(checkpoint.len() < 32);
+    /// # Ok::<(), jsonmodem::CheckpointError>(())
+    /// ```
+    pub fn serialize_compact(&self) -> Result<Vec<u8>, CheckpointError> {
+        // A fragment of a string cut off by the end of a chunk is held back
+        // until the next call; it is the only event that can be waiting.
+        let pending = match self.events.pending() {
+            [] => None,
+            [
+                ParseEvent::String {
+                    fragment,
+                    value,
+                    is_final: false,
+                    ..
+                },
+            ] => Some((fragment, value)),
+            _ => return Err(CheckpointError::Unsupported),
+        };
+        if self.events.builds_values() && (self.frames.last().is_some() || pending.is_some())
+            || self
+                .object_buffer
+                .as_ref()
+                .is_some_and(|buffer| !buffer.is_idle())
+        {
+            return Err(CheckpointError::Unsupported);
+        }
+
+        let mut out = vec![checkpoint::FORMAT_VERSION];
+        out.push(
+            u8::from(self.end_of_input)
+                | u8::from(self.partial_lex) << 1
+                | u8::from(self.single_quoted) << 2,
+        );
+        for n in [
+            self.pos,
+            self.byte_pos,
+            self.token_start,
+            self.line,
+            self.column,
+            self.fragment_start,
+            self.string_capacity,
+            self.string_bytes_flushed,
+            self.nesting_changes,
+        ] {
+            checkpoint::write_varint(&mut out, n);
+        }
+        out.push(self.parse_state as u8);
+        let (lex_state, high) = self.lex_state.to_parts();
+        out.push(lex_state);
+        checkpoint::write_varint(&mut out, usize::from(high));
+        let (escape, escape_len) = self.unicode_escape_buffer.to_parts();
+        checkpoint::write_varint(&mut out, escape as usize);
+        out.push(escape_len);
+        let (literal, literal_len) = self.expected_literal.to_parts();
+        out.extend([literal, literal_len]);
+        checkpoint::write_str(&mut out, &self.buffer);
+        self.source.write_compact(&mut out);
+
+        checkpoint::write_varint(&mut out, self.frames.iter().count());
+        for frame in self.frames.iter() {
+            match frame {
+                Frame::Array { next_index } => {
+                    out.push(0);
+                    checkpoint::write_varint(&mut out, *next_index);
+                }
+                Frame::Object { pending_key: None } => out.push(1),
+                Frame::Object {
+                    pending_key: Some(key),
+                } => {
+                    out.push(2);
+                    checkpoint::write_str(&mut out, key);
+                }
+            }
+        }
+        if let Some(buffer) = &self.object_buffer {
+            buffer.write_compact(&mut out);
+        }
+        out.push(match pending {
+            None => 0,
+            Some((_, None)) => 1,
+            Some((_, Some(_))) => 2,
+        });
+        if let Some((fragment, value)) = pending {
+            checkpoint::write_str(&mut out, fragment);
+            if let Some(value) = value {
+                checkpoint::write_str(&mut out, value);
+            }
+        }
+        Ok(out)
+    }
+
+    /// Restores a parser from a checkpoint written by
+    /// [`serialize_compact`](Self::serialize_compact). `options` should be
+    /// those the original parser was created with.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CheckpointError::Invalid`] if `bytes` are not a checkpoint
+    /// of this version.
+    pub fn deserialize_compact(
+        options: ParserOptions,
+        bytes: &[u8],
+    ) -> Result<Self, CheckpointError> {
+        let invalid = || CheckpointError::Invalid;
+        let mut reader = checkpoint::Reader::new(bytes);
+        if reader.byte()? != checkpoint::FORMAT_VERSION {
+            return Err(invalid());
+        }
+        let mut parser = Self::new(options);
+        let flags = reader.byte()?;
+        if flags > 0b111 {
+            return Err(invalid());
+        }
+        parser.end_of_input = flags & 1 != 0;
+        parser.partial_lex = flags & 0b10 != 0;
+        parser.single_quoted = flags & 0b100 != 0;
+        for n in [
+            &mut parser.pos,
+            &mut parser.byte_pos,
+            &mut parser.token_start,
+            &mut parser.line,
+            &mut parser.column,
+            &mut parser.fragment_start,
+            &mut parser.string_capacity,
+            &mut parser.string_bytes_flushed,
+            &mut parser.nesting_changes,
+        ] {
+            *n = reader.varint()?;
+        }
+        parser.parse_state = *PARSE_STATES
+            .get(usize::from(reader.byte()?))
+            .ok_or_else(invalid)?;
+        let lex_state = reader.byte()?;
+        let high = u16::try_from(reader.varint()?).map_err(|_| invalid())?;
+        parser.lex_state = LexState::from_parts(lex_state, high).ok_or_else(invalid)?;
+        let escape = u32::try_from(reader.varint()?).map_err(|_| invalid())?;
+        parser.unicode_escape_buffer =
+            UnicodeEscapeBuffer::from_parts(escape, reader.byte()?).ok_or_else(invalid)?;
+        let literal = reader.byte()?;
+        parser.expected_literal =
+            ExpectedLiteralBuffer::from_parts(literal, reader.byte()?).ok_or_else(invalid)?;
+        parser.buffer = reader.string()?;
+        if !parser.buffer.is_char_boundary(parser.fragment_start) {
+            return Err(invalid());
+        }
+        parser.source.read_compact(&mut reader)?;
+        if !parser.counters_match_input() {
+            return Err(invalid());
+        }
+        parser
+            .buffer
+            .try_reserve(parser.string_capacity)
+            .map_err(|_| invalid())?;
+
+        for _ in 0..reader.varint()? {
+            parser.frames.push(match reader.byte()? {
+                0 => Frame::Array {
+                    next_index: Some(reader.varint()?)
+                        .filter(|&index| index <= parser.pos)
+                        .ok_or_else(invalid)?,
+                },
+                1 => Frame::Object { pending_key: None },
+                2 => Frame::Object {
+                    pending_key: Some(reader.str()?.into()),
+                },
+                _ => return Err(invalid()),
+            });
+        }
+        if let Some(buffer) = &mut parser.object_buffer {
+            buffer.read_compact(&mut reader)?;
+        }
+        let pending = reader.byte()?;
+        if pending > 2 {
+            return Err(invalid());
+        }
+        if pending > 0 {
+            let event = ParseEvent::String {
+                path: parser.frames.to_child_path_components(),
+                fragment: reader.string()?,
+                value: if pending == 2 {
+                    Some(reader.string()?)
+                } else {
+                    None
+                },
+                is_final: false,
+            };
+            parser
+                .events
+                .push(&mut StdValueFactory, event)
+                .map_err(|_| invalid())?;
+        }
+        reader.finish()?;
+        Ok(parser)
+    }
+
+    /// Checks the counters of a restored checkpoint against the restored
+    /// buffers. The counters only ever count input already consumed, so one
+    /// that disagrees was not written by `serialize_compact`, and could
+    /// overflow or index out of bounds.
+    fn counters_match_input(&self) -> bool {
+        let pos = self.pos;
+        let counted = |n: usize| (1..=pos.saturating_add(1)).contains(&n);
+        pos == self.source.consumed()
+            && (pos..=pos.saturating_mul(4)).contains(&self.byte_pos)
+            && self.token_start <= self.byte_pos
+            && counted(self.line)
+            && counted(self.column)
+            && self.nesting_changes <= pos
+            && self
+                .string_bytes_flushed
+                .checked_add(self.buffer.len())
+                .is_some_and(|len| len <= self.byte_pos)
+    }
+
     /// Feeds a chunk of JSON text into the parser.
     ///
     /// The parser buffers the input and parses it incrementally,
@@ -1507,6 +2639,159 @@ impl StreamingParserImpl<Value> {
         self.feed_with(StdValueFactory, text)
     }
 
+    /// Feeds a chunk of UTF-8 encoded JSON into the parser.
+    ///
+    /// Unlike [`feed`](Self::feed), the chunk may begin or end in the middle
+    /// of a character, e.g. when reading a file in fixed-size blocks: the
+    /// bytes of a split character are held until the rest arrive. This
+    /// includes a byte order mark split across the first chunks, which is
+    /// then handled according to `bom_handling`. A character still
+    /// incomplete when the parser is finished is a syntax error.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the chunk is not valid UTF-8, apart from the
+    /// encoded surrogates accepted by [`DecodeMode::Cesu8`] and
+    /// [`DecodeMode::ReplaceInvalid`]. The `byte_offset` of the error is the
+    /// position of the invalid sequence in the input. None of the chunk is
+    /// consumed, and the parser can still be fed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use jsonmodem::{ParserOptions, StreamingParser};
+    /// let mut parser = StreamingParser::new(ParserOptions::default());
+    /// let text = "[\"é\"]".as_bytes();
+    /// assert_eq!(parser.feed_bytes(&text[..3]).unwrap().count(), 1);
+    /// assert_eq!(parser.feed_bytes(&text[3..]).unwrap().count(), 2);
+    /// ```
+    pub fn feed_bytes<'a>(
+        &'a mut self,
+        bytes: &[u8],
+    ) -> Result<StreamingParserIteratorWith<'a, StdValueFactory>, ParserError> {
+        // Where the held bytes, or else the chunk, start in the input.
+        let start = self.bytes_consumed() + self.source.pending_bytes();
+        if let Err(offset) = self.source.push_bytes(bytes) {
+            return Err(ParserError {
+                msg: "invalid UTF-8".to_string(),
+                line: self.line,
+                column: self.column,
+                byte_offset: start + offset,
+            });
+        }
+        Ok(StreamingParserIteratorWith {
+            parser: self,
+            factory: StdValueFactory,
+        })
+    }
+
+    /// Feeds a chunk of JSON text and collects every event it completes.
+    ///
+    /// This is equivalent to draining [`feed`](Self::feed), but gathers the
+    /// events into a [`SmallVec`] that stores up to eight events inline, which
+    /// avoids a heap allocation for the common case of a small chunk.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first syntax error encountered. Events produced before the
+    /// error are discarded, and the parser produces no further events.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use jsonmodem::{ParseEvent, ParserOptions, StreamingParser};
+    /// let mut parser = StreamingParser::new(ParserOptions::default());
+    /// let events = parser.feed_collect("[true, null]").unwrap();
+    /// assert_eq!(events.len(), 4);
+    /// assert!(!events.spilled());
+    /// ```
+    pub fn feed_collect(
+        &mut self,
+        text: &str,
+    ) -> Result<SmallVec<[ParseEvent<Value>; 8]>, ParserError> {
+        self.feed(text).collect()
+    }
+
+    /// Feeds every chunk in `chunks` in turn, yielding the events of each.
+    ///
+    /// This is equivalent to calling [`feed`](Self::feed) for each chunk and
+    /// draining its iterator, e.g. for a response whose chunks are already in
+    /// memory. Chunks are fed only as the events of the previous ones are
+    /// taken, and none are fed after a syntax error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use jsonmodem::{ParserOptions, StreamingParser};
+    /// let mut parser = StreamingParser::new(ParserOptions::default());
+    /// let events = parser.feed_chunk_iter(["[tr", "ue, n", "ull]"]);
+    /// assert_eq!(events.count(), 4);
+    /// ```
+    pub fn feed_chunk_iter(
+        &mut self,
+        chunks: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> impl Iterator<Item = Result<ParseEvent<Value>, ParserError>> {
+        let mut chunks = chunks.into_iter();
+        core::iter::from_fn(move || self.next_chunk_event(&mut chunks))
+    }
+
+    /// Feeds every chunk in `chunks`, then marks the end of input, yielding
+    /// all the events of the document.
+    ///
+    /// This is equivalent to [`feed_chunk_iter`](Self::feed_chunk_iter)
+    /// followed by [`finish`](Self::finish).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use jsonmodem::{ParseEvent, ParserOptions, StreamingParser};
+    /// let parser = StreamingParser::new(ParserOptions::default());
+    /// let events: Vec<_> = parser
+    ///     .feed_and_finish(["1", "2"])
+    ///     .collect::<Result<_, _>>()?;
+    /// assert!(matches!(
+    ///     events[..],
+    ///     [ParseEvent::Number { value: 12.0, .. }]
+    /// ));
+    /// # Ok::<(), jsonmodem::ParserError>(())
+    /// ```
+    pub fn feed_and_finish(
+        self,
+        chunks: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> impl Iterator<Item = Result<ParseEvent<Value>, ParserError>> {
+        let mut chunks = chunks.into_iter();
+        let mut parser = Some(self);
+        let mut closed = None;
+        core::iter::from_fn(move || {
+            if let Some(parser) = &mut parser {
+                if let Some(event) = parser.next_chunk_event(&mut chunks) {
+                    return Some(event);
+                }
+            }
+            closed
+                .get_or_insert_with(|| parser.take().map(Self::finish))
+                .as_mut()?
+                .next()
+        })
+    }
+
+    /// Returns the next event of the input fed so far, feeding chunks from
+    /// `chunks` as needed, or `None` once they are exhausted.
+    fn next_chunk_event(
+        &mut self,
+        chunks: &mut impl Iterator<Item = impl AsRef<str>>,
+    ) -> Option<Result<ParseEvent<Value>, ParserError>> {
+        loop {
+            if let Some(event) = self.next_event_with(&mut StdValueFactory) {
+                return Some(event);
+            }
+            if self.parse_state == ParseState::Error {
+                return None;
+            }
+            self.push_chunk(chunks.next()?.as_ref());
+        }
+    }
+
     #[must_use]
     /// Marks the end of input and returns a closed parser to consume pending
     /// events.
@@ -1517,23 +2802,30 @@ impl StreamingParserImpl<Value> {
     pub fn finish(self) -> ClosedStreamingParser<StdValueFactory> {
         self.finish_with(StdValueFactory)
     }
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub struct ParserError {
-    msg: String,
-    pub line: usize,
-    pub column: usize,
-}
 
-impl fmt::Display for ParserError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.msg.fmt(f)
+    /// Parses the rest of the document from `text` and returns its value.
+    ///
+    /// See [`into_value_with`](Self::into_value_with).
+    ///
+    /// # Errors
+    ///
+    /// Returns the first syntax error, or an error if the input ends before a
+    /// value is complete.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use jsonmodem::{ParserOptions, StreamingParser};
+    /// let parser = StreamingParser::new(ParserOptions::default());
+    /// let value = parser.into_value(r#"{"a": [1, "b"]}"#)?;
+    /// assert_eq!(value.to_string(), r#"{"a":[1,"b"]}"#);
+    /// # Ok::<(), jsonmodem::ParserError>(())
+    /// ```
+    pub fn into_value(self, text: &str) -> Result<Value, ParserError> {
+        self.into_value_with(StdValueFactory, text)
     }
 }
 
-impl core::error::Error for ParserError {}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1542,12 +2834,361 @@ mod tests {
     #[test]
     fn size_of_parser() {
         use core::mem::size_of;
-        assert_eq!(size_of::<StreamingParser>(), 280);
+        assert_eq!(size_of::<StreamingParser>(), 440);
     }
 
     #[test]
     fn size_of_closed_parser() {
         use core::mem::size_of;
-        assert_eq!(size_of::<ClosedStreamingParser<StdValueFactory>>(), 280);
+        assert_eq!(size_of::<ClosedStreamingParser<StdValueFactory>>(), 440);
+    }
+
+    #[test]
+    fn feed_collect_matches_feed() {
+        let json = r#"{"a": [1, "two", {"b": null}], "c": "long string value"}"#;
+        for parts in [1, 3, json.len()] {
+            let mut iterated = StreamingParser::new(ParserOptions::default());
+            let mut collected = StreamingParser::new(ParserOptions::default());
+            for chunk in crate::produce_chunks(json, parts) {
+                let expected: Vec<_> = iterated.feed(chunk).map(Result::unwrap).collect();
+                let actual = collected.feed_collect(chunk).unwrap();
+                assert_eq!(actual.as_slice(), expected.as_slice());
+            }
+        }
+    }
+
+    #[test]
+    fn feed_chunk_iter_matches_feed() {
+        let json = r#"{"a": [1, "two", {"b": null}], "c": "long string value"} 3"#;
+        let options = ParserOptions {
+            allow_multiple_json_values: true,
+            ..Default::default()
+        };
+        for parts in [1, 3, json.len()] {
+            let chunks = crate::produce_chunks(json, parts);
+            let mut parser = StreamingParser::new(options);
+            let mut expected = feed_all(&mut parser, &chunks);
+            let mut batched = StreamingParser::new(options);
+            let actual: Vec<_> = batched
+                .feed_chunk_iter(&chunks)
+                .map(Result::unwrap)
+                .collect();
+            assert_eq!(actual, expected);
+
+            expected.extend(parser.finish().map(Result::unwrap));
+            let actual: Vec<_> = StreamingParser::new(options)
+                .feed_and_finish(chunks.iter().map(ToString::to_string))
+                .map(Result::unwrap)
+                .collect();
+            assert_eq!(actual, expected);
+        }
+
+        let mut parser = StreamingParser::new(ParserOptions::default());
+        let results: Vec<_> = parser.feed_chunk_iter(["[1, x", "]"]).collect();
+        assert!(matches!(results[..], [Ok(_), Ok(_), Err(_)]));
+        let results: Vec<_> = StreamingParser::new(ParserOptions::default())
+            .feed_and_finish(["[1"])
+            .collect();
+        assert!(matches!(results[..], [Ok(_), Ok(_), Err(_)]));
+    }
+
+    #[test]
+    fn bytes_consumed_counts_utf8_bytes() {
+        let json = r#"{"ключ": ["héllo", "😀", 12.5e3], "k": "\u00e9"}"#;
+        assert!(json.len() > json.chars().count());
+        for parts in [1, 5, json.chars().count()] {
+            let mut parser = StreamingParser::new(ParserOptions::default());
+            let mut fed = 0;
+            for chunk in crate::produce_chunks(json, parts) {
+                parser.feed(chunk).for_each(|event| drop(event.unwrap()));
+                fed += chunk.len();
+                assert_eq!(parser.bytes_consumed(), fed);
+            }
+        }
+    }
+
+    #[test]
+    fn error_reports_byte_offset() {
+        let mut parser = StreamingParser::new(ParserOptions::default());
+        let err = parser.feed(r#"["é", x]"#).find_map(Result::err).unwrap();
+        assert_eq!(err.column, 7);
+        assert_eq!(err.byte_offset, 7);
+    }
+
+    #[test]
+    fn feed_collect_returns_error() {
+        let mut parser = StreamingParser::new(ParserOptions::default());
+        assert!(parser.feed_collect("[1, x]").is_err());
+    }
+
+    fn feed_all(parser: &mut StreamingParser, chunks: &[&str]) -> Vec<ParseEvent> {
+        let mut events = Vec::new();
+        for chunk in chunks {
+            events.extend(parser.feed(chunk).map(Result::unwrap));
+        }
+        events
+    }
+
+    fn assert_branches_match(options: ParserOptions, prefix: &str, rest: &[&str]) {
+        let mut original = StreamingParser::new(options);
+        feed_all(&mut original, &[prefix]);
+        let mut branch = original.clone();
+
+        let mut left = feed_all(&mut original, rest);
+        left.extend(original.finish().map(Result::unwrap));
+        let mut right = feed_all(&mut branch, rest);
+        right.extend(branch.finish().map(Result::unwrap));
+
+        assert!(!left.is_empty());
+        assert_eq!(left, right);
+    }
+
+    #[test]
+    fn clone_mid_string() {
+        for non_scalar_values in [NonScalarValueMode::None, NonScalarValueMode::All] {
+            let options = ParserOptions {
+                non_scalar_values,
+                string_value_mode: StringValueMode::Prefixes,
+                ..Default::default()
+            };
+            assert_branches_match(options, r#"{"a": ["hel"#, &[r"lo \u00", r#"e9", 1]}"#]);
+        }
+    }
+
+    #[test]
+    fn clone_mid_object() {
+        for non_scalar_values in [
+            NonScalarValueMode::None,
+            NonScalarValueMode::Roots,
+            NonScalarValueMode::All,
+        ] {
+            let options = ParserOptions {
+                non_scalar_values,
+                ..Default::default()
+            };
+            assert_branches_match(
+                options,
+                r#"{"users": [{"id": 1}, {"id": 2, "tags": {"x": tr"#,
+                &[r#"ue}, "name": "Grace"}], "n": 2}"#],
+            );
+        }
+    }
+
+    #[test]
+    fn clone_diverges_independently() {
+        let options = ParserOptions {
+            non_scalar_values: NonScalarValueMode::All,
+            ..Default::default()
+        };
+        let mut parser = StreamingParser::new(options);
+        feed_all(&mut parser, &[r#"{"a": {"b": "#]);
+        let mut branch = parser.clone();
+
+        feed_all(&mut parser, &["1}"]);
+        feed_all(&mut branch, &[r#""x"}, "c": ["#]);
+
+        assert_eq!(
+            parser.current_value().unwrap().to_string(),
+            r#"{"a":{"b":1}}"#
+        );
+        assert_eq!(
+            branch.current_value().unwrap().to_string(),
+            r#"{"a":{"b":"x"},"c":[]}"#
+        );
+    }
+
+    #[test]
+    fn into_value_completes_the_document() {
+        let json = r#"{"a": [1, true, null, {"b": "long string value"}], "c": "é"}"#;
+        let expected = StreamingParser::new(ParserOptions::default())
+            .into_value(json)
+            .unwrap();
+        assert_eq!(
+            expected.to_string(),
+            r#"{"a":[1,true,null,{"b":"long string value"}],"c":"é"}"#
+        );
+        for split in 0..json.len() {
+            if !json.is_char_boundary(split) {
+                continue;
+            }
+            // Events are produced lazily, so nothing is parsed until
+            // `into_value`.
+            let mut parser = StreamingParser::new(ParserOptions::default());
+            let _ = parser.feed(&json[..split]);
+            assert_eq!(parser.into_value(&json[split..]).unwrap(), expected);
+        }
+
+        let parser = StreamingParser::new(ParserOptions {
+            allow_multiple_json_values: true,
+            ..Default::default()
+        });
+        assert_eq!(
+            parser.into_value(r#""x" 2"#).unwrap(),
+            Value::String("x".into())
+        );
+    }
+
+    #[test]
+    fn into_value_errors() {
+        let parser = StreamingParser::new(ParserOptions::default());
+        assert!(parser.into_value("[1, x]").is_err());
+        let parser = StreamingParser::new(ParserOptions::default());
+        let err = parser.into_value(r#"{"a": [1, "#).unwrap_err();
+        assert_eq!(err.byte_offset, 10);
+        let parser = StreamingParser::new(ParserOptions::default());
+        assert!(parser.into_value("  ").is_err());
+        let mut parser = StreamingParser::new(ParserOptions::default());
+        assert_eq!(parser.feed("[1, ").count(), 2);
+        let err = parser.into_value("2]").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "into_value called after events were taken from the parser"
+        );
+    }
+
+    #[test]
+    fn peek_root_kind_keeps_the_input() {
+        for (json, kind) in [
+            ("  {\"a\": [1]}", ValueKind::Object),
+            ("[true]", ValueKind::Array),
+            ("42", ValueKind::Num),
+            ("\n-1.5", ValueKind::Num),
+            ("\u{FEFF}\"s\"", ValueKind::Str),
+            ("false", ValueKind::Bool),
+            ("null", ValueKind::Null),
+        ] {
+            let mut fresh = StreamingParser::new(ParserOptions::default());
+            let mut expected: Vec<_> = fresh.feed(json).collect();
+            expected.extend(fresh.finish());
+            let mut parser = StreamingParser::new(ParserOptions::default());
+            let _ = parser.feed(json);
+            assert_eq!(parser.peek_root_kind(), Some(kind), "{json}");
+            assert_eq!(parser.finish().collect::<Vec<_>>(), expected, "{json}");
+        }
+
+        let mut parser = StreamingParser::new(ParserOptions::default());
+        assert_eq!(parser.peek_root_kind(), None);
+        assert_eq!(parser.feed(" \t").count(), 0);
+        assert_eq!(parser.peek_root_kind(), None);
+        assert_eq!(parser.feed("[1").count(), 1);
+        assert_eq!(parser.peek_root_kind(), None);
+
+        let mut parser = StreamingParser::new(ParserOptions::default());
+        let _ = parser.feed("x");
+        assert_eq!(parser.peek_root_kind(), None);
+    }
+
+    #[test]
+    fn string_capacity_keeps_events() {
+        let json = r#"{"name": "a somewhat longer string", "tags": ["x", ""], "n": 1}"#;
+        for mode in [
+            StringValueMode::None,
+            StringValueMode::Values,
+            StringValueMode::Prefixes,
+        ] {
+            let options = ParserOptions {
+                string_value_mode: mode,
+                ..Default::default()
+            };
+            for parts in [1, 4, json.len()] {
+                let chunks = crate::produce_chunks(json, parts);
+                let run = |mut parser: StreamingParser| {
+                    let mut events = Vec::new();
+                    for chunk in &chunks {
+                        events.extend(parser.feed(chunk));
+                    }
+                    events.extend(parser.finish());
+                    events
+                };
+                assert_eq!(
+                    run(StreamingParser::new(options).with_string_capacity(64)),
+                    run(StreamingParser::new(options)),
+                    "{mode:?} {parts}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn capacity_hints_count_members_ahead() {
+        let hints = |chunks: &[&str], lookahead_capacity_hints| {
+            let mut parser = StreamingParser::new(ParserOptions {
+                lookahead_capacity_hints,
+                ..Default::default()
+            });
+            let mut hints = Vec::new();
+            for chunk in chunks {
+                for event in parser.feed(chunk) {
+                    match event.unwrap() {
+                        ParseEvent::ArrayStart { capacity_hint, .. }
+                        | ParseEvent::ObjectBegin { capacity_hint, .. } => {
+                            hints.push(capacity_hint);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            hints
+        };
+
+        let json = r#"[1, "a,]\"", [2, 3], {"k": [], "l,": {}}, null]"#;
+        assert_eq!(
+            hints(&[json], true),
+            [Some(5), Some(2), Some(2), Some(0), Some(0)]
+        );
+        assert_eq!(hints(&[json], false), [None; 5]);
+
+        // Members not yet fed are not counted.
+        assert_eq!(
+            hints(&["[", "1, [2, ", "{\"a\": 2", "}]]"], true),
+            [None, Some(1), Some(1)]
+        );
+        assert_eq!(hints(&["[\n  1,\n  2\n"], true), [Some(2)]);
+    }
+
+    #[test]
+    fn parent_keys_name_the_enclosing_member() {
+        let parent_keys = |attach_parent_keys| {
+            let mut parser = StreamingParser::new(ParserOptions {
+                attach_parent_keys,
+                ..Default::default()
+            });
+            let json = r#"{"user": {"tags": ["a"], "roles": [{"id": 1}]}, "é☕": []}"#;
+            let mut keys = Vec::new();
+            for event in parser.feed(json) {
+                match event.unwrap() {
+                    ParseEvent::ArrayStart {
+                        path, parent_key, ..
+                    }
+                    | ParseEvent::ObjectBegin {
+                        path, parent_key, ..
+                    } => {
+                        if attach_parent_keys {
+                            let last = match path.last() {
+                                Some(PathComponent::Key(key)) => Some(key.clone()),
+                                _ => None,
+                            };
+                            assert_eq!(parent_key, last);
+                        }
+                        keys.push(parent_key);
+                    }
+                    _ => {}
+                }
+            }
+            keys
+        };
+        let name = |key: &str| Some(Key::from(key));
+        assert_eq!(
+            parent_keys(true),
+            [
+                None,
+                name("user"),
+                name("tags"),
+                name("roles"),
+                None,
+                name("é☕")
+            ]
+        );
+        assert_eq!(parent_keys(false), vec![None; 6]);
     }
 }
@@ -0,0 +1,600 @@
+//! JSON Patch (RFC 6902).
+//!
+//! [`apply_json_patch`] applies a sequence of [`PatchOperation`]s to a
+//! document, each to the result of the one before. An operation streams the
+//! document through the parser once and rewrites it event by event: the
+//! events of a removed or replaced value are dropped, an added value is
+//! written where it belongs, and everything else is copied through. No
+//! [`Value`] is built for the document; `move`, `copy` and `test` read the
+//! value at their source with [`extract`] first.
+//!
+//! [`parse_json_patch`] reads the operations from a patch document.
+//!
+//! The output is compact JSON text. Members added to an object are written
+//! at its end.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{apply_json_patch, parse_json_patch};
+//!
+//! let doc = r#"{"name": "lamp", "tags": ["new"], "stock": {"count": 3}}"#;
+//! let patch = parse_json_patch(
+//!     r#"[
+//!         {"op": "add", "path": "/tags/0", "value": "sale"},
+//!         {"op": "move", "from": "/stock/count", "path": "/count"},
+//!         {"op": "remove", "path": "/stock"},
+//!         {"op": "test", "path": "/name", "value": "lamp"}
+//!     ]"#,
+//! )?;
+//! assert_eq!(
+//!     apply_json_patch(doc, &patch)?,
+//!     r#"{"name":"lamp","tags":["sale","new"],"count":3}"#
+//! );
+//! # Ok::<(), jsonmodem::PatchError>(())
+//! ```
+use alloc::{
+    borrow::Cow,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::{
+    ExtractError, Map, ParseEvent, ParserError, ParserOptions, PathComponent, StreamingParser,
+    Value, extract,
+    json_pointer::{parse_pointer, token_matches},
+    ndjson::CompactWriter,
+};
+
+/// One operation of a JSON Patch.
+///
+/// Paths are JSON Pointers (RFC 6901). The last token of the `path` of an
+/// `Add` into an array may be `-`, which refers to the end of the array.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchOperation {
+    /// Inserts `value` into an array before the element at `path`, or sets
+    /// the object member at `path`, replacing any value it had.
+    Add {
+        /// Where to add the value.
+        path: String,
+        /// The value to add.
+        value: Value,
+    },
+    /// Removes the value at `path`.
+    Remove {
+        /// The value to remove.
+        path: String,
+    },
+    /// Replaces the value at `path`, which must exist.
+    Replace {
+        /// The value to replace.
+        path: String,
+        /// The new value.
+        value: Value,
+    },
+    /// Removes the value at `from` and adds it at `path`.
+    Move {
+        /// The value to move.
+        from: String,
+        /// Where to add it.
+        path: String,
+    },
+    /// Adds a copy of the value at `from` at `path`.
+    Copy {
+        /// The value to copy.
+        from: String,
+        /// Where to add the copy.
+        path: String,
+    },
+    /// Checks that the value at `path` equals `value`.
+    Test {
+        /// The value to check.
+        path: String,
+        /// The value it must equal.
+        value: Value,
+    },
+}
+
+/// Error returned by [`apply_json_patch`] and [`parse_json_patch`].
+///
+/// `op` is the index of the operation that failed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchError {
+    /// The document is not valid JSON.
+    Document(ParserError),
+    /// The patch document is not valid JSON.
+    Patch(ParserError),
+    /// The patch document is not an array of operations, or an operation
+    /// has an unknown `op` or lacks a member it requires, or removes the
+    /// whole document.
+    InvalidOperation {
+        /// The index of the operation.
+        op: usize,
+    },
+    /// A `path` or `from` of an operation is not a valid JSON Pointer.
+    InvalidPointer {
+        /// The index of the operation.
+        op: usize,
+    },
+    /// The value at a `path` or `from` does not exist, or an `add` refers to
+    /// a missing parent or an index past the end of an array.
+    NotFound {
+        /// The index of the operation.
+        op: usize,
+    },
+    /// The `from` of a `move` is a proper prefix of its `path`.
+    MoveIntoChild {
+        /// The index of the operation.
+        op: usize,
+    },
+    /// The value at the `path` of a `test` differs from its `value`.
+    TestFailed {
+        /// The index of the operation.
+        op: usize,
+    },
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatchError::Document(err) => write!(f, "invalid document: {err}"),
+            PatchError::Patch(err) => write!(f, "invalid patch document: {err}"),
+            PatchError::InvalidOperation { op } => write!(f, "operation {op} is invalid"),
+            PatchError::InvalidPointer { op } => {
+                write!(f, "operation {op} has an invalid JSON pointer")
+            }
+            PatchError::NotFound { op } => write!(f, "path of operation {op} does not exist"),
+            PatchError::MoveIntoChild { op } => {
+                write!(f, "operation {op} moves a value into itself")
+            }
+            PatchError::TestFailed { op } => write!(f, "test operation {op} failed"),
+        }
+    }
+}
+
+impl core::error::Error for PatchError {}
+
+/// Applies the operations of `patch` to `doc` in order, returning the
+/// patched document.
+///
+/// # Errors
+///
+/// Returns [`PatchError::Document`] if `doc` is not valid JSON, and the error
+/// of the first operation that cannot be applied otherwise. No partial
+/// result is returned.
+pub fn apply_json_patch(doc: &str, patch: &[PatchOperation]) -> Result<String, PatchError> {
+    let mut doc = Cow::Borrowed(doc);
+    if patch.is_empty() {
+        return compact(&doc);
+    }
+    for (op, operation) in patch.iter().enumerate() {
+        doc = Cow::Owned(apply(&doc, operation, op)?);
+    }
+    Ok(doc.into_owned())
+}
+
+/// Reads the operations of a JSON Patch document.
+///
+/// # Errors
+///
+/// Returns [`PatchError::Patch`] if `patch` is not valid JSON, and
+/// [`PatchError::InvalidOperation`] if it is not an array of operations.
+pub fn parse_json_patch(patch: &str) -> Result<Vec<PatchOperation>, PatchError> {
+    let value = StreamingParser::new(ParserOptions::default())
+        .into_value(patch)
+        .map_err(PatchError::Patch)?;
+    let Value::Array(operations) = value else {
+        return Err(PatchError::InvalidOperation { op: 0 });
+    };
+    operations
+        .into_iter()
+        .enumerate()
+        .map(|(op, operation)| {
+            parse_operation(operation).ok_or(PatchError::InvalidOperation { op })
+        })
+        .collect()
+}
+
+fn parse_operation(operation: Value) -> Option<PatchOperation> {
+    let Value::Object(mut members) = operation else {
+        return None;
+    };
+    let path = take_string(&mut members, "path")?;
+    Some(match take_string(&mut members, "op")?.as_str() {
+        "add" => PatchOperation::Add {
+            path,
+            value: members.remove("value")?,
+        },
+        "remove" => PatchOperation::Remove { path },
+        "replace" => PatchOperation::Replace {
+            path,
+            value: members.remove("value")?,
+        },
+        "move" => PatchOperation::Move {
+            from: take_string(&mut members, "from")?,
+            path,
+        },
+        "copy" => PatchOperation::Copy {
+            from: take_string(&mut members, "from")?,
+            path,
+        },
+        "test" => PatchOperation::Test {
+            path,
+            value: members.remove("value")?,
+        },
+        _ => return None,
+    })
+}
+
+fn take_string(members: &mut Map, key: &str) -> Option<String> {
+    match members.remove(key)? {
+        Value::String(s) => Some(s),
+        _ => None,
+    }
+}
+
+/// Returns `doc` as compact JSON text.
+fn compact(doc: &str) -> Result<String, PatchError> {
+    match extract(doc, "") {
+        Ok(text) => Ok(text.into_owned()),
+        Err(ExtractError::Parser(err)) => Err(PatchError::Document(err)),
+        Err(_) => unreachable!("a document always has a root value"),
+    }
+}
+
+/// Applies operation number `op` to `doc`.
+fn apply(doc: &str, operation: &PatchOperation, op: usize) -> Result<String, PatchError> {
+    let pointer =
+        |pointer: &str| parse_pointer(pointer).map_err(|_| PatchError::InvalidPointer { op });
+    let lookup = |pointer: &str| {
+        extract(doc, pointer)
+            .map(Cow::into_owned)
+            .map_err(|err| match err {
+                ExtractError::Parser(err) => PatchError::Document(err),
+                ExtractError::InvalidPointer => PatchError::InvalidPointer { op },
+                ExtractError::PointerNotFound | ExtractError::AmbiguousPointer => {
+                    PatchError::NotFound { op }
+                }
+            })
+    };
+    match operation {
+        PatchOperation::Add { path, value } => {
+            edit(doc, &pointer(path)?, Some(&value.to_string()), true, op)
+        }
+        PatchOperation::Remove { path } => {
+            let target = pointer(path)?;
+            if target.is_empty() {
+                return Err(PatchError::InvalidOperation { op });
+            }
+            edit(doc, &target, None, false, op)
+        }
+        PatchOperation::Replace { path, value } => {
+            edit(doc, &pointer(path)?, Some(&value.to_string()), false, op)
+        }
+        PatchOperation::Move { from, path } => {
+            let (source, target) = (pointer(from)?, pointer(path)?);
+            if source.is_empty() {
+                return Err(PatchError::InvalidOperation { op });
+            }
+            if target.len() > source.len() && target.starts_with(&source) {
+                return Err(PatchError::MoveIntoChild { op });
+            }
+            let value = lookup(from)?;
+            let removed = edit(doc, &source, None, false, op)?;
+            edit(&removed, &target, Some(&value), true, op)
+        }
+        PatchOperation::Copy { from, path } => {
+            let target = pointer(path)?;
+            let value = lookup(from)?;
+            edit(doc, &target, Some(&value), true, op)
+        }
+        PatchOperation::Test { path, value } => {
+            pointer(path)?;
+            let actual = StreamingParser::new(ParserOptions::default())
+                .into_value(&lookup(path)?)
+                .map_err(PatchError::Document)?;
+            if actual != *value {
+                return Err(PatchError::TestFailed { op });
+            }
+            compact(doc)
+        }
+    }
+}
+
+/// Rewrites `doc` with the value at `target` replaced by the JSON text
+/// `value`, or removed if it is `None`. With `add`, the value is inserted
+/// into an array or added to an object if there is none at `target`.
+fn edit(
+    doc: &str,
+    target: &[String],
+    value: Option<&str>,
+    add: bool,
+    op: usize,
+) -> Result<String, PatchError> {
+    let mut editor = Editor {
+        target,
+        value,
+        add,
+        applied: false,
+        out: String::new(),
+        compact: CompactWriter::default(),
+        skip: None,
+        in_string: false,
+    };
+    let mut parser = StreamingParser::new(ParserOptions::default());
+    for event in parser.feed(doc) {
+        editor.event(&event.map_err(PatchError::Document)?);
+    }
+    for event in parser.finish() {
+        editor.event(&event.map_err(PatchError::Document)?);
+    }
+    if !editor.applied {
+        return Err(PatchError::NotFound { op });
+    }
+    Ok(editor.out)
+}
+
+/// Rewrites the events of a document with one edit.
+struct Editor<'a> {
+    target: &'a [String],
+    value: Option<&'a str>,
+    add: bool,
+    applied: bool,
+    out: String,
+    compact: CompactWriter,
+    /// The depth of the value being dropped, if any.
+    skip: Option<usize>,
+    in_string: bool,
+}
+
+impl Editor<'_> {
+    fn event(&mut self, event: &ParseEvent) {
+        // Writing to a `String` cannot fail.
+        let _ = self.write_event(event);
+    }
+
+    fn write_event(&mut self, event: &ParseEvent) -> fmt::Result {
+        let path = event.path();
+        let ends_value = match event {
+            ParseEvent::String { is_final, .. } => *is_final,
+            ParseEvent::ArrayStart { .. } | ParseEvent::ObjectBegin { .. } => false,
+            _ => true,
+        };
+        if let Some(depth) = self.skip {
+            if path.len() == depth && ends_value {
+                self.skip = None;
+            }
+            return Ok(());
+        }
+        let starts_value = match event {
+            ParseEvent::String { .. } => !core::mem::replace(&mut self.in_string, !ends_value),
+            ParseEvent::ArrayEnd { .. } | ParseEvent::ObjectEnd { .. } => false,
+            _ => true,
+        };
+        if !starts_value {
+            return self.write_token(event, false);
+        }
+
+        if is_at(path, self.target) {
+            let first = !core::mem::replace(&mut self.applied, true);
+            match self.value {
+                // An added array element goes before the one at its index.
+                Some(value) if first && self.add && is_index(path) => {
+                    self.compact.begin_value(path, &mut self.out)?;
+                    self.out.push_str(value);
+                }
+                Some(value) if first => {
+                    self.compact.begin_value(path, &mut self.out)?;
+                    self.out.push_str(value);
+                    self.drop_value(path, ends_value);
+                    return Ok(());
+                }
+                // A removed value, or a later duplicate of an edited member.
+                _ => {
+                    self.drop_value(path, ends_value);
+                    return Ok(());
+                }
+            }
+        }
+        self.compact.begin_value(path, &mut self.out)?;
+        self.write_token(event, true)
+    }
+
+    /// Drops the rest of the value starting at `path`.
+    fn drop_value(&mut self, path: &[PathComponent], ends_value: bool) {
+        self.in_string = false;
+        if !ends_value {
+            self.skip = Some(path.len());
+        }
+    }
+
+    /// Writes an added value at the end of the array or object at `path`, if
+    /// it is the parent of the target and the value belongs there.
+    fn append(&mut self, path: &[PathComponent], is_array: bool) -> fmt::Result {
+        let (Some(value), Some((last, parent))) = (self.value, self.target.split_last()) else {
+            return Ok(());
+        };
+        if self.applied || !self.add || !is_at(path, parent) {
+            return Ok(());
+        }
+        let Some(len) = self.compact.values() else {
+            return Ok(());
+        };
+        if is_array && *last != "-" && *last != len.to_string() {
+            return Ok(());
+        }
+        let key = if is_array { None } else { Some(&**last) };
+        self.compact.begin_member(key, &mut self.out)?;
+        self.out.push_str(value);
+        self.applied = true;
+        Ok(())
+    }
+
+    /// Writes the text of an event that is not edited. `starts_value` is
+    /// `true` for the first fragment of a string.
+    fn write_token(&mut self, event: &ParseEvent, starts_value: bool) -> fmt::Result {
+        match event {
+            ParseEvent::ArrayEnd { path, .. } => self.append(path, true)?,
+            ParseEvent::ObjectEnd { path, .. } => self.append(path, false)?,
+            _ => {}
+        }
+        self.compact.write_token(event, starts_value, &mut self.out)
+    }
+}
+
+/// Returns `true` if `path` is the location of `tokens`.
+fn is_at(path: &[PathComponent], tokens: &[String]) -> bool {
+    path.len() == tokens.len()
+        && tokens
+            .iter()
+            .zip(path)
+            .all(|(token, component)| token_matches(token, component))
+}
+
+fn is_index(path: &[PathComponent]) -> bool {
+    matches!(path.last(), Some(PathComponent::Index(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::String, vec, vec::Vec};
+
+    use super::{PatchError, PatchOperation, apply_json_patch, parse_json_patch};
+    use crate::Value;
+
+    fn patch(doc: &str, operations: &str) -> Result<String, PatchError> {
+        apply_json_patch(doc, &parse_json_patch(operations)?)
+    }
+
+    #[test]
+    fn each_operation() {
+        let doc = r#"{"a": {"b": [1, 2, 3], "c": "long string value"}, "d": null}"#;
+        for (operations, expected) in [
+            (
+                r#"[{"op": "add", "path": "/a/e", "value": {"f": [true]}}]"#,
+                r#"{"a":{"b":[1,2,3],"c":"long string value","e":{"f":[true]}},"d":null}"#,
+            ),
+            (
+                r#"[{"op": "add", "path": "/a/c", "value": 4}]"#,
+                r#"{"a":{"b":[1,2,3],"c":4},"d":null}"#,
+            ),
+            (
+                r#"[{"op": "add", "path": "/a/b/1", "value": "x"},
+                    {"op": "add", "path": "/a/b/-", "value": "y"},
+                    {"op": "add", "path": "/a/b/5", "value": "z"}]"#,
+                r#"{"a":{"b":[1,"x",2,3,"y","z"],"c":"long string value"},"d":null}"#,
+            ),
+            (r#"[{"op": "add", "path": "", "value": [1]}]"#, "[1]"),
+            (
+                r#"[{"op": "remove", "path": "/a/b/0"}, {"op": "remove", "path": "/a/c"}]"#,
+                r#"{"a":{"b":[2,3]},"d":null}"#,
+            ),
+            (
+                r#"[{"op": "replace", "path": "/a/b", "value": "é\"☕"},
+                    {"op": "replace", "path": "/d", "value": false}]"#,
+                r#"{"a":{"b":"é\"☕","c":"long string value"},"d":false}"#,
+            ),
+            (r#"[{"op": "replace", "path": "", "value": null}]"#, "null"),
+            (
+                r#"[{"op": "move", "from": "/a/b/0", "path": "/a/b/2"},
+                    {"op": "move", "from": "/a/c", "path": "/c"}]"#,
+                r#"{"a":{"b":[2,3,1]},"d":null,"c":"long string value"}"#,
+            ),
+            (
+                r#"[{"op": "copy", "from": "/a/b", "path": "/a/b/0"}]"#,
+                r#"{"a":{"b":[[1,2,3],1,2,3],"c":"long string value"},"d":null}"#,
+            ),
+            (
+                r#"[{"op": "test", "path": "/a", "value": {"c": "long string value", "b": [1, 2, 3]}},
+                    {"op": "test", "path": "/d", "value": null}]"#,
+                r#"{"a":{"b":[1,2,3],"c":"long string value"},"d":null}"#,
+            ),
+            (
+                "[]",
+                r#"{"a":{"b":[1,2,3],"c":"long string value"},"d":null}"#,
+            ),
+        ] {
+            assert_eq!(patch(doc, operations).unwrap(), expected, "{operations}");
+        }
+    }
+
+    #[test]
+    fn operations_can_be_built_directly() {
+        let operations = vec![
+            PatchOperation::Add {
+                path: "/~1".into(),
+                value: Value::String("slash".into()),
+            },
+            PatchOperation::Remove { path: "/a".into() },
+        ];
+        assert_eq!(
+            apply_json_patch(r#"{"a": 1, "a": 2}"#, &operations).unwrap(),
+            r#"{"/":"slash"}"#
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let doc = r#"{"a": [1], "b": {"c": 2}}"#;
+        for (operations, expected) in [
+            (
+                r#"[{"op": "remove", "path": "/a/0"}, {"op": "remove", "path": "/a/0"}]"#,
+                PatchError::NotFound { op: 1 },
+            ),
+            (
+                r#"[{"op": "add", "path": "/a/2", "value": 0}]"#,
+                PatchError::NotFound { op: 0 },
+            ),
+            (
+                r#"[{"op": "add", "path": "/x/y", "value": 0}]"#,
+                PatchError::NotFound { op: 0 },
+            ),
+            (
+                r#"[{"op": "replace", "path": "/b/d", "value": 0}]"#,
+                PatchError::NotFound { op: 0 },
+            ),
+            (
+                r#"[{"op": "copy", "from": "/z", "path": "/y"}]"#,
+                PatchError::NotFound { op: 0 },
+            ),
+            (
+                r#"[{"op": "move", "from": "/b", "path": "/b/c/d"}]"#,
+                PatchError::MoveIntoChild { op: 0 },
+            ),
+            (
+                r#"[{"op": "test", "path": "/b/c", "value": "2"}]"#,
+                PatchError::TestFailed { op: 0 },
+            ),
+            (
+                r#"[{"op": "remove", "path": "a"}]"#,
+                PatchError::InvalidPointer { op: 0 },
+            ),
+            (
+                r#"[{"op": "remove", "path": ""}]"#,
+                PatchError::InvalidOperation { op: 0 },
+            ),
+        ] {
+            assert_eq!(patch(doc, operations), Err(expected), "{operations}");
+        }
+
+        for operations in [
+            r#"{"op": "remove", "path": "/a"}"#,
+            r#"[{"op": "remove", "path": "/a"}, {"op": "add", "path": "/a"}]"#,
+            r#"[{"op": "undo", "path": "/a"}]"#,
+        ] {
+            assert!(
+                matches!(
+                    parse_json_patch(operations),
+                    Err(PatchError::InvalidOperation { .. })
+                ),
+                "{operations}"
+            );
+        }
+        assert!(matches!(parse_json_patch("[,]"), Err(PatchError::Patch(_))));
+        assert!(matches!(
+            apply_json_patch("{]", &Vec::new()),
+            Err(PatchError::Document(_))
+        ));
+    }
+}
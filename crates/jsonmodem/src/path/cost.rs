@@ -0,0 +1,216 @@
+//! Fuzzy matching of paths.
+//!
+//! Language models often write keys with inconsistent casing, pluralization
+//! or small typos, e.g. `userName` for `username` or `item` for `items`.
+//! [`path_similarity`] scores how close two paths are, between `0.0` and
+//! `1.0`, and [`closest_match`] picks the most similar of a set of expected
+//! paths.
+//!
+//! Paths are compared component by component. Two keys score by their edit
+//! distance, in which changing only the case of a letter costs half an edit;
+//! two indices score `1.0` if equal and `0.0` otherwise, as do a key and an
+//! index. The score of a path is the mean score of its components, counting
+//! `0.0` for each component of the longer path that the shorter one lacks.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{closest_match, path, path_similarity};
+//!
+//! assert_eq!(path_similarity(&path!["items", 0], &path!["items", 0]), 1.0);
+//! assert!(path_similarity(&path!["userName"], &path!["username"]) > 0.9);
+//!
+//! let expected = [path!["user", "name"], path!["user", "email"]];
+//! let (closest, _) = closest_match(&path!["usr", "emial"], &expected).unwrap();
+//! assert_eq!(closest, &path!["user", "email"][..]);
+//! ```
+use alloc::{vec, vec::Vec};
+
+use crate::PathComponent;
+
+/// The cost of inserting, deleting or substituting a character, in half
+/// edits.
+const EDIT: usize = 2;
+/// The cost of substituting a letter for the same letter in another case.
+const CASE_EDIT: usize = 1;
+
+/// Returns the similarity of two paths, from `0.0` for paths with nothing in
+/// common to `1.0` for equal paths.
+#[must_use]
+pub fn path_similarity(a: &[PathComponent], b: &[PathComponent]) -> f64 {
+    similarity_at_least(a, b, 0.0).unwrap_or(0.0)
+}
+
+/// Returns the candidate most similar to `target` and its similarity, or
+/// `None` if there are no candidates.
+///
+/// Of several equally similar candidates, the first is returned. Candidates
+/// that cannot beat the best so far are abandoned early, so a close first
+/// match makes the search faster.
+#[must_use]
+pub fn closest_match<'a>(
+    target: &[PathComponent],
+    candidates: &'a [Vec<PathComponent>],
+) -> Option<(&'a [PathComponent], f64)> {
+    let mut best: Option<(&'a [PathComponent], f64)> = None;
+    for candidate in candidates {
+        let threshold = best.map_or(0.0, |(_, score)| score);
+        if let Some(score) = similarity_at_least(target, candidate, threshold)
+            .filter(|&score| best.is_none_or(|(_, best)| score > best))
+        {
+            best = Some((candidate, score));
+        }
+    }
+    best
+}
+
+/// Returns the similarity of two paths, or `None` once it is known to be
+/// below `threshold`.
+#[expect(clippy::cast_precision_loss)]
+fn similarity_at_least(a: &[PathComponent], b: &[PathComponent], threshold: f64) -> Option<f64> {
+    let len = a.len().max(b.len());
+    if len == 0 {
+        return Some(1.0);
+    }
+    // The total loss of similarity, summed over components, still allowed.
+    let mut budget = (1.0 - threshold) * len as f64;
+    let mut loss = 0.0;
+    for i in 0..len {
+        let component_loss = match (a.get(i), b.get(i)) {
+            (Some(PathComponent::Key(a)), Some(PathComponent::Key(b))) => key_loss(a, b, budget)?,
+            (Some(PathComponent::Index(a)), Some(PathComponent::Index(b))) if a == b => 0.0,
+            _ => 1.0,
+        };
+        budget -= component_loss;
+        if budget < 0.0 {
+            return None;
+        }
+        loss += component_loss;
+    }
+    Some(1.0 - loss / len as f64)
+}
+
+/// Returns one minus the similarity of two keys, or `None` once it is known
+/// to be above `budget`.
+#[expect(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn key_loss(a: &str, b: &str, budget: f64) -> Option<f64> {
+    if a == b {
+        return Some(0.0);
+    }
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let scale = EDIT * a.len().max(b.len());
+    // The budget is not negative, so the cast rounds down.
+    let max_distance = (budget * scale as f64).min(scale as f64) as usize;
+    let distance = edit_distance(&a, &b, max_distance)?;
+    Some(distance as f64 / scale as f64)
+}
+
+/// Computes the edit distance of `a` and `b` in half edits with the
+/// Wagner-Fischer algorithm, one row at a time. Returns `None` as soon as
+/// every entry of a row exceeds `max`, since the distance cannot then be at
+/// most `max`.
+fn edit_distance(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    let mut previous: Vec<usize> = (0..=b.len()).map(|j| j * EDIT).collect();
+    let mut row = vec![0; b.len() + 1];
+    for (i, &ca) in a.iter().enumerate() {
+        row[0] = (i + 1) * EDIT;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = if ca == cb {
+                0
+            } else if ca.to_lowercase().eq(cb.to_lowercase()) {
+                CASE_EDIT
+            } else {
+                EDIT
+            };
+            row[j + 1] = (previous[j] + substitution)
+                .min(previous[j + 1] + EDIT)
+                .min(row[j] + EDIT);
+        }
+        if row.iter().all(|&distance| distance > max) {
+            return None;
+        }
+        core::mem::swap(&mut previous, &mut row);
+    }
+    Some(previous[b.len()]).filter(|&distance| distance <= max)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use super::{closest_match, edit_distance, path_similarity};
+    use crate::{PathComponent, path};
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn edit_distance_counts_half_edits() {
+        let distance = |a, b| edit_distance(&chars(a), &chars(b), usize::MAX).unwrap();
+        assert_eq!(distance("kitten", "sitting"), 6);
+        assert_eq!(distance("", "abc"), 6);
+        assert_eq!(distance("userName", "username"), 1);
+        assert_eq!(distance("ID", "id"), 2);
+        assert_eq!(distance("é", "É"), 1);
+        // Stops once the distance must exceed the maximum.
+        assert_eq!(edit_distance(&chars("abcdef"), &chars("uvwxyz"), 4), None);
+        assert_eq!(edit_distance(&chars("abc"), &chars("abd"), 2), Some(2));
+    }
+
+    #[test]
+    #[expect(clippy::float_cmp, reason = "these scores are exact")]
+    fn typos_and_casing_are_close() {
+        let score = |a: &[PathComponent], b: &[PathComponent]| path_similarity(a, b);
+        assert_eq!(score(&path!["a", 0], &path!["a", 0]), 1.0);
+        assert_eq!(score(&[], &[]), 1.0);
+        assert_eq!(score(&path!["a"], &path![0]), 0.0);
+
+        let casing = score(&path!["userName"], &path!["username"]);
+        let plural = score(&path!["item"], &path!["items"]);
+        let transposed = score(&path!["adress"], &path!["address"]);
+        let unrelated = score(&path!["color"], &path!["weight"]);
+        assert!((casing - 15.0 / 16.0).abs() < 1e-9, "{casing}");
+        assert!((plural - 0.8).abs() < 1e-9, "{plural}");
+        assert!(transposed > 0.8, "{transposed}");
+        assert!(unrelated < 0.3, "{unrelated}");
+
+        // Indices match only exactly, and a missing component scores zero.
+        assert_eq!(score(&path!["items", 0], &path!["items", 1]), 0.5);
+        assert_eq!(score(&path!["items"], &path!["items", 0]), 0.5);
+        assert!(
+            score(&path!["Items", 0, "Name"], &path!["items", 0, "name"])
+                > score(&path!["Items", 1, "Name"], &path!["items", 0, "name"])
+        );
+    }
+
+    #[test]
+    fn closest_match_prefers_first_of_equals() {
+        let candidates = vec![
+            path!["user", "first_name"],
+            path!["user", "last_name"],
+            path!["user", "emails", 0],
+            path!["user", "email"],
+        ];
+        let closest = |target: &[PathComponent]| closest_match(target, &candidates).unwrap();
+
+        assert_eq!(closest(&path!["user", "email"]), (&candidates[3][..], 1.0));
+        assert_eq!(closest(&path!["User", "lastName"]).0, &candidates[1][..]);
+        assert_eq!(closest(&path!["user", "frist_name"]).0, &candidates[0][..]);
+        assert_eq!(closest(&path!["usr", "emails", 0]).0, &candidates[2][..]);
+
+        let equals = [path!["a"], path!["c"]];
+        assert_eq!(
+            closest_match(&path!["b"], &equals),
+            Some((&equals[0][..], 0.0))
+        );
+        assert_eq!(closest_match(&path!["a"], &[]), None);
+        let far = [path![0, 1, 2]];
+        assert_eq!(closest_match(&path!["a"], &far), Some((&far[0][..], 0.0)));
+    }
+}
@@ -0,0 +1,362 @@
+//! Glob patterns over event paths.
+//!
+//! A [`Glob`] is a path in which some segments are wildcards. It is written
+//! with dots between keys and brackets around indices:
+//!
+//! | pattern            | matches                                          |
+//! | ------------------ | ------------------------------------------------ |
+//! | `name`             | the key `name`                                   |
+//! | `[0]`              | the index `0`                                    |
+//! | `["a.b"]`          | the key `a.b` (for keys containing `.` or `[`)   |
+//! | `*` or `[*]`       | any single key or index                          |
+//! | `**`               | zero or more keys or indices                     |
+//!
+//! For example `choices.**.delta.content` matches `choices[0].delta.content`
+//! and `choices[0].message.delta.content`, and `items[*][0]` matches the first
+//! element of every array in `items`. The empty pattern matches the root.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{Glob, ParseEvent, ParserOptions, StreamingParser, filter_by_glob};
+//!
+//! let mut parser = StreamingParser::new(ParserOptions::default());
+//! parser.feed(r#"{"choices": [{"delta": {"content": "Hi"}}, {"delta": {"content": "!"}}]}"#);
+//!
+//! let glob = Glob::compile("choices.**.delta.content").unwrap();
+//! let content: String = filter_by_glob(parser.finish().map(Result::unwrap), glob)
+//!     .filter_map(|event| match event {
+//!         ParseEvent::String { fragment, .. } => Some(fragment),
+//!         _ => None,
+//!     })
+//!     .collect();
+//! assert_eq!(content, "Hi!");
+//! ```
+use alloc::{format, string::String, vec, vec::Vec};
+use core::fmt;
+
+use crate::{JsonValue, ParseEvent, PathComponent};
+
+/// One segment of a [`Glob`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum GlobSegment {
+    /// Matches exactly this key or index.
+    Exact(PathComponent),
+    /// Matches any single key or index.
+    AnyOne,
+    /// Matches zero or more keys or indices.
+    RecursiveAny,
+}
+
+/// A compiled path pattern.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Glob {
+    pub pattern: Vec<GlobSegment>,
+}
+
+/// An error in the syntax of a glob pattern.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlobError {
+    msg: String,
+    /// Byte offset in the pattern at which the error was detected.
+    pub position: usize,
+}
+
+impl fmt::Display for GlobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.msg, self.position)
+    }
+}
+
+impl core::error::Error for GlobError {}
+
+impl Glob {
+    /// Parses a pattern such as `items.**.name` or `items[*][0]`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for empty segments (e.g. `a..b`), unterminated or
+    /// malformed brackets, and indices that do not fit in a `usize`.
+    pub fn compile(pattern: &str) -> Result<Self, GlobError> {
+        Compiler {
+            src: pattern,
+            pos: 0,
+            segments: Vec::new(),
+        }
+        .run()
+    }
+
+    /// Returns `true` if `path` matches the whole pattern.
+    #[must_use]
+    pub fn matches_path(&self, path: &[PathComponent]) -> bool {
+        // `matched[j]` records whether the segments seen so far match the
+        // first `j` components of `path`. Each `**` may absorb any number of
+        // components, so every (segment, prefix) pair is decided once.
+        let mut matched = vec![false; path.len() + 1];
+        matched[0] = true;
+        for segment in &self.pattern {
+            match segment {
+                GlobSegment::RecursiveAny => {
+                    for j in 1..=path.len() {
+                        matched[j] |= matched[j - 1];
+                    }
+                }
+                GlobSegment::AnyOne | GlobSegment::Exact(_) => {
+                    for j in (1..=path.len()).rev() {
+                        matched[j] = matched[j - 1]
+                            && match segment {
+                                GlobSegment::Exact(component) => *component == path[j - 1],
+                                _ => true,
+                            };
+                    }
+                    matched[0] = false;
+                }
+            }
+        }
+        matched[path.len()]
+    }
+}
+
+struct Compiler<'a> {
+    src: &'a str,
+    pos: usize,
+    segments: Vec<GlobSegment>,
+}
+
+impl Compiler<'_> {
+    fn error(&self, msg: &str) -> GlobError {
+        GlobError {
+            msg: msg.into(),
+            position: self.pos,
+        }
+    }
+
+    fn rest(&self) -> &str {
+        &self.src[self.pos..]
+    }
+
+    fn run(mut self) -> Result<Glob, GlobError> {
+        if self.src.is_empty() {
+            return Ok(Glob::default());
+        }
+        // A leading name needs no dot.
+        if !self.rest().starts_with('[') {
+            self.name()?;
+        }
+        while let Some(c) = self.rest().chars().next() {
+            match c {
+                '.' => {
+                    self.pos += 1;
+                    self.name()?;
+                }
+                '[' => self.bracket()?,
+                _ => return Err(self.error("expected '.' or '['")),
+            }
+        }
+        Ok(Glob {
+            pattern: self.segments,
+        })
+    }
+
+    /// Parses a dotted segment: a key, `*` or `**`.
+    fn name(&mut self) -> Result<(), GlobError> {
+        let len = self.rest().find(['.', '[']).unwrap_or(self.rest().len());
+        let name = &self.rest()[..len];
+        let segment = match name {
+            "" => return Err(self.error("empty path segment")),
+            "*" => GlobSegment::AnyOne,
+            "**" => GlobSegment::RecursiveAny,
+            key => GlobSegment::Exact(PathComponent::Key(key.into())),
+        };
+        self.segments.push(segment);
+        self.pos += len;
+        Ok(())
+    }
+
+    /// Parses `[*]`, `[N]` or `["key"]`.
+    fn bracket(&mut self) -> Result<(), GlobError> {
+        self.pos += 1;
+        let segment = if let Some(quoted) = self.rest().strip_prefix('"') {
+            let mut key = String::new();
+            let mut chars = quoted.char_indices();
+            let end = loop {
+                match chars.next() {
+                    Some((i, '"')) => break i,
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, c)) => key.push(c),
+                        None => return Err(self.error("unterminated key")),
+                    },
+                    Some((_, c)) => key.push(c),
+                    None => return Err(self.error("unterminated key")),
+                }
+            };
+            self.pos += end + 2;
+            GlobSegment::Exact(PathComponent::Key(key.into()))
+        } else {
+            let len = self
+                .rest()
+                .find(']')
+                .ok_or_else(|| self.error("unterminated '['"))?;
+            let inner = &self.rest()[..len];
+            let segment = if inner == "*" {
+                GlobSegment::AnyOne
+            } else if !inner.is_empty() && inner.bytes().all(|b| b.is_ascii_digit()) {
+                let index = inner
+                    .parse()
+                    .map_err(|_| self.error(&format!("index {inner} is too large")))?;
+                GlobSegment::Exact(PathComponent::Index(index))
+            } else {
+                return Err(self.error("expected '*', an index or a quoted key"));
+            };
+            self.pos += len;
+            segment
+        };
+        if !self.rest().starts_with(']') {
+            return Err(self.error("expected ']'"));
+        }
+        self.pos += 1;
+        self.segments.push(segment);
+        Ok(())
+    }
+}
+
+/// Keeps only the events whose path matches `glob`.
+///
+/// Containers are reported by their begin and end events; to receive the
+/// members of a matching container, end the pattern with `**`.
+pub fn filter_by_glob<V, I>(events: I, glob: Glob) -> GlobFilter<I::IntoIter>
+where
+    V: JsonValue,
+    I: IntoIterator<Item = ParseEvent<V>>,
+{
+    GlobFilter {
+        events: events.into_iter(),
+        glob,
+    }
+}
+
+/// Iterator returned by [`filter_by_glob`].
+#[derive(Debug, Clone)]
+pub struct GlobFilter<I> {
+    events: I,
+    glob: Glob,
+}
+
+impl<V, I> Iterator for GlobFilter<I>
+where
+    V: JsonValue,
+    I: Iterator<Item = ParseEvent<V>>,
+{
+    type Item = ParseEvent<V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.events
+            .by_ref()
+            .find(|event| self.glob.matches_path(event.path()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec, vec::Vec};
+
+    use super::{Glob, GlobSegment, filter_by_glob};
+    use crate::{ParseEvent, ParserOptions, PathComponent, path, tests::events};
+
+    fn matches(pattern: &str, path: &[PathComponent]) -> bool {
+        Glob::compile(pattern).unwrap().matches_path(path)
+    }
+
+    #[test]
+    fn compiles_segments() {
+        assert_eq!(
+            Glob::compile(r#"items[*][0].**.name["a.b"].*"#)
+                .unwrap()
+                .pattern,
+            vec![
+                GlobSegment::Exact("items".into()),
+                GlobSegment::AnyOne,
+                GlobSegment::Exact(0_usize.into()),
+                GlobSegment::RecursiveAny,
+                GlobSegment::Exact("name".into()),
+                GlobSegment::Exact("a.b".into()),
+                GlobSegment::AnyOne,
+            ]
+        );
+        assert_eq!(Glob::compile("").unwrap().pattern, vec![]);
+    }
+
+    #[test]
+    fn rejects_malformed_patterns() {
+        for (pattern, position) in [
+            ("a..b", 2),
+            ("a.", 2),
+            ("a[", 2),
+            ("a[x]", 2),
+            ("a[0", 2),
+            ("a[\"x", 2),
+            ("a[0]b", 4),
+            ("a[99999999999999999999999]", 2),
+        ] {
+            let err = Glob::compile(pattern).unwrap_err();
+            assert_eq!(err.position, position, "{pattern}: {err}");
+        }
+        assert_eq!(
+            Glob::compile("a..b").unwrap_err().to_string(),
+            "empty path segment at position 2"
+        );
+    }
+
+    #[test]
+    fn exact_and_single_wildcards() {
+        assert!(matches("", &[]));
+        assert!(!matches("", &path!["a"]));
+        assert!(matches("a.b", &path!["a", "b"]));
+        assert!(!matches("a.b", &path!["a"]));
+        assert!(!matches("a.b", &path!["a", "b", "c"]));
+        assert!(matches("items[*][0]", &path!["items", 3, 0]));
+        assert!(!matches("items[*][0]", &path!["items", 3, 1]));
+        assert!(matches("*.x", &path![7, "x"]));
+    }
+
+    #[test]
+    fn recursive_wildcards() {
+        assert!(matches("**", &[]));
+        assert!(matches("**", &path!["a", 0, "b"]));
+        assert!(matches("a.**", &path!["a"]));
+        assert!(matches("items.**.name", &path!["items", "name"]));
+        assert!(matches(
+            "items.**.name",
+            &path!["items", 0, "tags", 2, "name"]
+        ));
+        assert!(!matches("items.**.name", &path!["items", 0, "name", "x"]));
+        assert!(matches("**.name.**.name", &path!["name", "name"]));
+        assert!(matches("**.a.**.b.**", &path![1, "a", 2, "a", "b", 3]));
+        assert!(!matches("**.a.**.b", &path!["b", "a"]));
+    }
+
+    #[test]
+    fn filters_streaming_events() {
+        let json = r#"{"choices": [
+            {"index": 0, "delta": {"content": "Hel", "role": "assistant"}},
+            {"index": 1, "message": {"delta": {"content": "lo"}}}
+        ]}"#;
+        let glob = Glob::compile("choices.**.delta.content").unwrap();
+        let matched: Vec<_> = filter_by_glob(events(json, 1, ParserOptions::default()), glob)
+            .map(|event| match event {
+                ParseEvent::String { path, fragment, .. } => (path, fragment),
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            matched,
+            vec![
+                (path!["choices", 0, "delta", "content"], "Hel".into()),
+                (
+                    path!["choices", 1, "message", "delta", "content"],
+                    "lo".into()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,10 @@
+//! Matching and comparison of event paths.
+mod cost;
+mod glob;
+mod prefix;
+mod trie;
+
+pub use cost::{closest_match, path_similarity};
+pub use glob::{Glob, GlobError, GlobFilter, GlobSegment, filter_by_glob};
+pub use prefix::{common_prefix, is_prefix_of};
+pub use trie::{PathTrie, TrieFilter, filter_by_trie};
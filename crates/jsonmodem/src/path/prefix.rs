@@ -0,0 +1,95 @@
+//! Common prefixes of paths.
+//!
+//! Routing events by path, or describing where two documents differ, needs
+//! the innermost container that two values share: the longest common prefix
+//! of their paths. [`common_prefix`] finds it, and [`is_prefix_of`] tests
+//! whether one path is an ancestor of, or equal to, another.
+//!
+//! Paths need no separate ordering: slices of [`PathComponent`] compare
+//! lexicographically, so a path sorts directly after its prefixes.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{common_prefix, is_prefix_of, path};
+//!
+//! let a = path!["users", 0, "name"];
+//! let b = path!["users", 0, "email"];
+//! assert_eq!(common_prefix(&a, &b), &path!["users", 0][..]);
+//! assert!(is_prefix_of(&path!["users"], &a));
+//! assert!(path!["users"] < a);
+//! ```
+use crate::PathComponent;
+
+/// Returns the longest path that both `a` and `b` start with, borrowed from
+/// `a`. Paths with no component in common share the empty path, the root.
+#[must_use]
+pub fn common_prefix<'a>(a: &'a [PathComponent], b: &[PathComponent]) -> &'a [PathComponent] {
+    let len = a.iter().zip(b).take_while(|(a, b)| a == b).count();
+    &a[..len]
+}
+
+/// Returns whether `path` starts with `prefix`, i.e. whether `prefix` is the
+/// path of `path`'s value or of a container enclosing it.
+#[must_use]
+pub fn is_prefix_of(prefix: &[PathComponent], path: &[PathComponent]) -> bool {
+    path.starts_with(prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::{common_prefix, is_prefix_of};
+    use crate::{PathComponent, path};
+
+    #[test]
+    fn prefixes_at_every_depth() {
+        let a = path!["a", 0, "b", 1];
+        for len in 0..=a.len() {
+            let mut b = a[..len].to_vec();
+            assert_eq!(common_prefix(&a, &b), &a[..len]);
+            assert_eq!(common_prefix(&b, &a), &a[..len]);
+            assert!(is_prefix_of(&b, &a));
+            assert_eq!(is_prefix_of(&a, &b), len == a.len());
+
+            b.push(PathComponent::from("other"));
+            assert_eq!(common_prefix(&a, &b), &a[..len]);
+            assert!(!is_prefix_of(&b, &a));
+        }
+        // A key and an index never match, even when they look alike.
+        assert_eq!(common_prefix(&path!["0"], &path![0]), &[]);
+    }
+
+    #[test]
+    fn empty_paths() {
+        let empty: Vec<PathComponent> = Vec::new();
+        assert_eq!(common_prefix(&empty, &empty), &[]);
+        assert_eq!(common_prefix(&path!["a"], &empty), &[]);
+        assert!(is_prefix_of(&empty, &empty));
+        assert!(is_prefix_of(&empty, &path!["a", 0]));
+        assert!(!is_prefix_of(&path!["a"], &empty));
+    }
+
+    #[test]
+    fn paths_sort_after_their_prefixes() {
+        let mut paths = [
+            path!["b"],
+            path!["a", 1],
+            Vec::new(),
+            path!["a"],
+            path!["a", 0, "z"],
+        ];
+        paths.sort();
+        assert_eq!(
+            paths,
+            [
+                Vec::new(),
+                path!["a"],
+                path!["a", 0, "z"],
+                path!["a", 1],
+                path!["b"]
+            ]
+        );
+    }
+}
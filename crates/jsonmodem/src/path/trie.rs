@@ -0,0 +1,279 @@
+//! Dispatch of event paths to many glob patterns at once.
+//!
+//! Testing a path against each of many [`Glob`]s costs time proportional to
+//! the number of patterns. A [`PathTrie`] merges the patterns into one tree
+//! keyed by their segments, so patterns sharing a prefix are walked once and
+//! exact segments are found by lookup, and a path is matched against all of
+//! them in one pass over its components.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{Glob, PathTrie, path};
+//!
+//! let mut trie = PathTrie::new();
+//! trie.insert(
+//!     &Glob::compile("choices[*].delta.content").unwrap(),
+//!     "content",
+//! );
+//! trie.insert(&Glob::compile("choices.**.role").unwrap(), "role");
+//! trie.insert(&Glob::compile("**").unwrap(), "any");
+//!
+//! let path = path!["choices", 0, "delta", "content"];
+//! let mut found: Vec<_> = trie.matches(&path).collect();
+//! found.sort();
+//! assert_eq!(found, [&"any", &"content"]);
+//! assert_eq!(trie.matches(&path!["usage"]).collect::<Vec<_>>(), [&"any"]);
+//! ```
+use alloc::{boxed::Box, collections::BTreeMap, vec::Vec};
+use core::ptr;
+
+use crate::{Glob, GlobSegment, JsonValue, ParseEvent, PathComponent};
+
+/// A set of glob patterns, each with a value, matched against paths
+/// together.
+///
+/// A path matching several patterns yields the value of each, and a pattern
+/// inserted several times yields each of its values.
+#[derive(Debug, Clone)]
+pub struct PathTrie<V> {
+    root: Node<V>,
+}
+
+#[derive(Debug, Clone)]
+struct Node<V> {
+    exact: BTreeMap<PathComponent, Node<V>>,
+    /// The node after a `*` segment.
+    any_one: Option<Box<Node<V>>>,
+    /// The node after a `**` segment, which also matches every path
+    /// component until the next segment.
+    recursive: Option<Box<Node<V>>>,
+    /// The values of the patterns ending at this node.
+    values: Vec<V>,
+}
+
+impl<V> Default for Node<V> {
+    fn default() -> Self {
+        Self {
+            exact: BTreeMap::new(),
+            any_one: None,
+            recursive: None,
+            values: Vec::new(),
+        }
+    }
+}
+
+impl<V> Default for PathTrie<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> PathTrie<V> {
+    /// Creates an empty trie.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            root: Node::default(),
+        }
+    }
+
+    /// Adds `pattern`, yielding `value` for the paths it matches.
+    pub fn insert(&mut self, pattern: &Glob, value: V) {
+        let mut node = &mut self.root;
+        for segment in &pattern.pattern {
+            node = match segment {
+                GlobSegment::Exact(component) => node.exact.entry(component.clone()).or_default(),
+                GlobSegment::AnyOne => node.any_one.get_or_insert_default(),
+                GlobSegment::RecursiveAny => node.recursive.get_or_insert_default(),
+            };
+        }
+        node.values.push(value);
+    }
+
+    /// Returns the values of every pattern matching `path`, in no particular
+    /// order.
+    ///
+    /// The time taken is proportional to the length of `path` times the
+    /// number of `**` segments that can be active at once, independent of
+    /// the number of patterns.
+    pub fn matches(&self, path: &[PathComponent]) -> impl Iterator<Item = &V> {
+        // Each state is a node reached by the components so far, and whether
+        // it was reached through `**`, so that it absorbs further components.
+        let mut states = Vec::new();
+        add_state(&mut states, &self.root, false);
+        let mut next = Vec::new();
+        for component in path {
+            for &(node, absorbs) in &states {
+                if absorbs {
+                    add_state(&mut next, node, true);
+                }
+                if let Some(child) = node.exact.get(component) {
+                    add_state(&mut next, child, false);
+                }
+                if let Some(child) = &node.any_one {
+                    add_state(&mut next, child, false);
+                }
+            }
+            core::mem::swap(&mut states, &mut next);
+            next.clear();
+            if states.is_empty() {
+                break;
+            }
+        }
+        // A node may be reached both through `**` and not.
+        let mut nodes: Vec<&Node<V>> = Vec::new();
+        for (node, _) in states {
+            if !nodes.iter().any(|&seen| ptr::eq(seen, node)) {
+                nodes.push(node);
+            }
+        }
+        nodes.into_iter().flat_map(|node| &node.values)
+    }
+
+    /// Returns `true` if any pattern matches `path`.
+    #[must_use]
+    pub fn is_match(&self, path: &[PathComponent]) -> bool {
+        self.matches(path).next().is_some()
+    }
+}
+
+/// Adds `node` to `states` unless it is already there, along with the node
+/// after its `**`, which matches zero components.
+fn add_state<'a, V>(states: &mut Vec<(&'a Node<V>, bool)>, node: &'a Node<V>, absorbs: bool) {
+    if states
+        .iter()
+        .any(|&(seen, seen_absorbs)| ptr::eq(seen, node) && seen_absorbs == absorbs)
+    {
+        return;
+    }
+    states.push((node, absorbs));
+    if let Some(recursive) = &node.recursive {
+        add_state(states, recursive, true);
+    }
+}
+
+/// Keeps only the events whose path matches a pattern in `trie`.
+///
+/// Like [`filter_by_glob`](crate::filter_by_glob), but for many patterns at
+/// once. Use [`PathTrie::matches`] on the path of each event to find which.
+pub fn filter_by_trie<V, I, T>(events: I, trie: &PathTrie<T>) -> TrieFilter<'_, I::IntoIter, T>
+where
+    V: JsonValue,
+    I: IntoIterator<Item = ParseEvent<V>>,
+{
+    TrieFilter {
+        events: events.into_iter(),
+        trie,
+    }
+}
+
+/// Iterator returned by [`filter_by_trie`].
+#[derive(Debug, Clone)]
+pub struct TrieFilter<'a, I, T> {
+    events: I,
+    trie: &'a PathTrie<T>,
+}
+
+impl<V, I, T> Iterator for TrieFilter<'_, I, T>
+where
+    V: JsonValue,
+    I: Iterator<Item = ParseEvent<V>>,
+{
+    type Item = ParseEvent<V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.events
+            .by_ref()
+            .find(|event| self.trie.is_match(event.path()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use quickcheck_macros::quickcheck;
+
+    use super::{PathTrie, filter_by_trie};
+    use crate::{Glob, ParseEvent, ParserOptions, PathComponent, StreamingParser, path};
+
+    const PATTERNS: [&str; 12] = [
+        "",
+        "**",
+        "a",
+        "a.b",
+        "a.*",
+        "a.**",
+        "*.b",
+        "**.b",
+        "a.**.b",
+        "**.a.**.b",
+        "[0].**",
+        "a[1].b",
+    ];
+
+    fn trie() -> PathTrie<usize> {
+        let mut trie = PathTrie::new();
+        for (i, pattern) in PATTERNS.iter().enumerate() {
+            trie.insert(&Glob::compile(pattern).unwrap(), i);
+        }
+        trie
+    }
+
+    #[test]
+    fn matches_every_pattern_once() {
+        let trie = trie();
+        let matches = |path: &[PathComponent]| {
+            let mut found: Vec<_> = trie.matches(path).copied().collect();
+            found.sort_unstable();
+            found
+        };
+        assert_eq!(matches(&[]), [0, 1]);
+        assert_eq!(matches(&path!["a"]), [1, 2, 5]);
+        assert_eq!(matches(&path!["a", "b"]), [1, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(matches(&path!["a", 1, "b"]), [1, 5, 7, 8, 9, 11]);
+        assert_eq!(matches(&path![0, "x", "b"]), [1, 7, 10]);
+        assert!(!PathTrie::<()>::new().is_match(&[]));
+    }
+
+    #[quickcheck]
+    fn agrees_with_glob(path: Vec<bool>) -> bool {
+        // Paths over a small alphabet, so that patterns match often.
+        let path: Vec<PathComponent> = path
+            .into_iter()
+            .enumerate()
+            .map(|(i, key)| match (key, i % 2) {
+                (true, 0) => "a".into(),
+                (true, _) => "b".into(),
+                (false, _) => (i % 2).into(),
+            })
+            .collect();
+        let trie = trie();
+        let mut found: Vec<_> = trie.matches(&path).copied().collect();
+        found.sort_unstable();
+        let expected: Vec<_> = (0..PATTERNS.len())
+            .filter(|&i| Glob::compile(PATTERNS[i]).unwrap().matches_path(&path))
+            .collect();
+        found == expected
+    }
+
+    #[test]
+    fn filters_events() {
+        let mut trie = PathTrie::new();
+        trie.insert(&Glob::compile("a[*]").unwrap(), ());
+        trie.insert(&Glob::compile("c").unwrap(), ());
+        let mut parser = StreamingParser::new(ParserOptions::default());
+        let events: Vec<_> = parser
+            .feed(r#"{"a": [1, 2], "b": 3, "c": 4}"#)
+            .map(Result::unwrap)
+            .collect();
+        let paths: Vec<_> = filter_by_trie(events, &trie)
+            .map(|event| match event {
+                ParseEvent::Number { path, .. } => path,
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect();
+        assert_eq!(paths, [path!["a", 0], path!["a", 1], path!["c"]]);
+    }
+}
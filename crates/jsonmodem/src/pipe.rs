@@ -0,0 +1,194 @@
+//! Uniform sinks for event pipelines.
+//!
+//! [`ParseEventWriter`] is implemented by the consumers in this crate that
+//! take events one at a time, so that a parser can be connected to any of them
+//! with [`pipe_to`] instead of a hand-written loop.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{
+//!     EventRecorder, JsonPrettyPrinter, ParserOptions, PrettyOptions, StreamingParser, pipe_to,
+//! };
+//!
+//! let mut parser = StreamingParser::new(ParserOptions::default());
+//! let mut printer = JsonPrettyPrinter::new(String::new(), PrettyOptions::default());
+//! pipe_to(parser.feed(r#"{"a": [1, "#), &mut printer)?;
+//! pipe_to(parser.feed(r#"2]}"#), &mut printer)?;
+//! assert_eq!(printer.into_inner(), "{\n  \"a\": [\n    1,\n    2\n  ]\n}");
+//!
+//! let mut parser = StreamingParser::new(ParserOptions::default());
+//! let mut recorder = EventRecorder::new();
+//! pipe_to(parser.feed("[true, null]"), &mut recorder)?;
+//! assert_eq!(recorder.len(), 4);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+use alloc::vec::Vec;
+use core::{convert::Infallible, fmt};
+
+use crate::{
+    CompressedRecorder, EventRecorder, JsonPrettyPrinter, JsonValue, ParseEvent, ParserError,
+};
+
+/// A consumer of parse events.
+pub trait ParseEventWriter<V: JsonValue> {
+    /// The error returned when an event cannot be written.
+    type Error;
+
+    /// Consumes one event.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the event cannot be written; the meaning is
+    /// specific to the writer.
+    fn write_event(&mut self, event: ParseEvent<V>) -> Result<(), Self::Error>;
+}
+
+/// Error returned by [`pipe_to`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum PipeError<E> {
+    /// The event source reported a syntax error.
+    Parser(ParserError),
+    /// The writer failed.
+    Writer(E),
+}
+
+impl<E: fmt::Display> fmt::Display for PipeError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PipeError::Parser(err) => err.fmt(f),
+            PipeError::Writer(err) => write!(f, "writer error: {err}"),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> core::error::Error for PipeError<E> {}
+
+/// Writes every event from `events` to `writer`, stopping at the first error.
+///
+/// `events` is typically the iterator returned by `feed` or `finish`.
+///
+/// # Errors
+///
+/// Returns [`PipeError::Parser`] if `events` yields an error and
+/// [`PipeError::Writer`] if the writer fails.
+pub fn pipe_to<V, I, W>(events: I, writer: &mut W) -> Result<(), PipeError<W::Error>>
+where
+    V: JsonValue,
+    I: IntoIterator<Item = Result<ParseEvent<V>, ParserError>>,
+    W: ParseEventWriter<V> + ?Sized,
+{
+    for event in events {
+        writer
+            .write_event(event.map_err(PipeError::Parser)?)
+            .map_err(PipeError::Writer)?;
+    }
+    Ok(())
+}
+
+impl<V: JsonValue, W: ParseEventWriter<V> + ?Sized> ParseEventWriter<V> for &mut W {
+    type Error = W::Error;
+
+    fn write_event(&mut self, event: ParseEvent<V>) -> Result<(), Self::Error> {
+        (**self).write_event(event)
+    }
+}
+
+impl<V: JsonValue> ParseEventWriter<V> for Vec<ParseEvent<V>> {
+    type Error = Infallible;
+
+    fn write_event(&mut self, event: ParseEvent<V>) -> Result<(), Self::Error> {
+        self.push(event);
+        Ok(())
+    }
+}
+
+impl<V: JsonValue> ParseEventWriter<V> for EventRecorder<V> {
+    type Error = Infallible;
+
+    fn write_event(&mut self, event: ParseEvent<V>) -> Result<(), Self::Error> {
+        self.record(event);
+        Ok(())
+    }
+}
+
+impl<V: JsonValue> ParseEventWriter<V> for CompressedRecorder<V> {
+    type Error = Infallible;
+
+    fn write_event(&mut self, event: ParseEvent<V>) -> Result<(), Self::Error> {
+        self.record(event);
+        Ok(())
+    }
+}
+
+impl<V, W> ParseEventWriter<V> for JsonPrettyPrinter<W>
+where
+    V: JsonValue,
+    V::Str: AsRef<str>,
+    V::Num: Into<f64>,
+    V::Bool: Into<bool>,
+    W: fmt::Write,
+{
+    type Error = fmt::Error;
+
+    fn write_event(&mut self, event: ParseEvent<V>) -> Result<(), Self::Error> {
+        JsonPrettyPrinter::write_event(self, &event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec::Vec};
+
+    use super::{ParseEventWriter, PipeError, pipe_to};
+    use crate::{CompressedRecorder, ParseEvent, ParserOptions, StreamingParser, produce_chunks};
+
+    /// Accepts a fixed number of events.
+    struct Limited(usize);
+
+    impl ParseEventWriter<crate::Value> for Limited {
+        type Error = &'static str;
+
+        fn write_event(&mut self, _: ParseEvent) -> Result<(), Self::Error> {
+            self.0 = self.0.checked_sub(1).ok_or("full")?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn chunks_pipe_into_any_writer() {
+        let json = r#"{"a": ["x", 1, null], "b": {}}"#;
+        let mut expected = Vec::new();
+        let mut parser = StreamingParser::new(ParserOptions::default());
+        pipe_to(parser.feed(json), &mut expected).unwrap();
+        pipe_to(parser.finish(), &mut expected).unwrap();
+
+        let mut parser = StreamingParser::new(ParserOptions::default());
+        let mut recorder = CompressedRecorder::new();
+        for chunk in produce_chunks(json, 5) {
+            pipe_to(parser.feed(chunk), &mut recorder).unwrap();
+        }
+        pipe_to(parser.finish(), &mut recorder).unwrap();
+
+        let mut rebuilt = Vec::new();
+        for event in recorder.replay() {
+            rebuilt.write_event(event).unwrap();
+        }
+        assert_eq!(rebuilt.len(), 9);
+        assert_eq!(rebuilt, expected);
+    }
+
+    #[test]
+    fn errors_stop_the_pipe() {
+        let mut parser = StreamingParser::new(ParserOptions::default());
+        let mut events = Vec::new();
+        let err = pipe_to(parser.feed("[1, 2, x]"), &mut events).unwrap_err();
+        assert!(matches!(err, PipeError::Parser(_)));
+        assert_eq!(events.len(), 3);
+
+        let mut parser = StreamingParser::new(ParserOptions::default());
+        let err = pipe_to(parser.feed("[1, 2, 3]"), &mut Limited(2)).unwrap_err();
+        assert_eq!(err, PipeError::Writer("full"));
+        assert_eq!(err.to_string(), "writer error: full");
+    }
+}
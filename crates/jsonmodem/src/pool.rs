@@ -0,0 +1,321 @@
+//! Sharing of object keys across parse sessions.
+//!
+//! A long-running process that parses many documents with the same shape,
+//! e.g. the responses of one API, allocates the same property names for every
+//! document. [`StringPool`] is a [`JsonValueFactory`] that builds the usual
+//! [`Value`]s but keeps a table of the keys it has created and hands out a
+//! shared `Arc<str>` for every key seen before, in event paths and in built
+//! objects alike. Pass it to the `_with` methods of each parser in place of
+//! [`StdValueFactory`].
+//!
+//! With the `std` feature, [`SharedStringPool`] does the same behind a
+//! mutex, so that parsers on several threads can share one table.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::sync::Arc;
+//!
+//! use jsonmodem::{ParseEvent, ParserOptions, PathComponent, StreamingParser, StringPool};
+//!
+//! let mut pool = StringPool::new();
+//! let mut paths = Vec::new();
+//! for document in [r#"{"id": 1}"#, r#"{"id": 2}"#] {
+//!     let mut parser = StreamingParser::new(ParserOptions::default());
+//!     for event in parser.feed_with(&mut pool, document) {
+//!         if let ParseEvent::Number { path, .. } = event.unwrap() {
+//!             paths.push(path);
+//!         }
+//!     }
+//! }
+//! assert_eq!(pool.len(), 1);
+//! let [PathComponent::Key(first), PathComponent::Key(second)] = [&paths[0][0], &paths[1][0]]
+//! else {
+//!     unreachable!()
+//! };
+//! assert!(Arc::ptr_eq(first, second));
+//! ```
+use crate::{JsonValue, JsonValueFactory, PathInterner, StdValueFactory, Value, event::Key};
+
+/// A table of object keys, used as a factory that shares them.
+#[derive(Debug, Clone, Default)]
+pub struct StringPool {
+    keys: PathInterner,
+}
+
+impl StringPool {
+    /// Creates an empty pool.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the shared copy of `key`, adding it to the pool if it has not
+    /// been seen before.
+    pub fn intern(&mut self, key: &str) -> Key {
+        self.keys.intern_key(key)
+    }
+
+    /// The number of distinct keys in the pool.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns `true` if the pool holds no keys.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Removes every key from the pool. Keys already handed out stay valid.
+    pub fn clear(&mut self) {
+        self.keys.clear();
+    }
+}
+
+/// Implements the required methods of [`JsonValueFactory`] by delegating to
+/// [`StdValueFactory`], leaving the provided ones, such as `new_key`, free to
+/// override.
+macro_rules! delegate_to_std_factory {
+    () => {
+        type Value = Value;
+
+        fn new_null(&mut self) {}
+
+        fn new_bool(&mut self, b: bool) -> bool {
+            b
+        }
+
+        fn new_number(&mut self, n: f64) -> f64 {
+            n
+        }
+
+        fn new_string(&mut self, s: &str) -> <Value as JsonValue>::Str {
+            StdValueFactory.new_string(s)
+        }
+
+        fn new_array(&mut self) -> <Value as JsonValue>::Array {
+            StdValueFactory.new_array()
+        }
+
+        fn new_object(&mut self) -> <Value as JsonValue>::Object {
+            StdValueFactory.new_object()
+        }
+
+        fn push_string(
+            &mut self,
+            string: &mut <Value as JsonValue>::Str,
+            val: &<Value as JsonValue>::Str,
+        ) {
+            StdValueFactory.push_string(string, val);
+        }
+
+        fn push_str(&mut self, string: &mut <Value as JsonValue>::Str, val: &str) {
+            StdValueFactory.push_str(string, val);
+        }
+
+        fn push_array(&mut self, array: &mut <Value as JsonValue>::Array, val: Value) {
+            StdValueFactory.push_array(array, val);
+        }
+
+        fn insert_object(&mut self, obj: &mut <Value as JsonValue>::Object, key: &str, val: Value) {
+            let key = self.new_key(key);
+            StdValueFactory.object_insert(obj, key, val);
+        }
+
+        fn build_from_str(&mut self, s: <Value as JsonValue>::Str) -> Value {
+            Value::String(s)
+        }
+
+        fn build_from_num(&mut self, n: f64) -> Value {
+            Value::Number(n)
+        }
+
+        fn build_from_bool(&mut self, b: bool) -> Value {
+            Value::Boolean(b)
+        }
+
+        fn build_from_null(&mut self, (): ()) -> Value {
+            Value::Null
+        }
+
+        fn build_from_array(&mut self, a: <Value as JsonValue>::Array) -> Value {
+            Value::Array(a)
+        }
+
+        fn build_from_object(&mut self, o: <Value as JsonValue>::Object) -> Value {
+            Value::Object(o)
+        }
+
+        fn object_insert<'a, 'b: 'a>(
+            &'a mut self,
+            obj: &'b mut <Value as JsonValue>::Object,
+            key: Key,
+            val: Value,
+        ) -> &'b mut Value {
+            StdValueFactory.object_insert(obj, key, val)
+        }
+
+        fn array_push<'a, 'b: 'a>(
+            &'a mut self,
+            arr: &'b mut <Value as JsonValue>::Array,
+            val: Value,
+        ) -> &'b mut Value {
+            StdValueFactory.array_push(arr, val)
+        }
+    };
+}
+#[cfg(any(test, feature = "serde"))]
+pub(crate) use delegate_to_std_factory;
+
+impl JsonValueFactory for StringPool {
+    delegate_to_std_factory!();
+
+    fn new_key(&mut self, key: &str) -> Key {
+        self.intern(key)
+    }
+}
+
+/// A [`StringPool`] that can be shared by parsers on several threads.
+///
+/// Clones share the same table. Each new key takes the lock once.
+#[cfg(any(test, feature = "std"))]
+#[derive(Debug, Clone, Default)]
+pub struct SharedStringPool {
+    pool: alloc::sync::Arc<std::sync::Mutex<StringPool>>,
+}
+
+#[cfg(any(test, feature = "std"))]
+impl SharedStringPool {
+    /// Creates an empty pool.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the shared copy of `key`, adding it to the pool if it has not
+    /// been seen before.
+    #[must_use]
+    pub fn intern(&self, key: &str) -> Key {
+        self.lock().intern(key)
+    }
+
+    /// The number of distinct keys in the pool.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns `true` if the pool holds no keys.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, StringPool> {
+        // The table is valid even if a thread panicked while holding it.
+        self.pool
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+#[cfg(any(test, feature = "std"))]
+impl JsonValueFactory for SharedStringPool {
+    delegate_to_std_factory!();
+
+    fn new_key(&mut self, key: &str) -> Key {
+        self.intern(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{sync::Arc, vec::Vec};
+
+    use super::StringPool;
+    use crate::{ParseEvent, ParserOptions, PathComponent, StreamingParser, Value};
+
+    const DOCUMENT: &str = r#"{"user": {"name": "a", "tags": ["x"]}, "name": "b"}"#;
+
+    fn parse(pool: &mut StringPool) -> (Vec<ParseEvent>, Value) {
+        let mut parser = StreamingParser::new(ParserOptions::default());
+        let mut events: Vec<_> = parser
+            .feed_with(&mut *pool, DOCUMENT)
+            .map(Result::unwrap)
+            .collect();
+        events.extend(parser.finish_with(&mut *pool).map(Result::unwrap));
+        let value = StreamingParser::new(ParserOptions::default())
+            .into_value_with(&mut *pool, DOCUMENT)
+            .unwrap();
+        (events, value)
+    }
+
+    fn key(component: &PathComponent) -> &Arc<str> {
+        match component {
+            PathComponent::Key(key) => key,
+            PathComponent::Index(_) => panic!("not a key"),
+        }
+    }
+
+    #[test]
+    fn keys_are_shared_across_parsers() {
+        let mut pool = StringPool::new();
+        let (first, first_value) = parse(&mut pool);
+        let (second, second_value) = parse(&mut pool);
+        assert_eq!(pool.len(), 3);
+        assert_eq!(first, second);
+        assert_eq!(first_value, second_value);
+
+        // Both occurrences of `name`, in both documents, are one allocation.
+        let names: Vec<&Arc<str>> = first
+            .iter()
+            .chain(&second)
+            .filter_map(|event| match event.path().last() {
+                Some(PathComponent::Key(key)) => Some(key),
+                _ => None,
+            })
+            .filter(|key| &***key == "name")
+            .collect();
+        assert_eq!(names.len(), 4);
+        assert!(names.iter().all(|name| Arc::ptr_eq(name, names[0])));
+
+        // Built objects share them too.
+        let Value::Object(root) = &first_value else {
+            unreachable!()
+        };
+        let (root_name, _) = root.get_key_value("name").unwrap();
+        assert!(Arc::ptr_eq(root_name, names[0]));
+
+        pool.clear();
+        assert!(pool.is_empty());
+        assert_eq!(pool.intern("name"), *names[0]);
+        assert!(!Arc::ptr_eq(&pool.intern("name"), names[0]));
+    }
+
+    #[test]
+    fn shared_pool_is_shared_across_threads() {
+        use super::SharedStringPool;
+
+        let pool = SharedStringPool::new();
+        let keys: Vec<Arc<str>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| {
+                    let mut pool = pool.clone();
+                    scope.spawn(move || {
+                        let mut parser = StreamingParser::new(ParserOptions::default());
+                        parser
+                            .feed_with(&mut pool, DOCUMENT)
+                            .map(Result::unwrap)
+                            .find_map(|event| event.path().first().map(|c| key(c).clone()))
+                            .unwrap()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        assert_eq!(pool.len(), 1);
+        assert!(keys.iter().all(|key| Arc::ptr_eq(key, &keys[0])));
+    }
+}
@@ -0,0 +1,392 @@
+//! Rendering of `ParseEvent` streams as indented JSON text.
+//!
+//! [`JsonPrettyPrinter`] is the inverse of the parser: it consumes events one
+//! at a time and writes the document they describe. Only the structure of the
+//! events is used; the `value` carried by container end events is ignored, so
+//! the printer works with any `non_scalar_values` mode. String fragments are
+//! buffered until the final fragment arrives.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{JsonPrettyPrinter, ParserOptions, PrettyOptions, StreamingParser};
+//!
+//! let mut parser = StreamingParser::new(ParserOptions::default());
+//! let mut printer = JsonPrettyPrinter::new(String::new(), PrettyOptions::default());
+//! for event in parser.feed(r#"{"name": "jsonmodem", "tags": ["json", "stream"], "meta": {}}"#) {
+//!     printer.write_event(&event.unwrap())?;
+//! }
+//! for event in parser.finish() {
+//!     printer.write_event(&event.unwrap())?;
+//! }
+//! assert_eq!(
+//!     printer.into_inner(),
+//!     r#"{
+//!   "name": "jsonmodem",
+//!   "tags": [
+//!     "json",
+//!     "stream"
+//!   ],
+//!   "meta": {}
+//! }"#
+//! );
+//! # Ok::<(), core::fmt::Error>(())
+//! ```
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::fmt::{self, Write};
+
+use crate::{JsonValue, ParseEvent, PathComponent, value::write_escaped_string};
+
+/// Configuration for [`JsonPrettyPrinter`].
+#[derive(Debug, Clone)]
+pub struct PrettyOptions {
+    /// The string written once per nesting level, e.g. two spaces or `"\t"`.
+    ///
+    /// # Default
+    ///
+    /// Two spaces.
+    pub indent: Cow<'static, str>,
+    /// Arrays of at most this many scalar items are written on a single line,
+    /// e.g. `[1, 2, 3]`. Arrays containing arrays or objects are always
+    /// broken across lines.
+    ///
+    /// # Default
+    ///
+    /// `0`, meaning every non-empty array is broken across lines.
+    pub max_inline_array_items: usize,
+    /// Write every non-ASCII character as a `\uXXXX` escape (using surrogate
+    /// pairs outside the Basic Multilingual Plane), so that the output is
+    /// plain ASCII regardless of how strings were encoded in the input.
+    ///
+    /// # Default
+    ///
+    /// `false`
+    pub escape_non_ascii: bool,
+}
+
+impl Default for PrettyOptions {
+    fn default() -> Self {
+        Self {
+            indent: Cow::Borrowed("  "),
+            max_inline_array_items: 0,
+            escape_non_ascii: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Frame {
+    is_array: bool,
+    len: usize,
+    /// Rendered items of an array that may still fit on one line.
+    inline: Option<Vec<String>>,
+}
+
+/// Writes indented JSON text for a stream of [`ParseEvent`]s.
+///
+/// Events must form well-nested values, as produced by the parser. Multiple
+/// root values are written on separate lines.
+#[derive(Debug)]
+pub struct JsonPrettyPrinter<W: fmt::Write> {
+    writer: W,
+    options: PrettyOptions,
+    frames: Vec<Frame>,
+    roots: usize,
+    string: String,
+}
+
+impl<W: fmt::Write> JsonPrettyPrinter<W> {
+    /// Creates a printer that writes its output to `writer`.
+    pub fn new(writer: W, options: PrettyOptions) -> Self {
+        Self {
+            writer,
+            options,
+            frames: Vec::new(),
+            roots: 0,
+            string: String::new(),
+        }
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Writes the text for one event.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the underlying writer fails.
+    pub fn write_event<V>(&mut self, event: &ParseEvent<V>) -> fmt::Result
+    where
+        V: JsonValue,
+        V::Str: AsRef<str>,
+        V::Num: Into<f64>,
+        V::Bool: Into<bool>,
+    {
+        match event {
+            ParseEvent::Null { path } => self.scalar(path, String::from("null")),
+            ParseEvent::Boolean { path, value } => {
+                let text = if (*value).into() { "true" } else { "false" };
+                self.scalar(path, String::from(text))
+            }
+            ParseEvent::Number { path, value } => {
+                let n: f64 = (*value).into();
+                self.scalar(path, alloc::format!("{n}"))
+            }
+            ParseEvent::String {
+                path,
+                fragment,
+                is_final,
+                ..
+            } => {
+                self.string.push_str(fragment.as_ref());
+                if !*is_final {
+                    return Ok(());
+                }
+                let mut text = String::with_capacity(self.string.len() + 2);
+                write_string(&mut text, &self.string, self.options.escape_non_ascii)?;
+                self.string.clear();
+                self.scalar(path, text)
+            }
+            ParseEvent::StringComplete { path, value } => {
+                let mut text = String::with_capacity(value.as_ref().len() + 2);
+                write_string(&mut text, value.as_ref(), self.options.escape_non_ascii)?;
+                self.scalar(path, text)
+            }
+            ParseEvent::ArrayStart { path, .. } => self.open(path, true),
+            ParseEvent::ObjectBegin { path, .. } => self.open(path, false),
+            ParseEvent::ArrayEnd { .. } | ParseEvent::ObjectEnd { .. } => self.close(),
+            ParseEvent::ScalarBegin { .. }
+            | ParseEvent::ScalarEnd { .. }
+            | ParseEvent::ObjectKey { .. } => Ok(()),
+        }
+    }
+
+    fn scalar(&mut self, path: &[PathComponent], text: String) -> fmt::Result {
+        let max_inline = self.options.max_inline_array_items;
+        if let Some(Frame {
+            inline: Some(items),
+            ..
+        }) = self.frames.last_mut()
+        {
+            if items.len() < max_inline {
+                items.push(text);
+                return Ok(());
+            }
+            self.break_inline()?;
+        }
+        self.begin_value(path)?;
+        self.writer.write_str(&text)
+    }
+
+    fn open(&mut self, path: &[PathComponent], is_array: bool) -> fmt::Result {
+        self.break_inline()?;
+        self.begin_value(path)?;
+        self.writer.write_char(if is_array { '[' } else { '{' })?;
+        self.frames.push(Frame {
+            is_array,
+            len: 0,
+            inline: (is_array && self.options.max_inline_array_items > 0).then(Vec::new),
+        });
+        Ok(())
+    }
+
+    fn close(&mut self) -> fmt::Result {
+        let Some(frame) = self.frames.pop() else {
+            return Ok(());
+        };
+        if let Some(items) = frame.inline {
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    self.writer.write_str(", ")?;
+                }
+                self.writer.write_str(item)?;
+            }
+        } else if frame.len > 0 {
+            self.newline(self.frames.len())?;
+        }
+        self.writer
+            .write_char(if frame.is_array { ']' } else { '}' })
+    }
+
+    /// Writes out the buffered items of the innermost array, if it was still
+    /// a candidate for a single line, and switches it to one item per line.
+    fn break_inline(&mut self) -> fmt::Result {
+        let Some(items) = self.frames.last_mut().and_then(|frame| frame.inline.take()) else {
+            return Ok(());
+        };
+        for item in items {
+            self.begin_value(&[])?;
+            self.writer.write_str(&item)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the separator, indentation and (inside objects) key that
+    /// precede a value.
+    fn begin_value(&mut self, path: &[PathComponent]) -> fmt::Result {
+        let depth = self.frames.len();
+        let Some(frame) = self.frames.last_mut() else {
+            if self.roots > 0 {
+                self.writer.write_char('\n')?;
+            }
+            self.roots += 1;
+            return Ok(());
+        };
+        if frame.len > 0 {
+            self.writer.write_char(',')?;
+        }
+        frame.len += 1;
+        let is_array = frame.is_array;
+        self.newline(depth)?;
+        if !is_array {
+            if let Some(PathComponent::Key(key)) = path.last() {
+                let mut text = String::with_capacity(key.len() + 2);
+                write_string(&mut text, key, self.options.escape_non_ascii)?;
+                self.writer.write_str(&text)?;
+            }
+            self.writer.write_str(": ")?;
+        }
+        Ok(())
+    }
+
+    fn newline(&mut self, depth: usize) -> fmt::Result {
+        self.writer.write_char('\n')?;
+        for _ in 0..depth {
+            self.writer.write_str(&self.options.indent)?;
+        }
+        Ok(())
+    }
+}
+
+/// Appends `s` as a quoted JSON string literal.
+fn write_string(out: &mut String, s: &str, escape_non_ascii: bool) -> fmt::Result {
+    out.push('"');
+    if escape_non_ascii {
+        let mut buf = [0; 4];
+        for c in s.chars() {
+            if c.is_ascii() {
+                write_escaped_string(c.encode_utf8(&mut buf), out)?;
+            } else {
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    write!(out, "\\u{unit:04X}")?;
+                }
+            }
+        }
+    } else {
+        write_escaped_string(s, out)?;
+    }
+    out.push('"');
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use super::{JsonPrettyPrinter, PrettyOptions};
+    use crate::{ParseEvent, ParserOptions, tests::events};
+
+    fn multiple_values() -> ParserOptions {
+        ParserOptions {
+            allow_multiple_json_values: true,
+            ..Default::default()
+        }
+    }
+
+    fn pretty(events: &[ParseEvent], options: PrettyOptions) -> String {
+        let mut printer = JsonPrettyPrinter::new(String::new(), options);
+        for event in events {
+            printer.write_event(event).unwrap();
+        }
+        printer.into_inner()
+    }
+
+    const JSON: &str = r#"{"a": [1, 2.5, -3e-7], "b": {"c": [], "d": {}, "e": [[true], [null, "x"]]},
+        "s": "line\nbreak \"quoted\" é 😀", "kéy": [{"n": 1}, 2]}"#;
+
+    #[test]
+    fn round_trip_reproduces_events() {
+        let original = events(JSON, 1, multiple_values());
+        for options in [
+            PrettyOptions::default(),
+            PrettyOptions {
+                indent: "\t".into(),
+                max_inline_array_items: 2,
+                escape_non_ascii: true,
+            },
+        ] {
+            let text = pretty(&original, options);
+            assert_eq!(events(&text, 1, multiple_values()), original, "{text}");
+        }
+    }
+
+    #[test]
+    fn fragments_are_buffered() {
+        let whole = pretty(
+            &events(JSON, 1, multiple_values()),
+            PrettyOptions::default(),
+        );
+        for parts in [3, 20, JSON.len()] {
+            assert_eq!(
+                pretty(
+                    &events(JSON, parts, multiple_values()),
+                    PrettyOptions::default()
+                ),
+                whole
+            );
+        }
+    }
+
+    #[test]
+    fn small_arrays_are_inlined() {
+        let text = pretty(
+            &events(
+                r#"{"small": [1, 2], "large": [1, 2, 3], "nested": [[1], 2]}"#,
+                1,
+                multiple_values(),
+            ),
+            PrettyOptions {
+                max_inline_array_items: 2,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            text,
+            r#"{
+  "small": [1, 2],
+  "large": [
+    1,
+    2,
+    3
+  ],
+  "nested": [
+    [1],
+    2
+  ]
+}"#
+        );
+    }
+
+    #[test]
+    fn non_ascii_is_escaped() {
+        let text = pretty(
+            &events(r#"["é😀\u0001"]"#, 1, multiple_values()),
+            PrettyOptions {
+                escape_non_ascii: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(text, "[\n  \"\\u00E9\\uD83D\\uDE00\\u0001\"\n]");
+    }
+
+    #[test]
+    fn multiple_roots_are_separated() {
+        let text = pretty(
+            &events("1 [] \"x\"", 1, multiple_values()),
+            PrettyOptions::default(),
+        );
+        assert_eq!(text, "1\n[]\n\"x\"");
+    }
+}
@@ -0,0 +1,232 @@
+//! Progress reports for long parses.
+//!
+//! Parsing a large file can take a while, and a caller may want to show how
+//! far it has got. [`ProgressParser`] wraps a [`StreamingParserImpl`] and
+//! calls a callback with the [`StreamPosition`] reached each time parsing
+//! passes another multiple of an interval of input bytes.
+//!
+//! The callback runs synchronously, between events, so it should be cheap.
+//! Positions are checked after each event, so a single token longer than the
+//! interval, such as a long string fed in one chunk, leads to one report
+//! rather than one per interval crossed.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{ParserOptions, ProgressParser, StreamingParser};
+//!
+//! let mut reports = Vec::new();
+//! let mut parser = ProgressParser::new(
+//!     StreamingParser::new(ParserOptions::default()),
+//!     10,
+//!     |position| reports.push(position.bytes_consumed),
+//! );
+//! parser.feed("[1, 2, 3, 4, 5, 6, 7, 8, 9]").for_each(drop);
+//! parser.finish().for_each(drop);
+//! assert_eq!(reports, [11, 20]);
+//! ```
+use crate::{
+    JsonValue, JsonValueFactory, ParseEvent, ParserError, StdValueFactory, Value,
+    parser::{ClosedStreamingParser, StreamingParserImpl, StreamingParserIteratorWith},
+};
+
+/// How far a parse has got.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamPosition {
+    /// The number of bytes of input parsed; see
+    /// [`StreamingParserImpl::bytes_consumed`].
+    pub bytes_consumed: usize,
+    /// The line of the next character, starting at 1.
+    pub line: usize,
+    /// The column of the next character, starting at 1.
+    pub column: usize,
+    /// The number of arrays and objects open.
+    pub depth: usize,
+}
+
+/// The callback and when it is next due.
+#[derive(Debug)]
+struct Progress<C> {
+    callback: C,
+    interval: usize,
+    /// The number of bytes at which the next report is due.
+    next: usize,
+}
+
+impl<C: FnMut(StreamPosition)> Progress<C> {
+    fn report(&mut self, position: impl FnOnce() -> StreamPosition, bytes_consumed: usize) {
+        if bytes_consumed >= self.next {
+            self.next = (bytes_consumed / self.interval + 1) * self.interval;
+            (self.callback)(position());
+        }
+    }
+}
+
+/// A streaming parser that reports its progress to a callback.
+///
+/// See the [module documentation](self) for when the callback is called.
+#[derive(Debug)]
+pub struct ProgressParser<C, V: JsonValue = Value> {
+    parser: StreamingParserImpl<V>,
+    progress: Progress<C>,
+}
+
+impl<C: FnMut(StreamPosition), V: JsonValue> ProgressParser<C, V> {
+    /// Wraps `parser`, calling `callback` every `interval` bytes of input.
+    /// An interval of zero is treated as one.
+    #[must_use]
+    pub fn new(parser: StreamingParserImpl<V>, interval: usize, callback: C) -> Self {
+        let interval = interval.max(1);
+        Self {
+            progress: Progress {
+                callback,
+                interval,
+                next: parser.bytes_consumed() / interval * interval + interval,
+            },
+            parser,
+        }
+    }
+
+    /// Feeds a chunk of JSON text and returns an iterator over the events it
+    /// completes, building values with `factory`.
+    pub fn feed_with<'a, F: JsonValueFactory<Value = V>>(
+        &'a mut self,
+        factory: F,
+        text: &str,
+    ) -> ProgressEvents<'a, C, F> {
+        ProgressEvents {
+            events: self.parser.feed_with(factory, text),
+            progress: &mut self.progress,
+        }
+    }
+
+    /// Marks the end of input and returns an iterator over the remaining
+    /// events, building values with `factory`.
+    pub fn finish_with<F: JsonValueFactory<Value = V>>(
+        self,
+        factory: F,
+    ) -> FinishedProgressEvents<C, F> {
+        FinishedProgressEvents {
+            events: self.parser.finish_with(factory),
+            progress: self.progress,
+        }
+    }
+}
+
+impl<C: FnMut(StreamPosition)> ProgressParser<C> {
+    /// Feeds a chunk of JSON text and returns an iterator over the events it
+    /// completes.
+    pub fn feed<'a>(&'a mut self, text: &str) -> ProgressEvents<'a, C, StdValueFactory> {
+        self.feed_with(StdValueFactory, text)
+    }
+
+    /// Marks the end of input and returns an iterator over the remaining
+    /// events.
+    #[must_use]
+    pub fn finish(self) -> FinishedProgressEvents<C, StdValueFactory> {
+        self.finish_with(StdValueFactory)
+    }
+}
+
+/// Iterator returned by [`ProgressParser::feed`].
+pub struct ProgressEvents<'a, C, F: JsonValueFactory> {
+    events: StreamingParserIteratorWith<'a, F>,
+    progress: &'a mut Progress<C>,
+}
+
+impl<C: FnMut(StreamPosition), F: JsonValueFactory> Iterator for ProgressEvents<'_, C, F> {
+    type Item = Result<ParseEvent<F::Value>, ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let event = self.events.next()?;
+        let events = &self.events;
+        self.progress
+            .report(|| events.position(), events.bytes_consumed());
+        Some(event)
+    }
+}
+
+/// Iterator returned by [`ProgressParser::finish`].
+pub struct FinishedProgressEvents<C, F: JsonValueFactory> {
+    events: ClosedStreamingParser<F>,
+    progress: Progress<C>,
+}
+
+impl<C: FnMut(StreamPosition), F: JsonValueFactory> Iterator for FinishedProgressEvents<C, F> {
+    type Item = Result<ParseEvent<F::Value>, ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let event = self.events.next()?;
+        let events = &self.events;
+        self.progress
+            .report(|| events.position(), events.bytes_consumed());
+        Some(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::String, vec::Vec};
+    use core::fmt::Write;
+
+    use super::{ProgressParser, StreamPosition};
+    use crate::{ParserOptions, StreamingParser};
+
+    #[test]
+    fn reports_every_interval_of_a_large_document() {
+        // About 1 MB: an array of objects, one per line.
+        let mut json = String::from("[\n");
+        let mut i = 0;
+        while json.len() < 1 << 20 {
+            writeln!(
+                json,
+                r#"{{"id": {i}, "name": "item {i}", "tags": ["a", "b"]}},"#
+            )
+            .unwrap();
+            i += 1;
+        }
+        json.push_str("null\n]");
+
+        let interval = 64 << 10;
+        let mut reports: Vec<StreamPosition> = Vec::new();
+        let mut parser = ProgressParser::new(
+            StreamingParser::new(ParserOptions::default()),
+            interval,
+            |position| reports.push(position),
+        );
+        for chunk in json.as_bytes().chunks(10_000) {
+            let chunk = core::str::from_utf8(chunk).unwrap();
+            assert!(parser.feed(chunk).all(|event| event.is_ok()));
+        }
+        assert!(parser.finish().all(|event| event.is_ok()));
+
+        assert_eq!(reports.len(), json.len() / interval);
+        for (k, pair) in reports.windows(2).enumerate() {
+            assert!(pair[0].bytes_consumed < pair[1].bytes_consumed);
+            assert!(pair[0].line < pair[1].line);
+            // Reported as soon as the event crossing the boundary is pulled.
+            assert!(
+                (pair[0].bytes_consumed - (k + 1) * interval) < 100,
+                "{pair:?}"
+            );
+        }
+        assert!(
+            reports
+                .iter()
+                .all(|position| matches!(position.depth, 1..=3))
+        );
+    }
+
+    #[test]
+    fn long_tokens_report_once() {
+        let mut reports = Vec::new();
+        let mut parser = ProgressParser::new(
+            StreamingParser::new(ParserOptions::default()),
+            0,
+            |position: StreamPosition| reports.push((position.bytes_consumed, position.column)),
+        );
+        parser.feed(r#"["abcdef", 1]"#).for_each(drop);
+        parser.finish().for_each(drop);
+        assert_eq!(reports, [(1, 2), (9, 10), (12, 13), (13, 14)]);
+    }
+}
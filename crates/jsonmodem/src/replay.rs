@@ -0,0 +1,346 @@
+//! Recording of event streams for replay into several consumers.
+//!
+//! When one parsed stream feeds several subscribers that run at different
+//! times, re-parsing the input for each of them repeats all of the lexing
+//! work. [`EventRecorder`] keeps the events as they are and hands out
+//! references on every replay.
+//!
+//! Most of the memory held by recorded events is in their paths, which repeat
+//! heavily: every fragment of a string and every member of an object shares
+//! its parent's prefix. [`CompressedRecorder`] stores each distinct path once,
+//! in a trie of path components, and keeps only a node number per event. The
+//! trie also makes it cheap to replay just the events under a given path.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{CompressedRecorder, ParseEvent, ParserOptions, StreamingParser, path};
+//!
+//! let mut parser = StreamingParser::new(ParserOptions::default());
+//! let mut recorder = CompressedRecorder::new();
+//! for event in parser.feed(r#"{"a": [1, 2], "b": {"c": null}}"#) {
+//!     recorder.record(event.unwrap());
+//! }
+//!
+//! let under_b: Vec<_> = recorder.replay_prefix(&path!["b"]).collect();
+//! assert_eq!(
+//!     under_b,
+//!     vec![
+//!         ParseEvent::ObjectBegin {
+//!             path: path!["b"],
+//!             capacity_hint: None,
+//!             parent_key: None,
+//!         },
+//!         ParseEvent::Null {
+//!             path: path!["b", "c"]
+//!         },
+//!         ParseEvent::ObjectEnd {
+//!             path: path!["b"],
+//!             value: None
+//!         },
+//!     ]
+//! );
+//! ```
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use crate::{
+    JsonValue, ParseEvent, PathComponent, Value,
+    event::{Index, Key},
+};
+
+/// Stores events so that they can be replayed any number of times.
+#[derive(Debug, Clone)]
+pub struct EventRecorder<V: JsonValue = Value> {
+    events: Vec<ParseEvent<V>>,
+}
+
+impl<V: JsonValue> Default for EventRecorder<V> {
+    fn default() -> Self {
+        Self { events: Vec::new() }
+    }
+}
+
+impl<V: JsonValue> EventRecorder<V> {
+    /// Creates an empty recorder.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends one event.
+    pub fn record(&mut self, event: ParseEvent<V>) {
+        self.events.push(event);
+    }
+
+    /// Iterates over the recorded events in order.
+    pub fn replay(&self) -> impl Iterator<Item = &ParseEvent<V>> {
+        self.events.iter()
+    }
+
+    /// The number of recorded events.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns `true` if no events have been recorded.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Returns the recorded events.
+    #[must_use]
+    pub fn into_events(self) -> Vec<ParseEvent<V>> {
+        self.events
+    }
+}
+
+impl<V: JsonValue> Extend<ParseEvent<V>> for EventRecorder<V> {
+    fn extend<T: IntoIterator<Item = ParseEvent<V>>>(&mut self, iter: T) {
+        self.events.extend(iter);
+    }
+}
+
+/// An orderable copy of a path component, used to look up trie children.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Edge {
+    Key(Key),
+    Index(Index),
+}
+
+impl From<&PathComponent> for Edge {
+    fn from(component: &PathComponent) -> Self {
+        match component {
+            PathComponent::Key(key) => Edge::Key(key.clone()),
+            PathComponent::Index(index) => Edge::Index(*index),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    parent: usize,
+    depth: usize,
+    component: Option<PathComponent>,
+}
+
+/// The root node, i.e. the empty path.
+const ROOT: usize = 0;
+
+/// Stores events with their paths deduplicated in a trie.
+///
+/// Replayed events are rebuilt with owned paths, so replay allocates where
+/// [`EventRecorder`] does not; in exchange each distinct path is stored once.
+#[derive(Debug, Clone)]
+pub struct CompressedRecorder<V: JsonValue = Value> {
+    nodes: Vec<Node>,
+    children: BTreeMap<(usize, Edge), usize>,
+    /// Events with empty paths, paired with the node of their real path.
+    events: Vec<(usize, ParseEvent<V>)>,
+}
+
+impl<V: JsonValue> Default for CompressedRecorder<V> {
+    fn default() -> Self {
+        Self {
+            nodes: alloc::vec![Node {
+                parent: ROOT,
+                depth: 0,
+                component: None,
+            }],
+            children: BTreeMap::new(),
+            events: Vec::new(),
+        }
+    }
+}
+
+impl<V: JsonValue> CompressedRecorder<V> {
+    /// Creates an empty recorder.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends one event.
+    pub fn record(&mut self, mut event: ParseEvent<V>) {
+        let path = core::mem::take(event.path_mut());
+        let mut node = ROOT;
+        for component in path {
+            let parent = node;
+            let next = self.nodes.len();
+            node = *self
+                .children
+                .entry((parent, Edge::from(&component)))
+                .or_insert(next);
+            if node == next {
+                self.nodes.push(Node {
+                    parent,
+                    depth: self.nodes[parent].depth + 1,
+                    component: Some(component),
+                });
+            }
+        }
+        self.events.push((node, event));
+    }
+
+    /// Iterates over the recorded events in order.
+    pub fn replay(&self) -> impl Iterator<Item = ParseEvent<V>> + '_ {
+        self.events
+            .iter()
+            .map(|(node, event)| self.rebuild(*node, event))
+    }
+
+    /// Iterates over the events whose path starts with `prefix`, in order.
+    pub fn replay_prefix<'a>(
+        &'a self,
+        prefix: &[PathComponent],
+    ) -> impl Iterator<Item = ParseEvent<V>> + 'a {
+        let target = self.find(prefix);
+        self.events
+            .iter()
+            .filter(move |(node, _)| target.is_some_and(|target| self.is_under(*node, target)))
+            .map(|(node, event)| self.rebuild(*node, event))
+    }
+
+    /// Returns the position of the first event whose path starts with
+    /// `prefix`, or `None` if there is no such event.
+    #[must_use]
+    pub fn seek(&self, prefix: &[PathComponent]) -> Option<usize> {
+        let target = self.find(prefix)?;
+        self.events
+            .iter()
+            .position(|(node, _)| self.is_under(*node, target))
+    }
+
+    /// The number of recorded events.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns `true` if no events have been recorded.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// The number of path components stored, one per distinct non-empty
+    /// path. Compare with the sum of the path lengths of the events to
+    /// measure the saving.
+    #[must_use]
+    pub fn stored_components(&self) -> usize {
+        self.nodes.len() - 1
+    }
+
+    fn find(&self, path: &[PathComponent]) -> Option<usize> {
+        path.iter().try_fold(ROOT, |node, component| {
+            self.children.get(&(node, Edge::from(component))).copied()
+        })
+    }
+
+    fn is_under(&self, mut node: usize, ancestor: usize) -> bool {
+        let depth = self.nodes[ancestor].depth;
+        while self.nodes[node].depth > depth {
+            node = self.nodes[node].parent;
+        }
+        node == ancestor
+    }
+
+    fn rebuild(&self, node: usize, event: &ParseEvent<V>) -> ParseEvent<V> {
+        let mut path = Vec::with_capacity(self.nodes[node].depth);
+        let mut current = node;
+        while let Some(component) = &self.nodes[current].component {
+            path.push(component.clone());
+            current = self.nodes[current].parent;
+        }
+        path.reverse();
+        let mut event = event.clone();
+        *event.path_mut() = path;
+        event
+    }
+}
+
+impl<V: JsonValue> Extend<ParseEvent<V>> for CompressedRecorder<V> {
+    fn extend<T: IntoIterator<Item = ParseEvent<V>>>(&mut self, iter: T) {
+        for event in iter {
+            self.record(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::{CompressedRecorder, EventRecorder};
+    use crate::{ParserOptions, path, tests::events};
+
+    const JSON: &str = r#"{
+        "items": [
+            {"name": "first", "tags": ["a", "b"]},
+            {"name": "a much longer second name", "tags": []}
+        ],
+        "total": 2,
+        "itemsX": null
+    }"#;
+
+    #[test]
+    fn recorders_replay_every_event() {
+        for parts in [1, 10, JSON.len()] {
+            let original = events(JSON, parts, ParserOptions::default());
+            let mut plain = EventRecorder::new();
+            plain.extend(original.clone());
+            let mut compressed = CompressedRecorder::new();
+            compressed.extend(original.clone());
+
+            // Replaying twice gives the same events both times.
+            for _ in 0..2 {
+                assert!(plain.replay().eq(original.iter()));
+                assert_eq!(compressed.replay().collect::<Vec<_>>(), original);
+            }
+            assert_eq!(compressed.len(), original.len());
+        }
+    }
+
+    #[test]
+    fn paths_are_stored_once() {
+        let original = events(JSON, JSON.len(), ParserOptions::default());
+        let total: usize = original.iter().map(|event| event.path().len()).sum();
+        let mut compressed = CompressedRecorder::new();
+        compressed.extend(original);
+        // items, six paths under items[0], three under items[1], total and
+        // itemsX.
+        assert_eq!(compressed.stored_components(), 11);
+        assert!(total > 3 * compressed.stored_components());
+    }
+
+    #[test]
+    fn replay_prefix_selects_subtrees() {
+        let mut compressed = CompressedRecorder::new();
+        compressed.extend(events(JSON, 7, ParserOptions::default()));
+
+        let tags: Vec<_> = compressed
+            .replay_prefix(&path!["items", 0, "tags"])
+            .collect();
+        assert_eq!(tags.len(), 4);
+        assert!(
+            tags.iter()
+                .all(|event| event.path().starts_with(&path!["items", 0, "tags"]))
+        );
+
+        // A key that is a prefix of another key is not a path prefix.
+        let items = compressed.replay_prefix(&path!["items"]).count();
+        assert_eq!(
+            items,
+            compressed
+                .replay()
+                .filter(|event| event.path().first() == Some(&"items".into()))
+                .count()
+        );
+
+        assert_eq!(compressed.seek(&path!["total"]), Some(items + 1));
+        assert_eq!(compressed.seek(&path!["missing"]), None);
+        assert_eq!(compressed.replay_prefix(&path!["missing"]).count(), 0);
+        assert_eq!(compressed.replay_prefix(&[]).count(), compressed.len());
+    }
+}
@@ -0,0 +1,834 @@
+//! Encoding of event streams as Avro binary data.
+//!
+//! [`AvroEncoder`] consumes the events of one or more root values and writes
+//! each as an Avro datum in the binary encoding, as described by an
+//! [`AvroSchema`]. The schema is read from its usual JSON form with
+//! [`AvroSchema::parse`].
+//!
+//! Avro lays out the fields of a record in schema order and prefixes each
+//! array or map with its length, so the encoding of a value cannot be written
+//! until the value is complete. Each root value is therefore encoded in
+//! memory and written out once its last event arrives; memory use is
+//! proportional to the encoding of the largest root. Fields missing from an
+//! object take the default from the schema.
+//!
+//! The branch of a union is the first one that accepts the value: `null` for
+//! `null`, `int` or `long` for numbers without a fractional part that fit,
+//! `float` or `double` for any number, `string`, `bytes`, `fixed` or an enum
+//! for strings, and an array, map or record for containers. As in Avro's JSON
+//! encoding, `bytes` and `fixed` values are given as strings whose characters
+//! are all in U+0000..=U+00FF.
+//!
+//! Named types may be referred to by name once defined, but a type may not
+//! refer to itself.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{AvroEncoder, AvroSchema, ParserOptions, StreamingParser};
+//!
+//! let schema = AvroSchema::parse(
+//!     r#"{"type": "record", "name": "Point", "fields": [
+//!         {"name": "x", "type": "long"},
+//!         {"name": "label", "type": ["null", "string"], "default": null}
+//!     ]}"#,
+//! )?;
+//! let mut parser = StreamingParser::new(ParserOptions::default());
+//! let mut encoder = AvroEncoder::new(&schema, Vec::new());
+//! for event in parser.feed(r#"{"label": "a", "x": 1}"#) {
+//!     encoder.write_event(&event?)?;
+//! }
+//! assert_eq!(encoder.into_inner(), [0x02, 0x02, 0x02, b'a']);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+use std::io::Write;
+
+use crate::{ParseEvent, ParseEventWriter, ParserError, ParserOptions, PathComponent, Value};
+
+/// Error returned by [`AvroSchema::parse`] and [`AvroEncoder`].
+#[derive(Debug)]
+pub enum AvroError {
+    /// The schema is not valid JSON.
+    Parser(ParserError),
+    /// The schema is not a valid Avro schema, or uses an unsupported feature.
+    Schema(String),
+    /// The value at the path does not match its schema.
+    TypeMismatch(Vec<PathComponent>),
+    /// An object has a member that is not a field of its record.
+    UnknownField(Vec<PathComponent>),
+    /// An object lacks a field that has no default. The path is that of the
+    /// object.
+    MissingField {
+        /// The path of the object.
+        path: Vec<PathComponent>,
+        /// The name of the missing field.
+        field: String,
+    },
+    /// Writing the encoded value failed.
+    Io(std::io::Error),
+}
+
+impl fmt::Display for AvroError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AvroError::Parser(err) => write!(f, "invalid schema: {err}"),
+            AvroError::Schema(msg) => write!(f, "invalid schema: {msg}"),
+            AvroError::TypeMismatch(path) => write!(f, "value does not match schema at {path:?}"),
+            AvroError::UnknownField(path) => write!(f, "unknown field at {path:?}"),
+            AvroError::MissingField { path, field } => {
+                write!(f, "missing field {field:?} at {path:?}")
+            }
+            AvroError::Io(err) => err.fmt(f),
+        }
+    }
+}
+
+impl core::error::Error for AvroError {}
+
+impl From<std::io::Error> for AvroError {
+    fn from(err: std::io::Error) -> Self {
+        AvroError::Io(err)
+    }
+}
+
+/// An Avro schema.
+///
+/// Logical types are encoded as their underlying type, and the names and
+/// aliases of named types are only used to resolve references.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AvroSchema {
+    /// `null`.
+    Null,
+    /// `boolean`.
+    Boolean,
+    /// `int`, a 32-bit signed integer.
+    Int,
+    /// `long`, a 64-bit signed integer.
+    Long,
+    /// `float`, a single precision number.
+    Float,
+    /// `double`, a double precision number.
+    Double,
+    /// `bytes`.
+    Bytes,
+    /// `string`.
+    String,
+    /// A record, encoded as its fields in order.
+    Record {
+        /// The full name of the record.
+        name: String,
+        /// The fields of the record.
+        fields: Vec<AvroField>,
+    },
+    /// An enum, encoded as the index of its symbol.
+    Enum {
+        /// The full name of the enum.
+        name: String,
+        /// The symbols of the enum.
+        symbols: Vec<String>,
+    },
+    /// An array of items of one schema.
+    Array(Box<AvroSchema>),
+    /// A map from strings to values of one schema.
+    Map(Box<AvroSchema>),
+    /// A union of schemas, encoded as the index of a branch and a value of
+    /// that branch.
+    Union(Vec<AvroSchema>),
+    /// A fixed number of bytes.
+    Fixed {
+        /// The full name of the fixed type.
+        name: String,
+        /// The number of bytes.
+        size: usize,
+    },
+}
+
+/// A field of an Avro record.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AvroField {
+    /// The name of the field, which is the key of its member in an object.
+    pub name: String,
+    /// The schema of the field's value.
+    pub schema: AvroSchema,
+    /// The value used when an object has no member for the field.
+    pub default: Option<Value>,
+}
+
+impl AvroSchema {
+    /// Reads a schema from its JSON form.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `json` is not valid JSON or not a valid schema.
+    pub fn parse(json: &str) -> Result<Self, AvroError> {
+        let value = crate::StreamingParser::new(ParserOptions::default())
+            .into_value(json)
+            .map_err(AvroError::Parser)?;
+        parse_schema(&value, "", &mut BTreeMap::new())
+    }
+
+    /// Returns the index and schema of the first branch accepting a value of
+    /// which `pred` holds, or this schema itself if it is not a union.
+    fn select(&self, pred: impl Fn(&Self) -> bool) -> Option<(Option<usize>, &Self)> {
+        match self {
+            AvroSchema::Union(branches) => branches
+                .iter()
+                .enumerate()
+                .find(|(_, branch)| pred(branch))
+                .map(|(index, branch)| (Some(index), branch)),
+            schema if pred(schema) => Some((None, schema)),
+            _ => None,
+        }
+    }
+}
+
+fn schema_error(msg: &str) -> AvroError {
+    AvroError::Schema(msg.to_string())
+}
+
+fn parse_schema(
+    value: &Value,
+    namespace: &str,
+    names: &mut BTreeMap<String, AvroSchema>,
+) -> Result<AvroSchema, AvroError> {
+    let object = match value {
+        Value::String(name) => return parse_type_name(name, namespace, names),
+        Value::Array(branches) => {
+            return branches
+                .iter()
+                .map(|branch| parse_schema(branch, namespace, names))
+                .collect::<Result<_, _>>()
+                .map(AvroSchema::Union);
+        }
+        Value::Object(object) => object,
+        _ => return Err(schema_error("expected a string, array or object")),
+    };
+    let field = |key: &str| object.get(key);
+    let string = |key: &str| match field(key) {
+        Some(Value::String(s)) => Ok(s.as_str()),
+        _ => Err(AvroError::Schema(format!("expected a string {key:?}"))),
+    };
+    let type_name = match field("type") {
+        Some(Value::String(name)) => name.as_str(),
+        Some(nested) => return parse_schema(nested, namespace, names),
+        None => return Err(schema_error("missing \"type\"")),
+    };
+
+    let schema = match type_name {
+        "array" => AvroSchema::Array(Box::new(parse_schema(
+            field("items").ok_or_else(|| schema_error("missing \"items\""))?,
+            namespace,
+            names,
+        )?)),
+        "map" => AvroSchema::Map(Box::new(parse_schema(
+            field("values").ok_or_else(|| schema_error("missing \"values\""))?,
+            namespace,
+            names,
+        )?)),
+        "record" | "error" | "enum" | "fixed" => {
+            let name = string("name")?;
+            let namespace = match (name.rfind('.'), field("namespace")) {
+                (Some(dot), _) => &name[..dot],
+                (None, Some(Value::String(namespace))) => namespace.as_str(),
+                (None, _) => namespace,
+            };
+            let full_name = full_name(name, namespace);
+            let schema = match type_name {
+                "enum" => AvroSchema::Enum {
+                    name: full_name.clone(),
+                    symbols: match field("symbols") {
+                        Some(Value::Array(symbols)) => symbols
+                            .iter()
+                            .map(|symbol| match symbol {
+                                Value::String(symbol) => Ok(symbol.clone()),
+                                _ => Err(schema_error("expected a string symbol")),
+                            })
+                            .collect::<Result<_, _>>()?,
+                        _ => return Err(schema_error("missing \"symbols\"")),
+                    },
+                },
+                "fixed" => AvroSchema::Fixed {
+                    name: full_name.clone(),
+                    size: match field("size") {
+                        Some(Value::Number(size)) if size.fract() == 0.0 && *size >= 0.0 => {
+                            #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                            let size = *size as usize;
+                            size
+                        }
+                        _ => return Err(schema_error("missing \"size\"")),
+                    },
+                },
+                _ => AvroSchema::Record {
+                    name: full_name.clone(),
+                    fields: match field("fields") {
+                        Some(Value::Array(fields)) => fields
+                            .iter()
+                            .map(|field| parse_field(field, namespace, names))
+                            .collect::<Result<_, _>>()?,
+                        _ => return Err(schema_error("missing \"fields\"")),
+                    },
+                },
+            };
+            names.insert(full_name, schema.clone());
+            schema
+        }
+        primitive => parse_type_name(primitive, namespace, names)?,
+    };
+    Ok(schema)
+}
+
+fn parse_field(
+    value: &Value,
+    namespace: &str,
+    names: &mut BTreeMap<String, AvroSchema>,
+) -> Result<AvroField, AvroError> {
+    let Value::Object(object) = value else {
+        return Err(schema_error("expected a field object"));
+    };
+    let Some(Value::String(name)) = object.get("name") else {
+        return Err(schema_error("expected a string \"name\""));
+    };
+    let schema = parse_schema(
+        object
+            .get("type")
+            .ok_or_else(|| schema_error("missing \"type\""))?,
+        namespace,
+        names,
+    )?;
+    Ok(AvroField {
+        name: name.clone(),
+        schema,
+        default: object.get("default").cloned(),
+    })
+}
+
+fn parse_type_name(
+    name: &str,
+    namespace: &str,
+    names: &BTreeMap<String, AvroSchema>,
+) -> Result<AvroSchema, AvroError> {
+    Ok(match name {
+        "null" => AvroSchema::Null,
+        "boolean" => AvroSchema::Boolean,
+        "int" => AvroSchema::Int,
+        "long" => AvroSchema::Long,
+        "float" => AvroSchema::Float,
+        "double" => AvroSchema::Double,
+        "bytes" => AvroSchema::Bytes,
+        "string" => AvroSchema::String,
+        name => names
+            .get(&full_name(name, namespace))
+            .or_else(|| names.get(name))
+            .cloned()
+            .ok_or_else(|| AvroError::Schema(format!("unknown type {name:?}")))?,
+    })
+}
+
+fn full_name(name: &str, namespace: &str) -> String {
+    if name.contains('.') || namespace.is_empty() {
+        name.to_string()
+    } else {
+        format!("{namespace}.{name}")
+    }
+}
+
+/// Returns `true` if `value` can be encoded with `schema`, checking only the
+/// kind of arrays and objects.
+fn accepts(schema: &AvroSchema, value: &Value) -> bool {
+    match (schema, value) {
+        (AvroSchema::Null, Value::Null)
+        | (AvroSchema::Boolean, Value::Boolean(_))
+        | (AvroSchema::Float | AvroSchema::Double, Value::Number(_))
+        | (AvroSchema::String, Value::String(_))
+        | (AvroSchema::Array(_), Value::Array(_))
+        | (AvroSchema::Map(_) | AvroSchema::Record { .. }, Value::Object(_)) => true,
+        (AvroSchema::Int, Value::Number(n)) => {
+            n.fract() == 0.0 && (f64::from(i32::MIN)..=f64::from(i32::MAX)).contains(n)
+        }
+        // 2^63 itself is out of range.
+        #[expect(clippy::cast_precision_loss)]
+        (AvroSchema::Long, Value::Number(n)) => {
+            n.fract() == 0.0 && (i64::MIN as f64..i64::MAX as f64).contains(n)
+        }
+        (AvroSchema::Bytes, Value::String(s)) => s.chars().all(|c| c <= '\u{FF}'),
+        (AvroSchema::Fixed { size, .. }, Value::String(s)) => {
+            s.chars().all(|c| c <= '\u{FF}') && s.chars().count() == *size
+        }
+        (AvroSchema::Enum { symbols, .. }, Value::String(s)) => symbols.contains(s),
+        (AvroSchema::Union(branches), value) => {
+            branches.iter().any(|branch| accepts(branch, value))
+        }
+        _ => false,
+    }
+}
+
+fn write_long(out: &mut Vec<u8>, n: i64) {
+    #[expect(clippy::cast_sign_loss)]
+    let mut zigzag = ((n << 1) ^ (n >> 63)) as u64;
+    while zigzag >= 0x80 {
+        #[expect(clippy::cast_possible_truncation)]
+        out.push((zigzag as u8 & 0x7F) | 0x80);
+        zigzag >>= 7;
+    }
+    #[expect(clippy::cast_possible_truncation)]
+    out.push(zigzag as u8);
+}
+
+fn write_len(out: &mut Vec<u8>, len: usize) {
+    write_long(out, i64::try_from(len).unwrap_or(i64::MAX));
+}
+
+fn write_string_bytes(out: &mut Vec<u8>, s: &str) {
+    write_len(out, s.len());
+    out.extend_from_slice(s.as_bytes());
+}
+
+/// Encodes `value` with `schema`, returning `false` if it does not match.
+fn encode_value(schema: &AvroSchema, value: &Value, out: &mut Vec<u8>) -> bool {
+    if !accepts(schema, value) {
+        return false;
+    }
+    match (schema, value) {
+        (AvroSchema::Boolean, Value::Boolean(b)) => out.push(u8::from(*b)),
+        #[expect(clippy::cast_possible_truncation)]
+        (AvroSchema::Int | AvroSchema::Long, Value::Number(n)) => write_long(out, *n as i64),
+        #[expect(clippy::cast_possible_truncation)]
+        (AvroSchema::Float, Value::Number(n)) => out.extend_from_slice(&(*n as f32).to_le_bytes()),
+        (AvroSchema::Double, Value::Number(n)) => out.extend_from_slice(&n.to_le_bytes()),
+        (AvroSchema::String, Value::String(s)) => write_string_bytes(out, s),
+        (AvroSchema::Bytes | AvroSchema::Fixed { .. }, Value::String(s)) => {
+            if matches!(schema, AvroSchema::Bytes) {
+                write_len(out, s.chars().count());
+            }
+            out.extend(s.chars().map(|c| c as u8));
+        }
+        (AvroSchema::Enum { symbols, .. }, Value::String(s)) => {
+            let index = symbols.iter().position(|symbol| symbol == s);
+            write_len(out, index.unwrap_or_default());
+        }
+        (AvroSchema::Array(items), Value::Array(values)) => {
+            if !values.is_empty() {
+                write_len(out, values.len());
+                for value in values {
+                    if !encode_value(items, value, out) {
+                        return false;
+                    }
+                }
+            }
+            out.push(0);
+        }
+        (AvroSchema::Map(schema), Value::Object(members)) => {
+            if !members.is_empty() {
+                write_len(out, members.len());
+                for (key, value) in members {
+                    write_string_bytes(out, key);
+                    if !encode_value(schema, value, out) {
+                        return false;
+                    }
+                }
+            }
+            out.push(0);
+        }
+        (AvroSchema::Record { fields, .. }, Value::Object(members)) => {
+            for field in fields {
+                match members.get(field.name.as_str()).or(field.default.as_ref()) {
+                    Some(value) if encode_value(&field.schema, value, out) => {}
+                    _ => return false,
+                }
+            }
+        }
+        (AvroSchema::Union(branches), value) => {
+            let Some(index) = branches.iter().position(|branch| accepts(branch, value)) else {
+                return false;
+            };
+            write_len(out, index);
+            return encode_value(&branches[index], value, out);
+        }
+        _ => {}
+    }
+    true
+}
+
+/// An array, map or record being encoded.
+struct Frame<'s> {
+    kind: FrameKind<'s>,
+    /// The index of the union branch chosen for the container, if any.
+    branch: Option<usize>,
+    /// The number of items or entries so far.
+    count: usize,
+    /// The encoded items or entries so far.
+    bytes: Vec<u8>,
+}
+
+enum FrameKind<'s> {
+    Array(&'s AvroSchema),
+    Map(&'s AvroSchema),
+    Record {
+        fields: &'s [AvroField],
+        /// The encoded value of each field, in schema order.
+        values: Vec<Option<Vec<u8>>>,
+        /// The field of the member being encoded.
+        current: usize,
+    },
+}
+
+/// Writes the events of each root value as an Avro datum.
+pub struct AvroEncoder<'s, W> {
+    schema: &'s AvroSchema,
+    writer: W,
+    frames: Vec<Frame<'s>>,
+    /// The fragments so far of a string split across events.
+    string: String,
+    values: usize,
+}
+
+impl<'s, W: Write> AvroEncoder<'s, W> {
+    /// Creates an encoder writing values of `schema` to `writer`.
+    pub fn new(schema: &'s AvroSchema, writer: W) -> Self {
+        Self {
+            schema,
+            writer,
+            frames: Vec::new(),
+            string: String::new(),
+            values: 0,
+        }
+    }
+
+    /// The number of root values written so far.
+    pub fn values(&self) -> usize {
+        self.values
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Processes one event, writing a root value once it is complete.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the events do not match the schema, or if the
+    /// writer fails. The encoder should not be used after an error.
+    pub fn write_event(&mut self, event: &ParseEvent) -> Result<(), AvroError> {
+        let path = event.path();
+        let scalar = match event {
+            ParseEvent::Null { .. } => Value::Null,
+            ParseEvent::Boolean { value, .. } => Value::Boolean(*value),
+            ParseEvent::Number { value, .. } => Value::Number(*value),
+            ParseEvent::String {
+                fragment, is_final, ..
+            } => {
+                self.string.push_str(fragment);
+                if !is_final {
+                    return Ok(());
+                }
+                Value::String(core::mem::take(&mut self.string))
+            }
+            ParseEvent::StringComplete { value, .. } => Value::String(value.clone()),
+            ParseEvent::ArrayStart { .. } => {
+                let schema = self.child_schema(path)?;
+                let (branch, schema) = schema
+                    .select(|schema| matches!(schema, AvroSchema::Array(_)))
+                    .ok_or_else(|| AvroError::TypeMismatch(path.to_vec()))?;
+                let AvroSchema::Array(items) = schema else {
+                    unreachable!("selected an array schema");
+                };
+                self.push_frame(FrameKind::Array(items), branch);
+                return Ok(());
+            }
+            ParseEvent::ObjectBegin { .. } => {
+                let schema = self.child_schema(path)?;
+                let (branch, schema) = schema
+                    .select(|schema| {
+                        matches!(schema, AvroSchema::Map(_) | AvroSchema::Record { .. })
+                    })
+                    .ok_or_else(|| AvroError::TypeMismatch(path.to_vec()))?;
+                let kind = match schema {
+                    AvroSchema::Map(values) => FrameKind::Map(values),
+                    AvroSchema::Record { fields, .. } => FrameKind::Record {
+                        fields,
+                        values: fields.iter().map(|_| None).collect(),
+                        current: 0,
+                    },
+                    _ => unreachable!("selected a map or record schema"),
+                };
+                self.push_frame(kind, branch);
+                return Ok(());
+            }
+            ParseEvent::ArrayEnd { .. } | ParseEvent::ObjectEnd { .. } => {
+                let Some(frame) = self.frames.pop() else {
+                    return Ok(());
+                };
+                let bytes = finish_frame(frame, path)?;
+                return self.emit(path, bytes);
+            }
+            ParseEvent::ScalarBegin { .. }
+            | ParseEvent::ScalarEnd { .. }
+            | ParseEvent::ObjectKey { .. } => return Ok(()),
+        };
+        let schema = self.child_schema(path)?;
+        let mut bytes = Vec::new();
+        if !encode_value(schema, &scalar, &mut bytes) {
+            return Err(AvroError::TypeMismatch(path.to_vec()));
+        }
+        self.emit(path, bytes)
+    }
+
+    fn push_frame(&mut self, kind: FrameKind<'s>, branch: Option<usize>) {
+        self.frames.push(Frame {
+            kind,
+            branch,
+            count: 0,
+            bytes: Vec::new(),
+        });
+    }
+
+    /// The schema of the value starting at `path`.
+    fn child_schema(&mut self, path: &[PathComponent]) -> Result<&'s AvroSchema, AvroError> {
+        let Some(frame) = self.frames.last_mut() else {
+            return Ok(self.schema);
+        };
+        match &mut frame.kind {
+            FrameKind::Array(items) => Ok(*items),
+            FrameKind::Map(values) => Ok(*values),
+            FrameKind::Record {
+                fields, current, ..
+            } => {
+                let fields: &'s [AvroField] = fields;
+                let Some(PathComponent::Key(key)) = path.last() else {
+                    return Err(AvroError::TypeMismatch(path.to_vec()));
+                };
+                *current = fields
+                    .iter()
+                    .position(|field| *field.name == **key)
+                    .ok_or_else(|| AvroError::UnknownField(path.to_vec()))?;
+                Ok(&fields[*current].schema)
+            }
+        }
+    }
+
+    /// Hands the encoding of the complete value at `path` to its container,
+    /// or writes it out if it is a root.
+    fn emit(&mut self, path: &[PathComponent], bytes: Vec<u8>) -> Result<(), AvroError> {
+        let Some(frame) = self.frames.last_mut() else {
+            self.writer.write_all(&bytes)?;
+            self.values += 1;
+            return Ok(());
+        };
+        match &mut frame.kind {
+            FrameKind::Record {
+                values, current, ..
+            } => values[*current] = Some(bytes),
+            kind => {
+                if let (FrameKind::Map(_), Some(PathComponent::Key(key))) = (kind, path.last()) {
+                    write_string_bytes(&mut frame.bytes, key);
+                }
+                frame.count += 1;
+                frame.bytes.extend_from_slice(&bytes);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Encodes a closed container from its items, entries or fields.
+fn finish_frame(frame: Frame<'_>, path: &[PathComponent]) -> Result<Vec<u8>, AvroError> {
+    let mut out = Vec::new();
+    if let Some(branch) = frame.branch {
+        write_len(&mut out, branch);
+    }
+    match frame.kind {
+        FrameKind::Record { fields, values, .. } => {
+            for (field, value) in fields.iter().zip(values) {
+                match (value, &field.default) {
+                    (Some(bytes), _) => out.extend_from_slice(&bytes),
+                    (None, Some(default)) if encode_value(&field.schema, default, &mut out) => {}
+                    (None, _) => {
+                        return Err(AvroError::MissingField {
+                            path: path.to_vec(),
+                            field: field.name.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        FrameKind::Array(_) | FrameKind::Map(_) => {
+            // A single block holding every item, then the empty block.
+            if frame.count > 0 {
+                write_len(&mut out, frame.count);
+                out.extend_from_slice(&frame.bytes);
+            }
+            out.push(0);
+        }
+    }
+    Ok(out)
+}
+
+impl<W: Write> ParseEventWriter<Value> for AvroEncoder<'_, W> {
+    type Error = AvroError;
+
+    fn write_event(&mut self, event: ParseEvent) -> Result<(), Self::Error> {
+        AvroEncoder::write_event(self, &event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{boxed::Box, string::ToString, vec, vec::Vec};
+
+    use super::{AvroEncoder, AvroError, AvroSchema};
+    use crate::{ParserOptions, StreamingParser, path, produce_chunks};
+
+    fn encode(schema: &str, json: &str) -> Result<Vec<u8>, AvroError> {
+        let schema = AvroSchema::parse(schema)?;
+        let mut expected = None;
+        // The encoding does not depend on how the input is split.
+        for parts in [1, 3, json.len()] {
+            let mut parser = StreamingParser::new(ParserOptions {
+                allow_multiple_json_values: true,
+                ..Default::default()
+            });
+            let mut encoder = AvroEncoder::new(&schema, Vec::new());
+            for chunk in produce_chunks(json, parts) {
+                for event in parser.feed(chunk) {
+                    encoder.write_event(&event.map_err(AvroError::Parser)?)?;
+                }
+            }
+            for event in parser.finish() {
+                encoder.write_event(&event.map_err(AvroError::Parser)?)?;
+            }
+            let bytes = encoder.into_inner();
+            assert_eq!(*expected.get_or_insert_with(|| bytes.clone()), bytes);
+        }
+        Ok(expected.unwrap_or_default())
+    }
+
+    #[test]
+    fn primitives() {
+        // Examples from the Avro specification.
+        assert_eq!(
+            encode(r#""long""#, "0 -1 1 -64 64").unwrap(),
+            [0x00, 0x01, 0x02, 0x7F, 0x80, 0x01]
+        );
+        assert_eq!(
+            encode(r#""string""#, r#""foo""#).unwrap(),
+            [0x06, b'f', b'o', b'o']
+        );
+        assert_eq!(
+            encode(r#"{"type": "int"}"#, "true").map_err(|e| e.to_string()),
+            Err("value does not match schema at []".into())
+        );
+        assert_eq!(encode(r#""boolean""#, "true false").unwrap(), [1, 0]);
+        assert_eq!(encode(r#""null""#, "null").unwrap(), [0u8; 0]);
+        assert_eq!(encode(r#""double""#, "1.5").unwrap(), 1.5f64.to_le_bytes());
+        assert_eq!(encode(r#""float""#, "1.5").unwrap(), 1.5f32.to_le_bytes());
+        assert_eq!(
+            encode(r#""bytes""#, r#""ÿ\u0000""#).unwrap(),
+            [0x04, 0xFF, 0x00]
+        );
+        assert!(encode(r#""bytes""#, r#""☕""#).is_err());
+        assert!(encode(r#""int""#, "3000000000").is_err());
+        assert!(encode(r#""long""#, "1.5").is_err());
+    }
+
+    #[test]
+    fn unions_pick_the_first_matching_branch() {
+        let schema = r#"["null", "int", "double", "string", {"type": "array", "items": "int"}]"#;
+        assert_eq!(
+            encode(schema, r#"null 1 1.5 "a" [2]"#).unwrap(),
+            [
+                [0x00].as_slice(),
+                &[0x02, 0x02],
+                &[0x04],
+                &1.5f64.to_le_bytes(),
+                &[0x06, 0x02, b'a'],
+                &[0x08, 0x02, 0x04, 0x00],
+            ]
+            .concat()
+        );
+        assert!(encode(schema, "{}").is_err());
+    }
+
+    #[test]
+    fn records_are_written_in_schema_order() {
+        let schema = r#"{
+            "type": "record", "name": "User", "namespace": "test",
+            "fields": [
+                {"name": "id", "type": "long"},
+                {"name": "tags", "type": {"type": "array", "items": "string"}},
+                {"name": "scores", "type": {"type": "map", "values": "int"}, "default": {}},
+                {"name": "kind", "type": {"type": "enum", "name": "Kind", "symbols": ["A", "B"]}},
+                {"name": "other", "type": ["null", "Kind"], "default": null},
+                {"name": "hash", "type": {"type": "fixed", "name": "Hash", "size": 2}}
+            ]
+        }"#;
+        let json = r#"
+            {"kind": "B", "hash": "ab", "tags": ["x", "yz"], "id": 3, "scores": {"a": 1}}
+            {"id": -1, "tags": [], "kind": "A", "other": "A", "hash": "cd"}
+        "#;
+        assert_eq!(
+            encode(schema, json).unwrap(),
+            [
+                // First record.
+                [0x06].as_slice(),
+                &[0x04, 0x02, b'x', 0x04, b'y', b'z', 0x00],
+                &[0x02, 0x02, b'a', 0x02, 0x00],
+                &[0x02],
+                &[0x00],
+                b"ab",
+                // Second record, with defaults.
+                &[0x01],
+                &[0x00],
+                &[0x00],
+                &[0x00],
+                &[0x02, 0x00],
+                b"cd",
+            ]
+            .concat()
+        );
+
+        assert!(matches!(
+            encode(schema, r#"{"id": 1, "tags": [], "kind": "A"}"#),
+            Err(AvroError::MissingField { field, .. }) if field == "hash"
+        ));
+        assert!(matches!(
+            encode(schema, r#"{"id": 1, "extra": 2}"#),
+            Err(AvroError::UnknownField(path)) if path == path!["extra"]
+        ));
+        assert!(matches!(
+            encode(schema, r#"{"id": 1, "tags": [], "kind": "C"}"#),
+            Err(AvroError::TypeMismatch(path)) if path == path!["kind"]
+        ));
+    }
+
+    #[test]
+    fn invalid_schemas() {
+        for schema in [
+            r#""Unknown""#,
+            r#"{"type": "record", "name": "A", "fields": [{"name": "a", "type": "A"}]}"#,
+            r#"{"type": "array"}"#,
+            r#"{"type": "fixed", "name": "F"}"#,
+            "[1,,]",
+            "1",
+        ] {
+            assert!(AvroSchema::parse(schema).is_err(), "{schema}");
+        }
+        let schema = AvroSchema::parse(r#"{"type": "map", "values": ["null", "long"]}"#).unwrap();
+        assert_eq!(
+            schema,
+            AvroSchema::Map(Box::new(AvroSchema::Union(vec![
+                AvroSchema::Null,
+                AvroSchema::Long
+            ])))
+        );
+    }
+}
@@ -0,0 +1,1083 @@
+//! Streaming validation of event streams against a JSON Schema.
+//!
+//! [`validate_streaming`] checks events against a [`JsonSchema`] as they
+//! pass, without building the document. A schema is compiled from its JSON
+//! form with [`compile_schema`].
+//!
+//! The keywords `type`, `properties`, `required`, `items`, `enum`,
+//! `minLength`, `maxLength`, `minimum`, `maximum` and `pattern` of draft
+//! 2020-12 are supported, as are the schemas `true` and `false`. Other
+//! keywords are ignored, as unknown keywords are by the specification, so a
+//! schema relying on them accepts more than it would elsewhere. Patterns use
+//! the syntax of the `regex` crate, which agrees with ECMA-262 for common
+//! patterns but lacks look-around and backreferences.
+//!
+//! Validation keeps one frame per open array or object, so memory use is
+//! proportional to the nesting depth of the document, plus the value of any
+//! string checked against a `pattern` or `enum` and of any array or object
+//! checked against an `enum`.
+//!
+//! Requires the `json-schema` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{
+//!     ParserOptions, StreamingParser, ValidationError, compile_schema, validate_streaming,
+//! };
+//!
+//! let schema = compile_schema(
+//!     r#"{"type": "object", "required": ["id"],
+//!         "properties": {"id": {"type": "integer", "minimum": 1}}}"#,
+//! )?;
+//! let mut parser = StreamingParser::new(ParserOptions::default());
+//! let issues: Vec<_> = validate_streaming(&schema, parser.feed(r#"{"id": 0}"#))
+//!     .filter_map(|event| match event {
+//!         Err(ValidationError::Invalid(issue)) => Some(issue.constraint),
+//!         _ => None,
+//!     })
+//!     .collect();
+//! assert_eq!(issues, ["minimum"]);
+//! # Ok::<(), jsonmodem::SchemaError>(())
+//! ```
+use alloc::{
+    borrow::ToOwned,
+    boxed::Box,
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt;
+
+use regex::Regex;
+
+use crate::{
+    ParseEvent, ParserError, ParserOptions, PathComponent, StreamingParser, Value,
+    graphql::ValueBuilder,
+};
+
+/// Error returned by [`compile_schema`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaError {
+    /// The schema is not valid JSON.
+    Parser(ParserError),
+    /// The schema is not a valid JSON Schema.
+    Invalid(String),
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::Parser(err) => write!(f, "invalid schema: {err}"),
+            SchemaError::Invalid(msg) => write!(f, "invalid schema: {msg}"),
+        }
+    }
+}
+
+impl core::error::Error for SchemaError {}
+
+impl From<ParserError> for SchemaError {
+    fn from(err: ParserError) -> Self {
+        SchemaError::Parser(err)
+    }
+}
+
+/// A value that breaks a constraint of the schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationIssue {
+    /// The path of the value.
+    pub path: Vec<PathComponent>,
+    /// The keyword of the broken constraint, e.g. `"minLength"`, or `"false"`
+    /// where the schema allows no value.
+    pub constraint: &'static str,
+    /// A description of the problem.
+    pub description: String,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {:?}", self.description, self.path)
+    }
+}
+
+/// Error yielded by [`ValidatingEvents`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    /// The document is not valid JSON.
+    Parser(ParserError),
+    /// A value breaks a constraint of the schema.
+    Invalid(ValidationIssue),
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::Parser(err) => err.fmt(f),
+            ValidationError::Invalid(issue) => issue.fmt(f),
+        }
+    }
+}
+
+impl core::error::Error for ValidationError {}
+
+impl From<ParserError> for ValidationError {
+    fn from(err: ParserError) -> Self {
+        ValidationError::Parser(err)
+    }
+}
+
+/// Bits of [`JsonSchema::types`].
+const NULL: u8 = 1;
+const BOOLEAN: u8 = 1 << 1;
+const OBJECT: u8 = 1 << 2;
+const ARRAY: u8 = 1 << 3;
+const NUMBER: u8 = 1 << 4;
+const STRING: u8 = 1 << 5;
+const INTEGER: u8 = 1 << 6;
+
+const TYPE_NAMES: [(&str, u8); 7] = [
+    ("null", NULL),
+    ("boolean", BOOLEAN),
+    ("object", OBJECT),
+    ("array", ARRAY),
+    ("number", NUMBER),
+    ("string", STRING),
+    ("integer", INTEGER),
+];
+
+/// A compiled JSON Schema.
+///
+/// See the [module documentation](self) for the keywords supported.
+#[derive(Debug, Clone)]
+pub struct JsonSchema {
+    /// `false` for the schema `false`, which accepts no value.
+    accept: bool,
+    /// The types allowed, or all if `None`.
+    types: Option<u8>,
+    properties: BTreeMap<String, JsonSchema>,
+    required: Vec<String>,
+    items: Option<Box<JsonSchema>>,
+    enum_values: Option<Vec<Value>>,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    minimum: Option<f64>,
+    maximum: Option<f64>,
+    pattern: Option<Regex>,
+}
+
+impl JsonSchema {
+    const TRUE: JsonSchema = JsonSchema {
+        accept: true,
+        types: None,
+        properties: BTreeMap::new(),
+        required: Vec::new(),
+        items: None,
+        enum_values: None,
+        min_length: None,
+        max_length: None,
+        minimum: None,
+        maximum: None,
+        pattern: None,
+    };
+
+    /// Returns the schema of the child of a value at `component`, or `None`
+    /// if the child may be anything.
+    fn child(&self, component: &PathComponent) -> Option<&JsonSchema> {
+        match component {
+            PathComponent::Key(key) => self.properties.get(&**key),
+            PathComponent::Index(_) => self.items.as_deref(),
+        }
+    }
+
+    /// Whether the value of strings must be kept to check them.
+    fn needs_text(&self) -> bool {
+        self.pattern.is_some() || self.enum_values.is_some()
+    }
+}
+
+/// Compiles a JSON Schema from its JSON form.
+///
+/// # Errors
+///
+/// Returns an error if `json` is not valid JSON, or if a supported keyword
+/// has a value the specification does not allow, such as a negative
+/// `minLength`, an unknown type name or a pattern that does not compile.
+pub fn compile_schema(json: &str) -> Result<JsonSchema, SchemaError> {
+    let value = StreamingParser::new(ParserOptions::default()).into_value(json)?;
+    compile(&value, "#")
+}
+
+fn invalid(location: &str, msg: &str) -> SchemaError {
+    SchemaError::Invalid(format!("{location}: {msg}"))
+}
+
+fn compile(value: &Value, location: &str) -> Result<JsonSchema, SchemaError> {
+    let object = match value {
+        Value::Boolean(accept) => {
+            return Ok(JsonSchema {
+                accept: *accept,
+                ..JsonSchema::TRUE
+            });
+        }
+        Value::Object(object) => object,
+        _ => return Err(invalid(location, "a schema must be an object or a boolean")),
+    };
+    let mut schema = JsonSchema::TRUE;
+    for (keyword, value) in object {
+        let location = format!("{location}/{keyword}");
+        let location = location.as_str();
+        match &**keyword {
+            "type" => schema.types = Some(compile_types(value, location)?),
+            "properties" => {
+                let Value::Object(properties) = value else {
+                    return Err(invalid(location, "expected an object"));
+                };
+                for (name, value) in properties {
+                    let location = format!("{location}/{name}");
+                    schema
+                        .properties
+                        .insert(name.to_string(), compile(value, &location)?);
+                }
+            }
+            "required" => {
+                let Value::Array(names) = value else {
+                    return Err(invalid(location, "expected an array"));
+                };
+                for name in names {
+                    let Value::String(name) = name else {
+                        return Err(invalid(location, "expected an array of strings"));
+                    };
+                    if schema.required.contains(name) {
+                        return Err(invalid(location, "names must be unique"));
+                    }
+                    schema.required.push(name.clone());
+                }
+            }
+            "items" => {
+                if matches!(value, Value::Array(_)) {
+                    return Err(invalid(
+                        location,
+                        "the array form of `items` is `prefixItems` since draft 2020-12",
+                    ));
+                }
+                schema.items = Some(Box::new(compile(value, location)?));
+            }
+            "enum" => {
+                let Value::Array(values) = value else {
+                    return Err(invalid(location, "expected an array"));
+                };
+                schema.enum_values = Some(values.clone());
+            }
+            "minLength" => schema.min_length = Some(compile_length(value, location)?),
+            "maxLength" => schema.max_length = Some(compile_length(value, location)?),
+            "minimum" | "maximum" => {
+                let Value::Number(n) = value else {
+                    return Err(invalid(location, "expected a number"));
+                };
+                if &**keyword == "minimum" {
+                    schema.minimum = Some(*n);
+                } else {
+                    schema.maximum = Some(*n);
+                }
+            }
+            "pattern" => {
+                let Value::String(pattern) = value else {
+                    return Err(invalid(location, "expected a string"));
+                };
+                let regex =
+                    Regex::new(pattern).map_err(|err| invalid(location, &err.to_string()))?;
+                schema.pattern = Some(regex);
+            }
+            _ => {}
+        }
+    }
+    Ok(schema)
+}
+
+fn compile_types(value: &Value, location: &str) -> Result<u8, SchemaError> {
+    let type_bit = |name: &Value| match name {
+        Value::String(name) => TYPE_NAMES
+            .iter()
+            .find(|(type_name, _)| type_name == name)
+            .map(|&(_, bit)| bit)
+            .ok_or_else(|| invalid(location, &format!("unknown type {name:?}"))),
+        _ => Err(invalid(
+            location,
+            "expected a string or an array of strings",
+        )),
+    };
+    match value {
+        Value::Array(names) => names.iter().try_fold(0, |types, name| {
+            let bit = type_bit(name)?;
+            if types & bit == 0 {
+                Ok(types | bit)
+            } else {
+                Err(invalid(location, "type names must be unique"))
+            }
+        }),
+        name => type_bit(name),
+    }
+}
+
+#[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn compile_length(value: &Value, location: &str) -> Result<usize, SchemaError> {
+    match value {
+        Value::Number(n) if *n >= 0.0 && n % 1.0 == 0.0 => Ok(*n as usize),
+        _ => Err(invalid(location, "expected a non-negative integer")),
+    }
+}
+
+/// Checks `events` against `schema`, passing them through.
+///
+/// Each event is yielded as it arrives, followed by the issues it reveals;
+/// see [`SchemaValidator::check`]. Validation goes on after an issue, so a
+/// document may have several. Every root value of a stream is checked
+/// against the schema.
+///
+/// The validation state lives in the returned iterator, so `events` should
+/// cover whole values. To check events as chunks are fed to a parser, use a
+/// [`SchemaValidator`].
+pub fn validate_streaming<I>(schema: &JsonSchema, events: I) -> ValidatingEvents<'_, I>
+where
+    I: Iterator<Item = Result<ParseEvent, ParserError>>,
+{
+    ValidatingEvents {
+        events,
+        validator: SchemaValidator::new(schema),
+        issues: Vec::new().into_iter(),
+    }
+}
+
+/// Iterator returned by [`validate_streaming`].
+#[derive(Debug)]
+pub struct ValidatingEvents<'s, I> {
+    events: I,
+    validator: SchemaValidator<'s>,
+    issues: vec::IntoIter<ValidationIssue>,
+}
+
+impl<I: Iterator<Item = Result<ParseEvent, ParserError>>> Iterator for ValidatingEvents<'_, I> {
+    type Item = Result<ParseEvent, ValidationError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(issue) = self.issues.next() {
+            return Some(Err(ValidationError::Invalid(issue)));
+        }
+        Some(match self.events.next()? {
+            Ok(event) => {
+                self.issues = self.validator.check(&event).into_iter();
+                Ok(event)
+            }
+            Err(err) => Err(err.into()),
+        })
+    }
+}
+
+/// An open array or object.
+#[derive(Debug)]
+struct Frame<'s> {
+    /// The schema of the container, or `None` if it may be anything.
+    schema: Option<&'s JsonSchema>,
+    /// For an object, whether each of its schema's required properties has
+    /// been seen.
+    seen: Vec<bool>,
+    /// The value of the container, if its schema has an `enum`.
+    value: Option<ValueBuilder>,
+}
+
+/// A string whose fragments are arriving.
+#[derive(Debug)]
+struct StringState<'s> {
+    schema: &'s JsonSchema,
+    chars: usize,
+    /// The value so far, if the schema needs it.
+    text: Option<String>,
+}
+
+/// Checks events against a schema one at a time.
+///
+/// # Examples
+///
+/// ```
+/// use jsonmodem::{ParserOptions, SchemaValidator, StreamingParser, compile_schema};
+///
+/// let schema = compile_schema(r#"{"items": {"maxLength": 3}}"#)?;
+/// let mut validator = SchemaValidator::new(&schema);
+/// let mut parser = StreamingParser::new(ParserOptions::default());
+/// let mut issues = Vec::new();
+/// for chunk in [r#"["ab", "ab"#, r#"cd"]"#] {
+///     for event in parser.feed(chunk) {
+///         issues.extend(validator.check(&event?));
+///     }
+/// }
+/// assert_eq!(issues[0].constraint, "maxLength");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct SchemaValidator<'s> {
+    root: &'s JsonSchema,
+    stack: Vec<Frame<'s>>,
+    string: Option<StringState<'s>>,
+}
+
+impl<'s> SchemaValidator<'s> {
+    /// Creates a validator checking each root value against `schema`.
+    #[must_use]
+    pub fn new(schema: &'s JsonSchema) -> Self {
+        Self {
+            root: schema,
+            stack: Vec::new(),
+            string: None,
+        }
+    }
+
+    /// Checks the next event, returning the issues it reveals.
+    ///
+    /// An issue with a scalar is returned with its event, or with the last
+    /// fragment of a string. A missing required property, or an array or
+    /// object outside its `enum`, is returned with the event ending the
+    /// container.
+    pub fn check(&mut self, event: &ParseEvent) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        self.check_into(event, &mut issues);
+        issues
+    }
+
+    fn check_into(&mut self, event: &ParseEvent, issues: &mut Vec<ValidationIssue>) {
+        let path = event.path();
+        let closed = match event {
+            ParseEvent::ArrayEnd { .. } | ParseEvent::ObjectEnd { .. } => self.stack.pop(),
+            _ => None,
+        };
+        // The frame of the container at depth `i` is `stack[i]`.
+        for (depth, frame) in self.stack.iter_mut().enumerate() {
+            if let Some(value) = &mut frame.value {
+                value.push(event.clone(), depth);
+            }
+        }
+        let issue = |constraint, description| ValidationIssue {
+            path: path.to_vec(),
+            constraint,
+            description,
+        };
+
+        if let Some(frame) = closed {
+            end_container(frame, event, issues, issue);
+            return;
+        }
+
+        if let ParseEvent::String { .. } = event
+            && self.string.is_some()
+        {
+            self.check_string(event, issues, issue);
+            return;
+        }
+        let (kind, value) = match event {
+            ParseEvent::Null { .. } => (NULL, Some(Value::Null)),
+            ParseEvent::Boolean { value, .. } => (BOOLEAN, Some(Value::Boolean(*value))),
+            ParseEvent::Number { value, .. } => (NUMBER, Some(Value::Number(*value))),
+            ParseEvent::String { .. } | ParseEvent::StringComplete { .. } => (STRING, None),
+            ParseEvent::ArrayStart { .. } => (ARRAY, None),
+            ParseEvent::ObjectBegin { .. } => (OBJECT, None),
+            _ => return,
+        };
+
+        let schema = self.begin_value(path);
+        if kind == ARRAY || kind == OBJECT {
+            let seen = match schema {
+                Some(schema) if kind == OBJECT => vec![false; schema.required.len()],
+                _ => Vec::new(),
+            };
+            let value = schema
+                .filter(|schema| schema.enum_values.is_some())
+                .map(|_| {
+                    let mut builder = ValueBuilder::default();
+                    builder.push(event.clone(), path.len());
+                    builder
+                });
+            self.stack.push(Frame {
+                schema: schema.filter(|schema| schema.accept),
+                seen,
+                value,
+            });
+        }
+        let Some(schema) = schema else {
+            return;
+        };
+        if !schema.accept {
+            issues.push(issue("false", "no value is allowed here".into()));
+            return;
+        }
+        if let Some(types) = schema.types {
+            let matches = match value {
+                Some(Value::Number(n)) => {
+                    types & NUMBER != 0 || (types & INTEGER != 0 && n % 1.0 == 0.0)
+                }
+                _ => types & kind != 0,
+            };
+            if !matches {
+                issues.push(issue("type", format!("expected {}", type_list(types))));
+            }
+        }
+        match value {
+            Some(value) => {
+                if let Value::Number(n) = value {
+                    check_number(schema, n, issues, issue);
+                }
+                check_enum(schema, &value, issues, issue);
+            }
+            None if kind == STRING => {
+                self.string = Some(StringState {
+                    schema,
+                    chars: 0,
+                    text: schema.needs_text().then(String::new),
+                });
+                self.check_string(event, issues, issue);
+            }
+            None => {}
+        }
+    }
+
+    /// Records the start of a value at `path` in its parent, returning the
+    /// value's schema.
+    fn begin_value(&mut self, path: &[PathComponent]) -> Option<&'s JsonSchema> {
+        let Some((component, _)) = path.split_last() else {
+            return Some(self.root);
+        };
+        let parent = self.stack.last_mut()?;
+        let schema = parent.schema?;
+        if let PathComponent::Key(key) = component {
+            if let Some(index) = schema.required.iter().position(|name| **name == **key) {
+                parent.seen[index] = true;
+            }
+        }
+        schema.child(component)
+    }
+
+    /// Adds a fragment of the current string, checking the string once it is
+    /// complete.
+    fn check_string(
+        &mut self,
+        event: &ParseEvent,
+        issues: &mut Vec<ValidationIssue>,
+        issue: impl Fn(&'static str, String) -> ValidationIssue,
+    ) {
+        let (fragment, is_final) = match event {
+            ParseEvent::String {
+                fragment, is_final, ..
+            } => (fragment, *is_final),
+            ParseEvent::StringComplete { value, .. } => (value, true),
+            _ => return,
+        };
+        let Some(string) = &mut self.string else {
+            return;
+        };
+        string.chars += fragment.chars().count();
+        if let Some(text) = &mut string.text {
+            text.push_str(fragment);
+        }
+        if !is_final {
+            return;
+        }
+        let StringState {
+            schema,
+            chars,
+            text,
+        } = self.string.take().unwrap();
+        if let Some(min) = schema.min_length.filter(|&min| chars < min) {
+            issues.push(issue(
+                "minLength",
+                format!("string has {chars} characters, fewer than {min}"),
+            ));
+        }
+        if let Some(max) = schema.max_length.filter(|&max| chars > max) {
+            issues.push(issue(
+                "maxLength",
+                format!("string has {chars} characters, more than {max}"),
+            ));
+        }
+        let Some(text) = text else {
+            return;
+        };
+        if let Some(pattern) = schema
+            .pattern
+            .as_ref()
+            .filter(|pattern| !pattern.is_match(&text))
+        {
+            issues.push(issue(
+                "pattern",
+                format!("string does not match {:?}", pattern.as_str()),
+            ));
+        }
+        check_enum(schema, &Value::String(text), issues, issue);
+    }
+}
+
+/// Checks an array or object once its last event has arrived.
+fn end_container(
+    frame: Frame<'_>,
+    event: &ParseEvent,
+    issues: &mut Vec<ValidationIssue>,
+    issue: impl Fn(&'static str, String) -> ValidationIssue,
+) {
+    let Some(schema) = frame.schema else {
+        return;
+    };
+    for (name, _) in schema
+        .required
+        .iter()
+        .zip(&frame.seen)
+        .filter(|(_, seen)| !**seen)
+    {
+        issues.push(issue(
+            "required",
+            format!("missing required property {name:?}"),
+        ));
+    }
+    if let Some(value) = frame
+        .value
+        .and_then(|mut builder| builder.push(event.clone(), event.path().len()))
+    {
+        check_enum(schema, &value, issues, issue);
+    }
+}
+
+fn check_number(
+    schema: &JsonSchema,
+    n: f64,
+    issues: &mut Vec<ValidationIssue>,
+    issue: impl Fn(&'static str, String) -> ValidationIssue,
+) {
+    if let Some(minimum) = schema.minimum.filter(|&minimum| n < minimum) {
+        issues.push(issue(
+            "minimum",
+            format!("{n} is less than the minimum {minimum}"),
+        ));
+    }
+    if let Some(maximum) = schema.maximum.filter(|&maximum| n > maximum) {
+        issues.push(issue(
+            "maximum",
+            format!("{n} is greater than the maximum {maximum}"),
+        ));
+    }
+}
+
+fn check_enum(
+    schema: &JsonSchema,
+    value: &Value,
+    issues: &mut Vec<ValidationIssue>,
+    issue: impl Fn(&'static str, String) -> ValidationIssue,
+) {
+    if schema
+        .enum_values
+        .as_ref()
+        .is_some_and(|values| !values.contains(value))
+    {
+        issues.push(issue(
+            "enum",
+            "value is not one of the enum values".to_owned(),
+        ));
+    }
+}
+
+/// Returns the names of the types in `types`, e.g. `string or null`.
+fn type_list(types: u8) -> String {
+    let names: Vec<&str> = TYPE_NAMES
+        .iter()
+        .filter(|(_, bit)| types & bit != 0)
+        .map(|(name, _)| *name)
+        .collect();
+    names.join(" or ")
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec::Vec};
+
+    use super::{
+        SchemaError, SchemaValidator, ValidationError, ValidationIssue, compile_schema,
+        validate_streaming,
+    };
+    use crate::{ParserOptions, StreamingParser, Value, path};
+
+    /// Cases from the JSON Schema Test Suite (draft 2020-12) for the
+    /// supported keywords, in the suite's format.
+    const SUITE: &str = r#"[
+        {"description": "integer type matches integers", "schema": {"type": "integer"}, "tests": [
+            {"description": "an integer is an integer", "data": 1, "valid": true},
+            {"description": "a float with zero fractional part is an integer", "data": 1.0, "valid": true},
+            {"description": "a float is not an integer", "data": 1.1, "valid": false},
+            {"description": "a string is not an integer", "data": "foo", "valid": false},
+            {"description": "a string is still not an integer, even if it looks like one", "data": "1", "valid": false},
+            {"description": "an object is not an integer", "data": {}, "valid": false},
+            {"description": "an array is not an integer", "data": [], "valid": false},
+            {"description": "a boolean is not an integer", "data": true, "valid": false},
+            {"description": "null is not an integer", "data": null, "valid": false}
+        ]},
+        {"description": "number type matches numbers", "schema": {"type": "number"}, "tests": [
+            {"description": "an integer is a number", "data": 1, "valid": true},
+            {"description": "a float is a number", "data": 1.1, "valid": true},
+            {"description": "a string is not a number", "data": "foo", "valid": false},
+            {"description": "an object is not a number", "data": {}, "valid": false},
+            {"description": "null is not a number", "data": null, "valid": false}
+        ]},
+        {"description": "string type matches strings", "schema": {"type": "string"}, "tests": [
+            {"description": "1 is not a string", "data": 1, "valid": false},
+            {"description": "a string is a string", "data": "foo", "valid": true},
+            {"description": "an empty string is still a string", "data": "", "valid": true},
+            {"description": "an array is not a string", "data": [], "valid": false}
+        ]},
+        {"description": "object type matches objects", "schema": {"type": "object"}, "tests": [
+            {"description": "an object is an object", "data": {}, "valid": true},
+            {"description": "an array is not an object", "data": [], "valid": false},
+            {"description": "a string is not an object", "data": "foo", "valid": false}
+        ]},
+        {"description": "array type matches arrays", "schema": {"type": "array"}, "tests": [
+            {"description": "an array is an array", "data": [], "valid": true},
+            {"description": "an object is not an array", "data": {}, "valid": false},
+            {"description": "null is not an array", "data": null, "valid": false}
+        ]},
+        {"description": "boolean type matches booleans", "schema": {"type": "boolean"}, "tests": [
+            {"description": "zero is not a boolean", "data": 0, "valid": false},
+            {"description": "an empty string is not a boolean", "data": "", "valid": false},
+            {"description": "false is a boolean", "data": false, "valid": true},
+            {"description": "true is a boolean", "data": true, "valid": true},
+            {"description": "null is not a boolean", "data": null, "valid": false}
+        ]},
+        {"description": "null type matches only the null object", "schema": {"type": "null"}, "tests": [
+            {"description": "zero is not null", "data": 0, "valid": false},
+            {"description": "false is not null", "data": false, "valid": false},
+            {"description": "null is null", "data": null, "valid": true}
+        ]},
+        {"description": "multiple types can be validated", "schema": {"type": ["integer", "string"]}, "tests": [
+            {"description": "an integer is valid", "data": 1, "valid": true},
+            {"description": "a string is valid", "data": "foo", "valid": true},
+            {"description": "a float is invalid", "data": 1.1, "valid": false},
+            {"description": "an object is invalid", "data": {}, "valid": false},
+            {"description": "null is invalid", "data": null, "valid": false}
+        ]},
+        {"description": "type: array or object", "schema": {"type": ["array", "object"]}, "tests": [
+            {"description": "array is valid", "data": [1, 2, 3], "valid": true},
+            {"description": "object is valid", "data": {"foo": 123}, "valid": true},
+            {"description": "number is invalid", "data": 123, "valid": false},
+            {"description": "string is invalid", "data": "foo", "valid": false}
+        ]},
+        {"description": "object properties validation",
+         "schema": {"properties": {"foo": {"type": "integer"}, "bar": {"type": "string"}}}, "tests": [
+            {"description": "both properties present and valid is valid", "data": {"foo": 1, "bar": "baz"}, "valid": true},
+            {"description": "one property invalid is invalid", "data": {"foo": 1, "bar": {}}, "valid": false},
+            {"description": "both properties invalid is invalid", "data": {"foo": [], "bar": {}}, "valid": false},
+            {"description": "doesn't invalidate other properties", "data": {"quux": []}, "valid": true},
+            {"description": "ignores arrays", "data": [], "valid": true},
+            {"description": "ignores other non-objects", "data": 12, "valid": true}
+        ]},
+        {"description": "properties with boolean schema", "schema": {"properties": {"foo": true, "bar": false}}, "tests": [
+            {"description": "no property present is valid", "data": {}, "valid": true},
+            {"description": "only 'true' property present is valid", "data": {"foo": 1}, "valid": true},
+            {"description": "only 'false' property present is invalid", "data": {"bar": 2}, "valid": false},
+            {"description": "both properties present is invalid", "data": {"foo": 1, "bar": 2}, "valid": false}
+        ]},
+        {"description": "properties with escaped characters",
+         "schema": {"properties": {"foo\nbar": {"type": "number"}, "foo\"bar": {"type": "number"}, "foo\tbar": {"type": "number"}}}, "tests": [
+            {"description": "object with all numbers is valid", "data": {"foo\nbar": 1, "foo\"bar": 1, "foo\tbar": 1}, "valid": true},
+            {"description": "object with strings is invalid", "data": {"foo\nbar": "1", "foo\"bar": "1", "foo\tbar": "1"}, "valid": false}
+        ]},
+        {"description": "required validation", "schema": {"properties": {"foo": {}, "bar": {}}, "required": ["foo"]}, "tests": [
+            {"description": "present required property is valid", "data": {"foo": 1}, "valid": true},
+            {"description": "non-present required property is invalid", "data": {"bar": 1}, "valid": false},
+            {"description": "ignores arrays", "data": [], "valid": true},
+            {"description": "ignores strings", "data": "", "valid": true},
+            {"description": "ignores other non-objects", "data": 12, "valid": true}
+        ]},
+        {"description": "required with empty array", "schema": {"properties": {"foo": {}}, "required": []}, "tests": [
+            {"description": "property not required", "data": {}, "valid": true}
+        ]},
+        {"description": "required with escaped characters", "schema": {"required": ["foo\nbar", "foo\"bar"]}, "tests": [
+            {"description": "object with all properties present is valid", "data": {"foo\nbar": 1, "foo\"bar": 1}, "valid": true},
+            {"description": "object with some properties missing is invalid", "data": {"foo\nbar": "1"}, "valid": false}
+        ]},
+        {"description": "required properties whose names are Javascript object property names",
+         "schema": {"required": ["__proto__", "toString", "constructor"]}, "tests": [
+            {"description": "ignores arrays", "data": [], "valid": true},
+            {"description": "none of the properties mentioned", "data": {}, "valid": false},
+            {"description": "__proto__ present", "data": {"__proto__": "foo"}, "valid": false},
+            {"description": "all present", "data": {"__proto__": 12, "toString": {"length": "foo"}, "constructor": 37}, "valid": true}
+        ]},
+        {"description": "a schema given for items", "schema": {"items": {"type": "integer"}}, "tests": [
+            {"description": "valid items", "data": [1, 2, 3], "valid": true},
+            {"description": "wrong type of items", "data": [1, "x"], "valid": false},
+            {"description": "ignores non-arrays", "data": {"foo": "bar"}, "valid": true},
+            {"description": "JavaScript pseudo-array is valid", "data": {"0": "invalid", "length": 1}, "valid": true}
+        ]},
+        {"description": "items with boolean schema (true)", "schema": {"items": true}, "tests": [
+            {"description": "any array is valid", "data": [1, "foo", true], "valid": true},
+            {"description": "empty array is valid", "data": [], "valid": true}
+        ]},
+        {"description": "items with boolean schema (false)", "schema": {"items": false}, "tests": [
+            {"description": "any non-empty array is invalid", "data": [1, "foo", true], "valid": false},
+            {"description": "empty array is valid", "data": [], "valid": true}
+        ]},
+        {"description": "nested items", "schema": {"type": "array", "items": {"type": "array", "items":
+            {"type": "array", "items": {"type": "array", "items": {"type": "number"}}}}}, "tests": [
+            {"description": "valid nested array", "data": [[[[1]], [[2], [3]]], [[[4], [5], [6]]]], "valid": true},
+            {"description": "nested array with invalid type", "data": [[[["1"]], [[2], [3]]], [[[4], [5], [6]]]], "valid": false},
+            {"description": "not deep enough", "data": [[[1], [2], [3]], [[4], [5], [6]]], "valid": false}
+        ]},
+        {"description": "items with null instance elements", "schema": {"items": {"type": "null"}}, "tests": [
+            {"description": "allows null elements", "data": [null], "valid": true}
+        ]},
+        {"description": "simple enum validation", "schema": {"enum": [1, 2, 3]}, "tests": [
+            {"description": "one of the enum is valid", "data": 1, "valid": true},
+            {"description": "something else is invalid", "data": 4, "valid": false}
+        ]},
+        {"description": "heterogeneous enum validation", "schema": {"enum": [6, "foo", [], true, {"foo": 12}]}, "tests": [
+            {"description": "one of the enum is valid", "data": [], "valid": true},
+            {"description": "something else is invalid", "data": null, "valid": false},
+            {"description": "objects are deep compared", "data": {"foo": false}, "valid": false},
+            {"description": "valid object matches", "data": {"foo": 12}, "valid": true},
+            {"description": "extra properties in object is invalid", "data": {"foo": 12, "boo": 42}, "valid": false}
+        ]},
+        {"description": "heterogeneous enum-with-null validation", "schema": {"enum": [6, null]}, "tests": [
+            {"description": "null is valid", "data": null, "valid": true},
+            {"description": "number is valid", "data": 6, "valid": true},
+            {"description": "something else is invalid", "data": "test", "valid": false}
+        ]},
+        {"description": "enums in properties", "schema": {"type": "object",
+            "properties": {"foo": {"enum": ["foo"]}, "bar": {"enum": ["bar"]}}, "required": ["bar"]}, "tests": [
+            {"description": "both properties are valid", "data": {"foo": "foo", "bar": "bar"}, "valid": true},
+            {"description": "wrong foo value", "data": {"foo": "foot", "bar": "bar"}, "valid": false},
+            {"description": "wrong bar value", "data": {"foo": "foo", "bar": "bart"}, "valid": false},
+            {"description": "missing optional property is valid", "data": {"bar": "bar"}, "valid": true},
+            {"description": "missing required property is invalid", "data": {"foo": "foo"}, "valid": false},
+            {"description": "missing all properties is invalid", "data": {}, "valid": false}
+        ]},
+        {"description": "enum with escaped characters", "schema": {"enum": ["foo\nbar", "foo\rbar"]}, "tests": [
+            {"description": "member 1 is valid", "data": "foo\nbar", "valid": true},
+            {"description": "member 2 is valid", "data": "foo\rbar", "valid": true},
+            {"description": "another string is invalid", "data": "abc", "valid": false}
+        ]},
+        {"description": "enum with false does not match 0", "schema": {"enum": [false]}, "tests": [
+            {"description": "false is valid", "data": false, "valid": true},
+            {"description": "integer zero is invalid", "data": 0, "valid": false},
+            {"description": "float zero is invalid", "data": 0.0, "valid": false}
+        ]},
+        {"description": "enum with 0 does not match false", "schema": {"enum": [0]}, "tests": [
+            {"description": "false is invalid", "data": false, "valid": false},
+            {"description": "integer zero is valid", "data": 0, "valid": true},
+            {"description": "float zero is valid", "data": 0.0, "valid": true}
+        ]},
+        {"description": "enum with [false] does not match [0]", "schema": {"enum": [[false]]}, "tests": [
+            {"description": "[false] is valid", "data": [false], "valid": true},
+            {"description": "[0] is invalid", "data": [0], "valid": false},
+            {"description": "[0.0] is invalid", "data": [0.0], "valid": false}
+        ]},
+        {"description": "nul characters in strings", "schema": {"enum": ["hello\u0000there"]}, "tests": [
+            {"description": "match string with nul", "data": "hello\u0000there", "valid": true},
+            {"description": "do not match string lacking nul", "data": "hellothere", "valid": false}
+        ]},
+        {"description": "minLength validation", "schema": {"minLength": 2}, "tests": [
+            {"description": "longer is valid", "data": "foo", "valid": true},
+            {"description": "exact length is valid", "data": "fo", "valid": true},
+            {"description": "too short is invalid", "data": "f", "valid": false},
+            {"description": "ignores non-strings", "data": 1, "valid": true},
+            {"description": "one grapheme is not long enough", "data": "💩", "valid": false}
+        ]},
+        {"description": "minLength validation with a decimal", "schema": {"minLength": 2.0}, "tests": [
+            {"description": "longer is valid", "data": "foo", "valid": true},
+            {"description": "too short is invalid", "data": "f", "valid": false}
+        ]},
+        {"description": "maxLength validation", "schema": {"maxLength": 2}, "tests": [
+            {"description": "shorter is valid", "data": "f", "valid": true},
+            {"description": "exact length is valid", "data": "fo", "valid": true},
+            {"description": "too long is invalid", "data": "foo", "valid": false},
+            {"description": "ignores non-strings", "data": 100, "valid": true},
+            {"description": "two graphemes is long enough", "data": "💩💩", "valid": true}
+        ]},
+        {"description": "minimum validation", "schema": {"minimum": 1.1}, "tests": [
+            {"description": "above the minimum is valid", "data": 2.6, "valid": true},
+            {"description": "boundary point is valid", "data": 1.1, "valid": true},
+            {"description": "below the minimum is invalid", "data": 0.6, "valid": false},
+            {"description": "ignores non-numbers", "data": "x", "valid": true}
+        ]},
+        {"description": "minimum validation with signed integer", "schema": {"minimum": -2}, "tests": [
+            {"description": "negative above the minimum is valid", "data": -1, "valid": true},
+            {"description": "positive above the minimum is valid", "data": 0, "valid": true},
+            {"description": "boundary point is valid", "data": -2, "valid": true},
+            {"description": "boundary point with float is valid", "data": -2.0, "valid": true},
+            {"description": "float below the minimum is invalid", "data": -2.0001, "valid": false},
+            {"description": "int below the minimum is invalid", "data": -3, "valid": false}
+        ]},
+        {"description": "maximum validation", "schema": {"maximum": 3.0}, "tests": [
+            {"description": "below the maximum is valid", "data": 2.6, "valid": true},
+            {"description": "boundary point is valid", "data": 3.0, "valid": true},
+            {"description": "above the maximum is invalid", "data": 3.5, "valid": false},
+            {"description": "ignores non-numbers", "data": "x", "valid": true}
+        ]},
+        {"description": "maximum validation with unsigned integer", "schema": {"maximum": 300}, "tests": [
+            {"description": "below the maximum is invalid", "data": 299.97, "valid": true},
+            {"description": "boundary point integer is valid", "data": 300, "valid": true},
+            {"description": "boundary point float is valid", "data": 300.00, "valid": true},
+            {"description": "above the maximum is invalid", "data": 300.5, "valid": false}
+        ]},
+        {"description": "pattern validation", "schema": {"pattern": "^a*$"}, "tests": [
+            {"description": "a matching pattern is valid", "data": "aaa", "valid": true},
+            {"description": "a non-matching pattern is invalid", "data": "abc", "valid": false},
+            {"description": "ignores booleans", "data": true, "valid": true},
+            {"description": "ignores integers", "data": 123, "valid": true},
+            {"description": "ignores objects", "data": {}, "valid": true},
+            {"description": "ignores null", "data": null, "valid": true}
+        ]},
+        {"description": "pattern is not anchored", "schema": {"pattern": "a+"}, "tests": [
+            {"description": "matches a substring", "data": "xxaayy", "valid": true}
+        ]}
+    ]"#;
+
+    fn parse(json: &str) -> Value {
+        StreamingParser::new(ParserOptions::default())
+            .into_value(json)
+            .unwrap()
+    }
+
+    /// Validates `json`, fed in `chunks` chunks, returning the issues.
+    fn issues(schema: &str, json: &str, chunks: usize) -> Vec<ValidationIssue> {
+        let schema = compile_schema(schema).unwrap();
+        let mut validator = SchemaValidator::new(&schema);
+        let mut parser = StreamingParser::new(ParserOptions::default());
+        let mut issues = Vec::new();
+        for chunk in crate::produce_chunks(json, chunks) {
+            for event in parser.feed(chunk) {
+                issues.extend(validator.check(&event.unwrap()));
+            }
+        }
+        for event in parser.finish() {
+            issues.extend(validator.check(&event.unwrap()));
+        }
+        issues
+    }
+
+    #[test]
+    fn json_schema_test_suite() {
+        let Value::Array(groups) = parse(SUITE) else {
+            unreachable!()
+        };
+        for group in groups {
+            let Value::Object(group) = group else {
+                unreachable!()
+            };
+            let schema = group["schema"].to_string();
+            let Value::Array(tests) = &group["tests"] else {
+                unreachable!()
+            };
+            for test in tests {
+                let Value::Object(test) = test else {
+                    unreachable!()
+                };
+                let data = test["data"].to_string();
+                for chunks in [1, 3, data.len().max(1)] {
+                    let issues = issues(&schema, &data, chunks);
+                    assert_eq!(
+                        issues.is_empty(),
+                        test["valid"] == Value::Boolean(true),
+                        "{}: {}: {issues:?}",
+                        group["description"],
+                        test["description"],
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn issues_follow_their_values() {
+        let schema = r#"{
+            "type": "object",
+            "required": ["name", "id"],
+            "properties": {
+                "name": {"type": "string", "maxLength": 3, "pattern": "^[a-z]+$"},
+                "tags": {"items": {"enum": ["a", "b"]}}
+            }
+        }"#;
+        let json = r#"{"name": "Alice", "tags": ["a", "c", ["b"]]}"#;
+        for chunks in [1, 4, json.len()] {
+            let found: Vec<_> = issues(schema, json, chunks)
+                .into_iter()
+                .map(|issue| (issue.path, issue.constraint))
+                .collect();
+            assert_eq!(
+                found,
+                [
+                    (path!["name"], "maxLength"),
+                    (path!["name"], "pattern"),
+                    (path!["tags", 1], "enum"),
+                    (path!["tags", 2], "enum"),
+                    (Vec::new(), "required"),
+                ],
+                "{chunks}"
+            );
+        }
+        let issue = &issues(schema, "{}", 1)[0];
+        assert_eq!(issue.description, r#"missing required property "name""#);
+    }
+
+    #[test]
+    fn each_root_value_is_checked() {
+        let schema = compile_schema(r#"{"type": "integer"}"#).unwrap();
+        let mut parser = StreamingParser::new(ParserOptions {
+            allow_multiple_json_values: true,
+            ..ParserOptions::default()
+        });
+        let mut events: Vec<_> = validate_streaming(&schema, parser.feed("1 2.5 [3] 4")).collect();
+        events.extend(validate_streaming(&schema, parser.finish()));
+        let invalid: Vec<_> = events
+            .iter()
+            .filter_map(|event| match event {
+                Err(ValidationError::Invalid(issue)) => Some(issue.description.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(invalid, ["expected integer", "expected integer"]);
+        assert!(matches!(
+            validate_streaming(
+                &schema,
+                StreamingParser::new(ParserOptions::default()).feed("[1,]x")
+            )
+            .last(),
+            Some(Err(ValidationError::Parser(_)))
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_schemas() {
+        for (schema, message) in [
+            (r#"{"type": "float"}"#, r#"#/type: unknown type "float""#),
+            (
+                r#"{"minLength": -1}"#,
+                "#/minLength: expected a non-negative integer",
+            ),
+            (
+                r#"{"items": [{}]}"#,
+                "#/items: the array form of `items` is `prefixItems` since draft 2020-12",
+            ),
+            (
+                r#"{"properties": {"a": 1}}"#,
+                "#/properties/a: a schema must be an object or a boolean",
+            ),
+            (
+                r#"{"required": ["a", "a"]}"#,
+                "#/required: names must be unique",
+            ),
+        ] {
+            assert_eq!(
+                compile_schema(schema).unwrap_err(),
+                SchemaError::Invalid(message.into()),
+            );
+        }
+        assert!(matches!(
+            compile_schema(r#"{"pattern": "("}"#),
+            Err(SchemaError::Invalid(message)) if message.starts_with("#/pattern: ")
+        ));
+        assert!(matches!(compile_schema("{"), Err(SchemaError::Parser(_))));
+    }
+}
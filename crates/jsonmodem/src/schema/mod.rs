@@ -0,0 +1,16 @@
+//! Conversion of event streams to formats described by a schema, and
+//! inference of schemas from example events.
+#[cfg(any(test, feature = "avro"))]
+mod avro;
+#[cfg(any(test, feature = "json-schema"))]
+mod json_schema;
+mod typescript;
+
+#[cfg(any(test, feature = "avro"))]
+pub use avro::{AvroEncoder, AvroError, AvroField, AvroSchema};
+#[cfg(any(test, feature = "json-schema"))]
+pub use json_schema::{
+    JsonSchema, SchemaError, SchemaValidator, ValidatingEvents, ValidationError, ValidationIssue,
+    compile_schema, validate_streaming,
+};
+pub use typescript::infer_typescript_types;
@@ -0,0 +1,385 @@
+//! Inference of TypeScript types from example documents.
+//!
+//! [`infer_typescript_types`] reads the events of one or more example values,
+//! e.g. responses captured from an API, and writes TypeScript declarations
+//! that every example satisfies. The root is named `Root`, and every object
+//! becomes an interface named after the key it was found under.
+//!
+//! The type of a value is the union of the types of every example of it:
+//! `string`, `number`, `boolean` and `null` for scalars, `T[]` for arrays,
+//! where `T` is the union of the types of all their elements, and an interface
+//! for objects, whose members are those of every example. A member missing
+//! from some examples is optional.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{ParserOptions, StreamingParser, infer_typescript_types};
+//!
+//! let mut parser = StreamingParser::new(ParserOptions::default());
+//! let events: Vec<_> = parser
+//!     .feed(r#"{"id": 1, "tags": ["a"], "owner": {"name": "Ada", "email": null}}"#)
+//!     .collect::<Result<_, _>>()?;
+//! assert_eq!(
+//!     infer_typescript_types(&events),
+//!     "\
+//! interface Root {
+//!   id: number;
+//!   tags: string[];
+//!   owner: Owner;
+//! }
+//!
+//! interface Owner {
+//!   name: string;
+//!   email: null;
+//! }
+//! "
+//! );
+//! # Ok::<(), jsonmodem::ParserError>(())
+//! ```
+use alloc::{
+    boxed::Box,
+    collections::BTreeSet,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Write;
+
+use crate::{JsonValue, ParseEvent, PathComponent, value::escape_string};
+
+/// The union of the types of the examples of one value.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[allow(clippy::struct_excessive_bools)]
+struct Shape {
+    string: bool,
+    number: bool,
+    boolean: bool,
+    null: bool,
+    /// The union of the types of the elements of every array example.
+    array: Option<Box<Shape>>,
+    /// The members of every object example, in order of first appearance.
+    object: Option<Vec<Member>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Member {
+    key: String,
+    shape: Shape,
+    optional: bool,
+}
+
+impl Shape {
+    fn merge(&mut self, other: Shape) {
+        self.string |= other.string;
+        self.number |= other.number;
+        self.boolean |= other.boolean;
+        self.null |= other.null;
+        if let Some(items) = other.array {
+            match &mut self.array {
+                Some(mine) => mine.merge(*items),
+                None => self.array = Some(items),
+            }
+        }
+        if let Some(members) = other.object {
+            match &mut self.object {
+                Some(mine) => merge_members(mine, members),
+                None => self.object = Some(members),
+            }
+        }
+    }
+}
+
+/// Merges the members of another example of an object into `mine`. Members
+/// missing from either become optional.
+fn merge_members(mine: &mut Vec<Member>, theirs: Vec<Member>) {
+    for member in mine.iter_mut() {
+        if !theirs.iter().any(|other| other.key == member.key) {
+            member.optional = true;
+        }
+    }
+    for other in theirs {
+        match mine.iter_mut().find(|member| member.key == other.key) {
+            Some(member) => {
+                member.shape.merge(other.shape);
+                member.optional |= other.optional;
+            }
+            None => mine.push(Member {
+                optional: true,
+                ..other
+            }),
+        }
+    }
+}
+
+/// Adds an example of the value at `key` to an open object, or of an element
+/// to an open array.
+fn add(open: &mut Shape, key: Option<&PathComponent>, shape: Shape) {
+    match (key, &mut open.object) {
+        (Some(PathComponent::Key(key)), Some(members)) => {
+            let member = Member {
+                key: key.to_string(),
+                shape,
+                optional: false,
+            };
+            // A duplicate key adds another example to the same member.
+            match members
+                .iter_mut()
+                .find(|existing| existing.key == member.key)
+            {
+                Some(existing) => existing.shape.merge(member.shape),
+                None => members.push(member),
+            }
+        }
+        _ => open.array.get_or_insert_default().merge(shape),
+    }
+}
+
+/// Returns TypeScript declarations for the values described by `events`.
+///
+/// `events` may hold several root values, which are taken as examples of
+/// one type. A root object is declared as `interface Root`, and any other
+/// root as `type Root`. Incomplete values at the end of `events` are
+/// ignored.
+#[must_use]
+pub fn infer_typescript_types<V: JsonValue>(events: &[ParseEvent<V>]) -> String {
+    let mut root: Option<Shape> = None;
+    let mut open: Vec<Shape> = Vec::new();
+    for event in events {
+        let shape = match event {
+            ParseEvent::Null { .. } => Shape {
+                null: true,
+                ..Shape::default()
+            },
+            ParseEvent::Boolean { .. } => Shape {
+                boolean: true,
+                ..Shape::default()
+            },
+            ParseEvent::Number { .. } => Shape {
+                number: true,
+                ..Shape::default()
+            },
+            ParseEvent::String { is_final: true, .. } | ParseEvent::StringComplete { .. } => {
+                Shape {
+                    string: true,
+                    ..Shape::default()
+                }
+            }
+            ParseEvent::ArrayStart { .. } => {
+                open.push(Shape {
+                    array: Some(Box::default()),
+                    ..Shape::default()
+                });
+                continue;
+            }
+            ParseEvent::ObjectBegin { .. } => {
+                open.push(Shape {
+                    object: Some(Vec::new()),
+                    ..Shape::default()
+                });
+                continue;
+            }
+            ParseEvent::ArrayEnd { .. } | ParseEvent::ObjectEnd { .. } => {
+                let Some(shape) = open.pop() else {
+                    continue;
+                };
+                shape
+            }
+            _ => continue,
+        };
+        match open.last_mut() {
+            Some(parent) => add(parent, event.path().last(), shape),
+            None => root.get_or_insert_default().merge(shape),
+        }
+    }
+
+    let mut writer = Writer::default();
+    let Some(root) = root else {
+        return String::new();
+    };
+    let only_objects =
+        !(root.string || root.number || root.boolean || root.null || root.array.is_some());
+    match root.object {
+        Some(members) if only_objects => {
+            writer.declare("Root", members);
+        }
+        _ => {
+            writer.names.insert("Root".to_string());
+            let ty = writer.type_of(root, "Root");
+            writer
+                .declarations
+                .insert(0, format!("type Root = {ty};\n"));
+        }
+    }
+    writer.declarations.join("\n")
+}
+
+/// Writes declarations, giving every interface a distinct name.
+#[derive(Default)]
+struct Writer {
+    names: BTreeSet<String>,
+    declarations: Vec<String>,
+}
+
+impl Writer {
+    /// Declares an interface with `members`, named after `hint`, and returns
+    /// its name. It is written after the interfaces declared before it, and
+    /// before those of its members.
+    fn declare(&mut self, hint: &str, members: Vec<Member>) -> String {
+        let mut name = hint.to_string();
+        let mut n = 1;
+        while !self.names.insert(name.clone()) {
+            n += 1;
+            name = format!("{hint}{n}");
+        }
+        let slot = self.declarations.len();
+        self.declarations.push(String::new());
+        let mut body = format!("interface {name} {{\n");
+        for member in members {
+            let ty = self.type_of(member.shape, &type_name(&member.key));
+            let key = if is_identifier(&member.key) {
+                member.key
+            } else {
+                format!("\"{}\"", escape_string(&member.key))
+            };
+            let optional = if member.optional { "?" } else { "" };
+            let _ = writeln!(body, "  {key}{optional}: {ty};");
+        }
+        body.push_str("}\n");
+        self.declarations[slot] = body;
+        name
+    }
+
+    /// Returns the type of `shape`, declaring an interface named after `hint`
+    /// if it includes objects.
+    fn type_of(&mut self, shape: Shape, hint: &str) -> String {
+        let mut types = Vec::new();
+        if let Some(members) = shape.object {
+            types.push(self.declare(hint, members));
+        }
+        if let Some(items) = shape.array {
+            let item = self.type_of(*items, &format!("{hint}Item"));
+            if item.contains(' ') {
+                types.push(format!("({item})[]"));
+            } else {
+                types.push(format!("{item}[]"));
+            }
+        }
+        for (present, name) in [
+            (shape.string, "string"),
+            (shape.number, "number"),
+            (shape.boolean, "boolean"),
+            (shape.null, "null"),
+        ] {
+            if present {
+                types.push(name.to_string());
+            }
+        }
+        if types.is_empty() {
+            // The elements of arrays that were always empty.
+            return "unknown".to_string();
+        }
+        types.join(" | ")
+    }
+}
+
+/// Returns `true` if `key` can be written as a member name without quotes.
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Returns an interface name for the values of `key`, e.g. `LineItems` for
+/// `line_items`.
+fn type_name(key: &str) -> String {
+    let mut name = String::new();
+    for word in key.split(|c: char| !c.is_ascii_alphanumeric()) {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            name.push(first.to_ascii_uppercase());
+            name.extend(chars);
+        }
+    }
+    match name.chars().next() {
+        None => "Value".to_string(),
+        Some(c) if c.is_ascii_digit() => format!("T{name}"),
+        Some(_) => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::infer_typescript_types;
+    use crate::{ParserOptions, tests::events};
+
+    fn infer(json: &str, parts: usize) -> alloc::string::String {
+        let options = ParserOptions {
+            allow_multiple_json_values: true,
+            ..Default::default()
+        };
+        infer_typescript_types(&events(json, parts, options))
+    }
+
+    #[test]
+    fn nested_objects_unions_and_optional_members() {
+        let json = r#"
+            {"id": 1, "user": {"name": "Ada", "email": null}, "line_items": [
+                {"sku": "a", "qty": 2, "tags": []},
+                {"sku": "b", "qty": "many", "discount": {"pct": 5}, "tags": ["x", 1]}
+            ], "content-type": "json", "meta": {}}
+            {"id": 2, "user": null, "line_items": [], "content-type": "xml", "meta": {"user": {}}}
+        "#;
+        let expected = r#"interface Root {
+  id: number;
+  user: User | null;
+  line_items: LineItemsItem[];
+  "content-type": string;
+  meta: Meta;
+}
+
+interface User {
+  name: string;
+  email: null;
+}
+
+interface LineItemsItem {
+  sku: string;
+  qty: string | number;
+  tags: (string | number)[];
+  discount?: Discount;
+}
+
+interface Discount {
+  pct: number;
+}
+
+interface Meta {
+  user?: User2;
+}
+
+interface User2 {
+}
+"#;
+        for parts in [1, 7, json.len()] {
+            assert_eq!(infer(json, parts), expected);
+        }
+    }
+
+    #[test]
+    fn other_roots() {
+        assert_eq!(
+            infer("1 \"a\" null", 1),
+            "type Root = string | number | null;\n"
+        );
+        assert_eq!(infer("[]", 1), "type Root = unknown[];\n");
+        assert_eq!(
+            infer(r#"[{"1st": true}] {"a": 1}"#, 1),
+            "type Root = Root2 | RootItem[];\n\ninterface Root2 {\n  a: number;\n}\n\n\
+             interface RootItem {\n  \"1st\": boolean;\n}\n"
+        );
+        assert_eq!(infer("", 1), "");
+    }
+}
@@ -0,0 +1,105 @@
+//! Selection of every value matching a path pattern.
+//!
+//! JSONPath-style queries such as `$.items[*].name` ask for the text of every
+//! value at a matching path. [`select_all`] answers them with a [`Glob`]
+//! pattern in one pass over the document, without building any values: the
+//! parser only reports where each value starts and ends, and the result
+//! borrows those ranges of the input.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{Glob, select_all};
+//!
+//! let products = r#"{"items": [
+//!     {"name": "lamp", "price": 25},
+//!     {"name": "desk", "price": 140, "parts": [{"name": "leg"}]}
+//! ]}"#;
+//! let names = select_all(products, &Glob::compile("items[*].name").unwrap())?;
+//! assert_eq!(names, [r#""lamp""#, r#""desk""#]);
+//!
+//! let all_names = select_all(products, &Glob::compile("**.name").unwrap())?;
+//! assert_eq!(all_names, [r#""lamp""#, r#""desk""#, r#""leg""#]);
+//! # Ok::<(), jsonmodem::ParserError>(())
+//! ```
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{Glob, ParseEvent, ParserError, event_byte_spans};
+
+/// Returns the text of every value in `json` whose path matches `pattern`,
+/// in the order the values start in the document, without surrounding
+/// whitespace.
+///
+/// The text always borrows from `json`. A value nested in another matching
+/// value, e.g. with a `**` pattern, is returned along with it.
+///
+/// # Errors
+///
+/// Returns an error if `json` is not one complete, valid JSON document.
+pub fn select_all<'src>(json: &'src str, pattern: &Glob) -> Result<Vec<&'src str>, ParserError> {
+    let mut spans: Vec<Range<usize>> = Vec::new();
+    // The start offset of each open container, and whether it matches.
+    let mut open: Vec<(usize, bool)> = Vec::new();
+    for result in event_byte_spans(json) {
+        let (event, span) = result?;
+        let start = match &event {
+            ParseEvent::ArrayStart { path, .. } | ParseEvent::ObjectBegin { path, .. } => {
+                open.push((span.start, pattern.matches_path(path)));
+                continue;
+            }
+            ParseEvent::ArrayEnd { .. } | ParseEvent::ObjectEnd { .. } => match open.pop() {
+                Some((start, true)) => start,
+                _ => continue,
+            },
+            ParseEvent::Null { path }
+            | ParseEvent::Boolean { path, .. }
+            | ParseEvent::Number { path, .. }
+            | ParseEvent::String {
+                path,
+                is_final: true,
+                ..
+            }
+            | ParseEvent::StringComplete { path, .. }
+                if pattern.matches_path(path) =>
+            {
+                span.start
+            }
+            _ => continue,
+        };
+        spans.push(start..span.end);
+    }
+    // Containers are complete after their members, but come first.
+    spans.sort_by_key(|span| span.start);
+    Ok(spans.into_iter().map(|span| &json[span]).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::select_all;
+    use crate::Glob;
+
+    const JSON: &str = r#" {"a": [{"b": 1}, {"b": [true, null]}, {"c": "x"}], "b": "é☕"} "#;
+
+    fn select(pattern: &str) -> alloc::vec::Vec<&'static str> {
+        select_all(JSON, &Glob::compile(pattern).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn matching_values_in_document_order() {
+        assert_eq!(select("a[*].b"), ["1", "[true, null]"]);
+        assert_eq!(select("**.b"), ["1", "[true, null]", r#""é☕""#]);
+        assert_eq!(select("a[1].b[*]"), ["true", "null"]);
+        assert_eq!(select(""), [JSON.trim()]);
+        assert_eq!(
+            select("**")[..3],
+            [
+                JSON.trim(),
+                r#"[{"b": 1}, {"b": [true, null]}, {"c": "x"}]"#,
+                r#"{"b": 1}"#
+            ]
+        );
+        assert!(select("a[3]").is_empty());
+        assert!(select_all("[1, }", &Glob::compile("[*]").unwrap()).is_err());
+    }
+}
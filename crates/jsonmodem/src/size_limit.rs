@@ -0,0 +1,401 @@
+//! Writing of compact JSON under a byte limit.
+//!
+//! [`SizeLimitedWriter`] renders a stream of [`ParseEvent`]s as compact JSON,
+//! like [`NdjsonWriter`](crate::NdjsonWriter), but never writes more than a
+//! fixed number of bytes. This suits responses with a maximum body size: the
+//! [`TruncateMode`] decides whether running out of room is an error or whether
+//! the document is cut short. When truncating, the writer always keeps enough
+//! room to close the containers it has opened, so the output stays valid JSON.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{ParserOptions, SizeLimitedWriter, StreamingParser, TruncateMode, pipe_to};
+//!
+//! let mut parser = StreamingParser::new(ParserOptions::default());
+//! let mut writer = SizeLimitedWriter::new(String::new(), 24, TruncateMode::TruncateArray);
+//! pipe_to(
+//!     parser.feed(r#"{"items": [1, 2, 3, 4, 5, 6, 7, 8]}"#),
+//!     &mut writer,
+//! )?;
+//! assert!(writer.is_truncated());
+//! assert_eq!(writer.into_inner(), r#"{"items":[1,2,3,4,5,6]}"#);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+use alloc::string::String;
+use core::fmt;
+
+use crate::{JsonValue, ParseEvent, ParseEventWriter, ndjson::CompactWriter};
+
+/// What [`SizeLimitedWriter`] does with a value that does not fit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TruncateMode {
+    /// Fail with [`SizeLimitError::LimitExceeded`], leaving the output
+    /// incomplete.
+    #[default]
+    Error,
+    /// Write `null` in place of the value and carry on with the next one.
+    /// A container that does not fit is replaced as a whole.
+    TruncateValue,
+    /// Close every open array and object and ignore the rest of the events.
+    TruncateArray,
+}
+
+/// Error returned by [`SizeLimitedWriter`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SizeLimitError {
+    /// The next value would take the output past the limit, or, when
+    /// truncating, not even a truncated document fits.
+    LimitExceeded {
+        /// The number of bytes written before the value that did not fit.
+        bytes_written: usize,
+    },
+    /// The underlying writer failed.
+    Writer(fmt::Error),
+}
+
+impl fmt::Display for SizeLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SizeLimitError::LimitExceeded { bytes_written } => {
+                write!(f, "size limit exceeded after {bytes_written} bytes")
+            }
+            SizeLimitError::Writer(err) => write!(f, "writer error: {err}"),
+        }
+    }
+}
+
+impl core::error::Error for SizeLimitError {}
+
+impl From<fmt::Error> for SizeLimitError {
+    fn from(err: fmt::Error) -> Self {
+        SizeLimitError::Writer(err)
+    }
+}
+
+/// Writes compact JSON for a stream of [`ParseEvent`]s, stopping at a byte
+/// limit.
+///
+/// Events must form well-nested values, as produced by the parser. Multiple
+/// root values are written on separate lines. String fragments are buffered
+/// until the final fragment arrives, and the `value` carried by container end
+/// events is ignored.
+#[derive(Debug)]
+pub struct SizeLimitedWriter<W: fmt::Write> {
+    writer: W,
+    limit: usize,
+    mode: TruncateMode,
+    written: usize,
+    compact: CompactWriter,
+    roots: usize,
+    /// The text of the value being written, from its separator to its first
+    /// token, or to the final fragment of a string.
+    pending: String,
+    /// The length of the separator and key at the start of `pending`.
+    prefix_len: usize,
+    in_string: bool,
+    /// The number of containers, replaced by `null`, whose events are being
+    /// ignored.
+    skip: usize,
+    truncated: bool,
+    /// Set once the limit was exceeded in `Error` mode or all containers were
+    /// closed in `TruncateArray` mode.
+    stopped: bool,
+}
+
+impl<W: fmt::Write> SizeLimitedWriter<W> {
+    /// Creates a writer that writes at most `limit` bytes to `writer`.
+    pub fn new(writer: W, limit: usize, mode: TruncateMode) -> Self {
+        Self {
+            writer,
+            limit,
+            mode,
+            written: 0,
+            compact: CompactWriter::default(),
+            roots: 0,
+            pending: String::new(),
+            prefix_len: 0,
+            in_string: false,
+            skip: 0,
+            truncated: false,
+            stopped: false,
+        }
+    }
+
+    /// The number of bytes written so far.
+    #[must_use]
+    pub fn bytes_written(&self) -> usize {
+        self.written
+    }
+
+    /// Whether any value was replaced or cut short.
+    #[must_use]
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Writes the text for one event.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SizeLimitError::LimitExceeded`] if the event does not fit in
+    /// `Error` mode, for every event after that, and in the truncating modes
+    /// if not even `null` fits in place of a root value. Returns
+    /// [`SizeLimitError::Writer`] if the underlying writer fails.
+    pub fn write_event<V>(&mut self, event: &ParseEvent<V>) -> Result<(), SizeLimitError>
+    where
+        V: JsonValue,
+        V::Str: AsRef<str>,
+        V::Num: Into<f64>,
+        V::Bool: Into<bool>,
+    {
+        if self.stopped {
+            return match self.mode {
+                TruncateMode::Error => Err(self.limit_exceeded()),
+                _ => Ok(()),
+            };
+        }
+        if self.skip > 0 {
+            match event {
+                ParseEvent::ArrayStart { .. } | ParseEvent::ObjectBegin { .. } => self.skip += 1,
+                ParseEvent::ArrayEnd { .. } | ParseEvent::ObjectEnd { .. } => self.skip -= 1,
+                _ => {}
+            }
+            return Ok(());
+        }
+        let opens = match event {
+            ParseEvent::ArrayEnd { .. } | ParseEvent::ObjectEnd { .. } => {
+                self.pending.clear();
+                self.compact.write_token(event, false, &mut self.pending)?;
+                return self.emit_pending();
+            }
+            ParseEvent::ScalarBegin { .. }
+            | ParseEvent::ScalarEnd { .. }
+            | ParseEvent::ObjectKey { .. } => return Ok(()),
+            ParseEvent::ArrayStart { .. } | ParseEvent::ObjectBegin { .. } => true,
+            _ => false,
+        };
+        let starts_value = !self.in_string;
+        if starts_value {
+            self.pending.clear();
+            if self.compact.depth() == 0 {
+                if self.roots > 0 {
+                    self.pending.push('\n');
+                }
+                self.roots += 1;
+            }
+            self.compact.begin_value(event.path(), &mut self.pending)?;
+            self.prefix_len = self.pending.len();
+        }
+        self.compact
+            .write_token(event, starts_value, &mut self.pending)?;
+        if let ParseEvent::String { is_final, .. } = event {
+            self.in_string = !*is_final;
+            if self.in_string {
+                return Ok(());
+            }
+        }
+        self.value(opens)
+    }
+
+    /// Writes the pending value if it fits alongside the closing brackets of
+    /// every open container, counting the one it opens if `opens` is set.
+    /// Otherwise applies the truncate mode.
+    fn value(&mut self, opens: bool) -> Result<(), SizeLimitError> {
+        if self.fits(self.pending.len() + self.compact.depth()) {
+            return self.emit_pending();
+        }
+
+        if self.mode == TruncateMode::Error {
+            self.stopped = true;
+            return Err(self.limit_exceeded());
+        }
+        self.truncated = true;
+        if opens {
+            self.compact.close(&mut self.pending)?;
+        }
+        self.pending.truncate(self.prefix_len);
+        let closers = self.compact.depth();
+        let null_fits = self.fits(self.pending.len() + "null".len() + closers);
+        if null_fits && (self.mode == TruncateMode::TruncateValue || closers == 0) {
+            self.pending.push_str("null");
+            self.emit_pending()?;
+            if self.mode == TruncateMode::TruncateArray {
+                self.stopped = true;
+            } else if opens {
+                self.skip = 1;
+            }
+            return Ok(());
+        }
+        self.stopped = true;
+        if closers == 0 {
+            return Err(self.limit_exceeded());
+        }
+        self.pending.clear();
+        while self.compact.close(&mut self.pending)? {}
+        self.emit_pending()
+    }
+
+    fn fits(&self, len: usize) -> bool {
+        self.written + len <= self.limit
+    }
+
+    fn emit_pending(&mut self) -> Result<(), SizeLimitError> {
+        self.writer.write_str(&self.pending)?;
+        self.written += self.pending.len();
+        Ok(())
+    }
+
+    fn limit_exceeded(&self) -> SizeLimitError {
+        SizeLimitError::LimitExceeded {
+            bytes_written: self.written,
+        }
+    }
+}
+
+impl<V, W> ParseEventWriter<V> for SizeLimitedWriter<W>
+where
+    V: JsonValue,
+    V::Str: AsRef<str>,
+    V::Num: Into<f64>,
+    V::Bool: Into<bool>,
+    W: fmt::Write,
+{
+    type Error = SizeLimitError;
+
+    fn write_event(&mut self, event: ParseEvent<V>) -> Result<(), Self::Error> {
+        SizeLimitedWriter::write_event(self, &event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use super::{SizeLimitError, SizeLimitedWriter, TruncateMode};
+    use crate::{ParseEvent, ParserOptions, StreamingParser, Value, tests::events};
+
+    fn write(
+        events: &[ParseEvent],
+        limit: usize,
+        mode: TruncateMode,
+    ) -> Result<String, SizeLimitError> {
+        let mut writer = SizeLimitedWriter::new(String::new(), limit, mode);
+        for event in events {
+            writer.write_event(event)?;
+        }
+        Ok(writer.into_inner())
+    }
+
+    const JSON: &str =
+        r#"{"a": [1, 2.5, {"b": null}], "s": "x\"y é", "t": [true, [false]], "o": {}}"#;
+    const COMPACT: &str = r#"{"a":[1,2.5,{"b":null}],"s":"x\"y é","t":[true,[false]],"o":{}}"#;
+
+    #[test]
+    fn output_within_limit_is_unchanged() {
+        for parts in [1, 7, JSON.len()] {
+            for mode in [
+                TruncateMode::Error,
+                TruncateMode::TruncateValue,
+                TruncateMode::TruncateArray,
+            ] {
+                assert_eq!(
+                    write(
+                        &events(JSON, parts, ParserOptions::default()),
+                        COMPACT.len(),
+                        mode
+                    )
+                    .unwrap(),
+                    COMPACT
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn error_mode_fails_at_the_limit() {
+        let mut writer = SizeLimitedWriter::new(String::new(), 8, TruncateMode::Error);
+        let result: Result<(), _> = events("[12, 34, 56]", 1, ParserOptions::default())
+            .iter()
+            .try_for_each(|event| writer.write_event(event));
+        assert_eq!(
+            result,
+            Err(SizeLimitError::LimitExceeded { bytes_written: 6 })
+        );
+        assert_eq!(
+            writer.write_event(&events("7", 1, ParserOptions::default())[0]),
+            Err(SizeLimitError::LimitExceeded { bytes_written: 6 })
+        );
+        assert_eq!(writer.into_inner(), "[12,34");
+    }
+
+    #[test]
+    fn truncate_value_writes_null() {
+        let json =
+            r#"{"short": 1, "long": "abcdefghijklmnopqrstuvwxyz", "list": [1, 2, 3, 4], "end": 2}"#;
+        assert_eq!(
+            write(
+                &events(json, 1, ParserOptions::default()),
+                40,
+                TruncateMode::TruncateValue
+            )
+            .unwrap(),
+            r#"{"short":1,"long":null,"list":[1,2,3,4]}"#
+        );
+        // Not even `null` fits in place of the list: the object is closed.
+        assert_eq!(
+            write(
+                &events(json, 1, ParserOptions::default()),
+                30,
+                TruncateMode::TruncateValue
+            )
+            .unwrap(),
+            r#"{"short":1,"long":null}"#
+        );
+    }
+
+    #[test]
+    fn truncate_array_closes_containers() {
+        assert_eq!(
+            write(
+                &events(
+                    r#"[[1, 2], {"k": [3, 4, 5]}, 6]"#,
+                    1,
+                    ParserOptions::default()
+                ),
+                17,
+                TruncateMode::TruncateArray
+            )
+            .unwrap(),
+            r#"[[1,2],{"k":[3]}]"#
+        );
+    }
+
+    #[test]
+    fn truncated_output_is_valid_json() {
+        let expected: Value = StreamingParser::new(ParserOptions::default())
+            .into_value(JSON)
+            .unwrap();
+        for mode in [TruncateMode::TruncateValue, TruncateMode::TruncateArray] {
+            for limit in 0..=COMPACT.len() + 1 {
+                let Ok(text) = write(&events(JSON, 3, ParserOptions::default()), limit, mode)
+                else {
+                    assert!(limit < 4, "{mode:?} {limit}");
+                    continue;
+                };
+                assert!(text.len() <= limit, "{mode:?} {limit}: {text}");
+                let value = StreamingParser::new(ParserOptions::default())
+                    .into_value(&text)
+                    .unwrap_or_else(|err| panic!("{mode:?} {limit}: {text}: {err}"));
+                if limit >= COMPACT.len() {
+                    assert_eq!(value, expected);
+                }
+            }
+        }
+    }
+}
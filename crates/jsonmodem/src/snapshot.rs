@@ -0,0 +1,150 @@
+//! The latest value at every leaf path of a stream of documents.
+//!
+//! [`PathSnapshot`] consumes events, typically from a parser with
+//! [`ParserOptions::allow_multiple_json_values`] reading one document per
+//! update, and keeps the most recent scalar seen at each path. Paths that a
+//! later document leaves out keep their previous value, which suits
+//! dashboards that display the current state of many independent readings.
+//!
+//! Only scalars are recorded; arrays and objects are implied by the paths of
+//! their members. Strings are recorded once their final fragment arrives.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{ParserOptions, PathSnapshot, StreamingParser, Value, path};
+//!
+//! let mut parser = StreamingParser::new(ParserOptions {
+//!     allow_multiple_json_values: true,
+//!     ..Default::default()
+//! });
+//! let mut snapshot = PathSnapshot::new();
+//! for event in parser.feed(r#"{"cpu": 0.5, "mem": 10} {"cpu": 0.75}"#) {
+//!     snapshot.update(event.unwrap());
+//! }
+//! assert_eq!(snapshot.get(&path!["cpu"]), Some(&Value::Number(0.75)));
+//! assert_eq!(snapshot.get(&path!["mem"]), Some(&Value::Number(10.0)));
+//! ```
+//!
+//! [`ParserOptions::allow_multiple_json_values`]: crate::ParserOptions::allow_multiple_json_values
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use core::convert::Infallible;
+
+use crate::{ParseEvent, ParseEventWriter, PathComponent, Value};
+
+/// Keeps the latest scalar value at each path.
+#[derive(Debug, Clone, Default)]
+pub struct PathSnapshot {
+    values: BTreeMap<Vec<PathComponent>, Value>,
+    /// Fragments of the string in progress.
+    string: String,
+}
+
+impl PathSnapshot {
+    /// Creates an empty snapshot.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the value carried by `event`, if it is a scalar.
+    pub fn update(&mut self, event: ParseEvent) {
+        let (path, value) = match event {
+            ParseEvent::Null { path } => (path, Value::Null),
+            ParseEvent::Boolean { path, value } => (path, Value::Boolean(value)),
+            ParseEvent::Number { path, value } => (path, Value::Number(value)),
+            ParseEvent::String {
+                path,
+                fragment,
+                is_final,
+                ..
+            } => {
+                self.string.push_str(&fragment);
+                if !is_final {
+                    return;
+                }
+                (path, Value::String(core::mem::take(&mut self.string)))
+            }
+            ParseEvent::StringComplete { path, value } => (path, Value::String(value)),
+            _ => return,
+        };
+        self.values.insert(path, value);
+    }
+
+    /// Returns the latest value at `path`.
+    #[must_use]
+    pub fn get(&self, path: &[PathComponent]) -> Option<&Value> {
+        self.values.get(path)
+    }
+
+    /// Returns the latest value at every path seen, ordered by path.
+    #[must_use]
+    pub fn snapshot(&self) -> &BTreeMap<Vec<PathComponent>, Value> {
+        &self.values
+    }
+
+    /// The number of paths with a value.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if no scalar has been recorded.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl ParseEventWriter<Value> for PathSnapshot {
+    type Error = Infallible;
+
+    fn write_event(&mut self, event: ParseEvent) -> Result<(), Self::Error> {
+        self.update(event);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::PathSnapshot;
+    use crate::{
+        ParserOptions, PathComponent, StreamingParser, Value, path, pipe_to, produce_chunks,
+    };
+
+    #[test]
+    fn later_documents_overwrite_earlier_values() {
+        let json = r#"
+            {"host": "a", "cpu": [0.5, 0.25], "disk": {"free": 100, "ok": true}}
+            {"cpu": [0.75], "disk": {"ok": false}}
+            {"host": "a much longer host name", "disk": {"error": null}}
+        "#;
+        for parts in [1, 9, json.len()] {
+            let mut parser = StreamingParser::new(ParserOptions {
+                allow_multiple_json_values: true,
+                ..Default::default()
+            });
+            let mut snapshot = PathSnapshot::new();
+            for chunk in produce_chunks(json, parts) {
+                pipe_to(parser.feed(chunk), &mut snapshot).unwrap();
+            }
+            pipe_to(parser.finish(), &mut snapshot).unwrap();
+
+            let expected: Vec<(Vec<PathComponent>, Value)> = alloc::vec![
+                (path!["cpu", 0], Value::Number(0.75)),
+                (path!["cpu", 1], Value::Number(0.25)),
+                (path!["disk", "error"], Value::Null),
+                (path!["disk", "free"], Value::Number(100.0)),
+                (path!["disk", "ok"], Value::Boolean(false)),
+                (
+                    path!["host"],
+                    Value::String("a much longer host name".into())
+                ),
+            ];
+            assert!(snapshot.snapshot().clone().into_iter().eq(expected));
+            assert_eq!(snapshot.len(), 6);
+        }
+    }
+}
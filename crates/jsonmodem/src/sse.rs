@@ -0,0 +1,427 @@
+//! Parsing of JSON messages sent as Server-Sent Events.
+//!
+//! Streaming APIs often send each JSON message as the `data` of an event in
+//! the `text/event-stream` format:
+//!
+//! ```text
+//! : keep-alive
+//! data: {"choices": [{"delta": {"content": "Hel"}}]}
+//!
+//! data: {"choices": [{"delta": {"content": "lo"}}]}
+//!
+//! data: [DONE]
+//! ```
+//!
+//! [`SseJsonParser`] reads such a stream and feeds the data of each event to
+//! a fresh parser as it arrives, without waiting for the end of the line.
+//! Comment lines, which start with `:`, and fields other than `data`, such as
+//! `event` and `id`, are skipped. The `data` lines of an event are joined with
+//! newlines, as the format specifies, so a value may span several of them.
+//! The blank line ending an event completes its value.
+//!
+//! An event whose data is `[DONE]`, a sentinel sent by several AI APIs, ends
+//! the stream: any input after it is ignored.
+//!
+//! Each event must hold one JSON value, and its events have paths relative
+//! to that value. Lines may end with `\n`, `\r\n` or `\r`, and the input may
+//! be split across chunks at any byte.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{ParseEvent, ParserOptions, SseJsonParser};
+//!
+//! let mut parser = SseJsonParser::new(ParserOptions::default());
+//! let mut events = parser.feed_bytes(b": ping\ndata: {\"n\": 1}\n\nda")?;
+//! events.extend(parser.feed_bytes(b"ta: true\n\ndata: [DONE]\n\n")?);
+//! parser.finish()?;
+//! assert_eq!(parser.messages(), 2);
+//! assert!(parser.is_done());
+//! assert!(matches!(events[1], ParseEvent::Number { value: 1.0, .. }));
+//! assert!(matches!(events[3], ParseEvent::Boolean { value: true, .. }));
+//! # Ok::<(), jsonmodem::SseError>(())
+//! ```
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{ParseEvent, ParserError, ParserOptions, StreamingParser};
+
+/// The data of the event that ends the stream.
+const DONE: &[u8] = b"[DONE]";
+
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Error returned by [`SseJsonParser`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum SseError {
+    /// The input ended inside an event, before the blank line ending it.
+    Truncated,
+    /// The data of an event is not a single valid JSON value. The position
+    /// is relative to the start of the data.
+    Parser(ParserError),
+}
+
+impl fmt::Display for SseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SseError::Truncated => f.write_str("input ended inside an event"),
+            SseError::Parser(err) => err.fmt(f),
+        }
+    }
+}
+
+impl core::error::Error for SseError {}
+
+impl From<ParserError> for SseError {
+    fn from(err: ParserError) -> Self {
+        SseError::Parser(err)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Line {
+    /// At the start of a line.
+    Start,
+    /// Reading a field name; `data` is whether its first `len` bytes match
+    /// `data`.
+    Name { len: usize, data: bool },
+    /// Reading a comment.
+    Comment,
+    /// Reading the value of a field, after its colon.
+    Value { data: bool, at_start: bool },
+}
+
+/// A streaming parser for JSON messages sent as Server-Sent Events.
+#[derive(Debug)]
+pub struct SseJsonParser {
+    options: ParserOptions,
+    parser: StreamingParser,
+    line: Line,
+    /// Whether the last byte was a `\r`, so a `\n` after it is part of the
+    /// same line ending.
+    after_cr: bool,
+    /// The number of bytes of a leading byte order mark skipped.
+    bom: usize,
+    /// The number of `data` lines of the current event.
+    data_lines: usize,
+    /// Whether any data of the current event has been fed to the parser.
+    fed: bool,
+    /// While the first `data` line of an event may be `[DONE]`, the number of
+    /// its bytes held back.
+    held: Option<usize>,
+    done: bool,
+    messages: usize,
+}
+
+impl SseJsonParser {
+    /// Creates a parser using `options` for the data of every event.
+    ///
+    /// `allow_multiple_json_values` is turned off, since each event holds a
+    /// single value.
+    #[must_use]
+    pub fn new(options: ParserOptions) -> Self {
+        let options = ParserOptions {
+            allow_multiple_json_values: false,
+            ..options
+        };
+        Self {
+            options,
+            parser: StreamingParser::new(options),
+            line: Line::Start,
+            after_cr: false,
+            bom: 0,
+            data_lines: 0,
+            fed: false,
+            held: None,
+            done: false,
+            messages: 0,
+        }
+    }
+
+    /// The number of messages completed so far.
+    #[must_use]
+    pub fn messages(&self) -> usize {
+        self.messages
+    }
+
+    /// Returns `true` once a `[DONE]` event has been read.
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Feeds a chunk of the stream, returning the events it completes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data of an event is not a valid JSON value.
+    /// The parser should not be fed after an error.
+    pub fn feed_bytes(&mut self, mut bytes: &[u8]) -> Result<Vec<ParseEvent>, SseError> {
+        let mut events = Vec::new();
+        while self.bom < BOM.len() {
+            let Some((&byte, rest)) = bytes.split_first() else {
+                break;
+            };
+            if byte == BOM[self.bom] {
+                self.bom += 1;
+                bytes = rest;
+            } else {
+                self.bom = BOM.len();
+            }
+        }
+        while let Some((&byte, rest)) = bytes.split_first() {
+            if self.done {
+                break;
+            }
+            if self.after_cr {
+                self.after_cr = false;
+                if byte == b'\n' {
+                    bytes = rest;
+                    continue;
+                }
+            }
+            if let Line::Value { data, at_start } = self.line {
+                // Pass the value on up to the end of the line, skipping the
+                // space that may follow the colon.
+                let skip = usize::from(at_start && byte == b' ');
+                let end = bytes
+                    .iter()
+                    .position(|&b| b == b'\r' || b == b'\n')
+                    .unwrap_or(bytes.len());
+                if data && end > skip {
+                    self.data(&bytes[skip..end], &mut events)?;
+                }
+                self.line = Line::Value {
+                    data,
+                    at_start: false,
+                };
+                let Some((&eol, rest)) = bytes[end..].split_first() else {
+                    break;
+                };
+                self.after_cr = eol == b'\r';
+                self.end_line(&mut events)?;
+                self.line = Line::Start;
+                bytes = rest;
+                continue;
+            }
+            bytes = rest;
+            self.line = match (self.line, byte) {
+                (_, b'\r' | b'\n') => {
+                    self.after_cr = byte == b'\r';
+                    self.end_line(&mut events)?;
+                    Line::Start
+                }
+                (Line::Start, b':') | (Line::Comment, _) => Line::Comment,
+                (Line::Start, _) => Line::Name {
+                    len: 1,
+                    data: byte == b'd',
+                },
+                (Line::Name { len, data }, b':') => {
+                    let data = data && len == 4;
+                    if data {
+                        self.start_data_line(&mut events)?;
+                    }
+                    Line::Value {
+                        data,
+                        at_start: true,
+                    }
+                }
+                (Line::Name { len, data }, _) => Line::Name {
+                    len: len + 1,
+                    data: data && b"data".get(len) == Some(&byte),
+                },
+                (line @ Line::Value { .. }, _) => line,
+            };
+        }
+        Ok(events)
+    }
+
+    /// Marks the end of the stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SseError::Truncated`] if the stream ended inside an event
+    /// with data.
+    pub fn finish(&self) -> Result<(), SseError> {
+        if self.data_lines > 0 && !self.done {
+            Err(SseError::Truncated)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn end_line(&mut self, events: &mut Vec<ParseEvent>) -> Result<(), SseError> {
+        match self.line {
+            Line::Start => self.end_event(events)?,
+            // A line holding only a field name has an empty value.
+            Line::Name { len: 4, data: true } => {
+                self.start_data_line(events)?;
+                self.end_data_line(events)?;
+            }
+            Line::Value { data: true, .. } => self.end_data_line(events)?,
+            Line::Name { .. } | Line::Comment | Line::Value { .. } => {}
+        }
+        Ok(())
+    }
+
+    fn start_data_line(&mut self, events: &mut Vec<ParseEvent>) -> Result<(), SseError> {
+        if self.data_lines == 0 {
+            self.held = Some(0);
+        } else {
+            self.feed_parser(b"\n", events)?;
+        }
+        self.data_lines += 1;
+        Ok(())
+    }
+
+    fn end_data_line(&mut self, events: &mut Vec<ParseEvent>) -> Result<(), SseError> {
+        match self.held.take() {
+            Some(held) if held == DONE.len() => self.done = true,
+            Some(0) | None => {}
+            Some(held) => {
+                self.fed = true;
+                self.feed_parser(&DONE[..held], events)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Passes on part of a `data` line, holding back what may be `[DONE]`.
+    fn data(&mut self, data: &[u8], events: &mut Vec<ParseEvent>) -> Result<(), SseError> {
+        self.fed = true;
+        if let Some(held) = self.held {
+            let rest = &DONE[held..];
+            if rest.starts_with(data) {
+                self.held = Some(held + data.len());
+                return Ok(());
+            }
+            self.held = None;
+            self.feed_parser(&DONE[..held], events)?;
+        }
+        self.feed_parser(data, events)
+    }
+
+    fn feed_parser(&mut self, bytes: &[u8], events: &mut Vec<ParseEvent>) -> Result<(), SseError> {
+        for event in self.parser.feed_bytes(bytes)? {
+            events.push(event?);
+        }
+        Ok(())
+    }
+
+    /// Finishes the parser of the current event, if it has data, and starts
+    /// the next one.
+    fn end_event(&mut self, events: &mut Vec<ParseEvent>) -> Result<(), SseError> {
+        if self.data_lines == 0 {
+            return Ok(());
+        }
+        let parser = core::mem::replace(&mut self.parser, StreamingParser::new(self.options));
+        if self.fed {
+            for event in parser.finish() {
+                events.push(event?);
+            }
+            self.messages += 1;
+        }
+        self.data_lines = 0;
+        self.fed = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use super::{SseError, SseJsonParser};
+    use crate::{ParseEvent, ParserOptions, Value, event::reconstruct_values};
+
+    const STREAM: &str = concat!(
+        ": keep-alive\n",
+        "event: message\n",
+        "id: 1\n",
+        "data: {\"choices\": [{\"delta\": {\"content\": \"Hé\"}}]}\n",
+        "\n",
+        "retry: 1000\r\n",
+        "data:{\"choices\": [{\"delta\":\r\n",
+        "data: {\"content\": \"llo ☕\"}}]}\r\n",
+        "\r\n",
+        "data\n",
+        "\n",
+        "data: [1, \"[DONE]\"]\r",
+        "\r",
+        "data: [DONE]\n",
+        "\n",
+        "data: {\"ignored\": true}\n",
+        "\n",
+    );
+
+    fn parse(chunks: &[&[u8]]) -> Result<(SseJsonParser, Vec<ParseEvent>), SseError> {
+        let mut parser = SseJsonParser::new(ParserOptions::default());
+        let mut events = Vec::new();
+        for chunk in chunks {
+            events.extend(parser.feed_bytes(chunk)?);
+        }
+        parser.finish()?;
+        Ok((parser, events))
+    }
+
+    fn value(json: &str) -> Value {
+        crate::StreamingParser::new(ParserOptions::default())
+            .into_value(json)
+            .unwrap()
+    }
+
+    #[test]
+    fn events_split_at_every_boundary() {
+        let expected = vec![
+            value(r#"{"choices": [{"delta": {"content": "Hé"}}]}"#),
+            value(r#"{"choices": [{"delta": {"content": "llo ☕"}}]}"#),
+            value(r#"[1, "[DONE]"]"#),
+        ];
+        let input = STREAM.as_bytes();
+        // Splits inside each line, each line ending, each multi-byte
+        // character and the `[DONE]` sentinel.
+        for first in 0..input.len() {
+            for second in first..input.len() {
+                let chunks = [&input[..first], &input[first..second], &input[second..]];
+                let (parser, events) = parse(&chunks).unwrap();
+                assert_eq!(reconstruct_values(events), expected, "{first}, {second}");
+                assert_eq!(parser.messages(), 3);
+                assert!(parser.is_done());
+            }
+        }
+    }
+
+    #[test]
+    fn data_lines_are_joined_with_newlines() {
+        // The newline between the lines separates the two tokens.
+        let (_, events) = parse(&[b"\xEF\xBB\xBFdata: [1,\ndata:2]\n\n"]).unwrap();
+        assert_eq!(reconstruct_values(events), [value("[1, 2]")]);
+        // Strings may not contain a raw newline, so cannot span lines.
+        assert!(matches!(
+            parse(&[b"data: [\"a\ndata: b\"]\n\n"]),
+            Err(SseError::Parser(_))
+        ));
+    }
+
+    #[test]
+    fn reports_errors() {
+        assert!(matches!(
+            parse(&[b"data: {\"a\" 1}\n\n"]),
+            Err(SseError::Parser(_))
+        ));
+        assert!(matches!(
+            parse(&[b"data: [DONE]x\n\n"]),
+            Err(SseError::Parser(_))
+        ));
+        assert!(matches!(
+            parse(&[b"data: [DONE]\ndata: 1\n\n"]).map(|(parser, _)| parser.is_done()),
+            Ok(true)
+        ));
+        assert_eq!(
+            parse(&[b"data: {\"a\": 1}\n"]).map(|_| ()),
+            Err(SseError::Truncated)
+        );
+        // Events without data, and comments after the last event, are fine.
+        assert!(parse(&[b"data: 1\n\nevent: x\n\n: bye"]).is_ok());
+    }
+}
@@ -3,9 +3,11 @@
 use alloc::vec::Vec;
 
 use crate::{
-    JsonValue, JsonValueFactory, ParseEvent, StdValueFactory, Value,
+    JsonValue, JsonValueFactory, ParseEvent, PathComponent, StdValueFactory, StringValueMode,
+    Value,
+    error::ParserError,
     options::{NonScalarValueMode, ParserOptions},
-    parser::{ParserError, StreamingParserImpl},
+    parser::StreamingParserImpl,
 };
 
 /// A value produced during streaming parsing.
@@ -228,3 +230,301 @@ impl StreamingValuesParserImpl<Value> {
         self.finish_with(StdValueFactory)
     }
 }
+
+/// The root value yielded by [`JsonModemValues`] after an event.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PartialValue<V> {
+    /// A root value that is complete.
+    Complete(V),
+    /// A value read inside a root value that is not complete yet, or a
+    /// fragment of a string. [`JsonModemValues::current`] returns the root
+    /// value so far.
+    Partial {
+        /// The scalar at the yielded path, or the fragment of the string
+        /// there that this event read.
+        value: V,
+        /// Whether the string at the yielded path is still incomplete, with
+        /// more fragments to come.
+        incomplete_string: bool,
+    },
+}
+
+impl<V> PartialValue<V> {
+    /// Returns the value, complete or not.
+    pub fn value(&self) -> &V {
+        match self {
+            PartialValue::Complete(value) | PartialValue::Partial { value, .. } => value,
+        }
+    }
+
+    /// Returns the value, complete or not.
+    pub fn into_value(self) -> V {
+        match self {
+            PartialValue::Complete(value) | PartialValue::Partial { value, .. } => value,
+        }
+    }
+
+    /// Returns `true` for [`PartialValue::Complete`].
+    pub fn is_complete(&self) -> bool {
+        matches!(self, PartialValue::Complete(_))
+    }
+}
+
+/// Parser wrapper that yields the root value as it is built, after every
+/// scalar and string fragment.
+///
+/// Where [`StreamingValuesParser`] reports the values once per chunk, this
+/// iterator yields the path of each scalar or string fragment along with what
+/// was read there, so a consumer can react to each value as it is read
+/// instead of once the chunk is done. The root value built so far is borrowed
+/// from [`current`](Self::current) between items, rather than copied into
+/// each of them. A root value is yielded once more, as
+/// [`PartialValue::Complete`], when it is complete.
+///
+/// # Examples
+///
+/// ```
+/// use jsonmodem::{JsonModemValues, ParserOptions, PartialValue, path};
+///
+/// let mut values = JsonModemValues::new(ParserOptions::default());
+/// values.feed(r#"{"id": 7, "msg": "hi", "#);
+/// let (path, value) = values.nth(1).unwrap()?;
+/// assert_eq!(path, path!["msg"]);
+/// assert_eq!(value.value().to_string(), r#""hi""#);
+/// assert!(!value.is_complete());
+/// let root = values.current().unwrap();
+/// assert_eq!(root.to_string(), r#"{"id":7,"msg":"hi"}"#);
+///
+/// values.feed(r#""ok": true}"#);
+/// let (path, value) = values.last().unwrap()?;
+/// assert!(path.is_empty());
+/// assert!(matches!(value, PartialValue::Complete(_)));
+/// assert_eq!(
+///     value.value().to_string(),
+///     r#"{"id":7,"msg":"hi","ok":true}"#
+/// );
+/// # Ok::<(), jsonmodem::ParserError>(())
+/// ```
+#[derive(Debug)]
+pub struct JsonModemValues<F: JsonValueFactory = StdValueFactory> {
+    parser: StreamingParserImpl<F::Value>,
+    factory: F,
+}
+
+impl JsonModemValues {
+    /// Creates a parser building [`Value`]s.
+    ///
+    /// `non_scalar_values` and `string_value_mode` are ignored, since the
+    /// root value is always built.
+    #[must_use]
+    pub fn new(options: ParserOptions) -> Self {
+        Self::with_factory(options, StdValueFactory)
+    }
+}
+
+impl<F: JsonValueFactory> JsonModemValues<F> {
+    /// Creates a parser building values with `factory`.
+    ///
+    /// `non_scalar_values` and `string_value_mode` are ignored, since the
+    /// root value is always built.
+    #[must_use]
+    pub fn with_factory(options: ParserOptions, factory: F) -> Self {
+        Self {
+            parser: StreamingParserImpl::new(ParserOptions {
+                // `Roots` would drop the events of nested scalars.
+                non_scalar_values: NonScalarValueMode::All,
+                // Fragments are yielded as they are, and a root string is
+                // complete with its final one.
+                string_value_mode: StringValueMode::Values,
+                ..options
+            }),
+            factory,
+        }
+    }
+
+    /// Appends a chunk of input. The values it produces are read by iterating.
+    pub fn feed(&mut self, chunk: &str) {
+        self.parser.push_chunk(chunk);
+    }
+
+    /// Marks the end of input, completing a trailing number and making a
+    /// truncated document an error.
+    pub fn finish(&mut self) {
+        self.parser.end_input();
+    }
+
+    /// Returns the root value built so far, in which open arrays and objects
+    /// hold the members read so far. It is only meaningful while the last
+    /// item yielded was a [`PartialValue::Partial`].
+    #[must_use]
+    pub fn current(&self) -> Option<&F::Value> {
+        self.parser.unstable_get_current_value_ref()
+    }
+}
+
+impl<F: JsonValueFactory> Iterator for JsonModemValues<F> {
+    type Item = Result<(Vec<PathComponent>, PartialValue<F::Value>), ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let event = match self.parser.next_event_with(&mut self.factory)? {
+                Ok(event) => event,
+                Err(err) => return Some(Err(err)),
+            };
+            let f = &mut self.factory;
+            let (path, value) = match event {
+                ParseEvent::Null { path } if path.is_empty() => {
+                    let null = f.new_null();
+                    (path, PartialValue::Complete(f.build_from_null(null)))
+                }
+                ParseEvent::Boolean { path, value } if path.is_empty() => {
+                    (path, PartialValue::Complete(f.build_from_bool(value)))
+                }
+                ParseEvent::Number { path, value } if path.is_empty() => {
+                    (path, PartialValue::Complete(f.build_from_num(value)))
+                }
+                ParseEvent::String {
+                    path,
+                    value: Some(value),
+                    ..
+                } if path.is_empty() => (path, PartialValue::Complete(f.build_from_str(value))),
+                ParseEvent::ArrayEnd {
+                    path,
+                    value: Some(value),
+                } if path.is_empty() => (path, PartialValue::Complete(f.build_from_array(value))),
+                ParseEvent::ObjectEnd {
+                    path,
+                    value: Some(value),
+                } if path.is_empty() => (path, PartialValue::Complete(f.build_from_object(value))),
+                ParseEvent::Null { path } => {
+                    let null = f.new_null();
+                    (path, partial(f.build_from_null(null), false))
+                }
+                ParseEvent::Boolean { path, value } => {
+                    (path, partial(f.build_from_bool(value), false))
+                }
+                ParseEvent::Number { path, value } => {
+                    (path, partial(f.build_from_num(value), false))
+                }
+                ParseEvent::String {
+                    path,
+                    fragment,
+                    is_final,
+                    ..
+                } => (path, partial(f.build_from_str(fragment), !is_final)),
+                ParseEvent::StringComplete { path, value } => {
+                    (path, partial(f.build_from_str(value), false))
+                }
+                _ => continue,
+            };
+            return Some(Ok((path, value)));
+        }
+    }
+}
+
+fn partial<V>(value: V, incomplete_string: bool) -> PartialValue<V> {
+    PartialValue::Partial {
+        value,
+        incomplete_string,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        string::{String, ToString},
+        vec::Vec,
+    };
+
+    use super::{JsonModemValues, PartialValue};
+    use crate::{ParserOptions, PathComponent, path, produce_chunks};
+
+    /// Returns the path and text of every value yielded, with `*` marking a
+    /// complete value and `~` an incomplete string, and the root value so
+    /// far for a partial one.
+    fn yields(json: &str, parts: usize) -> Vec<(String, String, String)> {
+        let mut values = JsonModemValues::new(ParserOptions {
+            allow_multiple_json_values: true,
+            ..ParserOptions::default()
+        });
+        let mut out = Vec::new();
+        let mut record = |values: &mut JsonModemValues| {
+            while let Some(result) = values.next() {
+                let (path, value) = result.unwrap();
+                let mark = match &value {
+                    PartialValue::Complete(_) => "*",
+                    PartialValue::Partial {
+                        incomplete_string: true,
+                        ..
+                    } => "~",
+                    PartialValue::Partial { .. } => "",
+                };
+                let path = path
+                    .iter()
+                    .map(|component| match component {
+                        PathComponent::Key(key) => key.to_string(),
+                        PathComponent::Index(index) => index.to_string(),
+                    })
+                    .collect::<Vec<_>>();
+                let root = match value {
+                    PartialValue::Complete(_) => None,
+                    PartialValue::Partial { .. } => values.current().map(ToString::to_string),
+                };
+                out.push((
+                    path.join("."),
+                    alloc::format!("{mark}{}", value.value()),
+                    root.unwrap_or_default(),
+                ));
+            }
+        };
+        for chunk in produce_chunks(json, parts) {
+            values.feed(chunk);
+            record(&mut values);
+        }
+        values.finish();
+        record(&mut values);
+        out
+    }
+
+    #[test]
+    fn yields_after_every_scalar_and_fragment() {
+        let json = r#"{"a": ["xy", 1], "b": null} "s" 2"#;
+        let expected = [
+            ("a.0", r#"~"x""#, r#"{"a":["x"]}"#),
+            ("a.0", r#"~"y""#, r#"{"a":["xy"]}"#),
+            // The closing quote ends the string with an empty fragment.
+            ("a.0", r#""""#, r#"{"a":["xy"]}"#),
+            ("a.1", "1", r#"{"a":["xy",1]}"#),
+            ("b", "null", r#"{"a":["xy",1],"b":null}"#),
+            ("", r#"*{"a":["xy",1],"b":null}"#, ""),
+            ("", r#"~"s""#, r#""s""#),
+            ("", r#"*"s""#, ""),
+            ("", "*2", ""),
+        ];
+        let split = yields(json, json.len());
+        assert_eq!(
+            split,
+            expected.map(|(path, value, root)| (
+                path.to_string(),
+                value.to_string(),
+                root.to_string()
+            ))
+        );
+
+        // In one chunk, each string arrives in one fragment.
+        let whole = yields(json, 1);
+        assert_eq!(whole.len(), expected.len() - 3);
+        assert_eq!(whole.last(), split.last());
+    }
+
+    #[test]
+    fn errors_end_the_values() {
+        let mut values = JsonModemValues::new(ParserOptions::default());
+        values.feed("[1, x]");
+        let (path, value) = values.next().unwrap().unwrap();
+        assert_eq!(path, path![0]);
+        assert!(!value.is_complete());
+        assert!(values.next().unwrap().is_err());
+        assert!(values.next().is_none());
+    }
+}
@@ -0,0 +1,644 @@
+//! Parsing of the JSON files in a tar archive.
+//!
+//! Datasets are often shipped as a tar archive of many small JSON files.
+//! [`TarJsonExtractor`] reads such an archive as a byte stream, feeds the
+//! content of every regular file whose name ends in `.json` to a fresh parser
+//! and skips the other entries, without extracting anything. Headers are
+//! read in place when a chunk holds a whole one, and file contents are
+//! passed to the parser straight from the chunks fed, so memory use does not
+//! depend on the size of the files.
+//!
+//! Archives in the POSIX ustar, pax and GNU formats are read, including long
+//! file names stored in pax `path` records or GNU `L` entries. Each JSON file
+//! must hold one JSON value. Its events follow a [`TarEvent::File`] naming
+//! it and have paths relative to that value.
+//!
+//! Requires the `tar` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{ParseEvent, ParserOptions, TarEvent, TarJsonExtractor};
+//!
+//! # fn header(name: &str, size: usize) -> [u8; 512] {
+//! #     let mut block = [0; 512];
+//! #     block[..name.len()].copy_from_slice(name.as_bytes());
+//! #     block[124..135].copy_from_slice(format!("{size:011o}").as_bytes());
+//! #     block[156] = b'0';
+//! #     block[148..156].fill(b' ');
+//! #     let sum: u32 = block.iter().map(|&b| u32::from(b)).sum();
+//! #     block[148..155].copy_from_slice(format!("{sum:06o}\0").as_bytes());
+//! #     block
+//! # }
+//! # let mut archive = Vec::new();
+//! # for (name, content) in [("notes.txt", "hello"), ("a.json", "[true]")] {
+//! #     archive.extend(header(name, content.len()));
+//! #     archive.extend(content.as_bytes());
+//! #     archive.resize(archive.len().next_multiple_of(512), 0);
+//! # }
+//! # archive.resize(archive.len() + 1024, 0);
+//! // `archive` holds `notes.txt` and `a.json`, whose content is `[true]`.
+//! let mut extractor = TarJsonExtractor::new(ParserOptions::default());
+//! let mut events = Vec::new();
+//! for chunk in archive.chunks(100) {
+//!     events.extend(extractor.feed(chunk)?);
+//! }
+//! extractor.finish()?;
+//! assert_eq!(extractor.json_files(), 1);
+//! assert!(matches!(&events[0], TarEvent::File { filename } if &**filename == "a.json"));
+//! assert!(matches!(
+//!     events[2],
+//!     TarEvent::Event(ParseEvent::Boolean { value: true, .. })
+//! ));
+//! # Ok::<(), jsonmodem::TarError>(())
+//! ```
+use alloc::{string::String, sync::Arc, vec::Vec};
+use core::fmt;
+
+use crate::{ParseEvent, ParserError, ParserOptions, StreamingParser};
+
+/// The size of a tar header, and the unit in which entries are padded.
+const BLOCK: usize = 512;
+
+/// The longest file name or pax header accepted, in bytes.
+const MAX_METADATA_LEN: u64 = 64 << 10;
+
+/// An event of the JSON files in an archive.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TarEvent {
+    /// The events that follow, up to the next `File`, are of this file.
+    File {
+        /// The path of the file in the archive.
+        filename: Arc<str>,
+    },
+    /// An event of the current file.
+    Event(ParseEvent),
+}
+
+/// Error returned by [`TarJsonExtractor`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum TarError {
+    /// A header has a wrong checksum or an invalid field, or a file name or
+    /// pax header is too long.
+    Malformed {
+        /// Offset in bytes of the header from the start of the archive.
+        byte_offset: usize,
+    },
+    /// The archive ended inside an entry.
+    Truncated,
+    /// A JSON file is not a single valid JSON value. The position is relative
+    /// to the start of the file.
+    Parser {
+        /// The path of the file in the archive.
+        filename: Arc<str>,
+        /// The syntax error.
+        error: ParserError,
+    },
+}
+
+impl fmt::Display for TarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TarError::Malformed { byte_offset } => {
+                write!(f, "malformed tar header at byte {byte_offset}")
+            }
+            TarError::Truncated => f.write_str("archive ended inside an entry"),
+            TarError::Parser { filename, error } => write!(f, "{filename}: {error}"),
+        }
+    }
+}
+
+impl core::error::Error for TarError {}
+
+/// What is done with the content of an entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Content {
+    /// Fed to the parser.
+    Json,
+    /// Read as the name of the next entry.
+    LongName,
+    /// Read as pax records for the next entry.
+    Pax,
+    /// Ignored.
+    Skip,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Reading a header; `header` holds its first `header_len` bytes.
+    Header,
+    /// Reading the content of an entry.
+    Content { content: Content, remaining: u64 },
+    /// Skipping the padding after the content of an entry.
+    Padding { remaining: usize },
+    /// After the two zero blocks that end the archive.
+    End,
+}
+
+/// A streaming parser for the JSON files of a tar archive.
+#[derive(Debug)]
+pub struct TarJsonExtractor {
+    options: ParserOptions,
+    parser: StreamingParser,
+    state: State,
+    /// A header split across chunks.
+    header: [u8; BLOCK],
+    header_len: usize,
+    /// The number of consecutive zero blocks read.
+    zero_blocks: usize,
+    /// The content of a GNU long name or pax entry.
+    metadata: Vec<u8>,
+    /// The name given to the next entry by a long name or pax entry.
+    next_name: Option<String>,
+    /// The name of the JSON file being read.
+    filename: Arc<str>,
+    /// The padding after the content of the current entry.
+    padding: usize,
+    /// Offset in the archive of the first byte of the next chunk.
+    byte_pos: usize,
+    json_files: usize,
+}
+
+impl TarJsonExtractor {
+    /// Creates an extractor using `options` for every JSON file.
+    ///
+    /// `allow_multiple_json_values` is turned off, since each file holds a
+    /// single value.
+    #[must_use]
+    pub fn new(options: ParserOptions) -> Self {
+        let options = ParserOptions {
+            allow_multiple_json_values: false,
+            ..options
+        };
+        Self {
+            options,
+            parser: StreamingParser::new(options),
+            state: State::Header,
+            header: [0; BLOCK],
+            header_len: 0,
+            zero_blocks: 0,
+            metadata: Vec::new(),
+            next_name: None,
+            filename: Arc::from(""),
+            padding: 0,
+            byte_pos: 0,
+            json_files: 0,
+        }
+    }
+
+    /// The number of JSON files completed so far.
+    #[must_use]
+    pub fn json_files(&self) -> usize {
+        self.json_files
+    }
+
+    /// Feeds a chunk of the archive, returning the events it completes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a header is malformed or a JSON file is not a
+    /// valid JSON value. The extractor should not be fed after an error.
+    pub fn feed(&mut self, mut bytes: &[u8]) -> Result<Vec<TarEvent>, TarError> {
+        let mut events = Vec::new();
+        while !bytes.is_empty() && self.state != State::End {
+            let consumed = self.step(bytes, &mut events)?;
+            bytes = &bytes[consumed..];
+            self.byte_pos += consumed;
+        }
+        Ok(events)
+    }
+
+    /// Marks the end of the archive.
+    ///
+    /// An archive may end after the last entry without its closing zero
+    /// blocks, as many tools accept.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TarError::Truncated`] if the archive ended inside an entry.
+    pub fn finish(&self) -> Result<(), TarError> {
+        match self.state {
+            State::End => Ok(()),
+            State::Header if self.header_len == 0 => Ok(()),
+            _ => Err(TarError::Truncated),
+        }
+    }
+
+    /// Consumes what it can of `bytes`, returning how many bytes.
+    fn step(&mut self, bytes: &[u8], events: &mut Vec<TarEvent>) -> Result<usize, TarError> {
+        match self.state {
+            State::Header => {
+                if let Some(block) = bytes
+                    .first_chunk::<BLOCK>()
+                    .filter(|_| self.header_len == 0)
+                {
+                    self.read_header(block, self.byte_pos, events)?;
+                    return Ok(BLOCK);
+                }
+                let take = (BLOCK - self.header_len).min(bytes.len());
+                self.header[self.header_len..][..take].copy_from_slice(&bytes[..take]);
+                self.header_len += take;
+                if self.header_len == BLOCK {
+                    self.header_len = 0;
+                    let block = self.header;
+                    self.read_header(&block, self.byte_pos + take - BLOCK, events)?;
+                }
+                Ok(take)
+            }
+            State::Content { content, remaining } => {
+                let take = usize::try_from(remaining).map_or(bytes.len(), |n| n.min(bytes.len()));
+                let data = &bytes[..take];
+                match content {
+                    Content::Json => {
+                        let parsed = self.parser.feed_bytes(data).and_then(|parsed| {
+                            for event in parsed {
+                                events.push(TarEvent::Event(event?));
+                            }
+                            Ok(())
+                        });
+                        parsed.map_err(|error| self.parser_error(error))?;
+                    }
+                    Content::LongName | Content::Pax => self.metadata.extend_from_slice(data),
+                    Content::Skip => {}
+                }
+                let remaining = remaining - take as u64;
+                if remaining == 0 {
+                    self.end_entry(content, events)?;
+                } else {
+                    self.state = State::Content { content, remaining };
+                }
+                Ok(take)
+            }
+            State::Padding { remaining } => {
+                let take = remaining.min(bytes.len());
+                self.state = if take == remaining {
+                    State::Header
+                } else {
+                    State::Padding {
+                        remaining: remaining - take,
+                    }
+                };
+                Ok(take)
+            }
+            State::End => Ok(bytes.len()),
+        }
+    }
+
+    /// Reads the header at `byte_offset` and starts its entry.
+    fn read_header(
+        &mut self,
+        block: &[u8; BLOCK],
+        byte_offset: usize,
+        events: &mut Vec<TarEvent>,
+    ) -> Result<(), TarError> {
+        if block.iter().all(|&b| b == 0) {
+            self.zero_blocks += 1;
+            if self.zero_blocks == 2 {
+                self.state = State::End;
+            }
+            return Ok(());
+        }
+        self.zero_blocks = 0;
+        let malformed = TarError::Malformed { byte_offset };
+        let checksum = octal(&block[148..156]).ok_or(malformed.clone())?;
+        let sum: u64 = block
+            .iter()
+            .enumerate()
+            .map(|(i, &b)| {
+                if (148..156).contains(&i) {
+                    32
+                } else {
+                    u64::from(b)
+                }
+            })
+            .sum();
+        if sum != checksum {
+            return Err(malformed);
+        }
+        let size = number(&block[124..136]).ok_or(malformed.clone())?;
+        let content = match block[156] {
+            b'0' | b'\0' | b'7' => {
+                let name = self.next_name.take().unwrap_or_else(|| ustar_name(block));
+                if has_json_extension(&name) {
+                    self.filename = Arc::from(name);
+                    events.push(TarEvent::File {
+                        filename: self.filename.clone(),
+                    });
+                    Content::Json
+                } else {
+                    Content::Skip
+                }
+            }
+            b'L' | b'x' if size > MAX_METADATA_LEN => return Err(malformed),
+            b'L' => Content::LongName,
+            b'x' => Content::Pax,
+            _ => {
+                // A long name belongs to the entry right after it, whatever
+                // its type.
+                self.next_name = None;
+                Content::Skip
+            }
+        };
+        self.metadata.clear();
+        #[expect(clippy::cast_possible_truncation)]
+        let padding = (BLOCK - (size % BLOCK as u64) as usize) % BLOCK;
+        self.padding = padding;
+        self.state = State::Content {
+            content,
+            remaining: size,
+        };
+        if size == 0 {
+            self.end_entry(content, events)?;
+        }
+        Ok(())
+    }
+
+    /// Finishes the content of an entry and moves on to its padding.
+    fn end_entry(&mut self, content: Content, events: &mut Vec<TarEvent>) -> Result<(), TarError> {
+        match content {
+            Content::Json => {
+                let parser =
+                    core::mem::replace(&mut self.parser, StreamingParser::new(self.options));
+                for event in parser.finish() {
+                    let event = event.map_err(|error| self.parser_error(error))?;
+                    events.push(TarEvent::Event(event));
+                }
+                self.json_files += 1;
+            }
+            Content::LongName => {
+                let name = self.metadata.split(|&b| b == 0).next().unwrap_or_default();
+                self.next_name = Some(String::from_utf8_lossy(name).into_owned());
+            }
+            Content::Pax => {
+                if let Some(path) = pax_path(&self.metadata) {
+                    self.next_name = Some(path);
+                }
+            }
+            Content::Skip => {}
+        }
+        self.state = if self.padding == 0 {
+            State::Header
+        } else {
+            State::Padding {
+                remaining: self.padding,
+            }
+        };
+        Ok(())
+    }
+
+    fn parser_error(&self, error: ParserError) -> TarError {
+        TarError::Parser {
+            filename: self.filename.clone(),
+            error,
+        }
+    }
+}
+
+/// Returns the name of an entry from its ustar header: the `prefix` field,
+/// if any, and the `name` field, joined by `/`.
+fn ustar_name(block: &[u8; BLOCK]) -> String {
+    let field = |bytes: &[u8]| {
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        String::from_utf8_lossy(&bytes[..end]).into_owned()
+    };
+    let name = field(&block[..100]);
+    let prefix = if &block[257..262] == b"ustar" {
+        field(&block[345..500])
+    } else {
+        String::new()
+    };
+    if prefix.is_empty() {
+        name
+    } else {
+        prefix + "/" + &name
+    }
+}
+
+/// Returns `true` if `name` ends in `.json`, ignoring case.
+fn has_json_extension(name: &str) -> bool {
+    name.len() > 5
+        && name.as_bytes()[name.len() - 5..].eq_ignore_ascii_case(b".json")
+        && !name.ends_with("/.json")
+}
+
+/// Parses a numeric header field: octal digits, ended by a space or NUL, or
+/// a big-endian binary number flagged by the high bit of its first byte.
+fn number(field: &[u8]) -> Option<u64> {
+    match field.split_first() {
+        Some((&first, rest)) if first & 0x80 != 0 => {
+            rest.iter().try_fold(u64::from(first & 0x7F), |n, &b| {
+                n.checked_mul(256)?.checked_add(u64::from(b))
+            })
+        }
+        _ => octal(field),
+    }
+}
+
+/// Parses an octal header field, which may be padded with spaces or NULs.
+fn octal(field: &[u8]) -> Option<u64> {
+    let digits = field
+        .iter()
+        .skip_while(|&&b| b == b' ')
+        .take_while(|&&b| b != b' ' && b != 0);
+    let mut n: u64 = 0;
+    let mut any = false;
+    for &b in digits {
+        if !(b'0'..=b'7').contains(&b) {
+            return None;
+        }
+        n = n.checked_mul(8)?.checked_add(u64::from(b - b'0'))?;
+        any = true;
+    }
+    any.then_some(n)
+}
+
+/// Returns the `path` in pax extended header records, each of the form
+/// `<len> <key>=<value>\n`.
+fn pax_path(mut records: &[u8]) -> Option<String> {
+    let mut path = None;
+    while !records.is_empty() {
+        let space = records.iter().position(|&b| b == b' ')?;
+        let len: usize = core::str::from_utf8(&records[..space]).ok()?.parse().ok()?;
+        let record = records.get(space + 1..len)?.strip_suffix(b"\n")?;
+        if let Some(value) = record.strip_prefix(b"path=") {
+            path = Some(String::from_utf8_lossy(value).into_owned());
+        }
+        records = &records[len..];
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        format,
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+
+    use super::{TarError, TarEvent, TarJsonExtractor};
+    use crate::{ParseEvent, ParserOptions, Value, event::reconstruct_values, tests::events};
+
+    /// Returns a ustar header for an entry.
+    fn header(name: &str, size: usize, typeflag: u8) -> Vec<u8> {
+        let mut block = vec![0; 512];
+        let (prefix, name) = match name.len() {
+            0..=100 => ("", name),
+            _ => name.split_at(name.rfind('/').unwrap()),
+        };
+        let name = name.trim_start_matches('/');
+        block[..name.len()].copy_from_slice(name.as_bytes());
+        block[100..107].copy_from_slice(b"0000644");
+        block[124..135].copy_from_slice(format!("{size:011o}").as_bytes());
+        block[136..147].copy_from_slice(b"00000000000");
+        block[156] = typeflag;
+        block[257..263].copy_from_slice(b"ustar\0");
+        block[263..265].copy_from_slice(b"00");
+        block[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+        block[148..156].fill(b' ');
+        let sum: u32 = block.iter().map(|&b| u32::from(b)).sum();
+        block[148..155].copy_from_slice(format!("{sum:06o}\0").as_bytes());
+        block
+    }
+
+    fn entry(archive: &mut Vec<u8>, name: &str, typeflag: u8, content: &[u8]) {
+        archive.extend(header(name, content.len(), typeflag));
+        archive.extend(content);
+        archive.resize(archive.len().next_multiple_of(512), 0);
+    }
+
+    /// Feeds `archive` in chunks of `chunk_size` bytes, rebuilding the value
+    /// of each JSON file.
+    fn extract(archive: &[u8], chunk_size: usize) -> Result<Vec<(String, Value)>, TarError> {
+        let mut extractor = TarJsonExtractor::new(ParserOptions::default());
+        let mut files: Vec<(String, Vec<ParseEvent>)> = Vec::new();
+        for chunk in archive.chunks(chunk_size) {
+            for event in extractor.feed(chunk)? {
+                match event {
+                    TarEvent::File { filename } => files.push((filename.to_string(), Vec::new())),
+                    TarEvent::Event(event) => files.last_mut().unwrap().1.push(event),
+                }
+            }
+        }
+        extractor.finish()?;
+        assert_eq!(extractor.json_files(), files.len());
+        Ok(files
+            .into_iter()
+            .map(|(name, events)| {
+                let mut values = reconstruct_values(events);
+                assert_eq!(values.len(), 1);
+                (name, values.remove(0))
+            })
+            .collect())
+    }
+
+    fn value(json: &str) -> Value {
+        reconstruct_values(events(json, 1, ParserOptions::default())).remove(0)
+    }
+
+    #[test]
+    fn extracts_json_files_in_any_chunking() {
+        let mut archive = Vec::new();
+        entry(&mut archive, "data/", b'5', b"");
+        entry(&mut archive, "data/readme.txt", b'0', b"not json {");
+        entry(&mut archive, "data/a.json", b'0', br#"{"a": [1, 2, "x"]}"#);
+        let long = "x".repeat(600);
+        entry(
+            &mut archive,
+            "data/b.JSON",
+            b'0',
+            format!(r#""{long}""#).as_bytes(),
+        );
+        entry(&mut archive, "data/link.json", b'2', b"");
+        entry(&mut archive, "data/c.json", b'0', b"  null\n");
+        archive.resize(archive.len() + 1024, 0);
+        // Anything after the end of the archive is ignored.
+        archive.extend(b"trailing");
+
+        let expected = vec![
+            ("data/a.json".into(), value(r#"{"a": [1, 2, "x"]}"#)),
+            ("data/b.JSON".into(), Value::String(long)),
+            ("data/c.json".into(), Value::Null),
+        ];
+        for chunk_size in [1, 7, 100, 511, 512, 513, 1024, 1 << 16] {
+            assert_eq!(
+                extract(&archive, chunk_size).unwrap(),
+                expected,
+                "{chunk_size}"
+            );
+        }
+    }
+
+    #[test]
+    fn reads_long_names() {
+        let deep = format!("{}/deep.json", "d".repeat(120));
+        let gnu = format!("{}.json", "g".repeat(150));
+        let pax = format!("{}.json", "p".repeat(200));
+        let record = format!("path={pax}\n");
+        let record = format!("{} {record}", record.len() + 4);
+
+        let mut archive = Vec::new();
+        entry(&mut archive, &deep, b'0', b"1");
+        entry(
+            &mut archive,
+            "././@LongLink",
+            b'L',
+            format!("{gnu}\0").as_bytes(),
+        );
+        entry(&mut archive, "truncated", b'0', b"2");
+        entry(&mut archive, "PaxHeaders/x", b'x', record.as_bytes());
+        entry(&mut archive, "truncated.txt", b'0', b"3");
+        entry(&mut archive, "plain.json", b'0', b"4");
+
+        let names: Vec<_> = extract(&archive, 64)
+            .unwrap()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, [deep, gnu, pax, "plain.json".into()]);
+    }
+
+    #[test]
+    fn long_names_of_skipped_entries_are_dropped() {
+        let dir = format!("{}.json/", "d".repeat(150));
+        let mut archive = Vec::new();
+        entry(
+            &mut archive,
+            "././@LongLink",
+            b'L',
+            format!("{dir}\0").as_bytes(),
+        );
+        entry(&mut archive, "truncated", b'5', b"");
+        entry(&mut archive, "data.json", b'0', b"[true]");
+
+        assert_eq!(
+            extract(&archive, 64).unwrap(),
+            [("data.json".into(), value("[true]"))]
+        );
+    }
+
+    #[test]
+    fn reports_errors() {
+        let mut archive = Vec::new();
+        entry(&mut archive, "a.json", b'0', b"[1]");
+        let mut bad = archive.clone();
+        entry(&mut bad, "b.json", b'0', b"[2]");
+        bad[512 + 512] ^= 1;
+        assert_eq!(
+            extract(&bad, 100),
+            Err(TarError::Malformed { byte_offset: 1024 })
+        );
+
+        let mut invalid = archive.clone();
+        entry(&mut invalid, "b.json", b'0', b"[2 3]");
+        assert!(matches!(
+            extract(&invalid, 100),
+            Err(TarError::Parser { filename, .. }) if &*filename == "b.json"
+        ));
+
+        assert_eq!(extract(&archive[..600], 100), Err(TarError::Truncated));
+        // Without the closing zero blocks, the archive may end between entries.
+        assert_eq!(extract(&archive, 100).unwrap().len(), 1);
+    }
+}
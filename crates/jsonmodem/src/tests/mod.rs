@@ -1,11 +1,30 @@
 mod arbitrary;
 mod parse_bad;
 mod parse_good;
+mod property_event_builder;
 mod property_multivalue;
 mod property_partition;
 mod repro;
 pub mod utils;
+mod value;
 
 mod chunk_helpers;
 
+mod snapshot_buffered;
+mod snapshot_diff;
 mod snapshot_events;
+
+use alloc::vec::Vec;
+
+use crate::{ParseEvent, ParserOptions, StreamingParser, produce_chunks};
+
+/// Parses `json` with `options`, fed in `parts` chunks, returning its events.
+pub(crate) fn events(json: &str, parts: usize, options: ParserOptions) -> Vec<ParseEvent> {
+    let mut parser = StreamingParser::new(options);
+    let mut events = Vec::new();
+    for chunk in produce_chunks(json, parts) {
+        events.extend(parser.feed(chunk).map(Result::unwrap));
+    }
+    events.extend(parser.finish().map(Result::unwrap));
+    events
+}
@@ -1,6 +1,9 @@
 use alloc::{format, string::ToString, vec::Vec};
 
-use crate::{ParserOptions, StreamingParser, Value, options::NonScalarValueMode, value::Map};
+use crate::{
+    BomHandling, DecodeMode, ParserOptions, StreamingParser, StringValueMode, Value,
+    options::NonScalarValueMode, value::Map,
+};
 
 #[test]
 fn error_empty_document() {
@@ -179,6 +182,155 @@ fn error_invalid_unicode_escapes() {
     assert_eq!(err.column, 7);
 }
 
+#[test]
+fn error_unpaired_surrogates() {
+    for json in [
+        r#""\uD83D""#,
+        r#""\uDE00""#,
+        r#""\uD83Dx""#,
+        r#""\uD83D\n""#,
+        r#""\uD83D\u0041""#,
+    ] {
+        let mut parser = StreamingParser::new(ParserOptions::default());
+        let err = parser.feed(json).last().unwrap().unwrap_err();
+        assert!(
+            err.to_string().contains("unpaired surrogate"),
+            "{json}: {err}"
+        );
+    }
+}
+
+fn finish_with_pending_surrogate(options: ParserOptions) -> crate::ParserError {
+    let mut parser = StreamingParser::new(options);
+    assert!(parser.feed(r#"["a\uD83D"#).all(|r| r.is_ok()));
+    parser.finish().last().unwrap().unwrap_err()
+}
+
+#[test]
+fn error_truncated_surrogate_pair_on_finish() {
+    for decode_mode in [DecodeMode::StrictUnicode, DecodeMode::ReplaceInvalid] {
+        let err = finish_with_pending_surrogate(ParserOptions {
+            decode_mode,
+            error_on_truncated_surrogate_pair: true,
+            ..Default::default()
+        });
+        assert_eq!(err.to_string(), "truncated surrogate pair at end of input");
+    }
+}
+
+#[test]
+fn error_pending_surrogate_on_finish_follows_decode_mode() {
+    let err = finish_with_pending_surrogate(ParserOptions::default());
+    assert_eq!(
+        err.to_string(),
+        "Invalid unicode escape sequence: unpaired surrogate \\uD83D"
+    );
+
+    let err = finish_with_pending_surrogate(ParserOptions {
+        decode_mode: DecodeMode::ReplaceInvalid,
+        ..Default::default()
+    });
+    assert_eq!(err.to_string(), "JSON5: invalid end of input");
+}
+
+#[test]
+fn error_cesu8_unpaired_surrogate() {
+    let options = ParserOptions {
+        decode_mode: DecodeMode::Cesu8,
+        ..Default::default()
+    };
+    // A high surrogate followed by a character other than a low surrogate.
+    let mut parser = StreamingParser::new(options);
+    let err = parser.feed_bytes(b"[\"\xED\xA0\xBDx\"]").err().unwrap();
+    assert_eq!(err.byte_offset, 2);
+
+    // A low surrogate alone, after a valid pair split across chunks.
+    let mut parser = StreamingParser::new(options);
+    assert_eq!(parser.feed_bytes(b"\"\xED\xA0\xBD\xED").unwrap().count(), 0);
+    assert_eq!(parser.feed_bytes(b"\xB8\x80").unwrap().count(), 0);
+    let err = parser.feed_bytes(b"\xED\xB8\x80\"").err().unwrap();
+    assert_eq!(err.byte_offset, 7);
+
+    // A pair cut short by the end of input.
+    let mut parser = StreamingParser::new(options);
+    assert_eq!(parser.feed_bytes(b"\"\xED\xA0\xBD\xED").unwrap().count(), 0);
+    let err = parser.finish().last().unwrap().unwrap_err();
+    assert_eq!(err.byte_offset, 5);
+}
+
+#[test]
+fn error_single_quoted_strings() {
+    let mut parser = StreamingParser::new(ParserOptions::default());
+    let err = parser.feed("['a']").last().unwrap().unwrap_err();
+    assert_eq!(err.to_string(), "JSON5: invalid character '\\'' at 1:2");
+
+    // `\'` is only an escape inside single quotes.
+    let mut parser = StreamingParser::new(ParserOptions {
+        allow_single_quoted_strings: true,
+        ..Default::default()
+    });
+    let err = parser.feed(r#"["\'"]"#).last().unwrap().unwrap_err();
+    assert_eq!(err.to_string(), "JSON5: invalid character '\\'' at 1:4");
+}
+
+#[test]
+fn error_leading_bom_rejected() {
+    let options = ParserOptions {
+        bom_handling: BomHandling::Reject,
+        ..Default::default()
+    };
+    let mut parser = StreamingParser::new(options);
+    let err = parser.feed("\u{FEFF}[]").last().unwrap().unwrap_err();
+    assert_eq!(err.to_string(), "unexpected byte order mark");
+    assert_eq!((err.line, err.column, err.byte_offset), (1, 1, 0));
+
+    // Only the start of the input is checked.
+    let mut parser = StreamingParser::new(options);
+    assert!(parser.feed(" \u{FEFF}[]").all(|r| r.is_ok()));
+}
+
+#[test]
+fn error_invalid_utf8_bytes() {
+    let mut parser = StreamingParser::new(ParserOptions::default());
+    assert_eq!(parser.feed_bytes(b"[\"ab").unwrap().count(), 1);
+    let err = parser.feed_bytes(b"c\xFF\"]").err().unwrap();
+    assert_eq!(err.to_string(), "invalid UTF-8");
+    assert_eq!(err.byte_offset, 5);
+
+    // The rejected chunk is dropped, and the parser continues.
+    assert!(parser.feed_bytes(&[0xC3]).unwrap().all(|r| r.is_ok()));
+    let err = parser.feed_bytes(b"(\"]").err().unwrap();
+    assert_eq!(err.byte_offset, 4);
+    let events: Vec<_> = parser.feed_bytes(b"\xA9\"]").unwrap().collect();
+    assert!(events.iter().all(Result::is_ok));
+    assert_eq!(events.len(), 2);
+}
+
+#[test]
+fn error_incomplete_utf8_at_end() {
+    let mut parser = StreamingParser::new(ParserOptions::default());
+    assert!(
+        parser
+            .feed_bytes(b"[1, \xE2\x82")
+            .unwrap()
+            .all(|r| r.is_ok())
+    );
+    let err = parser.finish().last().unwrap().unwrap_err();
+    assert!(
+        err.to_string().starts_with("JSON5: invalid character"),
+        "{err}"
+    );
+    assert_eq!(err.byte_offset, 4);
+
+    // Inside a string the replacement character is accepted, and the error at
+    // end of input counts the incomplete character by its length in the input.
+    let mut parser = StreamingParser::new(ParserOptions::default());
+    assert!(parser.feed_bytes(b"\"ab\xC2").unwrap().all(|r| r.is_ok()));
+    let err = parser.finish().last().unwrap().unwrap_err();
+    assert_eq!(err.to_string(), "JSON5: invalid end of input");
+    assert_eq!(err.byte_offset, 4);
+}
+
 // Escaped digits 1–9
 #[test]
 fn error_escaped_digit_1_to_9() {
@@ -383,3 +535,157 @@ fn error_incorrectly_completed_partial_string_with_suffixes() {
         assert_eq!(err.column, 6);
     }
 }
+
+fn parse_with_string_limit(
+    limit: usize,
+    mode: StringValueMode,
+    chunks: &[&str],
+) -> Result<(), crate::ParserError> {
+    let mut parser = StreamingParser::new(ParserOptions {
+        max_string_bytes: Some(limit),
+        string_value_mode: mode,
+        ..Default::default()
+    });
+    for chunk in chunks {
+        for event in parser.feed(chunk) {
+            event?;
+        }
+    }
+    for event in parser.finish() {
+        event?;
+    }
+    Ok(())
+}
+
+#[test]
+fn max_string_bytes_allows_strings_at_limit() {
+    for mode in [
+        StringValueMode::None,
+        StringValueMode::Values,
+        StringValueMode::Prefixes,
+    ] {
+        assert!(parse_with_string_limit(5, mode, &[r#"["abcde"]"#]).is_ok());
+        // "é" is two bytes, the escape decodes to one.
+        assert!(parse_with_string_limit(5, mode, &[r#"["é\u0041bc"]"#]).is_ok());
+        assert!(parse_with_string_limit(5, mode, &[r#"{"abcde": 1}"#]).is_ok());
+    }
+}
+
+#[test]
+fn error_max_string_bytes_one_over_limit() {
+    for mode in [
+        StringValueMode::None,
+        StringValueMode::Values,
+        StringValueMode::Prefixes,
+    ] {
+        let err = parse_with_string_limit(5, mode, &[r#"["abcdef"]"#]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "string exceeds the maximum length of 5 bytes"
+        );
+        assert!(parse_with_string_limit(5, mode, &[r#"["abcd\n\n"]"#]).is_err());
+        assert!(parse_with_string_limit(5, mode, &[r#"{"abcdef": 1}"#]).is_err());
+    }
+}
+
+#[test]
+fn error_max_string_bytes_across_feeds() {
+    for mode in [
+        StringValueMode::None,
+        StringValueMode::Values,
+        StringValueMode::Prefixes,
+    ] {
+        assert!(parse_with_string_limit(6, mode, &["\"ab", "cd", "ef\""]).is_ok());
+        assert!(parse_with_string_limit(6, mode, &["\"ab", "cd", "ef", "g\""]).is_err());
+    }
+}
+
+#[test]
+fn max_string_bytes_is_per_string() {
+    let chunks = [r#"{"abcd": "ef"#, r#"gh", "ijkl": ["mnop", "qrst"]}"#];
+    assert!(parse_with_string_limit(4, StringValueMode::None, &chunks).is_ok());
+    assert!(parse_with_string_limit(3, StringValueMode::None, &chunks).is_err());
+}
+
+#[test]
+fn max_string_bytes_ignores_numbers() {
+    assert!(parse_with_string_limit(2, StringValueMode::None, &["[1234567, 1.2345e10]"]).is_ok());
+}
+
+fn parse_with_nesting_limit(limit: usize, chunks: &[&str]) -> Result<usize, crate::ParserError> {
+    let mut parser = StreamingParser::new(ParserOptions {
+        max_nesting_change_per_token: Some(limit),
+        allow_multiple_json_values: true,
+        ..Default::default()
+    });
+    let mut events = 0;
+    for chunk in chunks {
+        for event in parser.feed(chunk) {
+            event?;
+            events += 1;
+        }
+    }
+    for event in parser.finish() {
+        event?;
+        events += 1;
+    }
+    Ok(events)
+}
+
+#[test]
+fn max_nesting_change_per_token_counts_runs() {
+    let deep = "[[[[[[]]]]]]";
+    let bytes = deep.split_inclusive(|_| true).collect::<Vec<_>>();
+    for chunks in [&[deep][..], &bytes] {
+        assert_eq!(parse_with_nesting_limit(12, chunks).unwrap(), 12);
+        let err = parse_with_nesting_limit(11, chunks).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "more than 11 arrays or objects opened or closed in a row"
+        );
+    }
+    // The key and colon end the first run of two.
+    assert!(parse_with_nesting_limit(3, &["[{", "\"a\": [", "]}]"]).is_err());
+    assert!(parse_with_nesting_limit(4, &["[{", "\"a\": [", "]}]"]).is_ok());
+}
+
+#[test]
+fn max_nesting_change_per_token_allows_wide_documents() {
+    let wide = format!("[{}]", ["[]"; 50].join(","));
+    assert_eq!(parse_with_nesting_limit(3, &[&wide]).unwrap(), 102);
+    assert!(parse_with_nesting_limit(2, &[&wide]).is_err());
+}
+
+#[test]
+fn max_nesting_change_per_token_repeated_pairs() {
+    // Each root value starts a new run, and the frame stack ends up empty
+    // after every pair.
+    let pairs = "[] {} ".repeat(1_000);
+    let chunks = pairs.split_inclusive(|_| true).collect::<Vec<_>>();
+    assert_eq!(parse_with_nesting_limit(2, &chunks).unwrap(), 4_000);
+    assert_eq!(parse_with_nesting_limit(2, &[&pairs]).unwrap(), 4_000);
+    assert!(parse_with_nesting_limit(1, &["[]"]).is_err());
+}
+
+#[test]
+fn error_on_finish_before_a_value() {
+    for json in ["[", "[1,", r#"{"a":"#, r#"{"a": 1, "b": [{}, "#] {
+        let mut parser = StreamingParser::new(ParserOptions::default());
+        assert!(parser.feed(json).all(|r| r.is_ok()), "{json}");
+        let err = parser.finish().last().unwrap().unwrap_err();
+        assert_eq!(err.to_string(), "JSON5: invalid end of input", "{json}");
+    }
+}
+
+#[test]
+fn closed_parser_is_not_exhausted_after_truncated_input() {
+    for (json, remaining) in [(r#"{"a": [1, "b"#, 0), ("[1] [2]", 3), ("[1, x, 2]", 5)] {
+        let mut parser = StreamingParser::new(ParserOptions::default());
+        let _ = parser.feed(json);
+        let mut closed = parser.finish();
+        assert!(closed.by_ref().any(|event| event.is_err()));
+        assert!(closed.next().is_none());
+        assert_eq!(closed.remaining_bytes(), remaining, "{json}");
+        assert!(!closed.is_exhausted(), "{json}");
+    }
+}
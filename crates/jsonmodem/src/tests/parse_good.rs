@@ -1,8 +1,9 @@
-use alloc::{vec, vec::Vec};
+use alloc::{string::ToString, vec, vec::Vec};
 
 use crate::{
-    ParseEvent, StreamingParser, Value,
-    options::{NonScalarValueMode, ParserOptions},
+    BomHandling, ParseEvent, StreamingParser, Value,
+    options::{DecodeMode, NonScalarValueMode, ParserOptions},
+    tests::events,
     value::Map,
 };
 
@@ -44,6 +45,12 @@ fn finish_seq(chunks: &[&str]) -> Value {
         ParseEvent::ObjectEnd { value, .. } => Value::Object(core::mem::take(
             value.as_mut().expect("expected object value"),
         )),
+        ParseEvent::StringComplete { value, .. } => Value::String(core::mem::take(value)),
+        ParseEvent::ScalarBegin { .. }
+        | ParseEvent::ScalarEnd { .. }
+        | ParseEvent::ObjectKey { .. } => {
+            unreachable!("scalar boundary and key events are not enabled")
+        }
     }
 }
 
@@ -231,6 +238,218 @@ fn test_strings_and_escapes() {
     );
 }
 
+#[test]
+fn test_single_quoted_strings() {
+    let parse = |json: &str, parts: usize| {
+        let options = ParserOptions {
+            allow_single_quoted_strings: true,
+            non_scalar_values: NonScalarValueMode::Roots,
+            ..Default::default()
+        };
+        match events(json, parts, options).pop() {
+            Some(ParseEvent::ObjectEnd {
+                value: Some(value), ..
+            }) => Value::Object(value),
+            other => panic!("unexpected event {other:?}"),
+        }
+    };
+    let json = r#"{'': '', 'it\'s': 'say "hi"\n', "mixed": ['a', "b\"", '\u00e9\\']}"#;
+    let expected =
+        finish_seq(&[r#"{"": "", "it's": "say \"hi\"\n", "mixed": ["a", "b\"", "\u00e9\\"]}"#]);
+    for parts in [1, 3, json.len()] {
+        assert_eq!(parse(json, parts), expected, "{parts}");
+    }
+}
+
+#[test]
+fn test_leading_bom_skipped() {
+    // With four parts the BOM is a chunk of its own.
+    for parts in [1, 4] {
+        let options = ParserOptions {
+            bom_handling: BomHandling::Skip,
+            ..Default::default()
+        };
+        assert_eq!(events("\u{FEFF}[1]", parts, options).len(), 3);
+    }
+}
+
+#[test]
+fn test_bom_split_across_byte_feeds() {
+    let mut parser = StreamingParser::new(ParserOptions {
+        bom_handling: BomHandling::Skip,
+        ..Default::default()
+    });
+    for byte in [0xEF, 0xBB, 0xBF] {
+        assert_eq!(parser.feed_bytes(&[byte]).unwrap().count(), 0);
+    }
+    let events: Vec<_> = parser.feed_bytes(b"[]").unwrap().collect();
+    assert_eq!(events.len(), 2);
+    assert!(events.iter().all(Result::is_ok));
+
+    let mut parser = StreamingParser::new(ParserOptions {
+        bom_handling: BomHandling::Reject,
+        ..Default::default()
+    });
+    assert_eq!(parser.feed_bytes(&[0xEF, 0xBB]).unwrap().count(), 0);
+    let err = parser.feed_bytes(b"\xBF[]").unwrap().last().unwrap();
+    assert_eq!(err.unwrap_err().to_string(), "unexpected byte order mark");
+}
+
+#[test]
+fn test_feed_bytes_split_characters() {
+    let json = r#"{"k": "é€😀", "€": 1}"#.as_bytes();
+    let expected = finish_seq(&[r#"{"k": "é€😀", "€": 1}"#]);
+    for step in 1..=4 {
+        let mut parser = StreamingParser::new(ParserOptions {
+            non_scalar_values: NonScalarValueMode::Roots,
+            ..Default::default()
+        });
+        let mut events = Vec::new();
+        for chunk in json.chunks(step) {
+            events.extend(parser.feed_bytes(chunk).unwrap().map(Result::unwrap));
+        }
+        events.extend(parser.finish().map(Result::unwrap));
+        match events.pop() {
+            Some(ParseEvent::ObjectEnd {
+                value: Some(value), ..
+            }) => assert_eq!(Value::Object(value), expected),
+            other => panic!("unexpected event {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn test_feed_bytes_cesu8_surrogate_pairs() {
+    // U+1F600 and U+10FFFF, each as two three-byte surrogates.
+    let json = b"[\"a\xED\xA0\xBD\xED\xB8\x80b\", \"\xED\xAF\xBF\xED\xBF\xBF\"]";
+    let expected = finish_seq(&["[\"a\u{1F600}b\", \"\u{10FFFF}\"]"]);
+    for step in 1..=7 {
+        let mut parser = StreamingParser::new(ParserOptions {
+            decode_mode: DecodeMode::Cesu8,
+            non_scalar_values: NonScalarValueMode::Roots,
+            ..Default::default()
+        });
+        let mut events = Vec::new();
+        for chunk in json.chunks(step) {
+            events.extend(parser.feed_bytes(chunk).unwrap().map(Result::unwrap));
+        }
+        assert_eq!(parser.bytes_consumed(), json.len(), "{step}");
+        events.extend(parser.finish().map(Result::unwrap));
+        match events.pop() {
+            Some(ParseEvent::ArrayEnd {
+                value: Some(value), ..
+            }) => assert_eq!(Value::Array(value), expected, "{step}"),
+            other => panic!("unexpected event {other:?}"),
+        }
+    }
+
+    // Other modes reject CESU-8.
+    let mut parser = StreamingParser::new(ParserOptions::default());
+    assert!(parser.feed_bytes(json).is_err());
+}
+
+#[test]
+fn test_feed_bytes_replaces_encoded_surrogates() {
+    // Lone surrogates as WTF-8, and a pair encoded as CESU-8, which is
+    // replaced half by half.
+    let json = b"[\"a\xED\xA0\xBDb\", \"\xED\xBF\xBF\xED\xA0\xBD\xED\xB8\x80\xED\x9F\xBF\"]";
+    let expected = finish_seq(&["[\"a\u{FFFD}b\", \"\u{FFFD}\u{FFFD}\u{FFFD}\u{D7FF}\"]"]);
+    for step in 1..=7 {
+        let mut parser = StreamingParser::new(ParserOptions {
+            decode_mode: DecodeMode::ReplaceInvalid,
+            non_scalar_values: NonScalarValueMode::Roots,
+            ..Default::default()
+        });
+        let mut events = Vec::new();
+        for chunk in json.chunks(step) {
+            events.extend(parser.feed_bytes(chunk).unwrap().map(Result::unwrap));
+        }
+        assert_eq!(parser.bytes_consumed(), json.len(), "{step}");
+        events.extend(parser.finish().map(Result::unwrap));
+        match events.pop() {
+            Some(ParseEvent::ArrayEnd {
+                value: Some(value), ..
+            }) => assert_eq!(Value::Array(value), expected, "{step}"),
+            other => panic!("unexpected event {other:?}"),
+        }
+    }
+
+    // Other invalid UTF-8 is still rejected.
+    let mut parser = StreamingParser::new(ParserOptions {
+        decode_mode: DecodeMode::ReplaceInvalid,
+        ..Default::default()
+    });
+    let err = parser.feed_bytes(b"[\"\xED\xA0\xBD\xFF\"]").err().unwrap();
+    assert_eq!(err.byte_offset, 5);
+    assert!(
+        StreamingParser::new(ParserOptions::default())
+            .feed_bytes(b"\"\xED\xA0\xBD\"")
+            .is_err()
+    );
+}
+
+#[test]
+fn test_surrogate_pairs() {
+    assert_eq!(
+        finish_seq(&[r#""\uD83D\uDE00""#]),
+        Value::String("\u{1F600}".into())
+    );
+    assert_eq!(
+        finish_seq(&[r#""a\uD8"#, r"3D\u", r#"de00b""#]),
+        Value::String("a\u{1F600}b".into())
+    );
+    assert_eq!(
+        finish_seq(&[r#""\uD83D"#, r#"\uDE00\uDBFF\uDFFF""#]),
+        Value::String("\u{1F600}\u{10FFFF}".into())
+    );
+    // Every split point, including inside the low half's digits.
+    let json = r#""\uD83D\uDE00""#;
+    for at in 1..json.len() {
+        assert_eq!(
+            finish_seq(&[&json[..at], &json[at..]]),
+            Value::String("\u{1F600}".into())
+        );
+    }
+}
+
+#[test]
+fn test_surrogate_pair_split_across_fragments() {
+    let mut parser = StreamingParser::new(ParserOptions::default());
+    let mut fragments = Vec::new();
+    for chunk in ["\"x\\uD83D", "\\uDE00y\""] {
+        for event in parser.feed(chunk) {
+            if let ParseEvent::String { fragment, .. } = event.unwrap() {
+                fragments.push(fragment);
+            }
+        }
+    }
+    // The high surrogate is held back until its low half arrives.
+    assert_eq!(fragments, vec!["x".to_string(), "\u{1F600}y".to_string()]);
+}
+
+#[test]
+fn test_replace_unpaired_surrogates() {
+    let replace = |json: &str| {
+        let mut parser = StreamingParser::new(ParserOptions {
+            decode_mode: DecodeMode::ReplaceInvalid,
+            string_value_mode: crate::StringValueMode::Values,
+            ..Default::default()
+        });
+        parser.feed(json);
+        match parser.finish().last().unwrap().unwrap() {
+            ParseEvent::String { value, .. } => value.unwrap(),
+            other => panic!("unexpected event {other:?}"),
+        }
+    };
+
+    assert_eq!(replace(r#""\uD83D""#), "\u{FFFD}");
+    assert_eq!(replace(r#""\uDE00""#), "\u{FFFD}");
+    assert_eq!(replace(r#""a\uD83Db""#), "a\u{FFFD}b");
+    assert_eq!(replace(r#""\uD83D\n""#), "\u{FFFD}\n");
+    assert_eq!(replace(r#""\uD83D\uD83D\uDE00""#), "\u{FFFD}\u{1F600}");
+    assert_eq!(replace(r#""\uD83D\u0041""#), "\u{FFFD}A");
+}
+
 #[test]
 fn test_whitespace_inside() {
     assert_eq!(finish_seq(&["{\t\n  \r}\n"]), Value::Object(Map::new()));
@@ -279,3 +498,25 @@ fn test_streaming_multiple_values() {
     let evts: Vec<_> = parser.feed("   ").map(Result::unwrap).collect();
     assert!(evts.is_empty());
 }
+
+#[test]
+fn closed_parser_is_exhausted_after_complete_input() {
+    for (json, multiple) in [
+        (r#"{"a": [1, "b"]} "#, false),
+        ("1", false),
+        ("1 [2] ", true),
+        ("", true),
+    ] {
+        let mut parser = StreamingParser::new(ParserOptions {
+            allow_multiple_json_values: multiple,
+            ..Default::default()
+        });
+        let _ = parser.feed(json);
+        let mut closed = parser.finish();
+        assert_eq!(closed.remaining_bytes(), json.len());
+        assert_eq!(closed.is_exhausted(), json.is_empty());
+        assert!(closed.by_ref().all(|event| event.is_ok()));
+        assert_eq!(closed.remaining_bytes(), 0, "{json}");
+        assert!(closed.is_exhausted(), "{json}");
+    }
+}
@@ -0,0 +1,65 @@
+use alloc::{string::ToString, vec::Vec};
+
+use quickcheck::QuickCheck;
+
+use crate::{EventBuilder, ParseEvent, ParserOptions, PathComponent, StreamingParser, Value};
+
+/// Appends the events of `value`, at `path`, to `builder`.
+fn build(builder: EventBuilder, path: &mut Vec<PathComponent>, value: &Value) -> EventBuilder {
+    match value {
+        Value::Null => builder.null(path.clone()),
+        Value::Boolean(b) => builder.boolean(path.clone(), *b),
+        Value::Number(n) => builder.number(path.clone(), *n),
+        Value::String(s) => builder.string(path.clone(), s.clone(), true),
+        Value::Array(items) => {
+            let mut builder = builder.begin_array(path.clone());
+            for (i, item) in items.iter().enumerate() {
+                path.push(PathComponent::Index(i));
+                builder = build(builder, path, item);
+                path.pop();
+            }
+            builder.end_array(path.clone())
+        }
+        Value::Object(members) => {
+            let mut builder = builder.begin_object(path.clone());
+            for (key, member) in members {
+                path.push(PathComponent::Key(key.clone()));
+                builder = build(builder, path, member);
+                path.pop();
+            }
+            builder.end_object(path.clone())
+        }
+    }
+}
+
+/// Property: the events built for any value are balanced and equal to those
+/// the parser produces for its text.
+#[test]
+fn built_events_match_parsed_events_quickcheck() {
+    #[expect(clippy::needless_pass_by_value)]
+    fn prop(value: Value) -> bool {
+        let built = build(EventBuilder::new(), &mut Vec::new(), &value)
+            .try_build()
+            .expect("events built from a value are balanced");
+
+        let mut parser = StreamingParser::new(ParserOptions::default());
+        let mut parsed: Vec<ParseEvent> = parser
+            .feed(&value.to_string())
+            .map(Result::unwrap)
+            .collect();
+        parsed.extend(parser.finish().map(Result::unwrap));
+        built == parsed
+    }
+
+    let tests = if cfg!(any(miri, feature = "test-fast")) {
+        10
+    } else if is_ci::cached() {
+        10_000
+    } else {
+        1_000
+    };
+
+    QuickCheck::new()
+        .tests(tests)
+        .quickcheck(prop as fn(Value) -> bool);
+}
@@ -0,0 +1,289 @@
+//! Snapshot tests for the events produced by `JsonModemBuffers`, mirroring
+//! `snapshot_events` with the input split across several feeds so that string
+//! fragments have to be coalesced.
+#![cfg(not(miri))]
+
+use alloc::vec::Vec;
+
+use crate::{
+    BufferOptions, BufferedEvent, JsonModemBuffers, ParseEvent, ParserOptions, StreamingParser,
+    StringValueMode, produce_chunks,
+};
+
+const JSON: &str = r#"{
+        "users": [
+            {"id": 1, "name": "Ada"},
+            {"id": 2, "name": "Grace"}
+        ],
+        "meta": {"count": 2}
+    }"#;
+
+fn buffered_events(string_buffer_mode: StringValueMode) -> Vec<BufferedEvent> {
+    buffered_events_in(string_buffer_mode, 24)
+}
+
+fn buffered_events_in(string_buffer_mode: StringValueMode, parts: usize) -> Vec<BufferedEvent> {
+    let mut modem = JsonModemBuffers::new(
+        ParserOptions::default(),
+        BufferOptions { string_buffer_mode },
+    );
+    let mut events = Vec::new();
+    for chunk in produce_chunks(JSON, parts) {
+        events.extend(modem.feed(chunk).map(Result::unwrap));
+    }
+    events.extend(modem.finish().map(Result::unwrap));
+    events
+}
+
+#[test]
+fn snapshot_buffered_values() {
+    insta::assert_yaml_snapshot!(buffered_events(StringValueMode::Values), @r"
+    - kind: ObjectBegin
+      path: []
+    - kind: ArrayStart
+      path:
+        - users
+    - kind: ObjectBegin
+      path:
+        - users
+        - 0
+    - kind: Number
+      path:
+        - users
+        - 0
+        - id
+      value: 1
+    - kind: String
+      path:
+        - users
+        - 0
+        - name
+      fragment: Ad
+    - kind: String
+      path:
+        - users
+        - 0
+        - name
+      value: Ada
+      fragment: a
+      is_final: true
+    - kind: ObjectEnd
+      path:
+        - users
+        - 0
+    - kind: ObjectBegin
+      path:
+        - users
+        - 1
+    - kind: Number
+      path:
+        - users
+        - 1
+        - id
+      value: 2
+    - kind: String
+      path:
+        - users
+        - 1
+        - name
+      value: Grace
+      fragment: Grace
+      is_final: true
+    - kind: ObjectEnd
+      path:
+        - users
+        - 1
+    - kind: ArrayEnd
+      path:
+        - users
+    - kind: ObjectBegin
+      path:
+        - meta
+    - kind: Number
+      path:
+        - meta
+        - count
+      value: 2
+    - kind: ObjectEnd
+      path:
+        - meta
+    - kind: ObjectEnd
+      path: []
+    ");
+}
+
+#[test]
+fn snapshot_buffered_prefixes() {
+    insta::assert_yaml_snapshot!(buffered_events(StringValueMode::Prefixes), @r"
+    - kind: ObjectBegin
+      path: []
+    - kind: ArrayStart
+      path:
+        - users
+    - kind: ObjectBegin
+      path:
+        - users
+        - 0
+    - kind: Number
+      path:
+        - users
+        - 0
+        - id
+      value: 1
+    - kind: String
+      path:
+        - users
+        - 0
+        - name
+      value: Ad
+      fragment: Ad
+    - kind: String
+      path:
+        - users
+        - 0
+        - name
+      value: Ada
+      fragment: a
+      is_final: true
+    - kind: ObjectEnd
+      path:
+        - users
+        - 0
+    - kind: ObjectBegin
+      path:
+        - users
+        - 1
+    - kind: Number
+      path:
+        - users
+        - 1
+        - id
+      value: 2
+    - kind: String
+      path:
+        - users
+        - 1
+        - name
+      value: Grace
+      fragment: Grace
+      is_final: true
+    - kind: ObjectEnd
+      path:
+        - users
+        - 1
+    - kind: ArrayEnd
+      path:
+        - users
+    - kind: ObjectBegin
+      path:
+        - meta
+    - kind: Number
+      path:
+        - meta
+        - count
+      value: 2
+    - kind: ObjectEnd
+      path:
+        - meta
+    - kind: ObjectEnd
+      path: []
+    ");
+}
+
+#[test]
+fn buffered_none_leaves_values_unset() {
+    let events = buffered_events(StringValueMode::None);
+    assert!(
+        events
+            .iter()
+            .all(|event| !matches!(event, BufferedEvent::String { value: Some(_), .. }))
+    );
+}
+
+/// The core parser buffers strings itself when `string_value_mode` is set, so
+/// that consumers building values with the default factory can watch a string
+/// grow without the buffering layer. Both must report the same events.
+fn core_events_in(string_value_mode: StringValueMode, parts: usize) -> Vec<ParseEvent> {
+    let mut parser = StreamingParser::new(ParserOptions {
+        string_value_mode,
+        ..Default::default()
+    });
+    let mut events = Vec::new();
+    for chunk in produce_chunks(JSON, parts) {
+        events.extend(parser.feed(chunk).map(Result::unwrap));
+    }
+    events.extend(parser.finish().map(Result::unwrap));
+    events
+}
+
+#[test]
+fn core_prefixes_match_buffered_prefixes() {
+    for parts in [1, 7, 24, JSON.len()] {
+        assert_eq!(
+            core_events_in(StringValueMode::Prefixes, parts),
+            buffered_events_in(StringValueMode::Prefixes, parts),
+            "split into {parts} chunks"
+        );
+    }
+}
+
+#[test]
+fn core_values_match_buffered_values() {
+    for parts in [1, 7, 24, JSON.len()] {
+        assert_eq!(
+            core_events_in(StringValueMode::Values, parts),
+            buffered_events_in(StringValueMode::Values, parts),
+            "split into {parts} chunks"
+        );
+    }
+}
+
+#[test]
+fn sink_receives_routed_string() {
+    use alloc::{rc::Rc, string::String};
+    use core::cell::RefCell;
+
+    use crate::{STRING_SINK_PLACEHOLDER, path};
+
+    let blob = "0123456789abcdef".repeat(256);
+    let json = alloc::format!(r#"{{"name": "report", "data": "{blob}", "size": 4096}}"#);
+    let sink = Rc::new(RefCell::new(Vec::<u8>::new()));
+    let mut modem = JsonModemBuffers::new(
+        ParserOptions::default(),
+        BufferOptions {
+            string_buffer_mode: StringValueMode::Values,
+        },
+    )
+    .with_string_sink(path!["data"], sink.clone());
+
+    let mut events = Vec::new();
+    for chunk in produce_chunks(&json, 40) {
+        events.extend(modem.feed(chunk).map(Result::unwrap));
+    }
+    events.extend(modem.finish().map(Result::unwrap));
+
+    assert_eq!(*sink.borrow(), blob.as_bytes());
+    let strings: Vec<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            BufferedEvent::String {
+                path,
+                fragment,
+                value,
+                is_final: true,
+            } => Some((path.clone(), fragment.clone(), value.clone())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        strings,
+        [
+            (path!["name"], String::from("report"), Some("report".into())),
+            (
+                path!["data"],
+                String::new(),
+                Some(STRING_SINK_PLACEHOLDER.into())
+            ),
+        ]
+    );
+}
@@ -0,0 +1,189 @@
+//! Snapshot tests for `json_diff` over typical API response changes.
+#![cfg(not(miri))]
+
+use alloc::vec::Vec;
+
+use crate::{DiffEvent, ParserOptions, StringValueMode, json_diff, tests::events};
+
+fn changes(left: &str, right: &str) -> Vec<DiffEvent> {
+    json_diff(
+        events(left, 1, ParserOptions::default()),
+        events(right, 1, ParserOptions::default()),
+    )
+    .filter(|event| !matches!(event, DiffEvent::Unchanged { .. }))
+    .collect()
+}
+
+#[test]
+fn snapshot_diff_api_response() {
+    let before = r#"{
+        "status": "ok",
+        "user": {"id": 7, "name": "Ada", "roles": ["admin", "dev"]},
+        "meta": {"page": 1, "cursor": "abc"}
+    }"#;
+    let after = r#"{
+        "user": {"name": "Ada Lovelace", "id": 7, "roles": ["admin"], "active": true},
+        "status": "ok",
+        "meta": {"page": 2}
+    }"#;
+
+    insta::assert_yaml_snapshot!(changes(before, after), @r"
+    - kind: Changed
+      path:
+        - user
+        - name
+      old:
+        String: Ada
+      new:
+        String: Ada Lovelace
+    - kind: Removed
+      path:
+        - user
+        - roles
+        - 1
+      value:
+        String: dev
+    - kind: Added
+      path:
+        - user
+        - active
+      value:
+        Boolean: true
+    - kind: Changed
+      path:
+        - meta
+        - page
+      old:
+        Number: 1
+      new:
+        Number: 2
+    - kind: Removed
+      path:
+        - meta
+        - cursor
+      value:
+        String: abc
+    ");
+}
+
+#[test]
+fn snapshot_diff_type_changes() {
+    let before = r#"{"a": {"b": 1}, "c": [1, 2], "d": null, "e": "1"}"#;
+    let after = r#"{"a": 2, "c": {"0": 1}, "d": false, "e": 1}"#;
+
+    insta::assert_yaml_snapshot!(changes(before, after), @r#"
+    - kind: Changed
+      path:
+        - a
+      old: Object
+      new:
+        Number: 2
+    - kind: Changed
+      path:
+        - c
+      old: Array
+      new: Object
+    - kind: Removed
+      path:
+        - c
+        - 0
+      value:
+        Number: 1
+    - kind: Removed
+      path:
+        - c
+        - 1
+      value:
+        Number: 2
+    - kind: Added
+      path:
+        - c
+        - "0"
+      value:
+        Number: 1
+    - kind: Changed
+      path:
+        - d
+      old: "Null"
+      new:
+        Boolean: false
+    - kind: Changed
+      path:
+        - e
+      old:
+        String: "1"
+      new:
+        Number: 1
+    - kind: Removed
+      path:
+        - a
+        - b
+      value:
+        Number: 1
+    "#);
+}
+
+#[test]
+fn snapshot_diff_unchanged() {
+    let json = r#"{"items": [{"id": 1}, {"id": 2}], "total": 2}"#;
+    let diff: Vec<DiffEvent> = json_diff(
+        events(json, 1, ParserOptions::default()),
+        events(json, 1, ParserOptions::default()),
+    )
+    .collect();
+
+    insta::assert_yaml_snapshot!(diff, @r"
+    - kind: Unchanged
+      path: []
+      value: Object
+    - kind: Unchanged
+      path:
+        - items
+      value: Array
+    - kind: Unchanged
+      path:
+        - items
+        - 0
+      value: Object
+    - kind: Unchanged
+      path:
+        - items
+        - 0
+        - id
+      value:
+        Number: 1
+    - kind: Unchanged
+      path:
+        - items
+        - 1
+      value: Object
+    - kind: Unchanged
+      path:
+        - items
+        - 1
+        - id
+      value:
+        Number: 2
+    - kind: Unchanged
+      path:
+        - total
+      value:
+        Number: 2
+    ");
+}
+
+#[test]
+fn diff_ignores_chunking_and_string_mode() {
+    let json = r#"{"message": "hello, world", "tags": ["x", "yz"]}"#;
+    let left = events(json, 5, ParserOptions::default());
+    let right = events(
+        json,
+        json.len(),
+        ParserOptions {
+            string_value_mode: StringValueMode::Prefixes,
+            ..Default::default()
+        },
+    );
+
+    assert!(json_diff(left, right).all(|event| matches!(event, DiffEvent::Unchanged { .. })));
+}
@@ -0,0 +1,300 @@
+use alloc::{string::ToString, vec, vec::Vec};
+
+use crate::{Map, ParserOptions, StreamingParser, Value, path};
+
+fn object<const N: usize>(entries: [(&str, Value); N]) -> Value {
+    Value::Object(
+        entries
+            .into_iter()
+            .map(|(k, v)| (k.into(), v))
+            .collect::<Map>(),
+    )
+}
+
+#[test]
+fn fill_defaults_missing_and_null_fields() {
+    let mut value = object([
+        ("id", Value::Number(7.0)),
+        ("name", Value::Null),
+        (
+            "settings",
+            object([
+                ("theme", Value::String("dark".into())),
+                ("lang", Value::Null),
+            ]),
+        ),
+    ]);
+    let defaults = object([
+        ("id", Value::Number(0.0)),
+        ("name", Value::String("anonymous".into())),
+        ("active", Value::Boolean(true)),
+        (
+            "settings",
+            object([
+                ("theme", Value::String("light".into())),
+                ("lang", Value::String("en".into())),
+                ("beta", Value::Boolean(false)),
+            ]),
+        ),
+    ]);
+
+    value.fill_defaults(&defaults);
+
+    assert_eq!(
+        value,
+        object([
+            ("id", Value::Number(7.0)),
+            ("name", Value::String("anonymous".into())),
+            ("active", Value::Boolean(true)),
+            (
+                "settings",
+                object([
+                    ("theme", Value::String("dark".into())),
+                    ("lang", Value::String("en".into())),
+                    ("beta", Value::Boolean(false)),
+                ]),
+            ),
+        ])
+    );
+}
+
+#[test]
+fn fill_defaults_null_root_takes_whole_default() {
+    let defaults = object([("a", Value::Array(vec![Value::Number(1.0)]))]);
+    let mut value = Value::Null;
+    value.fill_defaults(&defaults);
+    assert_eq!(value, defaults);
+}
+
+#[test]
+fn fill_defaults_leaves_non_null_values_alone() {
+    let defaults = object([
+        ("list", Value::Array(vec![Value::Null, Value::Number(2.0)])),
+        ("nested", object([("x", Value::Number(1.0))])),
+    ]);
+    let mut value = object([
+        ("list", Value::Array(vec![Value::Number(1.0)])),
+        ("nested", Value::String("not an object".into())),
+    ]);
+    let expected = value.clone();
+
+    value.fill_defaults(&defaults);
+    assert_eq!(value, expected);
+}
+
+fn parse(json: &str) -> Value {
+    StreamingParser::new(ParserOptions::default())
+        .into_value(json)
+        .unwrap()
+}
+
+// RFC 8785, section 3.2.2.
+#[test]
+fn jcs_rfc8785_sample() {
+    let input = r#"{
+        "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+        "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+        "literals": [null, true, false]
+    }"#;
+    assert_eq!(
+        parse(input).to_jcs_string().unwrap(),
+        r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#
+    );
+}
+
+// RFC 8785, section 3.2.3.
+#[test]
+fn jcs_sorts_keys_by_utf16_code_units() {
+    let input = r#"{
+        "\u20ac": "Euro Sign",
+        "\r": "Carriage Return",
+        "\ufb33": "Hebrew Letter Dalet With Dagesh",
+        "1": "One",
+        "\ud83d\ude00": "Emoji: Grinning Face",
+        "\u0080": "Control",
+        "\u00f6": "Latin Small Letter O With Diaeresis"
+    }"#;
+    let Value::Object(map) = parse(input) else {
+        unreachable!()
+    };
+    let canonical = Value::Object(map).to_jcs_string().unwrap();
+    let keys: vec::Vec<_> = canonical
+        .split(',')
+        .map(|entry| entry.trim_start_matches('{').split(':').next().unwrap())
+        .collect();
+    assert_eq!(
+        keys,
+        [
+            r#""\r""#,
+            r#""1""#,
+            "\"\u{80}\"",
+            "\"ö\"",
+            "\"€\"",
+            "\"😀\"",
+            "\"\u{fb33}\"",
+        ]
+    );
+}
+
+// RFC 8785, appendix B.
+#[test]
+fn jcs_number_serialization() {
+    let cases: &[(u64, &str)] = &[
+        (0x0000_0000_0000_0000, "0"),
+        (0x8000_0000_0000_0000, "0"),
+        (0x0000_0000_0000_0001, "5e-324"),
+        (0x8000_0000_0000_0001, "-5e-324"),
+        (0x7fef_ffff_ffff_ffff, "1.7976931348623157e+308"),
+        (0xffef_ffff_ffff_ffff, "-1.7976931348623157e+308"),
+        (0x4340_0000_0000_0000, "9007199254740992"),
+        (0xc340_0000_0000_0000, "-9007199254740992"),
+        (0x4430_0000_0000_0000, "295147905179352830000"),
+        (0x44b5_2d02_c7e1_4af5, "9.999999999999997e+22"),
+        (0x44b5_2d02_c7e1_4af6, "1e+23"),
+        (0x44b5_2d02_c7e1_4af7, "1.0000000000000001e+23"),
+        (0x444b_1ae4_d6e2_ef4e, "999999999999999700000"),
+        (0x444b_1ae4_d6e2_ef4f, "999999999999999900000"),
+        (0x444b_1ae4_d6e2_ef50, "1e+21"),
+        (0x3eb0_c6f7_a0b5_ed8c, "9.999999999999997e-7"),
+        (0x3eb0_c6f7_a0b5_ed8d, "0.000001"),
+        (0x41b3_de43_5555_5553, "333333333.3333332"),
+        (0x41b3_de43_5555_5554, "333333333.33333325"),
+        (0x41b3_de43_5555_5555, "333333333.3333333"),
+        (0x41b3_de43_5555_5556, "333333333.3333334"),
+        (0x41b3_de43_5555_5557, "333333333.33333343"),
+        (0xbecb_f647_612f_3696, "-0.0000033333333333333333"),
+        (0x4314_3ff3_c1cb_0959, "1424953923781206.2"),
+    ];
+    for &(bits, expected) in cases {
+        let value = Value::Number(f64::from_bits(bits));
+        assert_eq!(value.to_jcs_string().unwrap(), expected, "{bits:#018x}");
+    }
+
+    for n in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        assert_eq!(Value::Array(vec![Value::Number(n)]).to_jcs_string(), None);
+    }
+}
+
+#[test]
+fn find_strings_matching_substring() {
+    let value = parse(
+        r#"{
+            "user": {"email": "jane@example.com", "name": "Jane"},
+            "messages": [
+                {"body": "reach me at jane@example.com", "tags": ["example.com", "misc"]},
+                {"body": "nothing here", "cc": ["bob@example.com", 42, null]}
+            ],
+            "domain": "example.com"
+        }"#,
+    );
+    let found = value.find_strings(|s| s.contains("@example.com"));
+    assert_eq!(
+        found,
+        vec![
+            (path!["messages", 0, "body"], "reach me at jane@example.com"),
+            (path!["messages", 1, "cc", 0], "bob@example.com"),
+            (path!["user", "email"], "jane@example.com"),
+        ]
+    );
+
+    assert_eq!(value.find_strings(|s| s.contains("example.com")).len(), 5);
+    assert!(value.find_strings(|s| s.contains("missing")).is_empty());
+    assert_eq!(
+        Value::String("x".into()).find_strings(|_| true),
+        vec![(Vec::new(), "x")]
+    );
+}
+
+#[test]
+fn compares_with_serde_json_values() {
+    let value = object([
+        ("n", Value::Number(1.0)),
+        ("f", Value::Number(-0.5)),
+        ("s", Value::String("x".into())),
+        (
+            "a",
+            Value::Array(vec![Value::Null, Value::Boolean(true), object([])]),
+        ),
+    ]);
+    let json = serde_json::json!({"a": [null, true, {}], "f": -0.5, "n": 1, "s": "x"});
+    assert_eq!(value, json);
+    assert_eq!(json, value);
+
+    for other in [
+        serde_json::json!({"a": [null, true, {}], "f": -0.5, "n": 2, "s": "x"}),
+        serde_json::json!({"a": [null, true], "f": -0.5, "n": 1, "s": "x"}),
+        serde_json::json!({"a": [null, true, {}], "f": -0.5, "n": 1}),
+        serde_json::json!({"a": [null, true, {}], "f": -0.5, "n": 1, "s": "x", "t": null}),
+        serde_json::json!({"a": [null, true, {}], "f": -0.5, "n": 1, "z": "x"}),
+        serde_json::json!([1]),
+    ] {
+        assert_ne!(value, other);
+    }
+    assert_ne!(Value::Null, serde_json::json!(false));
+    assert_ne!(Value::String("1".into()), serde_json::json!(1));
+}
+
+#[test]
+fn objects_serialize_in_key_order() {
+    let value = parse(r#"{"b": 1, "a": {"d": [{"f": 2, "e": 3}], "c": 4}, "": 5}"#);
+    assert_eq!(
+        value.to_string(),
+        r#"{"":5,"a":{"c":4,"d":[{"e":3,"f":2}]},"b":1}"#
+    );
+    assert_eq!(
+        value,
+        parse(r#"{"a": {"c": 4, "d": [{"e": 3, "f": 2}]}, "b": 1, "": 5}"#)
+    );
+}
+
+#[test]
+fn to_columns_pads_missing_keys_with_null() {
+    let rows = parse(
+        r#"[
+            {"id": 1, "name": "a", "ok": true},
+            {"name": "b", "score": 2.5},
+            {},
+            {"id": 4, "score": null}
+        ]"#,
+    );
+    let columns = rows.to_columns().unwrap();
+    let expected = [
+        (
+            "id",
+            vec![
+                Value::Number(1.0),
+                Value::Null,
+                Value::Null,
+                Value::Number(4.0),
+            ],
+        ),
+        (
+            "name",
+            vec![
+                Value::String("a".into()),
+                Value::String("b".into()),
+                Value::Null,
+                Value::Null,
+            ],
+        ),
+        (
+            "ok",
+            vec![Value::Boolean(true), Value::Null, Value::Null, Value::Null],
+        ),
+        (
+            "score",
+            vec![Value::Null, Value::Number(2.5), Value::Null, Value::Null],
+        ),
+    ];
+    assert!(
+        columns
+            .iter()
+            .map(|(key, column)| (key.as_str(), column.clone()))
+            .eq(expected)
+    );
+
+    assert_eq!(parse("[]").to_columns().unwrap().len(), 0);
+    assert_eq!(parse(r#"{"a": 1}"#).to_columns(), None);
+    assert_eq!(parse(r#"[{"a": 1}, 2]"#).to_columns(), None);
+    assert_eq!(parse(r#"[{"a": [1]}]"#).to_columns(), None);
+}
@@ -0,0 +1,265 @@
+//! Tokens of a document, without structural events.
+//!
+//! [`Tokenizer`] reports the punctuators, property names, strings, numbers
+//! and literals of its input in order, e.g. for a reformatter that rewrites
+//! the text token by token and has no use for paths or values. Like the
+//! parser, it is fed in chunks and reports each token as soon as it is
+//! complete; a string split across chunks arrives in fragments.
+//!
+//! Whether a string is a property name depends on where it appears, so the
+//! tokenizer still tracks the nesting of the document and reports structural
+//! errors such as `[1 2]`, but it builds no paths or events.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{ParserOptions, Token, Tokenizer};
+//!
+//! let mut tokenizer = Tokenizer::new(ParserOptions::default());
+//! tokenizer.feed(r#"{"a": [1, "#);
+//! tokenizer.feed(r#"null]}"#);
+//! tokenizer.finish();
+//! let tokens: Vec<_> =
+//!     core::iter::from_fn(|| tokenizer.next_token()).collect::<Result<_, _>>()?;
+//! assert_eq!(
+//!     tokens,
+//!     [
+//!         Token::Punctuator('{'),
+//!         Token::PropertyName("a".into()),
+//!         Token::Punctuator(':'),
+//!         Token::Punctuator('['),
+//!         Token::Number(1.0),
+//!         Token::Punctuator(','),
+//!         Token::Null,
+//!         Token::Punctuator(']'),
+//!         Token::Punctuator('}'),
+//!     ]
+//! );
+//! # Ok::<(), jsonmodem::ParserError>(())
+//! ```
+use alloc::string::String;
+
+use crate::{
+    JsonValueFactory, NonScalarValueMode, ParserError, ParserOptions, StdValueFactory,
+    StreamingParser, StringValueMode,
+    parser::{self, Number},
+};
+
+/// A token reported by a [`Tokenizer`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    /// One of `{`, `}`, `[`, `]`, `:` and `,`.
+    Punctuator(char),
+    /// The key of an object member, always complete.
+    PropertyName(String),
+    /// A fragment of a string value, decoded. The last fragment of each
+    /// string has `is_final` set.
+    String {
+        /// The text of the fragment.
+        fragment: String,
+        /// Whether the string ends with this fragment.
+        is_final: bool,
+    },
+    /// A number.
+    Number(f64),
+    /// `true` or `false`.
+    Boolean(bool),
+    /// `null`.
+    Null,
+}
+
+/// Splits input fed in chunks into [`Token`]s.
+///
+/// See the [module documentation](self) for details.
+#[derive(Debug, Clone)]
+pub struct Tokenizer {
+    parser: StreamingParser,
+}
+
+impl Tokenizer {
+    /// Creates a tokenizer.
+    ///
+    /// `options` are read as by [`StreamingParser::new`], except those that
+    /// only shape events, such as `string_value_mode`, which are ignored.
+    #[must_use]
+    pub fn new(options: ParserOptions) -> Self {
+        Self {
+            parser: StreamingParser::new(ParserOptions {
+                string_value_mode: StringValueMode::None,
+                non_scalar_values: NonScalarValueMode::None,
+                ..options
+            }),
+        }
+    }
+
+    /// Appends a chunk of input. Its tokens are read with
+    /// [`next_token`](Self::next_token).
+    pub fn feed(&mut self, chunk: &str) {
+        self.parser.push_chunk(chunk);
+    }
+
+    /// Marks the end of input, completing a trailing number or literal and
+    /// making a truncated document an error.
+    pub fn finish(&mut self) {
+        self.parser.end_input();
+    }
+
+    /// Returns the next token of the input fed so far, or `None` if more
+    /// input is needed to complete it, at the end of input, and after an
+    /// error.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input is not valid JSON. No tokens follow it.
+    pub fn next_token(&mut self) -> Option<Result<Token, ParserError>> {
+        let (token, partial) = match self.parser.next_token(&mut StdValueFactory)? {
+            Ok(token) => token,
+            Err(err) => return Some(Err(err)),
+        };
+        Some(Ok(match token {
+            parser::Token::Punctuator(c) => Token::Punctuator(char::from(c)),
+            parser::Token::PropertyName { value } => Token::PropertyName(value),
+            parser::Token::String { fragment, .. } => Token::String {
+                fragment,
+                is_final: !partial,
+            },
+            parser::Token::Number(n) => Token::Number(match n {
+                Number::F64(n) => n,
+                Number::I64(n) => StdValueFactory.new_number_i64(n),
+                Number::U64(n) => StdValueFactory.new_number_u64(n),
+            }),
+            parser::Token::Boolean(b) => Token::Boolean(b),
+            parser::Token::Null => Token::Null,
+            // `next_token` does not report the end of the input.
+            parser::Token::Eof => return None,
+        }))
+    }
+}
+
+impl Default for Tokenizer {
+    fn default() -> Self {
+        Self::new(ParserOptions::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::String, vec::Vec};
+
+    use super::{Token, Tokenizer};
+    use crate::{ParserOptions, produce_chunks};
+
+    fn tokenize(json: &str, parts: usize) -> Result<Vec<Token>, crate::ParserError> {
+        let mut tokenizer = Tokenizer::new(ParserOptions {
+            allow_multiple_json_values: true,
+            ..ParserOptions::default()
+        });
+        let mut tokens = Vec::new();
+        for chunk in produce_chunks(json, parts) {
+            tokenizer.feed(chunk);
+            while let Some(token) = tokenizer.next_token() {
+                tokens.push(token?);
+            }
+        }
+        tokenizer.finish();
+        while let Some(token) = tokenizer.next_token() {
+            tokens.push(token?);
+        }
+        // Join string fragments, whose boundaries depend on the chunking.
+        let mut joined: Vec<Token> = Vec::new();
+        for token in tokens {
+            if let (
+                Some(Token::String {
+                    fragment: pending,
+                    is_final: false,
+                }),
+                Token::String { fragment, is_final },
+            ) = (joined.last_mut(), &token)
+            {
+                pending.push_str(fragment);
+                if *is_final {
+                    *joined.last_mut().unwrap() = Token::String {
+                        fragment: core::mem::take(pending),
+                        is_final: true,
+                    };
+                }
+                continue;
+            }
+            joined.push(token);
+        }
+        Ok(joined)
+    }
+
+    fn string(s: &str) -> Token {
+        Token::String {
+            fragment: String::from(s),
+            is_final: true,
+        }
+    }
+
+    #[test]
+    fn complex_document() {
+        use Token::{Boolean, Null, Number, PropertyName, Punctuator};
+
+        let json = r#" {"name": "café ☕", "tags": [], "n": [-0.5e2, 12, true, false, null],
+            "nested": {"a\"b": {"": [{}]}}} 3 "x" "#;
+        let expected = [
+            Punctuator('{'),
+            PropertyName("name".into()),
+            Punctuator(':'),
+            string("café ☕"),
+            Punctuator(','),
+            PropertyName("tags".into()),
+            Punctuator(':'),
+            Punctuator('['),
+            Punctuator(']'),
+            Punctuator(','),
+            PropertyName("n".into()),
+            Punctuator(':'),
+            Punctuator('['),
+            Number(-50.0),
+            Punctuator(','),
+            Number(12.0),
+            Punctuator(','),
+            Boolean(true),
+            Punctuator(','),
+            Boolean(false),
+            Punctuator(','),
+            Null,
+            Punctuator(']'),
+            Punctuator(','),
+            PropertyName("nested".into()),
+            Punctuator(':'),
+            Punctuator('{'),
+            PropertyName("a\"b".into()),
+            Punctuator(':'),
+            Punctuator('{'),
+            PropertyName(String::new()),
+            Punctuator(':'),
+            Punctuator('['),
+            Punctuator('{'),
+            Punctuator('}'),
+            Punctuator(']'),
+            Punctuator('}'),
+            Punctuator('}'),
+            Punctuator('}'),
+            Number(3.0),
+            string("x"),
+        ];
+        for parts in [1, 7, json.len()] {
+            assert_eq!(tokenize(json, parts).unwrap(), expected, "{parts}");
+        }
+    }
+
+    #[test]
+    fn errors_end_the_tokens() {
+        let mut tokenizer = Tokenizer::default();
+        tokenizer.feed("[1 2]");
+        assert_eq!(tokenizer.next_token(), Some(Ok(Token::Punctuator('['))));
+        assert_eq!(tokenizer.next_token(), Some(Ok(Token::Number(1.0))));
+        assert!(tokenizer.next_token().unwrap().is_err());
+        assert_eq!(tokenizer.next_token(), None);
+
+        assert!(tokenize("[1,", 1).is_err());
+    }
+}
@@ -0,0 +1,376 @@
+//! [`tracing`](https://docs.rs/tracing) instrumentation of a parse.
+//!
+//! A service parsing JSON from its requests wants the parse to show up in its
+//! traces. [`TracingStreamingParser`] wraps a [`StreamingParserImpl`] and,
+//! as its events pass through, emits:
+//!
+//! * a `DEBUG` span named `json_value` for each root value, open from the first
+//!   event of the value to its last, with the fields `json.index`, the number
+//!   of root values before it, and `json.depth`, the deepest nesting reached
+//!   inside it, recorded when the value ends;
+//! * a `TRACE` event per string fragment, inside the span, with the fields
+//!   `json.path`, `json.depth`, `len`, the fragment's length in bytes, and
+//!   `is_final`;
+//! * an `ERROR` event for a [`ParserError`], with its message and position,
+//!   which also closes the span.
+//!
+//! A root value split across chunks keeps one span for all of its events.
+//! The contents of strings are not recorded.
+//!
+//! Requires the `tracing` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{ParserOptions, StreamingParser, TracingStreamingParser};
+//!
+//! let mut parser = TracingStreamingParser::new(StreamingParser::new(ParserOptions::default()));
+//! // With a subscriber installed, this records a `json_value` span with
+//! // `json.depth = 2` and a `TRACE` event for the string.
+//! let events = parser
+//!     .feed(r#"{"a": ["x"]}"#)
+//!     .collect::<Result<Vec<_>, _>>()?;
+//! assert_eq!(events.len(), 5);
+//! # Ok::<(), jsonmodem::ParserError>(())
+//! ```
+use tracing::{Span, field};
+
+use crate::{
+    JsonValue, JsonValueFactory, ParseEvent, ParserError, StdValueFactory, Value,
+    parser::{ClosedStreamingParser, StreamingParserImpl, StreamingParserIteratorWith},
+};
+
+/// The span of the root value in progress.
+#[derive(Debug, Default)]
+struct TraceState {
+    span: Option<Span>,
+    /// The number of root values begun.
+    roots: usize,
+    /// The deepest path seen in the current root value.
+    depth: usize,
+    /// Whether the current root value is a scalar that began with a
+    /// `ScalarBegin` event, and so ends with a `ScalarEnd`.
+    bounded: bool,
+}
+
+impl TraceState {
+    fn trace<V: JsonValue>(&mut self, event: &Result<ParseEvent<V>, ParserError>)
+    where
+        V::Str: AsRef<str>,
+    {
+        let event = match event {
+            Ok(event) => event,
+            Err(err) => {
+                let span = self.close();
+                tracing::error!(
+                    parent: span.as_ref().and_then(Span::id),
+                    line = err.line,
+                    column = err.column,
+                    byte_offset = err.byte_offset,
+                    "{err}"
+                );
+                return;
+            }
+        };
+        let path = event.path();
+        let span = self.span.get_or_insert_with(|| {
+            self.roots += 1;
+            tracing::debug_span!(
+                "json_value",
+                json.index = self.roots - 1,
+                json.depth = field::Empty
+            )
+        });
+        self.depth = self.depth.max(path.len());
+        if let ParseEvent::String {
+            fragment, is_final, ..
+        } = event
+        {
+            tracing::trace!(
+                parent: &*span,
+                json.path = ?path,
+                json.depth = path.len(),
+                len = fragment.as_ref().len(),
+                is_final,
+                "string fragment"
+            );
+        }
+        if !path.is_empty() {
+            return;
+        }
+        let ends_root = match event {
+            ParseEvent::ScalarBegin { .. } => {
+                self.bounded = true;
+                false
+            }
+            ParseEvent::ArrayStart { .. }
+            | ParseEvent::ObjectBegin { .. }
+            | ParseEvent::ObjectKey { .. } => false,
+            ParseEvent::ScalarEnd { .. }
+            | ParseEvent::ArrayEnd { .. }
+            | ParseEvent::ObjectEnd { .. } => true,
+            ParseEvent::String { is_final, .. } => *is_final && !self.bounded,
+            ParseEvent::Null { .. }
+            | ParseEvent::Boolean { .. }
+            | ParseEvent::Number { .. }
+            | ParseEvent::StringComplete { .. } => !self.bounded,
+        };
+        if ends_root {
+            self.close();
+        }
+    }
+
+    /// Records the depth of the current root value and ends its span,
+    /// returning it.
+    fn close(&mut self) -> Option<Span> {
+        let span = self.span.take()?;
+        span.record("json.depth", self.depth);
+        self.depth = 0;
+        self.bounded = false;
+        Some(span)
+    }
+}
+
+/// A streaming parser that reports its progress to `tracing`.
+///
+/// See the [module documentation](self) for the spans and events emitted.
+#[derive(Debug)]
+pub struct TracingStreamingParser<V: JsonValue = Value> {
+    parser: StreamingParserImpl<V>,
+    state: TraceState,
+}
+
+impl<V: JsonValue> TracingStreamingParser<V>
+where
+    V::Str: AsRef<str>,
+{
+    /// Wraps `parser`, which should not have been fed yet.
+    #[must_use]
+    pub fn new(parser: StreamingParserImpl<V>) -> Self {
+        Self {
+            parser,
+            state: TraceState::default(),
+        }
+    }
+
+    /// Feeds a chunk of JSON text and returns an iterator over the events it
+    /// completes, building values with `factory`.
+    pub fn feed_with<'a, F: JsonValueFactory<Value = V>>(
+        &'a mut self,
+        factory: F,
+        text: &str,
+    ) -> TracedEvents<'a, F> {
+        TracedEvents {
+            events: self.parser.feed_with(factory, text),
+            state: &mut self.state,
+        }
+    }
+
+    /// Marks the end of input and returns an iterator over the remaining
+    /// events, building values with `factory`.
+    pub fn finish_with<F: JsonValueFactory<Value = V>>(
+        self,
+        factory: F,
+    ) -> FinishedTracedEvents<F> {
+        FinishedTracedEvents {
+            events: self.parser.finish_with(factory),
+            state: self.state,
+        }
+    }
+}
+
+impl TracingStreamingParser {
+    /// Feeds a chunk of JSON text and returns an iterator over the events it
+    /// completes.
+    pub fn feed<'a>(&'a mut self, text: &str) -> TracedEvents<'a, StdValueFactory> {
+        self.feed_with(StdValueFactory, text)
+    }
+
+    /// Marks the end of input and returns an iterator over the remaining
+    /// events.
+    #[must_use]
+    pub fn finish(self) -> FinishedTracedEvents<StdValueFactory> {
+        self.finish_with(StdValueFactory)
+    }
+}
+
+/// Iterator returned by [`TracingStreamingParser::feed`].
+pub struct TracedEvents<'a, F: JsonValueFactory> {
+    events: StreamingParserIteratorWith<'a, F>,
+    state: &'a mut TraceState,
+}
+
+impl<F: JsonValueFactory> Iterator for TracedEvents<'_, F>
+where
+    <F::Value as JsonValue>::Str: AsRef<str>,
+{
+    type Item = Result<ParseEvent<F::Value>, ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let event = self.events.next()?;
+        self.state.trace(&event);
+        Some(event)
+    }
+}
+
+/// Iterator returned by [`TracingStreamingParser::finish`].
+pub struct FinishedTracedEvents<F: JsonValueFactory> {
+    events: ClosedStreamingParser<F>,
+    state: TraceState,
+}
+
+impl<F: JsonValueFactory> Iterator for FinishedTracedEvents<F>
+where
+    <F::Value as JsonValue>::Str: AsRef<str>,
+{
+    type Item = Result<ParseEvent<F::Value>, ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let event = self.events.next()?;
+        self.state.trace(&event);
+        Some(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        format,
+        string::{String, ToString},
+        vec::Vec,
+    };
+    use core::fmt::Write;
+    use std::sync::{Arc, Mutex};
+
+    use tracing::{
+        Event, Subscriber,
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+    };
+    use tracing_subscriber::{
+        Layer,
+        layer::{Context, SubscriberExt},
+        registry::LookupSpan,
+    };
+
+    use super::TracingStreamingParser;
+    use crate::{ParserOptions, StreamingParser, produce_chunks};
+
+    /// Writes the fields it visits as `name=value` pairs.
+    struct Fields<'a>(&'a mut String);
+
+    impl Visit for Fields<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn core::fmt::Debug) {
+            if !self.0.is_empty() {
+                self.0.push(' ');
+            }
+            write!(self.0, "{}={value:?}", field.name()).unwrap();
+        }
+    }
+
+    /// Records each span when it closes, and each event with the fields of
+    /// its parent span.
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<String>>>);
+
+    struct SpanFields(String);
+
+    impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Capture {
+        fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+            let mut fields = String::new();
+            attrs.record(&mut Fields(&mut fields));
+            ctx.span(id)
+                .unwrap()
+                .extensions_mut()
+                .insert(SpanFields(fields));
+        }
+
+        fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+            let span = ctx.span(id).unwrap();
+            let mut extensions = span.extensions_mut();
+            values.record(&mut Fields(
+                &mut extensions.get_mut::<SpanFields>().unwrap().0,
+            ));
+        }
+
+        fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+            let mut fields = String::new();
+            event.record(&mut Fields(&mut fields));
+            let parent = ctx.event_span(event).map_or_else(
+                || "none".to_string(),
+                |span| span.extensions().get::<SpanFields>().unwrap().0.clone(),
+            );
+            let level = event.metadata().level();
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{level} {fields} in [{parent}]"));
+        }
+
+        fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+            let span = ctx.span(&id).unwrap();
+            let extensions = span.extensions();
+            let fields = &extensions.get::<SpanFields>().unwrap().0;
+            self.0.lock().unwrap().push(format!(
+                "{} {} [{fields}]",
+                span.metadata().level(),
+                span.name()
+            ));
+        }
+    }
+
+    fn trace(json: &str, parts: usize) -> Vec<String> {
+        let capture = Capture::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            let mut parser = TracingStreamingParser::new(StreamingParser::new(ParserOptions {
+                allow_multiple_json_values: true,
+                ..ParserOptions::default()
+            }));
+            for chunk in produce_chunks(json, parts) {
+                parser.feed(chunk).for_each(drop);
+            }
+            parser.finish().for_each(drop);
+        });
+        capture.0.lock().unwrap().clone()
+    }
+
+    #[test]
+    fn spans_follow_root_values() {
+        let lines = trace(r#"{"a": ["xy", 1]} 2 [{"b": [1 2]}]"#, 1);
+        assert_eq!(
+            lines,
+            [
+                "TRACE message=string fragment json.path=[Key(\"a\"), Index(0)] json.depth=2 \
+                 len=2 is_final=true in [json.index=0]",
+                "DEBUG json_value [json.index=0 json.depth=2]",
+                "DEBUG json_value [json.index=1 json.depth=0]",
+                "ERROR message=JSON5: invalid character '2' at 1:30 line=1 column=30 \
+                 byte_offset=29 in [json.index=2 json.depth=3]",
+                "DEBUG json_value [json.index=2 json.depth=3]",
+            ]
+        );
+    }
+
+    #[test]
+    fn one_span_per_value_across_chunks() {
+        let json = r#""abc" {"d": "ef"}"#;
+        let lines = trace(json, json.len());
+        let spans: Vec<_> = lines
+            .iter()
+            .filter(|line| line.starts_with("DEBUG"))
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                "DEBUG json_value [json.index=0 json.depth=0]",
+                "DEBUG json_value [json.index=1 json.depth=1]",
+            ]
+        );
+        let fragments = lines
+            .iter()
+            .filter(|line| line.starts_with("TRACE"))
+            .count();
+        assert!(fragments >= 2, "{lines:#?}");
+    }
+}
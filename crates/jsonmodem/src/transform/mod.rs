@@ -0,0 +1,342 @@
+//! Rewriting of string values as they stream past.
+//!
+//! [`map_string_values`] calls a closure once for each whole string, e.g. to
+//! sanitize or translate it, and [`map_string_fragments`] calls it for each
+//! fragment as it arrives, e.g. to redact characters without holding back
+//! any output. Both wrap an iterator of events and pass every other event,
+//! and every error, through unchanged. [`RedactingParser`] hides whole
+//! values at chosen paths, e.g. personal data before it is logged.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{ParseEvent, ParserOptions, StreamingParser, map_string_values};
+//!
+//! let mut parser = StreamingParser::new(ParserOptions::default());
+//! let mut events = Vec::new();
+//! for chunk in [r#"{"name": "Ad"#, r#"a", "n": 1}"#] {
+//!     events.extend(parser.feed(chunk));
+//! }
+//! let strings: Vec<String> = map_string_values(events, |_, s| s.to_uppercase())
+//!     .filter_map(|event| match event.unwrap() {
+//!         ParseEvent::String { fragment, .. } => Some(fragment),
+//!         _ => None,
+//!     })
+//!     .collect();
+//! assert_eq!(strings, ["ADA"]);
+//! ```
+use alloc::string::String;
+
+use crate::{ParseEvent, PathComponent};
+
+mod redact;
+
+pub use redact::{FinishedRedactedEvents, RedactMode, RedactedEvents, RedactingParser};
+
+/// Replaces each string by the result of `f`, called with its path and its
+/// whole value.
+///
+/// The fragments of a string are held back until its final fragment arrives,
+/// and replaced by a single final `String` event holding the result. Its
+/// `value` is the result too, if the final fragment had a value. A
+/// [`ParseEvent::StringComplete`] event is mapped in place. Memory use is
+/// proportional to the longest string split across fragments.
+pub fn map_string_values<I, E, F>(events: I, f: F) -> MapStringValues<I::IntoIter, F>
+where
+    I: IntoIterator<Item = Result<ParseEvent, E>>,
+    F: FnMut(&[PathComponent], String) -> String,
+{
+    MapStringValues {
+        events: events.into_iter(),
+        f,
+        pending: None,
+    }
+}
+
+/// Iterator returned by [`map_string_values`].
+#[derive(Debug, Clone)]
+pub struct MapStringValues<I, F> {
+    events: I,
+    f: F,
+    /// The fragments so far of a string not yet complete.
+    pending: Option<String>,
+}
+
+impl<I, E, F> Iterator for MapStringValues<I, F>
+where
+    I: Iterator<Item = Result<ParseEvent, E>>,
+    F: FnMut(&[PathComponent], String) -> String,
+{
+    type Item = Result<ParseEvent, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let event = match self.events.next()? {
+                Ok(ParseEvent::String {
+                    path,
+                    value,
+                    fragment,
+                    is_final,
+                }) => {
+                    let string = match self.pending.take() {
+                        Some(mut string) => {
+                            string.push_str(&fragment);
+                            string
+                        }
+                        None => fragment,
+                    };
+                    if !is_final {
+                        self.pending = Some(string);
+                        continue;
+                    }
+                    let fragment = (self.f)(&path, string);
+                    ParseEvent::String {
+                        value: value.map(|_| fragment.clone()),
+                        path,
+                        fragment,
+                        is_final,
+                    }
+                }
+                Ok(ParseEvent::StringComplete { path, value }) => {
+                    let value = (self.f)(&path, value);
+                    ParseEvent::StringComplete { path, value }
+                }
+                other => return Some(other),
+            };
+            return Some(Ok(event));
+        }
+    }
+}
+
+/// Replaces each fragment of each string by the result of `f`, called with
+/// the path of the string and the fragment.
+///
+/// Every fragment is mapped on its own, as soon as it arrives, and keeps its
+/// `is_final` flag, so the result must not depend on where a string was
+/// split. The `value` of an event, if present, is the concatenation of the
+/// mapped fragments of its string so far, which are kept until the string
+/// ends. A [`ParseEvent::StringComplete`] event is mapped as a single
+/// fragment.
+pub fn map_string_fragments<I, E, F>(events: I, f: F) -> MapStringFragments<I::IntoIter, F>
+where
+    I: IntoIterator<Item = Result<ParseEvent, E>>,
+    F: FnMut(&[PathComponent], String) -> String,
+{
+    MapStringFragments {
+        events: events.into_iter(),
+        f,
+        prefix: String::new(),
+    }
+}
+
+/// Iterator returned by [`map_string_fragments`].
+#[derive(Debug, Clone)]
+pub struct MapStringFragments<I, F> {
+    events: I,
+    f: F,
+    /// The mapped fragments so far of the current string.
+    prefix: String,
+}
+
+impl<I, E, F> Iterator for MapStringFragments<I, F>
+where
+    I: Iterator<Item = Result<ParseEvent, E>>,
+    F: FnMut(&[PathComponent], String) -> String,
+{
+    type Item = Result<ParseEvent, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let event = match self.events.next()? {
+            Ok(ParseEvent::String {
+                path,
+                value,
+                fragment,
+                is_final,
+            }) => {
+                let fragment = (self.f)(&path, fragment);
+                self.prefix.push_str(&fragment);
+                let value = value.map(|_| self.prefix.clone());
+                if is_final {
+                    self.prefix.clear();
+                }
+                ParseEvent::String {
+                    path,
+                    value,
+                    fragment,
+                    is_final,
+                }
+            }
+            Ok(ParseEvent::StringComplete { path, value }) => {
+                let value = (self.f)(&path, value);
+                ParseEvent::StringComplete { path, value }
+            }
+            other => return Some(other),
+        };
+        Some(Ok(event))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        string::{String, ToString},
+        vec::Vec,
+    };
+
+    use super::{map_string_fragments, map_string_values};
+    use crate::{
+        ParseEvent, ParserError, ParserOptions, PathComponent, StreamingParser,
+        StringCoalescingAdapter, StringValueMode, path, produce_chunks,
+    };
+
+    const JSON: &str = r#"{"a": "héllo wörld", "b": ["", "x", "long ☕ string"], "c": 1}"#;
+
+    fn events(chunks: &[&str], mode: StringValueMode) -> Vec<Result<ParseEvent, ParserError>> {
+        let mut parser = StreamingParser::new(ParserOptions {
+            string_value_mode: mode,
+            ..Default::default()
+        });
+        let mut events = Vec::new();
+        for chunk in chunks {
+            events.extend(parser.feed(chunk));
+        }
+        events.extend(parser.finish());
+        events
+    }
+
+    /// The final `String` events, and the number of events in total.
+    fn finals(
+        events: impl Iterator<Item = Result<ParseEvent, ParserError>>,
+    ) -> (Vec<ParseEvent>, usize) {
+        let events: Vec<_> = events.map(Result::unwrap).collect();
+        let len = events.len();
+        let finals = events
+            .into_iter()
+            .filter(|event| matches!(event, ParseEvent::String { is_final: true, .. }))
+            .collect();
+        (finals, len)
+    }
+
+    fn final_string(path: Vec<PathComponent>, s: &str, with_value: bool) -> ParseEvent {
+        ParseEvent::String {
+            path,
+            value: with_value.then(|| s.into()),
+            fragment: s.into(),
+            is_final: true,
+        }
+    }
+
+    #[test]
+    fn values_are_mapped_whole() {
+        let expected = |with_value| {
+            [
+                final_string(path!["a"], "a: héllo wörld!", with_value),
+                final_string(path!["b", 0], "b.0: !", with_value),
+                final_string(path!["b", 1], "b.1: x!", with_value),
+                final_string(path!["b", 2], "b.2: long ☕ string!", with_value),
+            ]
+        };
+        for mode in [
+            StringValueMode::None,
+            StringValueMode::Values,
+            StringValueMode::Prefixes,
+        ] {
+            for parts in [1, 5, JSON.len()] {
+                let events = events(&produce_chunks(JSON, parts), mode);
+                let mut seen = Vec::new();
+                let mapped = map_string_values(events, |path, s| {
+                    seen.push(path.to_vec());
+                    let path: Vec<String> = path
+                        .iter()
+                        .map(|component| match component {
+                            PathComponent::Key(key) => key.to_string(),
+                            PathComponent::Index(index) => index.to_string(),
+                        })
+                        .collect();
+                    alloc::format!("{}: {s}!", path.join("."))
+                });
+                let (finals, len) = finals(mapped);
+                assert_eq!(
+                    finals,
+                    expected(mode != StringValueMode::None),
+                    "{mode:?} {parts}"
+                );
+                assert_eq!(len, 9, "{mode:?} {parts}");
+                assert_eq!(seen.len(), 4);
+            }
+        }
+    }
+
+    #[test]
+    fn fragments_are_mapped_in_place() {
+        for mode in [
+            StringValueMode::None,
+            StringValueMode::Values,
+            StringValueMode::Prefixes,
+        ] {
+            for parts in [1, 5, JSON.len()] {
+                let original = events(&produce_chunks(JSON, parts), mode);
+                let fragments = original.len();
+                let mapped: Vec<_> = map_string_fragments(original.clone(), |_, s| {
+                    s.replace(|c: char| !c.is_ascii(), "?")
+                })
+                .map(Result::unwrap)
+                .collect();
+                assert_eq!(mapped.len(), fragments);
+
+                // Flags, paths and values are consistent with the mapped
+                // fragments.
+                let mut string = String::new();
+                for (event, original) in mapped.iter().zip(&original) {
+                    let original = original.as_ref().unwrap();
+                    assert_eq!(event.path(), original.path());
+                    if let (
+                        ParseEvent::String {
+                            value,
+                            fragment,
+                            is_final,
+                            ..
+                        },
+                        ParseEvent::String {
+                            value: original_value,
+                            is_final: original_final,
+                            ..
+                        },
+                    ) = (event, original)
+                    {
+                        assert_eq!(is_final, original_final);
+                        string.push_str(fragment);
+                        assert_eq!(value.is_some(), original_value.is_some());
+                        if let Some(value) = value {
+                            assert_eq!(value, &string);
+                        }
+                        if *is_final {
+                            assert!(string.is_ascii(), "{string}");
+                            string.clear();
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn errors_and_complete_strings_pass_through() {
+        let events = events(&[r#"["ab"#, r#"c", "d"]"#, "x"], StringValueMode::None);
+        let coalesced = StringCoalescingAdapter::new(events.clone().into_iter());
+        let mapped: Vec<_> = map_string_values(coalesced, |_, s| s.repeat(2)).collect();
+        assert_eq!(
+            mapped[1],
+            Ok(ParseEvent::StringComplete {
+                path: path![0],
+                value: "abcabc".into(),
+            })
+        );
+        assert!(mapped.last().unwrap().is_err());
+
+        let coalesced = StringCoalescingAdapter::new(events.into_iter());
+        let mapped: Vec<_> = map_string_fragments(coalesced, |_, s| s.repeat(2)).collect();
+        assert_eq!(mapped[2], Ok(final_string(path![1], "dd", false)));
+        assert!(mapped.last().unwrap().is_err());
+    }
+}
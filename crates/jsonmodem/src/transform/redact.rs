@@ -0,0 +1,451 @@
+//! Redaction of values at chosen paths.
+//!
+//! Logs and traces of JSON traffic often must not hold personal data, e.g. to
+//! comply with the GDPR's data minimisation principle. [`RedactingParser`]
+//! wraps a parser and hides every value whose path matches one of a list of
+//! [`Glob`] patterns, such as `customer.email` or `**.iban`, before the events
+//! reach the caller. A [`RedactMode`] chooses what takes the place of a hidden
+//! value.
+//!
+//! A hidden string is held back until its final fragment arrives and then
+//! replaced by one event, so none of its fragments are emitted. The events of
+//! a hidden array or object are suppressed down to its end. The `value` of
+//! the `ArrayEnd` or `ObjectEnd` event of each container holding a hidden
+//! value is dropped, since it would otherwise repeat the value (see
+//! [`NonScalarValueMode`](crate::NonScalarValueMode)). `null` values are not
+//! personal data and are only removed in [`RedactMode::Remove`].
+//!
+//! # Examples
+//!
+//! Masking the contact details and payment data of an order before it is
+//! logged:
+//!
+//! ```
+//! use jsonmodem::{
+//!     Glob, ParseEvent, ParserOptions, RedactMode, RedactingParser, StreamingParser,
+//! };
+//!
+//! let patterns = ["customer.email", "customer.phone", "**.iban"]
+//!     .into_iter()
+//!     .map(|pattern| Glob::compile(pattern).unwrap())
+//!     .collect();
+//! let mut parser = RedactingParser::new(
+//!     StreamingParser::new(ParserOptions::default()),
+//!     patterns,
+//!     RedactMode::Replace,
+//! );
+//! let mut strings = Vec::new();
+//! for chunk in [
+//!     r#"{"customer": {"name": "Ada", "email": "ada@exa"#,
+//!     r#"mple.org", "phone": 5550100}, "payment": {"iban": "DE89 3704"}}"#,
+//! ] {
+//!     for event in parser.feed(chunk) {
+//!         if let ParseEvent::String { fragment, .. } = event? {
+//!             strings.push(fragment);
+//!         }
+//!     }
+//! }
+//! assert_eq!(
+//!     strings,
+//!     ["Ada", "***REDACTED***", "***REDACTED***", "***REDACTED***"]
+//! );
+//! # Ok::<(), jsonmodem::ParserError>(())
+//! ```
+//!
+//! Dropping the data of a whole subject, e.g. to honour an erasure request,
+//! while keeping the rest of the document:
+//!
+//! ```
+//! use jsonmodem::{Glob, ParserOptions, RedactMode, RedactingParser, StreamingParser, path};
+//!
+//! let mut parser = RedactingParser::new(
+//!     StreamingParser::new(ParserOptions::default()),
+//!     vec![Glob::compile("users[1]").unwrap()],
+//!     RedactMode::Remove,
+//! );
+//! let events: Vec<_> = parser
+//!     .feed(r#"{"users": [{"id": 1}, {"id": 2, "name": "Bob"}]}"#)
+//!     .collect::<Result<_, _>>()?;
+//! assert!(
+//!     events
+//!         .iter()
+//!         .all(|event| !event.path().starts_with(&path!["users", 1]))
+//! );
+//! assert_eq!(events.len(), 7);
+//! # Ok::<(), jsonmodem::ParserError>(())
+//! ```
+use alloc::vec::Vec;
+
+use crate::{
+    Glob, JsonValue, JsonValueFactory, ParseEvent, ParserError, PathComponent, StdValueFactory,
+    StringValueMode, Value,
+    parser::{ClosedStreamingParser, StreamingParserImpl, StreamingParserIteratorWith},
+};
+
+/// The string that replaces values in [`RedactMode::Replace`].
+const REDACTED: &str = "***REDACTED***";
+
+/// What takes the place of a value hidden by a [`RedactingParser`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RedactMode {
+    /// A string, number or boolean becomes the string `"***REDACTED***"`, and
+    /// an array or object becomes `null`.
+    #[default]
+    Replace,
+    /// The value and its events are removed. In an array, the indices of
+    /// later elements are unchanged, so the removal leaves a gap.
+    Remove,
+    /// The value becomes `null`.
+    Null,
+}
+
+/// Tracks which events to hide.
+#[derive(Debug)]
+struct Redactor {
+    patterns: Vec<Glob>,
+    mode: RedactMode,
+    /// Whether string events carry a `value`.
+    string_values: bool,
+    /// The depth of the hidden array or object being skipped.
+    skip: Option<usize>,
+    /// While a string's fragments are arriving, whether it is hidden.
+    string: Option<bool>,
+    /// Open containers at depths below this hold a hidden value.
+    redacted_below: usize,
+}
+
+impl Redactor {
+    fn redact<V: JsonValue>(
+        &mut self,
+        event: ParseEvent<V>,
+        replacement: &V::Str,
+    ) -> Option<ParseEvent<V>> {
+        let depth = event.path().len();
+        if let Some(skip) = self.skip {
+            if depth == skip
+                && matches!(
+                    event,
+                    ParseEvent::ArrayEnd { .. } | ParseEvent::ObjectEnd { .. }
+                )
+            {
+                self.skip = None;
+            }
+            return None;
+        }
+        let begins_value = match &event {
+            ParseEvent::String { is_final, .. } => {
+                let hidden = match self.string {
+                    Some(hidden) => hidden,
+                    None => self.matches(event.path()),
+                };
+                self.string = (!is_final).then_some(hidden);
+                if !hidden {
+                    return Some(event);
+                }
+                if !is_final {
+                    self.redacted_below = self.redacted_below.max(depth);
+                    return None;
+                }
+                false
+            }
+            ParseEvent::ArrayEnd { .. } | ParseEvent::ObjectEnd { .. } => {
+                return Some(self.end_container(event, depth));
+            }
+            _ => true,
+        };
+        if begins_value && !self.matches(event.path()) {
+            return Some(event);
+        }
+        self.redacted_below = self.redacted_below.max(depth);
+        match event {
+            ParseEvent::ArrayStart { path, .. } | ParseEvent::ObjectBegin { path, .. } => {
+                self.skip = Some(depth);
+                (self.mode != RedactMode::Remove).then_some(ParseEvent::Null { path })
+            }
+            ParseEvent::Null { .. }
+            | ParseEvent::ScalarBegin { .. }
+            | ParseEvent::ScalarEnd { .. }
+            | ParseEvent::ObjectKey { .. } => (self.mode != RedactMode::Remove).then_some(event),
+            ParseEvent::String { path, .. }
+            | ParseEvent::StringComplete { path, .. }
+            | ParseEvent::Number { path, .. }
+            | ParseEvent::Boolean { path, .. } => match self.mode {
+                RedactMode::Replace => Some(ParseEvent::String {
+                    path,
+                    value: self.string_values.then(|| replacement.clone()),
+                    fragment: replacement.clone(),
+                    is_final: true,
+                }),
+                RedactMode::Remove => None,
+                RedactMode::Null => Some(ParseEvent::Null { path }),
+            },
+            ParseEvent::ArrayEnd { .. } | ParseEvent::ObjectEnd { .. } => unreachable!(),
+        }
+    }
+
+    fn matches(&self, path: &[PathComponent]) -> bool {
+        self.patterns
+            .iter()
+            .any(|pattern| pattern.matches_path(path))
+    }
+
+    /// Drops the value of a container holding a hidden value.
+    fn end_container<V: JsonValue>(&mut self, event: ParseEvent<V>, depth: usize) -> ParseEvent<V> {
+        if depth >= self.redacted_below {
+            return event;
+        }
+        // Its ancestors hold the hidden value too; later siblings do not.
+        self.redacted_below = depth;
+        match event {
+            ParseEvent::ArrayEnd { path, .. } => ParseEvent::ArrayEnd { path, value: None },
+            ParseEvent::ObjectEnd { path, .. } => ParseEvent::ObjectEnd { path, value: None },
+            event => event,
+        }
+    }
+}
+
+/// A streaming parser that hides the values at chosen paths.
+///
+/// See the [module documentation](self) for how values are hidden.
+#[derive(Debug)]
+pub struct RedactingParser<V: JsonValue = Value> {
+    parser: StreamingParserImpl<V>,
+    redactor: Redactor,
+}
+
+impl<V: JsonValue> RedactingParser<V> {
+    /// Wraps `parser`, hiding each value whose path matches one of
+    /// `patterns` as `mode` specifies.
+    #[must_use]
+    pub fn new(parser: StreamingParserImpl<V>, patterns: Vec<Glob>, mode: RedactMode) -> Self {
+        Self {
+            redactor: Redactor {
+                patterns,
+                mode,
+                string_values: parser.string_value_mode() != StringValueMode::None,
+                skip: None,
+                string: None,
+                redacted_below: 0,
+            },
+            parser,
+        }
+    }
+
+    /// Feeds a chunk of JSON text and returns an iterator over the events it
+    /// completes, building values with `factory`.
+    pub fn feed_with<'a, F: JsonValueFactory<Value = V>>(
+        &'a mut self,
+        mut factory: F,
+        text: &str,
+    ) -> RedactedEvents<'a, F> {
+        RedactedEvents {
+            replacement: factory.new_string(REDACTED),
+            events: self.parser.feed_with(factory, text),
+            redactor: &mut self.redactor,
+        }
+    }
+
+    /// Marks the end of input and returns an iterator over the remaining
+    /// events, building values with `factory`.
+    pub fn finish_with<F: JsonValueFactory<Value = V>>(
+        self,
+        mut factory: F,
+    ) -> FinishedRedactedEvents<F> {
+        FinishedRedactedEvents {
+            replacement: factory.new_string(REDACTED),
+            events: self.parser.finish_with(factory),
+            redactor: self.redactor,
+        }
+    }
+}
+
+impl RedactingParser {
+    /// Feeds a chunk of JSON text and returns an iterator over the events it
+    /// completes.
+    pub fn feed<'a>(&'a mut self, text: &str) -> RedactedEvents<'a, StdValueFactory> {
+        self.feed_with(StdValueFactory, text)
+    }
+
+    /// Marks the end of input and returns an iterator over the remaining
+    /// events.
+    #[must_use]
+    pub fn finish(self) -> FinishedRedactedEvents<StdValueFactory> {
+        self.finish_with(StdValueFactory)
+    }
+}
+
+/// Iterator returned by [`RedactingParser::feed`].
+pub struct RedactedEvents<'a, F: JsonValueFactory> {
+    events: StreamingParserIteratorWith<'a, F>,
+    redactor: &'a mut Redactor,
+    replacement: <F::Value as JsonValue>::Str,
+}
+
+impl<F: JsonValueFactory> Iterator for RedactedEvents<'_, F> {
+    type Item = Result<ParseEvent<F::Value>, ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.events.next()? {
+                Ok(event) => {
+                    if let Some(event) = self.redactor.redact(event, &self.replacement) {
+                        return Some(Ok(event));
+                    }
+                }
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+/// Iterator returned by [`RedactingParser::finish`].
+pub struct FinishedRedactedEvents<F: JsonValueFactory> {
+    events: ClosedStreamingParser<F>,
+    redactor: Redactor,
+    replacement: <F::Value as JsonValue>::Str,
+}
+
+impl<F: JsonValueFactory> Iterator for FinishedRedactedEvents<F> {
+    type Item = Result<ParseEvent<F::Value>, ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.events.next()? {
+                Ok(event) => {
+                    if let Some(event) = self.redactor.redact(event, &self.replacement) {
+                        return Some(Ok(event));
+                    }
+                }
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::String, vec, vec::Vec};
+
+    use super::{RedactMode, RedactingParser};
+    use crate::{
+        Glob, NonScalarValueMode, ParseEvent, ParserOptions, StreamingParser, StringValueMode,
+        Value, event::reconstruct_values,
+    };
+
+    const JSON: &str = r#"{"user": {"name": "Ada Lovelace", "email": "ada@example.org",
+        "age": 36, "verified": true, "nick": null, "address": {"city": "London"}},
+        "items": [{"sku": "A1", "card": [4111, 1111]}, {"sku": "B2"}], "note": "ok"}"#;
+
+    const PATTERNS: [&str; 7] = [
+        "user.email",
+        "user.age",
+        "user.verified",
+        "user.nick",
+        "user.address",
+        "**.card",
+        "missing.path",
+    ];
+
+    fn redact(options: ParserOptions, mode: RedactMode, chunks: &[&str]) -> Vec<ParseEvent> {
+        let patterns = PATTERNS
+            .iter()
+            .map(|pattern| Glob::compile(pattern).unwrap())
+            .collect();
+        let mut parser = RedactingParser::new(StreamingParser::new(options), patterns, mode);
+        let mut events = Vec::new();
+        for chunk in chunks {
+            events.extend(parser.feed(chunk).map(Result::unwrap));
+        }
+        events.extend(parser.finish().map(Result::unwrap));
+        events
+    }
+
+    fn value(json: &str) -> Value {
+        StreamingParser::new(ParserOptions::default())
+            .into_value(json)
+            .unwrap()
+    }
+
+    #[test]
+    fn hides_values_in_every_mode() {
+        let redacted = r#""***REDACTED***""#;
+        for (mode, expected) in [
+            (
+                RedactMode::Replace,
+                alloc::format!(
+                    r#"{{"user": {{"name": "Ada Lovelace", "email": {redacted}, "age": {redacted},
+                    "verified": {redacted}, "nick": null, "address": null}},
+                    "items": [{{"sku": "A1", "card": null}}, {{"sku": "B2"}}], "note": "ok"}}"#
+                ),
+            ),
+            (
+                RedactMode::Null,
+                r#"{"user": {"name": "Ada Lovelace", "email": null, "age": null,
+                "verified": null, "nick": null, "address": null},
+                "items": [{"sku": "A1", "card": null}, {"sku": "B2"}], "note": "ok"}"#
+                    .into(),
+            ),
+            (
+                RedactMode::Remove,
+                r#"{"user": {"name": "Ada Lovelace"},
+                "items": [{"sku": "A1"}, {"sku": "B2"}], "note": "ok"}"#
+                    .into(),
+            ),
+        ] {
+            let expected = vec![value(&expected)];
+            for split in 0..=JSON.len() {
+                let events = redact(
+                    ParserOptions::default(),
+                    mode,
+                    &[&JSON[..split], &JSON[split..]],
+                );
+                assert!(events.iter().all(|event| match event {
+                    ParseEvent::String { fragment, .. } => {
+                        !fragment.contains('@') && !fragment.contains("London")
+                    }
+                    // Every number in the document is hidden.
+                    ParseEvent::Number { .. } => false,
+                    _ => true,
+                }));
+                assert_eq!(reconstruct_values(events), expected, "{mode:?} {split}");
+            }
+        }
+    }
+
+    #[test]
+    fn drops_the_values_of_containers_holding_hidden_values() {
+        let options = ParserOptions {
+            non_scalar_values: NonScalarValueMode::All,
+            string_value_mode: StringValueMode::Values,
+            ..ParserOptions::default()
+        };
+        let events = redact(options, RedactMode::Replace, &[JSON]);
+        let ends: Vec<(String, bool)> = events
+            .iter()
+            .filter_map(|event| match event {
+                ParseEvent::ArrayEnd { path, value } => {
+                    Some((alloc::format!("{path:?}"), value.is_some()))
+                }
+                ParseEvent::ObjectEnd { path, value } => {
+                    Some((alloc::format!("{path:?}"), value.is_some()))
+                }
+                _ => None,
+            })
+            .collect();
+        let expected = [
+            (r#"[Key("user")]"#, false),
+            (r#"[Key("items"), Index(0)]"#, false),
+            (r#"[Key("items"), Index(1)]"#, true),
+            (r#"[Key("items")]"#, false),
+            ("[]", false),
+        ];
+        assert_eq!(
+            ends,
+            expected.map(|(path, has_value)| (String::from(path), has_value))
+        );
+        assert!(events.iter().any(|event| matches!(
+            event,
+            ParseEvent::String { value: Some(value), .. } if value == "***REDACTED***"
+        )));
+    }
+}
@@ -0,0 +1,356 @@
+//! Validation of JSON documents without building values.
+//!
+//! [`validate_json`] first scans the document for the strict grammar of RFC
+//! 8259, without building paths or events and without allocating. Only a
+//! document the scan does not accept, because it is invalid, uses a leniency
+//! of the parser such as Unicode whitespace, or is nested more than 128 deep,
+//! is run through the parser, which finds the error and its position.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{is_valid_json, validate_json};
+//!
+//! assert!(is_valid_json(r#"{"a": [1, 2]}"#));
+//! assert!(!is_valid_json(r#"{"a": [1, 2}"#));
+//!
+//! let err = validate_json("[1,\n 2,\n x]").unwrap_err();
+//! assert_eq!((err.line, err.column), (3, 2));
+//! ```
+use crate::{ParserError, ParserOptions, StreamingParser};
+
+/// Returns `true` if `input` is one complete, valid JSON document.
+///
+/// See [`validate_json`].
+#[must_use]
+pub fn is_valid_json(input: &str) -> bool {
+    validate_json(input).is_ok()
+}
+
+/// Checks that `input` is one complete, valid JSON document, surrounded only
+/// by whitespace.
+///
+/// # Errors
+///
+/// Returns the first syntax error, with its line, column and byte offset, or
+/// an error at the end of the input if it ends before the document is
+/// complete.
+pub fn validate_json(input: &str) -> Result<(), ParserError> {
+    if Scanner::new(input.as_bytes()).document().is_some() {
+        return Ok(());
+    }
+    let mut parser = StreamingParser::new(ParserOptions::default());
+    for event in parser.feed(input) {
+        event?;
+    }
+    for event in parser.finish() {
+        event?;
+    }
+    Ok(())
+}
+
+/// Containers open at once beyond which [`Scanner`] leaves a document to the
+/// parser.
+const MAX_DEPTH: u32 = u128::BITS;
+
+/// A check of the strict JSON grammar over bytes.
+///
+/// Everything it accepts, the parser accepts with the default options, so a
+/// failed scan only means that the parser has to decide.
+struct Scanner<'a> {
+    input: &'a [u8],
+    pos: usize,
+    /// Whether each open container is an object, innermost in the lowest bit.
+    objects: u128,
+    depth: u32,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(input: &'a [u8]) -> Self {
+        Self {
+            input,
+            pos: 0,
+            objects: 0,
+            depth: 0,
+        }
+    }
+
+    /// Scans a whole document.
+    fn document(mut self) -> Option<()> {
+        self.whitespace();
+        loop {
+            self.value()?;
+            // Close containers until one continues with another value.
+            loop {
+                self.whitespace();
+                if self.depth == 0 {
+                    return (self.pos == self.input.len()).then_some(());
+                }
+                let in_object = self.objects & 1 == 1;
+                match self.next()? {
+                    b',' if in_object => {
+                        self.whitespace();
+                        self.key()?;
+                        break;
+                    }
+                    b',' => {
+                        self.whitespace();
+                        break;
+                    }
+                    b'}' if in_object => self.close(),
+                    b']' if !in_object => self.close(),
+                    _ => return None,
+                }
+            }
+        }
+    }
+
+    /// Scans a scalar, or the opening of a container up to its first value.
+    fn value(&mut self) -> Option<()> {
+        loop {
+            match self.next()? {
+                b'{' => {
+                    self.open(true)?;
+                    self.whitespace();
+                    if self.input.get(self.pos) == Some(&b'}') {
+                        self.pos += 1;
+                        self.close();
+                        return Some(());
+                    }
+                    self.key()?;
+                }
+                b'[' => {
+                    self.open(false)?;
+                    self.whitespace();
+                    if self.input.get(self.pos) == Some(&b']') {
+                        self.pos += 1;
+                        self.close();
+                        return Some(());
+                    }
+                }
+                b'"' => return self.string(),
+                b't' => return self.literal(b"rue"),
+                b'f' => return self.literal(b"alse"),
+                b'n' => return self.literal(b"ull"),
+                b'-' => {
+                    let first = self.next()?;
+                    return self.number(first);
+                }
+                first => return self.number(first),
+            }
+        }
+    }
+
+    /// Scans an object key, the colon after it and the whitespace before its
+    /// value.
+    fn key(&mut self) -> Option<()> {
+        if self.next()? != b'"' {
+            return None;
+        }
+        self.string()?;
+        self.whitespace();
+        if self.next()? != b':' {
+            return None;
+        }
+        self.whitespace();
+        Some(())
+    }
+
+    fn open(&mut self, object: bool) -> Option<()> {
+        if self.depth == MAX_DEPTH {
+            return None;
+        }
+        self.depth += 1;
+        self.objects = self.objects << 1 | u128::from(object);
+        Some(())
+    }
+
+    fn close(&mut self) {
+        self.depth -= 1;
+        self.objects >>= 1;
+    }
+
+    /// Scans the rest of a string after its opening quote.
+    fn string(&mut self) -> Option<()> {
+        loop {
+            match self.next()? {
+                b'"' => return Some(()),
+                b'\\' => match self.next()? {
+                    b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't' => {}
+                    b'u' => match self.hex()? {
+                        0xD800..=0xDBFF => {
+                            // An unpaired surrogate is an error by default.
+                            if self.next()? != b'\\' || self.next()? != b'u' {
+                                return None;
+                            }
+                            if !(0xDC00..=0xDFFF).contains(&self.hex()?) {
+                                return None;
+                            }
+                        }
+                        0xDC00..=0xDFFF => return None,
+                        _ => {}
+                    },
+                    _ => return None,
+                },
+                0..0x20 => return None,
+                _ => {}
+            }
+        }
+    }
+
+    /// Scans the four hex digits of a `\u` escape.
+    fn hex(&mut self) -> Option<u32> {
+        let digits = self.input.get(self.pos..self.pos + 4)?;
+        self.pos += 4;
+        digits.iter().try_fold(0, |code, &digit| {
+            Some(code << 4 | char::from(digit).to_digit(16)?)
+        })
+    }
+
+    /// Scans the rest of a number after its sign, starting with `first`.
+    fn number(&mut self, first: u8) -> Option<()> {
+        match first {
+            b'0' => {}
+            b'1'..=b'9' => self.digits(),
+            _ => return None,
+        }
+        if self.input.get(self.pos) == Some(&b'.') {
+            self.pos += 1;
+            self.first_digit()?;
+        }
+        if matches!(self.input.get(self.pos), Some(b'e' | b'E')) {
+            self.pos += 1;
+            if matches!(self.input.get(self.pos), Some(b'+' | b'-')) {
+                self.pos += 1;
+            }
+            self.first_digit()?;
+        }
+        Some(())
+    }
+
+    /// Scans one or more digits.
+    fn first_digit(&mut self) -> Option<()> {
+        if !self.next()?.is_ascii_digit() {
+            return None;
+        }
+        self.digits();
+        Some(())
+    }
+
+    fn digits(&mut self) {
+        while self.input.get(self.pos).is_some_and(u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+    }
+
+    fn literal(&mut self, rest: &[u8]) -> Option<()> {
+        if !self.input[self.pos..].starts_with(rest) {
+            return None;
+        }
+        self.pos += rest.len();
+        Some(())
+    }
+
+    fn whitespace(&mut self) {
+        while matches!(self.input.get(self.pos), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let byte = *self.input.get(self.pos)?;
+        self.pos += 1;
+        Some(byte)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::{String, ToString};
+
+    use quickcheck_macros::quickcheck;
+
+    use super::{Scanner, is_valid_json, validate_json};
+    use crate::{ParserOptions, StreamingParser, Value};
+
+    fn scans(json: &str) -> bool {
+        Scanner::new(json.as_bytes()).document().is_some()
+    }
+
+    fn parses(json: &str) -> bool {
+        StreamingParser::new(ParserOptions::default())
+            .into_value(json)
+            .is_ok()
+    }
+
+    #[test]
+    fn agrees_with_parsing() {
+        for json in [
+            r#" {"a": [1, -2.5e3, "é\"☕", true, null, {}]} "#,
+            "\"\\ud83d\\ude00\"",
+            "0",
+            "",
+            "  ",
+            "[1, x]",
+            r#"{"a": 1"#,
+            "[1] [2]",
+            "01",
+            "\"\\ud800\"",
+        ] {
+            let parsed = StreamingParser::new(ParserOptions::default()).into_value(json);
+            assert_eq!(validate_json(json), parsed.map(drop), "{json:?}");
+            assert_eq!(is_valid_json(json), validate_json(json).is_ok());
+        }
+    }
+
+    #[test]
+    fn leaves_lenient_documents_to_the_parser() {
+        for json in [
+            r#" {"a": [1, -2.5e3, "é\"☕", true, null, {}], "b": {"c": []}} "#,
+            "\"\\ud83d\\ude00 \\n\\u00e9\"",
+            "-0.0e+5",
+            "[[[[[[]]]]]]",
+        ] {
+            assert!(scans(json), "{json:?}");
+        }
+        let deep = "[".repeat(129) + &"]".repeat(129);
+        for json in [
+            "\u{FEFF}1",
+            "[1,\u{00A0}2]",
+            "\"\\ud800\"",
+            "\"\\udc00\\ud800\"",
+            "[1,]",
+            "01",
+            "1.",
+            "-",
+            "{\"a\" 1}",
+            "[1} ",
+            "\"\t\"",
+            &deep,
+        ] {
+            assert!(!scans(json), "{json:?}");
+            assert_eq!(is_valid_json(json), parses(json), "{json:?}");
+        }
+        assert!(is_valid_json(&deep));
+    }
+
+    #[quickcheck]
+    #[expect(clippy::needless_pass_by_value)]
+    fn scans_serialized_values(value: Value) -> bool {
+        scans(&value.to_string())
+    }
+
+    #[quickcheck]
+    #[expect(clippy::needless_pass_by_value)]
+    fn scans_only_what_parses(json: String) -> bool {
+        !scans(&json) || parses(&json)
+    }
+
+    #[test]
+    fn errors_report_their_position() {
+        let err = validate_json("{\"a\": [true,\n  fals]}").unwrap_err();
+        assert_eq!((err.line, err.column, err.byte_offset), (2, 7, 19));
+        let err = validate_json("[1, 2").unwrap_err();
+        assert_eq!(err.byte_offset, 5);
+    }
+}
@@ -4,7 +4,7 @@
 //! value, and provides helper functions for escaping JSON strings.
 use alloc::{collections::BTreeMap, string::String, vec::Vec};
 
-use crate::event::Key;
+use crate::event::{Key, PathComponent};
 
 pub type Str = String;
 pub type Map = BTreeMap<Key, Value>;
@@ -21,6 +21,11 @@ pub type Array = Vec<Value>;
 /// - Array
 /// - Object
 ///
+/// Objects are stored in a [`BTreeMap`], so their members are always kept,
+/// iterated and serialized in key order, whatever their order in the
+/// document. A parsed `Value` therefore needs no separate sorting step to be
+/// serialized deterministically.
+///
 /// # Examples
 ///
 /// ```
@@ -41,8 +46,9 @@ pub type Array = Vec<Value>;
     any(test, feature = "serde"),
     derive(serde::Serialize, serde::Deserialize)
 )]
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub enum Value {
+    #[default]
     Null,
     Boolean(bool),
     Number(f64),
@@ -51,12 +57,6 @@ pub enum Value {
     Object(Map),
 }
 
-impl Default for Value {
-    fn default() -> Self {
-        Self::Null
-    }
-}
-
 impl From<bool> for Value {
     fn from(v: bool) -> Self {
         Self::Boolean(v)
@@ -191,6 +191,310 @@ impl Value {
     pub fn is_object(&self) -> bool {
         matches!(self, Self::Object(..))
     }
+
+    /// Fills `null` values and missing object keys from `defaults`.
+    ///
+    /// A `null` value is replaced by a copy of the corresponding default. When
+    /// both values are objects, every key of `defaults` that is missing from
+    /// `self` is copied in and keys present on both sides are filled
+    /// recursively. All other values, including arrays, are left untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jsonmodem::{Map, Value};
+    ///
+    /// let mut config = Value::Object(Map::from([
+    ///     ("name".into(), Value::String("demo".into())),
+    ///     ("retries".into(), Value::Null),
+    /// ]));
+    /// let defaults = Value::Object(Map::from([
+    ///     ("retries".into(), Value::Number(3.0)),
+    ///     ("verbose".into(), Value::Boolean(false)),
+    /// ]));
+    ///
+    /// config.fill_defaults(&defaults);
+    /// assert_eq!(
+    ///     config.to_string(),
+    ///     r#"{"name":"demo","retries":3,"verbose":false}"#
+    /// );
+    /// ```
+    pub fn fill_defaults(&mut self, defaults: &Value) {
+        match (self, defaults) {
+            (this @ Self::Null, _) => this.clone_from(defaults),
+            (Self::Object(map), Self::Object(default_map)) => {
+                for (key, default) in default_map {
+                    match map.get_mut(key) {
+                        Some(value) => value.fill_defaults(default),
+                        None => {
+                            map.insert(key.clone(), default.clone());
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns every string in the value that satisfies `pred`, together with
+    /// its path, in document order (object members in key order).
+    ///
+    /// Object keys are not tested, only string values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jsonmodem::{Map, Value, path};
+    ///
+    /// let value = Value::Object(Map::from([
+    ///     ("email".into(), Value::String("a@example.com".into())),
+    ///     (
+    ///         "notes".into(),
+    ///         Value::Array(vec![
+    ///             Value::String("call me".into()),
+    ///             Value::String("or b@example.com".into()),
+    ///         ]),
+    ///     ),
+    /// ]));
+    /// assert_eq!(
+    ///     value.find_strings(|s| s.contains('@')),
+    ///     vec![
+    ///         (path!["email"], "a@example.com"),
+    ///         (path!["notes", 1], "or b@example.com"),
+    ///     ]
+    /// );
+    /// ```
+    pub fn find_strings(&self, pred: impl Fn(&str) -> bool) -> Vec<(Vec<PathComponent>, &str)> {
+        fn walk<'a>(
+            value: &'a Value,
+            pred: &impl Fn(&str) -> bool,
+            path: &mut Vec<PathComponent>,
+            found: &mut Vec<(Vec<PathComponent>, &'a str)>,
+        ) {
+            match value {
+                Value::String(s) if pred(s) => found.push((path.clone(), s)),
+                Value::Array(items) => {
+                    for (i, item) in items.iter().enumerate() {
+                        path.push(PathComponent::Index(i));
+                        walk(item, pred, path, found);
+                        path.pop();
+                    }
+                }
+                Value::Object(map) => {
+                    for (key, item) in map {
+                        path.push(PathComponent::Key(key.clone()));
+                        walk(item, pred, path, found);
+                        path.pop();
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut found = Vec::new();
+        walk(self, &pred, &mut Vec::new(), &mut found);
+        found
+    }
+
+    /// Converts an array of flat objects into columns: one vector per key,
+    /// holding that member of every object in order, with `null` where an
+    /// object lacks the key.
+    ///
+    /// Returns `None` unless the value is an array whose elements are all
+    /// objects with only scalar members. An empty array has no columns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jsonmodem::{Map, Value};
+    ///
+    /// let rows = Value::Array(vec![
+    ///     Value::Object(Map::from([("a".into(), Value::Number(1.0))])),
+    ///     Value::Object(Map::from([("b".into(), Value::Boolean(true))])),
+    /// ]);
+    /// let columns = rows.to_columns().unwrap();
+    /// assert_eq!(columns["a"], [Value::Number(1.0), Value::Null]);
+    /// assert_eq!(columns["b"], [Value::Null, Value::Boolean(true)]);
+    /// ```
+    #[must_use]
+    pub fn to_columns(&self) -> Option<BTreeMap<String, Vec<Value>>> {
+        let Value::Array(rows) = self else {
+            return None;
+        };
+        let mut columns = BTreeMap::<String, Vec<Value>>::new();
+        for (i, row) in rows.iter().enumerate() {
+            let Value::Object(members) = row else {
+                return None;
+            };
+            for (key, value) in members {
+                if value.is_array() || value.is_object() {
+                    return None;
+                }
+                let column = columns
+                    .entry(String::from(&**key))
+                    .or_insert_with(|| alloc::vec![Value::Null; i]);
+                column.resize(i, Value::Null);
+                column.push(value.clone());
+            }
+        }
+        for column in columns.values_mut() {
+            column.resize(rows.len(), Value::Null);
+        }
+        Some(columns)
+    }
+
+    /// Serializes the value as canonical JSON according to [RFC 8785] (JSON
+    /// Canonicalization Scheme), suitable for hashing and signing.
+    ///
+    /// Object keys are sorted by their UTF-16 code units, numbers use the
+    /// shortest ECMAScript representation, strings escape only what JSON
+    /// requires, and no whitespace is emitted.
+    ///
+    /// Returns `None` if the value contains a `NaN` or infinite number, which
+    /// have no JSON representation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jsonmodem::{Map, Value};
+    ///
+    /// let value = Value::Object(Map::from([
+    ///     ("b".into(), Value::Number(1e21)),
+    ///     ("a".into(), Value::Number(0.5)),
+    /// ]));
+    /// assert_eq!(value.to_jcs_string().unwrap(), r#"{"a":0.5,"b":1e+21}"#);
+    /// ```
+    ///
+    /// [RFC 8785]: https://datatracker.ietf.org/doc/html/rfc8785
+    #[must_use]
+    pub fn to_jcs_string(&self) -> Option<String> {
+        let mut out = String::new();
+        write_jcs(self, &mut out).ok()?;
+        Some(out)
+    }
+}
+
+/// Writes `value` in RFC 8785 canonical form. Fails on non-finite numbers.
+fn write_jcs(value: &Value, out: &mut String) -> core::fmt::Result {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Boolean(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => write_jcs_number(*n, out)?,
+        Value::String(s) => write_jcs_string(s, out),
+        Value::Array(arr) => {
+            out.push('[');
+            for (i, v) in arr.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_jcs(v, out)?;
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push('{');
+            for (i, (k, v)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_jcs_string(k, out);
+                out.push(':');
+                write_jcs(v, out)?;
+            }
+            out.push('}');
+        }
+    }
+    Ok(())
+}
+
+/// Writes a string literal using the minimal escaping required by RFC 8785.
+pub(crate) fn write_jcs_string(src: &str, out: &mut String) {
+    out.push('"');
+    write_jcs_string_contents(src, out);
+    out.push('"');
+}
+
+/// Writes the contents of a string literal, without its quotes, using the
+/// minimal escaping required by RFC 8785.
+pub(crate) fn write_jcs_string_contents(src: &str, out: &mut String) {
+    use core::fmt::Write;
+
+    for c in src.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{8}' => out.push_str("\\b"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\u{c}' => out.push_str("\\f"),
+            '\r' => out.push_str("\\r"),
+            c if c < '\u{20}' => {
+                // Writing to a `String` cannot fail.
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+}
+
+/// Writes a number the way ECMAScript's `Number.prototype.toString` does, as
+/// required by RFC 8785 section 3.2.2.3. Fails on non-finite numbers.
+pub(crate) fn write_jcs_number(n: f64, out: &mut String) -> core::fmt::Result {
+    use core::fmt::Write;
+
+    if !n.is_finite() {
+        return Err(core::fmt::Error);
+    }
+    if n == 0.0 {
+        // Covers -0 as well.
+        out.push('0');
+        return Ok(());
+    }
+    if n < 0.0 {
+        out.push('-');
+    }
+
+    // `{:e}` yields the shortest digit count that round-trips. When two
+    // candidates of that length are equally close, ECMAScript picks the even
+    // one, which is what exact formatting at that precision does.
+    let shortest = alloc::format!("{:e}", n.abs());
+    let precision = shortest
+        .split_once('e')
+        .map_or(0, |(mantissa, _)| mantissa.len().saturating_sub(2));
+    let sci = alloc::format!("{:.precision$e}", n.abs());
+    let (mantissa, exponent) = sci.split_once('e').ok_or(core::fmt::Error)?;
+    let exponent: i32 = exponent.parse().map_err(|_| core::fmt::Error)?;
+    let digits: String = mantissa.chars().filter(char::is_ascii_digit).collect();
+    let k = i32::try_from(digits.len()).map_err(|_| core::fmt::Error)?;
+    // The decimal point sits after the first `point` digits.
+    let point = exponent + 1;
+
+    if k <= point && point <= 21 {
+        out.push_str(&digits);
+        for _ in k..point {
+            out.push('0');
+        }
+    } else if 0 < point && point <= 21 {
+        let (int, frac) = digits.split_at(point.unsigned_abs() as usize);
+        write!(out, "{int}.{frac}")?;
+    } else if -6 < point && point <= 0 {
+        out.push_str("0.");
+        for _ in point..0 {
+            out.push('0');
+        }
+        out.push_str(&digits);
+    } else {
+        let (first, rest) = digits.split_at(1);
+        out.push_str(first);
+        if !rest.is_empty() {
+            write!(out, ".{rest}")?;
+        }
+        let sign = if point > 0 { '+' } else { '-' };
+        write!(out, "e{sign}{}", (point - 1).unsigned_abs())?;
+    }
+    Ok(())
 }
 
 /// Escapes control characters in a string for inclusion in a JSON string
@@ -273,3 +577,31 @@ impl core::fmt::Display for Value {
         }
     }
 }
+
+/// Compares with a [`serde_json::Value`], so that assertions written against
+/// `serde_json` keep working. Numbers are compared as `f64`.
+#[cfg(any(test, feature = "serde"))]
+impl PartialEq<serde_json::Value> for Value {
+    fn eq(&self, other: &serde_json::Value) -> bool {
+        match (self, other) {
+            (Value::Null, serde_json::Value::Null) => true,
+            (Value::Boolean(a), serde_json::Value::Bool(b)) => a == b,
+            (Value::Number(a), serde_json::Value::Number(b)) => b.as_f64() == Some(*a),
+            (Value::String(a), serde_json::Value::String(b)) => a == b,
+            (Value::Array(a), serde_json::Value::Array(b)) => a == b,
+            (Value::Object(a), serde_json::Value::Object(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .all(|(key, value)| b.get(&**key).is_some_and(|other| value == other))
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(any(test, feature = "serde"))]
+impl PartialEq<Value> for serde_json::Value {
+    fn eq(&self, other: &Value) -> bool {
+        other == self
+    }
+}
@@ -27,6 +27,41 @@ impl<V: JsonValue> ValueZipper<V> {
         }
     }
 
+    /// Deep-copies the zipper, re-entering the copy along `path`.
+    ///
+    /// The cursor holds pointers into `root`, so it cannot be copied as-is;
+    /// instead the copy walks `path` (the components that were used to enter
+    /// each open container) through its own tree.
+    pub fn clone_along(&self, path: &[PathComponent]) -> Self {
+        let mut root = Box::new((*self.root).clone());
+        let mut ptrs = Vec::with_capacity(self.path.capacity());
+        let mut current = NonNull::from(root.as_mut());
+        for pc in path.iter().take(self.path.len()) {
+            // SAFETY: `current` points either at `root` or at a descendant
+            // reached on a previous iteration. Nothing else borrows the new
+            // tree, and we only descend, so no container holding a pointed-to
+            // element is mutated.
+            let parent = unsafe { current.as_mut() };
+            let child = match pc {
+                PathComponent::Key(k) => {
+                    V::as_object_mut(parent).and_then(|o| V::object_get_mut(o, k))
+                }
+                PathComponent::Index(i) => {
+                    V::as_array_mut(parent).and_then(|a| V::array_get_mut(a, *i))
+                }
+            };
+            let Some(child) = child else { break };
+            current = NonNull::from(child);
+            ptrs.push(current);
+        }
+        Self {
+            root,
+            path: ptrs,
+            #[cfg(test)]
+            path_components: self.path_components.clone(),
+        }
+    }
+
     #[inline]
     fn current_mut(&mut self) -> &mut V {
         match self.path.last().copied().as_mut() {
@@ -450,6 +485,14 @@ impl<V: JsonValue> ValueBuilder<V> {
         }
     }
 
+    pub fn clone_along(&self, path: &[PathComponent]) -> Self {
+        let state = match &self.state {
+            BuilderState::Ready(z) => BuilderState::Ready(z.clone_along(path)),
+            BuilderState::Empty => BuilderState::Empty,
+        };
+        Self { state }
+    }
+
     #[inline]
     pub fn pop(&mut self) -> Result<&mut V, ZipperError> {
         match &mut self.state {
@@ -543,9 +586,16 @@ impl StreamingParserBuilder {
                         },
                     )?;
                 }
+                ParseEvent::StringComplete { path, value } => {
+                    self.state.set(
+                        path.last(),
+                        Value::String(value.clone()),
+                        &mut StdValueFactory,
+                    )?;
+                }
 
                 // ── container starts ───────────────────────────────────────
-                ParseEvent::ObjectBegin { path } => {
+                ParseEvent::ObjectBegin { path, .. } => {
                     use crate::value::Map;
 
                     self.state
@@ -553,7 +603,7 @@ impl StreamingParserBuilder {
                             Value::Object(Map::new())
                         })?;
                 }
-                ParseEvent::ArrayStart { path } => {
+                ParseEvent::ArrayStart { path, .. } => {
                     self.state
                         .enter_with(path.last(), &mut StdValueFactory, |_| {
                             Value::Array(Vec::new())
@@ -567,6 +617,9 @@ impl StreamingParserBuilder {
                         self.state.pop()?;
                     }
                 }
+                ParseEvent::ScalarBegin { .. }
+                | ParseEvent::ScalarEnd { .. }
+                | ParseEvent::ObjectKey { .. } => {}
             }
         }
 
@@ -600,7 +653,7 @@ mod tests {
     // ─────────────────────────────────────────────────────────────────────
 
     #[rstest]
-    #[timeout(Duration::from_millis(1_000))]
+    #[timeout(Duration::from_secs(1))]
     fn builds_complex_object_tree() {
         let mut b = StreamingParserBuilder::new(default_opts());
 
@@ -0,0 +1,590 @@
+//! Conversion of event streams to XML.
+//!
+//! [`JsonToXml`] writes the document described by a stream of events as XML,
+//! e.g. to forward JSON to a SOAP or other XML-only service without building
+//! the whole value first. Each root value becomes an element named by
+//! [`JsonToXmlOptions::root_element`], and within it:
+//!
+//! * an object becomes the children of its element, one element per member,
+//!   named by its key;
+//! * an array becomes the children of its element, one element per item, named
+//!   by [`JsonToXmlOptions::array_item_tag`], or, with
+//!   [`JsonToXmlOptions::repeat_member_tag`], an array that is the value of an
+//!   object member becomes one element per item named by the member's key;
+//! * a string, number or boolean becomes the text of its element, and `null` an
+//!   empty element.
+//!
+//! `{"name": "Ann", "tags": ["a", "b"]}` is written as
+//! `<root><name>Ann</name><tags><item>a</item><item>b</item></tags></root>`.
+//!
+//! Strings are written as their fragments arrive. Keys must be valid XML
+//! names, and strings may not hold characters XML 1.0 cannot represent, such
+//! as most control characters.
+//!
+//! # Examples
+//!
+//! ```
+//! use jsonmodem::{JsonToXml, JsonToXmlOptions, ParserOptions, StreamingParser};
+//!
+//! let options = JsonToXmlOptions {
+//!     repeat_member_tag: true,
+//!     attribute_prefix: Some("@".into()),
+//!     ..JsonToXmlOptions::default()
+//! };
+//! let mut xml = JsonToXml::new(String::new(), options);
+//! let mut parser = StreamingParser::new(ParserOptions::default());
+//! for event in parser.feed(r#"{"book": {"@id": 7, "author": ["A & B", "C"], "note": null}}"#) {
+//!     xml.write_event(&event?)?;
+//! }
+//! assert_eq!(
+//!     xml.into_inner(),
+//!     r#"<root><book id="7"><author>A &amp; B</author><author>C</author><note/></book></root>"#
+//! );
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+use alloc::{
+    borrow::Cow,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::{JsonValue, ParseEvent, ParseEventWriter, PathComponent};
+
+/// Configuration for [`JsonToXml`].
+#[derive(Debug, Clone)]
+pub struct JsonToXmlOptions {
+    /// The name of the element holding each root value.
+    ///
+    /// # Default
+    ///
+    /// `"root"`
+    pub root_element: Cow<'static, str>,
+    /// The name of the element holding each array item.
+    ///
+    /// # Default
+    ///
+    /// `"item"`
+    pub array_item_tag: Cow<'static, str>,
+    /// Write the items of an array that is the value of an object member as
+    /// repeated elements named by the member's key, without an element for
+    /// the array itself. Other arrays still use `array_item_tag`.
+    ///
+    /// # Default
+    ///
+    /// `false`
+    pub repeat_member_tag: bool,
+    /// Object members whose key starts with this prefix and whose value is
+    /// not an array or object are written as attributes of the object's
+    /// element, named by the rest of the key. They must come before the other
+    /// members of their object.
+    ///
+    /// # Default
+    ///
+    /// `None`, writing every member as an element.
+    pub attribute_prefix: Option<Cow<'static, str>>,
+}
+
+impl Default for JsonToXmlOptions {
+    fn default() -> Self {
+        Self {
+            root_element: Cow::Borrowed("root"),
+            array_item_tag: Cow::Borrowed("item"),
+            repeat_member_tag: false,
+            attribute_prefix: None,
+        }
+    }
+}
+
+/// Error returned by [`JsonToXml`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum XmlError {
+    /// A key is not a valid XML name, or a string holds a character XML
+    /// cannot represent.
+    Unrepresentable(Vec<PathComponent>),
+    /// A member to be written as an attribute follows a member written as an
+    /// element.
+    LateAttribute(Vec<PathComponent>),
+    /// The underlying writer failed.
+    Write(fmt::Error),
+}
+
+impl fmt::Display for XmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            XmlError::Unrepresentable(path) => {
+                write!(f, "value not representable in XML at {path:?}")
+            }
+            XmlError::LateAttribute(path) => write!(f, "attribute after child element at {path:?}"),
+            XmlError::Write(err) => err.fmt(f),
+        }
+    }
+}
+
+impl core::error::Error for XmlError {}
+
+impl From<fmt::Error> for XmlError {
+    fn from(err: fmt::Error) -> Self {
+        XmlError::Write(err)
+    }
+}
+
+/// Where a scalar value is written.
+#[derive(Debug)]
+enum Target {
+    Element(String),
+    Attribute,
+}
+
+#[derive(Debug)]
+struct Frame {
+    /// The name of the container's element, or `None` for an array whose
+    /// items repeat the tag of its member.
+    tag: Option<String>,
+    /// For an array, the name of the elements of its items.
+    item_tag: Option<String>,
+}
+
+/// Writes XML for a stream of [`ParseEvent`]s.
+///
+/// Events must form well-nested values, as produced by the parser. See the
+/// [module documentation](self) for the mapping.
+#[derive(Debug)]
+pub struct JsonToXml<W: fmt::Write> {
+    writer: W,
+    options: JsonToXmlOptions,
+    frames: Vec<Frame>,
+    /// Whether the start tag of the innermost element still lacks its `>`,
+    /// so that attributes can be added.
+    open: bool,
+    /// Where the string being written goes, if one is in progress.
+    string: Option<Target>,
+}
+
+impl<W: fmt::Write> JsonToXml<W> {
+    /// Creates a converter that writes its output to `writer`.
+    pub fn new(writer: W, options: JsonToXmlOptions) -> Self {
+        Self {
+            writer,
+            options,
+            frames: Vec::new(),
+            open: false,
+            string: None,
+        }
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Writes the XML for one event.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the event cannot be represented in XML or the
+    /// underlying writer fails.
+    pub fn write_event<V>(&mut self, event: &ParseEvent<V>) -> Result<(), XmlError>
+    where
+        V: JsonValue,
+        V::Str: AsRef<str>,
+        V::Num: Into<f64>,
+        V::Bool: Into<bool>,
+    {
+        match event {
+            ParseEvent::Null { path } => self.scalar(path, ""),
+            ParseEvent::Boolean { path, value } => {
+                self.scalar(path, if (*value).into() { "true" } else { "false" })
+            }
+            ParseEvent::Number { path, value } => {
+                let n: f64 = (*value).into();
+                self.scalar(path, &alloc::format!("{n}"))
+            }
+            ParseEvent::String {
+                path,
+                fragment,
+                is_final,
+                ..
+            } => self.string(path, fragment.as_ref(), *is_final),
+            ParseEvent::StringComplete { path, value } => self.string(path, value.as_ref(), true),
+            ParseEvent::ArrayStart { path, .. } => self.open(path, true),
+            ParseEvent::ObjectBegin { path, .. } => self.open(path, false),
+            ParseEvent::ArrayEnd { .. } | ParseEvent::ObjectEnd { .. } => self.close(),
+            ParseEvent::ScalarBegin { .. }
+            | ParseEvent::ScalarEnd { .. }
+            | ParseEvent::ObjectKey { .. } => Ok(()),
+        }
+    }
+
+    /// Returns where the value at `path` goes, writing its attribute name
+    /// if it is an attribute.
+    fn target(&mut self, path: &[PathComponent], scalar: bool) -> Result<Target, XmlError> {
+        let unrepresentable = || XmlError::Unrepresentable(path.to_vec());
+        let name = match (self.frames.last(), path.last()) {
+            (None, _) => self.options.root_element.to_string(),
+            (
+                Some(Frame {
+                    item_tag: Some(tag),
+                    ..
+                }),
+                _,
+            ) => tag.clone(),
+            (_, Some(PathComponent::Key(key))) => {
+                let attribute = self
+                    .options
+                    .attribute_prefix
+                    .as_deref()
+                    .and_then(|prefix| key.strip_prefix(prefix))
+                    .filter(|_| scalar);
+                if let Some(name) = attribute {
+                    if !is_xml_name(name) {
+                        return Err(unrepresentable());
+                    }
+                    if !self.open {
+                        return Err(XmlError::LateAttribute(path.to_vec()));
+                    }
+                    write!(self.writer, " {name}=\"")?;
+                    return Ok(Target::Attribute);
+                }
+                key.to_string()
+            }
+            (_, _) => return Err(unrepresentable()),
+        };
+        if !is_xml_name(&name) {
+            return Err(unrepresentable());
+        }
+        Ok(Target::Element(name))
+    }
+
+    /// Ends the start tag of the innermost element before a child.
+    fn begin_child(&mut self) -> fmt::Result {
+        if self.open {
+            self.open = false;
+            self.writer.write_char('>')?;
+        }
+        Ok(())
+    }
+
+    fn scalar(&mut self, path: &[PathComponent], text: &str) -> Result<(), XmlError> {
+        match self.target(path, true)? {
+            Target::Attribute => {
+                self.writer.write_str(text)?;
+                self.writer.write_char('"')?;
+            }
+            Target::Element(name) => {
+                self.begin_child()?;
+                if text.is_empty() {
+                    write!(self.writer, "<{name}/>")?;
+                } else {
+                    write!(self.writer, "<{name}>{text}</{name}>")?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn string(
+        &mut self,
+        path: &[PathComponent],
+        fragment: &str,
+        is_final: bool,
+    ) -> Result<(), XmlError> {
+        if !fragment.chars().all(is_xml_char) {
+            return Err(XmlError::Unrepresentable(path.to_vec()));
+        }
+        let target = if let Some(target) = self.string.take() {
+            target
+        } else {
+            let target = self.target(path, true)?;
+            if let Target::Element(name) = &target {
+                self.begin_child()?;
+                write!(self.writer, "<{name}>")?;
+            }
+            target
+        };
+        let in_attribute = matches!(target, Target::Attribute);
+        write_escaped(&mut self.writer, fragment, in_attribute)?;
+        if !is_final {
+            self.string = Some(target);
+            return Ok(());
+        }
+        match target {
+            Target::Attribute => self.writer.write_char('"')?,
+            Target::Element(name) => write!(self.writer, "</{name}>")?,
+        }
+        Ok(())
+    }
+
+    fn open(&mut self, path: &[PathComponent], is_array: bool) -> Result<(), XmlError> {
+        let item_tag = is_array.then(|| self.options.array_item_tag.to_string());
+        if is_array && self.options.repeat_member_tag {
+            if let (Some(Frame { item_tag: None, .. }), Some(PathComponent::Key(key))) =
+                (self.frames.last(), path.last())
+            {
+                if !is_xml_name(key) {
+                    return Err(XmlError::Unrepresentable(path.to_vec()));
+                }
+                self.frames.push(Frame {
+                    tag: None,
+                    item_tag: Some(key.to_string()),
+                });
+                return Ok(());
+            }
+        }
+        let Target::Element(name) = self.target(path, false)? else {
+            unreachable!("containers are never attributes");
+        };
+        self.begin_child()?;
+        write!(self.writer, "<{name}")?;
+        self.open = true;
+        self.frames.push(Frame {
+            tag: Some(name),
+            item_tag,
+        });
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<(), XmlError> {
+        if let Some(Frame { tag: Some(tag), .. }) = self.frames.pop() {
+            if self.open {
+                self.writer.write_str("/>")?;
+            } else {
+                write!(self.writer, "</{tag}>")?;
+            }
+            self.open = false;
+        }
+        Ok(())
+    }
+}
+
+impl<V, W> ParseEventWriter<V> for JsonToXml<W>
+where
+    V: JsonValue,
+    V::Str: AsRef<str>,
+    V::Num: Into<f64>,
+    V::Bool: Into<bool>,
+    W: fmt::Write,
+{
+    type Error = XmlError;
+
+    fn write_event(&mut self, event: ParseEvent<V>) -> Result<(), Self::Error> {
+        JsonToXml::write_event(self, &event)
+    }
+}
+
+/// Returns `true` if `name` is a valid XML element or attribute name,
+/// without a namespace prefix.
+fn is_xml_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+/// Returns `true` if XML 1.0 can represent `c`.
+fn is_xml_char(c: char) -> bool {
+    matches!(c, '\t' | '\n' | '\r' | '\u{20}'..='\u{FFFD}' | '\u{10000}'..)
+}
+
+/// Writes `text` escaped for element content or, with `in_attribute`, for a
+/// double-quoted attribute value. Characters a reader would normalize, such
+/// as carriage returns, are written as character references.
+fn write_escaped<W: fmt::Write>(writer: &mut W, text: &str, in_attribute: bool) -> fmt::Result {
+    let mut rest = text;
+    while let Some(at) = rest.find(|c| {
+        matches!(c, '&' | '<' | '>' | '\r') || in_attribute && matches!(c, '"' | '\t' | '\n')
+    }) {
+        writer.write_str(&rest[..at])?;
+        let c = rest[at..].chars().next().unwrap_or_default();
+        writer.write_str(match c {
+            '&' => "&amp;",
+            '<' => "&lt;",
+            '>' => "&gt;",
+            '"' => "&quot;",
+            '\t' => "&#x9;",
+            '\n' => "&#xA;",
+            _ => "&#xD;",
+        })?;
+        rest = &rest[at + c.len_utf8()..];
+    }
+    writer.write_str(rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        format,
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+
+    use quick_xml::{Reader, events::Event};
+
+    use super::{JsonToXml, JsonToXmlOptions, XmlError};
+    use crate::{ParserOptions, Value, event::reconstruct_values, path, tests::events, value::Map};
+
+    fn multiple_values() -> ParserOptions {
+        ParserOptions {
+            allow_multiple_json_values: true,
+            ..ParserOptions::default()
+        }
+    }
+
+    fn convert(json: &str, parts: usize, options: JsonToXmlOptions) -> Result<String, XmlError> {
+        let mut xml = JsonToXml::new(String::new(), options);
+        for event in events(json, parts, multiple_values()) {
+            xml.write_event(&event)?;
+        }
+        Ok(xml.into_inner())
+    }
+
+    #[derive(Debug, Default)]
+    struct Element {
+        name: String,
+        attributes: Vec<(String, String)>,
+        text: String,
+        children: Vec<Element>,
+    }
+
+    /// Reads `xml`, a sequence of elements, with `quick-xml`.
+    fn read(xml: &str) -> Vec<Element> {
+        let mut reader = Reader::from_str(xml);
+        let mut stack = vec![Element::default()];
+        loop {
+            match reader.read_event().unwrap() {
+                event @ (Event::Start(_) | Event::Empty(_)) => {
+                    let (Event::Start(start) | Event::Empty(start)) = &event else {
+                        unreachable!();
+                    };
+                    let element = Element {
+                        name: String::from_utf8(start.name().as_ref().to_vec()).unwrap(),
+                        attributes: start
+                            .attributes()
+                            .map(|attribute| {
+                                let attribute = attribute.unwrap();
+                                (
+                                    String::from_utf8(attribute.key.as_ref().to_vec()).unwrap(),
+                                    attribute.unescape_value().unwrap().into_owned(),
+                                )
+                            })
+                            .collect(),
+                        ..Element::default()
+                    };
+                    if matches!(event, Event::Start(_)) {
+                        stack.push(element);
+                    } else {
+                        stack.last_mut().unwrap().children.push(element);
+                    }
+                }
+                Event::End(_) => {
+                    let element = stack.pop().unwrap();
+                    stack.last_mut().unwrap().children.push(element);
+                }
+                Event::Text(text) => {
+                    let text = text.xml10_content().unwrap();
+                    stack.last_mut().unwrap().text.push_str(&text);
+                }
+                Event::GeneralRef(reference) => {
+                    let name = reference.decode().unwrap();
+                    let text = quick_xml::escape::unescape(&format!("&{name};"))
+                        .unwrap()
+                        .into_owned();
+                    stack.last_mut().unwrap().text.push_str(&text);
+                }
+                Event::Eof => break,
+                other => panic!("unexpected {other:?}"),
+            }
+        }
+        assert_eq!(stack.len(), 1);
+        stack.pop().unwrap().children
+    }
+
+    /// Rebuilds a value from an element written with the default options:
+    /// children named `item` form an array, other children an object, and
+    /// text a string, so scalars come back as strings.
+    fn to_value(element: &Element) -> Value {
+        match element.children.first() {
+            None => Value::String(element.text.as_str().into()),
+            Some(first) if first.name == "item" => {
+                Value::Array(element.children.iter().map(to_value).collect())
+            }
+            Some(_) => Value::Object(
+                element
+                    .children
+                    .iter()
+                    .map(|child| (child.name.as_str().into(), to_value(child)))
+                    .collect::<Map>(),
+            ),
+        }
+    }
+
+    /// `value` with every scalar written as a string, `null` as the empty
+    /// string.
+    fn stringify(value: Value) -> Value {
+        match value {
+            Value::Null => Value::String(String::new()),
+            Value::Boolean(b) => Value::String(b.to_string()),
+            Value::Number(n) => Value::String(format!("{n}")),
+            Value::String(s) => Value::String(s),
+            Value::Array(items) => Value::Array(items.into_iter().map(stringify).collect()),
+            Value::Object(members) => Value::Object(
+                members
+                    .into_iter()
+                    .map(|(key, member)| (key, stringify(member)))
+                    .collect(),
+            ),
+        }
+    }
+
+    #[test]
+    fn round_trips_through_quick_xml() {
+        let json = r#"{"name": "<a & b> \"c\"\r\n", "n": [1, -2.5, [true, null]],
+            "nested": {"x_1": "☕ é", "y-2.z": [{"k": false}]}} "second" [["deep"]]"#;
+        let expected: Vec<Value> = reconstruct_values(events(json, 1, multiple_values()))
+            .into_iter()
+            .map(stringify)
+            .collect();
+        for parts in [1, 5, json.len()] {
+            let xml = convert(json, parts, JsonToXmlOptions::default()).unwrap();
+            let roots = read(&xml);
+            assert!(roots.iter().all(|root| root.name == "root"));
+            let values: Vec<Value> = roots.iter().map(to_value).collect();
+            assert_eq!(values, expected, "{parts}: {xml}");
+        }
+    }
+
+    #[test]
+    fn options_shape_the_elements() {
+        let options = JsonToXmlOptions {
+            root_element: "doc".into(),
+            array_item_tag: "li".into(),
+            repeat_member_tag: true,
+            attribute_prefix: Some("@".into()),
+        };
+        let json = r#"{"@id": "a\"1", "@n": null, "tag": ["x", ["y"]], "empty": [], "o": {}} [1]"#;
+        let xml = convert(json, 1, options.clone()).unwrap();
+        assert_eq!(
+            xml,
+            "<doc id=\"a&quot;1\" n=\"\"><tag>x</tag><tag><li>y</li></tag><o/></doc>\
+             <doc><li>1</li></doc>"
+        );
+        let doc = &read(&xml)[0];
+        assert_eq!(
+            doc.attributes,
+            [("id".into(), "a\"1".into()), ("n".into(), String::new())]
+        );
+
+        assert_eq!(
+            convert(r#"{"a": 1, "@b": 2}"#, 1, options.clone()),
+            Err(XmlError::LateAttribute(path!["@b"]))
+        );
+        assert_eq!(
+            convert(r#"{"1a": 1}"#, 1, options.clone()),
+            Err(XmlError::Unrepresentable(path!["1a"]))
+        );
+        assert_eq!(
+            convert("[\"\\u0001\"]", 1, options),
+            Err(XmlError::Unrepresentable(path![0]))
+        );
+    }
+}
@@ -0,0 +1,206 @@
+//! Property tests feeding arbitrary bytes to the parser in arbitrary chunks.
+//!
+//! Half of the generated inputs are valid documents, which must survive being
+//! printed and parsed again; the other half are damaged documents, which
+//! exercise the error paths of the buffer, lexer and state machine.
+
+use jsonmodem::{
+    JsonPrettyPrinter, ParseEvent, ParserError, ParserOptions, PrettyOptions, StreamingParser,
+    StringValueMode,
+};
+use quickcheck::{Arbitrary, Gen, QuickCheck};
+
+const SCALARS: &[&[u8]] = &[
+    b"0",
+    b"-1.5e3",
+    b"true",
+    b"false",
+    b"null",
+    b"\"\"",
+    b"\"key\"",
+    b"\"\\u00e9\\n\\\"\"",
+    b"\"\\ud83d\\ude00\"",
+    "\"é☕😀\"".as_bytes(),
+];
+
+const WHITESPACE: &[&[u8]] = &[b"", b"", b" ", b"\n", b"\t\r\n  "];
+
+/// Bytes of a JSON document, damaged in half of the cases by replacing,
+/// inserting or removing arbitrary bytes.
+#[derive(Debug, Clone)]
+struct Input(Vec<u8>);
+
+impl Arbitrary for Input {
+    fn arbitrary(g: &mut Gen) -> Self {
+        let mut bytes = Vec::new();
+        if bool::arbitrary(g) {
+            bytes.extend_from_slice(b"\xEF\xBB\xBF");
+        }
+        write_value(g, &mut bytes, 4);
+        if bool::arbitrary(g) {
+            for _ in 0..=usize::arbitrary(g) % 4 {
+                let at = usize::arbitrary(g) % (bytes.len() + 1);
+                match u8::arbitrary(g) % 3 {
+                    0 => bytes.insert(at, u8::arbitrary(g)),
+                    1 if at < bytes.len() => bytes[at] = u8::arbitrary(g),
+                    _ if at < bytes.len() => drop(bytes.remove(at)),
+                    _ => {}
+                }
+            }
+        }
+        Input(bytes)
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        Box::new(self.0.shrink().map(Input))
+    }
+}
+
+fn write_value(g: &mut Gen, bytes: &mut Vec<u8>, depth: usize) {
+    bytes.extend_from_slice(g.choose(WHITESPACE).unwrap());
+    match u8::arbitrary(g) % 4 {
+        0 if depth > 0 => {
+            bytes.push(b'[');
+            for i in 0..usize::arbitrary(g) % 4 {
+                if i > 0 {
+                    bytes.push(b',');
+                }
+                write_value(g, bytes, depth - 1);
+            }
+            bytes.push(b']');
+        }
+        1 if depth > 0 => {
+            bytes.push(b'{');
+            for i in 0..usize::arbitrary(g) % 4 {
+                if i > 0 {
+                    bytes.push(b',');
+                }
+                bytes.extend_from_slice(g.choose(WHITESPACE).unwrap());
+                bytes.extend_from_slice(g.choose(&[&b"\"a\""[..], b"\"b\"", b"\"\""]).unwrap());
+                bytes.push(b':');
+                write_value(g, bytes, depth - 1);
+            }
+            bytes.push(b'}');
+        }
+        _ => bytes.extend_from_slice(g.choose(SCALARS).unwrap()),
+    }
+    bytes.extend_from_slice(g.choose(WHITESPACE).unwrap());
+}
+
+/// Feeds `bytes` split at `splits`, returning the events or the first error.
+fn parse(bytes: &[u8], splits: &[usize]) -> Result<Vec<ParseEvent>, ParserError> {
+    let mut parser = StreamingParser::new(ParserOptions {
+        string_value_mode: StringValueMode::Values,
+        ..Default::default()
+    });
+    let mut events = Vec::new();
+    let mut rest = bytes;
+    let mut splits = splits.iter();
+    while !rest.is_empty() {
+        let size = splits
+            .next()
+            .map_or(rest.len(), |split| split % rest.len() + 1);
+        let (chunk, tail) = rest.split_at(size);
+        for event in parser.feed_bytes(chunk)? {
+            events.push(event?);
+        }
+        rest = tail;
+    }
+    for event in parser.finish() {
+        events.push(event?);
+    }
+    Ok(events)
+}
+
+/// Replaces the fragments of each string by one event holding the whole
+/// string, since fragment boundaries depend on the chunking.
+fn coalesce(events: Vec<ParseEvent>) -> Vec<ParseEvent> {
+    events
+        .into_iter()
+        .filter_map(|event| match event {
+            ParseEvent::String {
+                is_final: false, ..
+            } => None,
+            ParseEvent::String {
+                path,
+                value: Some(value),
+                is_final: true,
+                ..
+            } => Some(ParseEvent::String {
+                path,
+                fragment: value.clone(),
+                value: Some(value),
+                is_final: true,
+            }),
+            event => Some(event),
+        })
+        .collect()
+}
+
+fn check(input: &Input, splits: &[usize]) -> bool {
+    match parse(&input.0, splits) {
+        Ok(events) => {
+            let mut printer = JsonPrettyPrinter::new(String::new(), PrettyOptions::default());
+            for event in &events {
+                printer.write_event(event).unwrap();
+            }
+            let text = printer.into_inner();
+            let reparsed = parse(text.as_bytes(), &[]).unwrap();
+            coalesce(reparsed) == coalesce(events)
+        }
+        Err(err) => err.line > 0 && err.column > 0 && err.byte_offset <= input.0.len(),
+    }
+}
+
+fn tests() -> u64 {
+    if cfg!(any(miri, feature = "test-fast")) {
+        10
+    } else if is_ci::cached() {
+        10_000
+    } else {
+        1_000
+    }
+}
+
+#[test]
+fn documents_in_arbitrary_chunks() {
+    #[expect(clippy::needless_pass_by_value)]
+    fn prop(input: Input, splits: Vec<usize>) -> bool {
+        check(&input, &splits)
+    }
+
+    QuickCheck::new()
+        .tests(tests())
+        .quickcheck(prop as fn(Input, Vec<usize>) -> bool);
+}
+
+#[test]
+fn raw_bytes_in_arbitrary_chunks() {
+    #[expect(clippy::needless_pass_by_value)]
+    fn prop(bytes: Vec<u8>, splits: Vec<usize>) -> bool {
+        check(&Input(bytes), &splits)
+    }
+
+    QuickCheck::new()
+        .tests(tests())
+        .quickcheck(prop as fn(Vec<u8>, Vec<usize>) -> bool);
+}
+
+#[test]
+fn valid_documents_round_trip() {
+    for json in [
+        r#"{"a": [1, -1.5e3, "é☕"], "b": {"c": null, "d": true}}"#,
+        "\u{FEFF}[\"\\ud83d\\ude00\"]",
+        " \"\" ",
+    ] {
+        let input = Input(json.as_bytes().to_vec());
+        assert!(parse(&input.0, &[]).is_ok(), "{json}");
+        for step in 0..json.len() {
+            assert!(
+                check(&input, &[step; 64]),
+                "{json} in chunks of {}",
+                step + 1
+            );
+        }
+    }
+}
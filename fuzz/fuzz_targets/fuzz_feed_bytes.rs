@@ -0,0 +1,33 @@
+#![no_main]
+use jsonmodem::{ParserOptions, StreamingParser, StringValueMode};
+use libfuzzer_sys::fuzz_target;
+
+/// Feeds raw bytes, including invalid UTF-8, through `feed_bytes`. The first
+/// byte selects the chunk size, so that characters and escapes are split at
+/// every position.
+fn parser(data: &[u8]) {
+    let Some((&chunk_size, data)) = data.split_first() else {
+        return;
+    };
+    let mut parser = StreamingParser::new(ParserOptions {
+        allow_multiple_json_values: chunk_size & 0x80 != 0,
+        string_value_mode: StringValueMode::Prefixes,
+        panic_on_error: false,
+        ..Default::default()
+    });
+    for chunk in data.chunks(usize::from(chunk_size & 0x7F).max(1)) {
+        let result = match parser.feed_bytes(chunk) {
+            Ok(mut events) => events.try_for_each(|event| event.map(drop)),
+            Err(err) => Err(err),
+        };
+        if let Err(err) = result {
+            assert!(err.line > 0 && err.column > 0);
+            return;
+        }
+    }
+    if let Err(err) = parser.finish().try_for_each(|event| event.map(drop)) {
+        assert!(err.line > 0 && err.column > 0);
+    }
+}
+
+fuzz_target!(|data: &[u8]| parser(data));
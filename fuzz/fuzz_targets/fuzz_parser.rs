@@ -182,7 +182,9 @@ fn parser(data: &[u8]) {
             2 => StringValueMode::Prefixes,
             _ => StringValueMode::None,
         },
+        allow_single_quoted_strings: flags & 32 != 0,
         panic_on_error: false,
+        ..Default::default()
     });
     for chunk in chunks.iter() {
         parser.feed(chunk);